@@ -1,13 +1,47 @@
-use gix::bstr::ByteSlice;
-use gix::config::tree;
-use std::ffi::OsStr;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+};
+
+use anyhow::Context;
+use gix::{
+    bstr::{BStr, ByteSlice},
+    config::tree,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// One line per line in the blamed file, with the abbreviated commit id and the line numbers.
+    Simple,
+    /// Like `git blame --porcelain`.
+    Porcelain,
+    /// Like `git blame --incremental`, with each entry written as soon as it is known.
+    Incremental,
+}
+
+pub struct Options {
+    pub blame: gix::blame::Options,
+    /// Revisions to ignore in addition to those listed in `blame.ignoreRevsFile`.
+    pub ignore_revs: Vec<String>,
+    /// Files with revisions to ignore in addition to `blame.ignoreRevsFile`.
+    pub ignore_revs_files: Vec<PathBuf>,
+    pub format: Format,
+}
 
 pub fn blame_file(
     mut repo: gix::Repository,
     file: &OsStr,
-    options: gix::blame::Options,
-    out: impl std::io::Write,
+    Options {
+        blame: mut options,
+        ignore_revs,
+        ignore_revs_files,
+        format,
+    }: Options,
+    mut out: impl std::io::Write,
     err: Option<&mut dyn std::io::Write>,
+    should_interrupt: &AtomicBool,
 ) -> anyhow::Result<()> {
     {
         let mut config = repo.config_snapshot_mut();
@@ -35,19 +69,82 @@ pub fn blame_file(
         .next()
         .expect("exactly one pattern");
 
+    let configured_ignore_revs_files: Vec<_> = repo
+        .config_snapshot()
+        .trusted_paths(&tree::Blame::IGNORE_REVS_FILE)?
+        .into_iter()
+        .map(std::borrow::Cow::into_owned)
+        .collect();
+    for path in configured_ignore_revs_files.iter().chain(&ignore_revs_files) {
+        let content = std::fs::read(path)
+            .with_context(|| format!("Could not read revisions to ignore from '{}'", path.display()))?;
+        options.ignore_revs.extend(gix::blame::ignore_revs::parse(&content)?);
+    }
+    for rev in &ignore_revs {
+        options
+            .ignore_revs
+            .insert(repo.rev_parse_single(rev.as_str())?.object()?.peel_to_commit()?.id);
+    }
+    let mark_unblamable_lines = repo
+        .config_snapshot()
+        .boolean(&tree::Blame::MARK_UNBLAMABLE_LINES)
+        .unwrap_or_default();
+    let since = options.since;
+
     let suspect: gix::ObjectId = repo.head()?.into_peeled_id()?.into();
     let cache: Option<gix::commitgraph::Graph> = repo.commit_graph_if_enabled()?;
     let mut resource_cache = repo.diff_resource_cache_for_tree_diff()?;
-    let outcome = gix::blame::file(
-        &repo.objects,
-        suspect,
-        cache,
-        &mut resource_cache,
-        file.as_bstr(),
-        options,
-    )?;
-    let statistics = outcome.statistics;
-    write_blame_entries(out, outcome)?;
+    let statistics = match format {
+        Format::Simple | Format::Porcelain => {
+            let outcome = gix::blame::file(
+                &repo.objects,
+                suspect,
+                cache,
+                &mut resource_cache,
+                file.as_bstr(),
+                options,
+            )?;
+            let statistics = outcome.statistics;
+            if format == Format::Simple {
+                write_blame_entries(out, outcome, file.as_bstr(), mark_unblamable_lines)?;
+            } else {
+                write_porcelain(&repo, out, outcome, file.as_bstr(), since)?;
+            }
+            statistics
+        }
+        Format::Incremental => {
+            let mut blame = gix::blame::incremental(
+                &repo.objects,
+                suspect,
+                cache,
+                &mut resource_cache,
+                file.as_bstr(),
+                options,
+                should_interrupt,
+            )?;
+            let mut commits_with_details = BTreeSet::new();
+            for entry in blame.by_ref() {
+                let entry = entry?;
+                writeln!(
+                    out,
+                    "{} {} {} {}",
+                    entry.commit_id,
+                    entry.start_in_source_file + 1,
+                    entry.start_in_blamed_file + 1,
+                    entry.len
+                )?;
+                if commits_with_details.insert(entry.commit_id) {
+                    write_commit_details(&repo, &mut out, entry.commit_id, since)?;
+                }
+                let source_file_path = entry
+                    .source_file_name
+                    .as_ref()
+                    .map_or(file.as_bstr(), |name| name.as_ref());
+                write_filename(&repo, &mut out, entry.commit_id, source_file_path)?;
+            }
+            blame.statistics()
+        }
+    };
 
     if let Some(err) = err {
         writeln!(err, "{statistics:#?}")?;
@@ -55,22 +152,177 @@ pub fn blame_file(
     Ok(())
 }
 
-fn write_blame_entries(mut out: impl std::io::Write, outcome: gix::blame::Outcome) -> Result<(), std::io::Error> {
+fn write_blame_entries(
+    mut out: impl std::io::Write,
+    outcome: gix::blame::Outcome,
+    file_path: &gix::bstr::BStr,
+    mark_unblamable_lines: bool,
+) -> Result<(), std::io::Error> {
+    // Like `git blame`, show the path of the source file only if lines originated in other files.
+    let show_source_file_name = outcome.entries.iter().any(|e| e.source_file_name.is_some());
     for (entry, lines_in_hunk) in outcome.entries_with_lines() {
         for ((actual_lno, source_lno), line) in entry
             .range_in_blamed_file()
             .zip(entry.range_in_source_file())
             .zip(lines_in_hunk)
         {
+            // Like `git blame`, keep the width of the output by shortening the id of commits marked as unblamable.
+            if mark_unblamable_lines && entry.unblamable {
+                write!(out, "*{short_id} ", short_id = entry.commit_id.to_hex_with_len(7))?;
+            } else {
+                write!(out, "{short_id} ", short_id = entry.commit_id.to_hex_with_len(8))?;
+            }
+            if show_source_file_name {
+                write!(
+                    out,
+                    "{} ",
+                    entry.source_file_name.as_ref().map_or(file_path, |name| name.as_ref())
+                )?;
+            }
             write!(
                 out,
-                "{short_id} {line_no} {src_line_no} {line}",
+                "{line_no} {src_line_no} {line}",
                 line_no = actual_lno + 1,
                 src_line_no = source_lno + 1,
-                short_id = entry.commit_id.to_hex_with_len(8),
             )?;
         }
     }
 
     Ok(())
 }
+
+/// Write `outcome` like `git blame --porcelain` does.
+fn write_porcelain(
+    repo: &gix::Repository,
+    mut out: impl std::io::Write,
+    outcome: gix::blame::Outcome,
+    file_path: &BStr,
+    since: Option<gix::date::Time>,
+) -> anyhow::Result<()> {
+    // Like `git`, repeat the file name for each entry of commits whose lines come from more than one path.
+    let mut paths_by_commit = BTreeMap::<_, BTreeSet<_>>::new();
+    for entry in &outcome.entries {
+        paths_by_commit
+            .entry(entry.commit_id)
+            .or_default()
+            .insert(entry.source_file_name.as_ref());
+    }
+    let mut commits_with_details = BTreeSet::new();
+    for (entry, lines_in_hunk) in outcome.entries_with_lines() {
+        let source_file_path = entry.source_file_name.as_ref().map_or(file_path, |name| name.as_ref());
+        for (pos, ((actual_lno, source_lno), line)) in entry
+            .range_in_blamed_file()
+            .zip(entry.range_in_source_file())
+            .zip(lines_in_hunk)
+            .enumerate()
+        {
+            write!(out, "{} {} {}", entry.commit_id, source_lno + 1, actual_lno + 1)?;
+            if pos == 0 {
+                writeln!(out, " {}", entry.len)?;
+                let is_new = commits_with_details.insert(entry.commit_id);
+                if is_new {
+                    write_commit_details(repo, &mut out, entry.commit_id, since)?;
+                }
+                if is_new || paths_by_commit[&entry.commit_id].len() > 1 {
+                    write_filename(repo, &mut out, entry.commit_id, source_file_path)?;
+                }
+            } else {
+                writeln!(out)?;
+            }
+            write!(out, "\t{line}")?;
+            if !line.ends_with(b"\n") {
+                writeln!(out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the author, committer and summary of the commit with `id`, and mark it as boundary if it has no parents
+/// or is older than `since`.
+fn write_commit_details(
+    repo: &gix::Repository,
+    mut out: impl std::io::Write,
+    id: gix::ObjectId,
+    since: Option<gix::date::Time>,
+) -> anyhow::Result<()> {
+    fn write_signature(
+        mut out: impl std::io::Write,
+        role: &str,
+        signature: gix::actor::SignatureRef<'_>,
+    ) -> std::io::Result<()> {
+        let time = signature.time;
+        let offset = time.offset.unsigned_abs();
+        writeln!(out, "{role} {}", signature.name)?;
+        writeln!(out, "{role}-mail <{}>", signature.email)?;
+        writeln!(out, "{role}-time {}", time.seconds)?;
+        writeln!(
+            out,
+            "{role}-tz {sign}{hours:02}{minutes:02}",
+            sign = if time.sign == gix::date::time::Sign::Minus {
+                '-'
+            } else {
+                '+'
+            },
+            hours = offset / 3600,
+            minutes = offset % 3600 / 60
+        )
+    }
+
+    let commit = repo.find_commit(id)?;
+    write_signature(&mut out, "author", commit.author()?)?;
+    let committer = commit.committer()?;
+    let is_boundary =
+        commit.parent_ids().next().is_none() || since.is_some_and(|since| committer.time.seconds < since.seconds);
+    write_signature(&mut out, "committer", committer)?;
+    writeln!(out, "summary {}", commit.message()?.summary())?;
+    if is_boundary {
+        writeln!(out, "boundary")?;
+    }
+    Ok(())
+}
+
+/// Write the path of the source file in the commit with `id`, along with the first parent which has a file at the same
+/// path or the file it was renamed from, if there is one.
+fn write_filename(
+    repo: &gix::Repository,
+    mut out: impl std::io::Write,
+    id: gix::ObjectId,
+    source_file_path: &BStr,
+) -> anyhow::Result<()> {
+    let commit = repo.find_commit(id)?;
+    let mut previous = None;
+    for parent_id in commit.parent_ids() {
+        let mut parent_tree = parent_id.object()?.peel_to_commit()?.tree()?;
+        if parent_tree
+            .peel_to_entry_by_path(gix::path::from_bstr(source_file_path))?
+            .is_some()
+        {
+            previous = Some((parent_id.detach(), source_file_path.to_owned()));
+            break;
+        }
+    }
+    if previous.is_none() {
+        if let Some(parent_id) = commit.parent_ids().next() {
+            let parent_tree = parent_id.object()?.peel_to_commit()?.tree()?;
+            let mut options = gix::diff::Options::default().with_rewrites(Some(Default::default()));
+            options.track_path();
+            previous = repo
+                .diff_tree_to_tree(&parent_tree, &commit.tree()?, options)?
+                .into_iter()
+                .find_map(|change| match change {
+                    gix::object::tree::diff::ChangeDetached::Rewrite {
+                        source_location,
+                        location,
+                        ..
+                    } if location == source_file_path => Some((parent_id.detach(), source_location)),
+                    _ => None,
+                });
+        }
+    }
+    if let Some((parent_id, path)) = previous {
+        writeln!(out, "previous {parent_id} {path}")?;
+    }
+    writeln!(out, "filename {source_file_path}")?;
+    Ok(())
+}
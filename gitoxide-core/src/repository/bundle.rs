@@ -0,0 +1,123 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use anyhow::bail;
+use gix::bstr::{BStr, ByteSlice};
+
+use crate::OutputFormat;
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+pub fn create(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    file: PathBuf,
+    specs: Vec<String>,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let mut bundle = gix::tempfile::new(
+        file.parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(".".as_ref()),
+        gix::tempfile::ContainingDirectory::Exists,
+        gix::tempfile::AutoRemove::Tempfile,
+    )?;
+    let outcome = repo.create_bundle(
+        specs.iter().map(|spec| BStr::new(spec.as_str())),
+        &mut bundle,
+        &mut progress,
+        should_interrupt,
+        Default::default(),
+    )?;
+    bundle.persist(&file)?;
+    writeln!(
+        out,
+        "wrote bundle with {} objects and {} references: {}",
+        outcome.num_objects,
+        outcome.header.refs.len(),
+        file.display()
+    )?;
+    Ok(())
+}
+
+pub fn verify(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    file: PathBuf,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let bundle = repo.verify_bundle(&file, &mut progress, should_interrupt)?;
+    let header = &bundle.header;
+    writeln!(
+        out,
+        "The bundle contains {} {}:",
+        header.refs.len(),
+        if header.refs.len() == 1 { "ref" } else { "refs" }
+    )?;
+    write_refs(&mut out, &header.refs)?;
+    if header.prerequisites.is_empty() {
+        writeln!(out, "The bundle records a complete history.")?;
+    } else {
+        writeln!(
+            out,
+            "The bundle requires {} {}:",
+            header.prerequisites.len(),
+            if header.prerequisites.len() == 1 { "ref" } else { "refs" }
+        )?;
+        for prerequisite in &header.prerequisites {
+            writeln!(out, "{} {}", prerequisite.id, prerequisite.comment)?;
+        }
+    }
+    writeln!(
+        out,
+        "The bundle uses this hash algorithm: {}",
+        header.object_hash.to_string().to_ascii_lowercase()
+    )?;
+    if let Some(filter) = &header.filter {
+        writeln!(out, "The bundle uses this filter: {filter}")?;
+    }
+    writeln!(out, "{} is okay", file.display())?;
+    Ok(())
+}
+
+pub fn list_heads(file: PathBuf, names: Vec<String>, mut out: impl std::io::Write) -> anyhow::Result<()> {
+    let bundle = gix::bundle::File::at(file)?;
+    let refs: Vec<_> = bundle
+        .header
+        .refs
+        .into_iter()
+        .filter(|r| names.is_empty() || names.iter().any(|name| r.name == name.as_bytes().as_bstr()))
+        .collect();
+    write_refs(&mut out, &refs)
+}
+
+pub fn unbundle(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    file: PathBuf,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let outcome = repo.unbundle(file, &mut progress, should_interrupt)?;
+    write_refs(&mut out, &outcome.header.refs)
+}
+
+fn write_refs(out: &mut dyn std::io::Write, refs: &[gix::bundle::Ref]) -> anyhow::Result<()> {
+    for r in refs {
+        writeln!(out, "{} {}", r.id, r.name)?;
+    }
+    Ok(())
+}
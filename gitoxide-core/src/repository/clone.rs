@@ -6,6 +6,8 @@ pub struct Options {
     pub handshake_info: bool,
     pub no_tags: bool,
     pub shallow: gix::remote::fetch::Shallow,
+    /// If set, create a partial clone by omitting objects matching this filter.
+    pub filter: Option<gix::remote::fetch::Filter>,
     pub ref_name: Option<gix::refs::PartialName>,
 }
 
@@ -34,6 +36,7 @@ pub(crate) mod function {
             no_tags,
             ref_name,
             shallow,
+            filter,
         }: Options,
     ) -> anyhow::Result<()>
     where
@@ -77,6 +80,7 @@ pub(crate) mod function {
         }
         let (mut checkout, fetch_outcome) = prepare
             .with_shallow(shallow)
+            .with_filter(filter)
             .with_ref_name(ref_name.as_ref())?
             .fetch_then_checkout(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
 
@@ -92,28 +96,31 @@ pub(crate) mod function {
             writeln!(out, "\t{:?}", fetch_outcome.handshake)?;
         }
 
-        match fetch_outcome.status {
+        let updates = match fetch_outcome.status {
             Status::NoPackReceived { dry_run, .. } => {
                 assert!(!dry_run, "dry-run unsupported");
                 writeln!(err, "The cloned repository appears to be empty")?;
+                None
             }
             Status::Change {
                 update_refs, negotiate, ..
-            } => {
-                let remote = repo
-                    .find_default_remote(gix::remote::Direction::Fetch)
-                    .expect("one origin remote")?;
-                let ref_specs = remote.refspecs(gix::remote::Direction::Fetch);
-                print_updates(
-                    &repo,
-                    &negotiate,
-                    update_refs,
-                    ref_specs,
-                    fetch_outcome.ref_map,
-                    &mut out,
-                    &mut err,
-                )?;
-            }
+            } => Some((update_refs, negotiate)),
+            Status::ObjectsDownloaded { update_refs, .. } => Some((update_refs, Default::default())),
+        };
+        if let Some((update_refs, negotiate)) = updates {
+            let remote = repo
+                .find_default_remote(gix::remote::Direction::Fetch)
+                .expect("one origin remote")?;
+            let ref_specs = remote.refspecs(gix::remote::Direction::Fetch);
+            print_updates(
+                &repo,
+                &negotiate,
+                update_refs,
+                ref_specs,
+                fetch_outcome.ref_map,
+                &mut out,
+                &mut err,
+            )?;
         }
 
         if let Some(gix::worktree::state::checkout::Outcome { collisions, errors, .. }) = outcome {
@@ -1,17 +1,51 @@
+use std::borrow::Cow;
+
 use anyhow::Context;
 use gix::bstr::{BString, ByteSlice};
 use gix::diff::blob::intern::TokenSource;
+use gix::diff::blob::platform::prepare_diff::Operation;
 use gix::diff::blob::unified_diff::{ContextSize, NewlineSeparator};
 use gix::diff::blob::UnifiedDiff;
+use gix::diff::blob::{moved, word_diff};
 use gix::objs::tree::EntryMode;
 use gix::odb::store::RefreshMode;
 use gix::prelude::ObjectIdExt;
 
+/// The way changes between trees are displayed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// Show each change along with the ids and modes involved.
+    #[default]
+    Changes,
+    /// Show the changed lines of each file along with a graph, like `git diff --stat`.
+    Stat,
+    /// Show the changed lines of each file in a machine-readable format, like `git diff --numstat`.
+    Numstat,
+    /// Show only the amount of changed files and lines, like `git diff --shortstat`.
+    Shortstat,
+    /// Show the share of changed lines of each directory, like `git diff --dirstat=lines`.
+    Dirstat,
+}
+
+/// The way changes to a file are displayed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum FileFormat {
+    /// Show changed lines in the unified diff format.
+    #[default]
+    Unified,
+    /// Show changed words in the given style, like `git diff --word-diff=<style>`.
+    WordDiff(word_diff::Style),
+    /// Show changed lines in the unified diff format with colors, and highlight blocks of moved lines like
+    /// `git diff --color-moved=<mode>`.
+    ColorMoved(moved::Mode),
+}
+
 pub fn tree(
     mut repo: gix::Repository,
     out: &mut dyn std::io::Write,
     old_treeish: BString,
     new_treeish: BString,
+    format: Format,
 ) -> anyhow::Result<()> {
     repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
     repo.objects.refresh = RefreshMode::Never;
@@ -24,6 +58,18 @@ pub fn tree(
 
     let changes = repo.diff_tree_to_tree(&old_tree, &new_tree, None)?;
 
+    if format != Format::Changes {
+        let stats = stats(&repo, changes)?;
+        match format {
+            Format::Changes => unreachable!("handled above"),
+            Format::Stat => stats.write_stat(out, &Default::default())?,
+            Format::Numstat => stats.write_numstat(out)?,
+            Format::Shortstat => stats.write_shortstat(out)?,
+            Format::Dirstat => stats.write_dirstat(out, &Default::default())?,
+        }
+        return Ok(());
+    }
+
     writeln!(
         out,
         "Diffing trees `{old_treeish}` ({old_tree_id}) -> `{new_treeish}` ({new_tree_id})\n"
@@ -33,6 +79,20 @@ pub fn tree(
     Ok(())
 }
 
+/// Compute the statistics of all `changes`, ordered by their location like `git` does.
+pub(crate) fn stats(
+    repo: &gix::Repository,
+    mut changes: Vec<gix::diff::tree_with_rewrites::Change>,
+) -> anyhow::Result<gix::diff::blob::Stats> {
+    changes.sort_by(|a, b| a.location().cmp(b.location()));
+    let mut resource_cache = repo.diff_resource_cache_for_tree_diff()?;
+    Ok(gix::diff::blob::Stats::from_changes(
+        changes.iter().map(gix::diff::tree_with_rewrites::Change::to_ref),
+        &mut resource_cache,
+        &repo.objects,
+    )?)
+}
+
 fn write_changes(
     repo: &gix::Repository,
     mut out: impl std::io::Write,
@@ -121,6 +181,8 @@ pub fn file(
     out: &mut dyn std::io::Write,
     old_revspec: BString,
     new_revspec: BString,
+    format: FileFormat,
+    whitespace: gix::diff::blob::whitespace::Options,
 ) -> Result<(), anyhow::Error> {
     repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
     repo.objects.refresh = RefreshMode::Never;
@@ -162,9 +224,61 @@ pub fn file(
         &repo.objects,
     )?;
 
-    let outcome = resource_cache.prepare_diff()?;
+    let funcname = resource_cache.funcname()?;
+    let (indent_heuristic, inter_hunk_context) = {
+        let config = repo.config_snapshot();
+        (
+            config
+                .boolean(&gix::config::tree::Diff::INDENT_HEURISTIC)
+                .unwrap_or(true),
+            config
+                .integer(&gix::config::tree::Diff::INTER_HUNK_CONTEXT)
+                .map(u32::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+        )
+    };
+    let style = match format {
+        FileFormat::Unified => None,
+        FileFormat::WordDiff(style) => Some(style),
+        FileFormat::ColorMoved(mode) => {
+            let outcome = resource_cache.prepare_diff()?;
+            let Operation::InternalDiff { algorithm } = outcome.operation else {
+                anyhow::bail!("Source or destination is binary and we can't diff that")
+            };
+            let hunks = gix::diff::patch::hunks_with_options(
+                outcome.old.data.as_slice().unwrap_or_default(),
+                outcome.new.data.as_slice().unwrap_or_default(),
+                algorithm,
+                &gix::diff::patch::hunks::Options {
+                    indent_heuristic,
+                    inter_hunk_context,
+                    funcname: Some(&funcname),
+                    whitespace,
+                    ..Default::default()
+                },
+            );
+            return write_color_moved(out, &hunks, mode);
+        }
+    };
+    if let Some(style) = style {
+        let default_regex = repo
+            .config_snapshot()
+            .string(&gix::config::tree::Diff::WORD_REGEX)
+            .map(Cow::into_owned);
+        let hunks = resource_cache
+            .word_diff(&word_diff::Options {
+                default_regex,
+                ..Default::default()
+            })?
+            .context("Source or destination is binary and we can't diff that")?;
+        for hunk in hunks {
+            hunk.write_to(out, style)?;
+        }
+        return Ok(());
+    }
 
-    use gix::diff::blob::platform::prepare_diff::Operation;
+    let outcome = resource_cache.prepare_diff()?;
 
     let algorithm = match outcome.operation {
         Operation::InternalDiff { algorithm } => algorithm,
@@ -186,15 +300,110 @@ pub fn file(
         String::new(),
         NewlineSeparator::AfterHeaderAndLine("\n"),
         ContextSize::symmetrical(3),
-    );
+    )
+    .with_section_headers(&funcname)
+    .with_inter_hunk_context(inter_hunk_context);
 
-    let unified_diff = gix::diff::blob::diff(algorithm, &interner, unified_diff)?;
+    let unified_diff = gix::diff::blob::whitespace::diff(
+        algorithm,
+        &interner,
+        &whitespace,
+        indent_heuristic,
+        3,
+        inter_hunk_context,
+        unified_diff,
+    )?;
 
     out.write_all(unified_diff.as_bytes())?;
 
     Ok(())
 }
 
+/// Write `hunks` with colors like `git diff --color-moved=<mode>` does.
+fn write_color_moved(
+    out: &mut dyn std::io::Write,
+    hunks: &[gix::diff::patch::Hunk],
+    mode: moved::Mode,
+) -> anyhow::Result<()> {
+    use gix::diff::patch::Line;
+
+    let lines: Vec<_> = hunks
+        .iter()
+        .flat_map(|hunk| {
+            std::iter::once(moved::Line::Other).chain(hunk.lines.iter().map(|line| match line {
+                Line::Context(_) => moved::Line::Other,
+                Line::Removed(content) => moved::Line::Removed(content.as_ref()),
+                Line::Added(content) => moved::Line::Added(content.as_ref()),
+            }))
+        })
+        .collect();
+    let mut moved = moved::detect(&lines, mode).into_iter();
+    for hunk in hunks {
+        moved.next();
+        let range = |start: u32, len: u32| {
+            if len == 1 {
+                start.to_string()
+            } else {
+                format!("{start},{len}")
+            }
+        };
+        write!(
+            out,
+            "\x1b[36m@@ -{} +{} @@\x1b[m",
+            range(hunk.old_start, hunk.old_len),
+            range(hunk.new_start, hunk.new_len)
+        )?;
+        if !hunk.section_header.is_empty() {
+            write!(out, " {}", hunk.section_header)?;
+        }
+        writeln!(out)?;
+        for line in &hunk.lines {
+            let moved = moved.next().flatten();
+            let (sign, content) = match line {
+                Line::Context(content) => (" ", content),
+                Line::Removed(content) => ("-", content),
+                Line::Added(content) => ("+", content),
+            };
+            let color = match (line, moved) {
+                (Line::Context(_), _) => "",
+                (
+                    _,
+                    Some(moved::Moved {
+                        dimmed: true,
+                        alternate,
+                    }),
+                ) => {
+                    if alternate {
+                        "\x1b[2;3m"
+                    } else {
+                        "\x1b[2m"
+                    }
+                }
+                (Line::Removed(_), Some(moved)) => {
+                    if moved.alternate {
+                        "\x1b[1;34m"
+                    } else {
+                        "\x1b[1;35m"
+                    }
+                }
+                (Line::Added(_), Some(moved)) => {
+                    if moved.alternate {
+                        "\x1b[1;33m"
+                    } else {
+                        "\x1b[1;36m"
+                    }
+                }
+                (Line::Removed(_), None) => "\x1b[31m",
+                (Line::Added(_), None) => "\x1b[32m",
+            };
+            write!(out, "{color}{sign}")?;
+            out.write_all(content.strip_suffix(b"\n").unwrap_or(content))?;
+            writeln!(out, "\x1b[m")?;
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn tokens_for_diffing(data: &[u8]) -> impl TokenSource<Token = &[u8]> {
     gix::diff::blob::sources::byte_lines(data)
 }
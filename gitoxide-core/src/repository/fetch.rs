@@ -9,6 +9,8 @@ pub struct Options {
     /// If non-empty, override all ref-specs otherwise configured in the remote
     pub ref_specs: Vec<BString>,
     pub shallow: gix::remote::fetch::Shallow,
+    /// If set, omit objects matching this filter instead of using the filter configured for a promisor remote.
+    pub filter: Option<gix::remote::fetch::Filter>,
     pub handshake_info: bool,
     pub negotiation_info: bool,
     pub open_negotiation_graph: Option<std::path::PathBuf>,
@@ -45,6 +47,7 @@ pub(crate) mod function {
             negotiation_info,
             open_negotiation_graph,
             shallow,
+            filter,
             ref_specs,
         }: Options,
     ) -> anyhow::Result<()>
@@ -61,12 +64,15 @@ pub(crate) mod function {
             remote.replace_refspecs(ref_specs.iter(), gix::remote::Direction::Fetch)?;
             remote = remote.with_fetch_tags(gix::remote::fetch::Tags::None);
         }
-        let res: gix::remote::fetch::Outcome = remote
+        let mut prepare = remote
             .connect(gix::remote::Direction::Fetch)?
             .prepare_fetch(&mut progress, Default::default())?
             .with_dry_run(dry_run)
-            .with_shallow(shallow)
-            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+            .with_shallow(shallow);
+        if filter.is_some() {
+            prepare = prepare.with_filter(filter);
+        }
+        let res: gix::remote::fetch::Outcome = prepare.receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
 
         if handshake_info {
             writeln!(out, "Handshake Information")?;
@@ -120,6 +126,28 @@ pub(crate) mod function {
                 }
                 Ok(())
             }
+            Status::ObjectsDownloaded {
+                update_refs,
+                write_pack_bundles,
+                loose_objects,
+            } => {
+                print_updates(
+                    &repo,
+                    &Default::default(),
+                    update_refs,
+                    ref_specs,
+                    res.ref_map,
+                    &mut out,
+                    err,
+                )?;
+                for write_pack_bundle in write_pack_bundles {
+                    if let Some(data_path) = write_pack_bundle.data_path {
+                        writeln!(out, "pack  file: \"{}\"", data_path.display()).ok();
+                    }
+                }
+                writeln!(out, "loose objects: {loose_objects}").ok();
+                Ok(())
+            }
         }?;
         if dry_run {
             writeln!(out, "DRY-RUN: No ref was updated and no pack was received.").ok();
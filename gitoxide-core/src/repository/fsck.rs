@@ -1,13 +1,39 @@
-use anyhow::Context;
-use gix::{objs::Kind, ObjectId};
+use std::sync::atomic::AtomicBool;
 
-pub fn function(mut repo: gix::Repository, spec: Option<String>, mut out: impl std::io::Write) -> anyhow::Result<()> {
-    let spec = spec.unwrap_or("HEAD".into());
+use anyhow::{bail, Context};
+use gix::{config::tree, objs::Kind, ObjectId};
 
+pub struct Options {
+    /// Like `git fsck --strict`, turn warnings into errors.
+    pub strict: bool,
+    /// Print all unreachable objects, not only dangling ones.
+    pub unreachable: bool,
+    /// Print dangling objects.
+    pub dangling: bool,
+}
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+/// Check the connectivity of all commits reachable from `spec`, or perform a full check of all objects
+/// if `spec` is `None`.
+pub fn function(
+    mut repo: gix::Repository,
+    spec: Option<String>,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    err: impl std::io::Write,
+    options: Options,
+) -> anyhow::Result<()> {
     repo.object_cache_size_if_unset(4 * 1024 * 1024);
     // We expect to be finding a bunch of non-existent objects here - never refresh the ODB
     repo.objects.refresh_never();
+    // Like `git`, never fetch missing objects in partial clones as we want to learn about them instead.
+    repo.objects.set_promisor(None);
 
+    let Some(spec) = spec else {
+        return full(repo, &mut progress, should_interrupt, out, err, options);
+    };
     let id = repo
         .rev_parse_single(spec.as_str())
         .context("Only single revisions are supported")?;
@@ -34,3 +60,150 @@ pub fn function(mut repo: gix::Repository, spec: Option<String>, mut out: impl s
     }
     Ok(())
 }
+
+fn full(
+    repo: gix::Repository,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    mut err: impl std::io::Write,
+    Options {
+        strict,
+        unreachable,
+        dangling,
+    }: Options,
+) -> anyhow::Result<()> {
+    let mut options = gix_fsck::full::Options::default();
+    options.object.strict = strict;
+    let config = repo.config_snapshot();
+    for section in config.plumbing().sections_by_name("fsck").into_iter().flatten() {
+        if section.header().subsection_name().is_some() {
+            continue;
+        }
+        for name in section.value_names() {
+            let Some(id) = gix_fsck::message::Id::from_name(name.as_ref()) else {
+                continue;
+            };
+            let value = section.value(name.as_ref()).unwrap_or_default();
+            let severity = gix_fsck::message::Severity::from_config_value(value.as_ref())
+                .with_context(|| format!("Invalid severity '{value}' of 'fsck.{name}'"))?;
+            options.object.severities.insert(id, severity);
+        }
+    }
+    if let Some(path) = config.trusted_path(&tree::Fsck::SKIP_LIST).transpose()? {
+        let content = std::fs::read(&path)
+            .with_context(|| format!("Could not read objects to skip from '{}'", path.display()))?;
+        options.skip_list = gix_fsck::skip_list::parse(&content)?;
+    }
+    if let Some(shallow_commits) = repo.shallow_commits()? {
+        options.shallow_commits.extend(shallow_commits.iter().copied());
+    }
+    options.promisor_objects = promisor_objects(&repo)?;
+
+    let outcome = gix_fsck::full(
+        &repo.objects,
+        repo.objects.iter()?.filter_map(Result::ok),
+        tips(&repo)?,
+        progress,
+        should_interrupt,
+        &options,
+    )?;
+
+    for problem in &outcome.problems {
+        use gix_fsck::full::Problem;
+        match problem {
+            Problem::Unreadable { id, source } => writeln!(err, "error: {id}: object could not be read: {source}")?,
+            Problem::HashMismatch { id, kind, actual } => {
+                writeln!(err, "error: {kind} {id}: hash mismatch, the object hashes to {actual}")?;
+            }
+            Problem::Invalid { id, kind, message } => writeln!(
+                err,
+                "{} in {kind} {id}: {}: {}",
+                if problem.is_error() { "error" } else { "warning" },
+                message.id,
+                message.description
+            )?,
+        }
+    }
+    for object in &outcome.unreachable {
+        if unreachable {
+            writeln!(out, "unreachable {} {}", object.kind, object.id)?;
+        } else if dangling && object.is_dangling {
+            writeln!(out, "dangling {} {}", object.kind, object.id)?;
+        }
+    }
+    for object in &outcome.missing {
+        match object.kind {
+            Some(kind) => writeln!(out, "missing {kind} {}", object.id)?,
+            None => writeln!(out, "missing {}", object.id)?,
+        }
+    }
+    if outcome.has_errors() {
+        bail!(
+            "Found missing or broken objects while checking {} objects",
+            outcome.num_objects
+        );
+    }
+    Ok(())
+}
+
+/// Return the ids of all objects in packs that were received from a promisor remote.
+fn promisor_objects(repo: &gix::Repository) -> anyhow::Result<gix::hashtable::HashSet> {
+    let mut out = gix::hashtable::HashSet::default();
+    let pack_dir = repo.objects.store_ref().path().join("pack");
+    let entries = match std::fs::read_dir(&pack_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+        Err(err) => return Err(err).with_context(|| format!("Could not list packs in '{}'", pack_dir.display())),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "idx") && gix::odb::promisor::is_promisor_pack(&path) {
+            let index = gix::odb::pack::index::File::at(&path, repo.object_hash())?;
+            out.extend(index.iter().map(|entry| entry.oid));
+        }
+    }
+    Ok(out)
+}
+
+/// Return all objects that are directly referenced by `HEAD`, references, their reflogs and the index.
+fn tips(repo: &gix::Repository) -> anyhow::Result<Vec<ObjectId>> {
+    fn log_ids(out: &mut Vec<ObjectId>, mut log: gix::refs::file::log::iter::Platform<'_, '_>) -> anyhow::Result<()> {
+        for line in log.all()?.into_iter().flatten() {
+            let line = line?;
+            out.extend(
+                [line.previous_oid(), line.new_oid()]
+                    .into_iter()
+                    .filter(|id| !id.is_null()),
+            );
+        }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    let head = repo.head()?;
+    out.extend(head.id().map(gix::Id::detach));
+    log_ids(&mut out, head.log_iter())?;
+    for reference in repo.references()?.all()? {
+        let reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+        out.extend(reference.target().try_id().map(ToOwned::to_owned));
+        log_ids(&mut out, reference.log_iter())?;
+    }
+
+    let index = repo.index_or_empty()?;
+    out.extend(
+        index
+            .entries()
+            .iter()
+            .filter(|entry| entry.mode != gix::index::entry::Mode::COMMIT)
+            .map(|entry| entry.id),
+    );
+    let mut trees: Vec<_> = index.tree().into_iter().collect();
+    while let Some(tree) = trees.pop() {
+        if tree.num_entries.is_some() {
+            out.push(tree.id);
+        }
+        trees.extend(&tree.children);
+    }
+    Ok(out)
+}
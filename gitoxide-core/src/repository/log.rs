@@ -1,17 +1,22 @@
 use anyhow::bail;
 use gix::bstr::{BString, ByteSlice};
 
-pub fn log(mut repo: gix::Repository, out: &mut dyn std::io::Write, path: Option<BString>) -> anyhow::Result<()> {
+pub fn log(
+    mut repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    path: Option<BString>,
+    stat: bool,
+) -> anyhow::Result<()> {
     repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
 
     if let Some(path) = path {
         log_file(repo, out, path)
     } else {
-        log_all(repo, out)
+        log_all(repo, out, stat)
     }
 }
 
-fn log_all(repo: gix::Repository, out: &mut dyn std::io::Write) -> Result<(), anyhow::Error> {
+fn log_all(repo: gix::Repository, out: &mut dyn std::io::Write, stat: bool) -> Result<(), anyhow::Error> {
     let head = repo.head()?.peel_to_commit_in_place()?;
     let topo = gix::traverse::commit::topo::Builder::from_iters(&repo.objects, [head.id], None::<Vec<gix::ObjectId>>)
         .build()?;
@@ -20,6 +25,9 @@ fn log_all(repo: gix::Repository, out: &mut dyn std::io::Write) -> Result<(), an
         let info = info?;
 
         write_info(&repo, &mut *out, &info)?;
+        if stat {
+            write_stat(&repo, &mut *out, &info)?;
+        }
     }
 
     Ok(())
@@ -48,3 +56,20 @@ fn write_info(
 
     Ok(())
 }
+
+/// Write the statistics of the changes of the commit in `info` compared to its first parent.
+fn write_stat(
+    repo: &gix::Repository,
+    out: &mut dyn std::io::Write,
+    info: &gix::traverse::commit::Info,
+) -> anyhow::Result<()> {
+    let new_tree = repo.find_commit(info.id)?.tree()?;
+    let old_tree = info
+        .parent_ids
+        .first()
+        .map(|id| -> anyhow::Result<_> { Ok(repo.find_commit(*id)?.tree()?) })
+        .transpose()?;
+    let changes = repo.diff_tree_to_tree(old_tree.as_ref(), &new_tree, None)?;
+    crate::repository::diff::stats(repo, changes)?.write_stat(out, &Default::default())?;
+    Ok(())
+}
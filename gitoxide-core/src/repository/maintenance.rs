@@ -0,0 +1,209 @@
+use std::{sync::atomic::AtomicBool, time::SystemTime};
+
+use anyhow::bail;
+use gix::{config::tree::Gc, repository::maintenance};
+
+use crate::OutputFormat;
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+/// Parse an expiry `date` like `2.weeks.ago`, `now` or `never` the way `gc.pruneExpire` is parsed.
+pub fn parse_expiry(date: &str) -> anyhow::Result<Option<SystemTime>> {
+    Ok(Gc::PRUNE_EXPIRE.try_into_expiry(gix::bstr::BStr::new(date).into(), SystemTime::now())?)
+}
+
+pub mod gc {
+    pub struct Options {
+        pub format: crate::OutputFormat,
+        /// Only collect garbage if there are too many loose objects or packs.
+        pub auto: bool,
+        /// If set, the expiry date of unreachable objects as passed on the command-line, overriding `gc.pruneExpire`.
+        pub prune: Option<String>,
+    }
+}
+
+pub fn gc(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    gc::Options { format, auto, prune }: gc::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let mut options = repo.gc_options(SystemTime::now())?;
+    options.auto = auto;
+    if let Some(prune) = prune {
+        options.prune_expire_before = parse_expiry(&prune)?;
+    }
+
+    let Some(outcome) = repo.gc(options, &mut progress, should_interrupt)? else {
+        writeln!(out, "Nothing to do")?;
+        return Ok(());
+    };
+    if let Some(pack_refs) = outcome.pack_refs {
+        writeln!(out, "packed references: {}", pack_refs.packed_refs)?;
+    }
+    writeln!(
+        out,
+        "expired reflog entries: {} in {} reflogs",
+        outcome.reflogs.removed_entries, outcome.reflogs.rewritten_reflogs
+    )?;
+    write_repack_outcome(&mut out, &outcome.repack)?;
+    if let Some(prune) = outcome.prune {
+        write_prune_outcome(&mut out, &prune, false)?;
+    }
+    Ok(())
+}
+
+pub mod repack {
+    pub struct Options {
+        pub format: crate::OutputFormat,
+        /// Only combine small packs so the remaining ones form a geometric progression with this factor.
+        pub geometric: Option<u32>,
+        /// Write unreachable objects into a cruft pack.
+        pub cruft: bool,
+        /// Write unreachable objects which are newer than this date into a cruft pack.
+        pub cruft_expiration: Option<String>,
+    }
+}
+
+pub fn repack(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    repack::Options {
+        format,
+        geometric,
+        cruft,
+        cruft_expiration,
+    }: repack::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let unreachable = match (cruft || cruft_expiration.is_some(), cruft_expiration) {
+        (false, _) => maintenance::repack::Unreachable::Drop,
+        (true, None) => maintenance::repack::Unreachable::Cruft { expire_before: None },
+        (true, Some(date)) => maintenance::repack::Unreachable::Cruft {
+            expire_before: parse_expiry(&date)?,
+        },
+    };
+    let outcome = repo.repack(
+        maintenance::repack::Options {
+            geometric_factor: geometric,
+            unreachable,
+            thread_limit: None,
+        },
+        &mut progress,
+        should_interrupt,
+    )?;
+    write_repack_outcome(&mut out, &outcome)
+}
+
+pub mod prune {
+    pub struct Options {
+        pub format: crate::OutputFormat,
+        /// Only prune unreachable loose objects older than this date, or all of them if unset.
+        pub expire: Option<String>,
+        /// Only print the objects that would be pruned.
+        pub dry_run: bool,
+    }
+}
+
+pub fn prune(
+    repo: gix::Repository,
+    mut progress: impl gix::NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    prune::Options {
+        format,
+        expire,
+        dry_run,
+    }: prune::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let expire_before = match expire {
+        Some(date) => match parse_expiry(&date)? {
+            Some(date) => Some(date),
+            None => {
+                writeln!(out, "Nothing to do as objects never expire")?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let outcome = repo.prune(
+        maintenance::prune::Options {
+            expire_before,
+            dry_run,
+            thread_limit: None,
+        },
+        &mut progress,
+        should_interrupt,
+    )?;
+    if dry_run {
+        for id in &outcome.pruned_objects {
+            writeln!(out, "{id}")?;
+        }
+    }
+    write_prune_outcome(&mut out, &outcome, dry_run)
+}
+
+fn write_repack_outcome(out: &mut dyn std::io::Write, outcome: &maintenance::repack::Outcome) -> anyhow::Result<()> {
+    if let Some(pack) = &outcome.pack {
+        writeln!(
+            out,
+            "wrote pack with {} objects: {}",
+            outcome.packed_objects,
+            pack.display()
+        )?;
+    }
+    if let Some(pack) = &outcome.cruft_pack {
+        writeln!(
+            out,
+            "wrote cruft pack with {} objects: {}",
+            outcome.cruft_objects,
+            pack.display()
+        )?;
+    }
+    if outcome.loosened_objects != 0 {
+        writeln!(out, "loosened unreachable objects: {}", outcome.loosened_objects)?;
+    }
+    if outcome.dropped_objects != 0 {
+        writeln!(out, "dropped unreachable objects: {}", outcome.dropped_objects)?;
+    }
+    writeln!(out, "removed packs: {}", outcome.removed_packs.len())?;
+    writeln!(out, "removed loose objects: {}", outcome.removed_loose_objects)?;
+    Ok(())
+}
+
+fn write_prune_outcome(
+    out: &mut dyn std::io::Write,
+    outcome: &maintenance::prune::Outcome,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let prefix = if dry_run { "would prune" } else { "pruned" };
+    writeln!(
+        out,
+        "{prefix} unreachable loose objects: {}",
+        outcome.pruned_objects.len()
+    )?;
+    writeln!(
+        out,
+        "{prefix} loose objects that are also packed: {}",
+        outcome.removed_packed_objects
+    )?;
+    if !outcome.pruned_shallow_commits.is_empty() {
+        writeln!(
+            out,
+            "{prefix} missing shallow commits: {}",
+            outcome.pruned_shallow_commits.len()
+        )?;
+    }
+    Ok(())
+}
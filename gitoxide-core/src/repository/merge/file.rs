@@ -10,11 +10,13 @@ use gix::object::tree::EntryKind;
 use gix::Id;
 use std::path::Path;
 
+#[allow(clippy::too_many_arguments)]
 pub fn file(
     repo: gix::Repository,
     out: &mut dyn std::io::Write,
     format: OutputFormat,
     conflict: Option<gix::merge::blob::builtin_driver::text::Conflict>,
+    whitespace: gix::diff::blob::whitespace::Options,
     base: BString,
     ours: BString,
     theirs: BString,
@@ -67,6 +69,7 @@ pub fn file(
     )?;
 
     let mut options = repo.blob_merge_options()?;
+    options.text.whitespace = whitespace;
     if let Some(conflict) = conflict {
         options.text.conflict = conflict;
         options.resolve_binary_with = match conflict {
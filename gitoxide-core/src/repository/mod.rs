@@ -22,6 +22,7 @@ pub mod archive;
 pub mod cat;
 pub use cat::function::cat;
 pub mod blame;
+pub mod bundle;
 pub mod commit;
 pub mod config;
 mod credential;
@@ -44,11 +45,12 @@ pub use clone::function::clone;
 pub use fetch::function::fetch;
 
 pub mod commitgraph;
-mod fsck;
+pub mod fsck;
 pub use fsck::function as fsck;
 pub mod index;
 pub mod log;
 pub mod mailmap;
+pub mod maintenance;
 mod merge_base;
 pub use merge_base::merge_base;
 pub mod merge;
@@ -27,10 +27,16 @@ pub enum Error {
     Traverse(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error(transparent)]
     DiffTree(#[from] gix_diff::tree::Error),
+    #[error(transparent)]
+    DiffTreeWithRewrites(#[from] gix_diff::tree_with_rewrites::Error),
+    #[error("Failed to traverse the tree of a parent commit to find moved or copied lines")]
+    TraverseTree(#[from] gix_traverse::tree::breadthfirst::Error),
     #[error("Invalid line range was given, line range is expected to be a 1-based inclusive range in the format '<start>,<end>'")]
     InvalidLineRange,
     #[error("Failure to decode commit during traversal")]
     DecodeCommit(#[from] gix_object::decode::Error),
+    #[error("Interrupted by user")]
+    Interrupted,
     #[error("Failed to get parent from commitgraph during traversal")]
     GetParentFromCommitGraph(#[from] gix_commitgraph::file::commit::Error),
 }
@@ -1,16 +1,18 @@
-use super::{process_changes, Change, UnblamedHunk};
-use crate::{BlameEntry, Error, Options, Outcome, Statistics};
+use super::{process_changes_of_overlapping_hunks, Change, UnblamedHunk};
+use crate::{BlameEntry, Error, MovedLines, MovedLinesSource, Options, Outcome, Statistics};
 use gix_diff::blob::intern::TokenSource;
 use gix_diff::tree::Visit;
 use gix_hash::ObjectId;
 use gix_object::{
-    bstr::{BStr, BString},
+    bstr::{BStr, BString, ByteSlice},
     FindExt,
 };
 use gix_traverse::commit::find as find_commit;
 use smallvec::SmallVec;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::num::NonZeroU32;
 use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Produce a list of consecutive [`BlameEntry`] instances to indicate in which commits the ranges of the file
 /// at `suspect:<file_path>` originated in.
@@ -71,8 +73,53 @@ pub fn file(
 ) -> Result<Outcome, Error> {
     let _span = gix_trace::coarse!("gix_blame::file()", ?file_path, ?suspect);
 
+    let should_interrupt = AtomicBool::default();
+    let mut blame = incremental(
+        odb,
+        suspect,
+        cache,
+        resource_cache,
+        file_path,
+        options,
+        &should_interrupt,
+    )?;
+    let mut out = blame.by_ref().collect::<Result<Vec<_>, _>>()?;
+    debug_assert_eq!(
+        blame.hunks_to_blame,
+        vec![],
+        "only if there is no portion of the file left we have completed the blame"
+    );
+
+    // I don’t know yet whether it would make sense to use a data structure instead that preserves
+    // order on insertion.
+    out.sort_by_key(|e| e.start_in_blamed_file);
+    Ok(Outcome {
+        entries: coalesce_blame_entries(out),
+        blob: blame.blamed_file_blob,
+        statistics: blame.stats,
+    })
+}
+
+/// Like [`file()`], but return an iterator that yields each [`BlameEntry`] as soon as it is known, similar to
+/// `git blame --incremental`.
+///
+/// Entries are yielded in the order in which they are found, and adjacent entries of the same commit aren't coalesced.
+/// If `should_interrupt` is set, the iteration yields [`Error::Interrupted`] before looking at the next commit, and
+/// can be resumed by calling [`next()`](Iterator::next()) again after resetting it.
+pub fn incremental<'a, T>(
+    odb: T,
+    suspect: ObjectId,
+    cache: Option<gix_commitgraph::Graph>,
+    resource_cache: &'a mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    options: Options,
+    should_interrupt: &'a AtomicBool,
+) -> Result<Incremental<'a, T>, Error>
+where
+    T: gix_object::Find + gix_object::FindHeader,
+{
     let mut stats = Statistics::default();
-    let (mut buf, mut buf2, mut buf3) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut buf, mut buf2) = (Vec::new(), Vec::new());
     let blamed_file_entry_id = find_path_entry_in_commit(
         &odb,
         &suspect,
@@ -89,52 +136,162 @@ pub fn file(
     let blamed_file_blob = odb.find_blob(&blamed_file_entry_id, &mut buf)?.data.to_vec();
     let num_lines_in_blamed = tokens_for_diffing(&blamed_file_blob).tokenize().count() as u32;
 
-    // Binary or otherwise empty?
-    if num_lines_in_blamed == 0 {
-        return Ok(Outcome::default());
+    let mut hunks_to_blame = Vec::new();
+    let mut queue: gix_revwalk::PriorityQueue<CommitTime, ObjectId> = gix_revwalk::PriorityQueue::new();
+    // Binary or otherwise empty files have nothing to blame.
+    if num_lines_in_blamed != 0 {
+        let range_in_blamed_file =
+            one_based_inclusive_to_zero_based_exclusive_range(options.range.clone(), num_lines_in_blamed)?;
+        hunks_to_blame.push(UnblamedHunk {
+            range_in_blamed_file: range_in_blamed_file.clone(),
+            suspects: [(suspect, range_in_blamed_file)].into(),
+            source_file_names: Default::default(),
+        });
+        let commit = find_commit(cache.as_ref(), &odb, &suspect, &mut buf)?;
+        queue.insert(commit_time(&commit)?, suspect);
     }
 
-    let range_in_blamed_file = one_based_inclusive_to_zero_based_exclusive_range(options.range, num_lines_in_blamed)?;
-    let mut hunks_to_blame = vec![UnblamedHunk {
-        range_in_blamed_file: range_in_blamed_file.clone(),
-        suspects: [(suspect, range_in_blamed_file)].into(),
-    }];
+    Ok(Incremental {
+        odb,
+        cache,
+        resource_cache,
+        file_path: file_path.to_owned(),
+        options,
+        should_interrupt,
+        blamed_file_blob,
+        hunks_to_blame,
+        queue,
+        previous_entry: None,
+        lines_blamed_through_ignored_commits: Vec::new(),
+        diff_state: Default::default(),
+        found: Default::default(),
+        stats,
+        buf: Vec::new(),
+        buf2: Vec::new(),
+        buf3: Vec::new(),
+    })
+}
 
-    let (mut buf, mut buf2) = (Vec::new(), Vec::new());
-    let commit = find_commit(cache.as_ref(), &odb, &suspect, &mut buf)?;
-    let mut queue: gix_revwalk::PriorityQueue<CommitTime, ObjectId> = gix_revwalk::PriorityQueue::new();
-    queue.insert(commit_time(&commit)?, suspect);
+/// An iterator over the [`BlameEntry`] instances of a *Blamed File*, as returned by [`incremental()`].
+pub struct Incremental<'a, T> {
+    odb: T,
+    cache: Option<gix_commitgraph::Graph>,
+    resource_cache: &'a mut gix_diff::blob::Platform,
+    file_path: BString,
+    options: Options,
+    should_interrupt: &'a AtomicBool,
+    blamed_file_blob: Vec<u8>,
+    hunks_to_blame: Vec<UnblamedHunk>,
+    queue: gix_revwalk::PriorityQueue<CommitTime, ObjectId>,
+    previous_entry: Option<(ObjectId, Option<BString>, ObjectId)>,
+    /// Ranges in the *Blamed File* whose blame was passed from an ignored commit to the lines it replaced,
+    /// which hence aren't identical to the lines in their *Source File*.
+    lines_blamed_through_ignored_commits: Vec<Range<u32>>,
+    diff_state: gix_diff::tree::State,
+    /// Entries that are known, but weren't returned yet.
+    found: VecDeque<BlameEntry>,
+    stats: Statistics,
+    buf: Vec<u8>,
+    buf2: Vec<u8>,
+    buf3: Vec<u8>,
+}
+
+impl<T> Incremental<'_, T> {
+    /// Return the content of the *Blamed File*, ready for tokenization like [`Outcome::blob`].
+    pub fn blob(&self) -> &[u8] {
+        &self.blamed_file_blob
+    }
+
+    /// Return information about the amount of work performed so far.
+    pub fn statistics(&self) -> Statistics {
+        self.stats
+    }
+
+    /// Return `true` if all lines of the *Blamed File* were blamed.
+    pub fn is_done(&self) -> bool {
+        self.found.is_empty() && (self.hunks_to_blame.is_empty() || self.queue.is_empty())
+    }
+}
+
+impl<T> Iterator for Incremental<'_, T>
+where
+    T: gix_object::Find + gix_object::FindHeader,
+{
+    type Item = Result<BlameEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.found.pop_front() {
+                return Some(Ok(entry));
+            }
+            if self.is_done() {
+                return None;
+            }
+            if self.should_interrupt.load(Ordering::Relaxed) {
+                return Some(Err(Error::Interrupted));
+            }
+            if let Err(err) = self.blame_next_suspect() {
+                // The state may be inconsistent now, so stop the iteration.
+                self.hunks_to_blame.clear();
+                return Some(Err(err));
+            }
+        }
+    }
+}
 
-    let mut out = Vec::new();
-    let mut diff_state = gix_diff::tree::State::default();
-    let mut previous_entry: Option<(ObjectId, ObjectId)> = None;
-    'outer: while let Some(suspect) = queue.pop_value() {
+impl<T> Incremental<'_, T>
+where
+    T: gix_object::Find + gix_object::FindHeader,
+{
+    /// Process the next suspect in the queue, and add all entries that are known afterwards to `found`.
+    fn blame_next_suspect(&mut self) -> Result<(), Error> {
+        let Self {
+            odb,
+            cache,
+            resource_cache,
+            file_path,
+            options,
+            should_interrupt: _,
+            blamed_file_blob,
+            hunks_to_blame,
+            queue,
+            previous_entry,
+            lines_blamed_through_ignored_commits,
+            diff_state,
+            found,
+            stats,
+            buf,
+            buf2,
+            buf3,
+        } = self;
+        let odb = &*odb;
+        let file_path = file_path.as_bstr();
+        let Some(suspect) = queue.pop_value() else {
+            return Ok(());
+        };
         stats.commits_traversed += 1;
         if hunks_to_blame.is_empty() {
-            break;
+            return Ok(());
         }
 
         let is_still_suspect = hunks_to_blame.iter().any(|hunk| hunk.suspects.contains_key(&suspect));
         if !is_still_suspect {
             // There are no `UnblamedHunk`s associated with this `suspect`, so we can continue with
             // the next one.
-            continue 'outer;
+            return Ok(());
         }
 
-        let commit = find_commit(cache.as_ref(), &odb, &suspect, &mut buf)?;
+        let commit = find_commit(cache.as_ref(), odb, &suspect, buf)?;
         let commit_time = commit_time(&commit)?;
 
         if let Some(since) = options.since {
             if commit_time < since.seconds {
-                if unblamed_to_out_is_done(&mut hunks_to_blame, &mut out, suspect) {
-                    break 'outer;
-                }
-
-                continue;
+                unblamed_to_out_is_done(hunks_to_blame, found, suspect);
+                return Ok(());
             }
         }
 
-        let parent_ids: ParentIds = collect_parents(commit, &odb, cache.as_ref(), &mut buf2)?;
+        let parent_ids: ParentIds = collect_parents(commit, odb, cache.as_ref(), buf2)?;
 
         if parent_ids.is_empty() {
             if queue.is_empty() {
@@ -143,154 +300,257 @@ pub fn file(
                 // the remaining lines to it, even though we don’t explicitly check whether that is
                 // true here. We could perhaps use diff-tree-to-tree to compare `suspect` against
                 // an empty tree to validate this assumption.
-                if unblamed_to_out_is_done(&mut hunks_to_blame, &mut out, suspect) {
-                    break 'outer;
-                }
+                unblamed_to_out_is_done(hunks_to_blame, found, suspect);
             }
             // There is more, keep looking.
-            continue;
+            return Ok(());
         }
 
-        let mut entry = previous_entry
-            .take()
-            .filter(|(id, _)| *id == suspect)
-            .map(|(_, entry)| entry);
-        if entry.is_none() {
-            entry = find_path_entry_in_commit(
-                &odb,
-                &suspect,
-                file_path,
-                cache.as_ref(),
-                &mut buf,
-                &mut buf2,
-                &mut stats,
-            )?;
-        }
-
-        let Some(entry_id) = entry else {
-            continue;
-        };
-
-        // This block asserts that, for every `UnblamedHunk`, all lines in the *Blamed File* are
-        // identical to the corresponding lines in the *Source File*.
-        #[cfg(debug_assertions)]
-        {
-            let source_blob = odb.find_blob(&entry_id, &mut buf)?.data.to_vec();
-            let mut source_interner = gix_diff::blob::intern::Interner::new(source_blob.len() / 100);
-            let source_lines_as_tokens: Vec<_> = tokens_for_diffing(&source_blob)
-                .tokenize()
-                .map(|token| source_interner.intern(token))
-                .collect();
-
-            let mut blamed_interner = gix_diff::blob::intern::Interner::new(blamed_file_blob.len() / 100);
-            let blamed_lines_as_tokens: Vec<_> = tokens_for_diffing(&blamed_file_blob)
-                .tokenize()
-                .map(|token| blamed_interner.intern(token))
-                .collect();
-
-            for hunk in hunks_to_blame.iter() {
-                if let Some(range_in_suspect) = hunk.suspects.get(&suspect) {
-                    let range_in_blamed_file = hunk.range_in_blamed_file.clone();
-
-                    for (blamed_line_number, source_line_number) in range_in_blamed_file.zip(range_in_suspect.clone()) {
-                        let source_token = source_lines_as_tokens[source_line_number as usize];
-                        let blame_token = blamed_lines_as_tokens[blamed_line_number as usize];
+        // `suspect` may be responsible for lines of multiple source files if the file was renamed differently
+        // in multiple branches, or if lines were moved or copied from other files. Each of them is processed on its own.
+        let mut source_file_names: Vec<_> = hunks_to_blame
+            .iter()
+            .filter(|hunk| hunk.suspects.contains_key(&suspect))
+            .map(|hunk| hunk.source_file_name(suspect).cloned())
+            .collect();
+        source_file_names.sort();
+        source_file_names.dedup();
+
+        for source_file_name in source_file_names {
+            let (mut hunks, other_hunks): (Vec<_>, Vec<_>) =
+                std::mem::take(hunks_to_blame).into_iter().partition(|hunk| {
+                    hunk.suspects.contains_key(&suspect) && hunk.source_file_name(suspect) == source_file_name.as_ref()
+                });
+            *hunks_to_blame = other_hunks;
+            let source_file_path = source_file_name.as_ref().map_or(file_path, |name| name.as_ref());
+
+            'source_file: {
+                let mut entry = previous_entry
+                    .take()
+                    .filter(|(id, name, _)| *id == suspect && *name == source_file_name)
+                    .map(|(_, _, entry)| entry);
+                if entry.is_none() {
+                    entry =
+                        find_path_entry_in_commit(odb, &suspect, source_file_path, cache.as_ref(), buf, buf2, stats)?;
+                }
 
-                        let source_line = BString::new(source_interner[source_token].into());
-                        let blamed_line = BString::new(blamed_interner[blame_token].into());
+                let Some(entry_id) = entry else {
+                    break 'source_file;
+                };
 
-                        assert_eq!(source_line, blamed_line);
+                // This block asserts that, for every `UnblamedHunk`, all lines in the *Blamed File* are
+                // identical to the corresponding lines in the *Source File*, except for lines whose blame was
+                // passed through ignored commits. Whitespace is compared as configured.
+                #[cfg(debug_assertions)]
+                {
+                    let source_blob = odb.find_blob(&entry_id, buf)?.data.to_vec();
+                    let mut source_interner = gix_diff::blob::intern::Interner::new(source_blob.len() / 100);
+                    let source_lines_as_tokens: Vec<_> = tokens_for_diffing(&source_blob)
+                        .tokenize()
+                        .map(|token| source_interner.intern(token))
+                        .collect();
+
+                    let mut blamed_interner = gix_diff::blob::intern::Interner::new(blamed_file_blob.len() / 100);
+                    let blamed_lines_as_tokens: Vec<_> = tokens_for_diffing(blamed_file_blob)
+                        .tokenize()
+                        .map(|token| blamed_interner.intern(token))
+                        .collect();
+
+                    for hunk in hunks.iter() {
+                        if let Some(range_in_suspect) = hunk.suspects.get(&suspect) {
+                            let range_in_blamed_file = hunk.range_in_blamed_file.clone();
+
+                            for (blamed_line_number, source_line_number) in
+                                range_in_blamed_file.zip(range_in_suspect.clone())
+                            {
+                                if lines_blamed_through_ignored_commits
+                                    .iter()
+                                    .any(|range| range.contains(&blamed_line_number))
+                                {
+                                    continue;
+                                }
+                                let source_token = source_lines_as_tokens[source_line_number as usize];
+                                let blame_token = blamed_lines_as_tokens[blamed_line_number as usize];
+
+                                let source_line =
+                                    BString::new(options.whitespace.normalize(source_interner[source_token]).into());
+                                let blamed_line =
+                                    BString::new(options.whitespace.normalize(blamed_interner[blame_token]).into());
+
+                                assert_eq!(source_line, blamed_line);
+                            }
+                        }
                     }
                 }
-            }
-        }
 
-        for (pid, (parent_id, parent_commit_time)) in parent_ids.iter().enumerate() {
-            if let Some(parent_entry_id) = find_path_entry_in_commit(
-                &odb,
-                parent_id,
-                file_path,
-                cache.as_ref(),
-                &mut buf,
-                &mut buf2,
-                &mut stats,
-            )? {
-                let no_change_in_entry = entry_id == parent_entry_id;
-                if pid == 0 {
-                    previous_entry = Some((*parent_id, parent_entry_id));
-                }
-                if no_change_in_entry {
-                    pass_blame_from_to(suspect, *parent_id, &mut hunks_to_blame);
-                    queue.insert(*parent_commit_time, *parent_id);
-                    continue 'outer;
+                for (pid, (parent_id, parent_commit_time)) in parent_ids.iter().enumerate() {
+                    if let Some(parent_entry_id) =
+                        find_path_entry_in_commit(odb, parent_id, source_file_path, cache.as_ref(), buf, buf2, stats)?
+                    {
+                        let no_change_in_entry = entry_id == parent_entry_id;
+                        if pid == 0 {
+                            *previous_entry = Some((*parent_id, source_file_name.clone(), parent_entry_id));
+                        }
+                        if no_change_in_entry {
+                            pass_blame_from_to(suspect, *parent_id, &mut hunks);
+                            queue.insert(*parent_commit_time, *parent_id);
+                            break 'source_file;
+                        }
+                    }
                 }
-            }
-        }
 
-        let more_than_one_parent = parent_ids.len() > 1;
-        for (parent_id, parent_commit_time) in parent_ids {
-            queue.insert(parent_commit_time, parent_id);
-            let changes_for_file_path = tree_diff_at_file_path(
-                &odb,
-                file_path,
-                suspect,
-                parent_id,
-                cache.as_ref(),
-                &mut stats,
-                &mut diff_state,
-                &mut buf,
-                &mut buf2,
-                &mut buf3,
-            )?;
-            let Some(modification) = changes_for_file_path else {
-                if more_than_one_parent {
-                    // None of the changes affected the file we’re currently blaming.
-                    // Copy blame to parent.
-                    for unblamed_hunk in &mut hunks_to_blame {
-                        unblamed_hunk.clone_blame(suspect, parent_id);
+                // The source file in each parent, used to find lines that were moved within the file.
+                let mut sources_in_parents = Vec::new();
+                // The changes compared to each parent, used to find the lines that an ignored `suspect` replaced.
+                let is_ignored = options.ignore_revs.contains(&suspect);
+                let mut changes_in_parents = Vec::new();
+                let more_than_one_parent = parent_ids.len() > 1;
+                for (parent_id, parent_commit_time) in parent_ids.iter().copied() {
+                    queue.insert(parent_commit_time, parent_id);
+                    let changes_for_file_path = tree_diff_at_file_path(
+                        odb,
+                        source_file_path,
+                        suspect,
+                        parent_id,
+                        cache.as_ref(),
+                        stats,
+                        diff_state,
+                        buf,
+                        buf2,
+                        buf3,
+                    )?;
+                    let Some(modification) = changes_for_file_path else {
+                        if more_than_one_parent {
+                            // None of the changes affected the file we’re currently blaming.
+                            // Copy blame to parent.
+                            for unblamed_hunk in &mut hunks {
+                                unblamed_hunk.clone_blame(suspect, parent_id);
+                            }
+                        } else {
+                            pass_blame_from_to(suspect, parent_id, &mut hunks);
+                        }
+                        continue;
+                    };
+
+                    match modification {
+                        gix_diff::tree::recorder::Change::Addition { .. } => {
+                            let rename_source = match options.rewrites {
+                                Some(rewrites) => find_rename_source(
+                                    odb,
+                                    resource_cache,
+                                    source_file_path,
+                                    suspect,
+                                    parent_id,
+                                    rewrites,
+                                    cache.as_ref(),
+                                    stats,
+                                    diff_state,
+                                    buf,
+                                    buf2,
+                                    buf3,
+                                )?,
+                                None => None,
+                            };
+                            if let Some((previous_path, previous_oid)) = rename_source {
+                                let changes = blob_changes(
+                                    odb,
+                                    resource_cache,
+                                    entry_id,
+                                    previous_oid,
+                                    source_file_path,
+                                    options,
+                                    stats,
+                                )?;
+                                let name = (previous_path != file_path).then(|| previous_path.clone());
+                                if is_ignored {
+                                    changes_in_parents.push((parent_id, changes.clone(), previous_oid, name.clone()));
+                                }
+                                hunks = process_changes_of_overlapping_hunks(hunks, changes, suspect, parent_id);
+                                for hunk in &mut hunks {
+                                    hunk.set_source_file_name(parent_id, name.clone());
+                                }
+                                sources_in_parents.push((parent_id, previous_path, previous_oid));
+                            } else if more_than_one_parent {
+                                // Do nothing under the assumption that this always (or almost always)
+                                // implies that the file comes from a different parent, compared to which
+                                // it was modified, not added.
+                            } else if options.moved_lines.is_none()
+                                && unblamed_to_out_is_done(&mut hunks, found, suspect)
+                            {
+                                break 'source_file;
+                            }
+                        }
+                        gix_diff::tree::recorder::Change::Deletion { .. } => {
+                            unreachable!("We already found file_path in suspect^{{tree}}, so it can't be deleted")
+                        }
+                        gix_diff::tree::recorder::Change::Modification { previous_oid, oid, .. } => {
+                            let changes =
+                                blob_changes(odb, resource_cache, oid, previous_oid, source_file_path, options, stats)?;
+                            if is_ignored {
+                                changes_in_parents.push((
+                                    parent_id,
+                                    changes.clone(),
+                                    previous_oid,
+                                    source_file_name.clone(),
+                                ));
+                            }
+                            hunks = process_changes_of_overlapping_hunks(hunks, changes, suspect, parent_id);
+                            sources_in_parents.push((parent_id, source_file_path.to_owned(), previous_oid));
+                        }
                     }
-                } else {
-                    pass_blame_from_to(suspect, parent_id, &mut hunks_to_blame);
                 }
-                continue;
-            };
 
-            match modification {
-                gix_diff::tree::recorder::Change::Addition { .. } => {
-                    if more_than_one_parent {
-                        // Do nothing under the assumption that this always (or almost always)
-                        // implies that the file comes from a different parent, compared to which
-                        // it was modified, not added.
-                    } else if unblamed_to_out_is_done(&mut hunks_to_blame, &mut out, suspect) {
-                        break 'outer;
+                if !changes_in_parents.is_empty() {
+                    let suspect_blob = odb.find_blob(&entry_id, buf)?.data.to_vec();
+                    for (parent_id, changes, previous_oid, name) in changes_in_parents {
+                        let parent_blob = odb.find_blob(&previous_oid, buf)?.data.to_vec();
+                        hunks = pass_blame_of_ignored_commit(
+                            hunks,
+                            &changes,
+                            &suspect_blob,
+                            &parent_blob,
+                            suspect,
+                            parent_id,
+                            name,
+                            lines_blamed_through_ignored_commits,
+                        );
                     }
                 }
-                gix_diff::tree::recorder::Change::Deletion { .. } => {
-                    unreachable!("We already found file_path in suspect^{{tree}}, so it can't be deleted")
-                }
-                gix_diff::tree::recorder::Change::Modification { previous_oid, oid, .. } => {
-                    let changes = blob_changes(
-                        &odb,
-                        resource_cache,
-                        oid,
-                        previous_oid,
-                        file_path,
-                        options.diff_algorithm,
-                        &mut stats,
+
+                if let Some(moved_lines) = options.moved_lines {
+                    hunks = pass_blame_for_moved_lines(
+                        odb,
+                        hunks,
+                        MovedLinesContext {
+                            suspect,
+                            suspect_blob_id: entry_id,
+                            blamed_file_path: file_path,
+                            parent_ids: &parent_ids,
+                            sources_in_parents: &sources_in_parents,
+                            options: moved_lines,
+                        },
+                        cache.as_ref(),
+                        stats,
+                        diff_state,
+                        buf,
+                        buf2,
+                        buf3,
                     )?;
-                    hunks_to_blame = process_changes(hunks_to_blame, changes, suspect, parent_id);
                 }
             }
+            hunks_to_blame.extend(hunks);
         }
+        hunks_to_blame.sort_by_key(|hunk| hunk.range_in_blamed_file.start);
 
         hunks_to_blame.retain_mut(|unblamed_hunk| {
             if unblamed_hunk.suspects.len() == 1 {
-                if let Some(entry) = BlameEntry::from_unblamed_hunk(unblamed_hunk, suspect) {
+                if let Some(mut entry) = BlameEntry::from_unblamed_hunk(unblamed_hunk, suspect) {
                     // At this point, we have copied blame for every hunk to a parent. Hunks
                     // that have only `suspect` left in `suspects` have not passed blame to any
                     // parent, and so they can be converted to a `BlameEntry` and moved to
                     // `out`.
-                    out.push(entry);
+                    entry.unblamable = options.ignore_revs.contains(&suspect);
+                    found.push_back(entry);
                     return false;
                 }
             }
@@ -300,14 +560,16 @@ pub fn file(
 
         // This block asserts that line ranges for each suspect never overlap. If they did overlap
         // this would mean that the same line in a *Source File* would map to more than one line in
-        // the *Blamed File* and this is not possible.
+        // the *Blamed File* and this is not possible, unless lines were copied.
         #[cfg(debug_assertions)]
-        {
+        if options.moved_lines.is_none() {
             let ranges = hunks_to_blame.iter().fold(
-                std::collections::BTreeMap::<ObjectId, Vec<Range<u32>>>::new(),
+                std::collections::BTreeMap::<(ObjectId, Option<&BString>), Vec<Range<u32>>>::new(),
                 |mut acc, hunk| {
                     for (suspect, range) in hunk.suspects.clone() {
-                        acc.entry(suspect).or_default().push(range);
+                        acc.entry((suspect, hunk.source_file_name(suspect)))
+                            .or_default()
+                            .push(range);
                     }
 
                     acc
@@ -324,22 +586,8 @@ pub fn file(
                 }
             }
         }
+        Ok(())
     }
-
-    debug_assert_eq!(
-        hunks_to_blame,
-        vec![],
-        "only if there is no portion of the file left we have completed the blame"
-    );
-
-    // I don’t know yet whether it would make sense to use a data structure instead that preserves
-    // order on insertion.
-    out.sort_by(|a, b| a.start_in_blamed_file.cmp(&b.start_in_blamed_file));
-    Ok(Outcome {
-        entries: coalesce_blame_entries(out),
-        blob: blamed_file_blob,
-        statistics: stats,
-    })
 }
 
 /// This function assumes that `range` has 1-based inclusive line numbers and converts it to the
@@ -375,7 +623,7 @@ fn pass_blame_from_to(from: ObjectId, to: ObjectId, hunks_to_blame: &mut Vec<Unb
 /// Return `true` if we are done because `hunks_to_blame` is empty.
 fn unblamed_to_out_is_done(
     hunks_to_blame: &mut Vec<UnblamedHunk>,
-    out: &mut Vec<BlameEntry>,
+    out: &mut VecDeque<BlameEntry>,
     suspect: ObjectId,
 ) -> bool {
     let mut without_suspect = Vec::new();
@@ -411,6 +659,8 @@ fn coalesce_blame_entries(lines_blamed: Vec<BlameEntry>) -> Vec<BlameEntry> {
                 let previous_source_range = previous_entry.range_in_source_file();
                 let current_source_range = entry.range_in_source_file();
                 if previous_entry.commit_id == entry.commit_id
+                    && previous_entry.source_file_name == entry.source_file_name
+                    && previous_entry.unblamable == entry.unblamable
                     && previous_blamed_range.end == current_blamed_range.start
                     // As of 2024-09-19, the check below only is in `git`, but not in `libgit2`.
                     && previous_source_range.end == current_source_range.start
@@ -422,6 +672,8 @@ fn coalesce_blame_entries(lines_blamed: Vec<BlameEntry>) -> Vec<BlameEntry> {
                         len: NonZeroU32::new((current_source_range.end - previous_source_range.start) as u32)
                             .expect("BUG: hunks are never zero-sized"),
                         commit_id: previous_entry.commit_id,
+                        source_file_name: previous_entry.source_file_name.clone(),
+                        unblamable: previous_entry.unblamable,
                     };
 
                     acc.pop();
@@ -555,13 +807,479 @@ fn tree_diff_at_file_path(
     }
 }
 
+/// Find the path and blob id of the file in `parent_id` that `file_path` in `id` was renamed or copied from,
+/// using rewrite tracking according to `rewrites`.
+#[allow(clippy::too_many_arguments)]
+fn find_rename_source(
+    odb: impl gix_object::Find + gix_object::FindHeader,
+    resource_cache: &mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    id: ObjectId,
+    parent_id: ObjectId,
+    rewrites: gix_diff::Rewrites,
+    cache: Option<&gix_commitgraph::Graph>,
+    stats: &mut Statistics,
+    state: &mut gix_diff::tree::State,
+    commit_buf: &mut Vec<u8>,
+    lhs_tree_buf: &mut Vec<u8>,
+    rhs_tree_buf: &mut Vec<u8>,
+) -> Result<Option<(BString, ObjectId)>, Error> {
+    use gix_diff::tree_with_rewrites::{Action, ChangeRef};
+
+    let parent_tree_id = tree_id(find_commit(cache, &odb, &parent_id, commit_buf)?)?;
+    let parent_tree_iter = odb.find_tree_iter(&parent_tree_id, lhs_tree_buf)?;
+    stats.trees_decoded += 1;
+
+    let tree_id = tree_id(find_commit(cache, &odb, &id, commit_buf)?)?;
+    let tree_iter = odb.find_tree_iter(&tree_id, rhs_tree_buf)?;
+    stats.trees_decoded += 1;
+
+    let mut source = None;
+    let result = gix_diff::tree_with_rewrites(
+        parent_tree_iter,
+        tree_iter,
+        resource_cache,
+        state,
+        &odb,
+        |change| -> Result<_, std::convert::Infallible> {
+            if let ChangeRef::Rewrite {
+                source_location,
+                source_id,
+                location,
+                ..
+            } = change
+            {
+                if location == file_path {
+                    source = Some((source_location.to_owned(), source_id));
+                    return Ok(Action::Cancel);
+                }
+            }
+            Ok(Action::Continue)
+        },
+        gix_diff::tree_with_rewrites::Options {
+            location: Some(gix_diff::tree::recorder::Location::Path),
+            rewrites: Some(rewrites),
+        },
+    );
+    stats.trees_diffed += 1;
+
+    match result {
+        Ok(_) | Err(gix_diff::tree_with_rewrites::Error::Diff(gix_diff::tree::Error::Cancelled)) => Ok(source),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Information needed to find lines that were moved or copied into a source file in `suspect`.
+struct MovedLinesContext<'a> {
+    suspect: ObjectId,
+    /// The version of the source file in `suspect`.
+    suspect_blob_id: ObjectId,
+    blamed_file_path: &'a BStr,
+    parent_ids: &'a ParentIds,
+    /// `(parent_id, path, blob_id)` of the source file in parents of `suspect` which have it.
+    sources_in_parents: &'a [(ObjectId, BString, ObjectId)],
+    options: MovedLines,
+}
+
+/// Look for the lines of all hunks that would be blamed on `suspect` in files of its parents, and pass the blame
+/// of all lines that were found to the respective parent, similar to `git blame -M` and `git blame -C`.
+#[allow(clippy::too_many_arguments)]
+fn pass_blame_for_moved_lines(
+    odb: impl gix_object::Find + gix_object::FindHeader,
+    mut hunks: Vec<UnblamedHunk>,
+    ctx: MovedLinesContext<'_>,
+    cache: Option<&gix_commitgraph::Graph>,
+    stats: &mut Statistics,
+    state: &mut gix_diff::tree::State,
+    buf: &mut Vec<u8>,
+    lhs_tree_buf: &mut Vec<u8>,
+    rhs_tree_buf: &mut Vec<u8>,
+) -> Result<Vec<UnblamedHunk>, Error> {
+    let is_unblamed = |hunk: &UnblamedHunk| hunk.suspects.len() == 1 && hunk.suspects.contains_key(&ctx.suspect);
+    if !hunks.iter().any(is_unblamed) {
+        return Ok(hunks);
+    }
+
+    let suspect_blob = odb.find_blob(&ctx.suspect_blob_id, buf)?.data.to_vec();
+    for (parent_id, _) in ctx.parent_ids.iter().copied() {
+        let mut candidates: Vec<(BString, ObjectId)> = ctx
+            .sources_in_parents
+            .iter()
+            .filter(|(id, ..)| *id == parent_id)
+            .map(|(_, path, blob_id)| (path.clone(), *blob_id))
+            .collect();
+        match ctx.options.source {
+            MovedLinesSource::SameFile => {}
+            MovedLinesSource::ChangedFiles => {
+                let parent_tree_id = tree_id(find_commit(cache, &odb, &parent_id, buf)?)?;
+                let parent_tree_iter = odb.find_tree_iter(&parent_tree_id, lhs_tree_buf)?;
+                let tree_id = tree_id(find_commit(cache, &odb, &ctx.suspect, buf)?)?;
+                let tree_iter = odb.find_tree_iter(&tree_id, rhs_tree_buf)?;
+                stats.trees_decoded += 2;
+
+                let mut recorder = gix_diff::tree::Recorder::default();
+                gix_diff::tree(parent_tree_iter, tree_iter, &mut *state, &odb, &mut recorder)?;
+                stats.trees_diffed += 1;
+                candidates.extend(recorder.records.into_iter().filter_map(|change| match change {
+                    gix_diff::tree::recorder::Change::Modification {
+                        previous_entry_mode,
+                        previous_oid,
+                        path,
+                        ..
+                    } => previous_entry_mode.is_blob().then_some((path, previous_oid)),
+                    gix_diff::tree::recorder::Change::Deletion {
+                        entry_mode, oid, path, ..
+                    } => entry_mode.is_blob().then_some((path, oid)),
+                    gix_diff::tree::recorder::Change::Addition { .. } => None,
+                }));
+            }
+            MovedLinesSource::AnyFile => {
+                let parent_tree_id = tree_id(find_commit(cache, &odb, &parent_id, buf)?)?;
+                let parent_tree_iter = odb.find_tree_iter(&parent_tree_id, lhs_tree_buf)?;
+                stats.trees_decoded += 1;
+
+                let mut recorder = gix_traverse::tree::Recorder::default();
+                gix_traverse::tree::breadthfirst(
+                    parent_tree_iter,
+                    gix_traverse::tree::breadthfirst::State::default(),
+                    &odb,
+                    &mut recorder,
+                )?;
+                candidates.extend(
+                    recorder
+                        .records
+                        .into_iter()
+                        .filter(|entry| entry.mode.is_blob())
+                        .map(|entry| (entry.filepath, entry.oid)),
+                );
+            }
+        }
+
+        let mut seen = BTreeSet::new();
+        for (path, blob_id) in candidates {
+            if !hunks.iter().any(is_unblamed) {
+                return Ok(hunks);
+            }
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let candidate_blob = odb.find_blob(&blob_id, buf)?.data.to_vec();
+            let name = (path != ctx.blamed_file_path).then_some(path);
+            hunks = pass_blame_for_lines_found_in(
+                hunks,
+                &suspect_blob,
+                &candidate_blob,
+                ctx.suspect,
+                parent_id,
+                name,
+                ctx.options.min_alnum_chars,
+            );
+        }
+    }
+    Ok(hunks)
+}
+
+/// Split all hunks that are only blamed on `suspect` so that each group of consecutive lines that can also be found
+/// in `candidate_blob` of `parent` is passed to `parent`, as long as it has at least `min_alnum_chars` alphanumeric
+/// characters. `name` is the path of `candidate_blob` if it differs from the path of the *Blamed File*.
+fn pass_blame_for_lines_found_in(
+    hunks: Vec<UnblamedHunk>,
+    suspect_blob: &[u8],
+    candidate_blob: &[u8],
+    suspect: ObjectId,
+    parent: ObjectId,
+    name: Option<BString>,
+    min_alnum_chars: u32,
+) -> Vec<UnblamedHunk> {
+    let mut interner = gix_diff::blob::intern::Interner::new((suspect_blob.len() + candidate_blob.len()) / 100);
+    let suspect_lines: Vec<_> = tokens_for_diffing(suspect_blob)
+        .tokenize()
+        .map(|token| interner.intern(token))
+        .collect();
+    let candidate_lines: Vec<_> = tokens_for_diffing(candidate_blob)
+        .tokenize()
+        .map(|token| interner.intern(token))
+        .collect();
+    let mut positions_in_candidate = HashMap::<_, Vec<usize>>::new();
+    for (pos, token) in candidate_lines.iter().enumerate() {
+        positions_in_candidate.entry(*token).or_default().push(pos);
+    }
+    let score = |lines: Range<usize>| -> u32 {
+        suspect_lines[lines]
+            .iter()
+            .map(|token| interner[*token].iter().filter(|b| b.is_ascii_alphanumeric()).count() as u32)
+            .sum()
+    };
+
+    let mut out = Vec::with_capacity(hunks.len());
+    for hunk in hunks {
+        let range = match hunk.suspects.get(&suspect) {
+            Some(range) if hunk.suspects.len() == 1 => range.clone(),
+            _ => {
+                out.push(hunk);
+                continue;
+            }
+        };
+        let piece =
+            |range_in_suspect: Range<u32>, owner: ObjectId, range_in_owner: Range<u32>, name: Option<&BString>| {
+                let start_in_blamed_file = hunk.range_in_blamed_file.start + (range_in_suspect.start - range.start);
+                UnblamedHunk {
+                    range_in_blamed_file: start_in_blamed_file..start_in_blamed_file + range_in_suspect.len() as u32,
+                    suspects: [(owner, range_in_owner)].into(),
+                    source_file_names: name.map(|name| (owner, name.clone())).into_iter().collect(),
+                }
+            };
+
+        let mut pieces = Vec::new();
+        let (mut cursor, mut line) = (range.start, range.start);
+        while line < range.end {
+            let longest_match = positions_in_candidate
+                .get(&suspect_lines[line as usize])
+                .into_iter()
+                .flatten()
+                .map(|&start| {
+                    let len = suspect_lines[line as usize..range.end as usize]
+                        .iter()
+                        .zip(&candidate_lines[start..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    (len, start)
+                })
+                .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+            match longest_match {
+                Some((len, start)) if score(line as usize..line as usize + len) >= min_alnum_chars => {
+                    let len = len as u32;
+                    if cursor < line {
+                        pieces.push(piece(
+                            cursor..line,
+                            suspect,
+                            cursor..line,
+                            hunk.source_file_name(suspect),
+                        ));
+                    }
+                    let start = start as u32;
+                    pieces.push(piece(line..line + len, parent, start..start + len, name.as_ref()));
+                    line += len;
+                    cursor = line;
+                }
+                _ => line += 1,
+            }
+        }
+        if pieces.is_empty() {
+            out.push(hunk);
+            continue;
+        }
+        if cursor < range.end {
+            pieces.push(piece(
+                cursor..range.end,
+                suspect,
+                cursor..range.end,
+                hunk.source_file_name(suspect),
+            ));
+        }
+        out.extend(pieces);
+    }
+    out
+}
+
+/// Pass the blame for lines of all hunks that are only blamed on the ignored `suspect` to the lines they replaced in
+/// `parent_blob` of `parent`, if they are similar enough, and leave all other lines with `suspect`. `changes` are the
+/// changes from `parent_blob` to `suspect_blob`, and `name` is the path of `parent_blob` if it differs from the path
+/// of the *Blamed File*. The ranges in the *Blamed File* whose blame was passed are added to `passed_lines`.
+///
+/// This is what `git blame --ignore-rev` does.
+#[allow(clippy::too_many_arguments)]
+fn pass_blame_of_ignored_commit(
+    hunks: Vec<UnblamedHunk>,
+    changes: &[Change],
+    suspect_blob: &[u8],
+    parent_blob: &[u8],
+    suspect: ObjectId,
+    parent: ObjectId,
+    name: Option<BString>,
+    passed_lines: &mut Vec<Range<u32>>,
+) -> Vec<UnblamedHunk> {
+    let is_unblamed = |hunk: &UnblamedHunk| hunk.suspects.len() == 1 && hunk.suspects.contains_key(&suspect);
+    if !hunks.iter().any(is_unblamed) {
+        return hunks;
+    }
+
+    let suspect_lines: Vec<_> = tokens_for_diffing(suspect_blob).tokenize().collect();
+    let parent_lines: Vec<_> = tokens_for_diffing(parent_blob).tokenize().collect();
+    let mut line_in_parent = vec![None; suspect_lines.len()];
+    let mut start_in_parent = 0;
+    for change in changes {
+        match change {
+            Change::Unchanged(unchanged) => start_in_parent += unchanged.len(),
+            Change::AddedOrReplaced(added, number_of_lines_deleted) => {
+                let replaced = start_in_parent..start_in_parent + *number_of_lines_deleted as usize;
+                let added = added.start as usize..added.end as usize;
+                for (line, matching_line) in added.clone().zip(fuzzy_find_matching_lines(
+                    &suspect_lines[added],
+                    &parent_lines[replaced.clone()],
+                )) {
+                    line_in_parent[line] = matching_line.map(|l| (replaced.start + l) as u32);
+                }
+                start_in_parent = replaced.end;
+            }
+            Change::Deleted(_, number_of_lines_deleted) => start_in_parent += *number_of_lines_deleted as usize,
+        }
+    }
+
+    let mut out = Vec::with_capacity(hunks.len());
+    for hunk in hunks {
+        let range = match hunk.suspects.get(&suspect) {
+            Some(range) if hunk.suspects.len() == 1 => range.clone(),
+            _ => {
+                out.push(hunk);
+                continue;
+            }
+        };
+        // Split the hunk into groups of lines that either match consecutive lines in `parent`, or don't match at all.
+        let mut start = range.start;
+        while start < range.end {
+            let first_match = line_in_parent[start as usize];
+            let len = (start..range.end)
+                .zip(0..)
+                .take_while(|&(line, offset)| match (first_match, line_in_parent[line as usize]) {
+                    (Some(first), Some(matching_line)) => matching_line == first + offset,
+                    (None, None) => true,
+                    _ => false,
+                })
+                .count() as u32;
+            let start_in_blamed_file = hunk.range_in_blamed_file.start + (start - range.start);
+            let range_in_blamed_file = start_in_blamed_file..start_in_blamed_file + len;
+            out.push(match first_match {
+                Some(first) => {
+                    passed_lines.push(range_in_blamed_file.clone());
+                    UnblamedHunk {
+                        range_in_blamed_file,
+                        suspects: [(parent, first..first + len)].into(),
+                        source_file_names: name.iter().map(|name| (parent, name.clone())).collect(),
+                    }
+                }
+                None => UnblamedHunk {
+                    range_in_blamed_file,
+                    suspects: [(suspect, start..start + len)].into(),
+                    source_file_names: hunk.source_file_names.clone(),
+                },
+            });
+            start += len;
+        }
+    }
+    out
+}
+
+/// Return the index of the line in `parent_lines` that each line in `lines` is most similar to, or `None` if there
+/// is no such line, using the same heuristic as `git blame --ignore-rev`.
+///
+/// Each line is matched to at most one line, and lines that were matched are in the same order in both.
+fn fuzzy_find_matching_lines(lines: &[&[u8]], parent_lines: &[&[u8]]) -> Vec<Option<usize>> {
+    /// How many lines around the expected position of a line in `parent_lines` are searched for a similar line.
+    const MAX_SEARCH_DISTANCE: usize = 10;
+
+    let mut out = vec![None; lines.len()];
+    if lines.is_empty() || parent_lines.is_empty() {
+        return out;
+    }
+    let fingerprints: Vec<_> = lines.iter().map(|line| fingerprint(line)).collect();
+    let parent_fingerprints: Vec<_> = parent_lines.iter().map(|line| fingerprint(line)).collect();
+
+    let mut ranges_to_match = vec![(0..lines.len(), 0..parent_lines.len())];
+    while let Some((lines, parent_lines)) = ranges_to_match.pop() {
+        if lines.is_empty() || parent_lines.is_empty() {
+            continue;
+        }
+        // `(certainty, line, line_in_parent)` of the line that is most certainly matched.
+        let mut best = None::<(usize, usize, usize)>;
+        for line in lines.clone() {
+            // The line in the parent this line would correspond to if lines were spread evenly.
+            let expected = parent_lines.start + ((line - lines.start) * 2 + 1) * parent_lines.len() / (lines.len() * 2);
+            let mut candidates: Vec<_> = (expected.saturating_sub(MAX_SEARCH_DISTANCE).max(parent_lines.start)
+                ..(expected + MAX_SEARCH_DISTANCE + 1).min(parent_lines.end))
+                .collect();
+            // Prefer the closest line among equally similar ones.
+            candidates.sort_by_key(|candidate| candidate.abs_diff(expected));
+
+            let (mut best_similarity, mut second_best_similarity, mut best_line) = (0, 0, None);
+            for candidate in candidates {
+                let similarity = similarity(&fingerprints[line], &parent_fingerprints[candidate]);
+                if similarity > best_similarity {
+                    second_best_similarity = best_similarity;
+                    best_similarity = similarity;
+                    best_line = Some(candidate);
+                } else if similarity > second_best_similarity {
+                    second_best_similarity = similarity;
+                }
+            }
+            let Some(best_line) = best_line else { continue };
+            // Lines that match two lines equally well are less certain, but a very good match with two lines
+            // is still preferred over a poor match with a single line.
+            let certainty = best_similarity * 2 - second_best_similarity;
+            if best.map_or(true, |(best_certainty, ..)| certainty > best_certainty) {
+                best = Some((certainty, line, best_line));
+            }
+        }
+        if let Some((_, line, line_in_parent)) = best {
+            out[line] = Some(line_in_parent);
+            ranges_to_match.push((lines.start..line, parent_lines.start..line_in_parent));
+            ranges_to_match.push((line + 1..lines.end, line_in_parent + 1..parent_lines.end));
+        }
+    }
+    out
+}
+
+/// Return the sorted pairs of adjacent characters in `line`, with letters in lower case, and with all whitespace
+/// treated the same.
+fn fingerprint(line: &[u8]) -> Vec<u16> {
+    let mut out = Vec::with_capacity(line.len() + 1);
+    let mut previous = 0;
+    for byte in line.iter().chain(Some(&b' ')) {
+        let byte = if byte.is_ascii_whitespace() {
+            0
+        } else {
+            byte.to_ascii_lowercase()
+        };
+        let pair = u16::from(previous) | (u16::from(byte) << 8);
+        previous = byte;
+        // Pairs of whitespace aren't interesting.
+        if pair != 0 {
+            out.push(pair);
+        }
+    }
+    out.sort_unstable();
+    out
+}
+
+/// Return the amount of pairs of characters that are in both sorted fingerprints `a` and `b`.
+fn similarity(a: &[u16], b: &[u16]) -> usize {
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+    let mut count = 0;
+    while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+        match x.cmp(y) {
+            std::cmp::Ordering::Less => {
+                a.next();
+            }
+            std::cmp::Ordering::Greater => {
+                b.next();
+            }
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                a.next();
+                b.next();
+            }
+        }
+    }
+    count
+}
+
 fn blob_changes(
     odb: impl gix_object::Find + gix_object::FindHeader,
     resource_cache: &mut gix_diff::blob::Platform,
     oid: ObjectId,
     previous_oid: ObjectId,
     file_path: &BStr,
-    diff_algorithm: gix_diff::blob::Algorithm,
+    options: &Options,
     stats: &mut Statistics,
 ) -> Result<Vec<Change>, Error> {
     /// Record all [`Change`]s to learn about additions, deletions and unchanged portions of a *Source File*.
@@ -640,7 +1358,16 @@ fn blob_changes(
     let number_of_lines_in_destination = input.after.len();
     let change_recorder = ChangeRecorder::new(number_of_lines_in_destination as u32);
 
-    let res = gix_diff::blob::diff(diff_algorithm, &input, change_recorder);
+    let res = gix_diff::blob::whitespace::diff(
+        options.diff_algorithm,
+        &input,
+        &options.whitespace,
+        // Like `diff.indentHeuristic`, which is `true` by default in `git`.
+        true,
+        0,
+        0,
+        change_recorder,
+    );
     stats.blobs_diffed += 1;
     Ok(res)
 }
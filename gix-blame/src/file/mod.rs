@@ -4,6 +4,7 @@ use std::num::NonZeroU32;
 use std::ops::Range;
 
 use gix_hash::ObjectId;
+use gix_object::bstr::BString;
 
 use crate::types::{BlameEntry, Either, LineRange};
 use crate::types::{Change, Offset, UnblamedHunk};
@@ -357,6 +358,37 @@ fn process_changes(
     new_hunks_to_blame
 }
 
+/// Like [`process_changes()`], but also handles hunks whose ranges in `suspect` overlap, which happens if lines were copied.
+/// These are processed in separate passes, as each pass expects hunks with ranges in ascending order that don't overlap.
+fn process_changes_of_overlapping_hunks(
+    mut hunks_to_blame: Vec<UnblamedHunk>,
+    changes: Vec<Change>,
+    suspect: ObjectId,
+    parent: ObjectId,
+) -> Vec<UnblamedHunk> {
+    hunks_to_blame.sort_by_key(|hunk| hunk.suspects.get(&suspect).map(|range| range.start));
+    let mut passes: Vec<Vec<UnblamedHunk>> = Vec::new();
+    for hunk in hunks_to_blame {
+        let start = hunk.suspects.get(&suspect).map_or(0, |range| range.start);
+        let pass = passes.iter_mut().find(|pass| {
+            pass.last()
+                .and_then(|last| last.suspects.get(&suspect))
+                .map_or(true, |last| last.end <= start)
+        });
+        match pass {
+            Some(pass) => pass.push(hunk),
+            None => passes.push(vec![hunk]),
+        }
+    }
+    if passes.len() <= 1 {
+        return process_changes(passes.pop().unwrap_or_default(), changes, suspect, parent);
+    }
+    passes
+        .into_iter()
+        .flat_map(|pass| process_changes(pass, changes.clone(), suspect, parent))
+        .collect()
+}
+
 impl UnblamedHunk {
     fn shift_by(mut self, suspect: ObjectId, offset: Offset) -> Self {
         self.suspects.entry(suspect).and_modify(|e| *e = e.shift_by(offset));
@@ -387,11 +419,13 @@ impl UnblamedHunk {
                         range_in_blamed_file: self.range_in_blamed_file.start
                             ..(self.range_in_blamed_file.start + split_at_from_start),
                         suspects: new_suspects_before.collect(),
+                        source_file_names: self.source_file_names.clone(),
                     };
                     let new_hunk_after = Self {
                         range_in_blamed_file: (self.range_in_blamed_file.start + split_at_from_start)
                             ..(self.range_in_blamed_file.end),
                         suspects: new_suspects_after.collect(),
+                        source_file_names: self.source_file_names,
                     };
 
                     Either::Right((new_hunk_before, new_hunk_after))
@@ -405,27 +439,62 @@ impl UnblamedHunk {
     /// This is like [`Self::pass_blame()`], but easier to use in places where the 'passing' is
     /// done 'inline'.
     fn passed_blame(mut self, from: ObjectId, to: ObjectId) -> Self {
-        if let Some(range_in_suspect) = self.suspects.remove(&from) {
-            self.suspects.insert(to, range_in_suspect);
-        }
+        self.pass_blame(from, to);
         self
     }
 
-    /// Transfer all ranges from the commit at `from` to the commit at `to`.
+    /// Transfer all ranges from the commit at `from` to the commit at `to`, along with the path of the source file.
     fn pass_blame(&mut self, from: ObjectId, to: ObjectId) {
         if let Some(range_in_suspect) = self.suspects.remove(&from) {
             self.suspects.insert(to, range_in_suspect);
+            match self.source_file_names.remove(&from) {
+                Some(name) => {
+                    self.source_file_names.insert(to, name);
+                }
+                None => {
+                    self.source_file_names.remove(&to);
+                }
+            }
         }
     }
 
     fn clone_blame(&mut self, from: ObjectId, to: ObjectId) {
         if let Some(range_in_suspect) = self.suspects.get(&from) {
             self.suspects.insert(to, range_in_suspect.clone());
+            match self.source_file_names.get(&from).cloned() {
+                Some(name) => {
+                    self.source_file_names.insert(to, name);
+                }
+                None => {
+                    self.source_file_names.remove(&to);
+                }
+            }
         }
     }
 
     fn remove_blame(&mut self, suspect: ObjectId) {
         self.suspects.remove(&suspect);
+        self.source_file_names.remove(&suspect);
+    }
+
+    /// Set the path of the source file in `suspect` to `name`, or `None` if it's the path of the *Blamed File*.
+    fn set_source_file_name(&mut self, suspect: ObjectId, name: Option<BString>) {
+        if !self.suspects.contains_key(&suspect) {
+            return;
+        }
+        match name {
+            Some(name) => {
+                self.source_file_names.insert(suspect, name);
+            }
+            None => {
+                self.source_file_names.remove(&suspect);
+            }
+        }
+    }
+
+    /// Return the path of the source file in `suspect` if it differs from the path of the *Blamed File*.
+    fn source_file_name(&self, suspect: ObjectId) -> Option<&BString> {
+        self.source_file_names.get(&suspect)
     }
 }
 
@@ -439,6 +508,8 @@ impl BlameEntry {
             start_in_source_file: range_in_source_file.start,
             len: force_non_zero(range_in_source_file.len() as u32),
             commit_id,
+            source_file_name: unblamed_hunk.source_file_name(commit_id).cloned(),
+            unblamable: false,
         })
     }
 }
@@ -12,6 +12,7 @@ fn new_unblamed_hunk(range_in_blamed_file: Range<u32>, suspect: ObjectId, offset
     UnblamedHunk {
         range_in_blamed_file,
         suspects: [(suspect, range_in_destination)].into(),
+        source_file_names: Default::default(),
     }
 }
 
@@ -72,7 +73,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 3..5,
-                suspects: [(suspect, 3..5)].into()
+                suspects: [(suspect, 3..5)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -80,7 +82,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 0..3,
-                suspects: [(suspect, 0..3)].into()
+                suspects: [(suspect, 0..3)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(3));
@@ -106,7 +109,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 3..5,
-                suspects: [(suspect, 3..5)].into()
+                suspects: [(suspect, 3..5)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -115,11 +119,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 0..2,
-                    suspects: [(parent, 0..2)].into()
+                    suspects: [(parent, 0..2)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 2..3,
-                    suspects: [(suspect, 2..3)].into()
+                    suspects: [(suspect, 2..3)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -146,7 +152,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 13..15,
-                suspects: [(suspect, 13..15)].into()
+                suspects: [(suspect, 13..15)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -155,11 +162,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 10..12,
-                    suspects: [(parent, 5..7)].into()
+                    suspects: [(parent, 5..7)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 12..13,
-                    suspects: [(suspect, 12..13)].into()
+                    suspects: [(suspect, 12..13)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -187,7 +196,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 15..17,
-                suspects: [(suspect, 10..12)].into()
+                suspects: [(suspect, 10..12)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -196,11 +206,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 12..14,
-                    suspects: [(parent, 7..9)].into()
+                    suspects: [(parent, 7..9)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 14..15,
-                    suspects: [(suspect, 9..10)].into()
+                    suspects: [(suspect, 9..10)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -227,7 +239,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 3..5,
-                suspects: [(suspect, 3..5)].into()
+                suspects: [(suspect, 3..5)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -235,7 +248,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 0..3,
-                suspects: [(suspect, 0..3)].into()
+                suspects: [(suspect, 0..3)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(2));
@@ -262,7 +276,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 4..5,
-                suspects: [(suspect, 3..4)].into()
+                suspects: [(suspect, 3..4)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -270,7 +285,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 1..4,
-                suspects: [(suspect, 0..3)].into()
+                suspects: [(suspect, 0..3)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(2));
@@ -297,7 +313,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 6..7,
-                suspects: [(suspect, 5..6)].into()
+                suspects: [(suspect, 5..6)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -306,11 +323,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 3..4,
-                    suspects: [(parent, 0..1)].into()
+                    suspects: [(parent, 0..1)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..6,
-                    suspects: [(suspect, 3..5)].into()
+                    suspects: [(suspect, 3..5)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -340,7 +359,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 23..24,
-                suspects: [(suspect, 25..26)].into()
+                suspects: [(suspect, 25..26)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(1));
@@ -369,7 +389,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 23..24,
-                suspects: [(suspect, 21..22)].into()
+                suspects: [(suspect, 21..22)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(1));
@@ -399,11 +420,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 71..107,
-                    suspects: [(parent, 70..106)].into()
+                    suspects: [(parent, 70..106)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 107..109,
-                    suspects: [(suspect, 106..108)].into()
+                    suspects: [(suspect, 106..108)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -434,11 +457,13 @@ mod process_change {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 149..155,
-                    suspects: [(parent, 137..143)].into()
+                    suspects: [(parent, 137..143)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 155..156,
-                    suspects: [(suspect, 143..144)].into()
+                    suspects: [(suspect, 143..144)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -468,7 +493,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 3..6,
-                suspects: [(parent, 5..8)].into()
+                suspects: [(parent, 5..8)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Deleted(3));
@@ -495,7 +521,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 9..11,
-                suspects: [(suspect, 6..8)].into()
+                suspects: [(suspect, 6..8)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -524,7 +551,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 4..15,
-                suspects: [(suspect, 5..16)].into()
+                suspects: [(suspect, 5..16)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -553,7 +581,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 23..25,
-                suspects: [(suspect, 25..27)].into()
+                suspects: [(suspect, 25..27)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -584,7 +613,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 15..16,
-                suspects: [(parent, 16..17)].into()
+                suspects: [(parent, 16..17)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(1));
@@ -611,7 +641,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 23..25,
-                suspects: [(suspect, 22..24)].into()
+                suspects: [(suspect, 22..24)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -642,7 +673,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 2..5,
-                suspects: [(suspect, 5..8)].into()
+                suspects: [(suspect, 5..8)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(3));
@@ -669,7 +701,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 14..19,
-                suspects: [(suspect, 15..20)].into()
+                suspects: [(suspect, 15..20)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -677,7 +710,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 12..14,
-                suspects: [(parent, 10..12)].into()
+                suspects: [(parent, 10..12)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(1));
@@ -706,7 +740,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 110..114,
-                suspects: [(parent, 106..110)].into()
+                suspects: [(parent, 106..110)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(3));
@@ -732,7 +767,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 0..5,
-                suspects: [(suspect, 0..5)].into()
+                suspects: [(suspect, 0..5)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -762,7 +798,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 0..5,
-                suspects: [(parent, 0..5)].into()
+                suspects: [(parent, 0..5)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(0));
@@ -783,6 +820,7 @@ mod process_change {
             Some(UnblamedHunk {
                 range_in_blamed_file: 22..30,
                 suspects: [(suspect, 21..29)].into(),
+                source_file_names: Default::default(),
             }),
             Some(Change::Unchanged(21..23)),
         );
@@ -791,7 +829,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 22..30,
-                suspects: [(suspect, 21..29)].into()
+                suspects: [(suspect, 21..29)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -821,7 +860,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 0..5,
-                suspects: [(parent, 0..5)].into()
+                suspects: [(parent, 0..5)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Added(0));
@@ -847,7 +887,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 2..16,
-                suspects: [(suspect, 2..16)].into()
+                suspects: [(suspect, 2..16)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -875,7 +916,8 @@ mod process_change {
             hunk,
             Some(UnblamedHunk {
                 range_in_blamed_file: 14..16,
-                suspects: [(suspect, 14..16)].into()
+                suspects: [(suspect, 14..16)].into(),
+                source_file_names: Default::default()
             })
         );
         assert_eq!(change, None);
@@ -883,7 +925,8 @@ mod process_change {
             new_hunks_to_blame,
             [UnblamedHunk {
                 range_in_blamed_file: 2..14,
-                suspects: [(parent, 2..14)].into()
+                suspects: [(parent, 2..14)].into(),
+                source_file_names: Default::default()
             }]
         );
         assert_eq!(offset_in_destination, Offset::Deleted(4));
@@ -982,6 +1025,7 @@ mod process_changes {
             [UnblamedHunk {
                 range_in_blamed_file: 0..4,
                 suspects: [(suspect, 0..4)].into(),
+                source_file_names: Default::default(),
             },]
         );
     }
@@ -1000,10 +1044,12 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..4,
                     suspects: [(suspect, 0..4)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..6,
                     suspects: [(parent, 0..2)].into(),
+                    source_file_names: Default::default(),
                 },
             ]
         );
@@ -1027,14 +1073,17 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..2,
                     suspects: [(parent, 0..2)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 2..4,
                     suspects: [(suspect, 2..4)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..6,
                     suspects: [(parent, 2..4)].into(),
+                    source_file_names: Default::default(),
                 },
             ]
         );
@@ -1058,14 +1107,17 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..1,
                     suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 1..4,
                     suspects: [(suspect, 1..4)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..6,
                     suspects: [(parent, 0..2)].into(),
+                    source_file_names: Default::default(),
                 }
             ]
         );
@@ -1085,10 +1137,12 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..1,
                     suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 1..6,
                     suspects: [(parent, 0..5)].into(),
+                    source_file_names: Default::default(),
                 }
             ]
         );
@@ -1108,10 +1162,12 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 2..3,
                     suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 3..6,
                     suspects: [(parent, 0..3)].into(),
+                    source_file_names: Default::default(),
                 }
             ]
         );
@@ -1131,10 +1187,12 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..4,
                     suspects: [(suspect, 0..4)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..6,
                     suspects: [(parent, 3..5)].into(),
+                    source_file_names: Default::default(),
                 }
             ]
         );
@@ -1153,6 +1211,7 @@ mod process_changes {
             [UnblamedHunk {
                 range_in_blamed_file: 4..6,
                 suspects: [(parent, 0..2)].into(),
+                source_file_names: Default::default(),
             }]
         );
     }
@@ -1171,10 +1230,12 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 1..2,
                     suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 2..3,
                     suspects: [(parent, 2..3)].into(),
+                    source_file_names: Default::default(),
                 }
             ]
         );
@@ -1198,14 +1259,17 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 0..2,
                     suspects: [(suspect, 0..2)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 2..3,
                     suspects: [(parent, 0..1)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 3..4,
                     suspects: [(suspect, 3..4)].into(),
+                    source_file_names: Default::default(),
                 },
             ]
         );
@@ -1219,10 +1283,12 @@ mod process_changes {
             UnblamedHunk {
                 range_in_blamed_file: 0..30,
                 suspects: [(suspect, 0..30)].into(),
+                source_file_names: Default::default(),
             },
             UnblamedHunk {
                 range_in_blamed_file: 31..37,
                 suspects: [(suspect, 31..37)].into(),
+                source_file_names: Default::default(),
             },
         ];
         let changes = vec![
@@ -1237,19 +1303,23 @@ mod process_changes {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 0..16,
-                    suspects: [(parent, 0..16)].into()
+                    suspects: [(parent, 0..16)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 16..17,
-                    suspects: [(suspect, 16..17)].into()
+                    suspects: [(suspect, 16..17)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 17..30,
-                    suspects: [(parent, 16..29)].into()
+                    suspects: [(parent, 16..29)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 31..37,
-                    suspects: [(parent, 30..36)].into()
+                    suspects: [(parent, 30..36)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
@@ -1263,14 +1333,17 @@ mod process_changes {
             UnblamedHunk {
                 range_in_blamed_file: 1..3,
                 suspects: [(suspect, 1..3)].into(),
+                source_file_names: Default::default(),
             },
             UnblamedHunk {
                 range_in_blamed_file: 5..7,
                 suspects: [(suspect, 5..7)].into(),
+                source_file_names: Default::default(),
             },
             UnblamedHunk {
                 range_in_blamed_file: 8..10,
                 suspects: [(suspect, 8..10)].into(),
+                source_file_names: Default::default(),
             },
         ];
         let changes = vec![
@@ -1286,22 +1359,27 @@ mod process_changes {
                 UnblamedHunk {
                     range_in_blamed_file: 1..3,
                     suspects: [(parent, 1..3)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 5..6,
                     suspects: [(parent, 5..6)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 6..7,
                     suspects: [(suspect, 6..7)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 8..9,
                     suspects: [(suspect, 8..9)].into(),
+                    source_file_names: Default::default(),
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 9..10,
                     suspects: [(parent, 6..7)].into(),
+                    source_file_names: Default::default(),
                 },
             ]
         );
@@ -1323,13 +1401,59 @@ mod process_changes {
             [
                 UnblamedHunk {
                     range_in_blamed_file: 0..4,
-                    suspects: [(suspect, 0..4)].into()
+                    suspects: [(suspect, 0..4)].into(),
+                    source_file_names: Default::default()
                 },
                 UnblamedHunk {
                     range_in_blamed_file: 4..7,
-                    suspects: [(parent, 3..6)].into()
+                    suspects: [(parent, 3..6)].into(),
+                    source_file_names: Default::default()
                 }
             ]
         );
     }
 }
+
+mod process_changes_of_overlapping_hunks {
+    use super::*;
+    use crate::file::{process_changes_of_overlapping_hunks, Change, UnblamedHunk};
+
+    #[test]
+    fn copied_lines_are_processed_separately() {
+        let suspect = zero_sha();
+        let parent = one_sha();
+        let hunks_to_blame = vec![
+            new_unblamed_hunk(0..2, suspect, Offset::Added(0)),
+            // The same lines of `suspect` were copied to the end of the *Blamed File*.
+            new_unblamed_hunk(4..6, suspect, Offset::Added(4)),
+        ];
+        let changes = vec![Change::AddedOrReplaced(0..1, 0), Change::Unchanged(1..4)];
+        let new_hunks_to_blame = process_changes_of_overlapping_hunks(hunks_to_blame, changes, suspect, parent);
+
+        assert_eq!(
+            new_hunks_to_blame,
+            [
+                UnblamedHunk {
+                    range_in_blamed_file: 0..1,
+                    suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
+                },
+                UnblamedHunk {
+                    range_in_blamed_file: 1..2,
+                    suspects: [(parent, 0..1)].into(),
+                    source_file_names: Default::default(),
+                },
+                UnblamedHunk {
+                    range_in_blamed_file: 4..5,
+                    suspects: [(suspect, 0..1)].into(),
+                    source_file_names: Default::default(),
+                },
+                UnblamedHunk {
+                    range_in_blamed_file: 5..6,
+                    suspects: [(parent, 0..1)].into(),
+                    source_file_names: Default::default(),
+                },
+            ]
+        );
+    }
+}
@@ -17,7 +17,10 @@
 mod error;
 pub use error::Error;
 mod types;
-pub use types::{BlameEntry, Options, Outcome, Statistics};
+pub use types::{BlameEntry, MovedLines, MovedLinesSource, Options, Outcome, Statistics};
+
+///
+pub mod ignore_revs;
 
 mod file;
-pub use file::function::file;
+pub use file::function::{file, incremental, Incremental};
@@ -3,7 +3,7 @@ use gix_hash::ObjectId;
 use gix_object::bstr::BString;
 use std::num::NonZeroU32;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ops::{AddAssign, Range, SubAssign},
 };
 
@@ -18,6 +18,53 @@ pub struct Options {
     pub range: Option<std::ops::Range<u32>>,
     /// Don't consider commits before the given date.
     pub since: Option<gix_date::Time>,
+    /// If `Some(..)`, follow the *Blamed File* across renames (and copies, if enabled), similar to what `git blame` does.
+    /// Otherwise, the blame stops at the commit that added the file under its current name.
+    pub rewrites: Option<gix_diff::Rewrites>,
+    /// If `Some(..)`, lines that would be blamed on a commit are looked up in other files or other places of the same
+    /// file in its parent to find their actual origin, similar to `git blame -M` and `git blame -C`.
+    pub moved_lines: Option<MovedLines>,
+    /// Commits whose changes are ignored, as if they never happened, similar to `git blame --ignore-rev`.
+    ///
+    /// Lines changed by these commits are blamed on a similar line in their parent, if there is one,
+    /// or are [marked as unblamable](BlameEntry::unblamable) otherwise.
+    pub ignore_revs: BTreeSet<ObjectId>,
+    /// Changes in whitespace to ignore when comparing the versions of a file, similar to `git blame -w`.
+    pub whitespace: gix_diff::blob::whitespace::Options,
+}
+
+/// Determine where to look for the origin of lines that were moved or copied, similar to `-M` and `-C` of `git blame`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovedLinesSource {
+    /// Look for lines that were moved or copied within the same file, like `git blame -M`.
+    #[default]
+    SameFile,
+    /// Additionally look for lines that were moved or copied from files changed in the same commit, like `git blame -C`.
+    ChangedFiles,
+    /// Additionally look for lines that were moved or copied from any file in the parent commit, like `git blame -C -C -C`.
+    ///
+    /// Note that this is very costly.
+    AnyFile,
+}
+
+/// Options to control how lines that were moved or copied are detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovedLines {
+    /// Where to look for the origin of lines.
+    pub source: MovedLinesSource,
+    /// The minimal amount of alphanumeric characters in a group of lines for it to be considered moved or copied.
+    ///
+    /// This is the equivalent of the optional number passed to `git blame -M` and `-C`, defaulting to 20 as well.
+    pub min_alnum_chars: u32,
+}
+
+impl Default for MovedLines {
+    fn default() -> Self {
+        MovedLines {
+            source: Default::default(),
+            min_alnum_chars: 20,
+        }
+    }
 }
 
 /// The outcome of [`file()`](crate::file()).
@@ -146,11 +193,23 @@ pub struct BlameEntry {
     pub len: NonZeroU32,
     /// The commit that introduced the section into the *Source File*.
     pub commit_id: ObjectId,
+    /// The path to the *Source File* in `commit_id`, or `None` if it is the same as the path of the *Blamed File*.
+    ///
+    /// It differs if the file was renamed, or if lines were moved or copied from another file.
+    pub source_file_name: Option<BString>,
+    /// If `true`, `commit_id` is one of the [ignored commits](Options::ignore_revs), and the lines couldn't be matched
+    /// to lines in one of its parents. `git blame` marks them with `*` if `blame.markUnblamableLines` is set.
+    pub unblamable: bool,
 }
 
 impl BlameEntry {
     /// Create a new instance.
-    pub fn new(range_in_blamed_file: Range<u32>, range_in_source_file: Range<u32>, commit_id: ObjectId) -> Self {
+    pub fn new(
+        range_in_blamed_file: Range<u32>,
+        range_in_source_file: Range<u32>,
+        commit_id: ObjectId,
+        source_file_name: Option<BString>,
+    ) -> Self {
         debug_assert!(
             range_in_blamed_file.end > range_in_blamed_file.start,
             "{range_in_blamed_file:?}"
@@ -166,6 +225,8 @@ impl BlameEntry {
             start_in_source_file: range_in_source_file.start,
             len: NonZeroU32::new(range_in_blamed_file.len() as u32).expect("BUG: hunks are never empty"),
             commit_id,
+            source_file_name,
+            unblamable: false,
         }
     }
 }
@@ -200,6 +261,8 @@ pub struct UnblamedHunk {
     pub range_in_blamed_file: Range<u32>,
     /// Maps a commit to the range in a source file (i.e. *Blamed File* at a revision) that is equal to `range_in_blamed_file`.
     pub suspects: BTreeMap<ObjectId, Range<u32>>,
+    /// Maps a commit in `suspects` to the path of its source file, if it differs from the path of the *Blamed File*.
+    pub source_file_names: BTreeMap<ObjectId, BString>,
 }
 
 #[derive(Debug)]
@@ -209,7 +272,7 @@ pub(crate) enum Either<T, U> {
 }
 
 /// A single change between two blobs, or an unchanged region.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Change {
     /// A range of tokens that wasn't changed.
     Unchanged(Range<u32>),
@@ -1,10 +1,12 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use gix_hash::ObjectId;
 use gix_object::bstr;
 
 struct Baseline<'a> {
     lines: bstr::Lines<'a>,
+    filenames: BTreeMap<ObjectId, bstr::BString>,
+    blamed_file_path: &'a str,
 }
 
 mod baseline {
@@ -39,10 +41,15 @@ mod baseline {
     }
 
     impl Baseline<'_> {
-        pub fn collect(baseline_path: impl AsRef<Path>) -> std::io::Result<Vec<BlameEntry>> {
+        pub fn collect(baseline_path: impl AsRef<Path>, blamed_file_path: &str) -> std::io::Result<Vec<BlameEntry>> {
             let content = std::fs::read(baseline_path)?;
 
-            Ok(Baseline { lines: content.lines() }.collect())
+            Ok(Baseline {
+                lines: content.lines(),
+                filenames: Default::default(),
+                blamed_file_path,
+            }
+            .collect())
         }
     }
 
@@ -93,6 +100,9 @@ mod baseline {
                         (line_number_in_final_file - 1)..(line_number_in_final_file + number_of_lines_in_group - 1);
                     assert!(ranges.is_none(), "should not overwrite existing ranges");
                     ranges = Some((blame_range, source_range));
+                } else if fields[0] == "filename" {
+                    // The filename is only repeated if it changes for a commit.
+                    self.filenames.insert(commit_id, line["filename ".len()..].into());
                 } else if !is_known_header_field(&fields[0]) && ObjectId::from_hex(fields[0].as_bytes()).is_err() {
                     panic!("unexpected line: '{:?}'", line.as_bstr());
                 }
@@ -102,7 +112,17 @@ mod baseline {
                 // No new lines were parsed, so we assume the iterator is finished.
                 return None;
             };
-            Some(BlameEntry::new(range_in_blamed_file, range_in_source_file, commit_id))
+            let source_file_name = self
+                .filenames
+                .get(&commit_id)
+                .filter(|name| *name != self.blamed_file_path)
+                .cloned();
+            Some(BlameEntry::new(
+                range_in_blamed_file,
+                range_in_source_file,
+                commit_id,
+                source_file_name,
+            ))
         }
     }
 }
@@ -195,6 +215,10 @@ macro_rules! mktest {
                     diff_algorithm: gix_diff::blob::Algorithm::Histogram,
                     range: None,
                     since: None,
+                    rewrites: Some(gix_diff::Rewrites::default()),
+                    moved_lines: None,
+                    ignore_revs: Default::default(),
+                    whitespace: Default::default(),
                 },
             )?
             .entries;
@@ -202,7 +226,10 @@ macro_rules! mktest {
             assert_eq!(lines_blamed.len(), $number_of_lines);
 
             let git_dir = fixture_path().join(".git");
-            let baseline = Baseline::collect(git_dir.join(format!("{}.baseline", $case)))?;
+            let baseline = Baseline::collect(
+                git_dir.join(format!("{}.baseline", $case)),
+                format!("{}.txt", $case).as_str(),
+            )?;
 
             assert_eq!(baseline.len(), $number_of_lines);
             assert_eq!(lines_blamed, baseline);
@@ -243,11 +270,320 @@ mktest!(
     3
 );
 
-/// As of 2024-09-24, these tests are expected to fail.
+mktest!(after_rename, "after-rename", 4);
+
+#[test]
+fn renames_are_not_followed_without_rewrites() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let lines_blamed = gix_blame::file(
+        &odb,
+        suspect,
+        None,
+        &mut resource_cache,
+        "after-rename.txt".into(),
+        gix_blame::Options {
+            diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+            range: None,
+            since: None,
+            rewrites: None,
+            moved_lines: None,
+            ignore_revs: Default::default(),
+            whitespace: Default::default(),
+        },
+    )?
+    .entries;
+
+    assert_eq!(
+        lines_blamed.len(),
+        2,
+        "everything but the first line is blamed on the rename"
+    );
+    assert!(lines_blamed.iter().all(|entry| entry.source_file_name.is_none()));
+    Ok(())
+}
+
+#[test]
+fn moved_lines_within_file() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let lines_blamed = gix_blame::file(
+        &odb,
+        suspect,
+        None,
+        &mut resource_cache,
+        "moved-lines.txt".into(),
+        gix_blame::Options {
+            diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+            range: None,
+            since: None,
+            rewrites: Some(gix_diff::Rewrites::default()),
+            moved_lines: Some(gix_blame::MovedLines::default()),
+            ignore_revs: Default::default(),
+            whitespace: Default::default(),
+        },
+    )?
+    .entries;
+
+    let git_dir = fixture_path().join(".git");
+    let baseline = Baseline::collect(git_dir.join("moved-lines-M.baseline"), "moved-lines.txt")?;
+    assert_eq!(lines_blamed, baseline);
+    Ok(())
+}
+
+#[test]
+fn copied_lines_from_changed_files() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let options = |source| gix_blame::Options {
+        diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+        range: None,
+        since: None,
+        rewrites: Some(gix_diff::Rewrites::default()),
+        moved_lines: Some(gix_blame::MovedLines {
+            source,
+            ..Default::default()
+        }),
+        ignore_revs: Default::default(),
+        whitespace: Default::default(),
+    };
+    let git_dir = fixture_path().join(".git");
+    let baseline = Baseline::collect(git_dir.join("copied-lines-C.baseline"), "copied-lines.txt")?;
+    assert_eq!(
+        baseline[1].source_file_name.as_ref().expect("copied"),
+        "source-of-copy.txt"
+    );
+
+    for source in [
+        gix_blame::MovedLinesSource::ChangedFiles,
+        gix_blame::MovedLinesSource::AnyFile,
+    ] {
+        let lines_blamed = gix_blame::file(
+            &odb,
+            suspect,
+            None,
+            &mut resource_cache,
+            "copied-lines.txt".into(),
+            options(source),
+        )?
+        .entries;
+        assert_eq!(lines_blamed, baseline, "{source:?}");
+    }
+
+    let lines_blamed = gix_blame::file(
+        &odb,
+        suspect,
+        None,
+        &mut resource_cache,
+        "copied-lines.txt".into(),
+        options(gix_blame::MovedLinesSource::SameFile),
+    )?
+    .entries;
+    assert_eq!(
+        lines_blamed.len(),
+        1,
+        "without looking at other files, the whole file is new"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignored_revs() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let git_dir = fixture_path().join(".git");
+    let ignore_revs = gix_blame::ignore_revs::parse(&std::fs::read(git_dir.join("ignored-revs"))?)?;
+    assert_eq!(ignore_revs.len(), 1, "comments and empty lines are skipped");
+
+    let mut lines_blamed = gix_blame::file(
+        &odb,
+        suspect,
+        None,
+        &mut resource_cache,
+        "ignored-revs.txt".into(),
+        gix_blame::Options {
+            diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+            range: None,
+            since: None,
+            rewrites: Some(gix_diff::Rewrites::default()),
+            moved_lines: None,
+            ignore_revs: ignore_revs.clone(),
+            whitespace: Default::default(),
+        },
+    )?
+    .entries;
+
+    // Lines marked with `*` by `git` couldn't be blamed on a commit other than the ignored one.
+    let marked = std::fs::read_to_string(git_dir.join("ignored-revs-marked.baseline"))?;
+    let unblamable_lines: Vec<_> = marked
+        .lines()
+        .enumerate()
+        .filter_map(|(line, content)| content.starts_with('*').then_some(line))
+        .collect();
+    assert_eq!(unblamable_lines, [1]);
+    let actual_unblamable_lines: Vec<_> = lines_blamed
+        .iter()
+        .filter(|entry| entry.unblamable)
+        .flat_map(gix_blame::BlameEntry::range_in_blamed_file)
+        .collect();
+    assert_eq!(actual_unblamable_lines, unblamable_lines);
+    assert!(lines_blamed
+        .iter()
+        .all(|entry| entry.unblamable == ignore_revs.contains(&entry.commit_id)));
+
+    for entry in &mut lines_blamed {
+        entry.unblamable = false;
+    }
+    let baseline = Baseline::collect(git_dir.join("ignored-revs.baseline"), "ignored-revs.txt")?;
+    assert_eq!(lines_blamed, baseline);
+    Ok(())
+}
+
+#[test]
+fn ignore_whitespace() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let git_dir = fixture_path().join(".git");
+    for (whitespace, baseline) in [
+        (gix_diff::blob::whitespace::Options::default(), "whitespace.baseline"),
+        (
+            gix_diff::blob::whitespace::Options {
+                ignore_all_space: true,
+                ..Default::default()
+            },
+            "whitespace-ignore-all-space.baseline",
+        ),
+    ] {
+        let lines_blamed = gix_blame::file(
+            &odb,
+            suspect,
+            None,
+            &mut resource_cache,
+            "whitespace.txt".into(),
+            gix_blame::Options {
+                diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+                range: None,
+                since: None,
+                rewrites: Some(gix_diff::Rewrites::default()),
+                moved_lines: None,
+                ignore_revs: Default::default(),
+                whitespace,
+            },
+        )?
+        .entries;
+
+        let baseline = Baseline::collect(git_dir.join(baseline), "whitespace.txt")?;
+        assert_eq!(lines_blamed, baseline, "{whitespace:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn incremental_blame_yields_the_same_lines_as_blaming_the_whole_file() -> gix_testtools::Result {
+    let Fixture {
+        odb,
+        mut resource_cache,
+        suspect,
+    } = Fixture::new()?;
+
+    let options = gix_blame::Options {
+        diff_algorithm: gix_diff::blob::Algorithm::Histogram,
+        range: None,
+        since: None,
+        rewrites: Some(gix_diff::Rewrites::default()),
+        moved_lines: None,
+        ignore_revs: Default::default(),
+        whitespace: Default::default(),
+    };
+    let commit_per_line = |entries: &[gix_blame::BlameEntry]| -> BTreeMap<usize, (ObjectId, usize)> {
+        entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .range_in_blamed_file()
+                    .zip(entry.range_in_source_file())
+                    .map(|(line, source_line)| (line, (entry.commit_id, source_line)))
+            })
+            .collect()
+    };
+    for case in ["simple", "after-rename", "file-changed-in-two-branches"] {
+        let file_path = format!("{case}.txt");
+        let expected = gix_blame::file(
+            &odb,
+            suspect,
+            None,
+            &mut resource_cache,
+            file_path.as_str().into(),
+            options.clone(),
+        )?;
+
+        let should_interrupt = std::sync::atomic::AtomicBool::new(true);
+        let mut blame = gix_blame::incremental(
+            &odb,
+            suspect,
+            None,
+            &mut resource_cache,
+            file_path.as_str().into(),
+            options.clone(),
+            &should_interrupt,
+        )?;
+        assert_eq!(blame.blob(), expected.blob);
+        assert!(
+            matches!(blame.next(), Some(Err(gix_blame::Error::Interrupted))),
+            "interruptions are checked before each commit"
+        );
+        assert!(!blame.is_done());
+
+        should_interrupt.store(false, std::sync::atomic::Ordering::Relaxed);
+        let entries = blame.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert!(blame.is_done(), "it can be resumed after an interruption");
+        assert_eq!(
+            commit_per_line(&entries),
+            commit_per_line(&expected.entries),
+            "{case}: only the order and the coalescing of entries differ"
+        );
+        assert_eq!(
+            commit_per_line(&entries).len(),
+            entries.iter().map(|entry| entry.len.get() as usize).sum::<usize>(),
+            "{case}: each line is yielded once"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid_ignore_revs_file() {
+    let err = gix_blame::ignore_revs::parse(b"# comment\n\nabc\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 3 doesn't contain a full hexadecimal object id: 'abc'"
+    );
+}
+
+/// These used to differ from `git` until groups of changed lines were placed like `git` does it.
 ///
 /// Context: https://github.com/Byron/gitoxide/pull/1453#issuecomment-2371013904
 #[test]
-#[should_panic = "empty-lines-myers"]
 fn diff_disparity() {
     for case in ["empty-lines-myers", "empty-lines-histogram"] {
         let Fixture {
@@ -266,6 +602,10 @@ fn diff_disparity() {
                 diff_algorithm: gix_diff::blob::Algorithm::Histogram,
                 range: None,
                 since: None,
+                rewrites: Some(gix_diff::Rewrites::default()),
+                moved_lines: None,
+                ignore_revs: Default::default(),
+                whitespace: Default::default(),
             },
         )
         .unwrap()
@@ -274,7 +614,7 @@ fn diff_disparity() {
         assert_eq!(lines_blamed.len(), 5);
 
         let git_dir = fixture_path().join(".git");
-        let baseline = Baseline::collect(git_dir.join(format!("{case}.baseline"))).unwrap();
+        let baseline = Baseline::collect(git_dir.join(format!("{case}.baseline")), &format!("{case}.txt")).unwrap();
 
         assert_eq!(lines_blamed, baseline, "{case}");
     }
@@ -298,6 +638,10 @@ fn line_range() {
             diff_algorithm: gix_diff::blob::Algorithm::Histogram,
             range: Some(1..2),
             since: None,
+            rewrites: None,
+            moved_lines: None,
+            ignore_revs: Default::default(),
+            whitespace: Default::default(),
         },
     )
     .unwrap()
@@ -306,7 +650,7 @@ fn line_range() {
     assert_eq!(lines_blamed.len(), 2);
 
     let git_dir = fixture_path().join(".git");
-    let baseline = Baseline::collect(git_dir.join("simple-lines-1-2.baseline")).unwrap();
+    let baseline = Baseline::collect(git_dir.join("simple-lines-1-2.baseline"), "simple.txt").unwrap();
 
     assert_eq!(lines_blamed, baseline);
 }
@@ -329,6 +673,10 @@ fn since() {
             diff_algorithm: gix_diff::blob::Algorithm::Histogram,
             range: None,
             since: Some(gix_date::parse("2025-01-31", None).unwrap()),
+            rewrites: None,
+            moved_lines: None,
+            ignore_revs: Default::default(),
+            whitespace: Default::default(),
         },
     )
     .unwrap()
@@ -337,7 +685,7 @@ fn since() {
     assert_eq!(lines_blamed.len(), 1);
 
     let git_dir = fixture_path().join(".git");
-    let baseline = Baseline::collect(git_dir.join("simple-since.baseline")).unwrap();
+    let baseline = Baseline::collect(git_dir.join("simple-since.baseline"), "simple.txt").unwrap();
 
     assert_eq!(lines_blamed, baseline);
 }
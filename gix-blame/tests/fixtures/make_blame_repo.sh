@@ -225,6 +225,55 @@ git commit -q -m c15.2
 
 git merge branch-that-has-earlier-commit || true
 
+echo -e "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8" > before-rename.txt
+git add before-rename.txt
+git commit -q -m c16
+
+git mv before-rename.txt after-rename.txt
+echo -e "line 1\nline 2 changed during rename\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8" > after-rename.txt
+git add after-rename.txt
+git commit -q -m c16.1
+
+echo -e "line 0\nline 1\nline 2 changed during rename\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8" > after-rename.txt
+git add after-rename.txt
+git commit -q -m c16.2
+
+echo -e "first line of the block that will move\nsecond line of the block that will move\nunrelated 1\nunrelated 2\nunrelated 3" > moved-lines.txt
+echo -e "this is the origin of the copied lines\nand this is the second copied line\nthis line stays here" > source-of-copy.txt
+git add moved-lines.txt source-of-copy.txt
+git commit -q -m c17
+
+echo -e "unrelated 1\nunrelated 2\nunrelated 3\nfirst line of the block that will move\nsecond line of the block that will move" > moved-lines.txt
+echo -e "a new line before the copy\nthis is the origin of the copied lines\nand this is the second copied line\na new line after the copy" > copied-lines.txt
+echo -e "this line stays here" > source-of-copy.txt
+git add moved-lines.txt copied-lines.txt source-of-copy.txt
+git commit -q -m c17.1
+
+echo -e "fn main() {\n    let x = compute(1,2);\n    println!(\"{}\",x);\n}" > ignored-revs.txt
+git add ignored-revs.txt
+git commit -q -m c18
+
+echo -e "fn main() {\n    let x = compute(1,2);\n    println!(\"result: {}\",x);\n}" > ignored-revs.txt
+git add ignored-revs.txt
+git commit -q -m c18.1
+
+echo -e "fn main() {\n    // reformatted by a tool\n    let x = compute(1, 2);\n    println!(\"result: {}\", x);\n}" > ignored-revs.txt
+git add ignored-revs.txt
+git commit -q -m c18.2
+echo -e "# reformatting\n$(git rev-parse HEAD)  # c18.2\n" > .git/ignored-revs
+
+echo -e "fn main() {\n    // reformatted by a tool\n    let x = compute(1, 2);\n    println!(\"result: {}\", x);\n    x\n}" > ignored-revs.txt
+git add ignored-revs.txt
+git commit -q -m c18.3
+
+echo -e "fn main() {\n    let a = compute(1, 2);\n    let b = a + 1;\n    b\n}" > whitespace.txt
+git add whitespace.txt
+git commit -q -m c19
+
+echo -e "fn main() {\n\tlet a = compute(1,  2);\n    let b = a + 2;   \n\n    b\n}" > whitespace.txt
+git add whitespace.txt
+git commit -q -m c19.1
+
 git blame --porcelain simple.txt > .git/simple.baseline
 git blame --porcelain -L 1,2 simple.txt > .git/simple-lines-1-2.baseline
 git blame --porcelain --since 2025-01-31 simple.txt > .git/simple-since.baseline
@@ -247,6 +296,14 @@ git blame --porcelain file-only-changed-in-branch.txt > .git/file-only-changed-i
 git blame --porcelain file-changed-in-two-branches.txt > .git/file-changed-in-two-branches.baseline
 git blame --porcelain file-topo-order-different-than-date-order.txt > .git/file-topo-order-different-than-date-order.baseline
 
+git blame --porcelain after-rename.txt > .git/after-rename.baseline
+git blame --porcelain -M moved-lines.txt > .git/moved-lines-M.baseline
+git blame --porcelain -C copied-lines.txt > .git/copied-lines-C.baseline
+git blame --porcelain --ignore-revs-file .git/ignored-revs ignored-revs.txt > .git/ignored-revs.baseline
+git -c blame.markUnblamableLines=true blame -s --ignore-revs-file .git/ignored-revs ignored-revs.txt > .git/ignored-revs-marked.baseline
+git blame --porcelain whitespace.txt > .git/whitespace.baseline
+git blame --porcelain -w whitespace.txt > .git/whitespace-ignore-all-space.baseline
+
 git blame --porcelain empty-lines-histogram.txt > .git/empty-lines-histogram.baseline
 
 git config --local diff.algorithm myers
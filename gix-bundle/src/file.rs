@@ -0,0 +1,165 @@
+use std::{
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_features::progress::DynNestedProgress;
+
+use crate::{File, Header, Version};
+
+///
+pub mod open {
+    use std::path::PathBuf;
+
+    /// The error returned by [`File::at()`](crate::File::at()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open bundle at '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error("Could not decode header of bundle at '{}'", path.display())]
+        Header {
+            path: PathBuf,
+            source: crate::header::decode::Error,
+        },
+    }
+}
+
+///
+pub mod write_pack {
+    /// The error returned by [`File::write_pack_to_directory()`](crate::File::write_pack_to_directory()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open the pack of the bundle")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::bundle::write::Error),
+    }
+}
+
+///
+pub mod verify {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [`File::verify()`](crate::File::verify()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The repository lacks {} prerequisite commit(s) of the bundle, like {}", missing.len(), missing[0])]
+        MissingPrerequisites { missing: Vec<ObjectId> },
+        #[error("The pack of the bundle is invalid")]
+        Pack(#[from] super::write_pack::Error),
+    }
+}
+
+/// Return `true` if `path` is a file that starts with the signature of a bundle.
+pub fn is_bundle(path: &Path) -> bool {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut buf = [0u8; 16];
+    file.read_exact(&mut buf).is_ok() && [Version::V2, Version::V3].iter().any(|v| v.signature() == buf)
+}
+
+/// Initialization
+impl File {
+    /// Open the bundle at `path` and decode its header.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, open::Error> {
+        let path = path.into();
+        let io_err = |source| open::Error::Io {
+            path: path.clone(),
+            source,
+        };
+        let mut read = BufReader::new(std::fs::File::open(&path).map_err(io_err)?);
+        let header = Header::from_read(&mut read).map_err(|source| open::Error::Header {
+            path: path.clone(),
+            source,
+        })?;
+        let pack_offset = read.stream_position().map_err(io_err)?;
+        Ok(File {
+            header,
+            path,
+            pack_offset,
+        })
+    }
+}
+
+/// Access
+impl File {
+    /// The path from which the bundle was opened.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The offset in bytes at which the pack starts.
+    pub fn pack_offset(&self) -> u64 {
+        self.pack_offset
+    }
+
+    /// Open the bundle and return a reader positioned at the first byte of its pack.
+    pub fn pack_reader(&self) -> std::io::Result<BufReader<std::fs::File>> {
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.pack_offset))?;
+        Ok(BufReader::new(file))
+    }
+}
+
+/// Operations
+impl File {
+    /// Write the pack of this bundle along with a generated index into `directory`, or only validate it if `None`,
+    /// similar to [`gix_pack::Bundle::write_to_directory()`], whose parameters are passed through.
+    ///
+    /// As the pack may be *thin*, `thin_pack_base_object_lookup` should be set to the object database that contains
+    /// the [prerequisites](crate::Header::prerequisites).
+    pub fn write_pack_to_directory(
+        &self,
+        directory: Option<&Path>,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        thin_pack_base_object_lookup: Option<impl gix_object::Find>,
+        options: gix_pack::bundle::write::Options,
+    ) -> Result<gix_pack::bundle::write::Outcome, write_pack::Error> {
+        let mut pack = self.pack_reader()?;
+        Ok(gix_pack::Bundle::write_to_directory(
+            &mut pack,
+            directory,
+            progress,
+            should_interrupt,
+            thin_pack_base_object_lookup,
+            options,
+        )?)
+    }
+
+    /// Assure that all [prerequisites](crate::Header::prerequisites) exist in `objects`, and that the pack of this bundle is valid,
+    /// similar to `git bundle verify`.
+    ///
+    /// Note that the pack is fully decoded, with deltas resolved against `objects` if it is *thin*.
+    pub fn verify<Find>(
+        &self,
+        objects: Find,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_pack::bundle::write::Outcome, verify::Error>
+    where
+        Find: gix_object::Find + gix_object::Exists,
+    {
+        let missing = self.header.missing_prerequisites(&objects);
+        if !missing.is_empty() {
+            return Err(verify::Error::MissingPrerequisites { missing });
+        }
+        Ok(self.write_pack_to_directory(
+            None,
+            progress,
+            should_interrupt,
+            Some(objects),
+            gix_pack::bundle::write::Options {
+                thread_limit: None,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: self.header.object_hash,
+            },
+        )?)
+    }
+}
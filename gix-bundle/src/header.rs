@@ -0,0 +1,201 @@
+use std::io::{BufRead, Write};
+
+use bstr::{BStr, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Header, Prerequisite, Ref, Version};
+
+///
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [`Header::from_read()`](crate::Header::from_read()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the bundle header")]
+        Io(#[from] std::io::Error),
+        #[error("Expected a bundle signature like '# v2 git bundle', got {line:?}")]
+        Signature { line: BString },
+        #[error("Unknown capability {name:?} - the bundle can't be read safely")]
+        UnknownCapability { name: BString },
+        #[error("Capability {line:?} must appear before all prerequisites and references, and only in v3 bundles")]
+        UnexpectedCapability { line: BString },
+        #[error("Object format {value:?} is not supported")]
+        UnsupportedObjectFormat { value: BString },
+        #[error("Capability 'filter' needs a value")]
+        MissingFilterValue,
+        #[error("Line {line:?} is neither a prerequisite nor a reference")]
+        InvalidLine { line: BString },
+        #[error("The bundle header ended unexpectedly, it must be terminated with an empty line")]
+        UnexpectedEof,
+    }
+}
+
+/// Initialization
+impl Header {
+    /// Create a new header without prerequisites and references for objects hashed with `object_hash` in a pack that was
+    /// created with `filter`, choosing the oldest [`Version`] which is able to represent it.
+    pub fn new(object_hash: gix_hash::Kind, filter: Option<bstr::BString>) -> Self {
+        let version = if object_hash == gix_hash::Kind::Sha1 && filter.is_none() {
+            Version::V2
+        } else {
+            Version::V3
+        };
+        Header {
+            version,
+            object_hash,
+            filter,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        }
+    }
+
+    /// Decode a header from `read`, which is positioned at the first byte of the pack afterwards.
+    pub fn from_read(read: &mut dyn BufRead) -> Result<Self, decode::Error> {
+        let mut buf = Vec::new();
+        let mut read_line = |buf: &mut Vec<u8>| -> Result<bool, decode::Error> {
+            buf.clear();
+            if read.read_until(b'\n', buf)? == 0 {
+                return Ok(false);
+            }
+            if buf.last() == Some(&b'\n') {
+                buf.pop();
+            }
+            Ok(true)
+        };
+
+        if !read_line(&mut buf)? {
+            return Err(decode::Error::UnexpectedEof);
+        }
+        let version = match buf.as_slice() {
+            b"# v2 git bundle" => Version::V2,
+            b"# v3 git bundle" => Version::V3,
+            _ => return Err(decode::Error::Signature { line: buf.into() }),
+        };
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        let mut may_have_capabilities = version == Version::V3;
+        loop {
+            if !read_line(&mut buf)? {
+                return Err(decode::Error::UnexpectedEof);
+            }
+            if buf.is_empty() {
+                break;
+            }
+            if let Some(capability) = buf.strip_prefix(b"@") {
+                if !may_have_capabilities {
+                    return Err(decode::Error::UnexpectedCapability { line: buf.into() });
+                }
+                header.set_capability(capability.as_bstr())?;
+                continue;
+            }
+            may_have_capabilities = false;
+
+            let invalid_line = |line: &[u8]| decode::Error::InvalidLine { line: line.into() };
+            match buf.strip_prefix(b"-") {
+                Some(prerequisite) => {
+                    let (hex, comment) = prerequisite.split_once_str(b" ").unwrap_or((prerequisite, b""));
+                    header.prerequisites.push(Prerequisite {
+                        id: parse_id(hex, header.object_hash).ok_or_else(|| invalid_line(&buf))?,
+                        comment: comment.into(),
+                    });
+                }
+                None => {
+                    let (hex, name) = buf.split_once_str(b" ").ok_or_else(|| invalid_line(&buf))?;
+                    if name.is_empty() {
+                        return Err(invalid_line(&buf));
+                    }
+                    header.refs.push(Ref {
+                        id: parse_id(hex, header.object_hash).ok_or_else(|| invalid_line(&buf))?,
+                        name: name.into(),
+                    });
+                }
+            }
+        }
+        Ok(header)
+    }
+
+    fn set_capability(&mut self, capability: &BStr) -> Result<(), decode::Error> {
+        let (name, value) = match capability.split_once_str(b"=") {
+            Some((name, value)) => (name.as_bstr(), Some(value.as_bstr())),
+            None => (capability, None),
+        };
+        match name.as_bytes() {
+            b"object-format" => {
+                self.object_hash = value
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| decode::Error::UnsupportedObjectFormat {
+                        value: value.map(ToOwned::to_owned).unwrap_or_default(),
+                    })?;
+            }
+            b"filter" => self.filter = Some(value.ok_or(decode::Error::MissingFilterValue)?.to_owned()),
+            _ => return Err(decode::Error::UnknownCapability { name: name.to_owned() }),
+        }
+        Ok(())
+    }
+}
+
+fn parse_id(hex: &[u8], object_hash: gix_hash::Kind) -> Option<ObjectId> {
+    if hex.len() != object_hash.len_in_hex() {
+        return None;
+    }
+    ObjectId::from_hex(hex).ok()
+}
+
+/// Access and serialization
+impl Header {
+    /// Return the ids of all prerequisites which don't exist in `objects` and thus prevent the pack of the bundle
+    /// from being received.
+    pub fn missing_prerequisites(&self, objects: &dyn gix_object::Exists) -> Vec<ObjectId> {
+        self.prerequisites
+            .iter()
+            .filter(|prerequisite| !objects.exists(&prerequisite.id))
+            .map(|prerequisite| prerequisite.id)
+            .collect()
+    }
+
+    /// Find the reference with the given full `name`.
+    pub fn find_ref(&self, name: &BStr) -> Option<&Ref> {
+        self.refs.iter().find(|r| r.name == name)
+    }
+
+    /// Write this header to `out`, which can be followed by the pack to produce a complete bundle.
+    ///
+    /// Note that capabilities are only written in [v3](Version::V3) bundles.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        out.write_all(self.version.signature())?;
+        if self.version == Version::V3 {
+            writeln!(
+                out,
+                "@object-format={}",
+                self.object_hash.to_string().to_ascii_lowercase()
+            )?;
+            if let Some(filter) = &self.filter {
+                out.write_all(b"@filter=")?;
+                out.write_all(filter)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        for prerequisite in &self.prerequisites {
+            write!(out, "-{}", prerequisite.id)?;
+            if !prerequisite.comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(&prerequisite.comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for r in &self.refs {
+            write!(out, "{} ", r.id)?;
+            out.write_all(&r.name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")
+    }
+}
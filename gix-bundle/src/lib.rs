@@ -0,0 +1,83 @@
+//! Read and write [git bundles](https://git-scm.com/docs/gitformat-bundle), files which contain a pack along with
+//! the references it provides and the commits it requires to be present in the receiving repository.
+//!
+//! A bundle starts with a [`Header`] that lists the *prerequisites* and *references*, which is followed by the pack
+//! in the same format as it would be sent by a server. Use [`File`] to open a bundle on disk, to verify that a repository
+//! can receive it, and to write its pack into an object database.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use std::path::PathBuf;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The version of the bundle format.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Version {
+    /// The original format which only supports SHA-1 and doesn't have capabilities.
+    #[default]
+    V2,
+    /// The format which declares capabilities, like the `object-format` and the `filter` used to create the pack.
+    V3,
+}
+
+impl Version {
+    /// Return the line that starts a bundle of this version, including the trailing newline.
+    pub fn signature(&self) -> &'static [u8] {
+        match self {
+            Version::V2 => b"# v2 git bundle\n",
+            Version::V3 => b"# v3 git bundle\n",
+        }
+    }
+}
+
+/// A commit which has to be present in a repository for it to be able to receive the pack of a bundle.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Prerequisite {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// An informational comment, typically the subject of the commit, which may be empty.
+    pub comment: BString,
+}
+
+/// A reference provided by a bundle.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Ref {
+    /// The object the reference points to, which is contained in the pack or is reachable from a [prerequisite](Prerequisite).
+    pub id: ObjectId,
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+}
+
+/// The header of a bundle which precedes its pack.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Header {
+    /// The version of the bundle format.
+    pub version: Version,
+    /// The kind of hash used for all object ids in the bundle, as declared by the `object-format` capability.
+    pub object_hash: gix_hash::Kind,
+    /// The filter used to create a pack with missing objects, as declared by the `filter` capability,
+    /// like `blob:none`.
+    pub filter: Option<BString>,
+    /// The commits which are required to exist in the receiving repository.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references provided by the bundle.
+    pub refs: Vec<Ref>,
+}
+
+/// A bundle on disk along with its decoded header.
+#[derive(Debug, Clone)]
+pub struct File {
+    /// The decoded header of the bundle.
+    pub header: Header,
+    path: PathBuf,
+    pack_offset: u64,
+}
+
+///
+pub mod header;
+
+///
+pub mod file;
+pub use file::is_bundle;
@@ -0,0 +1,229 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_bundle::{header, Header, Version};
+use gix_hash::ObjectId;
+use gix_object::bstr::{BStr, ByteSlice};
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn fixture(name: &str) -> PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_bundles.sh")
+        .expect("fixture path")
+        .join(name)
+}
+
+fn odb(repo: &str) -> gix_odb::Handle {
+    gix_odb::at(fixture(repo).join(".git").join("objects")).expect("valid odb")
+}
+
+fn first_commit() -> ObjectId {
+    ObjectId::from_hex(std::fs::read(fixture("first")).expect("written").trim()).expect("valid")
+}
+
+fn write_pack_options() -> gix_pack::bundle::write::Options {
+    gix_pack::bundle::write::Options {
+        thread_limit: None,
+        iteration_mode: gix_pack::data::input::Mode::Verify,
+        index_version: Default::default(),
+        object_hash: gix_hash::Kind::Sha1,
+    }
+}
+
+mod header_decode {
+    use super::*;
+
+    #[test]
+    fn all_refs_without_prerequisites() -> Result {
+        let bundle = gix_bundle::File::at(fixture("all.bundle"))?;
+        assert_eq!(bundle.header.version, Version::V2);
+        assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+        assert!(bundle.header.prerequisites.is_empty());
+        assert!(bundle.header.filter.is_none());
+
+        let heads = std::fs::read(fixture("all.heads"))?;
+        let expected: Vec<_> = heads
+            .lines()
+            .map(|line| {
+                let (hex, name) = line.split_once_str(" ").expect("two tokens");
+                (ObjectId::from_hex(hex).expect("valid"), name.as_bstr())
+            })
+            .collect();
+        let actual: Vec<_> = bundle
+            .header
+            .refs
+            .iter()
+            .map(|r| (r.id, r.name.as_ref()))
+            .collect::<Vec<(ObjectId, &BStr)>>();
+        assert_eq!(actual, expected, "refs are the same as listed by git");
+        assert_eq!(
+            actual.iter().map(|(_, name)| name.to_string()).collect::<Vec<_>>(),
+            ["refs/heads/main", "refs/heads/other", "refs/tags/v1", "HEAD"]
+        );
+        assert!(bundle.header.find_ref("refs/tags/v1".into()).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn prerequisites_with_comment() -> Result {
+        let bundle = gix_bundle::File::at(fixture("incremental.bundle"))?;
+        assert_eq!(bundle.header.prerequisites.len(), 1);
+        let prerequisite = &bundle.header.prerequisites[0];
+        assert_eq!(prerequisite.comment, "second", "the subject of the commit");
+        assert_eq!(bundle.header.refs.len(), 1);
+        assert_eq!(bundle.header.refs[0].name, "refs/heads/main");
+        Ok(())
+    }
+
+    #[test]
+    fn v3_with_capabilities() -> Result {
+        let bundle = gix_bundle::File::at(fixture("v3.bundle"))?;
+        assert_eq!(bundle.header.version, Version::V3);
+        assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+        assert!(bundle.header.filter.is_none());
+
+        let header = Header::from_read(&mut &b"# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n\nPACK"[..])?;
+        assert_eq!(header.filter.as_ref().map(|f| f.as_bstr()), Some("blob:none".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn reader_is_positioned_at_pack() -> Result {
+        let bundle = gix_bundle::File::at(fixture("all.bundle"))?;
+        let mut buf = [0u8; 4];
+        std::io::Read::read_exact(&mut bundle.pack_reader()?, &mut buf)?;
+        assert_eq!(&buf, b"PACK");
+        assert!(gix_bundle::is_bundle(&fixture("all.bundle")));
+        assert!(!gix_bundle::is_bundle(&fixture("first")));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        for (input, expected) in [
+            (&b""[..], "UnexpectedEof"),
+            (b"# v4 git bundle\n\n", "Signature"),
+            (b"# v2 git bundle\n", "UnexpectedEof"),
+            (b"# v2 git bundle\n@object-format=sha1\n\n", "UnexpectedCapability"),
+            (b"# v3 git bundle\n@unknown\n\n", "UnknownCapability"),
+            (b"# v3 git bundle\n@object-format=md5\n\n", "UnsupportedObjectFormat"),
+            (b"# v3 git bundle\n@filter\n\n", "MissingFilterValue"),
+            (b"# v2 git bundle\n-abc\n\n", "InvalidLine"),
+            (
+                b"# v2 git bundle\n0000000000000000000000000000000000000000\n\n",
+                "InvalidLine",
+            ),
+        ] {
+            let err = Header::from_read(&mut &input[..]).unwrap_err();
+            assert!(
+                format!("{err:?}").starts_with(expected),
+                "{input:?}: expected {expected}, got {err:?}"
+            );
+        }
+        assert!(matches!(
+            Header::from_read(&mut &b"# v3 git bundle\n-0000000000000000000000000000000000000000\n@filter=x\n\n"[..]),
+            Err(header::decode::Error::UnexpectedCapability { .. })
+        ));
+    }
+}
+
+mod header_write {
+    use super::*;
+
+    #[test]
+    fn roundtrip() -> Result {
+        for name in ["all.bundle", "incremental.bundle", "v3.bundle"] {
+            let bundle = gix_bundle::File::at(fixture(name))?;
+            let mut buf = Vec::new();
+            bundle.header.write_to(&mut buf)?;
+            let mut expected = std::fs::read(fixture(name))?;
+            expected.truncate(bundle.pack_offset() as usize);
+            assert_eq!(
+                buf.as_bstr(),
+                expected.as_bstr(),
+                "{name}: headers are written exactly like git does"
+            );
+            assert_eq!(Header::from_read(&mut buf.as_slice())?, bundle.header);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn new_chooses_the_oldest_possible_version() {
+        assert_eq!(Header::new(gix_hash::Kind::Sha1, None).version, Version::V2);
+        assert_eq!(
+            Header::new(gix_hash::Kind::Sha1, Some("blob:none".into())).version,
+            Version::V3
+        );
+    }
+}
+
+mod verify {
+    use super::*;
+
+    #[test]
+    fn missing_prerequisites() -> Result {
+        let bundle = gix_bundle::File::at(fixture("incremental.bundle"))?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let empty = gix_odb::at(tmp.path())?;
+        let err = bundle
+            .verify(&empty, &mut gix_features::progress::Discard, &AtomicBool::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            gix_bundle::file::verify::Error::MissingPrerequisites { missing } if missing == [bundle.header.prerequisites[0].id]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn complete_bundle() -> Result {
+        let bundle = gix_bundle::File::at(fixture("all.bundle"))?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let empty = gix_odb::at(tmp.path())?;
+        let outcome = bundle.verify(&empty, &mut gix_features::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(outcome.index.num_objects, 13, "4 commits, 4 trees, 4 blobs and a tag");
+        Ok(())
+    }
+
+    #[test]
+    fn thin_pack_with_prerequisites() -> Result {
+        let bundle = gix_bundle::File::at(fixture("incremental.bundle"))?;
+        let outcome = bundle.verify(
+            odb("repo"),
+            &mut gix_features::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(outcome.index.num_objects, 3, "commit, tree and blob");
+        Ok(())
+    }
+}
+
+#[test]
+fn write_pack_to_directory() -> Result {
+    let bundle = gix_bundle::File::at(fixture("incremental.bundle"))?;
+    let objects = odb("only-first");
+    assert!(
+        !bundle.header.missing_prerequisites(&objects).is_empty(),
+        "only the first commit is present"
+    );
+    assert!(gix_object::Exists::exists(&objects, &first_commit()));
+
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let outcome = gix_bundle::File::at(fixture("all.bundle"))?.write_pack_to_directory(
+        Some(dir.path()),
+        &mut gix_features::progress::Discard,
+        &AtomicBool::default(),
+        None::<gix_object::find::Never>,
+        write_pack_options(),
+    )?;
+    let index_path = outcome.index_path.expect("written");
+    assert!(index_path.starts_with(dir.path()));
+    let index = gix_pack::index::File::at(&index_path, gix_hash::Kind::Sha1)?;
+    for r in &bundle.header.refs {
+        assert!(
+            index.lookup(r.id).is_some(),
+            "the tip of the incremental bundle is contained"
+        );
+    }
+    Ok(())
+}
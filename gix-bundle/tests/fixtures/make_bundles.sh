@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  echo a > a && git add a && git commit -q -m "first"
+  git rev-parse HEAD > ../first
+  echo b > b && git add b && git commit -q -m "second"
+  git tag -m "annotated" v1
+  echo c > c && git add c && git commit -q -m "third"
+  git checkout -q -b other HEAD~1
+  echo d > d && git add d && git commit -q -m "other"
+  git checkout -q main
+
+  git bundle create -q ../all.bundle --all
+  git bundle create -q ../incremental.bundle main~1..main
+  git bundle create -q --version=3 ../v3.bundle main
+  git bundle list-heads ../all.bundle > ../all.heads
+)
+
+git init -q only-first
+(cd only-first
+  git fetch -q ../repo "$(cat ../first)"
+)
@@ -0,0 +1,249 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::blob::Driver;
+
+/// A driver that is built into `git`, and thus known without configuration.
+struct Builtin {
+    name: &'static str,
+    /// The patterns to find function names with, one per line.
+    funcname: &'static [&'static str],
+    /// The regular expression to find words with, without the [`WORD_REGEX_SUFFIX`].
+    word_regex: &'static str,
+    ignore_case: bool,
+}
+
+/// Appended to the word regex of each builtin driver to make each non-whitespace character and each multibyte UTF-8
+/// character a word if nothing else matches.
+const WORD_REGEX_SUFFIX: &str = r"|[^[:space:]]|[\xc0-\xff][\x80-\xbf]+";
+
+/// The builtin drivers of `git` in the order of their names, translated to the syntax of the `regex` crate.
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "ada",
+        funcname: &[
+            r"!^(.*[ \t])?(is[ \t]+new|renames|is[ \t]+separate)([ \t].*)?$",
+            r"!^[ \t]*with[ \t].*$",
+            r"^[ \t]*((procedure|function)[ \t]+.*)$",
+            r"^[ \t]*((package|protected|task)[ \t]+.*)$",
+        ],
+        word_regex: r"[a-zA-Z][a-zA-Z0-9_]*|[-+]?[0-9][0-9#_.aAbBcCdDeEfF]*([eE][+-]?[0-9_]+)?|=>|\.\.|\*\*|:=|/=|>=|<=|<<|>>|<>",
+        ignore_case: true,
+    },
+    Builtin {
+        name: "bash",
+        funcname: &[
+            r"^[ \t]*(([a-zA-Z_][a-zA-Z0-9_]*[ \t]*\([ \t]*\))|(function[ \t]+[a-zA-Z_][a-zA-Z0-9_]*(([ \t]*\([ \t]*\))|([ \t]+)))[ \t]*(\{|\(\(?|\[\[))",
+        ],
+        word_regex: r"[^ \t]+",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "bibtex",
+        funcname: &[r#"(@[a-zA-Z]{1,}[ \t]*\{{0,1}[ \t]*[^ \t"@',\\#}{~%]*).*$"#],
+        word_regex: r#"[={}"]|[^={}" \t]+"#,
+        ignore_case: false,
+    },
+    Builtin {
+        name: "cpp",
+        funcname: &[
+            r"!^[ \t]*[A-Za-z_][A-Za-z_0-9]*:[[:space:]]*($|/[/*])",
+            r"^((::[[:space:]]*)?[A-Za-z_].*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[0-9][0-9.]*([Ee][-+]?[0-9]+)?[fFlLuU]*|0[xXbB][0-9a-fA-F]+[lLuU]*|\.[0-9][0-9]*([Ee][-+]?[0-9]+)?[fFlL]?|[-+*/<>%&^|=!]=|--|\+\+|<<=?|>>=?|&&|\|\||::|->\*?|\.\*|<=>",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "csharp",
+        funcname: &[
+            r"!^[ \t]*(do|while|for|if|else|instanceof|new|return|switch|case|throw|catch|using)",
+            r"^[ \t]*(((static|public|internal|private|protected|new|virtual|sealed|override|unsafe|async)[ \t]+)*[\]\[<>@.~_[:alnum:]]+[ \t]+[<>@._[:alnum:]]+[ \t]*\(.*\))[ \t]*$",
+            r"^[ \t]*(((static|public|internal|private|protected|new|virtual|sealed|override|unsafe)[ \t]+)*[\]\[<>@.~_[:alnum:]]+[ \t]+[@._[:alnum:]]+)[ \t]*$",
+            r"^[ \t]*(((static|public|internal|private|protected|new|unsafe|sealed|abstract|partial)[ \t]+)*(class|enum|interface|struct|record)[ \t]+.*)$",
+            r"^[ \t]*(namespace[ \t]+.*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\+\+|<<=?|>>=?|&&|\|\||::|->",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "css",
+        funcname: &[r"![:;][[:space:]]*$", r"^[:\[@.#]?[_a-z0-9].*$"],
+        word_regex: r"-?[_a-zA-Z][-_a-zA-Z0-9]*|-?[0-9]+|#[0-9a-fA-F]+",
+        ignore_case: true,
+    },
+    Builtin {
+        name: "dts",
+        funcname: &[r"!;", r"!=", r"^[ \t]*((/[ \t]*\{|&?[a-zA-Z_]).*)"],
+        word_regex: r"[a-zA-Z0-9,._+?#-]+|[-+*/%&^|!~]|>>|<<|&&|\|\|",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "elixir",
+        funcname: &[r"^[ \t]*((def(macro|module|impl|protocol|p)?|test)[ \t].*)$"],
+        word_regex: r"[@:]?[a-zA-Z0-9@_?!]+|[-+]?0[xob][0-9a-fA-F]+|[-+]?[0-9][0-9_.]*([eE][-+]?[0-9_]+)?|:?(\+\+|--|\.\.|~~~|<>|\^\^\^|<?\|>|<<<?|>?>>|<<?~|~>?>|<~>|<=|>=|===?|!==?|=~|&&&?|\|\|\|?|=>|<-|\\\\|->)|:?%[A-Za-z0-9_.]\{\}?",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "fortran",
+        funcname: &[
+            r"!^([C*]|[ \t]*!)",
+            r"!^[ \t]*MODULE[ \t]+PROCEDURE[ \t]",
+            r#"^[ \t]*((END[ \t]+)?(PROGRAM|MODULE|BLOCK[ \t]+DATA|([^!'" \t]+[ \t]+)*(SUBROUTINE|FUNCTION))[ \t]+[A-Z].*)$"#,
+        ],
+        word_regex: r"[a-zA-Z][a-zA-Z0-9_]*|\.([Ee][Qq]|[Nn][Ee]|[Gg][TtEe]|[Ll][TtEe]|[Tt][Rr][Uu][Ee]|[Ff][Aa][Ll][Ss][Ee]|[Aa][Nn][Dd]|[Oo][Rr]|[Nn]?[Ee][Qq][Vv]|[Nn][Oo][Tt])\.|[-+]?[0-9.]+([AaIiDdEeFfLlTtXx][Ss]?[-+]?[0-9.]*)?(_[a-zA-Z0-9][a-zA-Z0-9_]*)?|//|\*\*|::|[/<>=]=",
+        ignore_case: true,
+    },
+    Builtin {
+        name: "fountain",
+        funcname: &[r"^((\.[^.]|(int|ext|est|int\.?/ext|i/e)[. ]).*)$"],
+        word_regex: r"[^ \t-]+",
+        ignore_case: true,
+    },
+    Builtin {
+        name: "golang",
+        funcname: &[
+            r"^[ \t]*(func[ \t]*.*(\{[ \t]*)?)",
+            r"^[ \t]*(type[ \t].*(struct|interface)[ \t]*(\{[ \t]*)?)",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.eE]+i?|0[xX]?[0-9a-fA-F]+i?|[-+*/<>%&^|=!:]=|--|\+\+|<<=?|>>=?|&\^=?|&&|\|\||<-|\.{3}",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "html",
+        funcname: &[r"^[ \t]*(<[Hh][1-6]([ \t].*)?>.*)$"],
+        word_regex: r"[^<>= \t]+",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "java",
+        funcname: &[
+            r"!^[ \t]*(catch|do|for|if|instanceof|new|return|switch|throw|while)",
+            r"^[ \t]*(([a-z]+[ \t]+)*(class|enum|interface)[ \t]+[A-Za-z][A-Za-z0-9_$]*[ \t]+.*)$",
+            r"^[ \t]*(([A-Za-z_<>&][\]\[?&<>.,A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\([^;]*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\+\+|<<=?|>>>?=?|&&|\|\|",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "kotlin",
+        funcname: &[r"^[ \t]*(([a-z]+[ \t]+)*(fun|class|interface)[ \t]+.*)$"],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|0[xXbB][0-9a-fA-F_]+[lLuU]*|[0-9][0-9_]*([.][0-9_]*)?([Ee][-+]?[0-9]+)?[fFlLuU]*|[.][0-9][0-9_]*([Ee][-+]?[0-9]+)?[fFlLuU]?|[-+*/<>%&^|=!]==?|--|\+\+|<<=|>>=|&&|\|\||->|\.\*|!!|[?:.][.:]",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "markdown",
+        funcname: &[r"^ {0,3}#{1,6}[ \t].*"],
+        word_regex: r"[^<>= \t]+",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "matlab",
+        funcname: &[r"^[[:space:]]*((classdef|function)[[:space:]].*)$|^(%%%?|##)[[:space:]].*$"],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|[=~<>]=|\.[*/\\^']|\|\||&&",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "objc",
+        funcname: &[
+            r"!^[ \t]*(do|for|if|else|return|switch|while)",
+            r"^[ \t]*([-+][ \t]*\([ \t]*[A-Za-z_][A-Za-z_0-9* \t]*\)[ \t]*[A-Za-z_].*)$",
+            r"^[ \t]*(([A-Za-z_][A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\([^;]*)$",
+            r"^(@(implementation|interface|protocol)[ \t].*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\+\+|<<=?|>>=?|&&|\|\||::|->",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "pascal",
+        funcname: &[
+            r"^(((class[ \t]+)?(procedure|function)|constructor|destructor|interface|implementation|initialization|finalization)[ \t]*.*)$",
+            r"^(.*=[ \t]*(class|record).*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|<>|<=|>=|:=|\.\.",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "perl",
+        funcname: &[
+            r"^package .*",
+            r"^sub [[:alnum:]_':]+[ \t]*(\([^)]*\)[ \t]*)?(:[^;#]*)?(\{[ \t]*)?(#.*)?$",
+            r"^(BEGIN|END|INIT|CHECK|UNITCHECK|AUTOLOAD|DESTROY)[ \t]*(\{[ \t]*)?(#.*)?$",
+            r"^=head[0-9] .*",
+        ],
+        word_regex: r"[[:alpha:]_'][[:alnum:]_']*|0[xb]?[0-9a-fA-F_]*|[0-9a-fA-F_]+(\.[0-9a-fA-F_]+)?([eE][-+]?[0-9_]+)?|=>|-[rwxoRWXOezsfdlpSugkbctTBMAC>]|~~|::|&&=|\|\|=|//=|\*\*=|&&|\|\||//|\+\+|--|\*\*|\.\.\.?|[-+*/%.^&<>=!|]=|=~|!~|<<|<>|<=>|>>",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "php",
+        funcname: &[
+            r"^[\t ]*(((public|protected|private|static|abstract|final)[\t ]+)*function.*)$",
+            r"^[\t ]*((((final|abstract)[\t ]+)?class|enum|interface|trait).*)$",
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|[-+*/<>%&^|=!.]=|--|\+\+|<<=?|>>=?|===|&&|\|\||::|->",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "python",
+        funcname: &[r"^[ \t]*((class|(async[ \t]+)?def)[ \t].*)$"],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[jJlL]?|0[xX]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|//=?|<<=?|>>=?|\*\*=?",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "ruby",
+        funcname: &[r"^[ \t]*((class|module|def)[ \t].*)$"],
+        word_regex: r"(@|@@|\$)?[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|\?(\\C-)?(\\M-)?.|//=?|[-+*/<>%&^|=!]=|<<=?|>>=?|===|\.{1,3}|::|[!=]~",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "rust",
+        funcname: &[
+            r#"^[\t ]*((pub(\([^\\)]+\))?[\t ]+)?((async|const|unsafe|extern([\t ]+"[^"]+"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[< \t]+[^;]*)$"#,
+        ],
+        word_regex: r"[a-zA-Z_][a-zA-Z0-9_]*|[0-9][0-9_a-fA-Fiosuxz]*(\.([0-9]*[eE][+-]?)?[0-9_fF]*)?|[-+*\\/<>%&^|=!:]=|<<=?|>>=?|&&|\|\||->|=>|\.{2}=|\.{3}|::",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "scheme",
+        funcname: &[
+            r"^[\t ]*(\(((define|def(struct|syntax|class|method|rules|record|proto|alias)?)[-*/ \t]|(library|module|struct|class)[*+ \t]).*)$",
+        ],
+        word_regex: r"\|([^\\]*)\||([^\]\[)(}{ \t])+",
+        ignore_case: false,
+    },
+    Builtin {
+        name: "tex",
+        funcname: &[r"^(\\((sub)*section|chapter|part)\*{0,1}\{.*)$"],
+        word_regex: r"\\[a-zA-Z@]+|\\.|[a-zA-Z0-9\x80-\xff]+",
+        ignore_case: false,
+    },
+];
+
+impl Builtin {
+    fn to_driver(&self) -> Driver {
+        Driver {
+            name: self.name.into(),
+            funcname: Some(self.funcname.join("\n").into()),
+            funcname_ignore_case: self.ignore_case,
+            word_regex: Some(format!("{}{WORD_REGEX_SUFFIX}", self.word_regex).into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Builtin drivers
+impl Driver {
+    /// Return the driver that `git` knows as `name` without configuration, like `rust` or `python`, with the
+    /// [`funcname`](Self::funcname) and [`word_regex`](Self::word_regex) to use for the respective language.
+    ///
+    /// Note that its regular expressions use the syntax of the `regex` crate.
+    pub fn builtin(name: &BStr) -> Option<Driver> {
+        BUILTINS
+            .binary_search_by(|builtin| builtin.name.as_bytes().as_bstr().cmp(name))
+            .ok()
+            .map(|idx| BUILTINS[idx].to_driver())
+    }
+
+    /// Return all drivers that `git` knows without configuration, ordered by name.
+    pub fn builtins() -> impl Iterator<Item = Driver> {
+        BUILTINS.iter().map(Builtin::to_driver)
+    }
+}
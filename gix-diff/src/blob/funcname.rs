@@ -0,0 +1,229 @@
+//! Find the function or section that a hunk belongs to, to show it in hunk headers like `@@ -10,7 +10,8 @@ fn main() {`.
+use bstr::{BStr, BString, ByteSlice};
+
+/// The maximum length of a section header in bytes, longer ones are truncated.
+const MAX_LEN: usize = 80;
+
+/// A way to find lines that start a function or section, like `diff.<driver>.xfuncname` does in `git`.
+///
+/// Its [default](Self::default()) considers all lines that start with a letter, `_` or `$`, like `git` does if no pattern
+/// is configured.
+#[derive(Default, Debug, Clone)]
+pub struct Funcname {
+    /// The patterns to try in order, or `None` to use the default.
+    #[cfg(feature = "funcname")]
+    patterns: Option<Vec<Pattern>>,
+}
+
+#[cfg(feature = "funcname")]
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: regex::bytes::Regex,
+    /// If `true`, lines matching this pattern are never section headers.
+    negate: bool,
+}
+
+///
+#[cfg(feature = "funcname")]
+pub mod new {
+    use bstr::BString;
+
+    /// The error returned by [Funcname::new()](super::Funcname::new()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not compile the regular expression '{pattern}' to find function names with")]
+        Regex { pattern: BString, source: regex::Error },
+        #[error("The last pattern to find function names with must not be negated, got '{pattern}'")]
+        NegatedLastPattern { pattern: BString },
+    }
+}
+
+/// Initialization
+#[cfg(feature = "funcname")]
+impl Funcname {
+    /// Create a new instance from `patterns`, one regular expression per line, which are tried in order on each line
+    /// until one matches. If that pattern starts with `!`, the line is not a section header, otherwise the text of its
+    /// first capture group is, or the whole match if there is no such group.
+    /// Match case-insensitively if `ignore_case` is `true`.
+    ///
+    /// Note that the patterns use the syntax of the `regex` crate and aren't POSIX extended regular expressions
+    /// like in `git`, which only matters for uncommon constructs.
+    pub fn new(patterns: &BStr, ignore_case: bool) -> Result<Self, new::Error> {
+        if let Some(last) = patterns.lines().next_back().filter(|line| line.starts_with(b"!")) {
+            return Err(new::Error::NegatedLastPattern { pattern: last.into() });
+        }
+        let patterns = patterns
+            .lines()
+            .map(|line| {
+                let (line, negate) = line.strip_prefix(b"!").map_or((line, false), |line| (line, true));
+                line.to_str()
+                    .map_err(|err| regex::Error::Syntax(err.to_string()))
+                    .and_then(|pattern| {
+                        regex::bytes::RegexBuilder::new(pattern)
+                            .unicode(false)
+                            .case_insensitive(ignore_case)
+                            .build()
+                    })
+                    .map(|regex| Pattern { regex, negate })
+                    .map_err(|source| new::Error::Regex {
+                        pattern: line.into(),
+                        source,
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Funcname {
+            patterns: Some(patterns),
+        })
+    }
+}
+
+/// Matching
+impl Funcname {
+    /// Return the section header if `line` starts a function or section, without trailing whitespace and truncated
+    /// to 80 bytes, or `None` if it doesn't.
+    pub fn find<'a>(&self, line: &'a [u8]) -> Option<&'a BStr> {
+        #[cfg(feature = "funcname")]
+        if let Some(patterns) = &self.patterns {
+            let line = line
+                .strip_suffix(b"\n")
+                .map_or(line, |line| line.strip_suffix(b"\r").unwrap_or(line));
+            let captures = patterns
+                .iter()
+                .find_map(|pattern| pattern.regex.captures(line).map(|captures| (pattern.negate, captures)))
+                .filter(|(negate, _)| !negate)?
+                .1;
+            let header = captures.get(1).or_else(|| captures.get(0)).expect("there was a match");
+            return Some(truncate_and_trim(header.as_bytes()));
+        }
+        line.first()
+            .filter(|b| b.is_ascii_alphabetic() || **b == b'_' || **b == b'$')
+            .map(|_| truncate_and_trim(line))
+    }
+
+    /// Return a way to find the section headers of all hunks of a single diff.
+    pub fn search(&self) -> Search<'_> {
+        Search {
+            funcname: self,
+            unsearched_start: 0,
+            header: BString::default(),
+        }
+    }
+}
+
+/// Find section headers of the hunks of a diff, which are expected in order, by searching the old file backwards from
+/// the start of each hunk. If no new section header is found, the one of the previous hunk is used.
+#[derive(Debug, Clone)]
+pub struct Search<'a> {
+    funcname: &'a Funcname,
+    /// The first line that wasn't searched yet, as lines before it were searched for previous hunks.
+    unsearched_start: u32,
+    /// The most recently found section header.
+    header: BString,
+}
+
+impl Search<'_> {
+    /// Return the section header of a hunk that starts at the 0-based line `hunk_start` of the old file, using `old_line(n)`
+    /// to obtain the 0-based line `n` of the old file.
+    ///
+    /// It's empty if there is none.
+    pub fn section_header<'l>(&mut self, hunk_start: u32, mut old_line: impl FnMut(u32) -> &'l [u8]) -> &BStr {
+        if let Some(header) = (self.unsearched_start..hunk_start)
+            .rev()
+            .find_map(|line_number| self.funcname.find(old_line(line_number)))
+        {
+            self.header = header.to_owned();
+        }
+        self.unsearched_start = self.unsearched_start.max(hunk_start);
+        self.header.as_ref()
+    }
+}
+
+fn truncate_and_trim(line: &[u8]) -> &BStr {
+    let mut line = &line[..line.len().min(MAX_LEN)];
+    while line
+        .last()
+        .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r'))
+    {
+        line = &line[..line.len() - 1];
+    }
+    line.as_bstr()
+}
+
+/// Convert `pattern` from the POSIX basic regular expression syntax, as used by `diff.<driver>.funcname`, to the extended
+/// syntax used by `diff.<driver>.xfuncname`, by swapping the meaning of escaped and unescaped `(`, `)`, `{`, `}`, `|`, `+`
+/// and `?` outside of bracket expressions.
+pub fn basic_to_extended(pattern: &BStr) -> BString {
+    let mut out = BString::default();
+    let mut bytes = pattern.iter().copied().peekable();
+    let is_special = |b: u8| matches!(b, b'(' | b')' | b'{' | b'}' | b'|' | b'+' | b'?');
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' => match bytes.next() {
+                Some(next) if is_special(next) => out.push(next),
+                Some(next) => out.extend_from_slice(&[b'\\', next]),
+                None => out.push(b'\\'),
+            },
+            b'[' => {
+                out.push(b);
+                if bytes.next_if_eq(&b'^').is_some() {
+                    out.push(b'^');
+                }
+                if bytes.next_if_eq(&b']').is_some() {
+                    out.push(b']');
+                }
+                while let Some(b) = bytes.next() {
+                    out.push(b);
+                    match b {
+                        b']' => break,
+                        b'[' if bytes.peek().is_some_and(|next| matches!(next, b':' | b'.' | b'=')) => {
+                            let delimiter = bytes.next().expect("peeked");
+                            out.push(delimiter);
+                            while let Some(b) = bytes.next() {
+                                out.push(b);
+                                if b == delimiter && bytes.next_if_eq(&b']').is_some() {
+                                    out.push(b']');
+                                    break;
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            b if is_special(b) => out.extend_from_slice(&[b'\\', b]),
+            b => out.push(b),
+        }
+    }
+    out
+}
+
+#[cfg(feature = "funcname")]
+mod platform {
+    use crate::blob::{funcname, Funcname, Platform};
+
+    /// Section headers
+    impl Platform {
+        /// Return a way to find the section headers of the hunks of a diff between the [previously set](Self::set_resource())
+        /// resources, using the `funcname` of the diff driver of the old resource, or of the new one if it has none,
+        /// configured with the `diff=<driver>` attribute.
+        ///
+        /// If neither driver has a `funcname`, or no resources are set, the [default](Funcname::default()) is used.
+        pub fn funcname(&self) -> Result<Funcname, funcname::new::Error> {
+            let driver = self.resources().and_then(|(old, new)| {
+                let driver = |idx: Option<usize>| {
+                    idx.map(|idx| &self.filter.drivers[idx])
+                        .filter(|d| d.funcname.is_some())
+                };
+                driver(old.driver_index).or_else(|| driver(new.driver_index))
+            });
+            match driver {
+                Some(driver) => Funcname::new(
+                    driver.funcname.as_ref().expect("filtered").as_ref(),
+                    driver.funcname_ignore_case,
+                ),
+                None => Ok(Funcname::default()),
+            }
+        }
+    }
+}
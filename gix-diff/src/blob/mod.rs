@@ -5,15 +5,38 @@ use std::{collections::HashMap, path::PathBuf};
 use bstr::BString;
 pub use imara_diff::*;
 
+mod driver;
+
+///
+pub mod funcname;
+pub use funcname::Funcname;
+
 ///
 pub mod pipeline;
 
 ///
 pub mod platform;
 
+///
+pub mod moved;
+
+///
+pub mod sliders;
+
+///
+pub mod stats;
+pub use stats::Stats;
+
 pub mod unified_diff;
 pub use unified_diff::_impl::UnifiedDiff;
 
+///
+pub mod whitespace;
+
+///
+#[cfg(feature = "word-diff")]
+pub mod word_diff;
+
 /// Information about the diff performed to detect similarity.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct DiffLineStats {
@@ -65,6 +88,15 @@ pub struct Driver {
     /// If `Some(false)`, it won't be considered binary, and the its data will not be sampled for the null-byte either.
     /// Leaving it to `None` means binary detection is automatic, and is based on the presence of the `0` byte in the first 8kB of the buffer.
     pub is_binary: Option<bool>,
+    /// A regular expression to match words with when producing word-diffs, overriding the splitting of lines at whitespace.
+    /// It's the value of `diff.<driver>.wordRegex`.
+    pub word_regex: Option<BString>,
+    /// Regular expressions to find the function or section a hunk belongs to, one per line, where those starting with `!`
+    /// exclude matching lines. See [`Funcname::new()`] for details.
+    /// It's the value of `diff.<driver>.xfuncname`, or of `diff.<driver>.funcname` after converting it to the same syntax.
+    pub funcname: Option<BString>,
+    /// If `true`, the [`funcname`](Self::funcname) patterns are matched case-insensitively, as needed by some builtin drivers.
+    pub funcname_ignore_case: bool,
 }
 
 /// A conversion pipeline to take an object or path from what's stored in `git` to what can be diffed, while
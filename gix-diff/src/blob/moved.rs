@@ -0,0 +1,222 @@
+//! Detect blocks of lines that were moved within a diff, similar to `git diff --color-moved`.
+use std::collections::HashMap;
+
+/// The minimal amount of alphanumeric characters a block needs to have to be considered moved,
+/// as blocks with less of them typically only consist of braces and similarly generic lines.
+const MIN_ALNUM_COUNT: usize = 20;
+
+/// Determine how moved lines are detected and grouped into blocks.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Any removed line that was added elsewhere, and vice versa, is considered moved, like `--color-moved=plain`.
+    Plain,
+    /// Find blocks of moved lines which must have at least 20 alphanumeric characters, like `--color-moved=blocks`.
+    Blocks,
+    /// Like [`Blocks`](Mode::Blocks), but adjacent blocks are distinguished by [alternating](Moved::alternate),
+    /// like `--color-moved=zebra`.
+    #[default]
+    Zebra,
+    /// Like [`Zebra`](Mode::Zebra), but lines within blocks are marked [dimmed](Moved::dimmed) so only
+    /// the borders of blocks stand out, like `--color-moved=dimmed-zebra`.
+    DimmedZebra,
+}
+
+/// A line of a diff for use in [`detect()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Line<'a> {
+    /// A removed line, including its line terminator.
+    Removed(&'a [u8]),
+    /// An added line, including its line terminator.
+    Added(&'a [u8]),
+    /// Any other line, like a context line or a header, which is never moved and ends blocks of moved lines.
+    Other,
+}
+
+impl<'a> Line<'a> {
+    fn content(&self) -> Option<&'a [u8]> {
+        match self {
+            Line::Removed(content) | Line::Added(content) => Some(content),
+            Line::Other => None,
+        }
+    }
+
+    fn is_added(&self) -> Option<bool> {
+        match self {
+            Line::Removed(_) => Some(false),
+            Line::Added(_) => Some(true),
+            Line::Other => None,
+        }
+    }
+}
+
+/// Information about a line that was detected as moved.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Moved {
+    /// If `true`, the line is part of a block that directly follows another moved block, and should be displayed
+    /// in an alternative way to tell both apart. Only set in [`Mode::Zebra`] and [`Mode::DimmedZebra`].
+    pub alternate: bool,
+    /// If `true`, the line isn't at the border of a block and is uninteresting, so it should be displayed in a dimmed way.
+    /// Only set in [`Mode::DimmedZebra`].
+    pub dimmed: bool,
+}
+
+/// Find the [`Removed`](Line::Removed) lines that were [`Added`](Line::Added) elsewhere, and vice versa, among all
+/// `lines` of a diff, which may span multiple files, and return a [`Moved`] marker for each line that is part of a moved block.
+///
+/// The returned `Vec` has the same length as `lines`, and lines are compared exactly, including whitespace.
+pub fn detect(lines: &[Line<'_>], mode: Mode) -> Vec<Option<Moved>> {
+    let mut ids = HashMap::<&[u8], usize>::new();
+    let line_ids: Vec<_> = lines
+        .iter()
+        .map(|line| {
+            line.content().map(|content| {
+                let next_id = ids.len();
+                *ids.entry(content).or_insert(next_id)
+            })
+        })
+        .collect();
+    let mut removed_by_id = vec![Vec::new(); ids.len()];
+    let mut added_by_id = vec![Vec::new(); ids.len()];
+    for (idx, (line, id)) in lines.iter().zip(&line_ids).enumerate() {
+        match (line, id) {
+            (Line::Removed(_), Some(id)) => removed_by_id[*id].push(idx),
+            (Line::Added(_), Some(id)) => added_by_id[*id].push(idx),
+            _ => {}
+        }
+    }
+    let next_line = |idx: usize| {
+        lines
+            .get(idx + 1)
+            .filter(|next| next.is_added().is_some() && next.is_added() == lines[idx].is_added())
+            .map(|_| idx + 1)
+    };
+
+    let mut out = vec![None; lines.len()];
+    // The positions of the lines of the other side that match the current block so far.
+    let mut potential_blocks = Vec::<usize>::new();
+    let mut block_sign = None;
+    let mut block_length = 0;
+    let mut alternate = false;
+    let mut n = 0;
+    while n < lines.len() {
+        let line = &lines[n];
+        let mut matches = match (line, line_ids[n]) {
+            (Line::Added(_), Some(id)) => Some(removed_by_id[id].as_slice()),
+            (Line::Removed(_), Some(id)) => Some(added_by_id[id].as_slice()),
+            _ => {
+                alternate = false;
+                None
+            }
+        }
+        .filter(|matches| !matches.is_empty());
+
+        if !potential_blocks.is_empty() && (matches.is_none() || line.is_added() != block_sign) {
+            if !keep_last_block(lines, &mut out, mode, n, block_length) && block_length > 1 {
+                // Rewind in case there is another match starting at the second line of the block.
+                matches = None;
+                n -= block_length;
+            }
+            potential_blocks.clear();
+            block_length = 0;
+            alternate = false;
+        }
+        let Some(matches) = matches else {
+            block_sign = None;
+            n += 1;
+            continue;
+        };
+
+        if mode == Mode::Plain {
+            out[n] = Some(Moved::default());
+            n += 1;
+            continue;
+        }
+
+        potential_blocks.retain_mut(|pos| match next_line(*pos) {
+            Some(next) if line_ids[next] == line_ids[n] => {
+                *pos = next;
+                true
+            }
+            _ => false,
+        });
+
+        if potential_blocks.is_empty() {
+            let contiguous = keep_last_block(lines, &mut out, mode, n, block_length);
+            if !contiguous && block_length > 1 {
+                // Rewind in case there is another match starting at the second line of the block.
+                n -= block_length;
+            } else {
+                potential_blocks.extend_from_slice(matches);
+            }
+
+            alternate = contiguous && !potential_blocks.is_empty() && block_sign == line.is_added() && !alternate;
+            block_sign = if potential_blocks.is_empty() {
+                None
+            } else {
+                line.is_added()
+            };
+            block_length = 0;
+        }
+
+        if !potential_blocks.is_empty() {
+            block_length += 1;
+            out[n] = Some(Moved {
+                alternate: alternate && mode != Mode::Blocks,
+                dimmed: false,
+            });
+        }
+        n += 1;
+    }
+    keep_last_block(lines, &mut out, mode, n, block_length);
+
+    if mode == Mode::DimmedZebra {
+        dim_moved_lines(lines, &mut out);
+    }
+    out
+}
+
+/// Unmark the block of `block_length` lines before `n` if it has too few alphanumeric characters to be considered moved.
+/// Return `true` if the block was kept.
+fn keep_last_block(lines: &[Line<'_>], out: &mut [Option<Moved>], mode: Mode, n: usize, block_length: usize) -> bool {
+    if mode == Mode::Plain {
+        return block_length != 0;
+    }
+    let block = n - block_length..n;
+    let alnum_count = lines[block.clone()]
+        .iter()
+        .filter_map(Line::content)
+        .flat_map(|content| content.iter().take_while(|b| **b != 0))
+        .filter(|b| b.is_ascii_alphanumeric())
+        .take(MIN_ALNUM_COUNT)
+        .count();
+    if alnum_count >= MIN_ALNUM_COUNT {
+        return true;
+    }
+    for moved in &mut out[block] {
+        *moved = None;
+    }
+    false
+}
+
+/// Mark all moved lines as dimmed unless they are at the border of a block.
+fn dim_moved_lines(lines: &[Line<'_>], out: &mut [Option<Moved>]) {
+    for n in 0..lines.len() {
+        let Some(moved) = out[n] else { continue };
+        let neighbour = |idx: Option<usize>| {
+            idx.filter(|idx| lines.get(*idx).is_some_and(|line| line.is_added().is_some()))
+                .map(|idx| out[idx])
+        };
+        let prev = neighbour(n.checked_sub(1));
+        let next = neighbour(Some(n + 1));
+
+        let same_block = |other: Option<Option<Moved>>| {
+            other.is_some_and(|other| other.is_some_and(|other| other.alternate == moved.alternate))
+        };
+        let is_border = |other: Option<Option<Moved>>| {
+            other.is_some_and(|other| other.is_some_and(|other| other.alternate != moved.alternate))
+        };
+        if (same_block(prev) && same_block(next)) || !(is_border(prev) || is_border(next)) {
+            out[n] = Some(Moved { dimmed: true, ..moved });
+        }
+    }
+}
@@ -0,0 +1,430 @@
+//! Shift groups of changed lines to where `git` would place them, to produce the same hunks as `git diff`.
+//!
+//! A group of added or removed lines is a *slider* if it can be moved up or down without changing the meaning of
+//! the diff, which is the case if the lines before or after it are the same as the lines at its other end.
+//! Like `git`, sliders are moved down as far as possible, unless they can be aligned with changes in the other file,
+//! or the *indent heuristic* finds a position that is easier to read, which typically is between blocks of code.
+use std::{hash::Hash, ops::Range};
+
+use imara_diff::{
+    intern::{InternedInput, Token},
+    Algorithm, Sink,
+};
+
+/// Diff `input` with `algorithm` and pass all changes to `sink` after shifting sliders to where `git` would place them,
+/// and return the result of the `sink`.
+///
+/// If `indent_heuristic` is `true`, the indentation of lines around sliders is used to choose their position,
+/// like `diff.indentHeuristic` does, which is enabled by default in `git`.
+pub fn diff<T, S>(algorithm: Algorithm, input: &InternedInput<T>, indent_heuristic: bool, sink: S) -> S::Out
+where
+    T: Eq + Hash + AsRef<[u8]>,
+    S: Sink,
+{
+    diff_tokens(algorithm, &input.before, &input.after, input, indent_heuristic, sink)
+}
+
+/// Like [`diff()`], but compare the lines of `before` and `after` instead of the ones in `input`, which are only
+/// used to measure indentation for the indent heuristic.
+///
+/// Both have to contain the same amount of lines and use the interner of `input`.
+pub(crate) fn diff_tokens<T, S>(
+    algorithm: Algorithm,
+    before: &[Token],
+    after: &[Token],
+    input: &InternedInput<T>,
+    indent_heuristic: bool,
+    mut sink: S,
+) -> S::Out
+where
+    T: Eq + Hash + AsRef<[u8]>,
+    S: Sink,
+{
+    let mut before = File::new(before);
+    let mut after = File::new(after);
+    imara_diff::diff_with_tokens(
+        algorithm,
+        before.tokens,
+        after.tokens,
+        input.interner.num_tokens(),
+        |removed: Range<u32>, added: Range<u32>| {
+            before.set_changed(removed);
+            after.set_changed(added);
+        },
+    );
+    let lines =
+        |tokens: &[Token]| -> Vec<&[u8]> { tokens.iter().map(|token| input.interner[*token].as_ref()).collect() };
+    let (before_lines, after_lines) = if indent_heuristic {
+        (lines(&input.before), lines(&input.after))
+    } else {
+        Default::default()
+    };
+    compact(&mut before, &after, indent_heuristic.then_some(before_lines.as_slice()));
+    compact(&mut after, &before, indent_heuristic.then_some(after_lines.as_slice()));
+
+    let (mut old, mut new) = (0, 0);
+    while old < before.len() || new < after.len() {
+        if before.is_changed(old) || after.is_changed(new) {
+            let (old_start, new_start) = (old, new);
+            while before.is_changed(old) {
+                old += 1;
+            }
+            while after.is_changed(new) {
+                new += 1;
+            }
+            sink.process_change(old_start as u32..old as u32, new_start as u32..new as u32);
+        } else {
+            old += 1;
+            new += 1;
+        }
+    }
+    sink.finish()
+}
+
+/// The lines of one side of a diff, along with the information which of them changed.
+struct File<'a> {
+    tokens: &'a [Token],
+    /// Whether each line changed, with an unchanged line before the first and after the last line so that
+    /// groups never have to check for the start or end of the file.
+    changed: Vec<bool>,
+}
+
+/// A group of consecutive changed lines, which is empty if it's the position in the file where lines were changed
+/// in the other file.
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    /// The first changed line.
+    start: isize,
+    /// The first unchanged line after the group.
+    end: isize,
+}
+
+impl<'a> File<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        File {
+            tokens,
+            changed: vec![false; tokens.len() + 2],
+        }
+    }
+
+    fn len(&self) -> isize {
+        self.tokens.len() as isize
+    }
+
+    fn is_changed(&self, line: isize) -> bool {
+        self.changed[(line + 1) as usize]
+    }
+
+    fn set_changed(&mut self, lines: Range<u32>) {
+        for line in lines {
+            self.changed[line as usize + 1] = true;
+        }
+    }
+
+    fn lines_match(&self, a: isize, b: isize) -> bool {
+        self.tokens[a as usize] == self.tokens[b as usize]
+    }
+
+    fn first_group(&self) -> Group {
+        let mut end = 0;
+        while self.is_changed(end) {
+            end += 1;
+        }
+        Group { start: 0, end }
+    }
+
+    /// Move `group` to the next group and return `true`, or return `false` if it's the last one.
+    fn next_group(&self, group: &mut Group) -> bool {
+        if group.end == self.len() {
+            return false;
+        }
+        group.start = group.end + 1;
+        group.end = group.start;
+        while self.is_changed(group.end) {
+            group.end += 1;
+        }
+        true
+    }
+
+    /// Move `group` to the previous group and return `true`, or return `false` if it's the first one.
+    fn previous_group(&self, group: &mut Group) -> bool {
+        if group.start == 0 {
+            return false;
+        }
+        group.end = group.start - 1;
+        group.start = group.end;
+        while self.is_changed(group.start - 1) {
+            group.start -= 1;
+        }
+        true
+    }
+
+    /// Shift `group` down by one line if possible, merging it with the following group if they touch, and return `true`,
+    /// or return `false` if it can't be shifted.
+    fn slide_down(&mut self, group: &mut Group) -> bool {
+        if group.end < self.len() && self.lines_match(group.start, group.end) {
+            self.changed[(group.start + 1) as usize] = false;
+            self.changed[(group.end + 1) as usize] = true;
+            group.start += 1;
+            group.end += 1;
+            while self.is_changed(group.end) {
+                group.end += 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Shift `group` up by one line if possible, merging it with the preceding group if they touch, and return `true`,
+    /// or return `false` if it can't be shifted.
+    fn slide_up(&mut self, group: &mut Group) -> bool {
+        if group.start > 0 && self.lines_match(group.start - 1, group.end - 1) {
+            group.start -= 1;
+            group.end -= 1;
+            self.changed[(group.start + 1) as usize] = true;
+            self.changed[(group.end + 1) as usize] = false;
+            while self.is_changed(group.start - 1) {
+                group.start -= 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The maximum amount of lines a slider is moved up from its lowest position when trying the indent heuristic.
+const INDENT_HEURISTIC_MAX_SLIDING: isize = 100;
+
+/// Shift the groups of changed lines in `file` as far down as possible, and then up again to align them with a group
+/// in `other` or, if the `lines` of `file` are set to enable the indent heuristic, to the position with the best
+/// split score.
+///
+/// This is a port of `xdl_change_compact()` of `git`.
+fn compact(file: &mut File<'_>, other: &File<'_>, lines: Option<&[&[u8]]>) {
+    let mut group = file.first_group();
+    let mut other_group = other.first_group();
+    loop {
+        if group.end != group.start {
+            let mut earliest_end;
+            let mut end_matching_other;
+            loop {
+                let group_size = group.end - group.start;
+                end_matching_other = None;
+
+                while file.slide_up(&mut group) {
+                    other.previous_group(&mut other_group);
+                }
+                earliest_end = group.end;
+                if other_group.end > other_group.start {
+                    end_matching_other = Some(group.end);
+                }
+
+                while file.slide_down(&mut group) {
+                    other.next_group(&mut other_group);
+                    if other_group.end > other_group.start {
+                        end_matching_other = Some(group.end);
+                    }
+                }
+                if group_size == group.end - group.start {
+                    break;
+                }
+            }
+
+            if group.end == earliest_end {
+                // The group can't be shifted.
+            } else if end_matching_other.is_some() {
+                while other_group.end == other_group.start {
+                    file.slide_up(&mut group);
+                    other.previous_group(&mut other_group);
+                }
+            } else if let Some(lines) = lines {
+                let group_size = group.end - group.start;
+                let line = |line: isize| lines[line as usize];
+                let mut best: Option<(isize, Score)> = None;
+                let first_shift = earliest_end
+                    .max(group.end - group_size - 1)
+                    .max(group.end - INDENT_HEURISTIC_MAX_SLIDING);
+                for shift in first_shift..=group.end {
+                    let mut score = Score::default();
+                    score.add(&Split::measure(shift, file.len(), line));
+                    score.add(&Split::measure(shift - group_size, file.len(), line));
+                    if best.map_or(true, |(_, best)| score.cmp(&best) <= 0) {
+                        best = Some((shift, score));
+                    }
+                }
+                let best_shift = best.expect("at least one shift").0;
+                while group.end > best_shift {
+                    file.slide_up(&mut group);
+                    other.previous_group(&mut other_group);
+                }
+            }
+        }
+
+        if !file.next_group(&mut group) {
+            break;
+        }
+        other.next_group(&mut other_group);
+    }
+}
+
+/// The maximum indentation that is considered, more is treated as this value.
+const MAX_INDENT: i32 = 200;
+/// The maximum amount of blank lines to look at around a split.
+const MAX_BLANKS: i32 = 20;
+
+/// Information about the lines around a split, which is the position between two lines.
+struct Split {
+    /// `true` if the split is at the end of the file.
+    end_of_file: bool,
+    /// The indentation of the line after the split, or -1 if it is blank.
+    indent: i32,
+    /// The amount of consecutive blank lines before the split.
+    pre_blank: i32,
+    /// The indentation of the closest non-blank line before the split, or -1 if there is none.
+    pre_indent: i32,
+    /// The amount of consecutive blank lines after the line following the split.
+    post_blank: i32,
+    /// The indentation of the closest non-blank line after the line following the split, or -1 if there is none.
+    post_indent: i32,
+}
+
+impl Split {
+    /// Measure the split before the 0-based line `split` of a file with `len` lines, with `line(n)` returning line `n`.
+    fn measure<'a>(split: isize, len: isize, line: impl Fn(isize) -> &'a [u8]) -> Self {
+        let mut pre_blank = 0;
+        let mut pre_indent = -1;
+        for line_number in (0..split).rev() {
+            pre_indent = indent(line(line_number));
+            if pre_indent != -1 {
+                break;
+            }
+            pre_blank += 1;
+            if pre_blank == MAX_BLANKS {
+                pre_indent = 0;
+                break;
+            }
+        }
+
+        let (end_of_file, indent_after) = if split >= len {
+            (true, -1)
+        } else {
+            (false, indent(line(split)))
+        };
+
+        let mut post_blank = 0;
+        let mut post_indent = -1;
+        for line_number in split + 1..len {
+            post_indent = indent(line(line_number));
+            if post_indent != -1 {
+                break;
+            }
+            post_blank += 1;
+            if post_blank == MAX_BLANKS {
+                post_indent = 0;
+                break;
+            }
+        }
+
+        Split {
+            end_of_file,
+            indent: indent_after,
+            pre_blank,
+            pre_indent,
+            post_blank,
+            post_indent,
+        }
+    }
+}
+
+/// Return the indentation of `line` with tabs expanded to multiples of 8, or -1 if it only consists of whitespace.
+fn indent(line: &[u8]) -> i32 {
+    let mut indent = 0;
+    for byte in line {
+        match byte {
+            b' ' => indent += 1,
+            b'\t' => indent += 8 - indent % 8,
+            b'\n' | b'\r' | b'\x0b' | b'\x0c' => {}
+            _ => return indent,
+        }
+        if indent >= MAX_INDENT {
+            return MAX_INDENT;
+        }
+    }
+    -1
+}
+
+const START_OF_FILE_PENALTY: i32 = 1;
+const END_OF_FILE_PENALTY: i32 = 21;
+const TOTAL_BLANK_WEIGHT: i32 = -30;
+const POST_BLANK_WEIGHT: i32 = 6;
+const RELATIVE_INDENT_PENALTY: i32 = -4;
+const RELATIVE_INDENT_WITH_BLANK_PENALTY: i32 = 10;
+const RELATIVE_OUTDENT_PENALTY: i32 = 24;
+const RELATIVE_OUTDENT_WITH_BLANK_PENALTY: i32 = 17;
+const RELATIVE_DEDENT_PENALTY: i32 = 23;
+const RELATIVE_DEDENT_WITH_BLANK_PENALTY: i32 = 17;
+const INDENT_WEIGHT: i32 = 60;
+
+/// The badness of a position of a slider, where lower is better.
+#[derive(Default, Debug, Clone, Copy)]
+struct Score {
+    effective_indent: i32,
+    penalty: i32,
+}
+
+impl Score {
+    fn add(&mut self, split: &Split) {
+        if split.pre_indent == -1 && split.pre_blank == 0 {
+            self.penalty += START_OF_FILE_PENALTY;
+        }
+        if split.end_of_file {
+            self.penalty += END_OF_FILE_PENALTY;
+        }
+
+        let post_blank = if split.indent == -1 { 1 + split.post_blank } else { 0 };
+        let total_blank = split.pre_blank + post_blank;
+        self.penalty += TOTAL_BLANK_WEIGHT * total_blank;
+        self.penalty += POST_BLANK_WEIGHT * post_blank;
+
+        let indent = if split.indent != -1 {
+            split.indent
+        } else {
+            split.post_indent
+        };
+        let any_blanks = total_blank != 0;
+        self.effective_indent += indent;
+
+        if indent == -1 || split.pre_indent == -1 || indent == split.pre_indent {
+            // No adjustments needed.
+        } else if indent > split.pre_indent {
+            self.penalty += if any_blanks {
+                RELATIVE_INDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_INDENT_PENALTY
+            };
+        } else if split.post_indent != -1 && split.post_indent > indent {
+            // The line is indented less than its predecessor, and the one after it more, so it likely starts a block.
+            self.penalty += if any_blanks {
+                RELATIVE_OUTDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_OUTDENT_PENALTY
+            };
+        } else {
+            // The line is indented less than its predecessor, so it likely ends a block.
+            self.penalty += if any_blanks {
+                RELATIVE_DEDENT_WITH_BLANK_PENALTY
+            } else {
+                RELATIVE_DEDENT_PENALTY
+            };
+        }
+    }
+
+    /// Return a negative value if `self` is better than `other`, 0 if they are equal, and a positive value otherwise.
+    fn cmp(&self, other: &Score) -> i32 {
+        let cmp_indents = (self.effective_indent > other.effective_indent) as i32
+            - (self.effective_indent < other.effective_indent) as i32;
+        INDENT_WEIGHT * cmp_indents + (self.penalty - other.penalty)
+    }
+}
@@ -0,0 +1,578 @@
+use std::io;
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    blob::{
+        platform::{prepare_diff, resource},
+        Platform,
+    },
+    quote::quote_path,
+};
+
+/// The amount of changes made to a single resource.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Counts {
+    /// The resource was diffed line by line.
+    Lines {
+        /// The amount of lines that were added.
+        insertions: u32,
+        /// The amount of lines that were removed.
+        removals: u32,
+    },
+    /// At least one side of the resource was considered binary, so only its size is known.
+    ///
+    /// Both sizes are `0` if the content didn't change, which happens if only the mode changed or with exact renames.
+    Binary {
+        /// The size of the old version of the resource in bytes, or `0` if it didn't exist.
+        old_size: u64,
+        /// The size of the new version of the resource in bytes, or `0` if it doesn't exist anymore.
+        new_size: u64,
+    },
+}
+
+impl Counts {
+    /// Return `true` if there are no changes to the content at all.
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Counts::Lines { insertions, removals } => insertions == 0 && removals == 0,
+            Counts::Binary { old_size, new_size } => old_size == 0 && new_size == 0,
+        }
+    }
+}
+
+/// The statistics of changes to a single file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The location of the file before it was renamed or copied to [`location`](Self::location), or `None` if it wasn't.
+    pub source_location: Option<BString>,
+    /// The location of the file, which is its old location if it was deleted.
+    pub location: BString,
+    /// The changes made to the content of the file.
+    pub counts: Counts,
+}
+
+impl File {
+    /// Return the name of this file as `git` would display it in diff statistics, which is quoted if needed
+    /// and looks like `dir/{old => new}/file` for renames and copies.
+    pub fn display_name(&self) -> BString {
+        match &self.source_location {
+            Some(source) if *source != self.location => rename_name(source.as_ref(), self.location.as_ref()),
+            _ => quote_path(self.location.as_ref()).into_owned(),
+        }
+    }
+}
+
+/// The statistics of changes to a set of files, for example as obtained by diffing two trees, in the order in which they should be displayed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// The statistics of each changed file.
+    pub files: Vec<File>,
+}
+
+///
+pub mod stat {
+    /// Options for use in [Stats::write_stat()](super::Stats::write_stat()).
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Options {
+        /// The amount of columns the output may take at most, defaulting to 80. This is similar to `git diff --stat=<width>`.
+        pub width: usize,
+        /// If set, the maximum amount of columns to use for the name of files, similar to `git diff --stat-name-width`.
+        pub name_width: Option<usize>,
+        /// If set, the maximum amount of columns to use for the graph, similar to `git diff --stat-graph-width`.
+        pub graph_width: Option<usize>,
+        /// If set, the amount of files to list at most, similar to `git diff --stat-count`.
+        pub count: Option<usize>,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                width: 80,
+                name_width: None,
+                graph_width: None,
+                count: None,
+            }
+        }
+    }
+}
+
+///
+pub mod dirstat {
+    /// Determine how the changes to a file are weighted when computing the changes of the directory it's in.
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Mode {
+        /// Count the amount of added and removed lines, like `git diff --dirstat=lines`.
+        ///
+        /// Binary files count one line for every 64 bytes of their old and new size.
+        #[default]
+        Lines,
+        /// Count each changed file once, like `git diff --dirstat=files`.
+        Files,
+    }
+
+    /// Options for use in [Stats::write_dirstat()](super::Stats::write_dirstat()).
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Options {
+        /// How to weigh the changes to each file.
+        pub mode: Mode,
+        /// The share of all changes a directory must have to be listed in permille, defaulting to `30`, which is 3%.
+        pub threshold_permille: u32,
+        /// If `true`, the changes of a listed directory also count towards the changes of its parent directory,
+        /// similar to `git diff --dirstat=cumulative`.
+        pub cumulative: bool,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                mode: Mode::default(),
+                threshold_permille: 30,
+                cumulative: false,
+            }
+        }
+    }
+}
+
+///
+pub mod from_changes {
+    /// The error returned by [Stats::from_changes()](super::Stats::from_changes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        SetResource(#[from] crate::blob::platform::set_resource::Error),
+        #[error(transparent)]
+        PrepareDiff(#[from] crate::blob::platform::prepare_diff::Error),
+    }
+}
+
+/// Statistics
+impl Platform {
+    /// Count the lines that were added to and removed from the [previously set](Self::set_resource()) resources,
+    /// or obtain their size if either of them is considered binary, which can be controlled with the `diff`
+    /// and `-diff` attributes.
+    ///
+    /// External diff commands are ignored and the internal diff algorithm is always used.
+    pub fn stats(&mut self) -> Result<Counts, prepare_diff::Error> {
+        let skip_internal_diff =
+            std::mem::replace(&mut self.options.skip_internal_diff_if_external_is_configured, false);
+        let counts = self.prepare_diff().map(counts_of_prepared_diff);
+        self.options.skip_internal_diff_if_external_is_configured = skip_internal_diff;
+        counts
+    }
+}
+
+fn counts_of_prepared_diff(prep: prepare_diff::Outcome<'_>) -> Counts {
+    match prep.operation {
+        prepare_diff::Operation::InternalDiff { algorithm } => {
+            let input = crate::blob::intern::InternedInput::new(prep.old.intern_source(), prep.new.intern_source());
+            let counter = crate::blob::diff(algorithm, &input, crate::blob::sink::Counter::default());
+            Counts::Lines {
+                insertions: counter.insertions,
+                removals: counter.removals,
+            }
+        }
+        prepare_diff::Operation::ExternalCommand { .. } => {
+            unreachable!("we disabled external commands")
+        }
+        prepare_diff::Operation::SourceOrDestinationIsBinary => {
+            if prep.old.id == prep.new.id && !prep.old.id.is_null() {
+                Counts::Binary {
+                    old_size: 0,
+                    new_size: 0,
+                }
+            } else {
+                let size = |data: resource::Data<'_>| match data {
+                    resource::Data::Missing => 0,
+                    resource::Data::Buffer(buf) => buf.len() as u64,
+                    resource::Data::Binary { size } => size,
+                };
+                Counts::Binary {
+                    old_size: size(prep.old.data),
+                    new_size: size(prep.new.data),
+                }
+            }
+        }
+    }
+}
+
+/// Lifecycle
+impl Stats {
+    /// Compute the statistics of all `changes` of a tree diff in the given order, using `resource_cache` to obtain
+    /// the diffable content of each file with the help of `objects`.
+    ///
+    /// Changes to trees are skipped, and changed submodules are counted like a single changed line, as `git` does.
+    ///
+    /// ### Warning about Memory Consumption
+    ///
+    /// `resource_cache` only grows, so one should call [`Platform::clear_resource_cache`] occasionally.
+    pub fn from_changes<'a>(
+        changes: impl IntoIterator<Item = crate::tree_with_rewrites::ChangeRef<'a>>,
+        resource_cache: &mut Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<Self, from_changes::Error> {
+        use crate::tree_with_rewrites::ChangeRef;
+
+        let mut files = Vec::new();
+        for change in changes {
+            let (source_location, source_mode) = match change {
+                ChangeRef::Addition { .. } => (None, None),
+                ChangeRef::Deletion { entry_mode, .. } => (None, Some(entry_mode)),
+                ChangeRef::Modification {
+                    previous_entry_mode, ..
+                } => (None, Some(previous_entry_mode)),
+                ChangeRef::Rewrite {
+                    source_location,
+                    source_entry_mode,
+                    ..
+                } => (Some(source_location.to_owned()), Some(source_entry_mode)),
+            };
+            let entry_mode = change.entry_mode();
+            if entry_mode.is_tree() {
+                continue;
+            }
+            let new_mode = (!matches!(change, ChangeRef::Deletion { .. })).then_some(entry_mode);
+            let counts =
+                if source_mode.is_some_and(|mode| mode.is_commit()) || new_mode.is_some_and(|mode| mode.is_commit()) {
+                    let (_, id) = change.entry_mode_and_id();
+                    let (_, source_id) = change.source_entry_mode_and_id();
+                    let changed =
+                        id != source_id || matches!(change, ChangeRef::Addition { .. } | ChangeRef::Deletion { .. });
+                    Counts::Lines {
+                        insertions: u32::from(changed && new_mode.is_some_and(|mode| mode.is_commit())),
+                        removals: u32::from(changed && source_mode.is_some_and(|mode| mode.is_commit())),
+                    }
+                } else {
+                    resource_cache.set_resource_by_change(change, objects)?;
+                    resource_cache.stats()?
+                };
+            files.push(File {
+                source_location,
+                location: change.location().to_owned(),
+                counts,
+            });
+        }
+        Ok(Stats { files })
+    }
+}
+
+/// Access
+impl Stats {
+    /// Return the total amount of lines that were added to all non-binary files.
+    pub fn insertions(&self) -> u64 {
+        self.line_totals().0
+    }
+
+    /// Return the total amount of lines that were removed from all non-binary files.
+    pub fn removals(&self) -> u64 {
+        self.line_totals().1
+    }
+
+    fn line_totals(&self) -> (u64, u64) {
+        self.files
+            .iter()
+            .fold((0, 0), |(insertions, removals), file| match file.counts {
+                Counts::Lines {
+                    insertions: added,
+                    removals: removed,
+                } => (insertions + u64::from(added), removals + u64::from(removed)),
+                Counts::Binary { .. } => (insertions, removals),
+            })
+    }
+}
+
+/// Output
+impl Stats {
+    /// Write the statistics of each file with a graph of the changes to `out`, followed by a summary line,
+    /// like `git diff --stat` does.
+    ///
+    /// Nothing is written if there are no files.
+    pub fn write_stat(&self, out: &mut dyn io::Write, options: &stat::Options) -> io::Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        let shown = &self.files[..options
+            .count
+            .map_or(self.files.len(), |count| count.min(self.files.len()))];
+        let names: Vec<_> = shown.iter().map(File::display_name).collect();
+
+        let mut max_len = 0;
+        let mut max_change = 0;
+        let mut number_width = 0;
+        let mut bin_width = 0;
+        for (file, name) in shown.iter().zip(&names) {
+            max_len = max_len.max(name.chars().count());
+            match file.counts {
+                Counts::Binary { old_size, new_size } => {
+                    bin_width = bin_width.max(14 + decimal_width(old_size) + decimal_width(new_size));
+                    number_width = 3;
+                }
+                Counts::Lines { insertions, removals } => {
+                    max_change = max_change.max(insertions as usize + removals as usize);
+                }
+            }
+        }
+        number_width = number_width.max(decimal_width(max_change as u64));
+        // Guarantee that the graph gets 6 and the name 10 columns.
+        let width = options.width.max(16 + 6 + number_width);
+
+        let mut graph_width = if max_change + 4 > bin_width {
+            max_change
+        } else {
+            bin_width - 4
+        };
+        if let Some(max_graph_width) = options.graph_width.filter(|width| *width < graph_width) {
+            graph_width = max_graph_width;
+        }
+        let mut name_width = options
+            .name_width
+            .filter(|width| *width > 0 && *width < max_len)
+            .unwrap_or(max_len);
+        if name_width + number_width + 6 + graph_width > width {
+            if graph_width > (width * 3 / 8).saturating_sub(number_width + 6) {
+                graph_width = (width * 3 / 8).saturating_sub(number_width + 6).max(6);
+            }
+            if let Some(max_graph_width) = options.graph_width.filter(|width| graph_width > *width) {
+                graph_width = max_graph_width;
+            }
+            if name_width > width.saturating_sub(number_width + 6 + graph_width) {
+                name_width = width.saturating_sub(number_width + 6 + graph_width);
+            } else {
+                graph_width = width - number_width - 6 - name_width;
+            }
+        }
+
+        for (file, name) in shown.iter().zip(&names) {
+            let mut name = name.as_bstr();
+            let mut prefix = "";
+            let mut len = name_width;
+            let name_len = name.chars().count();
+            if name_len > name_width {
+                prefix = "...";
+                len = len.saturating_sub(3);
+                let start = name
+                    .char_indices()
+                    .nth(name_len - len)
+                    .map_or(name.len(), |(start, ..)| start);
+                name = name[start..].as_bstr();
+                if let Some(slash) = name.find_byte(b'/') {
+                    name = name[slash..].as_bstr();
+                }
+            }
+            let padding = len.saturating_sub(name.chars().count());
+            write!(out, " {prefix}")?;
+            out.write_all(name)?;
+            write!(out, "{:padding$}", "")?;
+            match file.counts {
+                Counts::Binary { old_size, new_size } => {
+                    write!(out, " | {:>number_width$}", "Bin")?;
+                    if old_size != 0 || new_size != 0 {
+                        write!(out, " {old_size} -> {new_size} bytes")?;
+                    }
+                    writeln!(out)?;
+                }
+                Counts::Lines { insertions, removals } => {
+                    let (mut add, mut del) = (insertions as usize, removals as usize);
+                    let changes = add + del;
+                    if graph_width <= max_change {
+                        let mut total = scale_linear(add + del, graph_width, max_change);
+                        if total < 2 && add != 0 && del != 0 {
+                            total = 2;
+                        }
+                        if add < del {
+                            add = scale_linear(add, graph_width, max_change);
+                            del = total - add;
+                        } else {
+                            del = scale_linear(del, graph_width, max_change);
+                            add = total - del;
+                        }
+                    }
+                    write!(out, " | {changes:>number_width$}")?;
+                    if changes != 0 {
+                        out.write_all(b" ")?;
+                    }
+                    writeln!(out, "{}{}", "+".repeat(add), "-".repeat(del))?;
+                }
+            }
+        }
+        if shown.len() < self.files.len() {
+            writeln!(out, " ...")?;
+        }
+        self.write_shortstat(out)
+    }
+
+    /// Write the summary line with the amount of changed files, insertions and removals to `out`,
+    /// like `git diff --shortstat` does.
+    ///
+    /// Nothing is written if there are no files.
+    pub fn write_shortstat(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        let (insertions, removals) = self.line_totals();
+        let plural =
+            |count: u64, singular: &'static str, plural: &'static str| if count == 1 { singular } else { plural };
+        let files = self.files.len() as u64;
+        write!(out, " {files} {}", plural(files, "file changed", "files changed"))?;
+        if insertions != 0 || removals == 0 {
+            write!(
+                out,
+                ", {insertions} {}",
+                plural(insertions, "insertion(+)", "insertions(+)")
+            )?;
+        }
+        if removals != 0 || insertions == 0 {
+            write!(out, ", {removals} {}", plural(removals, "deletion(-)", "deletions(-)"))?;
+        }
+        writeln!(out)
+    }
+
+    /// Write the amount of added and removed lines of each file to `out` in a machine-readable format,
+    /// like `git diff --numstat` does. Binary files show `-` instead of the amount of lines.
+    pub fn write_numstat(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        for file in &self.files {
+            match file.counts {
+                Counts::Lines { insertions, removals } => write!(out, "{insertions}\t{removals}\t")?,
+                Counts::Binary { .. } => out.write_all(b"-\t-\t")?,
+            }
+            out.write_all(&file.display_name())?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Write the share of changes of each directory with at least the configured share of all changes to `out`,
+    /// like `git diff --dirstat` does.
+    ///
+    /// Files are attributed to the directory of their new location, and directories are only listed
+    /// if their changes aren't entirely made in a single sub-directory.
+    pub fn write_dirstat(&self, out: &mut dyn io::Write, options: &dirstat::Options) -> io::Result<()> {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .filter_map(|file| {
+                let damage = match (options.mode, file.counts) {
+                    (_, counts) if counts.is_empty() => 0,
+                    (dirstat::Mode::Files, _) => 1,
+                    (dirstat::Mode::Lines, Counts::Lines { insertions, removals }) => {
+                        u64::from(insertions) + u64::from(removals)
+                    }
+                    (dirstat::Mode::Lines, Counts::Binary { old_size, new_size }) => (old_size + new_size + 63) / 64,
+                };
+                (damage != 0).then_some((file.location.as_bstr(), damage))
+            })
+            .collect();
+        let total: u64 = files.iter().map(|(_, damage)| damage).sum();
+        if total == 0 {
+            return Ok(());
+        }
+        files.sort_by(|a, b| a.0.cmp(b.0));
+        let mut files = files.as_slice();
+        gather_dirstat(out, &mut files, total, "".into(), options)?;
+        Ok(())
+    }
+}
+
+/// Consume all `files` in `base` and return their changes, printing `base` if it has enough of the `total` changes.
+fn gather_dirstat(
+    out: &mut dyn io::Write,
+    files: &mut &[(&BStr, u64)],
+    total: u64,
+    base: &BStr,
+    options: &dirstat::Options,
+) -> io::Result<u64> {
+    let mut sum = 0;
+    let mut sources = 0;
+    while let Some(&(name, damage)) = files.first() {
+        if !name.starts_with(base) {
+            break;
+        }
+        match name[base.len()..].find_byte(b'/') {
+            Some(slash) => {
+                let sub_dir = name[..base.len() + slash + 1].as_bstr();
+                sum += gather_dirstat(out, files, total, sub_dir, options)?;
+                sources += 1;
+            }
+            None => {
+                sum += damage;
+                *files = &files[1..];
+                sources += 2;
+            }
+        }
+    }
+
+    // The top-level isn't shown, nor directories whose changes are all in a single sub-directory.
+    if !base.is_empty() && sources != 1 && sum != 0 {
+        let permille = sum * 1000 / total;
+        if permille >= u64::from(options.threshold_permille) {
+            out.write_all(format!("{:4}.{}% ", permille / 10, permille % 10).as_bytes())?;
+            out.write_all(base)?;
+            writeln!(out)?;
+            if !options.cumulative {
+                return Ok(0);
+            }
+        }
+    }
+    Ok(sum)
+}
+
+/// Return the name of a file renamed or copied from `old` to `new` like `dir/{old => new}/file`, with common parts
+/// factored out, like `git` displays it in diff statistics and summaries.
+///
+/// Both paths are quoted if needed.
+pub fn rename_name(old: &BStr, new: &BStr) -> BString {
+    let (old, new) = (quote_path(old), quote_path(new));
+    let (a, b) = (old.as_bytes(), new.as_bytes());
+
+    let mut prefix_len = 0;
+    for (pos, (x, y)) in a.iter().zip(b).enumerate() {
+        if x != y {
+            break;
+        }
+        if *x == b'/' {
+            prefix_len = pos + 1;
+        }
+    }
+
+    // Compare from the end, including the slash that ends the common prefix, if there is one.
+    let mut suffix_len = 0;
+    let min_len = prefix_len.saturating_sub(1);
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > min_len && j > min_len && a[i - 1] == b[j - 1] {
+        i -= 1;
+        j -= 1;
+        if a[i] == b'/' {
+            suffix_len = a.len() - i;
+        }
+    }
+
+    let a_mid = a.len().saturating_sub(prefix_len + suffix_len);
+    let b_mid = b.len().saturating_sub(prefix_len + suffix_len);
+    let mut out = BString::default();
+    let factored = prefix_len + suffix_len > 0;
+    if factored {
+        out.extend_from_slice(&a[..prefix_len]);
+        out.push(b'{');
+    }
+    out.extend_from_slice(&a[prefix_len..prefix_len + a_mid]);
+    out.extend_from_slice(b" => ");
+    out.extend_from_slice(&b[prefix_len..prefix_len + b_mid]);
+    if factored {
+        out.push(b'}');
+        out.extend_from_slice(&a[a.len() - suffix_len..]);
+    }
+    out
+}
+
+fn scale_linear(value: usize, width: usize, max_change: usize) -> usize {
+    if value == 0 {
+        0
+    } else {
+        1 + value * (width - 1) / max_change
+    }
+}
+
+fn decimal_width(value: u64) -> usize {
+    value.to_string().len()
+}
@@ -70,6 +70,7 @@ pub trait ConsumeHunk {
 
 pub(super) mod _impl {
     use super::{ConsumeHunk, ContextSize, NewlineSeparator};
+    use crate::blob::{funcname, Funcname};
     use bstr::{ByteSlice, ByteVec};
     use imara_diff::{intern, Sink};
     use intern::{InternedInput, Interner, Token};
@@ -89,17 +90,22 @@ pub(super) mod _impl {
         interner: &'a Interner<T>,
 
         pos: u32,
+        /// The position in `after` that corresponds to `pos`.
+        after_pos: u32,
         before_hunk_start: u32,
         after_hunk_start: u32,
         before_hunk_len: u32,
         after_hunk_len: u32,
         /// Symmetrical context before and after the changed hunk.
         ctx_size: u32,
+        /// Additional unchanged lines between changes that may be shown to place them into the same hunk.
+        inter_hunk_ctx_size: u32,
 
         buffer: Vec<u8>,
         header_buf: String,
         delegate: D,
         newline: NewlineSeparator<'a>,
+        section_headers: Option<funcname::Search<'a>>,
 
         err: Option<std::io::Error>,
     }
@@ -133,13 +139,30 @@ pub(super) mod _impl {
                 before: &input.before,
                 after: &input.after,
                 pos: 0,
+                after_pos: 0,
                 ctx_size: context_size.symmetrical,
+                inter_hunk_ctx_size: 0,
                 newline: newline_separator,
+                section_headers: None,
 
                 err: None,
             }
         }
 
+        /// Append the function or section each hunk belongs to to its header, as found by `funcname`
+        /// in the old version of the input, like `git diff` does.
+        pub fn with_section_headers(mut self, funcname: &'a Funcname) -> Self {
+            self.section_headers = Some(funcname.search());
+            self
+        }
+
+        /// Place changes into the same hunk if they are separated by no more than `lines` unchanged lines in addition to
+        /// the context lines of both, like `git diff --inter-hunk-context=<lines>` does.
+        pub fn with_inter_hunk_context(mut self, lines: u32) -> Self {
+            self.inter_hunk_ctx_size = lines;
+            self
+        }
+
         fn print_tokens(&mut self, tokens: &[Token], prefix: char) {
             for &token in tokens {
                 self.buffer.push_char(prefix);
@@ -167,15 +190,25 @@ pub(super) mod _impl {
             self.update_pos(end, end);
 
             self.header_buf.clear();
+            let (interner, before) = (self.interner, self.before);
+            let section_header = self
+                .section_headers
+                .as_mut()
+                .map(|search| {
+                    search.section_header(self.before_hunk_start, |line| interner[before[line as usize]].as_ref())
+                })
+                .filter(|header| !header.is_empty());
 
             std::fmt::Write::write_fmt(
                 &mut self.header_buf,
                 format_args!(
-                    "@@ -{},{} +{},{} @@{nl}",
+                    "@@ -{},{} +{},{} @@{space}{section_header}{nl}",
                     self.before_hunk_start + 1,
                     self.before_hunk_len,
                     self.after_hunk_start + 1,
                     self.after_hunk_len,
+                    space = if section_header.is_some() { " " } else { "" },
+                    section_header = section_header.map(|header| header.to_str_lossy()).unwrap_or_default(),
                     nl = match self.newline {
                         NewlineSeparator::AfterHeaderAndLine(nl) | NewlineSeparator::AfterHeaderAndWhenNeeded(nl) => {
                             nl
@@ -199,9 +232,14 @@ pub(super) mod _impl {
         }
 
         fn update_pos(&mut self, print_to: u32, move_to: u32) {
-            self.print_tokens(&self.before[self.pos as usize..print_to as usize], ' ');
             let len = print_to - self.pos;
+            // Like in `git`, unchanged lines are taken from `after` as they may differ if whitespace is ignored.
+            self.print_tokens(
+                &self.after[self.after_pos as usize..(self.after_pos + len) as usize],
+                ' ',
+            );
             self.pos = move_to;
+            self.after_pos += len;
             self.before_hunk_len += len;
             self.after_hunk_len += len;
         }
@@ -219,7 +257,7 @@ pub(super) mod _impl {
                 return;
             }
             if ((self.pos == 0) && (before.start - self.pos > self.ctx_size))
-                || (before.start - self.pos > 2 * self.ctx_size)
+                || (before.start - self.pos > 2 * self.ctx_size + self.inter_hunk_ctx_size)
             {
                 if let Err(err) = self.flush() {
                     self.err = Some(err);
@@ -228,8 +266,10 @@ pub(super) mod _impl {
                 self.pos = before.start - self.ctx_size;
                 self.before_hunk_start = self.pos;
                 self.after_hunk_start = after.start - self.ctx_size;
+                self.after_pos = self.after_hunk_start;
             }
             self.update_pos(before.start, before.end);
+            self.after_pos = after.end;
             self.before_hunk_len += before.end - before.start;
             self.after_hunk_len += after.end - after.start;
             self.print_tokens(&self.before[before.start as usize..before.end as usize], '-');
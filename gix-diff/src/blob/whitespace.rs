@@ -0,0 +1,246 @@
+//! Ignore changes in whitespace when diffing, like `git diff --ignore-space-change` and related flags do.
+//!
+//! Lines are compared after normalizing them, but the original lines are passed on, so that diffs show what's
+//! actually in the files.
+use std::{borrow::Cow, collections::HashMap, hash::Hash, ops::Range};
+
+use imara_diff::{
+    intern::{InternedInput, Interner, Token},
+    Algorithm, Sink,
+};
+
+/// Options to control which changes in whitespace are ignored.
+///
+/// The default is to not ignore anything.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// Ignore all whitespace when comparing lines, like `--ignore-all-space` (`-w`).
+    pub ignore_all_space: bool,
+    /// Ignore changes in the amount of whitespace, like `--ignore-space-change` (`-b`).
+    ///
+    /// Whitespace at the end of lines is ignored, and all other sequences of whitespace are considered equal.
+    pub ignore_space_change: bool,
+    /// Ignore changes in whitespace at the end of lines, like `--ignore-space-at-eol`.
+    pub ignore_space_at_eol: bool,
+    /// Ignore a carriage-return at the end of lines, like `--ignore-cr-at-eol`.
+    pub ignore_cr_at_eol: bool,
+    /// Ignore changes whose lines are all blank, like `--ignore-blank-lines`.
+    ///
+    /// Such changes are still shown if they are close to other changes and would be part of their hunk.
+    pub ignore_blank_lines: bool,
+}
+
+impl Options {
+    /// Return `true` if lines are compared after normalizing their whitespace.
+    pub fn ignores_whitespace_in_lines(&self) -> bool {
+        self.ignore_all_space || self.ignore_space_change || self.ignore_space_at_eol || self.ignore_cr_at_eol
+    }
+
+    /// Return `true` if nothing is ignored.
+    pub fn is_empty(&self) -> bool {
+        !self.ignores_whitespace_in_lines() && !self.ignore_blank_lines
+    }
+
+    /// Return `line` in a form that is equal to all other lines it should be considered equal to.
+    ///
+    /// If whitespace [is ignored](Self::ignores_whitespace_in_lines()), the line ending is ignored as well.
+    pub fn normalize<'a>(&self, line: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.ignore_all_space {
+            if line.iter().any(|b| is_space(*b)) {
+                Cow::Owned(line.iter().copied().filter(|b| !is_space(*b)).collect())
+            } else {
+                Cow::Borrowed(line)
+            }
+        } else if self.ignore_space_change {
+            let line = trim_end(line);
+            if line
+                .windows(2)
+                .any(|w| is_space(w[0]) && (w[0] != b' ' || is_space(w[1])))
+            {
+                let mut out = Vec::with_capacity(line.len());
+                let mut previous_is_space = false;
+                for &b in line {
+                    if is_space(b) {
+                        if !previous_is_space {
+                            out.push(b' ');
+                        }
+                        previous_is_space = true;
+                    } else {
+                        out.push(b);
+                        previous_is_space = false;
+                    }
+                }
+                Cow::Owned(out)
+            } else {
+                Cow::Borrowed(line)
+            }
+        } else if self.ignore_space_at_eol {
+            Cow::Borrowed(trim_end(line))
+        } else if self.ignore_cr_at_eol {
+            Cow::Borrowed(match line.strip_suffix(b"\n") {
+                Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+                None => line,
+            })
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    /// Return `true` if `line` is blank, which is the case if it only contains a line ending or, if whitespace
+    /// [is ignored](Self::ignores_whitespace_in_lines()), if it only consists of whitespace.
+    pub fn is_blank(&self, line: &[u8]) -> bool {
+        if self.ignores_whitespace_in_lines() {
+            line.iter().all(|b| is_space(*b))
+        } else {
+            line.len() <= 1
+        }
+    }
+
+    /// Return a token for each token in `interner`, which is the first token that is
+    /// [equal to it after normalization](Self::normalize()).
+    ///
+    /// Use it to map lines to tokens that can be compared to find changes while ignoring whitespace.
+    pub fn canonical_tokens<T: AsRef<[u8]>>(&self, interner: &Interner<T>) -> Vec<Token> {
+        let mut first_token_by_line = HashMap::<Cow<'_, [u8]>, Token>::new();
+        (0..interner.num_tokens())
+            .map(|token| {
+                let token = Token(token);
+                *first_token_by_line
+                    .entry(self.normalize(interner[token].as_ref()))
+                    .or_insert(token)
+            })
+            .collect()
+    }
+}
+
+/// Diff `input` with `algorithm` while ignoring whitespace according to `options`, and pass all changes to `sink`
+/// to return its result. Sliders are placed [like `git` does](crate::blob::sliders::diff()),
+/// using the `indent_heuristic` if `true`.
+///
+/// `context_lines` and `inter_hunk_context` are the settings of the hunks that will be produced by `sink`,
+/// as they decide which changes to blank lines are close enough to other changes to be shown
+/// if [blank lines are ignored](Options::ignore_blank_lines).
+pub fn diff<T, S>(
+    algorithm: Algorithm,
+    input: &InternedInput<T>,
+    options: &Options,
+    indent_heuristic: bool,
+    context_lines: u32,
+    inter_hunk_context: u32,
+    mut sink: S,
+) -> S::Out
+where
+    T: Eq + Hash + AsRef<[u8]>,
+    S: Sink,
+{
+    let normalized;
+    let (before, after) = if options.ignores_whitespace_in_lines() {
+        let canonical = options.canonical_tokens(&input.interner);
+        let map = |tokens: &[Token]| -> Vec<Token> { tokens.iter().map(|token| canonical[token.0 as usize]).collect() };
+        normalized = (map(&input.before), map(&input.after));
+        (normalized.0.as_slice(), normalized.1.as_slice())
+    } else {
+        (input.before.as_slice(), input.after.as_slice())
+    };
+    if !options.ignore_blank_lines {
+        return crate::blob::sliders::diff_tokens(algorithm, before, after, input, indent_heuristic, sink);
+    }
+
+    let mut changes = Vec::new();
+    crate::blob::sliders::diff_tokens(
+        algorithm,
+        before,
+        after,
+        input,
+        indent_heuristic,
+        |before: Range<u32>, after: Range<u32>| changes.push((before, after)),
+    );
+    let is_blank = |tokens: &[Token], range: &Range<u32>| {
+        tokens[range.start as usize..range.end as usize]
+            .iter()
+            .all(|token| options.is_blank(input.interner[*token].as_ref()))
+    };
+    let changes: Vec<_> = changes
+        .into_iter()
+        .map(|(before, after)| {
+            let ignore = is_blank(&input.before, &before) && is_blank(&input.after, &after);
+            Change { before, after, ignore }
+        })
+        .collect();
+    for change in shown_changes(&changes, context_lines, inter_hunk_context) {
+        sink.process_change(change.before.clone(), change.after.clone());
+    }
+    sink.finish()
+}
+
+struct Change {
+    before: Range<u32>,
+    after: Range<u32>,
+    /// `true` if all lines of the change are blank.
+    ignore: bool,
+}
+
+/// Return the `changes` that remain if changes to blank lines are ignored unless they are close to other changes.
+///
+/// This is a port of `xdl_get_hunk()` of `git`.
+fn shown_changes(changes: &[Change], context_lines: u32, inter_hunk_context: u32) -> impl Iterator<Item = &Change> {
+    let max_common = i64::from(2 * context_lines + inter_hunk_context);
+    let max_ignorable = i64::from(context_lines);
+    let distance = |previous: &Change, next: &Change| i64::from(next.before.start) - i64::from(previous.before.end);
+
+    let mut shown = Vec::new();
+    let mut start = 0;
+    while start < changes.len() {
+        let mut previous = start;
+        while previous < changes.len() && changes[previous].ignore {
+            if changes
+                .get(previous + 1)
+                .map_or(true, |next| distance(&changes[previous], next) >= max_ignorable)
+            {
+                start = previous + 1;
+            }
+            previous += 1;
+        }
+        if start == changes.len() {
+            break;
+        }
+
+        let mut last = start;
+        let mut ignored = 0;
+        for current in start + 1..changes.len() {
+            let (previous, change) = (current - 1, &changes[current]);
+            let distance = distance(&changes[previous], change);
+            if distance > max_common {
+                break;
+            }
+            if distance < max_ignorable && (!change.ignore || last == previous) {
+                last = current;
+                ignored = 0;
+            } else if distance < max_ignorable && change.ignore {
+                ignored += i64::from(change.after.end - change.after.start);
+            } else if last != previous
+                && i64::from(change.before.start) + ignored - i64::from(changes[last].before.end) > max_common
+            {
+                break;
+            } else if !change.ignore {
+                last = current;
+                ignored = 0;
+            } else {
+                ignored += i64::from(change.after.end - change.after.start);
+            }
+        }
+        shown.extend(start..=last);
+        start = last + 1;
+    }
+    shown.into_iter().map(move |idx| &changes[idx])
+}
+
+/// Like `isspace()` in C.
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
+
+fn trim_end(line: &[u8]) -> &[u8] {
+    let end = line.iter().rposition(|b| !is_space(*b)).map_or(0, |pos| pos + 1);
+    &line[..end]
+}
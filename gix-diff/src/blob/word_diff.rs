@@ -0,0 +1,389 @@
+//! Produce diffs that show changes word by word instead of line by line, similar to `git diff --word-diff`.
+use std::{io, ops::Range};
+
+use bstr::{BStr, BString, ByteSlice};
+use imara_diff::intern::InternedInput;
+
+use crate::blob::{platform::prepare_diff, sliders, sources::byte_lines_with_terminator, Algorithm, Platform};
+
+/// Options for use in [Platform::word_diff()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of unchanged lines to show around each change, defaulting to `3`.
+    pub context_lines: u32,
+    /// A regular expression to find words with, overriding any `wordRegex` of the diff drivers of the resources,
+    /// similar to `git diff --word-diff-regex=<regex>`.
+    pub regex: Option<BString>,
+    /// The regular expression to use if neither [`regex`](Self::regex) is set nor any of the diff drivers of the
+    /// resources has a `wordRegex`. It's the value of `diff.wordRegex`.
+    pub default_regex: Option<BString>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            regex: None,
+            default_regex: None,
+        }
+    }
+}
+
+/// The error returned by [Platform::word_diff()] and [hunks()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not compile the regular expression '{regex}' to find words with")]
+    Regex { regex: BString, source: regex::Error },
+    #[error(transparent)]
+    PrepareDiff(#[from] prepare_diff::Error),
+}
+
+/// Determine how a [`Hunk`] of a word-diff is written.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Mark removed words like `[-this-]` and added words like `{+this+}`, like `--word-diff=plain`.
+    #[default]
+    Plain,
+    /// A line-based format meant for consumption by scripts, like `--word-diff=porcelain`.
+    ///
+    /// Each run of unchanged, removed and added words is on its own line, prefixed with ` `, `-` and `+` respectively,
+    /// and the end of each line of the new file is marked with a line containing only `~`.
+    Porcelain,
+    /// Show removed words in red and added words in green using ANSI escape codes, like `--word-diff=color`.
+    Color,
+}
+
+/// A piece of a [`Hunk`] of a word-diff.
+///
+/// All lines end with a newline, even if the last line of a file has no line terminator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Part {
+    /// A whole line that is the same in both the old and the new file, including its line terminator.
+    Context(BString),
+    /// Text within changed lines that is the same in both files, which may span multiple lines.
+    Unchanged(BString),
+    /// Text that was removed from the old file, which may span multiple lines.
+    Removed(BString),
+    /// Text that was added in the new file, which may span multiple lines.
+    Added(BString),
+}
+
+/// A hunk of a word-diff, which spans the same lines as the respective hunk of a line-based unified diff.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The 1-based line in the old file at which the hunk starts, or the line after which lines are inserted if `old_len` is 0.
+    pub old_start: u32,
+    /// The amount of lines in the old file that this hunk spans.
+    pub old_len: u32,
+    /// The 1-based line in the new file at which the hunk starts, or the line after which lines were removed if `new_len` is 0.
+    pub new_start: u32,
+    /// The amount of lines in the new file that this hunk spans.
+    pub new_len: u32,
+    /// The parts of the hunk in order.
+    pub parts: Vec<Part>,
+}
+
+/// Word-diffs
+impl Platform {
+    /// Perform a word-diff of the [previously set](Self::set_resource()) resources, and return its hunks,
+    /// or `None` if either of them is considered binary.
+    ///
+    /// Words are runs of non-whitespace characters unless a regular expression is configured, in which case each
+    /// of its non-overlapping matches is a word. It's taken from `options`, or from the `wordRegex` of the diff driver
+    /// of the old or the new resource in that order, configured with the `diff=<driver>` attribute.
+    ///
+    /// External diff commands are ignored and the internal diff algorithm is always used.
+    pub fn word_diff(&mut self, options: &Options) -> Result<Option<Vec<Hunk>>, Error> {
+        let regex = options.regex.clone().or_else(|| {
+            let (old, new) = self.resources()?;
+            let word_regex = |idx: Option<usize>| idx.and_then(|idx| self.filter.drivers[idx].word_regex.clone());
+            word_regex(old.driver_index).or_else(|| word_regex(new.driver_index))
+        });
+        let regex = regex.or_else(|| options.default_regex.clone());
+
+        let skip_internal_diff =
+            std::mem::replace(&mut self.options.skip_internal_diff_if_external_is_configured, false);
+        let context_lines = options.context_lines;
+        let hunks = self
+            .prepare_diff()
+            .map_err(Error::from)
+            .and_then(|prep| match prep.operation {
+                prepare_diff::Operation::InternalDiff { algorithm } => hunks(
+                    prep.old.data.as_slice().unwrap_or_default(),
+                    prep.new.data.as_slice().unwrap_or_default(),
+                    algorithm,
+                    context_lines,
+                    regex.as_ref().map(AsRef::as_ref),
+                )
+                .map(Some),
+                prepare_diff::Operation::ExternalCommand { .. } => {
+                    unreachable!("we disabled external commands")
+                }
+                prepare_diff::Operation::SourceOrDestinationIsBinary => Ok(None),
+            });
+        self.options.skip_internal_diff_if_external_is_configured = skip_internal_diff;
+        hunks
+    }
+}
+
+/// Diff the lines of `old` and `new` with `algorithm`, and return the hunks of a word-diff with `context_lines` of
+/// unchanged lines around each change, like `git diff --word-diff -U<context_lines>` does.
+///
+/// Lines that changed are diffed word by word, where words are runs of non-whitespace characters, or each match of `regex`
+/// if it is set. Its matches never span multiple lines, and finding words stops at the first empty match.
+/// Note that `regex` uses the syntax of the `regex` crate and isn't a POSIX extended regular expression like in `git`.
+pub fn hunks(
+    old: &[u8],
+    new: &[u8],
+    algorithm: Algorithm,
+    context_lines: u32,
+    regex: Option<&BStr>,
+) -> Result<Vec<Hunk>, Error> {
+    let regex = regex
+        .map(|regex| {
+            regex
+                .to_str()
+                .map_err(|err| regex::Error::Syntax(err.to_string()))
+                .and_then(|pattern| {
+                    regex::bytes::RegexBuilder::new(pattern)
+                        .unicode(false)
+                        .multi_line(true)
+                        .build()
+                })
+                .map_err(|source| Error::Regex {
+                    regex: regex.to_owned(),
+                    source,
+                })
+        })
+        .transpose()?;
+
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut changes = Vec::<(Range<u32>, Range<u32>)>::new();
+    sliders::diff(algorithm, &input, true, |before: Range<u32>, after: Range<u32>| {
+        changes.push((before, after));
+    });
+
+    let text = |tokens: &[imara_diff::intern::Token], range: Range<u32>| {
+        let mut text = BString::default();
+        for token in &tokens[range.start as usize..range.end as usize] {
+            let line = input.interner[*token];
+            text.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                text.push(b'\n');
+            }
+        }
+        text
+    };
+    let old_len = input.before.len() as u32;
+    let mut hunks = Vec::new();
+    let mut group_start = 0;
+    while group_start < changes.len() {
+        let mut group_end = group_start;
+        while changes
+            .get(group_end + 1)
+            .is_some_and(|next| next.0.start - changes[group_end].0.end <= 2 * context_lines)
+        {
+            group_end += 1;
+        }
+        let (first, last) = (&changes[group_start], &changes[group_end]);
+        let leading = first.0.start.min(context_lines);
+        let trailing = (old_len - last.0.end).min(context_lines);
+        let old_range = first.0.start - leading..last.0.end + trailing;
+        let new_range = first.1.start - leading..last.1.end + trailing;
+
+        let mut parts = Vec::new();
+        let mut pos = old_range.start;
+        let context = |parts: &mut Vec<Part>, range: Range<u32>| {
+            parts.extend(
+                input.before[range.start as usize..range.end as usize]
+                    .iter()
+                    .map(|token| Part::Context(text(&[*token], 0..1))),
+            );
+        };
+        for (before, after) in &changes[group_start..=group_end] {
+            context(&mut parts, pos..before.start);
+            diff_words(
+                &text(&input.before, before.clone()),
+                &text(&input.after, after.clone()),
+                regex.as_ref(),
+                &mut parts,
+            );
+            pos = before.end;
+        }
+        context(&mut parts, pos..old_range.end);
+
+        let start = |range: &Range<u32>| if range.is_empty() { range.start } else { range.start + 1 };
+        hunks.push(Hunk {
+            old_start: start(&old_range),
+            old_len: old_range.len() as u32,
+            new_start: start(&new_range),
+            new_len: new_range.len() as u32,
+            parts,
+        });
+        group_start = group_end + 1;
+    }
+    Ok(hunks)
+}
+
+/// Diff the words of `minus` and `plus` and append the resulting parts to `out`.
+fn diff_words(minus: &[u8], plus: &[u8], regex: Option<&regex::bytes::Regex>, out: &mut Vec<Part>) {
+    if plus.is_empty() {
+        if !minus.is_empty() {
+            out.push(Part::Removed(minus.into()));
+        }
+        return;
+    }
+    let (minus_words, plus_words) = (words(minus, regex), words(plus, regex));
+    let mut input = InternedInput::default();
+    input.update_before(minus_words.iter().map(|word| &minus[word.clone()]));
+    input.update_after(plus_words.iter().map(|word| &plus[word.clone()]));
+
+    // An empty range is located at the end of the word before it.
+    let span = |words: &[Range<usize>], range: Range<u32>| {
+        if range.is_empty() {
+            let end = range.start.checked_sub(1).map_or(0, |prev| words[prev as usize].end);
+            end..end
+        } else {
+            words[range.start as usize].start..words[range.end as usize - 1].end
+        }
+    };
+    let mut current_plus = 0;
+    sliders::diff(
+        Algorithm::Myers,
+        &input,
+        false,
+        |before: Range<u32>, after: Range<u32>| {
+            let (minus_span, plus_span) = (span(&minus_words, before), span(&plus_words, after));
+            if current_plus != plus_span.start {
+                out.push(Part::Unchanged(plus[current_plus..plus_span.start].into()));
+            }
+            if !minus_span.is_empty() {
+                out.push(Part::Removed(minus[minus_span].into()));
+            }
+            if !plus_span.is_empty() {
+                out.push(Part::Added(plus[plus_span.clone()].into()));
+            }
+            current_plus = plus_span.end;
+        },
+    );
+    if current_plus != plus.len() {
+        out.push(Part::Unchanged(plus[current_plus..].into()));
+    }
+}
+
+/// Return the ranges of all words in `text`, either as matched by `regex` or as runs of non-whitespace characters.
+fn words(text: &[u8], regex: Option<&regex::bytes::Regex>) -> Vec<Range<usize>> {
+    let is_space = |b: u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+    let mut words = Vec::new();
+    let mut begin = 0;
+    while begin < text.len() {
+        if let Some(m) = regex.and_then(|regex| regex.find_at(text, begin)) {
+            let end = text[m.start()..m.end()]
+                .find_byte(b'\n')
+                .map_or(m.end(), |pos| m.start() + pos);
+            if m.start() == end {
+                break;
+            }
+            words.push(m.start()..end);
+            begin = end;
+            continue;
+        }
+        let Some(start) = text[begin..].iter().position(|b| !is_space(*b)).map(|pos| begin + pos) else {
+            break;
+        };
+        let end = text[start..]
+            .iter()
+            .position(|b| is_space(*b))
+            .map_or(text.len(), |pos| start + pos);
+        words.push(start..end);
+        begin = end;
+    }
+    words
+}
+
+impl Hunk {
+    /// Write this hunk to `out` in the given `style`, starting with its `@@` header line, like `git diff --word-diff=<style>`.
+    pub fn write_to(&self, out: &mut dyn io::Write, style: Style) -> io::Result<()> {
+        let color = style == Style::Color;
+        if color {
+            out.write_all(b"\x1b[36m")?;
+        }
+        write!(out, "@@ -")?;
+        write_range(out, self.old_start, self.old_len)?;
+        write!(out, " +")?;
+        write_range(out, self.new_start, self.new_len)?;
+        out.write_all(if color { b" @@\x1b[m\n" } else { b" @@\n" })?;
+
+        let (unchanged, removed, added, newline): (Element<'_>, Element<'_>, Element<'_>, &[u8]) = match style {
+            Style::Plain => (("", "", ""), ("", "[-", "-]"), ("", "{+", "+}"), b"\n"),
+            Style::Porcelain => (("", " ", "\n"), ("", "-", "\n"), ("", "+", "\n"), b"~\n"),
+            Style::Color => (("", "", ""), ("\x1b[31m", "", ""), ("\x1b[32m", "", ""), b"\n"),
+        };
+        for part in &self.parts {
+            match part {
+                Part::Context(line) => match style {
+                    Style::Plain => out.write_all(line)?,
+                    Style::Porcelain => {
+                        out.write_all(b" ")?;
+                        out.write_all(line)?;
+                        out.write_all(b"~\n")?;
+                    }
+                    Style::Color => {
+                        let content = line.strip_suffix(b"\n").unwrap_or(line);
+                        let (content, cr) = match content.strip_suffix(b"\r") {
+                            Some(content) => (content, "\r"),
+                            None => (content, ""),
+                        };
+                        if !content.is_empty() {
+                            out.write_all(content)?;
+                            out.write_all(b"\x1b[m")?;
+                        }
+                        writeln!(out, "{cr}")?;
+                    }
+                },
+                Part::Unchanged(text) => write_element(out, unchanged, newline, text)?,
+                Part::Removed(text) => write_element(out, removed, newline, text)?,
+                Part::Added(text) => write_element(out, added, newline, text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The color, prefix and suffix of an element of a word-diff.
+type Element<'a> = (&'a str, &'a str, &'a str);
+
+/// Write `text` split into lines, each wrapped into `element` and followed by `newline` if it was terminated.
+fn write_element(
+    out: &mut dyn io::Write,
+    (color, prefix, suffix): Element<'_>,
+    newline: &[u8],
+    text: &[u8],
+) -> io::Result<()> {
+    for line in text.lines_with_terminator() {
+        let content = line.strip_suffix(b"\n");
+        let has_newline = content.is_some();
+        let content = content.unwrap_or(line);
+        if !content.is_empty() {
+            write!(out, "{color}{prefix}")?;
+            out.write_all(content)?;
+            write!(out, "{suffix}")?;
+            if !color.is_empty() {
+                out.write_all(b"\x1b[m")?;
+            }
+        }
+        if has_newline {
+            out.write_all(newline)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_range(out: &mut dyn io::Write, start: u32, len: u32) -> io::Result<()> {
+    if len == 1 {
+        write!(out, "{start}")
+    } else {
+        write!(out, "{start},{len}")
+    }
+}
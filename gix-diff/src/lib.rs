@@ -67,3 +67,10 @@ pub use index::function::diff as index;
 ///
 #[cfg(feature = "blob")]
 pub mod blob;
+
+///
+#[cfg(feature = "patch")]
+pub mod patch;
+
+#[cfg(any(feature = "blob", feature = "patch"))]
+mod quote;
@@ -0,0 +1,198 @@
+use bstr::ByteSlice;
+
+use crate::patch::{binary, BinaryData, Content, File, Hunk, Line};
+
+/// The error returned by [`apply()`](crate::patch::apply()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The patch for the binary file doesn't contain the data needed to apply it")]
+    BinaryWithoutData,
+    #[error("The binary patch doesn't apply as the file it was made for has a different content")]
+    BinaryPreimageMismatch,
+    #[error("The binary delta could not be applied as it is corrupt or was made for a different file")]
+    BinaryDelta,
+}
+
+/// Options for use in [`apply()`](crate::patch::apply()).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The maximum amount of context lines at the beginning and at the end of a hunk that may be ignored when
+    /// searching for a place to apply it, like `--fuzz` of GNU `patch`.
+    ///
+    /// With the typical 3 lines of context, a `fuzz` of 1 is the same as `git apply -C2`.
+    /// Note that anchoring hunks at the beginning or the end of a file is only enforced without fuzz.
+    pub fuzz: u32,
+}
+
+/// What happened to a single hunk when [applying](crate::patch::apply()) a patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkOutcome {
+    /// The hunk was applied.
+    Applied {
+        /// The amount of lines the hunk was moved relative to where it was expected, which accounts for the
+        /// hunks applied before it.
+        offset: i64,
+        /// The amount of context lines that were ignored at the beginning and the end of the hunk to make it apply.
+        fuzz: u32,
+    },
+    /// The hunk couldn't be applied as its context or the lines it removes couldn't be found.
+    Rejected,
+}
+
+/// The result of [`apply()`](crate::patch::apply()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The content of the file after applying all hunks that could be applied.
+    pub data: Vec<u8>,
+    /// The outcome of each hunk, in order, or an empty list for binary patches.
+    pub hunks: Vec<HunkOutcome>,
+}
+
+impl Outcome {
+    /// Return `true` if at least one hunk was rejected, which means [`data`](Self::data) isn't the full result.
+    pub fn has_rejected_hunks(&self) -> bool {
+        self.hunks.iter().any(|h| matches!(h, HunkOutcome::Rejected))
+    }
+
+    /// Return the indices of all hunks that were rejected.
+    pub fn rejected_hunks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.hunks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, h)| matches!(h, HunkOutcome::Rejected).then_some(idx))
+    }
+}
+
+/// Apply the changes to the content in `patch` to `old`, the content of the file before the change, similar to `git apply`,
+/// and return the content of the file after the change, along with information about each hunk.
+///
+/// Text hunks are searched for close to where they are expected, and those that can't be placed are rejected,
+/// which is no error. Use `options` to allow ignoring context lines if hunks can't be placed otherwise.
+/// Binary patches apply fully or fail with an error.
+///
+/// Note that changes to the mode, name or existence of the file have to be handled by the caller.
+pub fn apply(old: &[u8], patch: &File, options: Options) -> Result<Outcome, Error> {
+    Ok(match &patch.content {
+        Content::Text(hunks) => apply_hunks(old, hunks, options),
+        Content::Binary(binary) => {
+            if let Some(old_id) = patch.old_id.filter(|id| !id.as_oid().is_null()) {
+                let actual = gix_object::compute_hash(old_id.as_oid().kind(), gix_object::Kind::Blob, old);
+                if old_id.cmp_oid(&actual).is_ne() {
+                    return Err(Error::BinaryPreimageMismatch);
+                }
+            }
+            let data = match &binary.forward {
+                BinaryData::Literal(data) => data.clone(),
+                BinaryData::Delta(delta) => binary::apply_delta(old, delta).ok_or(Error::BinaryDelta)?,
+            };
+            Outcome {
+                data,
+                hunks: Vec::new(),
+            }
+        }
+        Content::BinaryWithoutData => return Err(Error::BinaryWithoutData),
+    })
+}
+
+fn apply_hunks(old: &[u8], hunks: &[Hunk], options: Options) -> Outcome {
+    let mut image: Vec<&[u8]> = old.lines_with_terminator().collect();
+    // How much the hunks applied so far deviated from their expected position.
+    let mut slip = 0i64;
+    let mut outcomes = Vec::with_capacity(hunks.len());
+    for hunk in hunks {
+        let preimage: Vec<&[u8]> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Context(line) | Line::Removed(line) => Some(line.as_slice()),
+                Line::Added(_) => None,
+            })
+            .collect();
+        let postimage: Vec<&[u8]> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Context(line) | Line::Added(line) => Some(line.as_slice()),
+                Line::Removed(_) => None,
+            })
+            .collect();
+        let leading = hunk.lines.iter().take_while(|l| matches!(l, Line::Context(_))).count();
+        let trailing = hunk
+            .lines
+            .iter()
+            .rev()
+            .take_while(|l| matches!(l, Line::Context(_)))
+            .count()
+            .min(hunk.lines.len() - leading);
+        // Hunks before this one were already applied, so we are working in the coordinates of the new file.
+        let expected = i64::from(if hunk.new_len == 0 {
+            hunk.new_start
+        } else {
+            hunk.new_start.saturating_sub(1)
+        }) + slip;
+        // Hunks without any context, as produced by `git diff -U0`, can't be anchored.
+        let may_anchor = leading != 0 || trailing != 0;
+
+        let mut outcome = HunkOutcome::Rejected;
+        for fuzz in 0..=options.fuzz {
+            let cut_leading = leading.min(fuzz as usize);
+            let cut_trailing = trailing.min(fuzz as usize);
+            if fuzz > 0 && cut_leading + cut_trailing == 0 {
+                break;
+            }
+            let pre = &preimage[cut_leading..preimage.len() - cut_trailing];
+            let post = &postimage[cut_leading..postimage.len() - cut_trailing];
+            let match_beginning = fuzz == 0 && may_anchor && hunk.old_start <= 1;
+            let match_end = fuzz == 0 && may_anchor && trailing == 0;
+            if let Some(pos) = find_position(&image, pre, expected + cut_leading as i64, match_beginning, match_end) {
+                image.splice(pos..pos + pre.len(), post.iter().copied());
+                let offset = pos as i64 - cut_leading as i64 - expected;
+                slip += offset;
+                outcome = HunkOutcome::Applied { offset, fuzz };
+                break;
+            }
+        }
+        if outcome == HunkOutcome::Rejected {
+            slip -= i64::from(hunk.new_len) - i64::from(hunk.old_len);
+        }
+        outcomes.push(outcome);
+    }
+    Outcome {
+        data: image.concat(),
+        hunks: outcomes,
+    }
+}
+
+/// Find the position of `preimage` in `image`, starting at `expected` and moving away from it in both directions.
+fn find_position(
+    image: &[&[u8]],
+    preimage: &[&[u8]],
+    expected: i64,
+    match_beginning: bool,
+    match_end: bool,
+) -> Option<usize> {
+    let max = image.len().checked_sub(preimage.len())?;
+    let matches = |pos: usize| image[pos..][..preimage.len()] == *preimage;
+    if match_beginning {
+        return (matches(0) && (!match_end || max == 0)).then_some(0);
+    }
+    if match_end {
+        return matches(max).then_some(max);
+    }
+    let expected = expected.clamp(0, max as i64) as usize;
+    for distance in 0..=max {
+        let backward = expected.checked_sub(distance);
+        let forward = Some(expected + distance).filter(|pos| *pos <= max);
+        if backward.is_none() && forward.is_none() {
+            break;
+        }
+        if let Some(pos) = backward.filter(|pos| matches(*pos)) {
+            return Some(pos);
+        }
+        if let Some(pos) = forward.filter(|pos| matches(*pos)) {
+            return Some(pos);
+        }
+    }
+    None
+}
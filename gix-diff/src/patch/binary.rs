@@ -0,0 +1,141 @@
+//! Encoding and decoding of binary patches as produced by `git diff --binary`.
+
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+fn base85_value(byte: u8) -> Option<u32> {
+    BASE85_ALPHABET.iter().position(|b| *b == byte).map(|pos| pos as u32)
+}
+
+/// Decode a single `line` of base85 data without its line terminator, prefixed with a character that encodes
+/// the amount of decoded bytes, and append the decoded bytes to `out`.
+///
+/// Return `None` if the line isn't valid.
+pub(crate) fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let (len, encoded) = line.split_first()?;
+    let mut len = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if encoded.len() != (len + 3) / 4 * 5 {
+        return None;
+    }
+    for chunk in encoded.chunks(5) {
+        let mut acc = 0u32;
+        for byte in chunk {
+            acc = acc.checked_mul(85)?.checked_add(base85_value(*byte)?)?;
+        }
+        let bytes = acc.to_be_bytes();
+        let take = len.min(4);
+        out.extend_from_slice(&bytes[..take]);
+        len -= take;
+    }
+    Some(())
+}
+
+/// Decompress the zlib stream in `data` which is expected to inflate to exactly `size` bytes.
+///
+/// Return `None` if `size` is more than `data` could possibly inflate to, to not allocate memory on the claim of the patch.
+pub(crate) fn inflate(data: &[u8], size: usize) -> Option<Vec<u8>> {
+    // Even perfectly compressible data can't be compressed more than that.
+    const MAX_DEFLATE_RATIO: usize = 1032;
+    if size > data.len().saturating_mul(MAX_DEFLATE_RATIO) {
+        return None;
+    }
+    let mut out = vec![0; size.checked_add(1)?];
+    let (status, _consumed, written) = gix_features::zlib::Inflate::default().once(data, &mut out).ok()?;
+    if status != gix_features::zlib::Status::StreamEnd || written != size {
+        return None;
+    }
+    out.truncate(size);
+    Some(out)
+}
+
+fn decode_size(delta: &mut &[u8]) -> Option<usize> {
+    let mut size = 0usize;
+    let mut shift = 0;
+    loop {
+        let (byte, rest) = delta.split_first()?;
+        *delta = rest;
+        size |= ((byte & 0x7f) as usize).checked_shl(shift)?;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(size);
+        }
+    }
+}
+
+/// Apply `delta` in git's delta format to `base`, and return the result, or `None` if `delta` is invalid or doesn't apply to `base`.
+pub(crate) fn apply_delta(base: &[u8], mut delta: &[u8]) -> Option<Vec<u8>> {
+    let base_size = decode_size(&mut delta)?;
+    if base_size != base.len() {
+        return None;
+    }
+    let result_size = decode_size(&mut delta)?;
+    let mut out = Vec::with_capacity(result_size.min(base.len() + delta.len()));
+    while let Some((&cmd, rest)) = delta.split_first() {
+        delta = rest;
+        if cmd & 0x80 != 0 {
+            let mut offset = 0usize;
+            let mut size = 0usize;
+            for (bit, shift) in [(0x01, 0), (0x02, 8), (0x04, 16), (0x08, 24)] {
+                if cmd & bit != 0 {
+                    let (byte, rest) = delta.split_first()?;
+                    delta = rest;
+                    offset |= (*byte as usize) << shift;
+                }
+            }
+            for (bit, shift) in [(0x10, 0), (0x20, 8), (0x40, 16)] {
+                if cmd & bit != 0 {
+                    let (byte, rest) = delta.split_first()?;
+                    delta = rest;
+                    size |= (*byte as usize) << shift;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            out.extend_from_slice(base.get(offset..offset.checked_add(size)?)?);
+        } else if cmd != 0 {
+            let size = cmd as usize;
+            out.extend_from_slice(delta.get(..size)?);
+            delta = &delta[size..];
+        } else {
+            return None;
+        }
+    }
+    (out.len() == result_size).then_some(out)
+}
+
+/// Encode `data` of up to 52 bytes as a single line of base85 data, prefixed with a character that encodes
+/// the amount of bytes, and append it without line terminator to `out`.
+pub(crate) fn encode_base85_line(data: &[u8], out: &mut Vec<u8>) {
+    debug_assert!(
+        !data.is_empty() && data.len() <= 52,
+        "{} bytes can't be encoded",
+        data.len()
+    );
+    let len = data.len() as u8;
+    out.push(if len <= 26 { b'A' + len - 1 } else { b'a' + len - 27 });
+    for chunk in data.chunks(4) {
+        let mut bytes = [0u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let mut acc = u32::from_be_bytes(bytes);
+        let mut encoded = [0u8; 5];
+        for byte in encoded.iter_mut().rev() {
+            *byte = BASE85_ALPHABET[(acc % 85) as usize];
+            acc /= 85;
+        }
+        out.extend_from_slice(&encoded);
+    }
+}
+
+/// Compress `data` into a zlib stream.
+pub(crate) fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut out = gix_features::zlib::stream::deflate::Write::new(Vec::new());
+    out.write_all(data)?;
+    out.flush()?;
+    Ok(out.into_inner())
+}
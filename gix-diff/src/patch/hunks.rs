@@ -0,0 +1,159 @@
+use std::ops::Range;
+
+use imara_diff::intern::InternedInput;
+
+use crate::{
+    blob::{sources::byte_lines_with_terminator, whitespace, Algorithm, Funcname},
+    patch::{Hunk, Line},
+};
+
+/// Options for use in [`hunks_with_options()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options<'a> {
+    /// The amount of unchanged lines to show around each change, like `git diff -U<context_lines>`, defaulting to `3`.
+    pub context_lines: u32,
+    /// The amount of unchanged lines between changes that may additionally be shown to place them into the same hunk,
+    /// like `git diff --inter-hunk-context=<n>`, defaulting to `0`.
+    pub inter_hunk_context: u32,
+    /// If `true`, the indentation of lines is used to place groups of changed lines that could also be shown elsewhere,
+    /// like `diff.indentHeuristic`, defaulting to `true` like in `git`.
+    pub indent_heuristic: bool,
+    /// Changes in whitespace to ignore, with nothing being ignored by default.
+    pub whitespace: whitespace::Options,
+    /// If set, the section header of each hunk is set to the function or section it belongs to as found by this instance.
+    pub funcname: Option<&'a Funcname>,
+}
+
+impl Default for Options<'_> {
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            inter_hunk_context: 0,
+            indent_heuristic: true,
+            whitespace: Default::default(),
+            funcname: None,
+        }
+    }
+}
+
+/// Diff the lines of `old` and `new` with `algorithm` and return the hunks of a unified diff with `context_lines`
+/// of unchanged lines around each change, like `git diff -U<context_lines>` does.
+///
+/// Changes that are separated by no more than twice the amount of context lines are placed into the same hunk,
+/// and the indent heuristic is used. The section header of each hunk is left empty.
+pub fn hunks(old: &[u8], new: &[u8], algorithm: Algorithm, context_lines: u32) -> Vec<Hunk> {
+    hunks_with_options(
+        old,
+        new,
+        algorithm,
+        &Options {
+            context_lines,
+            ..Default::default()
+        },
+    )
+}
+
+/// Like [`hunks()`], but set the section header of each hunk to the function or section it belongs to
+/// as found by `funcname` in `old`.
+pub fn hunks_with_section_headers(
+    old: &[u8],
+    new: &[u8],
+    algorithm: Algorithm,
+    context_lines: u32,
+    funcname: &Funcname,
+) -> Vec<Hunk> {
+    hunks_with_options(
+        old,
+        new,
+        algorithm,
+        &Options {
+            context_lines,
+            funcname: Some(funcname),
+            ..Default::default()
+        },
+    )
+}
+
+/// Diff the lines of `old` and `new` with `algorithm` and return the hunks of a unified diff as configured by `options`.
+///
+/// Groups of changed lines are placed like `git` does, see [`sliders`](crate::blob::sliders) for details,
+/// and changes that are separated by no more than twice the amount of context lines plus the inter-hunk context
+/// are placed into the same hunk.
+/// Lines that are equal when [ignoring whitespace](whitespace::Options) are shown as they are in `new`.
+pub fn hunks_with_options(old: &[u8], new: &[u8], algorithm: Algorithm, options: &Options<'_>) -> Vec<Hunk> {
+    let Options {
+        context_lines,
+        inter_hunk_context,
+        indent_heuristic,
+        whitespace,
+        funcname,
+    } = *options;
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut changes = Vec::<(Range<u32>, Range<u32>)>::new();
+    whitespace::diff(
+        algorithm,
+        &input,
+        &whitespace,
+        indent_heuristic,
+        context_lines,
+        inter_hunk_context,
+        |before: Range<u32>, after: Range<u32>| {
+            changes.push((before, after));
+        },
+    );
+
+    let lines = |tokens: &[imara_diff::intern::Token], range: Range<u32>| {
+        tokens[range.start as usize..range.end as usize]
+            .iter()
+            .map(|token| input.interner[*token].into())
+            .collect::<Vec<_>>()
+    };
+    let old_len = input.before.len() as u32;
+    let mut search = funcname.map(Funcname::search);
+    let mut hunks = Vec::new();
+    let mut group_start = 0;
+    while group_start < changes.len() {
+        let mut group_end = group_start;
+        while changes
+            .get(group_end + 1)
+            .is_some_and(|next| next.0.start - changes[group_end].0.end <= 2 * context_lines + inter_hunk_context)
+        {
+            group_end += 1;
+        }
+        let (first, last) = (&changes[group_start], &changes[group_end]);
+        let leading = first.0.start.min(context_lines);
+        let trailing = (old_len - last.0.end).min(context_lines);
+        let old_range = first.0.start - leading..last.0.end + trailing;
+        let new_range = first.1.start - leading..last.1.end + trailing;
+
+        // Like in `git`, unchanged lines are taken from `new` as they may differ if whitespace is ignored.
+        let mut hunk_lines = Vec::new();
+        let mut pos = new_range.start;
+        for (before, after) in &changes[group_start..=group_end] {
+            hunk_lines.extend(lines(&input.after, pos..after.start).into_iter().map(Line::Context));
+            hunk_lines.extend(lines(&input.before, before.clone()).into_iter().map(Line::Removed));
+            hunk_lines.extend(lines(&input.after, after.clone()).into_iter().map(Line::Added));
+            pos = after.end;
+        }
+        hunk_lines.extend(lines(&input.after, pos..new_range.end).into_iter().map(Line::Context));
+
+        let start = |range: &Range<u32>| if range.is_empty() { range.start } else { range.start + 1 };
+        hunks.push(Hunk {
+            old_start: start(&old_range),
+            old_len: old_range.len() as u32,
+            new_start: start(&new_range),
+            new_len: new_range.len() as u32,
+            section_header: search
+                .as_mut()
+                .map(|search| {
+                    search
+                        .section_header(old_range.start, |line| input.interner[input.before[line as usize]])
+                        .to_owned()
+                })
+                .unwrap_or_default(),
+            lines: hunk_lines,
+        });
+        group_start = group_end + 1;
+    }
+    hunks
+}
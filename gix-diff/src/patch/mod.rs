@@ -0,0 +1,137 @@
+//! Parse patches in the unified diff format as produced by `git diff` and `git format-patch`, and apply them to blobs,
+//! similar to `git apply`, and produce them.
+use bstr::BString;
+use gix_object::tree::EntryMode;
+
+/// A patch for a single file, as parsed from the output of `git diff` or other tools producing unified diffs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The path of the file before the change, or `None` if the file was added.
+    ///
+    /// The first path component, like `a/`, is removed.
+    pub old_path: Option<BString>,
+    /// The path of the file after the change, or `None` if the file was deleted.
+    ///
+    /// The first path component, like `b/`, is removed.
+    pub new_path: Option<BString>,
+    /// The mode of the file before the change, if known.
+    pub old_mode: Option<EntryMode>,
+    /// The mode of the file after the change, if known.
+    pub new_mode: Option<EntryMode>,
+    /// The possibly abbreviated id of the blob before the change, as seen in the `index` header line.
+    pub old_id: Option<gix_hash::Prefix>,
+    /// The possibly abbreviated id of the blob after the change, as seen in the `index` header line.
+    pub new_id: Option<gix_hash::Prefix>,
+    /// What happens to the file.
+    pub operation: Operation,
+    /// The changes to the content of the file.
+    pub content: Content,
+}
+
+/// What happens to a file in a [patch](File).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// The file is modified in place, which includes changes to its mode only.
+    Modification,
+    /// The file is added.
+    Addition,
+    /// The file is deleted.
+    Deletion,
+    /// The file at `old_path` is moved to `new_path`, possibly with modifications.
+    Rename {
+        /// The similarity of both files in percent, if it was provided.
+        similarity: Option<u8>,
+    },
+    /// The file at `old_path` is copied to `new_path`, possibly with modifications.
+    Copy {
+        /// The similarity of both files in percent, if it was provided.
+        similarity: Option<u8>,
+    },
+}
+
+/// The changes to the content of a [file](File).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    /// Changes to text, in hunks, which may be empty if only the mode or the location of a file changes.
+    Text(Vec<Hunk>),
+    /// Changes to binary data as produced by `git diff --binary`.
+    Binary(Binary),
+    /// Binary files differ, but the patch doesn't contain the data to make the change, as in `Binary files a/x and b/x differ`.
+    BinaryWithoutData,
+}
+
+/// A section of lines that changed in a text file, as introduced by a `@@ -1,2 +1,3 @@` header line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The 1-based line in the old file at which the hunk starts, or the line after which lines are inserted if `old_len` is 0.
+    pub old_start: u32,
+    /// The amount of lines in the old file that this hunk spans.
+    pub old_len: u32,
+    /// The 1-based line in the new file at which the hunk starts, or the line after which lines were removed if `new_len` is 0.
+    pub new_start: u32,
+    /// The amount of lines in the new file that this hunk spans.
+    pub new_len: u32,
+    /// The text after the second `@@` in the hunk header, typically the function the hunk is in, without leading or trailing whitespace.
+    pub section_header: BString,
+    /// The lines of the hunk in order.
+    pub lines: Vec<Line>,
+}
+
+/// A line in a [`Hunk`].
+///
+/// Its content includes the line terminator unless the line is the last one of a file without a trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// A line that is the same in both the old and the new file.
+    Context(BString),
+    /// A line that only exists in the old file.
+    Removed(BString),
+    /// A line that only exists in the new file.
+    Added(BString),
+}
+
+impl Line {
+    /// Return the content of the line, without the leading ` `, `-` or `+`.
+    pub fn content(&self) -> &BString {
+        match self {
+            Line::Context(line) | Line::Removed(line) | Line::Added(line) => line,
+        }
+    }
+}
+
+/// Changes to binary data, as produced by `git diff --binary`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binary {
+    /// The data to turn the old file into the new file.
+    pub forward: BinaryData,
+    /// The data to turn the new file into the old file, if present.
+    pub reverse: Option<BinaryData>,
+}
+
+/// The decoded and decompressed payload of a [binary patch](Binary).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryData {
+    /// The full content of the resulting file.
+    Literal(Vec<u8>),
+    /// Instructions in git's delta format to produce the resulting file from the source file.
+    Delta(Vec<u8>),
+}
+
+mod binary;
+
+///
+pub mod parse;
+pub use parse::parse;
+
+///
+pub mod apply;
+pub use apply::apply;
+
+mod write;
+pub use crate::quote::quote_path;
+
+///
+#[cfg(feature = "blob")]
+pub mod hunks;
+#[cfg(feature = "blob")]
+pub use hunks::{hunks, hunks_with_options, hunks_with_section_headers};
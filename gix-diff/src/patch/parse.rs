@@ -0,0 +1,374 @@
+use bstr::{BString, ByteSlice};
+use gix_object::tree::EntryMode;
+
+use crate::patch::{binary, Binary, BinaryData, Content, File, Hunk, Line, Operation};
+
+/// The error returned by [`parse()`](crate::patch::parse()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not parse hunk header in line {line_number}: {line:?}")]
+    HunkHeader { line_number: usize, line: BString },
+    #[error("The hunk starting at line {line_number} has fewer lines than announced in its header")]
+    TruncatedHunk { line_number: usize },
+    #[error("Could not parse file mode in line {line_number}: {line:?}")]
+    Mode { line_number: usize, line: BString },
+    #[error("Could not parse object ids in line {line_number}: {line:?}")]
+    Index { line_number: usize, line: BString },
+    #[error("Could not determine the file names of the patch in line {line_number}")]
+    FileName { line_number: usize },
+    #[error("The binary patch data in line {line_number} is corrupt")]
+    Binary { line_number: usize },
+}
+
+/// Parse all patches for files in `input`, which is typically the output of `git diff`, `git diff --binary`,
+/// `git format-patch` or other tools that produce diffs in the unified format, like `diff -u`.
+///
+/// Everything that isn't a patch, like commit messages or email headers, is ignored.
+/// Paths have their first component, like `a/` and `b/`, removed, which is the same as the default of `git apply`.
+pub fn parse(input: &[u8]) -> Result<Vec<File>, Error> {
+    let lines: Vec<&[u8]> = input.lines_with_terminator().collect();
+    let mut files = Vec::new();
+    let mut pos = 0;
+    while pos < lines.len() {
+        let line = lines[pos];
+        if let Some(names) = line.strip_prefix(b"diff --git ") {
+            let (old_path, new_path) = git_header_names(trim_newline(names)).unzip();
+            pos += 1;
+            let file = parse_git_file(&lines, &mut pos, old_path, new_path)?;
+            files.push(file);
+        } else if line.starts_with(b"--- ")
+            && lines.get(pos + 1).is_some_and(|l| l.starts_with(b"+++ "))
+            && lines.get(pos + 2).is_some_and(|l| l.starts_with(b"@@ -"))
+        {
+            let old_path = traditional_name(&line[4..]);
+            let new_path = traditional_name(&lines[pos + 1][4..]);
+            pos += 2;
+            let operation = match (&old_path, &new_path) {
+                (None, _) => Operation::Addition,
+                (_, None) => Operation::Deletion,
+                _ => Operation::Modification,
+            };
+            files.push(File {
+                old_path,
+                new_path,
+                old_mode: None,
+                new_mode: None,
+                old_id: None,
+                new_id: None,
+                operation,
+                content: Content::Text(parse_hunks(&lines, &mut pos)?),
+            });
+        } else {
+            pos += 1;
+        }
+    }
+    Ok(files)
+}
+
+fn parse_git_file(
+    lines: &[&[u8]],
+    pos: &mut usize,
+    old_path: Option<BString>,
+    new_path: Option<BString>,
+) -> Result<File, Error> {
+    let header_line_number = *pos;
+    let mut file = File {
+        old_path,
+        new_path,
+        old_mode: None,
+        new_mode: None,
+        old_id: None,
+        new_id: None,
+        operation: Operation::Modification,
+        content: Content::Text(Vec::new()),
+    };
+    let mut similarity = None;
+    while let Some(line) = lines.get(*pos) {
+        let line_number = *pos + 1;
+        let line = trim_newline(line);
+        if let Some(mode) = line.strip_prefix(b"old mode ") {
+            file.old_mode = Some(parse_mode(mode, line, line_number)?);
+        } else if let Some(mode) = line.strip_prefix(b"new mode ") {
+            file.new_mode = Some(parse_mode(mode, line, line_number)?);
+        } else if let Some(mode) = line.strip_prefix(b"deleted file mode ") {
+            file.old_mode = Some(parse_mode(mode, line, line_number)?);
+            file.operation = Operation::Deletion;
+        } else if let Some(mode) = line.strip_prefix(b"new file mode ") {
+            file.new_mode = Some(parse_mode(mode, line, line_number)?);
+            file.operation = Operation::Addition;
+        } else if let Some(path) = line.strip_prefix(b"rename from ") {
+            file.old_path = Some(unquote_if_needed(path));
+            file.operation = Operation::Rename { similarity };
+        } else if let Some(path) = line.strip_prefix(b"rename to ") {
+            file.new_path = Some(unquote_if_needed(path));
+            file.operation = Operation::Rename { similarity };
+        } else if let Some(path) = line.strip_prefix(b"copy from ") {
+            file.old_path = Some(unquote_if_needed(path));
+            file.operation = Operation::Copy { similarity };
+        } else if let Some(path) = line.strip_prefix(b"copy to ") {
+            file.new_path = Some(unquote_if_needed(path));
+            file.operation = Operation::Copy { similarity };
+        } else if let Some(percentage) = line.strip_prefix(b"similarity index ") {
+            similarity = percentage
+                .strip_suffix(b"%")
+                .and_then(|p| p.to_str().ok())
+                .and_then(|p| p.parse().ok());
+            match &mut file.operation {
+                Operation::Rename { similarity: s } | Operation::Copy { similarity: s } => *s = similarity,
+                _ => {}
+            }
+        } else if line.starts_with(b"dissimilarity index ") {
+        } else if let Some(ids) = line.strip_prefix(b"index ") {
+            let err = || Error::Index {
+                line_number,
+                line: line.into(),
+            };
+            let (ids, mode) = match ids.find_byte(b' ') {
+                Some(pos) => (&ids[..pos], Some(&ids[pos + 1..])),
+                None => (ids, None),
+            };
+            let Some((old, new)) = ids.split_once_str("..") else {
+                return Err(err());
+            };
+            file.old_id = Some(parse_prefix(old).ok_or_else(err)?);
+            file.new_id = Some(parse_prefix(new).ok_or_else(err)?);
+            if let Some(mode) = mode {
+                let mode = parse_mode(mode, line, line_number)?;
+                file.old_mode = Some(mode);
+                file.new_mode = Some(mode);
+            }
+        } else if line.starts_with(b"--- ") && lines.get(*pos + 1).is_some_and(|l| l.starts_with(b"+++ ")) {
+            // The names in the `diff --git` line are ambiguous if they contain spaces, these are not.
+            if let Some(path) = traditional_name(&line[4..]) {
+                file.old_path = Some(path);
+            }
+            if let Some(path) = traditional_name(&lines[*pos + 1][4..]) {
+                file.new_path = Some(path);
+            }
+            *pos += 2;
+            file.content = Content::Text(parse_hunks(lines, pos)?);
+            break;
+        } else if line.starts_with(b"Binary files ") && line.ends_with(b" differ") {
+            file.content = Content::BinaryWithoutData;
+            *pos += 1;
+            break;
+        } else if line == b"GIT binary patch" {
+            *pos += 1;
+            let forward = parse_binary_data(lines, pos)?.ok_or(Error::Binary { line_number })?;
+            let reverse = parse_binary_data(lines, pos)?;
+            file.content = Content::Binary(Binary { forward, reverse });
+            break;
+        } else {
+            break;
+        }
+        *pos += 1;
+    }
+
+    match file.operation {
+        Operation::Addition => file.old_path = None,
+        Operation::Deletion => file.new_path = None,
+        _ => {}
+    }
+    if file.old_path.is_none() && file.new_path.is_none() {
+        return Err(Error::FileName {
+            line_number: header_line_number,
+        });
+    }
+    Ok(file)
+}
+
+fn parse_hunks(lines: &[&[u8]], pos: &mut usize) -> Result<Vec<Hunk>, Error> {
+    let mut hunks = Vec::new();
+    while let Some(line) = lines.get(*pos).filter(|l| l.starts_with(b"@@ -")) {
+        let line_number = *pos + 1;
+        let (old_start, old_len, new_start, new_len, section_header) = parse_hunk_header(trim_newline(line))
+            .ok_or_else(|| Error::HunkHeader {
+                line_number,
+                line: trim_newline(line).into(),
+            })?;
+        *pos += 1;
+
+        let truncated = || Error::TruncatedHunk { line_number };
+        let mut old_remaining = old_len;
+        let mut new_remaining = new_len;
+        let mut hunk_lines = Vec::<Line>::new();
+        while old_remaining > 0 || new_remaining > 0 {
+            let line = lines.get(*pos).ok_or_else(truncated)?;
+            let (first, content) = line.split_first().ok_or_else(truncated)?;
+            match first {
+                b' ' | b'\n' | b'\r' => {
+                    let content = if *first == b' ' { content } else { *line };
+                    old_remaining = old_remaining.checked_sub(1).ok_or_else(truncated)?;
+                    new_remaining = new_remaining.checked_sub(1).ok_or_else(truncated)?;
+                    hunk_lines.push(Line::Context(content.into()));
+                }
+                b'-' => {
+                    old_remaining = old_remaining.checked_sub(1).ok_or_else(truncated)?;
+                    hunk_lines.push(Line::Removed(content.into()));
+                }
+                b'+' => {
+                    new_remaining = new_remaining.checked_sub(1).ok_or_else(truncated)?;
+                    hunk_lines.push(Line::Added(content.into()));
+                }
+                b'\\' => strip_last_newline(&mut hunk_lines),
+                _ => return Err(truncated()),
+            }
+            *pos += 1;
+        }
+        if lines.get(*pos).is_some_and(|l| l.starts_with(b"\\")) {
+            strip_last_newline(&mut hunk_lines);
+            *pos += 1;
+        }
+        hunks.push(Hunk {
+            old_start,
+            old_len,
+            new_start,
+            new_len,
+            section_header: section_header.into(),
+            lines: hunk_lines,
+        });
+    }
+    Ok(hunks)
+}
+
+/// Handle `\ No newline at end of file`, which refers to the previous line.
+fn strip_last_newline(lines: &mut [Line]) {
+    if let Some(Line::Context(line) | Line::Removed(line) | Line::Added(line)) = lines.last_mut() {
+        if line.ends_with(b"\n") {
+            line.pop();
+        }
+    }
+}
+
+/// Parse `@@ -1,2 +3,4 @@ section`.
+fn parse_hunk_header(line: &[u8]) -> Option<(u32, u32, u32, u32, &[u8])> {
+    let rest = line.strip_prefix(b"@@ -")?;
+    let (old, rest) = rest.split_once_str(" +")?;
+    let (new, rest) = rest.split_once_str(" @@")?;
+    let parse_range = |range: &[u8]| -> Option<(u32, u32)> {
+        let range = range.to_str().ok()?;
+        Some(match range.split_once(',') {
+            Some((start, len)) => (start.parse().ok()?, len.parse().ok()?),
+            None => (range.parse().ok()?, 1),
+        })
+    };
+    let (old_start, old_len) = parse_range(old)?;
+    let (new_start, new_len) = parse_range(new)?;
+    Some((old_start, old_len, new_start, new_len, rest.trim()))
+}
+
+/// Parse the data of a `literal` or `delta` section of a binary patch, which ends with an empty line.
+/// Return `None` if there is no such section.
+fn parse_binary_data(lines: &[&[u8]], pos: &mut usize) -> Result<Option<BinaryData>, Error> {
+    let Some(header) = lines.get(*pos).map(|l| trim_newline(l)) else {
+        return Ok(None);
+    };
+    let line_number = *pos + 1;
+    let (is_literal, size) = if let Some(size) = header.strip_prefix(b"literal ") {
+        (true, size)
+    } else if let Some(size) = header.strip_prefix(b"delta ") {
+        (false, size)
+    } else {
+        return Ok(None);
+    };
+    let size: usize = size
+        .to_str()
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(Error::Binary { line_number })?;
+    *pos += 1;
+
+    let mut compressed = Vec::new();
+    loop {
+        let line_number = *pos + 1;
+        let line = trim_newline(lines.get(*pos).ok_or(Error::Binary { line_number })?);
+        *pos += 1;
+        if line.is_empty() {
+            break;
+        }
+        binary::decode_base85_line(line, &mut compressed).ok_or(Error::Binary { line_number })?;
+    }
+    let data = binary::inflate(&compressed, size).ok_or(Error::Binary { line_number })?;
+    Ok(Some(if is_literal {
+        BinaryData::Literal(data)
+    } else {
+        BinaryData::Delta(data)
+    }))
+}
+
+fn parse_mode(mode: &[u8], line: &[u8], line_number: usize) -> Result<EntryMode, Error> {
+    u32::from_str_radix(mode.to_str().unwrap_or_default(), 8)
+        .ok()
+        .and_then(|mode| EntryMode::try_from(mode).ok())
+        .ok_or_else(|| Error::Mode {
+            line_number,
+            line: line.into(),
+        })
+}
+
+fn parse_prefix(hex: &[u8]) -> Option<gix_hash::Prefix> {
+    gix_hash::Prefix::from_hex(hex.to_str().ok()?).ok()
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Parse the path in a `---` or `+++` line, with `/dev/null` being `None`.
+fn traditional_name(name: &[u8]) -> Option<BString> {
+    let name = trim_newline(name);
+    let name = if name.starts_with(b"\"") {
+        unquote(name).map(|(name, _)| name)?
+    } else {
+        // A tab separates the path from a timestamp, or marks the end of paths with spaces in them.
+        name.split_str("\t").next().unwrap_or(name).into()
+    };
+    (name != "/dev/null").then(|| strip_first_component(&name))
+}
+
+/// Parse the names in `a/old b/new`, which are ambiguous if unquoted and containing spaces.
+/// In that case, we assume both names are the same, as there would be `rename` headers otherwise.
+fn git_header_names(names: &[u8]) -> Option<(BString, BString)> {
+    if names.starts_with(b"\"") {
+        let (old, consumed) = unquote(names)?;
+        let new = names[consumed..].strip_prefix(b" ")?;
+        return Some((
+            strip_first_component(&old),
+            strip_first_component(&unquote_if_needed(new)),
+        ));
+    }
+    if names.ends_with(b"\"") {
+        let pos = names.rfind(b" \"")?;
+        let new = unquote(&names[pos + 1..])?.0;
+        return Some((strip_first_component(&names[..pos]), strip_first_component(&new)));
+    }
+    names
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b' ')
+        .map(|(pos, _)| {
+            (
+                strip_first_component(&names[..pos]),
+                strip_first_component(&names[pos + 1..]),
+            )
+        })
+        .find(|(old, new)| old == new)
+}
+
+fn strip_first_component(path: &[u8]) -> BString {
+    path.find_byte(b'/').map_or(path, |pos| &path[pos + 1..]).into()
+}
+
+fn unquote_if_needed(path: &[u8]) -> BString {
+    unquote(path).map_or_else(|| path.into(), |(path, _)| path)
+}
+
+/// Undo C-style quoting of `input` if it starts with a `"`, and return the unquoted string along with the amount
+/// of bytes consumed, including both quotes.
+fn unquote(input: &[u8]) -> Option<(BString, usize)> {
+    gix_quote::ansi_c::undo(input.as_bstr())
+        .ok()
+        .map(|(unquoted, consumed)| (unquoted.into_owned(), consumed))
+}
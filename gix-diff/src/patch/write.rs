@@ -0,0 +1,200 @@
+use std::io;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::{
+    patch::{binary, Binary, BinaryData, Content, File, Hunk, Line, Operation},
+    quote::{needs_quoting, quote_into},
+};
+
+/// The maximum amount of bytes encoded in a single line of a binary patch.
+const BINARY_BYTES_PER_LINE: usize = 52;
+
+impl File {
+    /// Write this patch to `out` in the format produced by `git diff`, using the `a/` and `b/` prefixes for paths.
+    ///
+    /// Paths that contain special characters are quoted like `git` does, and binary data is compressed and
+    /// encoded as in `git diff --binary`. Note that the compressed data isn't necessarily the same as the one
+    /// `git` would produce, but decodes to the same bytes.
+    ///
+    /// # Panics
+    ///
+    /// If neither [`old_path`](Self::old_path) nor [`new_path`](Self::new_path) is set.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let old_path = self
+            .old_path
+            .as_ref()
+            .or(self.new_path.as_ref())
+            .expect("a patch has at least one path")
+            .as_bstr();
+        let new_path = self.new_path.as_ref().map_or(old_path, |path| path.as_bstr());
+
+        out.write_all(b"diff --git ")?;
+        write_path(out, b"a/", old_path)?;
+        out.write_all(b" ")?;
+        write_path(out, b"b/", new_path)?;
+        out.write_all(b"\n")?;
+
+        match self.operation {
+            Operation::Addition => {
+                if let Some(mode) = self.new_mode {
+                    writeln!(out, "new file mode {:06o}", *mode)?;
+                }
+            }
+            Operation::Deletion => {
+                if let Some(mode) = self.old_mode {
+                    writeln!(out, "deleted file mode {:06o}", *mode)?;
+                }
+            }
+            Operation::Modification | Operation::Rename { .. } | Operation::Copy { .. } => {
+                if let Some((old_mode, new_mode)) = self.old_mode.zip(self.new_mode).filter(|(old, new)| old != new) {
+                    writeln!(out, "old mode {:06o}", *old_mode)?;
+                    writeln!(out, "new mode {:06o}", *new_mode)?;
+                }
+            }
+        }
+        if let Operation::Rename { similarity } | Operation::Copy { similarity } = self.operation {
+            let verb = if matches!(self.operation, Operation::Rename { .. }) {
+                "rename"
+            } else {
+                "copy"
+            };
+            if let Some(similarity) = similarity {
+                writeln!(out, "similarity index {similarity}%")?;
+            }
+            write!(out, "{verb} from ")?;
+            write_path(out, b"", old_path)?;
+            write!(out, "\n{verb} to ")?;
+            write_path(out, b"", new_path)?;
+            out.write_all(b"\n")?;
+        }
+        if let Some((old_id, new_id)) = self.old_id.zip(self.new_id) {
+            write!(out, "index {old_id}..{new_id}")?;
+            let unchanged_mode = match self.operation {
+                Operation::Addition | Operation::Deletion => None,
+                _ => self.old_mode.zip(self.new_mode).filter(|(old, new)| old == new),
+            };
+            if let Some((mode, _)) = unchanged_mode {
+                write!(out, " {:06o}", *mode)?;
+            }
+            out.write_all(b"\n")?;
+        }
+
+        let old_name = (self.operation != Operation::Addition).then_some(old_path);
+        let new_name = (self.operation != Operation::Deletion).then_some(new_path);
+        match &self.content {
+            Content::Text(hunks) if hunks.is_empty() => {}
+            Content::Text(hunks) => {
+                out.write_all(b"--- ")?;
+                write_file_name(out, b"a/", old_name)?;
+                out.write_all(b"+++ ")?;
+                write_file_name(out, b"b/", new_name)?;
+                for hunk in hunks {
+                    hunk.write_to(out)?;
+                }
+            }
+            Content::Binary(Binary { forward, reverse }) => {
+                out.write_all(b"GIT binary patch\n")?;
+                write_binary_data(out, forward)?;
+                if let Some(reverse) = reverse {
+                    write_binary_data(out, reverse)?;
+                }
+            }
+            Content::BinaryWithoutData => {
+                out.write_all(b"Binary files ")?;
+                write_binary_file_name(out, b"a/", old_name)?;
+                out.write_all(b" and ")?;
+                write_binary_file_name(out, b"b/", new_name)?;
+                out.write_all(b" differ\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Hunk {
+    /// Write this hunk to `out`, starting with its `@@` header line, in the format produced by `git diff`.
+    ///
+    /// Lines without a trailing newline are followed by `\ No newline at end of file`.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        out.write_all(b"@@ -")?;
+        write_range(out, self.old_start, self.old_len)?;
+        out.write_all(b" +")?;
+        write_range(out, self.new_start, self.new_len)?;
+        out.write_all(b" @@")?;
+        if !self.section_header.is_empty() {
+            out.write_all(b" ")?;
+            out.write_all(&self.section_header)?;
+        }
+        out.write_all(b"\n")?;
+        for line in &self.lines {
+            let (prefix, content) = match line {
+                Line::Context(content) => (b' ', content),
+                Line::Removed(content) => (b'-', content),
+                Line::Added(content) => (b'+', content),
+            };
+            out.write_all(&[prefix])?;
+            out.write_all(content)?;
+            if content.last() != Some(&b'\n') {
+                out.write_all(b"\n\\ No newline at end of file\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_range(out: &mut dyn io::Write, start: u32, len: u32) -> io::Result<()> {
+    if len == 1 {
+        write!(out, "{start}")
+    } else {
+        write!(out, "{start},{len}")
+    }
+}
+
+fn write_file_name(out: &mut dyn io::Write, prefix: &[u8], path: Option<&BStr>) -> io::Result<()> {
+    match path {
+        Some(path) => {
+            write_path(out, prefix, path)?;
+            if path.contains(&b' ') {
+                out.write_all(b"\t")?;
+            }
+        }
+        None => out.write_all(b"/dev/null")?,
+    }
+    out.write_all(b"\n")
+}
+
+fn write_binary_file_name(out: &mut dyn io::Write, prefix: &[u8], path: Option<&BStr>) -> io::Result<()> {
+    match path {
+        Some(path) => write_path(out, prefix, path),
+        None => out.write_all(b"/dev/null"),
+    }
+}
+
+/// Write `prefix` and `path`, and quote both like `git` does if `path` contains special characters.
+fn write_path(out: &mut dyn io::Write, prefix: &[u8], path: &BStr) -> io::Result<()> {
+    if !needs_quoting(path) {
+        out.write_all(prefix)?;
+        return out.write_all(path);
+    }
+    let mut buf = Vec::with_capacity(prefix.len() + path.len() + 2);
+    quote_into(&mut buf, prefix, path);
+    out.write_all(&buf)
+}
+
+fn write_binary_data(out: &mut dyn io::Write, data: &BinaryData) -> io::Result<()> {
+    let (kind, data) = match data {
+        BinaryData::Literal(data) => ("literal", data),
+        BinaryData::Delta(data) => ("delta", data),
+    };
+    writeln!(out, "{kind} {}", data.len())?;
+    let compressed = binary::deflate(data)?;
+    let mut line = Vec::with_capacity(1 + BINARY_BYTES_PER_LINE / 4 * 5 + 1);
+    for chunk in compressed.chunks(BINARY_BYTES_PER_LINE) {
+        line.clear();
+        binary::encode_base85_line(chunk, &mut line);
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    out.write_all(b"\n")
+}
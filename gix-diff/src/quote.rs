@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+
+use bstr::BStr;
+
+pub(crate) fn needs_quoting(path: &BStr) -> bool {
+    path.iter()
+        .any(|&b| !(0x20..0x7f).contains(&b) || b == b'"' || b == b'\\')
+}
+
+/// Return `path` in double quotes with special characters escaped if it contains any, like `git` does
+/// in the headers of patches and in diff statistics, or return it unchanged otherwise.
+pub fn quote_path(path: &BStr) -> Cow<'_, BStr> {
+    if !needs_quoting(path) {
+        return Cow::Borrowed(path);
+    }
+    let mut buf = Vec::with_capacity(path.len() + 2);
+    quote_into(&mut buf, b"", path);
+    Cow::Owned(buf.into())
+}
+
+pub(crate) fn quote_into(buf: &mut Vec<u8>, prefix: &[u8], path: &BStr) {
+    buf.push(b'"');
+    buf.extend_from_slice(prefix);
+    for &b in path.iter() {
+        let escaped: &[u8] = match b {
+            b'\x07' => b"\\a",
+            b'\x08' => b"\\b",
+            b'\t' => b"\\t",
+            b'\n' => b"\\n",
+            b'\x0b' => b"\\v",
+            b'\x0c' => b"\\f",
+            b'\r' => b"\\r",
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b if !(0x20..0x7f).contains(&b) => {
+                buf.extend_from_slice(format!("\\{b:03o}").as_bytes());
+                continue;
+            }
+            b => {
+                buf.push(b);
+                continue;
+            }
+        };
+        buf.extend_from_slice(escaped);
+    }
+    buf.push(b'"');
+}
@@ -0,0 +1,224 @@
+use gix_diff::blob::{
+    funcname,
+    unified_diff::{ContextSize, NewlineSeparator},
+    word_diff, Algorithm, Driver, Funcname, UnifiedDiff,
+};
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::blob::word_diff::{new_platform, set_resources};
+
+#[test]
+fn section_headers_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_funcname_repo.sh")?;
+    for (name, driver) in [
+        ("code.rs", Some("rust")),
+        ("code.py", Some("python")),
+        ("code.cpp", Some("cpp")),
+        ("code.java", Some("java")),
+        ("code.go", Some("golang")),
+        ("code.adb", Some("ada")),
+        ("code.sh", Some("bash")),
+        ("code.tex", Some("tex")),
+        ("default", None),
+    ] {
+        let funcname = match driver {
+            Some(driver) => {
+                let driver = Driver::builtin(driver.into()).expect("known driver");
+                Funcname::new(driver.funcname.expect("set").as_ref(), driver.funcname_ignore_case)?
+            }
+            None => Funcname::default(),
+        };
+        let old = std::fs::read(root.join(format!("{name}.old")))?;
+        let new = std::fs::read(root.join(format!("{name}.new")))?;
+        let expected = std::fs::read(root.join(format!("{name}.headers")))?;
+
+        let hunks = gix_diff::patch::hunks_with_section_headers(&old, &new, Algorithm::Myers, 1, &funcname);
+        let actual: BString = hunks
+            .iter()
+            .map(|hunk| {
+                let header = format!(
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+                );
+                if hunk.section_header.is_empty() {
+                    format!("{header}\n")
+                } else {
+                    format!("{header} {}\n", hunk.section_header)
+                }
+            })
+            .collect::<String>()
+            .into();
+        assert_eq!(actual, expected.as_bstr(), "{name}");
+
+        let input = gix_diff::blob::intern::InternedInput::new(
+            gix_diff::blob::sources::byte_lines_with_terminator(&old),
+            gix_diff::blob::sources::byte_lines_with_terminator(&new),
+        );
+        let diff = gix_diff::blob::diff(
+            Algorithm::Myers,
+            &input,
+            UnifiedDiff::new(
+                &input,
+                Vec::new(),
+                NewlineSeparator::AfterHeaderAndWhenNeeded("\n"),
+                ContextSize::symmetrical(1),
+            )
+            .with_section_headers(&funcname),
+        )?;
+        let headers: BString = diff
+            .lines_with_terminator()
+            .filter(|line| line.starts_with(b"@@ "))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>()
+            .into();
+        assert_eq!(headers, expected.as_bstr(), "{name}: unified diff");
+    }
+    Ok(())
+}
+
+#[test]
+fn platform_uses_the_funcname_of_the_driver() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_funcname_repo.sh")?;
+    let mut platform = new_platform(
+        &root,
+        [
+            Driver {
+                name: "custom".into(),
+                funcname: Some("!^section two$\n^section (.*)$".into()),
+                ..Default::default()
+            },
+            Driver {
+                name: "basic".into(),
+                funcname: Some(funcname::basic_to_extended(r"^section \(on.*\)$".into())),
+                ..Default::default()
+            },
+        ],
+    );
+    for name in ["custom", "basic"] {
+        set_resources(&mut platform, name)?;
+        let funcname = platform.funcname()?;
+        let (old, new) = platform.resources().expect("set");
+        let hunks = gix_diff::patch::hunks_with_section_headers(
+            old.data.as_slice().expect("present"),
+            new.data.as_slice().expect("present"),
+            Algorithm::Myers,
+            1,
+            &funcname,
+        );
+        let expected = std::fs::read(root.join(format!("{name}.headers")))?;
+        for (hunk, expected) in hunks.iter().zip(expected.lines()) {
+            assert_eq!(
+                hunk.section_header,
+                expected.splitn_str(3, "@@ ").nth(2).expect("section header"),
+                "{name}"
+            );
+        }
+    }
+
+    set_resources(&mut platform, "default")?;
+    assert_eq!(
+        platform.funcname()?.find(b"fn main() {\n"),
+        Some("fn main() {".into()),
+        "without a driver, the default is used"
+    );
+    Ok(())
+}
+
+#[test]
+fn find() -> crate::Result {
+    let funcname = Funcname::default();
+    assert_eq!(funcname.find(b"  indented"), None);
+    assert_eq!(funcname.find(b"1 digit"), None);
+    assert_eq!(funcname.find(b"$dollar \t\r\n"), Some("$dollar".into()));
+    let long = "a".repeat(100);
+    assert_eq!(
+        funcname.find(long.as_bytes()),
+        Some(long[..80].into()),
+        "headers are truncated to 80 bytes"
+    );
+
+    let funcname = Funcname::new("!^skip\n^(fn|struct) (.*)\n^mod".into(), false)?;
+    assert_eq!(funcname.find(b"skip fn x"), None, "negated patterns reject lines");
+    assert_eq!(
+        funcname.find(b"fn main   \r\n"),
+        Some("fn".into()),
+        "the first capture group is used"
+    );
+    assert_eq!(
+        funcname.find(b"module x"),
+        Some("mod".into()),
+        "the whole match is used without capture group"
+    );
+    assert_eq!(funcname.find(b"nothing"), None);
+    assert_eq!(
+        Funcname::new("^FN .*".into(), true)?.find(b"fn x"),
+        Some("fn x".into()),
+        "patterns may be case-insensitive"
+    );
+    Ok(())
+}
+
+#[test]
+fn search_reuses_the_previous_header() {
+    let lines: [&[u8]; 5] = [b"first", b" a", b" b", b" c", b"second"];
+    let funcname = Funcname::default();
+    let mut search = funcname.search();
+    assert_eq!(
+        search.section_header(0, |n| lines[n as usize]),
+        "",
+        "nothing before the first line"
+    );
+    assert_eq!(search.section_header(2, |n| lines[n as usize]), "first");
+    assert_eq!(
+        search.section_header(4, |n| lines[n as usize]),
+        "first",
+        "without a new match, the previous header is kept"
+    );
+}
+
+#[test]
+fn invalid_patterns() {
+    assert!(matches!(
+        Funcname::new("^a\n!^b".into(), false).unwrap_err(),
+        funcname::new::Error::NegatedLastPattern { pattern } if pattern == "!^b"
+    ));
+    assert!(matches!(
+        Funcname::new("(".into(), false).unwrap_err(),
+        funcname::new::Error::Regex { .. }
+    ));
+}
+
+#[test]
+fn builtin_drivers_have_valid_patterns() -> crate::Result {
+    let builtins: Vec<_> = Driver::builtins().collect();
+    assert_eq!(builtins.len(), 25);
+    for driver in builtins {
+        assert_eq!(
+            Driver::builtin(driver.name.as_ref()).as_ref(),
+            Some(&driver),
+            "builtins can be found by name"
+        );
+        Funcname::new(driver.funcname.expect("set").as_ref(), driver.funcname_ignore_case)?;
+        word_diff::hunks(
+            b"a",
+            b"b",
+            Algorithm::Myers,
+            3,
+            Some(driver.word_regex.expect("set").as_ref()),
+        )?;
+    }
+    assert_eq!(Driver::builtin("unknown".into()), None);
+    Ok(())
+}
+
+#[test]
+fn basic_to_extended() {
+    for (basic, extended) in [
+        (r"^\(a\|b\)\{1,2\}x+y?$", r"^(a|b){1,2}x\+y\?$"),
+        (r"(literal)|{}", r"\(literal\)\|\{\}"),
+        (r"[(][]|][^]+][[:alpha:]+]\.", r"[(][]|][^]+][[:alpha:]+]\."),
+    ] {
+        assert_eq!(funcname::basic_to_extended(basic.into()), extended, "{basic}");
+    }
+}
@@ -1,3 +1,9 @@
+mod funcname;
+mod moved;
 pub(crate) mod pipeline;
 mod platform;
+mod sliders;
+mod stats;
 mod unified_diff;
+mod whitespace;
+mod word_diff;
@@ -0,0 +1,64 @@
+use gix_diff::blob::moved::{self, Line, Mode, Moved};
+use gix_object::bstr::{BString, ByteSlice};
+
+#[test]
+fn moved_lines_are_the_same_as_the_ones_detected_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_word_diff_repo.sh")?;
+    let diff = std::fs::read(root.join("moved.diff"))?;
+    let diff_lines: Vec<BString> = diff.lines().map(|line| [line, b"\n"].concat().into()).collect();
+    let lines: Vec<_> = diff_lines
+        .iter()
+        .map(|line| match line.first() {
+            Some(b'-') => Line::Removed(&line[1..]),
+            Some(b'+') => Line::Added(&line[1..]),
+            _ => Line::Other,
+        })
+        .collect();
+
+    for (mode, name) in [
+        (Mode::Plain, "plain"),
+        (Mode::Blocks, "blocks"),
+        (Mode::Zebra, "zebra"),
+        (Mode::DimmedZebra, "dimmed-zebra"),
+    ] {
+        let colored = std::fs::read(root.join("moved").with_extension(name))?;
+        let expected: Vec<_> = colored.lines().map(moved_of_colored_line).collect();
+        assert_eq!(moved::detect(&lines, mode), expected, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn blocks_need_enough_alphanumeric_characters() {
+    let lines = [
+        Line::Removed(b"}\n"),
+        Line::Other,
+        Line::Added(b"}\n"),
+        Line::Removed(b"a longer line that moved\n"),
+        Line::Other,
+        Line::Added(b"a longer line that moved\n"),
+    ];
+    let moved = Some(Moved::default());
+    assert_eq!(
+        moved::detect(&lines, Mode::Plain),
+        [moved, None, moved, moved, None, moved]
+    );
+    assert_eq!(
+        moved::detect(&lines, Mode::Blocks),
+        [None, None, None, moved, None, moved]
+    );
+}
+
+/// Decode the marker of a line from the colors `git` uses by default.
+fn moved_of_colored_line(line: &[u8]) -> Option<Moved> {
+    let color = line.strip_prefix(b"\x1b[")?;
+    let color = &color[..color.find_byte(b'm')?];
+    let (alternate, dimmed) = match color {
+        b"1;35" | b"1;36" => (false, false),
+        b"1;34" | b"1;33" => (true, false),
+        b"2" => (false, true),
+        b"2;3" => (true, true),
+        _ => return None,
+    };
+    Some(Moved { alternate, dimmed })
+}
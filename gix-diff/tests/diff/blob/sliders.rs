@@ -0,0 +1,117 @@
+use gix_diff::{
+    blob::{
+        intern::InternedInput,
+        sliders,
+        sources::byte_lines_with_terminator,
+        unified_diff::{ContextSize, NewlineSeparator},
+        Algorithm, Funcname, UnifiedDiff,
+    },
+    patch,
+};
+use gix_object::bstr::{BString, ByteSlice};
+
+#[test]
+fn hunks_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_heuristics_repo.sh")?;
+    let funcname = Funcname::default();
+    for name in [
+        "blank-lines",
+        "functions",
+        "nested",
+        "rust",
+        "removal",
+        "aligned",
+        "end-of-file",
+    ] {
+        let old = std::fs::read(root.join(name).with_extension("old"))?;
+        let new = std::fs::read(root.join(name).with_extension("new"))?;
+        for (indent_heuristic, extension) in [(true, "indent"), (false, "no-indent")] {
+            let expected = std::fs::read(root.join(name).with_extension(extension))?;
+            let options = patch::hunks::Options {
+                indent_heuristic,
+                funcname: Some(&funcname),
+                ..Default::default()
+            };
+            assert_eq!(
+                write(&patch::hunks_with_options(&old, &new, Algorithm::Myers, &options))?,
+                expected.as_bstr(),
+                "{name}.{extension}"
+            );
+            assert_eq!(
+                unified_diff(&old, &new, indent_heuristic, 3, 0)?,
+                expected.as_bstr(),
+                "{name}.{extension}: unified diff"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn inter_hunk_context_is_the_same_as_in_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_heuristics_repo.sh")?;
+    let old = std::fs::read(root.join("inter-hunk.old"))?;
+    let new = std::fs::read(root.join("inter-hunk.new"))?;
+    for lines in 0..=3 {
+        let expected = std::fs::read(root.join(format!("inter-hunk.{lines}")))?;
+        let options = patch::hunks::Options {
+            context_lines: 1,
+            inter_hunk_context: lines,
+            ..Default::default()
+        };
+        assert_eq!(
+            write(&patch::hunks_with_options(&old, &new, Algorithm::Myers, &options))?,
+            expected.as_bstr(),
+            "{lines}"
+        );
+        assert_eq!(
+            unified_diff(&old, &new, true, 1, lines)?,
+            expected.as_bstr(),
+            "{lines}: unified diff"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn sliders_are_moved_down_without_heuristic() {
+    let mut changes = Vec::new();
+    let input = InternedInput::new(&b"a\nb\n"[..], &b"a\nb\na\nb\n"[..]);
+    sliders::diff(Algorithm::Myers, &input, false, |before, after| {
+        changes.push((before, after));
+    });
+    assert_eq!(changes, [(2..2, 2..4)], "the added lines are placed at the end");
+}
+
+fn write(hunks: &[patch::Hunk]) -> std::io::Result<BString> {
+    let mut out = Vec::new();
+    for hunk in hunks {
+        hunk.write_to(&mut out)?;
+    }
+    Ok(out.into())
+}
+
+fn unified_diff(
+    old: &[u8],
+    new: &[u8],
+    indent_heuristic: bool,
+    context_lines: u32,
+    inter_hunk_context: u32,
+) -> std::io::Result<BString> {
+    let funcname = Funcname::default();
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let out = sliders::diff(
+        Algorithm::Myers,
+        &input,
+        indent_heuristic,
+        UnifiedDiff::new(
+            &input,
+            Vec::new(),
+            NewlineSeparator::AfterHeaderAndWhenNeeded("\n"),
+            ContextSize::symmetrical(context_lines),
+        )
+        .with_section_headers(&funcname)
+        .with_inter_hunk_context(inter_hunk_context),
+    )?;
+    Ok(out.into())
+}
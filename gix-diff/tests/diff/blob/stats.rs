@@ -0,0 +1,201 @@
+use std::{convert::Infallible, path::Path};
+
+use gix_diff::blob::{
+    stats::{dirstat, stat, Counts},
+    Stats,
+};
+use gix_object::{bstr::ByteSlice, FindExt, TreeRefIter};
+use gix_worktree::stack::state::attributes;
+
+#[test]
+fn stats_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_stats_repo.sh")?;
+    let stats = stats_between_tags(&root)?;
+
+    let expected =
+        |name: &str| -> crate::Result<String> { Ok(std::fs::read_to_string(root.join(format!("{name}.out")))?) };
+    let stat = |options: stat::Options| -> crate::Result<String> {
+        let mut out = Vec::new();
+        stats.write_stat(&mut out, &options)?;
+        Ok(out.to_str()?.to_owned())
+    };
+    let dirstat = |options: dirstat::Options| -> crate::Result<String> {
+        let mut out = Vec::new();
+        stats.write_dirstat(&mut out, &options)?;
+        Ok(out.to_str()?.to_owned())
+    };
+
+    assert_eq!(stat(Default::default())?, expected("stat")?);
+    assert_eq!(
+        stat(stat::Options {
+            width: 50,
+            ..Default::default()
+        })?,
+        expected("stat-narrow")?
+    );
+    assert_eq!(
+        stat(stat::Options {
+            count: Some(3),
+            ..Default::default()
+        })?,
+        expected("stat-count")?
+    );
+    assert_eq!(
+        stat(stat::Options {
+            name_width: Some(10),
+            ..Default::default()
+        })?,
+        expected("stat-name-width")?
+    );
+    assert_eq!(
+        stat(stat::Options {
+            graph_width: Some(5),
+            ..Default::default()
+        })?,
+        expected("stat-graph-width")?
+    );
+
+    let mut out = Vec::new();
+    stats.write_shortstat(&mut out)?;
+    assert_eq!(out.as_bstr(), expected("shortstat")?);
+    out.clear();
+    stats.write_numstat(&mut out)?;
+    assert_eq!(out.as_bstr(), expected("numstat")?);
+
+    let lines = dirstat::Options {
+        threshold_permille: 0,
+        ..Default::default()
+    };
+    assert_eq!(dirstat(lines)?, expected("dirstat-lines")?);
+    assert_eq!(
+        dirstat(dirstat::Options {
+            threshold_permille: 100,
+            ..lines
+        })?,
+        expected("dirstat-lines-threshold")?
+    );
+    assert_eq!(
+        dirstat(dirstat::Options {
+            cumulative: true,
+            ..lines
+        })?,
+        expected("dirstat-lines-cumulative")?
+    );
+    assert_eq!(
+        dirstat(dirstat::Options {
+            mode: dirstat::Mode::Files,
+            ..Default::default()
+        })?,
+        expected("dirstat-files")?
+    );
+    Ok(())
+}
+
+#[test]
+fn counts_and_totals() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_stats_repo.sh")?;
+    let stats = stats_between_tags(&root)?;
+
+    let file = |location: &str| {
+        stats
+            .files
+            .iter()
+            .find(|file| file.location == location)
+            .expect("file is present")
+    };
+    assert_eq!(
+        file("marked.txt").counts,
+        Counts::Binary {
+            old_size: 5,
+            new_size: 10
+        },
+        "text files can be marked as binary with the `-diff` attribute"
+    );
+    assert_eq!(
+        file("script").counts,
+        Counts::Lines {
+            insertions: 0,
+            removals: 0
+        },
+        "only the mode was changed"
+    );
+    let renamed = file("new/name.txt");
+    assert_eq!(renamed.source_location.as_ref().expect("renamed"), "old/name.txt");
+    assert_eq!(renamed.display_name(), "{old => new}/name.txt");
+    assert_eq!(file("ünicode").display_name(), r#""\303\274nicode""#);
+
+    assert_eq!(stats.insertions(), 11);
+    assert_eq!(stats.removals(), 54);
+    Ok(())
+}
+
+#[test]
+fn rename_names() {
+    for (old, new, expected) in [
+        ("a", "b", "a => b"),
+        ("dir/a", "dir/b", "dir/{a => b}"),
+        ("a/file", "b/file", "{a => b}/file"),
+        ("dir/a/file", "dir/b/file", "dir/{a => b}/file"),
+        ("dir/file", "file", "dir/file => file"),
+        ("file", "dir/file", "file => dir/file"),
+        ("a/b/c", "a/c", "a/{b => }/c"),
+    ] {
+        assert_eq!(
+            gix_diff::blob::stats::rename_name(old.into(), new.into()),
+            expected,
+            "{old} -> {new}"
+        );
+    }
+}
+
+fn stats_between_tags(root: &Path) -> crate::Result<Stats> {
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let tree = |name: &str| -> crate::Result<Vec<u8>> {
+        let hex = std::fs::read_to_string(root.join(name).with_extension("tree"))?;
+        let tree_id = gix_hash::ObjectId::from_hex(hex.trim().as_bytes())?;
+        let mut buf = Vec::new();
+        odb.find_tree(&tree_id, &mut buf)?;
+        Ok(buf)
+    };
+    let (before, after) = (tree("before")?, tree("after")?);
+
+    let mut resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(Default::default(), Default::default(), Vec::new(), Default::default()),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        gix_worktree::Stack::new(
+            root,
+            gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+                Default::default(),
+                None,
+                attributes::Source::WorktreeThenIdMapping,
+                Default::default(),
+            )),
+            gix_worktree::glob::pattern::Case::Sensitive,
+            Vec::new(),
+            Vec::new(),
+        ),
+    );
+    let mut changes = Vec::new();
+    gix_diff::tree_with_rewrites(
+        TreeRefIter::from_bytes(&before),
+        TreeRefIter::from_bytes(&after),
+        &mut resource_cache,
+        &mut Default::default(),
+        &odb,
+        |change| -> Result<_, Infallible> {
+            changes.push(change.into_owned());
+            Ok(gix_diff::tree_with_rewrites::Action::Continue)
+        },
+        gix_diff::tree_with_rewrites::Options {
+            location: Some(gix_diff::tree::recorder::Location::Path),
+            rewrites: Some(Default::default()),
+        },
+    )?;
+    changes.sort_by(|a, b| a.location().cmp(b.location()));
+    Ok(Stats::from_changes(
+        changes.iter().map(gix_diff::tree_with_rewrites::Change::to_ref),
+        &mut resource_cache,
+        &odb,
+    )?)
+}
@@ -0,0 +1,180 @@
+use gix_diff::{
+    blob::{
+        intern::InternedInput,
+        sources::byte_lines_with_terminator,
+        unified_diff::{ContextSize, NewlineSeparator},
+        whitespace, Algorithm, UnifiedDiff,
+    },
+    patch,
+};
+use gix_object::bstr::{BString, ByteSlice};
+
+#[test]
+fn hunks_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_whitespace_repo.sh")?;
+    for name in ["spaces", "blank-lines", "line-endings"] {
+        let old = std::fs::read(root.join(name).with_extension("old"))?;
+        let new = std::fs::read(root.join(name).with_extension("new"))?;
+        for (extension, options, context_lines) in [
+            ("none", whitespace::Options::default(), 3),
+            (
+                "ignore-all-space",
+                whitespace::Options {
+                    ignore_all_space: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-space-change",
+                whitespace::Options {
+                    ignore_space_change: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-space-at-eol",
+                whitespace::Options {
+                    ignore_space_at_eol: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-cr-at-eol",
+                whitespace::Options {
+                    ignore_cr_at_eol: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-blank-lines",
+                whitespace::Options {
+                    ignore_blank_lines: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-blank-lines-and-space-change",
+                whitespace::Options {
+                    ignore_blank_lines: true,
+                    ignore_space_change: true,
+                    ..Default::default()
+                },
+                3,
+            ),
+            (
+                "ignore-blank-lines-U1",
+                whitespace::Options {
+                    ignore_blank_lines: true,
+                    ..Default::default()
+                },
+                1,
+            ),
+        ] {
+            let expected = std::fs::read(root.join(format!("{name}.{extension}")))?;
+            let hunks = patch::hunks_with_options(
+                &old,
+                &new,
+                Algorithm::Myers,
+                &patch::hunks::Options {
+                    context_lines,
+                    whitespace: options,
+                    ..Default::default()
+                },
+            );
+            let mut actual = Vec::new();
+            for hunk in &hunks {
+                hunk.write_to(&mut actual)?;
+            }
+            assert_eq!(actual.as_bstr(), expected.as_bstr(), "{name}.{extension}");
+
+            let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+            let actual: BString = whitespace::diff(
+                Algorithm::Myers,
+                &input,
+                &options,
+                true,
+                context_lines,
+                0,
+                UnifiedDiff::new(
+                    &input,
+                    Vec::new(),
+                    NewlineSeparator::AfterHeaderAndWhenNeeded("\n"),
+                    ContextSize::symmetrical(context_lines),
+                ),
+            )?
+            .into();
+            assert_eq!(
+                actual
+                    .lines()
+                    .filter(|line| !line.starts_with(b"\\"))
+                    .collect::<Vec<_>>(),
+                expected
+                    .lines()
+                    .filter(|line| !line.starts_with(b"\\"))
+                    .collect::<Vec<_>>(),
+                "{name}.{extension}: unified diff"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn normalize() {
+    let all = whitespace::Options {
+        ignore_all_space: true,
+        ..Default::default()
+    };
+    assert_eq!(all.normalize(b" a \t b\r\n").as_bstr(), "ab");
+
+    let change = whitespace::Options {
+        ignore_space_change: true,
+        ..Default::default()
+    };
+    assert_eq!(change.normalize(b" a \t b  \r\n").as_bstr(), " a b");
+    assert_eq!(change.normalize(b"a b\n").as_bstr(), "a b");
+
+    let at_eol = whitespace::Options {
+        ignore_space_at_eol: true,
+        ..Default::default()
+    };
+    assert_eq!(at_eol.normalize(b" a  b \t\n").as_bstr(), " a  b");
+
+    let cr_at_eol = whitespace::Options {
+        ignore_cr_at_eol: true,
+        ..Default::default()
+    };
+    assert_eq!(cr_at_eol.normalize(b"a \r\n").as_bstr(), "a ");
+    assert_eq!(
+        cr_at_eol.normalize(b"a\r").as_bstr(),
+        "a\r",
+        "carriage-returns are only ignored before a newline"
+    );
+
+    assert_eq!(
+        whitespace::Options::default().normalize(b" a \n").as_bstr(),
+        " a \n",
+        "nothing is ignored by default"
+    );
+}
+
+#[test]
+fn is_blank() {
+    let options = whitespace::Options::default();
+    assert!(options.is_blank(b"\n"));
+    assert!(options.is_blank(b""));
+    assert!(
+        !options.is_blank(b" \n"),
+        "whitespace is only considered blank if it is ignored"
+    );
+    let options = whitespace::Options {
+        ignore_space_at_eol: true,
+        ..Default::default()
+    };
+    assert!(options.is_blank(b" \t\r\n"));
+}
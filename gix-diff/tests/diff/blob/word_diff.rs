@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use gix_diff::blob::{
+    pipeline,
+    word_diff::{self, Part, Style},
+    Algorithm, Platform, ResourceKind,
+};
+use gix_object::{bstr::ByteSlice, tree::EntryKind};
+use gix_worktree::stack::state::attributes;
+
+#[test]
+fn hunks_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_word_diff_repo.sh")?;
+    for name in ["lines", "no-newline", "removal-only"] {
+        let hunks = word_diff::hunks(
+            &std::fs::read(root.join(name).with_extension("old"))?,
+            &std::fs::read(root.join(name).with_extension("new"))?,
+            Algorithm::Myers,
+            3,
+            None,
+        )?;
+        for (style, extension) in [
+            (Style::Plain, "plain"),
+            (Style::Porcelain, "porcelain"),
+            (Style::Color, "color"),
+        ] {
+            assert_eq!(
+                write(&hunks, style)?,
+                std::fs::read(root.join(name).with_extension(extension))?.as_bstr(),
+                "{name}.{extension}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn parts() -> crate::Result {
+    let hunks = word_diff::hunks(b"a b c\nsame", b"a x c\nsame\nmore", Algorithm::Myers, 3, None)?;
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(
+        hunks[0].parts,
+        [
+            Part::Unchanged("a ".into()),
+            Part::Removed("b".into()),
+            Part::Added("x".into()),
+            Part::Unchanged(" c\nsame\n".into()),
+            Part::Added("more".into()),
+            Part::Unchanged("\n".into()),
+        ],
+        "lines without trailing newline are treated as if they had one"
+    );
+
+    let hunks = word_diff::hunks(b"a\nb\n", b"a\nc\n", Algorithm::Myers, 0, None)?;
+    assert_eq!(
+        hunks[0].parts,
+        [
+            Part::Removed("b".into()),
+            Part::Added("c".into()),
+            Part::Unchanged("\n".into())
+        ]
+    );
+    assert_eq!((hunks[0].old_start, hunks[0].old_len), (2, 1));
+    Ok(())
+}
+
+#[test]
+fn invalid_regex() {
+    let err = word_diff::hunks(b"a", b"b", Algorithm::Myers, 3, Some("(".into())).unwrap_err();
+    assert!(matches!(err, word_diff::Error::Regex { .. }));
+}
+
+#[test]
+fn regex_from_options_then_drivers_then_default() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_word_diff_repo.sh")?;
+    let regex = "[a-z]+|[0-9]+|[^[:space:]]";
+    let mut platform = new_platform(
+        &root,
+        Some(gix_diff::blob::Driver {
+            name: "words".into(),
+            word_regex: Some(regex.into()),
+            ..Default::default()
+        }),
+    );
+    let expected = std::fs::read(root.join("driver.plain"))?;
+
+    set_resources(&mut platform, "driver")?;
+    let hunks = platform
+        .word_diff(&word_diff::Options {
+            default_regex: Some("[^[:space:]]".into()),
+            ..Default::default()
+        })?
+        .expect("not binary");
+    assert_eq!(
+        write(&hunks, Style::Plain)?,
+        expected.as_bstr(),
+        "the driver regex has precedence over the default one"
+    );
+
+    let hunks = platform
+        .word_diff(&word_diff::Options {
+            regex: Some(r"\S+".into()),
+            ..Default::default()
+        })?
+        .expect("not binary");
+    assert_eq!(
+        write(&hunks, Style::Plain)?,
+        "@@ -1,2 +1,2 @@\n[-foo_bar(baz, 42);-]{+foo_qux(baz, 43);+}\nvalue = [-compute(a+b);-]{+compute(a-b);+}\n",
+        "the regex of the options override everything"
+    );
+
+    set_resources(&mut platform, "regex")?;
+    let hunks = platform
+        .word_diff(&word_diff::Options {
+            default_regex: Some(regex.into()),
+            ..Default::default()
+        })?
+        .expect("not binary");
+    assert_eq!(
+        write(&hunks, Style::Plain)?,
+        std::fs::read(root.join("regex.plain"))?.as_bstr(),
+        "without a driver, the default is used"
+    );
+    Ok(())
+}
+
+fn write(hunks: &[word_diff::Hunk], style: Style) -> std::io::Result<gix_object::bstr::BString> {
+    let mut out = Vec::new();
+    for hunk in hunks {
+        hunk.write_to(&mut out, style)?;
+    }
+    Ok(out.into())
+}
+
+pub(crate) fn set_resources(platform: &mut Platform, name: &str) -> crate::Result {
+    for (extension, kind) in [
+        ("old", ResourceKind::OldOrSource),
+        ("new", ResourceKind::NewOrDestination),
+    ] {
+        platform.set_resource(
+            gix_hash::Kind::Sha1.null(),
+            EntryKind::Blob,
+            format!("{name}.{extension}").as_str().into(),
+            kind,
+            &gix_object::find::Never,
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn new_platform(root: &Path, drivers: impl IntoIterator<Item = gix_diff::blob::Driver>) -> Platform {
+    let attributes = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            attributes::Source::WorktreeThenIdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let filter = gix_diff::blob::Pipeline::new(
+        pipeline::WorktreeRoots {
+            old_root: Some(root.into()),
+            new_root: Some(root.into()),
+        },
+        gix_filter::Pipeline::default(),
+        drivers.into_iter().collect(),
+        crate::blob::pipeline::convert_to_diffable::default_options(),
+    );
+    Platform::new(Default::default(), filter, pipeline::Mode::ToGit, attributes)
+}
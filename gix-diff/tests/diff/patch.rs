@@ -0,0 +1,351 @@
+use gix_diff::patch::{self, apply::HunkOutcome, Content, Line, Operation};
+use gix_object::{bstr::ByteSlice, tree::EntryKind};
+
+#[test]
+fn git_patches_apply_to_the_files_they_were_made_for() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_patches.sh")?;
+    for name in [
+        "text",
+        "copy",
+        "lose-newline",
+        "binary-added",
+        "binary-changed",
+        "binary-deleted",
+    ] {
+        let files = patch::parse(&std::fs::read(root.join(format!("{name}.patch")))?)?;
+        assert!(!files.is_empty(), "{name}: there are patches to apply");
+        for file in files {
+            let old = match &file.old_path {
+                Some(path) => std::fs::read(root.join(format!("{name}.before")).join(path.to_path()?))?,
+                None => Vec::new(),
+            };
+            let outcome = patch::apply(&old, &file, Default::default())?;
+            assert!(!outcome.has_rejected_hunks(), "{name}: {file:?}");
+            assert!(
+                outcome
+                    .hunks
+                    .iter()
+                    .all(|h| *h == HunkOutcome::Applied { offset: 0, fuzz: 0 }),
+                "{name}: all hunks apply where they are expected"
+            );
+            match &file.new_path {
+                Some(path) => {
+                    let expected = std::fs::read(root.join(format!("{name}.after")).join(path.to_path()?))?;
+                    assert_eq!(outcome.data.as_bstr(), expected.as_bstr(), "{name}: {path}");
+                }
+                None => assert!(outcome.data.is_empty(), "{name}: deleted files have no content"),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn git_headers() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_patches.sh")?;
+    let files = patch::parse(&std::fs::read(root.join("text.patch"))?)?;
+    let summary: Vec<_> = files
+        .iter()
+        .map(|f| {
+            (
+                f.old_path.as_ref().map(ToString::to_string),
+                f.new_path.as_ref().map(ToString::to_string),
+                f.operation,
+            )
+        })
+        .collect();
+    let some = |path: &str| Some(path.to_string());
+    assert_eq!(
+        summary,
+        [
+            (None, some("added"), Operation::Addition),
+            (some("deleted"), None, Operation::Deletion),
+            (some("no-newline"), some("no-newline"), Operation::Modification),
+            (some("numbers"), some("numbers"), Operation::Modification),
+            (
+                some("to-rename"),
+                some("renamed"),
+                Operation::Rename { similarity: Some(95) }
+            ),
+            (some("script"), some("script"), Operation::Modification),
+            (some("with space"), some("with space"), Operation::Modification),
+            (some("ünicode"), some("ünicode"), Operation::Modification),
+        ]
+    );
+
+    let added = &files[0];
+    assert_eq!(added.new_mode.map(|m| m.kind()), Some(EntryKind::Blob));
+    assert_eq!(added.old_id.expect("present").to_string(), "0000000");
+    assert_eq!(added.new_id.expect("present").to_string(), "8a1218a");
+
+    let Content::Text(hunks) = &files[2].content else {
+        panic!("text patch")
+    };
+    assert_eq!(
+        hunks[0].lines,
+        [
+            Line::Context("a\n".into()),
+            Line::Removed("b\n".into()),
+            Line::Added("B\n".into()),
+            Line::Context("c".into())
+        ],
+        "the last line has no newline"
+    );
+
+    let Content::Text(hunks) = &files[3].content else {
+        panic!("text patch")
+    };
+    assert_eq!(
+        hunks
+            .iter()
+            .map(|h| (h.old_start, h.old_len, h.new_start, h.new_len))
+            .collect::<Vec<_>>(),
+        [(1, 5, 1, 5), (12, 7, 12, 7), (26, 5, 26, 4)]
+    );
+
+    let script = &files[5];
+    assert_eq!(script.old_mode.map(|m| m.kind()), Some(EntryKind::Blob));
+    assert_eq!(script.new_mode.map(|m| m.kind()), Some(EntryKind::BlobExecutable));
+    assert_eq!(script.content, Content::Text(Vec::new()), "mode changes have no hunks");
+    Ok(())
+}
+
+#[test]
+fn binary_patches() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_patches.sh")?;
+    let files = patch::parse(&std::fs::read(root.join("binary-changed.patch"))?)?;
+    assert_eq!(files.len(), 2);
+    for file in &files {
+        let Content::Binary(binary) = &file.content else {
+            panic!("binary patch expected")
+        };
+        assert!(binary.reverse.is_some(), "git always produces reverse patches");
+    }
+    assert!(matches!(
+        &files[1].content,
+        Content::Binary(patch::Binary {
+            forward: patch::BinaryData::Delta(_),
+            ..
+        })
+    ));
+
+    let err = patch::apply(b"not the original", &files[1], Default::default()).unwrap_err();
+    assert!(
+        matches!(err, patch::apply::Error::BinaryPreimageMismatch),
+        "binary patches are verified against the object id of the old file"
+    );
+
+    let files = patch::parse(b"diff --git a/b b/b\nindex 1234567..89abcde 100644\nBinary files a/b and b/b differ\n")?;
+    assert_eq!(files[0].content, Content::BinaryWithoutData);
+    assert!(matches!(
+        patch::apply(b"", &files[0], Default::default()),
+        Err(patch::apply::Error::BinaryWithoutData)
+    ));
+
+    let err =
+        patch::parse(b"diff --git a/b b/b\nGIT binary patch\nliteral 9\nQcmZQzWJ=1+OD...00^)Gi2wiq\n\n").unwrap_err();
+    assert!(matches!(err, patch::parse::Error::Binary { line_number: 4 }));
+
+    for size in ["18446744073709551615", "99999999999999"] {
+        let err = patch::parse(
+            format!("diff --git a/b b/b\nGIT binary patch\nliteral {size}\nQcmZQzWJ=1+ODw7c00^)Gi2wiq\n\n").as_bytes(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, patch::parse::Error::Binary { line_number: 3 }),
+            "sizes that the compressed data can't inflate to are rejected without trusting them"
+        );
+    }
+    Ok(())
+}
+
+const PATCH: &str = "\
+--- a/file
++++ b/file
+@@ -2,3 +2,3 @@ section
+ 2
+-3
++three
+ 4
+@@ -7,3 +7,3 @@
+ 7
+-8
++eight
+ 9
+";
+
+fn numbers(range: std::ops::RangeInclusive<u32>) -> String {
+    let mut out = String::new();
+    for n in range {
+        out.push_str(&n.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn traditional_unified_diff() -> crate::Result {
+    let files = patch::parse(PATCH.as_bytes())?;
+    assert_eq!(files.len(), 1);
+    let file = &files[0];
+    assert_eq!(file.old_path.as_ref().expect("set"), "file");
+    assert_eq!(file.new_path.as_ref().expect("set"), "file");
+    assert_eq!(file.operation, Operation::Modification);
+    let Content::Text(hunks) = &file.content else {
+        panic!("text patch")
+    };
+    assert_eq!(hunks[0].section_header, "section");
+
+    let outcome = patch::apply(numbers(1..=10).as_bytes(), file, Default::default())?;
+    assert_eq!(
+        outcome.data.as_bstr(),
+        numbers(1..=10).replace("3\n", "three\n").replace("8\n", "eight\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn hunks_are_searched_for_if_they_moved() -> crate::Result {
+    let files = patch::parse(PATCH.as_bytes())?;
+    let old = format!("{}{}", numbers(100..=101), numbers(1..=10));
+    let outcome = patch::apply(old.as_bytes(), &files[0], Default::default())?;
+    assert_eq!(
+        outcome.hunks,
+        [
+            HunkOutcome::Applied { offset: 2, fuzz: 0 },
+            HunkOutcome::Applied { offset: 0, fuzz: 0 }
+        ],
+        "the offset of the first hunk is accounted for when expecting the second one"
+    );
+    assert_eq!(
+        outcome.data.as_bstr(),
+        old.replacen("3\n", "three\n", 1).replace("\n8\n", "\neight\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn rejected_hunks_and_fuzz() -> crate::Result {
+    let files = patch::parse(PATCH.as_bytes())?;
+    let old = numbers(1..=10).replace("9\n", "nine\n");
+    let outcome = patch::apply(old.as_bytes(), &files[0], Default::default())?;
+    assert_eq!(
+        outcome.hunks,
+        [HunkOutcome::Applied { offset: 0, fuzz: 0 }, HunkOutcome::Rejected]
+    );
+    assert!(outcome.has_rejected_hunks());
+    assert_eq!(outcome.rejected_hunks().collect::<Vec<_>>(), [1]);
+    assert_eq!(
+        outcome.data.as_bstr(),
+        old.replace("3\n", "three\n"),
+        "hunks that apply are applied nonetheless"
+    );
+
+    let outcome = patch::apply(old.as_bytes(), &files[0], patch::apply::Options { fuzz: 1 })?;
+    assert_eq!(
+        outcome.hunks,
+        [
+            HunkOutcome::Applied { offset: 0, fuzz: 0 },
+            HunkOutcome::Applied { offset: 0, fuzz: 1 }
+        ],
+        "ignoring one line of context makes the second hunk apply"
+    );
+    assert_eq!(
+        outcome.data.as_bstr(),
+        old.replace("3\n", "three\n").replace("8\n", "eight\n")
+    );
+
+    let files = patch::parse(b"--- a/file\n+++ b/file\n@@ -1,3 +1,2 @@\n a\n-b\n c\n")?;
+    let outcome = patch::apply(b"b\nc\n", &files[0], patch::apply::Options { fuzz: 1 })?;
+    assert_eq!(
+        outcome.hunks,
+        [HunkOutcome::Applied { offset: -1, fuzz: 1 }],
+        "a hunk whose leading context was removed by fuzz can apply at the very beginning"
+    );
+    assert_eq!(outcome.data.as_bstr(), "c\n");
+    Ok(())
+}
+
+#[test]
+fn truncated_hunks_are_an_error() {
+    let err = patch::parse(b"--- a/file\n+++ b/file\n@@ -1,3 +1,3 @@\n 1\n-2\n+two\n").unwrap_err();
+    assert!(matches!(err, patch::parse::Error::TruncatedHunk { line_number: 3 }));
+}
+
+#[test]
+fn written_patches_are_identical_to_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_patches.sh")?;
+    for name in ["text", "copy", "lose-newline"] {
+        let expected = std::fs::read(root.join(format!("{name}.patch")))?;
+        let mut actual = Vec::new();
+        for file in patch::parse(&expected)? {
+            file.write_to(&mut actual)?;
+        }
+        assert_eq!(actual.as_bstr(), expected.as_bstr(), "{name}");
+    }
+
+    for name in ["binary-added", "binary-changed", "binary-deleted"] {
+        let expected = patch::parse(&std::fs::read(root.join(format!("{name}.patch")))?)?;
+        let mut written = Vec::new();
+        for file in &expected {
+            file.write_to(&mut written)?;
+        }
+        assert_eq!(
+            patch::parse(&written)?,
+            expected,
+            "{name}: the compressed data may differ, but it decodes to the same bytes"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn hunks_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_patches.sh")?;
+    for name in ["text", "copy", "lose-newline"] {
+        for file in patch::parse(&std::fs::read(root.join(format!("{name}.patch")))?)? {
+            let Content::Text(mut expected) = file.content.clone() else {
+                unreachable!("only text patches")
+            };
+            for hunk in &mut expected {
+                hunk.section_header.clear();
+            }
+            let read = |dir: &str, path: Option<&gix_object::bstr::BString>| -> std::io::Result<Vec<u8>> {
+                match path {
+                    Some(path) => std::fs::read(root.join(format!("{name}.{dir}")).join(path.to_str_lossy().as_ref())),
+                    None => Ok(Vec::new()),
+                }
+            };
+            let old_path = (file.operation != Operation::Addition)
+                .then_some(file.old_path.as_ref())
+                .flatten();
+            let new_path = (file.operation != Operation::Deletion)
+                .then_some(file.new_path.as_ref())
+                .flatten();
+            let actual = patch::hunks(
+                &read("before", old_path)?,
+                &read("after", new_path)?,
+                gix_diff::blob::Algorithm::Myers,
+                3,
+            );
+            assert_eq!(actual, expected, "{name}: {:?}", file.new_path);
+        }
+    }
+
+    let hunks = patch::hunks(
+        numbers(1..=20).as_bytes(),
+        numbers(1..=20)
+            .replace("\n4\n", "\nfour\n")
+            .replace("\n11\n", "\neleven\n")
+            .as_bytes(),
+        gix_diff::blob::Algorithm::Myers,
+        3,
+    );
+    assert_eq!(
+        hunks.len(),
+        1,
+        "changes with up to 6 lines between them are in the same hunk"
+    );
+    assert_eq!((hunks[0].old_start, hunks[0].old_len), (1, 14));
+    Ok(())
+}
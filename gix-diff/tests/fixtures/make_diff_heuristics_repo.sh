@@ -0,0 +1,206 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create pairs of files named `<case>.old` and `<case>.new` whose diffs contain groups of changed lines that could be
+# placed elsewhere, and store the hunks of `git diff` between them with and without the indent heuristic
+# in `<case>.indent` and `<case>.no-indent` respectively.
+# `inter-hunk.<n>` contains the hunks of the `inter-hunk` case with `--inter-hunk-context=<n>`.
+git init -q
+
+cat <<EOF >blank-lines.old
+1
+2
+a
+
+b
+3
+4
+EOF
+
+cat <<EOF >blank-lines.new
+1
+2
+a
+
+b
+a
+
+b
+3
+4
+EOF
+
+cat <<EOF >functions.old
+1
+2
+/* function */
+foo() {
+    foo
+}
+
+3
+4
+EOF
+
+cat <<EOF >functions.new
+1
+2
+/* function */
+bar() {
+    foo
+}
+
+/* function */
+foo() {
+    foo
+}
+
+3
+4
+EOF
+
+cat <<EOF >nested.old
+def first():
+    if x:
+        a()
+        b()
+
+    return 1
+
+def second():
+    return 2
+EOF
+
+cat <<EOF >nested.new
+def first():
+    if x:
+        a()
+        b()
+
+    if y:
+        a()
+        b()
+
+    return 1
+
+def second():
+    return 2
+EOF
+
+cat <<EOF >rust.old
+impl A {
+    fn a() {
+        one();
+    }
+
+    fn c() {
+        three();
+    }
+}
+EOF
+
+cat <<EOF >rust.new
+impl A {
+    fn a() {
+        one();
+    }
+
+    fn b() {
+        two();
+    }
+
+    fn c() {
+        three();
+    }
+}
+EOF
+
+cat <<EOF >removal.old
+start
+	if (a) {
+		x();
+	}
+	if (a) {
+		x();
+	}
+end
+EOF
+
+cat <<EOF >removal.new
+start
+	if (a) {
+		x();
+	}
+end
+EOF
+
+cat <<EOF >aligned.old
+a
+b
+c
+x
+a
+b
+c
+y
+EOF
+
+cat <<EOF >aligned.new
+a
+b
+c
+a
+b
+c
+z
+EOF
+
+cat <<EOF >end-of-file.old
+x
+x
+EOF
+
+cat <<EOF >end-of-file.new
+x
+x
+x
+x
+EOF
+
+cat <<EOF >inter-hunk.old
+1
+2
+3
+4
+5
+6
+7
+8
+9
+10
+11
+12
+13
+14
+15
+EOF
+
+sed -e 's/^2$/two/' -e 's/^9$/nine/' -e 's/^14$/fourteen/' inter-hunk.old >inter-hunk.new
+
+function hunks() {
+  local name=${1:?first argument is the case name}
+  local out=${2:?second argument is the output file}
+  shift 2
+  git diff --no-index --no-color "$@" "$name.old" "$name.new" >"$out.tmp" || test $? = 1
+  tail -n +5 "$out.tmp" >"$out"
+  rm "$out.tmp"
+}
+
+for name in blank-lines functions nested rust removal aligned end-of-file; do
+  hunks $name $name.indent --indent-heuristic
+  hunks $name $name.no-indent --no-indent-heuristic
+done
+
+for lines in 0 1 2 3; do
+  hunks inter-hunk inter-hunk.$lines -U1 --inter-hunk-context=$lines
+done
@@ -0,0 +1,53 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create a repository with the `before` and `after` tags, with the ids of their trees in `<tag>.tree`, and store the output of `git diff` with various
+# statistics options between them in `<name>.out`.
+git init -q
+git config core.fileMode true
+
+mkdir -p dir/sub old very/long/directory/name/that/will/be/truncated/in/the/output
+echo "marked.txt -diff" > .gitattributes
+seq 1 100 > dir/a
+seq 1 10 > dir/sub/b
+seq 1 20 > old/name.txt
+seq 1 5 > very/long/directory/name/that/will/be/truncated/in/the/output/file.txt
+printf 'bin\0ary' > bin.dat
+echo "text" > marked.txt
+echo "content" > script
+seq 1 3 > deleted
+echo "content" > ünicode
+git add . && git commit -q -m before && git tag before
+
+seq 1 2 100 > dir/a
+seq 2 11 > dir/sub/b
+seq 1 7 > dir/sub/c
+mkdir new && git mv old/name.txt new/name.txt && echo 21 >> new/name.txt
+seq 1 6 > very/long/directory/name/that/will/be/truncated/in/the/output/file.txt
+printf 'more bin\0ary' > bin.dat
+echo "more text" > marked.txt
+chmod +x script
+git rm -q deleted
+echo "more content" >> ünicode
+git add . && git commit -q -m after && git tag after
+
+git rev-parse before^{tree} > before.tree
+git rev-parse after^{tree} > after.tree
+
+function out() {
+  local name=${1:?}
+  shift
+  git diff -M "$@" before after > "$name.out"
+}
+
+out stat --stat
+out stat-narrow --stat=50
+out stat-count --stat-count=3
+out stat-name-width --stat --stat-name-width=10
+out stat-graph-width --stat --stat-graph-width=5
+out shortstat --shortstat
+out numstat --numstat
+out dirstat-lines --dirstat=lines,0
+out dirstat-lines-threshold --dirstat=lines,10
+out dirstat-files --dirstat=files
+out dirstat-lines-cumulative --dirstat=lines,cumulative,0
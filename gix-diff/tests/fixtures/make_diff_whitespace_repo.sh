@@ -0,0 +1,35 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create pairs of files named `<case>.old` and `<case>.new` that differ in whitespace, and store the hunks of `git diff`
+# between them with the whitespace options in `<case>.<option>`, with `none` for no options.
+git init -q
+
+printf 'fn main() {\n    let a = 1;\n    let b  =  2;\n    call(a,b);\n    trailing();\n    crlf();\n\n    same();\n}\n' >spaces.old
+printf 'fn main() {\n\tlet a = 1;\n    let b = 2;\n    call(a, b);\n    trailing();   \n    crlf();\r\n\n    same();\n}   \n' >spaces.new
+
+printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n17\n18\n19\n20\n' >blank-lines.old
+printf '1\n\n2\n3\n4\n5\n6\n7\n8\n\n9\nnine\n10\n11\n12\n13\n14\n15\n16\n  \n17\n18\n19\n20\n\n\n' >blank-lines.new
+
+printf 'a\nb\nc\r\nd\r' >line-endings.old
+printf 'a \nb\r\nc\nd' >line-endings.new
+
+function hunks() {
+  local name=${1:?first argument is the case name}
+  local out=${2:?second argument is the output file}
+  shift 2
+  git diff --no-index --no-color "$@" "$name.old" "$name.new" >"$out.tmp" || test $? = 1
+  tail -n +5 "$out.tmp" >"$out"
+  rm "$out.tmp"
+}
+
+for name in spaces blank-lines line-endings; do
+  hunks $name $name.none
+  hunks $name $name.ignore-all-space --ignore-all-space
+  hunks $name $name.ignore-space-change --ignore-space-change
+  hunks $name $name.ignore-space-at-eol --ignore-space-at-eol
+  hunks $name $name.ignore-cr-at-eol --ignore-cr-at-eol
+  hunks $name $name.ignore-blank-lines --ignore-blank-lines
+  hunks $name $name.ignore-blank-lines-and-space-change --ignore-blank-lines --ignore-space-change
+  hunks $name $name.ignore-blank-lines-U1 --ignore-blank-lines -U1
+done
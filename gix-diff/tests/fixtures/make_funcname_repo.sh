@@ -0,0 +1,250 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create pairs of files named `<case>.old` and `<case>.new`, and store the hunk headers of `git diff -U1` between them,
+# which include the function names, in `<case>.headers`. Diff drivers are assigned by the extension of `<case>`.
+git init -q
+
+cat <<EOF >.gitattributes
+*.rs diff=rust
+*.py diff=python
+*.cpp diff=cpp
+*.java diff=java
+*.go diff=golang
+*.adb diff=ada
+*.sh diff=bash
+*.tex diff=tex
+custom* diff=custom
+basic* diff=basic
+EOF
+
+cat <<EOF >code.rs.old
+use std::io;
+
+pub struct Config {
+    value: u32,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        let value = 1;
+        let other = 2;
+        let third = 3;
+        Config { value }
+    }
+
+    pub(crate) async fn load(path: &str) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        let parsed = parse(&data);
+        let checked = check(parsed);
+        Ok(checked)
+    }
+}
+
+fn parse(data: &[u8]) -> Config {
+    let first = data[0];
+    let second = data[1];
+    let third = data[2];
+    Config { value: 0 }
+}
+EOF
+sed -e 's/let other = 2;/let other = 20;/' -e 's/let checked = check(parsed);/let checked = check(parsed)?;/' -e 's/let third = data\[2\];/let third = data[3];/' code.rs.old >code.rs.new
+
+cat <<EOF >code.py.old
+import os
+
+class Reader:
+    def __init__(self, path):
+        self.path = path
+        self.size = 0
+        self.open = False
+
+    async def read(self):
+        with open(self.path) as f:
+            data = f.read()
+        self.size = len(data)
+        return data
+
+def main():
+    reader = Reader("x")
+    first = 1
+    second = 2
+    return reader
+EOF
+sed -e 's/self.open = False/self.open = True/' -e 's/return data/return data.strip()/' -e 's/second = 2/second = 3/' code.py.old >code.py.new
+
+cat <<EOF >code.cpp.old
+#include <vector>
+
+namespace app {
+
+int compute(int a, int b)
+{
+    int sum = a + b;
+public:
+    int other = 2;
+    int third = 3;
+    return sum;
+}
+
+static void helper(std::vector<int>& v)
+{
+    v.push_back(1);
+    v.push_back(2);
+    v.push_back(3);
+    v.push_back(4);
+}
+
+}
+EOF
+sed -e 's/int third = 3;/int third = 30;/' -e 's/v.push_back(3);/v.push_back(33);/' code.cpp.old >code.cpp.new
+
+cat <<EOF >code.java.old
+package app;
+
+public class Main {
+    private int value;
+
+    public static void main(String[] args) {
+        int a = 1;
+        int b = 2;
+        if (a < b) {
+            a = b;
+            b = 3;
+            a = 4;
+        }
+    }
+
+    protected List<String> names(int count) {
+        List<String> out = new ArrayList<>();
+        out.add("a");
+        out.add("b");
+        return out;
+    }
+}
+EOF
+sed -e 's/a = 4;/a = 5;/' -e 's/out.add("b");/out.add("c");/' code.java.old >code.java.new
+
+cat <<EOF >code.go.old
+package main
+
+type Config struct {
+	Value int
+	Other int
+	Third int
+}
+
+func (c *Config) Load(path string) error {
+	data := read(path)
+	c.Value = len(data)
+	c.Other = 2
+	c.Third = 3
+	return nil
+}
+EOF
+sed -e 's/Third int/Third int64/' -e 's/c.Third = 3/c.Third = 4/' code.go.old >code.go.new
+
+cat <<EOF >code.adb.old
+with Ada.Text_IO;
+
+PACKAGE BODY Greetings IS
+   x : Integer := 1;
+   y : Integer := 2;
+   z : Integer := 3;
+
+   procedure Hello is
+   begin
+      Put_Line ("Hello");
+      Put_Line ("World");
+      Put_Line ("Again");
+   end Hello;
+end Greetings;
+EOF
+sed -e 's/z : Integer := 3;/z : Integer := 4;/' -e 's/"Again"/"Once more"/' code.adb.old >code.adb.new
+
+cat <<EOF >code.sh.old
+#!/bin/sh
+set -e
+
+setup() {
+  first=1
+  second=2
+  third=3
+}
+
+function teardown {
+  rm -rf dir
+  echo done
+  exit 0
+}
+EOF
+sed -e 's/third=3/third=4/' -e 's/echo done/echo finished/' code.sh.old >code.sh.new
+
+cat <<'EOF' >code.tex.old
+\documentclass{article}
+\begin{document}
+\section{Introduction}
+First line.
+Second line.
+Third line.
+Fourth line.
+\subsection*{Details}
+More text.
+Even more text.
+The end.
+\end{document}
+EOF
+sed -e 's/Fourth line./Fourth line changed./' -e 's/The end./The very end./' code.tex.old >code.tex.new
+
+cat <<EOF >custom.old
+section one
+  not a header
+  a
+  b
+  c
+  d
+section two
+  e
+  f
+  g
+  h
+EOF
+sed -e 's/  d/  D/' -e 's/  h/  H/' custom.old >custom.new
+cp custom.old basic.old
+cp custom.new basic.new
+
+cat <<EOF >default.old
+fn_like_line
+	indented
+  also indented
+1 starts with a digit
+a
+b
+_underscore
+c
+d
+e
+f
+\$dollar and trailing whitespace 	 
+g
+h
+i
+j
+EOF
+sed -e 's/^b$/B/' -e 's/^e$/E/' -e 's/^h$/H/' default.old >default.new
+
+function headers() {
+  local name=${1:?first argument is the case name}
+  shift
+  cp "$name.old" "$name"
+  git add "$name"
+  cp "$name.new" "$name"
+  git "$@" diff --no-color -U1 "$name" | grep '^@@' >"$name.headers"
+}
+
+for name in code.rs code.py code.cpp code.java code.go code.adb code.sh code.tex default; do
+  headers $name
+done
+headers custom -c diff.custom.xfuncname='!^section two$
+^section (.*)$'
+headers basic -c diff.basic.funcname='^section \(on.*\)$'
@@ -0,0 +1,70 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create patches with `git diff` in `<name>.patch`, along with the files they apply to in `<name>.before/`
+# and the expected result in `<name>.after/`.
+function export_tree() {
+  local rev=${1:?} dir=${2:?}
+  mkdir "../$dir"
+  git archive "$rev" | tar -x -C "../$dir"
+}
+
+function patch() {
+  local name=${1:?}
+  shift
+  git diff "$@" before after > "../$name.patch"
+  export_tree before "$name.before"
+  export_tree after "$name.after"
+  git tag -d before after >/dev/null
+}
+
+git init -q repo
+cd repo
+git config core.fileMode true
+
+seq 1 30 > numbers
+seq 1 10 > deleted
+printf 'a\nb\nc' > no-newline
+seq 20 40 > to-rename
+echo "content" > "with space"
+echo "content" > ünicode
+echo "#!/bin/sh" > script
+git add . && git commit -qm "init" && git tag before
+
+sed -i -e 's/^2$/two/' -e 's/^15$/fifteen/' -e '/^29$/d' numbers
+git rm -q deleted
+printf 'a\nB\nc' > no-newline
+git mv to-rename renamed && echo 41 >> renamed
+echo "changed" > "with space"
+echo "changed" > ünicode
+chmod +x script
+seq 1 5 > added
+git add . && git commit -qm "text changes" && git tag after
+patch text -M
+
+git tag before
+cp numbers copied && echo 31 >> copied
+git add . && git commit -qm "copy" && git tag after
+patch copy -C -C
+
+git tag before
+printf 'without-newline' > numbers
+git add . && git commit -qm "lose newline" && git tag after
+patch lose-newline
+
+git tag before
+printf '\x00\x01\x02binary' > binary
+printf '\x00' > binary-large && seq 1 2000 >> binary-large
+git add . && git commit -qm "add binary" && git tag after
+patch binary-added --binary
+
+git tag before
+printf '\x00\x01\x02changed' > binary
+printf '\x00' > binary-large && seq 1 2001 >> binary-large
+git add . && git commit -qm "change binary" && git tag after
+patch binary-changed --binary --full-index
+
+git tag before
+git rm -q binary
+git commit -qm "delete binary" && git tag after
+patch binary-deleted --binary
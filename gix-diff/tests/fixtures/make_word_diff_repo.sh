@@ -0,0 +1,139 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create pairs of files named `<case>.old` and `<case>.new`, and store the output of `git diff --word-diff=<style>` between them
+# in `<case>.<style>`, and the output of `git diff --color-moved=<mode>` in `moved.<mode>`, without the file header and
+# without the function names in hunk headers.
+git init -q
+
+cat <<EOF >.gitattributes
+driver.* diff=words
+EOF
+
+cat <<EOF >lines.old
+The quick brown fox
+jumps over the lazy dog.
+
+unchanged line one
+unchanged line two
+unchanged line three
+this line will be removed entirely
+unchanged line four
+unchanged	with tab
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+trailing  spaces  and   words
+the end is near
+EOF
+
+cat <<EOF >lines.new
+The slow brown fox
+jumps over the  dog, eventually.
+
+unchanged line one
+unchanged line two
+unchanged line three
+unchanged line four
+unchanged	with tab
+an added line
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+more lines in between
+trailing spaces and  more  words
+the end is near
+and it has come
+EOF
+
+printf 'a b c\nsame' >no-newline.old
+printf 'a x c\nsame\nmore' >no-newline.new
+
+printf 'first\nsecond line\nthird\n' >removal-only.old
+printf 'first\nthird\n' >removal-only.new
+
+cat <<EOF >regex.old
+foo_bar(baz, 42);
+value = compute(a+b);
+EOF
+
+cat <<EOF >regex.new
+foo_qux(baz, 43);
+value = compute(a-b);
+EOF
+
+cp regex.old driver.old
+cp regex.new driver.new
+
+function word_diff() {
+  local name=${1:?first argument is the case name}
+  local style=${2:?second argument is the style}
+  local out=${3:?third argument is the output file}
+  shift 3
+  local color=--no-color
+  if [ "$style" = color ]; then
+    color=--color=always
+  fi
+  git "$@" diff --no-index --word-diff="$style" $color "$name.old" "$name.new" >"$out.tmp" || test $? = 1
+  tail -n +5 "$out.tmp" | sed -E 's/^((\x1b\[36m)?@@ [^@]+ @@(\x1b\[m)?).*/\1/' >"$out"
+  rm "$out.tmp"
+}
+
+for style in plain porcelain color; do
+  for name in lines no-newline removal-only; do
+    word_diff $name $style $name.$style
+  done
+done
+regex='[a-z]+|[0-9]+|[^[:space:]]'
+word_diff regex plain regex.plain -c diff.wordRegex="$regex"
+word_diff driver plain driver.plain -c diff.words.wordRegex="$regex" -c diff.wordRegex='[^[:space:]]'
+
+cat <<EOF >moved.old
+keep 1
+x y
+keep 2
+alpha block with a first line
+alpha block with a second line
+keep 3
+keep 4
+keep 5
+beta block with a first line
+beta block with a second line
+keep 6
+keep 7
+keep 8
+gamma block with a first line
+gamma block with a second line
+keep 9
+EOF
+
+cat <<EOF >moved.new
+keep 1
+keep 2
+keep 3
+keep 4
+keep 5
+keep 6
+x y
+keep 7
+keep 8
+keep 9
+beta block with a first line
+beta block with a second line
+alpha block with a first line
+alpha block with a second line
+gamma block with a first line
+gamma block with a second line
+EOF
+
+git diff --no-index --no-color moved.old moved.new | tail -n +5 >moved.diff || test $? = 1
+for mode in plain blocks zebra dimmed-zebra; do
+  git diff --no-index --color=always --color-moved=$mode moved.old moved.new | tail -n +5 >moved.$mode || test $? = 1
+done
@@ -0,0 +1,208 @@
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::progress::{self, Count, DynNestedProgress, Progress};
+use gix_hash::ObjectId;
+use gix_hashtable::{HashMap, HashSet};
+use gix_object::{tree::EntryKind, Kind, ObjectRef};
+
+use crate::full::{Error, Missing, Options, Outcome, Problem, ProgressId, Unreachable};
+
+/// Check all objects with `ids` in `objects` for validity and verify their hashes, then find all objects that are
+/// reachable from the `tips`, like the objects pointed to by references, reflogs and the index, to learn which
+/// objects are missing or unreachable.
+///
+/// Objects that are reachable but not among `ids` are looked up in `objects` as needed, without checking them.
+/// Submodule commits are never expected to exist, and neither are objects referenced by
+/// [promisor objects](Options::promisor_objects).
+///
+/// Use `progress` to learn about the amount of checked objects, and `should_interrupt` to stop the operation early.
+///
+/// This is the equivalent of `git fsck --full`, while the starting points must be provided by the caller.
+pub fn full<Find>(
+    objects: &Find,
+    ids: impl IntoIterator<Item = ObjectId>,
+    tips: impl IntoIterator<Item = ObjectId>,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    options: &Options,
+) -> Result<Outcome, Error>
+where
+    Find: gix_object::Find + ?Sized,
+{
+    let mut out = Outcome::default();
+    let mut buf = Vec::new();
+    let mut nodes = HashMap::<ObjectId, Node>::default();
+    let mut referenced = HashSet::default();
+    let mut mismatched = HashSet::default();
+
+    let ids = ids.into_iter();
+    let start = std::time::Instant::now();
+    let mut checked = progress.add_child_with_id("checking objects".into(), ProgressId::CheckedObjects.into());
+    checked.init(ids.size_hint().1, progress::count("objects"));
+    for id in ids {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        checked.inc();
+        if nodes.contains_key(&id) || mismatched.contains(&id) {
+            // The same object may be stored multiple times.
+            continue;
+        }
+        let data = match objects.try_find(&id, &mut buf) {
+            Ok(Some(data)) => data,
+            Ok(None) => continue,
+            Err(source) => {
+                out.problems.push(Problem::Unreadable { id, source });
+                continue;
+            }
+        };
+        out.num_objects += 1;
+        let actual = gix_object::compute_hash(id.kind(), data.kind, data.data);
+        if actual != id {
+            out.problems.push(Problem::HashMismatch {
+                id,
+                kind: data.kind,
+                actual,
+            });
+            // Like `git`, consider the object to exist under its actual id, so the expected one is missing.
+            mismatched.insert(id);
+        }
+        let id = actual;
+        if !options.skip_list.contains(&id) {
+            crate::object::check(data.kind, data.data, id.kind(), &options.object, |message| {
+                out.problems.push(Problem::Invalid {
+                    id,
+                    kind: data.kind,
+                    message,
+                });
+            });
+        }
+        let node = Node::from_data(data);
+        referenced.extend(node.references.iter().map(|(id, _kind)| *id));
+        nodes.insert(id, node);
+    }
+    checked.show_throughput(start);
+
+    let mut reachable = HashSet::default();
+    // Missing starting points, whose kind is learned once an object refers to them.
+    let mut missing_tips = HashMap::<ObjectId, usize>::default();
+    let start = std::time::Instant::now();
+    let mut reached = progress.add_child_with_id("checking connectivity".into(), ProgressId::ReachableObjects.into());
+    reached.init(Some(nodes.len()), progress::count("objects"));
+    let mut queue: VecDeque<_> = tips.into_iter().map(|id| (id, None, None)).collect();
+    while let Some((id, kind, referenced_by)) = queue.pop_front() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if !reachable.insert(id) {
+            if let Some(missing) = kind
+                .and_then(|_| missing_tips.remove(&id))
+                .map(|idx| &mut out.missing[idx])
+            {
+                missing.kind = kind;
+                missing.referenced_by = referenced_by;
+            }
+            continue;
+        }
+        reached.inc();
+        if !nodes.contains_key(&id) {
+            // Objects that weren't listed, like those in alternates, are only needed to follow their references.
+            let data = if mismatched.contains(&id) {
+                Ok(None)
+            } else {
+                objects.try_find(&id, &mut buf)
+            };
+            match data {
+                Ok(Some(data)) => {
+                    nodes.insert(id, Node::from_data(data));
+                }
+                Ok(None) => {
+                    if kind.is_none() {
+                        missing_tips.insert(id, out.missing.len());
+                    }
+                    out.missing.push(Missing {
+                        id,
+                        kind,
+                        referenced_by,
+                    });
+                    continue;
+                }
+                Err(source) => {
+                    out.problems.push(Problem::Unreadable { id, source });
+                    continue;
+                }
+            }
+        }
+        let node = &nodes[&id];
+        let is_shallow = node.kind == Kind::Commit && options.shallow_commits.contains(&id);
+        queue.extend(
+            node.references
+                .iter()
+                .filter(|(referenced_id, kind)| {
+                    !(is_shallow && *kind == Kind::Commit)
+                        && (!reachable.contains(referenced_id) || missing_tips.contains_key(referenced_id))
+                })
+                .map(|(referenced_id, kind)| (*referenced_id, Some(*kind), Some(id))),
+        );
+    }
+    reached.show_throughput(start);
+
+    if !options.promisor_objects.is_empty() {
+        let promised: HashSet = options
+            .promisor_objects
+            .iter()
+            .filter_map(|id| nodes.get(id))
+            .flat_map(|node| node.references.iter().map(|(id, _kind)| *id))
+            .collect();
+        out.missing.retain(|missing| !promised.contains(&missing.id));
+    }
+
+    out.unreachable = nodes
+        .iter()
+        .filter(|(id, _node)| !reachable.contains(*id))
+        .map(|(id, node)| Unreachable {
+            id: *id,
+            kind: node.kind,
+            is_dangling: !referenced.contains(id),
+        })
+        .collect();
+    out.unreachable.sort_by_key(|object| object.id);
+    Ok(out)
+}
+
+struct Node {
+    kind: Kind,
+    /// The objects this one refers to, along with their expected kind.
+    references: Vec<(ObjectId, Kind)>,
+}
+
+impl Node {
+    fn from_data(data: gix_object::Data<'_>) -> Self {
+        let references = match ObjectRef::from_bytes(data.kind, data.data) {
+            Ok(ObjectRef::Commit(commit)) => std::iter::once((commit.tree(), Kind::Tree))
+                .chain(commit.parents().map(|id| (id, Kind::Commit)))
+                .collect(),
+            Ok(ObjectRef::Tree(tree)) => tree
+                .entries
+                .iter()
+                .filter_map(|entry| {
+                    let kind = match entry.mode.kind() {
+                        EntryKind::Tree => Kind::Tree,
+                        EntryKind::Blob | EntryKind::BlobExecutable | EntryKind::Link => Kind::Blob,
+                        EntryKind::Commit => return None,
+                    };
+                    Some((entry.oid.to_owned(), kind))
+                })
+                .collect(),
+            Ok(ObjectRef::Tag(tag)) => vec![(tag.target(), tag.target_kind)],
+            Ok(ObjectRef::Blob(_)) | Err(_) => Vec::new(),
+        };
+        Node {
+            kind: data.kind,
+            references,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use gix_hash::ObjectId;
+use gix_object::Kind;
+
+use crate::{message::Severity, Message};
+
+pub(super) mod function;
+
+/// The error returned by [`full()`](crate::full()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Options for [`full()`](crate::full()).
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Options to check the validity of each object.
+    pub object: crate::object::Options,
+    /// Objects whose [invalidity](Problem::Invalid) shouldn't be reported, like configured with `fsck.skipList`.
+    pub skip_list: gix_hashtable::HashSet,
+    /// Commits at the boundary of a shallow repository, whose parents are not expected to exist.
+    pub shallow_commits: gix_hashtable::HashSet,
+    /// Objects in packs that were received from a promisor remote, whose references are not expected to exist
+    /// as these were promised by the remote, like in partial clones.
+    pub promisor_objects: gix_hashtable::HashSet,
+}
+
+/// A problem with an object that exists in the database.
+#[derive(Debug)]
+pub enum Problem {
+    /// The object could not be read from the database.
+    Unreadable {
+        /// The id of the object.
+        id: ObjectId,
+        /// The reason it could not be read.
+        source: gix_object::find::Error,
+    },
+    /// The hash of the object's data doesn't match the id it was stored under.
+    ///
+    /// Like in `git`, the object is treated as if it existed under its `actual` id, so `id` will be [missing](Missing)
+    /// if it is reachable.
+    HashMismatch {
+        /// The id the object was stored under.
+        id: ObjectId,
+        /// The kind of the object.
+        kind: Kind,
+        /// The id the object's data actually hashes to.
+        actual: ObjectId,
+    },
+    /// The object's data is invalid.
+    Invalid {
+        /// The id of the object.
+        id: ObjectId,
+        /// The kind of the object.
+        kind: Kind,
+        /// What's wrong with it.
+        message: Message,
+    },
+}
+
+impl Problem {
+    /// Return `true` if this problem means the object is broken, instead of only being suspicious.
+    pub fn is_error(&self) -> bool {
+        match self {
+            Problem::Unreadable { .. } | Problem::HashMismatch { .. } => true,
+            Problem::Invalid { message, .. } => message.severity == Severity::Error,
+        }
+    }
+}
+
+/// An object that is reachable, but doesn't exist in the database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Missing {
+    /// The id of the object.
+    pub id: ObjectId,
+    /// The kind of the object as expected by the object referring to it, or `None` if it is a starting point
+    /// that no other object refers to.
+    pub kind: Option<Kind>,
+    /// The object that refers to it, or `None` if it is a starting point that no other object refers to.
+    pub referenced_by: Option<ObjectId>,
+}
+
+/// An object that exists in the database but isn't reachable from any of the starting points.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Unreachable {
+    /// The id of the object.
+    pub id: ObjectId,
+    /// The kind of the object.
+    pub kind: Kind,
+    /// If `true`, the object isn't referred to by any other object, like a commit that was amended.
+    pub is_dangling: bool,
+}
+
+/// The result of [`full()`](crate::full()).
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The problems with objects in the order they were found.
+    pub problems: Vec<Problem>,
+    /// Objects that are reachable but don't exist, in the order they were found.
+    pub missing: Vec<Missing>,
+    /// Objects that aren't reachable, sorted by id.
+    pub unreachable: Vec<Unreachable>,
+    /// The amount of objects that were checked.
+    pub num_objects: usize,
+}
+
+impl Outcome {
+    /// Return `true` if objects are missing or broken, which is when `git fsck` would fail.
+    pub fn has_errors(&self) -> bool {
+        !self.missing.is_empty() || self.problems.iter().any(Problem::is_error)
+    }
+}
+
+/// The progress ids used in [`full()`](crate::full()).
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of objects whose data was checked.
+    CheckedObjects,
+    /// The amount of objects found to be reachable.
+    ReachableObjects,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CheckedObjects => *b"FSCO",
+            ProgressId::ReachableObjects => *b"FSRO",
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! A library for performing object database integrity and connectivity checks
+//!
+//! Use [`full()`] to check all objects and find those that are missing or unreachable, similar to `git fsck --full`,
+//! or [`Connectivity`] to only find missing objects reachable from commits.
 #![deny(rust_2018_idioms, unsafe_code, missing_docs)]
 
 use std::collections::VecDeque;
@@ -7,6 +10,30 @@ use gix_hash::ObjectId;
 use gix_hashtable::HashSet;
 use gix_object::{tree::EntryKind, Exists, FindExt, Kind};
 
+///
+pub mod message;
+
+///
+pub mod object;
+
+///
+pub mod skip_list;
+
+///
+pub mod full;
+pub use full::function::full;
+
+/// A problem found in an object by [`object::check()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// The identifier of the problem.
+    pub id: message::Id,
+    /// How to treat the problem, which is either an [error](message::Severity::Error) or a [warning](message::Severity::Warning).
+    pub severity: message::Severity,
+    /// A description of the problem, like `not properly sorted`.
+    pub description: std::borrow::Cow<'static, str>,
+}
+
 /// Perform a connectivity check.
 pub struct Connectivity<T, F>
 where
@@ -0,0 +1,224 @@
+use gix_object::bstr::BStr;
+
+/// The identifier of a problem that can be found in an object, as used in `git` to configure its severity with `fsck.<msg-id>`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[allow(missing_docs)]
+pub enum Id {
+    NulInHeader,
+    UnterminatedHeader,
+    BadDate,
+    BadDateOverflow,
+    BadEmail,
+    BadName,
+    BadObjectSha1,
+    BadParentSha1,
+    BadTimezone,
+    BadTree,
+    BadTreeSha1,
+    BadType,
+    DuplicateEntries,
+    MissingAuthor,
+    MissingCommitter,
+    MissingEmail,
+    MissingNameBeforeEmail,
+    MissingObject,
+    MissingSpaceBeforeDate,
+    MissingSpaceBeforeEmail,
+    MissingTag,
+    MissingTagEntry,
+    MissingTree,
+    MissingType,
+    MissingTypeEntry,
+    MultipleAuthors,
+    TreeNotSorted,
+    ZeroPaddedDate,
+    EmptyName,
+    FullPathname,
+    HasDot,
+    HasDotdot,
+    HasDotgit,
+    NullSha1,
+    ZeroPaddedFilemode,
+    NulInCommit,
+    BadFilemode,
+    BadTagName,
+    MissingTaggerEntry,
+    ExtraHeaderEntry,
+}
+
+impl Id {
+    /// All known identifiers.
+    pub const ALL: &'static [Id] = &[
+        Id::NulInHeader,
+        Id::UnterminatedHeader,
+        Id::BadDate,
+        Id::BadDateOverflow,
+        Id::BadEmail,
+        Id::BadName,
+        Id::BadObjectSha1,
+        Id::BadParentSha1,
+        Id::BadTimezone,
+        Id::BadTree,
+        Id::BadTreeSha1,
+        Id::BadType,
+        Id::DuplicateEntries,
+        Id::MissingAuthor,
+        Id::MissingCommitter,
+        Id::MissingEmail,
+        Id::MissingNameBeforeEmail,
+        Id::MissingObject,
+        Id::MissingSpaceBeforeDate,
+        Id::MissingSpaceBeforeEmail,
+        Id::MissingTag,
+        Id::MissingTagEntry,
+        Id::MissingTree,
+        Id::MissingType,
+        Id::MissingTypeEntry,
+        Id::MultipleAuthors,
+        Id::TreeNotSorted,
+        Id::ZeroPaddedDate,
+        Id::EmptyName,
+        Id::FullPathname,
+        Id::HasDot,
+        Id::HasDotdot,
+        Id::HasDotgit,
+        Id::NullSha1,
+        Id::ZeroPaddedFilemode,
+        Id::NulInCommit,
+        Id::BadFilemode,
+        Id::BadTagName,
+        Id::MissingTaggerEntry,
+        Id::ExtraHeaderEntry,
+    ];
+
+    /// Return the name of the identifier as used in `git`, like `treeNotSorted`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Id::NulInHeader => "nulInHeader",
+            Id::UnterminatedHeader => "unterminatedHeader",
+            Id::BadDate => "badDate",
+            Id::BadDateOverflow => "badDateOverflow",
+            Id::BadEmail => "badEmail",
+            Id::BadName => "badName",
+            Id::BadObjectSha1 => "badObjectSha1",
+            Id::BadParentSha1 => "badParentSha1",
+            Id::BadTimezone => "badTimezone",
+            Id::BadTree => "badTree",
+            Id::BadTreeSha1 => "badTreeSha1",
+            Id::BadType => "badType",
+            Id::DuplicateEntries => "duplicateEntries",
+            Id::MissingAuthor => "missingAuthor",
+            Id::MissingCommitter => "missingCommitter",
+            Id::MissingEmail => "missingEmail",
+            Id::MissingNameBeforeEmail => "missingNameBeforeEmail",
+            Id::MissingObject => "missingObject",
+            Id::MissingSpaceBeforeDate => "missingSpaceBeforeDate",
+            Id::MissingSpaceBeforeEmail => "missingSpaceBeforeEmail",
+            Id::MissingTag => "missingTag",
+            Id::MissingTagEntry => "missingTagEntry",
+            Id::MissingTree => "missingTree",
+            Id::MissingType => "missingType",
+            Id::MissingTypeEntry => "missingTypeEntry",
+            Id::MultipleAuthors => "multipleAuthors",
+            Id::TreeNotSorted => "treeNotSorted",
+            Id::ZeroPaddedDate => "zeroPaddedDate",
+            Id::EmptyName => "emptyName",
+            Id::FullPathname => "fullPathname",
+            Id::HasDot => "hasDot",
+            Id::HasDotdot => "hasDotdot",
+            Id::HasDotgit => "hasDotgit",
+            Id::NullSha1 => "nullSha1",
+            Id::ZeroPaddedFilemode => "zeroPaddedFilemode",
+            Id::NulInCommit => "nulInCommit",
+            Id::BadFilemode => "badFilemode",
+            Id::BadTagName => "badTagName",
+            Id::MissingTaggerEntry => "missingTaggerEntry",
+            Id::ExtraHeaderEntry => "extraHeaderEntry",
+        }
+    }
+
+    /// Find the identifier for `name`, compared case-insensitively like keys in the `git` configuration are.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|id| id.as_str().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Return the severity of the message if it isn't configured otherwise.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Id::NulInHeader
+            | Id::UnterminatedHeader
+            | Id::BadDate
+            | Id::BadDateOverflow
+            | Id::BadEmail
+            | Id::BadName
+            | Id::BadObjectSha1
+            | Id::BadParentSha1
+            | Id::BadTimezone
+            | Id::BadTree
+            | Id::BadTreeSha1
+            | Id::BadType
+            | Id::DuplicateEntries
+            | Id::MissingAuthor
+            | Id::MissingCommitter
+            | Id::MissingEmail
+            | Id::MissingNameBeforeEmail
+            | Id::MissingObject
+            | Id::MissingSpaceBeforeDate
+            | Id::MissingSpaceBeforeEmail
+            | Id::MissingTag
+            | Id::MissingTagEntry
+            | Id::MissingTree
+            | Id::MissingType
+            | Id::MissingTypeEntry
+            | Id::MultipleAuthors
+            | Id::TreeNotSorted
+            | Id::ZeroPaddedDate => Severity::Error,
+            Id::EmptyName
+            | Id::FullPathname
+            | Id::HasDot
+            | Id::HasDotdot
+            | Id::HasDotgit
+            | Id::NullSha1
+            | Id::ZeroPaddedFilemode
+            | Id::NulInCommit => Severity::Warning,
+            Id::BadFilemode | Id::BadTagName | Id::MissingTaggerEntry => Severity::Info,
+            Id::ExtraHeaderEntry => Severity::Ignore,
+        }
+    }
+}
+
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How to treat a message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The object is considered broken.
+    Error,
+    /// The object is considered suspicious, which is reported but doesn't make it broken.
+    Warning,
+    /// Like [`Warning`](Self::Warning), but it isn't turned into an error in strict mode.
+    ///
+    /// This is only used for [default severities](Id::default_severity()).
+    Info,
+    /// The message isn't reported at all.
+    Ignore,
+}
+
+impl Severity {
+    /// Parse `value` of `fsck.<msg-id>` in the `git` configuration, which is one of `error`, `warn` or `ignore`.
+    pub fn from_config_value(value: &BStr) -> Option<Self> {
+        Some(match value.to_ascii_lowercase().as_slice() {
+            b"error" => Severity::Error,
+            b"warn" => Severity::Warning,
+            b"ignore" => Severity::Ignore,
+            _ => return None,
+        })
+    }
+}
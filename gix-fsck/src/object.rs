@@ -0,0 +1,393 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use gix_object::{bstr::ByteSlice, Kind};
+
+use crate::{
+    message::{Id, Severity},
+    Message,
+};
+
+/// Options for [`check()`].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// If `true`, like `git fsck --strict`, messages that are [warnings](Severity::Warning) by default are errors,
+    /// and trees with entries that have group-write permissions, i.e. mode `100664`, are considered to have bad modes.
+    pub strict: bool,
+    /// The severity of messages that should differ from their [default](Id::default_severity()),
+    /// like configured with `fsck.<msg-id>`.
+    pub severities: HashMap<Id, Severity>,
+}
+
+impl Options {
+    /// Return the severity of messages with `id`, which is never [`Info`](Severity::Info).
+    pub fn severity(&self, id: Id) -> Severity {
+        match self.severities.get(&id).copied() {
+            Some(severity) => severity,
+            None => match id.default_severity() {
+                Severity::Warning if self.strict => Severity::Error,
+                Severity::Info => Severity::Warning,
+                severity => severity,
+            },
+        }
+    }
+}
+
+/// Check the `data` of an object of `kind` for validity, and call `report` with each message that isn't
+/// [ignored](Severity::Ignore).
+/// Object ids in the data are expected to be of `hash_kind`.
+///
+/// Like in `git`, the check of commits and tags stops after the first [error](Severity::Error), while all problems of
+/// trees are reported. Blobs are always valid.
+pub fn check(kind: Kind, data: &[u8], hash_kind: gix_hash::Kind, options: &Options, report: impl FnMut(Message)) {
+    let mut report = Report { options, report };
+    let hex_len = hash_kind.len_in_hex();
+    let _ = match kind {
+        Kind::Commit => commit(data, hex_len, &mut report),
+        Kind::Tree => tree(data, hash_kind.len_in_bytes(), options.strict, &mut report),
+        Kind::Tag => tag(data, hex_len, &mut report),
+        Kind::Blob => Ok(()),
+    };
+}
+
+/// Signal that checking should stop as an error was found.
+struct Stop;
+
+struct Report<'a, F> {
+    options: &'a Options,
+    report: F,
+}
+
+impl<F: FnMut(Message)> Report<'_, F> {
+    fn report(&mut self, id: Id, description: impl Into<Cow<'static, str>>) -> Result<(), Stop> {
+        let severity = self.options.severity(id);
+        if severity == Severity::Ignore {
+            return Ok(());
+        }
+        (self.report)(Message {
+            id,
+            severity,
+            description: description.into(),
+        });
+        if severity == Severity::Error {
+            Err(Stop)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn commit(data: &[u8], hex_len: usize, r: &mut Report<'_, impl FnMut(Message)>) -> Result<(), Stop> {
+    verify_headers(data, r)?;
+    let Some(mut rest) = data.strip_prefix(b"tree ") else {
+        return r.report(Id::MissingTree, "invalid format - expected 'tree' line");
+    };
+    rest = match hex_line(rest, hex_len) {
+        Some(rest) => rest,
+        None => {
+            r.report(Id::BadTreeSha1, "invalid 'tree' line format - bad sha1")?;
+            next_line(rest)
+        }
+    };
+    while let Some(parent) = rest.strip_prefix(b"parent ") {
+        rest = match hex_line(parent, hex_len) {
+            Some(rest) => rest,
+            None => {
+                r.report(Id::BadParentSha1, "invalid 'parent' line format - bad sha1")?;
+                next_line(parent)
+            }
+        };
+    }
+    let mut num_authors = 0;
+    while let Some(author) = rest.strip_prefix(b"author ") {
+        num_authors += 1;
+        rest = ident(author, r)?;
+    }
+    match num_authors {
+        0 => r.report(Id::MissingAuthor, "invalid format - expected 'author' line")?,
+        1 => {}
+        _ => r.report(Id::MultipleAuthors, "invalid format - multiple 'author' lines")?,
+    }
+    let Some(committer) = rest.strip_prefix(b"committer ") else {
+        return r.report(Id::MissingCommitter, "invalid format - expected 'committer' line");
+    };
+    ident(committer, r)?;
+    if data.contains(&0) {
+        r.report(Id::NulInCommit, "NUL byte in the commit object body")?;
+    }
+    Ok(())
+}
+
+fn tag(data: &[u8], hex_len: usize, r: &mut Report<'_, impl FnMut(Message)>) -> Result<(), Stop> {
+    verify_headers(data, r)?;
+    let Some(mut rest) = data.strip_prefix(b"object ") else {
+        return r.report(Id::MissingObject, "invalid format - expected 'object' line");
+    };
+    rest = match hex_line(rest, hex_len) {
+        Some(rest) => rest,
+        None => {
+            r.report(Id::BadObjectSha1, "invalid 'object' line format - bad sha1")?;
+            next_line(rest)
+        }
+    };
+    let Some(kind) = rest.strip_prefix(b"type ") else {
+        return r.report(Id::MissingTypeEntry, "invalid format - expected 'type' line");
+    };
+    let Some(eol) = kind.find_byte(b'\n') else {
+        return r.report(Id::MissingType, "invalid format - unexpected end after 'type' line");
+    };
+    if Kind::from_bytes(&kind[..eol]).is_err() {
+        r.report(Id::BadType, "invalid 'type' value")?;
+    }
+    rest = &kind[eol + 1..];
+    let Some(name) = rest.strip_prefix(b"tag ") else {
+        return r.report(Id::MissingTagEntry, "invalid format - expected 'tag' line");
+    };
+    let Some(eol) = name.find_byte(b'\n') else {
+        return r.report(Id::MissingTag, "invalid format - unexpected end after 'type' line");
+    };
+    let mut ref_name = b"refs/tags/".to_vec();
+    ref_name.extend_from_slice(&name[..eol]);
+    if gix_validate::reference::name(ref_name.as_bstr()).is_err() {
+        r.report(Id::BadTagName, format!("invalid 'tag' name: {}", name[..eol].as_bstr()))?;
+    }
+    rest = &name[eol + 1..];
+    match rest.strip_prefix(b"tagger ") {
+        Some(tagger) => rest = ident(tagger, r)?,
+        None => r.report(Id::MissingTaggerEntry, "invalid format - expected 'tagger' line")?,
+    }
+    if !rest.is_empty() && !rest.starts_with(b"\n") {
+        r.report(Id::ExtraHeaderEntry, "invalid format - extra header(s) after 'tagger'")?;
+    }
+    Ok(())
+}
+
+fn tree(data: &[u8], hash_len: usize, strict: bool, r: &mut Report<'_, impl FnMut(Message)>) -> Result<(), Stop> {
+    let mut entries = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let Some(entry) = tree_entry(rest, hash_len) else {
+            return r.report(Id::BadTree, "cannot be parsed as a tree");
+        };
+        rest = &rest[entry.len..];
+        entries.push(entry);
+    }
+
+    let (mut has_null_sha1, mut has_full_path, mut has_empty_name, mut has_dot, mut has_dotdot, mut has_dotgit) =
+        (false, false, false, false, false, false);
+    let (mut has_zero_pad, mut has_bad_modes, mut has_dup_entries, mut not_properly_sorted) =
+        (false, false, false, false);
+    let mut names = HashSet::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        has_null_sha1 |= entry.hash.iter().all(|b| *b == 0);
+        has_full_path |= entry.name.contains(&b'/');
+        has_empty_name |= entry.name.is_empty();
+        has_dot |= entry.name == b".";
+        has_dotdot |= entry.name == b"..";
+        has_dotgit |= matches!(
+            gix_validate::path::component(
+                entry.name.as_bstr(),
+                None,
+                gix_validate::path::component::Options {
+                    protect_windows: false,
+                    protect_hfs: true,
+                    protect_ntfs: true,
+                }
+            ),
+            Err(gix_validate::path::component::Error::DotGitDir)
+        );
+        has_zero_pad |= entry.mode_bytes.starts_with(b"0");
+        has_bad_modes |= match entry.mode {
+            0o100755 | 0o100644 | 0o120000 | 0o040000 | 0o160000 => false,
+            0o100664 => strict,
+            _ => true,
+        };
+        has_dup_entries |= !names.insert(entry.name);
+        if let Some(previous) = idx.checked_sub(1).map(|idx| &entries[idx]) {
+            match is_ordered(previous, entry) {
+                Ordering::Ordered => {}
+                Ordering::Unordered => not_properly_sorted = true,
+                Ordering::Duplicate => has_dup_entries = true,
+            }
+        }
+    }
+
+    for (is_set, id, description) in [
+        (has_null_sha1, Id::NullSha1, "contains entries pointing to null sha1"),
+        (has_full_path, Id::FullPathname, "contains full pathnames"),
+        (has_empty_name, Id::EmptyName, "contains empty pathname"),
+        (has_dot, Id::HasDot, "contains '.'"),
+        (has_dotdot, Id::HasDotdot, "contains '..'"),
+        (has_dotgit, Id::HasDotgit, "contains '.git'"),
+        (has_zero_pad, Id::ZeroPaddedFilemode, "contains zero-padded file modes"),
+        (has_bad_modes, Id::BadFilemode, "contains bad file modes"),
+        (has_dup_entries, Id::DuplicateEntries, "contains duplicate file entries"),
+        (not_properly_sorted, Id::TreeNotSorted, "not properly sorted"),
+    ] {
+        if is_set {
+            // Like `git`, all problems of a tree are reported, even after an error.
+            let _ = r.report(id, description);
+        }
+    }
+    Ok(())
+}
+
+struct TreeEntry<'a> {
+    mode_bytes: &'a [u8],
+    mode: u32,
+    name: &'a [u8],
+    hash: &'a [u8],
+    /// The amount of bytes the entry takes in the tree.
+    len: usize,
+}
+
+fn tree_entry(data: &[u8], hash_len: usize) -> Option<TreeEntry<'_>> {
+    let space = data.find_byte(b' ')?;
+    let mode_bytes = &data[..space];
+    if mode_bytes.is_empty() || mode_bytes.len() > 7 || !mode_bytes.iter().all(|b| (b'0'..=b'7').contains(b)) {
+        return None;
+    }
+    let mode = mode_bytes.iter().fold(0, |mode, b| (mode << 3) + u32::from(b - b'0'));
+    let name_start = space + 1;
+    let name_len = data[name_start..].find_byte(0)?;
+    let hash_start = name_start + name_len + 1;
+    let hash = data.get(hash_start..hash_start + hash_len)?;
+    Some(TreeEntry {
+        mode_bytes,
+        mode,
+        name: &data[name_start..][..name_len],
+        hash,
+        len: hash_start + hash_len,
+    })
+}
+
+enum Ordering {
+    Ordered,
+    Unordered,
+    Duplicate,
+}
+
+/// Compare entries like `git` does, which sorts directories as if their name ended with a slash.
+fn is_ordered(a: &TreeEntry<'_>, b: &TreeEntry<'_>) -> Ordering {
+    let len = a.name.len().min(b.name.len());
+    match a.name[..len].cmp(&b.name[..len]) {
+        std::cmp::Ordering::Less => return Ordering::Ordered,
+        std::cmp::Ordering::Greater => return Ordering::Unordered,
+        std::cmp::Ordering::Equal => {}
+    }
+    let next_byte = |entry: &TreeEntry<'_>| match entry.name.get(len) {
+        Some(b) => *b,
+        None if entry.mode & 0o170000 == 0o040000 => b'/',
+        None => 0,
+    };
+    if a.name.len() == b.name.len() {
+        return Ordering::Duplicate;
+    }
+    if next_byte(a) < next_byte(b) {
+        Ordering::Ordered
+    } else {
+        Ordering::Unordered
+    }
+}
+
+/// Assure the header of `data` is terminated and free of NUL bytes.
+fn verify_headers(data: &[u8], r: &mut Report<'_, impl FnMut(Message)>) -> Result<(), Stop> {
+    for (idx, b) in data.iter().enumerate() {
+        match b {
+            0 => return r.report(Id::NulInHeader, "unterminated header: NUL in header"),
+            b'\n' if data.get(idx + 1) == Some(&b'\n') => return Ok(()),
+            _ => {}
+        }
+    }
+    if data.last() == Some(&b'\n') {
+        return Ok(());
+    }
+    r.report(Id::UnterminatedHeader, "unterminated header")
+}
+
+/// Check the identity of an author, committer or tagger in the line at the beginning of `data`,
+/// and return the data past the line.
+///
+/// This is a port of `fsck_ident()` in `git`.
+fn ident<'a>(data: &'a [u8], r: &mut Report<'_, impl FnMut(Message)>) -> Result<&'a [u8], Stop> {
+    let rest = next_line(data);
+    let line = &data[..data.len() - rest.len()];
+    let check = || -> Result<(), (Id, &'static str)> {
+        if line.first() == Some(&b'<') {
+            return Err((
+                Id::MissingNameBeforeEmail,
+                "invalid author/committer line - missing space before email",
+            ));
+        }
+        let email_start = match line.iter().position(|b| matches!(b, b'<' | b'>' | b'\n')) {
+            Some(pos) if line[pos] == b'<' => pos,
+            Some(pos) if line[pos] == b'>' => {
+                return Err((Id::BadName, "invalid author/committer line - bad name"));
+            }
+            _ => return Err((Id::MissingEmail, "invalid author/committer line - missing email")),
+        };
+        if email_start == 0 || line[email_start - 1] != b' ' {
+            return Err((
+                Id::MissingSpaceBeforeEmail,
+                "invalid author/committer line - missing space before email",
+            ));
+        }
+        let email_end = match line[email_start + 1..]
+            .iter()
+            .position(|b| matches!(b, b'<' | b'>' | b'\n'))
+        {
+            Some(pos) if line[email_start + 1 + pos] == b'>' => email_start + 1 + pos,
+            _ => return Err((Id::BadEmail, "invalid author/committer line - bad email")),
+        };
+        let Some(date) = line[email_end + 1..].strip_prefix(b" ") else {
+            return Err((
+                Id::MissingSpaceBeforeDate,
+                "invalid author/committer line - missing space before date",
+            ));
+        };
+        if date.first() == Some(&b'0') && date.get(1) != Some(&b' ') {
+            return Err((Id::ZeroPaddedDate, "invalid author/committer line - zero-padded date"));
+        }
+        let num_digits = date.iter().take_while(|b| b.is_ascii_digit()).count();
+        let overflows = date[..num_digits]
+            .iter()
+            .try_fold(0i64, |time, b| time.checked_mul(10)?.checked_add(i64::from(b - b'0')))
+            .is_none();
+        if overflows {
+            return Err((
+                Id::BadDateOverflow,
+                "invalid author/committer line - date causes integer overflow",
+            ));
+        }
+        let Some(tz) = (num_digits != 0)
+            .then(|| date[num_digits..].strip_prefix(b" "))
+            .flatten()
+        else {
+            return Err((Id::BadDate, "invalid author/committer line - bad date"));
+        };
+        let is_valid_tz =
+            tz.len() >= 6 && matches!(tz[0], b'+' | b'-') && tz[1..5].iter().all(u8::is_ascii_digit) && tz[5] == b'\n';
+        if !is_valid_tz {
+            return Err((Id::BadTimezone, "invalid author/committer line - bad time zone"));
+        }
+        Ok(())
+    };
+    if let Err((id, description)) = check() {
+        r.report(id, description)?;
+    }
+    Ok(rest)
+}
+
+/// Return the data past the line with the hexadecimal object id of `hex_len` at the beginning of `data`,
+/// or `None` if there is no such line.
+fn hex_line(data: &[u8], hex_len: usize) -> Option<&[u8]> {
+    let hex = data.get(..hex_len)?;
+    (hex.iter().all(u8::is_ascii_hexdigit) && data.get(hex_len) == Some(&b'\n')).then(|| &data[hex_len + 1..])
+}
+
+/// Return the data past the first line in `data`, or nothing if there is no newline.
+fn next_line(data: &[u8]) -> &[u8] {
+    data.find_byte(b'\n').map_or(&[], |pos| &data[pos + 1..])
+}
@@ -0,0 +1,36 @@
+use gix_hash::ObjectId;
+use gix_hashtable::HashSet;
+use gix_object::bstr::{BString, ByteSlice};
+
+/// The error returned by [`parse()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number} doesn't contain a full hexadecimal object id: '{line}'")]
+    InvalidObjectId {
+        line_number: usize,
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+}
+
+/// Parse `input` in the format of the file configured with `fsck.skipList` into the set of ids of objects
+/// whose problems should [not be reported](crate::full::Options::skip_list).
+///
+/// Like in `git`, each line contains a full hexadecimal object id, and everything after `#` is a comment.
+/// Empty lines are skipped.
+pub fn parse(input: &[u8]) -> Result<HashSet, Error> {
+    let mut out = HashSet::default();
+    for (line_number, line) in input.lines().enumerate() {
+        let hex = line.find_byte(b'#').map_or(line, |pos| &line[..pos]).trim();
+        if hex.is_empty() {
+            continue;
+        }
+        out.insert(ObjectId::from_hex(hex).map_err(|source| Error::InvalidObjectId {
+            line_number: line_number + 1,
+            line: line.into(),
+            source,
+        })?);
+    }
+    Ok(out)
+}
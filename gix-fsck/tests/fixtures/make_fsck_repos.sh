@@ -0,0 +1,98 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Each repository has a `tips` file with all objects reachable from references, the reflog and the index,
+# along with the output of various invocations of `git fsck`.
+
+function write_tips() {
+  { git rev-parse HEAD
+    git for-each-ref --format='%(objectname)'
+    git reflog --all --format='%H'
+    git ls-files -s | awk '{print $2}'
+  } | sort -u > tips
+}
+
+function baselines() {
+  touch skip-list
+  git fsck > fsck.baseline 2>&1 || true
+  git fsck --strict > fsck-strict.baseline 2>&1 || true
+  git fsck --unreachable --no-dangling > fsck-unreachable.baseline 2>&1 || true
+  git -c fsck.zeroPaddedFilemode=ignore -c fsck.badEmail=warn -c fsck.skipList=skip-list fsck > fsck-configured.baseline 2>&1 || true
+}
+
+function tree_with() {
+  local out=$1; shift
+  : > "$out"
+  while [ $# -gt 0 ]; do
+    printf '%s %s\0' "$1" "$2" >> "$out"
+    echo "$3" | xxd -r -p >> "$out"
+    shift 3
+  done
+  git hash-object -t tree --literally -w "$out"
+}
+
+git init invalid
+(cd invalid
+  echo a > a
+  echo b > b
+  git add a b
+  git commit -q -m "initial"
+  git tag -a -m "annotated" v1
+  git checkout -q -b other
+  echo c > c
+  git add c
+  git commit -q -m "other"
+  git checkout -q main 2>/dev/null || git checkout -q master
+
+  blob=$(git rev-parse @:a)
+  tree=$(git rev-parse @^{tree})
+  commit=$(git rev-parse @)
+  tree_with bad-mode 100664 f "$blob" > /dev/null
+  tree_with zero-padded 0100644 g "$blob" > /dev/null
+  tree_with unsorted 100644 b "$blob" 100644 a "$blob" > /dev/null
+  tree_with duplicate 100644 a "$blob" 040000 a "$tree" > /dev/null
+  tree_with dotgit 100644 .GIT "$blob" > /dev/null
+  printf 'tree %s\nauthor a <a@b 1 +0000\ncommitter a <a@b> 1 +0000\n\nbad email\n' "$tree" | git hash-object -t commit --literally -w --stdin > skip-list
+  printf 'tree %s\nauthor a <a@b> 1 +0000\ncommitter a <a@b> 1 0000\n\nbad timezone\n' "$tree" | git hash-object -t commit --literally -w --stdin > /dev/null
+  printf 'tree %s\ncommitter a <a@b> 1 +0000\n\nmissing author\n' "$tree" | git hash-object -t commit --literally -w --stdin > /dev/null
+  printf 'tree %s\nauthor a <a@b> 01 +0000\ncommitter a <a@b> 1 +0000\n\nzero-padded date\n' "$tree" | git hash-object -t commit --literally -w --stdin > /dev/null
+  printf 'tree %s\nparent %s\nauthor a <a@b> 1 +0000\ncommitter a <a@b> 1 +0000\n\nvalid but dangling\n' "$tree" "$commit" | git hash-object -t commit --literally -w --stdin > /dev/null
+  printf 'object %s\ntype commit\ntag v2\n\nno tagger\n' "$commit" | git hash-object -t tag --literally -w --stdin > /dev/null
+  printf 'object %s\ntype commit\ntag v3..\ntagger a <a@b> 1 +0000\n\nbad tag name\n' "$commit" | git hash-object -t tag --literally -w --stdin > /dev/null
+  echo "unreachable blob" > unreachable
+  tree_with unreachable-tree 100644 unreachable "$(git hash-object -w unreachable)" > /dev/null
+
+  write_tips
+  baselines
+)
+
+git init missing
+(cd missing
+  mkdir dir
+  echo a > a
+  echo b > dir/b
+  git add a dir
+  git commit -q -m "initial"
+  echo c > a
+  git commit -q -am "second"
+  rm .git/objects/"$(git rev-parse @~1:a | sed 's/../&\//')"
+  rm .git/objects/"$(git rev-parse @:dir | sed 's/../&\//')"
+
+  write_tips
+  baselines
+)
+
+git init hash-mismatch
+(cd hash-mismatch
+  echo a > a
+  git add a
+  git commit -q -m "initial"
+  blob=$(git rev-parse @:a)
+  other=$(echo other | git hash-object -w --stdin)
+  path=.git/objects/$(echo "$blob" | sed 's/../&\//')
+  chmod u+w "$path"
+  cp .git/objects/"$(echo "$other" | sed 's/../&\//')" "$path"
+
+  write_tips
+  baselines
+)
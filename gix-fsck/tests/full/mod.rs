@@ -0,0 +1,234 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_fsck::{
+    full::{Options, Outcome, Problem},
+    message::{Id, Severity},
+};
+use gix_hash::ObjectId;
+use gix_object::bstr::ByteSlice;
+
+use crate::hex_to_id;
+
+fn repo_path(name: &str) -> std::path::PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_fsck_repos.sh")
+        .expect("fixture path")
+        .join(name)
+}
+
+fn run(repo_name: &str, options: &Options) -> Outcome {
+    let repo = repo_path(repo_name);
+    let mut db = gix_odb::at(repo.join(".git").join("objects")).expect("valid odb");
+    db.refresh_never();
+    let ids: Vec<ObjectId> = db
+        .iter()
+        .expect("indices can be loaded")
+        .map(|id| id.expect("iteration works"))
+        .collect();
+    let tips: Vec<ObjectId> = std::fs::read(repo.join("tips"))
+        .expect("tips were written")
+        .lines()
+        .map(|line| hex_to_id(line.to_str().expect("ascii")))
+        .collect();
+    gix_fsck::full(
+        &db,
+        ids,
+        tips,
+        &mut gix_features::progress::Discard,
+        &AtomicBool::default(),
+        options,
+    )
+    .expect("not interrupted")
+}
+
+/// Format the `outcome` like `git fsck` would, for comparison with the lines of `git` that refer to objects.
+fn git_lines(outcome: &Outcome, show_unreachable: bool) -> Vec<String> {
+    let mut lines: Vec<_> = outcome
+        .problems
+        .iter()
+        .filter_map(|problem| match problem {
+            Problem::Invalid { id, kind, message } => Some(format!(
+                "{} in {kind} {id}: {}: {}",
+                if message.severity == Severity::Error {
+                    "error"
+                } else {
+                    "warning"
+                },
+                message.id,
+                message.description
+            )),
+            Problem::Unreadable { .. } | Problem::HashMismatch { .. } => None,
+        })
+        .chain(outcome.unreachable.iter().filter_map(|object| {
+            if show_unreachable {
+                Some(format!("unreachable {} {}", object.kind, object.id))
+            } else {
+                object
+                    .is_dangling
+                    .then(|| format!("dangling {} {}", object.kind, object.id))
+            }
+        }))
+        .chain(outcome.missing.iter().map(|object| {
+            format!(
+                "missing {} {}",
+                object.kind.expect("all missing objects are referenced"),
+                object.id
+            )
+        }))
+        .collect();
+    lines.sort();
+    lines
+}
+
+fn baseline(repo_name: &str, name: &str) -> Vec<String> {
+    let mut lines: Vec<_> = std::fs::read_to_string(repo_path(repo_name).join(name))
+        .expect("baseline was written")
+        .lines()
+        .filter(|line| {
+            ["warning in ", "error in ", "dangling ", "unreachable ", "missing "]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn invalid_objects_and_dangling_objects_match_git() {
+    let outcome = run("invalid", &Options::default());
+    assert_eq!(git_lines(&outcome, false), baseline("invalid", "fsck.baseline"));
+    assert!(outcome.has_errors());
+    assert!(outcome.missing.is_empty());
+}
+
+#[test]
+fn strict_mode_turns_warnings_into_errors() {
+    let mut options = Options::default();
+    options.object.strict = true;
+    let outcome = run("invalid", &options);
+    assert_eq!(git_lines(&outcome, false), baseline("invalid", "fsck-strict.baseline"));
+}
+
+#[test]
+fn unreachable_objects_include_those_that_are_referenced() {
+    let outcome = run("invalid", &Options::default());
+    assert_eq!(
+        git_lines(&outcome, true),
+        baseline("invalid", "fsck-unreachable.baseline")
+    );
+    assert!(
+        outcome.unreachable.windows(2).all(|w| w[0].id < w[1].id),
+        "unreachable objects are sorted by id"
+    );
+}
+
+#[test]
+fn configured_severities_and_skip_list() -> crate::Result {
+    let mut options = Options {
+        skip_list: gix_fsck::skip_list::parse(&std::fs::read(repo_path("invalid").join("skip-list"))?)?,
+        ..Default::default()
+    };
+    options.object.severities.extend([
+        (Id::ZeroPaddedFilemode, Severity::Ignore),
+        (Id::BadEmail, Severity::Warning),
+    ]);
+    let outcome = run("invalid", &options);
+    assert_eq!(
+        git_lines(&outcome, false),
+        baseline("invalid", "fsck-configured.baseline")
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_objects_are_reported_with_the_kind_their_parent_expects() {
+    let outcome = run("missing", &Options::default());
+    assert_eq!(git_lines(&outcome, false), baseline("missing", "fsck.baseline"));
+    assert!(outcome.has_errors());
+    assert!(outcome.missing.iter().all(|object| object.referenced_by.is_some()));
+}
+
+#[test]
+fn hash_mismatches_make_the_expected_object_missing() {
+    let outcome = run("hash-mismatch", &Options::default());
+    assert_eq!(git_lines(&outcome, false), baseline("hash-mismatch", "fsck.baseline"));
+    assert_eq!(outcome.problems.len(), 1);
+    let Problem::HashMismatch { id, kind, actual } = &outcome.problems[0] else {
+        panic!("expected a hash mismatch, got {:?}", outcome.problems[0])
+    };
+    assert_eq!(*kind, gix_object::Kind::Blob);
+    assert_eq!(outcome.missing[0].id, *id);
+    assert_eq!(outcome.unreachable[0].id, *actual);
+}
+
+#[test]
+fn interruption_is_respected() {
+    let repo = repo_path("invalid");
+    let db = gix_odb::at(repo.join(".git").join("objects")).expect("valid odb");
+    let err = gix_fsck::full(
+        &db,
+        db.iter().expect("indices can be loaded").map(Result::unwrap),
+        None,
+        &mut gix_features::progress::Discard,
+        &AtomicBool::new(true),
+        &Options::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, gix_fsck::full::Error::Interrupted));
+}
+
+#[test]
+fn objects_referenced_by_promisor_objects_are_not_missing() {
+    for repo_name in ["blobless", "treeless"] {
+        let objects_dir = gix_testtools::scripted_fixture_read_only("make_test_repos.sh")
+            .expect("fixture path")
+            .join(repo_name)
+            .join(".git")
+            .join("objects");
+        let mut db = gix_odb::at(&objects_dir).expect("valid odb");
+        db.refresh_never();
+        let ids: Vec<ObjectId> = db
+            .iter()
+            .expect("indices can be loaded")
+            .map(|id| id.expect("iteration works"))
+            .collect();
+
+        let mut promisor_objects = gix_hashtable::HashSet::default();
+        for entry in std::fs::read_dir(objects_dir.join("pack")).expect("pack directory exists") {
+            let path = entry.expect("readable").path();
+            if path.extension().is_some_and(|ext| ext == "idx") && gix_odb::promisor::is_promisor_pack(&path) {
+                let index = gix_odb::pack::index::File::at(&path, gix_hash::Kind::Sha1).expect("valid index");
+                promisor_objects.extend(index.iter().map(|entry| entry.oid));
+            }
+        }
+        assert_eq!(
+            promisor_objects.len(),
+            ids.len(),
+            "all objects were received from the remote"
+        );
+
+        let run = |options: &Options| {
+            gix_fsck::full(
+                &db,
+                ids.iter().copied(),
+                ids.iter().copied(),
+                &mut gix_features::progress::Discard,
+                &AtomicBool::default(),
+                options,
+            )
+            .expect("not interrupted")
+        };
+        assert!(
+            !run(&Options::default()).missing.is_empty(),
+            "{repo_name}: filtered objects are missing"
+        );
+
+        let outcome = run(&Options {
+            promisor_objects,
+            ..Default::default()
+        });
+        assert_eq!(outcome.missing, [], "{repo_name}: but they were promised");
+        assert!(!outcome.has_errors());
+    }
+}
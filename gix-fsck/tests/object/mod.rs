@@ -0,0 +1,99 @@
+use gix_fsck::{
+    message::{Id, Severity},
+    object::{check, Options},
+    Message,
+};
+use gix_object::Kind;
+
+fn messages(kind: Kind, data: &[u8], options: &Options) -> Vec<Message> {
+    let mut out = Vec::new();
+    check(kind, data, gix_hash::Kind::Sha1, options, |message| out.push(message));
+    out
+}
+
+fn ids(kind: Kind, data: &[u8], options: &Options) -> Vec<(Id, Severity)> {
+    messages(kind, data, options)
+        .into_iter()
+        .map(|message| (message.id, message.severity))
+        .collect()
+}
+
+const TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+#[test]
+fn valid_commit_and_tag_have_no_messages() {
+    let commit = format!("tree {TREE}\nauthor a <a@b> 1 +0000\ncommitter a <a@b> 1 +0000\n\nmessage\n");
+    assert_eq!(ids(Kind::Commit, commit.as_bytes(), &Options::default()), []);
+
+    let tag = format!("object {TREE}\ntype tree\ntag v1\ntagger a <a@b> 1 +0000\n\nmessage\n");
+    assert_eq!(ids(Kind::Tag, tag.as_bytes(), &Options::default()), []);
+    assert_eq!(ids(Kind::Blob, b"anything\0", &Options::default()), []);
+}
+
+#[test]
+fn checking_stops_at_the_first_error() {
+    let commit = "tree invalid\nauthor a <a@b 1 +0000\n\n";
+    assert_eq!(
+        ids(Kind::Commit, commit.as_bytes(), &Options::default()),
+        [(Id::BadTreeSha1, Severity::Error)]
+    );
+}
+
+#[test]
+fn severities_can_be_configured() {
+    let commit = format!("tree {TREE}\nauthor a <a@b 1 +0000\ncommitter a <a@b> 1 +0000\n\n");
+    let mut options = Options::default();
+    assert_eq!(
+        ids(Kind::Commit, commit.as_bytes(), &options),
+        [(Id::BadEmail, Severity::Error)]
+    );
+
+    options.severities.insert(Id::BadEmail, Severity::Warning);
+    assert_eq!(
+        ids(Kind::Commit, commit.as_bytes(), &options),
+        [(Id::BadEmail, Severity::Warning)],
+        "after a warning the remaining header is still checked"
+    );
+
+    options.severities.insert(Id::BadEmail, Severity::Ignore);
+    assert_eq!(ids(Kind::Commit, commit.as_bytes(), &options), []);
+}
+
+#[test]
+fn info_messages_are_warnings_even_in_strict_mode() {
+    let tag = format!("object {TREE}\ntype tree\ntag v1\n\nno tagger\n");
+    let options = Options {
+        strict: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(Kind::Tag, tag.as_bytes(), &options),
+        [(Id::MissingTaggerEntry, Severity::Warning)]
+    );
+}
+
+#[test]
+fn message_ids_round_trip_through_their_names() {
+    for id in Id::ALL {
+        assert_eq!(Id::from_name(id.as_str()), Some(*id));
+        assert_eq!(Id::from_name(&id.as_str().to_ascii_lowercase()), Some(*id));
+    }
+    assert_eq!(Id::from_name("unknown"), None);
+    assert_eq!(Severity::from_config_value("WARN".into()), Some(Severity::Warning));
+    assert_eq!(Severity::from_config_value("info".into()), None);
+}
+
+#[test]
+fn skip_list_parsing() -> crate::Result {
+    let ids = gix_fsck::skip_list::parse(
+        b"# comment\n\n4b825dc642cb6eb9a060e54bf8d69288fbee4904 # empty tree\n  e69de29bb2d1d6434b8b29ae775ad8c2e48c5391\n",
+    )?;
+    assert_eq!(ids.len(), 2);
+
+    let err = gix_fsck::skip_list::parse(b"\nnot-an-id").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2 doesn't contain a full hexadecimal object id: 'not-an-id'"
+    );
+    Ok(())
+}
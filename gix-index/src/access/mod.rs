@@ -6,10 +6,6 @@ use filetime::FileTime;
 use crate::entry::{Stage, StageRaw};
 use crate::{entry, extension, AccelerateLookup, Entry, PathStorage, PathStorageRef, State, Version};
 
-// TODO: integrate this somehow, somewhere, depending on later usage.
-#[allow(dead_code)]
-mod sparse;
-
 /// General information and entries
 impl State {
     /// Return the version used to store this state's information on disk.
@@ -481,6 +477,9 @@ impl State {
         mode: entry::Mode,
         path: &BStr,
     ) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(path);
+        }
         let path = {
             let path_start = self.path_backing.len();
             self.path_backing.push_str(path);
@@ -520,6 +519,7 @@ impl State {
     ///
     /// Note that the memory used for the removed entries paths is not freed, as it's append-only, and
     /// that some extensions might refer to paths which are now deleted.
+    /// The [`TREE` extension](State::tree()) is invalidated accordingly though.
     ///
     /// ### Performance
     ///
@@ -528,10 +528,16 @@ impl State {
     pub fn remove_entries(&mut self, mut should_remove: impl FnMut(usize, &BStr, &mut Entry) -> bool) {
         let mut index = 0;
         let paths = &self.path_backing;
+        let mut tree = self.tree.as_mut();
         self.entries.retain_mut(|e| {
             let path = e.path_in(paths);
             let res = !should_remove(index, path, e);
             index += 1;
+            if !res {
+                if let Some(tree) = tree.as_mut() {
+                    tree.invalidate_path(path);
+                }
+            }
             res
         });
     }
@@ -542,8 +548,13 @@ impl State {
     ///
     /// Note that the memory used for the removed entries paths is not freed, as it's append-only, and
     /// that some extensions might refer to paths which are now deleted.
+    /// The [`TREE` extension](State::tree()) is invalidated accordingly though.
     pub fn remove_entry_at_index(&mut self, index: usize) -> Entry {
-        self.entries.remove(index)
+        let entry = self.entries.remove(index);
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(entry.path_in(&self.path_backing));
+        }
+        entry
     }
 }
 
@@ -553,6 +564,11 @@ impl State {
     pub fn tree(&self) -> Option<&extension::Tree> {
         self.tree.as_ref()
     }
+    /// Access the `tree` extension mutably, for instance to [invalidate](extension::Tree::invalidate_path()) paths
+    /// of entries that were changed in place.
+    pub fn tree_mut(&mut self) -> Option<&mut extension::Tree> {
+        self.tree.as_mut()
+    }
     /// Remove the `tree` extension.
     pub fn remove_tree(&mut self) -> Option<extension::Tree> {
         self.tree.take()
@@ -1,4 +1,6 @@
-use crate::extension::Signature;
+use bstr::BStr;
+
+use crate::extension::{Signature, Tree};
 
 /// The signature for tree extensions
 pub const SIGNATURE: Signature = *b"TREE";
@@ -11,6 +13,32 @@ pub use decode::decode;
 
 mod write;
 
+impl Tree {
+    /// Mark all trees leading to the entry at `rela_path` as invalid, like the root tree, so they will be recomputed
+    /// when [writing the tree](crate::State::write_tree()).
+    /// Should `rela_path` point to a tree itself, it will be removed entirely.
+    ///
+    /// This must be called for every path whose entry was added, removed or changed to keep the cache valid.
+    pub fn invalidate_path(&mut self, rela_path: &BStr) {
+        let mut cursor = self;
+        let mut components = rela_path.split(|b| *b == b'/').peekable();
+        loop {
+            cursor.num_entries = None;
+            let Some(name) = components.next() else {
+                break;
+            };
+            let Ok(idx) = cursor.children.binary_search_by(|t| t.name.as_slice().cmp(name)) else {
+                break;
+            };
+            if components.peek().is_none() {
+                cursor.children.remove(idx);
+                break;
+            }
+            cursor = &mut cursor.children[idx];
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use gix_testtools::size_ok;
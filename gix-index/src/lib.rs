@@ -38,6 +38,12 @@ pub mod verify;
 ///
 pub mod write;
 
+///
+pub mod write_tree;
+
+///
+pub mod sparse;
+
 pub mod fs;
 
 /// All known versions of a git index file.
@@ -0,0 +1,232 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_object::tree::EntryKind;
+
+use crate::{entry, extension, Entry, State};
+
+/// Configuration related to sparse indexes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// If true, certain entries in the index will be excluded / skipped for certain operations,
+    /// based on the ignore patterns in the `.git/info/sparse-checkout` file. These entries will
+    /// carry the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This typically is the value of `core.sparseCheckout` in the git configuration.
+    pub sparse_checkout: bool,
+
+    /// Interpret the `.git/info/sparse-checkout` file using _cone mode_.
+    ///
+    /// If true, _cone mode_ is active and entire directories will be included in the checkout, as well as files in the root
+    /// of the repository.
+    /// If false, non-cone mode is active and entries to _include_ will be matched with patterns like those found in `.gitignore` files.
+    ///
+    /// This typically is the value of `core.sparseCheckoutCone` in the git configuration.
+    pub directory_patterns_only: bool,
+
+    /// If true, will attempt to write a sparse index file which only works in cone mode.
+    ///
+    /// A sparse index has [`DIR` entries][crate::entry::Mode::DIR] that represent entire directories to be skipped
+    /// during checkout and other operations due to the added presence of
+    /// the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This is typically the value of `index.sparse` in the git configuration.
+    pub write_sparse_index: bool,
+}
+
+impl Options {
+    /// Derive a valid mode from all parameters that affect the 'sparseness' of the index.
+    ///
+    /// Some combinations of them degenerate to one particular mode.
+    pub fn sparse_mode(&self) -> Mode {
+        match (
+            self.sparse_checkout,
+            self.directory_patterns_only,
+            self.write_sparse_index,
+        ) {
+            (true, true, true) => Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+            (true, true, false) => Mode::IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+            (true, false, _) => Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+            (false, _, _) => Mode::Disabled,
+        }
+    }
+}
+
+/// Describes the configuration how a sparse index should be written, or if one should be written at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// index with DIR entries for exclusion and included entries, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+    /// index with all file entries and skip worktree flags for exclusion, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+    /// index with all file entries and skip-worktree flags for exclusion, `ignore` patterns to include entries in `.git/info/sparse-checkout` file.
+    IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+    /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
+    Disabled,
+}
+
+///
+pub mod expand {
+    /// The error returned by [State::expand_sparse_directories()](crate::State::expand_sparse_directories()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not traverse the tree of sparse directory at '{path}'")]
+        Traverse {
+            path: bstr::BString,
+            source: gix_traverse::tree::depthfirst::Error,
+        },
+    }
+}
+
+/// Sparse directories
+impl State {
+    /// Replace all [sparse directory entries](entry::Mode::DIR) with the entries of the tree they point to, accessing
+    /// trees with `objects`, and return the amount of expanded directories.
+    ///
+    /// The new entries are marked with [`SKIP_WORKTREE`](entry::Flags::SKIP_WORKTREE) just like the directory they replace,
+    /// and the index won't be [sparse](State::is_sparse()) anymore. The `TREE` extension is invalidated accordingly.
+    pub fn expand_sparse_directories(&mut self, objects: impl gix_object::Find) -> Result<usize, expand::Error> {
+        if !self.is_sparse {
+            return Ok(0);
+        }
+        let _span = gix_features::trace::coarse!("gix_index::State::expand_sparse_directories()");
+        let mut state = gix_traverse::tree::depthfirst::State::default();
+        let mut expanded = Vec::new();
+        for (idx, entry) in self.entries.iter().enumerate().filter(|(_, e)| e.mode.is_sparse()) {
+            let dir = entry.path(self);
+            let mut recorder = gix_traverse::tree::Recorder::default();
+            gix_traverse::tree::depthfirst(entry.id, &mut state, &objects, &mut recorder).map_err(|err| {
+                expand::Error::Traverse {
+                    path: dir.to_owned(),
+                    source: err,
+                }
+            })?;
+            expanded.push((idx, dir.to_owned(), recorder.records));
+        }
+
+        let num_expanded = expanded.len();
+        for (idx, dir, records) in expanded.into_iter().rev() {
+            let sparse_dir = self.entries.remove(idx);
+            let dir = dir.strip_suffix(b"/").unwrap_or(&dir).as_bstr();
+            if let Some(tree) = self.tree.as_mut() {
+                tree.invalidate_path(dir);
+            }
+            for record in records.into_iter().filter(|r| !r.mode.is_tree()) {
+                let mode = match record.mode.kind() {
+                    EntryKind::Tree => unreachable!("filtered above"),
+                    EntryKind::Blob => entry::Mode::FILE,
+                    EntryKind::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+                    EntryKind::Link => entry::Mode::SYMLINK,
+                    EntryKind::Commit => entry::Mode::COMMIT,
+                };
+                let path_start = self.path_backing.len();
+                self.path_backing.push_str(dir);
+                self.path_backing.push(b'/');
+                self.path_backing.push_str(&record.filepath);
+                self.entries.push(Entry {
+                    stat: entry::Stat::default(),
+                    id: record.oid,
+                    flags: sparse_dir.flags,
+                    mode,
+                    path: path_start..self.path_backing.len(),
+                });
+            }
+        }
+        self.sort_entries();
+        self.is_sparse = false;
+        Ok(num_expanded)
+    }
+
+    /// Replace all entries in directories for which `is_excluded_dir(rela_dir_path_with_trailing_slash)` returns `true`
+    /// with a single [sparse directory entry](entry::Mode::DIR) that points to the tree of the directory, and return
+    /// the amount of created sparse directory entries.
+    ///
+    /// As the tree ids are obtained from the `TREE` extension, only directories which are still valid in it can be
+    /// collapsed, so [`write_tree()`](State::write_tree()) should be called beforehand.
+    /// Further, all entries in the directory must be unconflicted and marked with [`SKIP_WORKTREE`](entry::Flags::SKIP_WORKTREE),
+    /// and it must not contain submodules.
+    ///
+    /// Note that this typically is only done in [cone mode](Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs).
+    pub fn collapse_sparse_directories(&mut self, mut is_excluded_dir: impl FnMut(&BStr) -> bool) -> usize {
+        let Some(mut root) = self.tree.take() else {
+            return 0;
+        };
+        let _span = gix_features::trace::coarse!("gix_index::State::collapse_sparse_directories()");
+        let mut collapsed = Vec::new();
+        self.find_collapsible_directories(&mut root, &mut BString::default(), &mut is_excluded_dir, &mut collapsed);
+        self.tree = Some(root);
+        if collapsed.is_empty() {
+            return 0;
+        }
+
+        collapsed.sort_by_key(|(range, _, _)| range.start);
+        let num_collapsed = collapsed.len();
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut collapsed = collapsed.into_iter().peekable();
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            match collapsed.next_if(|(range, _, _)| range.start == idx) {
+                Some((range, id, dir)) => {
+                    let path_start = self.path_backing.len();
+                    self.path_backing.push_str(&dir);
+                    entries.push(Entry {
+                        stat: entry::Stat::default(),
+                        id,
+                        flags: entry::Flags::EXTENDED | entry::Flags::SKIP_WORKTREE,
+                        mode: entry::Mode::DIR,
+                        path: path_start..self.path_backing.len(),
+                    });
+                    idx = range.end;
+                }
+                None => {
+                    entries.push(self.entries[idx].clone());
+                    idx += 1;
+                }
+            }
+        }
+        self.entries = entries;
+        self.is_sparse = true;
+        num_collapsed
+    }
+
+    /// Find all directories below `tree` at `dir` which can be collapsed, and return the amount of entries that would be removed
+    /// by doing so, while removing the collapsed directories from `tree` and adjusting its entry count.
+    fn find_collapsible_directories(
+        &self,
+        tree: &mut extension::Tree,
+        dir: &mut BString,
+        is_excluded_dir: &mut dyn FnMut(&BStr) -> bool,
+        out: &mut Vec<(std::ops::Range<usize>, gix_hash::ObjectId, BString)>,
+    ) -> u32 {
+        let mut num_removed_entries = 0;
+        tree.children.retain_mut(|child| {
+            let prev_len = dir.len();
+            dir.push_str(&child.name);
+            dir.push(b'/');
+            let range = self.prefixed_entries_range(dir.as_bstr()).filter(|range| {
+                child.num_entries == Some(range.len() as u32)
+                    && self.entries[range.clone()].iter().all(|e| {
+                        e.stage_raw() == 0
+                            && e.flags.contains(entry::Flags::SKIP_WORKTREE)
+                            && e.mode != entry::Mode::COMMIT
+                    })
+            });
+            let keep = match range.filter(|_| is_excluded_dir(dir.as_bstr())) {
+                Some(range) => {
+                    num_removed_entries += range.len() as u32 - 1;
+                    out.push((range, child.id, dir.clone()));
+                    false
+                }
+                None => {
+                    num_removed_entries += self.find_collapsible_directories(child, dir, is_excluded_dir, out);
+                    true
+                }
+            };
+            dir.truncate(prev_len);
+            keep
+        });
+        if let Some(num_entries) = tree.num_entries.as_mut() {
+            *num_entries -= num_removed_entries;
+        }
+        num_removed_entries
+    }
+}
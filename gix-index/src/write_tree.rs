@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryKind;
+
+use crate::{entry, extension, Entry, State};
+
+/// The error returned by [State::write_tree()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot write a tree from an index with unmerged entry at '{path}'")]
+    Unmerged { path: BString },
+    #[error("The entry at '{path}' has an invalid mode {mode:?} which can't be stored in a tree")]
+    InvalidMode { path: BString, mode: entry::Mode },
+    #[error(transparent)]
+    Edit(#[from] gix_object::tree::editor::Error),
+    #[error("Could not write a tree object")]
+    WriteTree(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// The outcome of [State::write_tree()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The id of the root tree representing the whole index.
+    pub tree_id: ObjectId,
+    /// The amount of trees that were passed to `out` for writing, which is `0` if the cached root tree was still valid.
+    pub num_written_trees: usize,
+    /// The amount of (sub-)trees whose id was taken from the `TREE` extension.
+    pub num_reused_trees: usize,
+}
+
+/// Tree creation
+impl State {
+    /// Create a tree from all entries in this index, pass each tree to `out` for writing, and return the id of the root tree.
+    ///
+    /// Trees which are still valid in the [`TREE` extension](State::tree()) are reused, so only invalidated trees will
+    /// be written via [`gix_object::tree::Editor`]. Afterwards, the `TREE` extension is created or updated to reflect
+    /// the written trees.
+    /// Note that the extension is only correct if [`extension::Tree::invalidate_path()`] was called for each changed
+    /// path, which is done automatically when entries are added or removed through this instance.
+    ///
+    /// Entries marked as [intent-to-add](entry::Flags::INTENT_TO_ADD) or [to be removed](entry::Flags::REMOVE) are
+    /// skipped, and the trees containing intent-to-add entries remain invalid in the `TREE` extension, while sparse
+    /// directory entries are added as trees. It's an error to have entries in any stage but
+    /// [the unconflicted one](entry::Stage::Unconflicted).
+    pub fn write_tree<E>(
+        &mut self,
+        mut out: impl FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+    ) -> Result<Outcome, Error>
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let _span = gix_features::trace::coarse!("gix_index::State::write_tree()");
+        if let Some(tree) = self
+            .tree
+            .as_ref()
+            .filter(|tree| tree.num_entries == Some(self.entries.len() as u32))
+        {
+            return Ok(Outcome {
+                tree_id: tree.id,
+                num_written_trees: 0,
+                num_reused_trees: 1,
+            });
+        }
+
+        let mut editor =
+            gix_object::tree::Editor::new(gix_object::Tree::empty(), &gix_object::find::Never, self.object_hash);
+        let mut ctx = Context {
+            state: self,
+            editor: &mut editor,
+            num_reused_trees: 0,
+        };
+        let mut root = ctx.add_directory(0..self.entries.len(), "".into(), self.tree.as_ref())?;
+        let num_reused_trees = ctx.num_reused_trees;
+
+        // Keep the name and subtree-ids of each written tree to learn the ids of the trees we have created.
+        let mut written_subtrees = HashMap::<ObjectId, Vec<(BString, ObjectId)>>::new();
+        let mut num_written_trees = 0;
+        root.id = editor
+            .write(|tree| {
+                let id = out(tree)?;
+                num_written_trees += 1;
+                written_subtrees.entry(id).or_insert_with(|| {
+                    tree.entries
+                        .iter()
+                        .filter(|e| e.mode.is_tree())
+                        .map(|e| (e.filename.clone(), e.oid))
+                        .collect()
+                });
+                Ok(id)
+            })
+            .map_err(|err: E| Error::WriteTree(err.into()))?;
+        assign_written_ids(&mut root, &written_subtrees);
+
+        let outcome = Outcome {
+            tree_id: root.id,
+            num_written_trees,
+            num_reused_trees,
+        };
+        self.tree = Some(root);
+        Ok(outcome)
+    }
+}
+
+struct Context<'a, 'find> {
+    state: &'a State,
+    editor: &'a mut gix_object::tree::Editor<'find>,
+    num_reused_trees: usize,
+}
+
+impl Context<'_, '_> {
+    /// Add all entries in `range` which are in the directory at `dir` (with trailing slash unless it's the root)
+    /// to the editor, reusing `cached` subtrees if possible, and return a `TREE` extension node describing it.
+    /// Its id will be null as it's not known yet.
+    fn add_directory(
+        &mut self,
+        range: Range<usize>,
+        dir: &BStr,
+        cached: Option<&extension::Tree>,
+    ) -> Result<extension::Tree, Error> {
+        let state = self.state;
+        let entries = &state.entries[range.clone()];
+        let mut children = Vec::new();
+        let mut idx = 0;
+        while let Some(entry) = entries.get(idx) {
+            let path = entry.path(state);
+            let rela_path = &path[dir.len()..];
+            // Sparse directory entries end with a slash, but are leaves nonetheless.
+            match rela_path.find_byte(b'/').filter(|pos| *pos + 1 != rela_path.len()) {
+                Some(slash) => {
+                    let name = &rela_path[..slash];
+                    let subdir = &path[..dir.len() + slash + 1];
+                    let num_entries = entries[idx..].partition_point(|e| e.path(state).starts_with(subdir));
+                    let cached_child = cached.and_then(|tree| {
+                        tree.children
+                            .binary_search_by(|t| t.name.as_slice().cmp(name))
+                            .ok()
+                            .map(|idx| &tree.children[idx])
+                    });
+                    match cached_child.filter(|tree| tree.num_entries == Some(num_entries as u32)) {
+                        Some(valid_tree) => {
+                            self.editor.upsert(
+                                subdir[..subdir.len() - 1].split_str("/"),
+                                EntryKind::Tree,
+                                valid_tree.id,
+                            )?;
+                            self.num_reused_trees += 1;
+                            children.push(valid_tree.clone());
+                        }
+                        None => {
+                            let start = range.start + idx;
+                            let tree =
+                                self.add_directory(start..start + num_entries, subdir.as_bstr(), cached_child)?;
+                            children.push(tree);
+                        }
+                    }
+                    idx += num_entries;
+                }
+                None => {
+                    self.add_entry(entry, path)?;
+                    idx += 1;
+                }
+            }
+        }
+        let has_intent_to_add_entries = entries
+            .iter()
+            .any(|entry| entry.flags.contains(entry::Flags::INTENT_TO_ADD));
+        Ok(extension::Tree {
+            name: dir
+                .strip_suffix(b"/")
+                .and_then(|dir| dir.rsplit_str("/").next())
+                .unwrap_or_default()
+                .into(),
+            id: ObjectId::null(state.object_hash),
+            // Like `git`, keep trees with intent-to-add entries invalid as they don't represent all of their entries.
+            num_entries: (!has_intent_to_add_entries).then_some(entries.len() as u32),
+            children,
+        })
+    }
+
+    fn add_entry(&mut self, entry: &Entry, path: &BStr) -> Result<(), Error> {
+        if entry.stage_raw() != 0 {
+            return Err(Error::Unmerged { path: path.to_owned() });
+        }
+        if entry
+            .flags
+            .intersects(entry::Flags::INTENT_TO_ADD | entry::Flags::REMOVE)
+        {
+            return Ok(());
+        }
+        let (path, kind) = if entry.mode.is_sparse() {
+            (path.strip_suffix(b"/").unwrap_or(path).as_bstr(), EntryKind::Tree)
+        } else {
+            let kind = entry
+                .mode
+                .to_tree_entry_mode()
+                .map(|mode| mode.kind())
+                .ok_or_else(|| Error::InvalidMode {
+                    path: path.to_owned(),
+                    mode: entry.mode,
+                })?;
+            (path, kind)
+        };
+        self.editor.upsert(path.split_str("/"), kind, entry.id)?;
+        Ok(())
+    }
+}
+
+/// Set the ids of all trees that were written, and remove those that were not written as they turned out empty.
+fn assign_written_ids(tree: &mut extension::Tree, written_subtrees: &HashMap<ObjectId, Vec<(BString, ObjectId)>>) {
+    let subtrees = written_subtrees.get(&tree.id);
+    tree.children.retain_mut(|child| {
+        if !child.id.is_null() {
+            return true;
+        }
+        let Some(id) = subtrees.and_then(|subtrees| {
+            subtrees
+                .iter()
+                .find_map(|(name, id)| (name.as_slice() == child.name.as_slice()).then_some(*id))
+        }) else {
+            return false;
+        };
+        child.id = id;
+        assign_written_ids(child, written_subtrees);
+        true
+    });
+}
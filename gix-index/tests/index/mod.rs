@@ -3,6 +3,8 @@ mod entry;
 mod file;
 mod fs;
 mod init;
+mod sparse;
+mod write_tree;
 
 use std::path::{Path, PathBuf};
 
@@ -0,0 +1,72 @@
+use bstr::{BStr, ByteSlice};
+use gix_index::entry::{Flags, Mode};
+
+use crate::index::{write_tree::hash_tree, Fixture};
+
+fn entries(index: &gix_index::State) -> Vec<(&BStr, gix_hash::ObjectId, Mode, Flags)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index), e.id, e.mode, e.flags))
+        .collect()
+}
+
+#[test]
+fn expand_sparse_directories_matches_git() -> crate::Result {
+    let mut sparse = Fixture::Generated("v3_sparse_index").open();
+    let expected = Fixture::Generated("v3_skip_worktree").open();
+
+    let objects = gix_odb::at(sparse.path().parent().expect("in .git").join("objects"))?;
+    let num_expanded = sparse.expand_sparse_directories(&objects)?;
+    assert_eq!(num_expanded, 2, "c1/c3/ and d/");
+    assert!(!sparse.is_sparse());
+    assert_eq!(entries(&sparse), entries(&expected));
+    assert_eq!(
+        sparse.tree().and_then(|tree| tree.num_entries),
+        None,
+        "the root tree was invalidated as it has more entries now"
+    );
+
+    assert_eq!(
+        sparse.expand_sparse_directories(&objects)?,
+        0,
+        "non-sparse indices are unchanged"
+    );
+    Ok(())
+}
+
+#[test]
+fn collapse_sparse_directories_matches_git() -> crate::Result {
+    let mut index = Fixture::Generated("v3_skip_worktree").open();
+    let expected = Fixture::Generated("v3_sparse_index").open();
+
+    index.remove_tree();
+    assert_eq!(
+        index.collapse_sparse_directories(|_| true),
+        0,
+        "nothing can be collapsed without TREE extension"
+    );
+    let tree_id = index.write_tree(hash_tree)?.tree_id;
+
+    let mut dirs = Vec::new();
+    let num_collapsed = index.collapse_sparse_directories(|dir| {
+        dirs.push(dir.to_str().expect("valid UTF-8").to_owned());
+        true
+    });
+    assert_eq!(
+        dirs,
+        ["c1/c3/", "d/"],
+        "directories with included entries aren't considered, and collapsed ones aren't descended into"
+    );
+    assert_eq!(num_collapsed, 2);
+    assert!(index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected));
+
+    let outcome = index.write_tree(hash_tree)?;
+    assert_eq!(
+        outcome.num_written_trees, 0,
+        "the TREE extension was adjusted to remain valid"
+    );
+    assert_eq!(outcome.tree_id, tree_id, "sparse directories are kept as trees");
+    Ok(())
+}
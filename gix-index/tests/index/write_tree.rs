@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+use crate::index::Fixture;
+
+pub fn hash_tree(tree: &gix_object::Tree) -> Result<ObjectId, std::convert::Infallible> {
+    let mut buf = Vec::new();
+    gix_object::WriteTo::write_to(tree, &mut buf).expect("in-memory writes don't fail");
+    Ok(gix_object::compute_hash(
+        gix_hash::Kind::Sha1,
+        gix_object::Kind::Tree,
+        &buf,
+    ))
+}
+
+fn head_tree_id(fixture: &str) -> ObjectId {
+    let dir = scripted_fixture_read_only_standalone(Path::new("make_index").join(fixture).with_extension("sh"))
+        .expect("script works");
+    let hex = std::fs::read_to_string(dir.join("head.tree")).expect("written by fixture");
+    hex.trim().parse().expect("valid hash")
+}
+
+#[test]
+fn without_cache_matches_git_and_recreates_the_tree_extension() -> crate::Result {
+    for name in ["v2", "v2_more_files", "v2_all_file_kinds", "v4_more_files_IEOT"] {
+        let mut file = Fixture::Generated(name).open();
+        let expected_extension = file
+            .remove_tree()
+            .expect("git writes the TREE extension after committing");
+
+        let outcome = file.write_tree(hash_tree)?;
+        assert_eq!(
+            outcome.tree_id,
+            head_tree_id(name),
+            "{name}: the tree must match what git wrote"
+        );
+        assert_eq!(outcome.num_reused_trees, 0);
+        assert_eq!(
+            file.tree(),
+            Some(&expected_extension),
+            "{name}: the extension is recreated exactly like git would"
+        );
+        file.verify_extensions(false, gix_object::find::Never)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn valid_cache_is_reused() -> crate::Result {
+    let name = "v4_more_files_IEOT";
+    let mut file = Fixture::Generated(name).open();
+    let outcome = file.write_tree(hash_tree)?;
+    assert_eq!(outcome.tree_id, head_tree_id(name));
+    assert_eq!(outcome.num_written_trees, 0, "the root tree is still valid");
+    Ok(())
+}
+
+#[test]
+fn only_invalidated_trees_are_written() -> crate::Result {
+    let mut file = Fixture::Generated("v4_more_files_IEOT").open();
+    let before = file.tree().cloned().expect("present");
+    file.remove_entries(|_, path, _| path == "d/last/34");
+
+    let outcome = file.write_tree(hash_tree)?;
+    assert_eq!(outcome.num_written_trees, 3, "root, d and d/last were invalidated");
+    assert_ne!(outcome.tree_id, before.id);
+
+    let mut uncached = file.clone();
+    uncached.remove_tree();
+    let expected = uncached.write_tree(hash_tree)?;
+    assert_eq!(
+        outcome.tree_id, expected.tree_id,
+        "the cached tree is the same as the recomputed one"
+    );
+    assert_eq!(
+        file.tree(),
+        uncached.tree(),
+        "both trees have been updated in the same way"
+    );
+
+    let last = &file.tree().expect("present").children[0].children[0];
+    assert_eq!(last.name.as_bstr(), "last");
+    assert_eq!(last.num_entries, Some(2));
+    Ok(())
+}
+
+#[test]
+fn trees_with_intent_to_add_entries_are_invalid() -> crate::Result {
+    let mut file = Fixture::Generated("v4_more_files_IEOT").open();
+    let mut without_entry = file.clone();
+    without_entry.remove_entries(|_, path, _| path == "d/last/34");
+    without_entry.remove_tree();
+    let expected = without_entry.write_tree(hash_tree)?;
+
+    file.entries_mut_with_paths()
+        .find(|(_, path)| *path == "d/last/34")
+        .expect("present")
+        .0
+        .flags
+        .insert(gix_index::entry::Flags::INTENT_TO_ADD);
+    file.tree_mut().expect("present").invalidate_path("d/last/34".into());
+    let outcome = file.write_tree(hash_tree)?;
+    assert_eq!(
+        outcome.tree_id, expected.tree_id,
+        "intent-to-add entries aren't part of the tree"
+    );
+
+    let root = file.tree().expect("present");
+    let d = &root.children[0];
+    let last = &d.children[0];
+    assert_eq!(
+        [root.num_entries, d.num_entries, last.num_entries],
+        [None, None, None],
+        "all trees containing the intent-to-add entry are invalid, just like git would write them"
+    );
+
+    let outcome = file.write_tree(hash_tree)?;
+    assert_eq!(outcome.tree_id, expected.tree_id);
+    assert_ne!(outcome.num_written_trees, 0, "invalid trees are written again");
+    Ok(())
+}
+
+#[test]
+fn unmerged_entries_are_an_error() {
+    let mut file = Fixture::Loose("conflicting-file").open();
+    let err = file.write_tree(hash_tree).unwrap_err();
+    assert!(matches!(err, gix_index::write_tree::Error::Unmerged { .. }));
+}
@@ -1,10 +1,11 @@
 use crate::blob::builtin_driver::text::utils::{
     assure_ends_with_nl, contains_lines, detect_line_ending, detect_line_ending_or_nl, fill_ancestor,
     hunks_differ_in_diff3, take_intersecting, tokens, write_ancestor, write_conflict_marker, write_hunks,
-    zealously_contract_hunks, CollectHunks, Hunk, Side,
+    zealously_contract_hunks, CollectHunks, Hunk, Side, Tokens,
 };
 use crate::blob::builtin_driver::text::{Conflict, ConflictStyle, Labels, Options};
 use crate::blob::Resolution;
+use imara_diff::intern::Token;
 use std::ops::Range;
 
 /// Merge `current` and `other` with `ancestor` as base according to `opts`.
@@ -34,32 +35,97 @@ pub fn merge<'a>(
     Options {
         diff_algorithm,
         conflict,
+        whitespace,
     }: Options,
 ) -> Resolution {
     out.clear();
     input.update_before(tokens(ancestor));
     input.update_after(tokens(current));
-
-    let hunks = imara_diff::diff(
-        diff_algorithm,
-        input,
-        CollectHunks {
-            side: Side::Current,
-            hunks: Default::default(),
-        },
-    );
-
     let current_tokens = std::mem::take(&mut input.after);
     input.update_after(tokens(other));
 
-    let mut hunks = imara_diff::diff(
-        diff_algorithm,
-        input,
-        CollectHunks {
-            side: Side::Other,
-            hunks,
+    let normalized = whitespace.ignores_whitespace_in_lines().then(|| {
+        let canonical = whitespace.canonical_tokens(&input.interner);
+        let normalize = |tokens: &[Token]| -> Vec<Token> { tokens.iter().map(|t| canonical[t.0 as usize]).collect() };
+        [
+            normalize(&input.before),
+            normalize(&current_tokens),
+            normalize(&input.after),
+        ]
+    });
+
+    let (mut hunks, num_current_hunks) = {
+        let [ancestor, current, other] = match &normalized {
+            Some([ancestor, current, other]) => [ancestor.as_slice(), current, other],
+            None => [input.before.as_slice(), &current_tokens, &input.after],
+        };
+        let num_tokens = input.interner.num_tokens();
+        let hunks = imara_diff::diff_with_tokens(
+            diff_algorithm,
+            ancestor,
+            current,
+            num_tokens,
+            CollectHunks {
+                side: Side::Current,
+                hunks: Default::default(),
+            },
+        );
+        let num_current_hunks = hunks.len();
+        let hunks = imara_diff::diff_with_tokens(
+            diff_algorithm,
+            ancestor,
+            other,
+            num_tokens,
+            CollectHunks {
+                side: Side::Other,
+                hunks,
+            },
+        );
+        (hunks, num_current_hunks)
+    };
+
+    if normalized.is_some() {
+        // Like `git`, if one side only changed whitespace, the other side is the result.
+        let unchanged_side = if num_current_hunks == 0 {
+            Some((Side::Other, input.after.len()))
+        } else if num_current_hunks == hunks.len() {
+            Some((Side::Current, current_tokens.len()))
+        } else {
+            None
+        };
+        if let Some((side, num_tokens)) = unchanged_side {
+            let whole_side = Hunk {
+                before: 0..0,
+                after: 0..num_tokens as u32,
+                side,
+            };
+            write_hunks(std::slice::from_ref(&whole_side), input, &current_tokens, out);
+            return Resolution::Complete;
+        }
+
+        // Like `git`, unchanged lines are taken from the current side as they may differ in whitespace from the ancestor.
+        let (mut ancestor_pos, mut current_pos) = (0, 0);
+        for hunk in &hunks[..num_current_hunks] {
+            let num_unchanged = hunk.before.start as usize - ancestor_pos;
+            input.before[ancestor_pos..][..num_unchanged]
+                .copy_from_slice(&current_tokens[current_pos..][..num_unchanged]);
+            (ancestor_pos, current_pos) = (hunk.before.end as usize, hunk.after.end as usize);
+        }
+        let num_unchanged = input.before.len() - ancestor_pos;
+        input.before[ancestor_pos..].copy_from_slice(&current_tokens[current_pos..][..num_unchanged]);
+    }
+    let tokens = match &normalized {
+        Some([ancestor, current, other]) => Tokens {
+            ancestor,
+            current,
+            other,
+        },
+        None => Tokens {
+            ancestor: &input.before,
+            current: &current_tokens,
+            other: &input.after,
         },
-    );
+    };
 
     if hunks.is_empty() {
         write_ancestor(input, 0, input.before.len(), out);
@@ -94,7 +160,7 @@ pub fn merge<'a>(
                 let marker_size = marker_size.get();
                 let (hunks_front_and_back, num_hunks_front) = match style {
                     ConflictStyle::Merge | ConflictStyle::ZealousDiff3 => {
-                        zealously_contract_hunks(&mut current_hunks, &mut intersecting, input, &current_tokens)
+                        zealously_contract_hunks(&mut current_hunks, &mut intersecting, tokens)
                     }
                     ConflictStyle::Diff3 => (Vec::new(), 0),
                 };
@@ -146,7 +212,7 @@ pub fn merge<'a>(
                         }
                         ConflictStyle::Diff3 | ConflictStyle::ZealousDiff3 => {
                             if contains_lines(our_hunks) || contains_lines(their_hunks) {
-                                if hunks_differ_in_diff3(style, our_hunks, their_hunks, input, &current_tokens) {
+                                if hunks_differ_in_diff3(style, our_hunks, their_hunks, tokens) {
                                     resolution = Resolution::Conflict;
                                     write_conflict_marker(out, b'<', current_label, marker_size, nl);
                                     write_hunks(our_hunks, input, &current_tokens, out);
@@ -180,7 +246,7 @@ pub fn merge<'a>(
                         unreachable!("initial hunks are never ancestors")
                     }
                 };
-                if hunks_differ_in_diff3(ConflictStyle::Diff3, our_hunks, their_hunks, input, &current_tokens) {
+                if hunks_differ_in_diff3(ConflictStyle::Diff3, our_hunks, their_hunks, tokens) {
                     resolution = Resolution::CompleteWithAutoResolvedConflict;
                 }
                 let hunks_to_write = if conflict == Conflict::ResolveWithOurs {
@@ -198,7 +264,7 @@ pub fn merge<'a>(
             }
             Conflict::ResolveWithUnion => {
                 let (hunks_front_and_back, num_hunks_front) =
-                    zealously_contract_hunks(&mut current_hunks, &mut intersecting, input, &current_tokens);
+                    zealously_contract_hunks(&mut current_hunks, &mut intersecting, tokens);
 
                 let (our_hunks, their_hunks) = match filled_hunks_side {
                     Side::Current => (&current_hunks, &intersecting),
@@ -207,7 +273,7 @@ pub fn merge<'a>(
                         unreachable!("initial hunks are never ancestors")
                     }
                 };
-                if hunks_differ_in_diff3(ConflictStyle::Diff3, our_hunks, their_hunks, input, &current_tokens) {
+                if hunks_differ_in_diff3(ConflictStyle::Diff3, our_hunks, their_hunks, tokens) {
                     resolution = Resolution::CompleteWithAutoResolvedConflict;
                 }
                 let (front_hunks, back_hunks) = hunks_front_and_back.split_at(num_hunks_front);
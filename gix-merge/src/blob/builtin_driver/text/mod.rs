@@ -73,6 +73,12 @@ pub struct Options {
     pub diff_algorithm: imara_diff::Algorithm,
     /// Decide what to do to automatically resolve conflicts, or to keep them.
     pub conflict: Conflict,
+    /// Changes in whitespace to ignore when comparing lines, like `-Xignore-space-change` and related options do.
+    ///
+    /// If one side only changed whitespace, the other side is used as merge result. Unchanged lines are
+    /// taken from the *current* side.
+    /// Note that [`ignore_blank_lines`](gix_diff::blob::whitespace::Options::ignore_blank_lines) has no effect here.
+    pub whitespace: gix_diff::blob::whitespace::Options,
 }
 
 impl Default for Options {
@@ -80,6 +86,7 @@ impl Default for Options {
         Options {
             conflict: Default::default(),
             diff_algorithm: imara_diff::Algorithm::Myers,
+            whitespace: Default::default(),
         }
     }
 }
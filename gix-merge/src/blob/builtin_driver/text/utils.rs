@@ -1,24 +1,17 @@
 use crate::blob::builtin_driver::text::ConflictStyle;
-use bstr::{BStr, ByteSlice, ByteVec};
+use bstr::{BStr, ByteVec};
 use std::iter::Peekable;
 use std::ops::Range;
 
 /// Used only when `diff3` is the conflict style as `zdiff3` automatically reduces hunks into nothing.
 /// Here we check if all hunks are the same.
-pub fn hunks_differ_in_diff3(
-    style: ConflictStyle,
-    a: &[Hunk],
-    b: &[Hunk],
-    input: &imara_diff::intern::InternedInput<&[u8]>,
-    current_tokens: &[imara_diff::intern::Token],
-) -> bool {
+pub fn hunks_differ_in_diff3(style: ConflictStyle, a: &[Hunk], b: &[Hunk], tokens: Tokens<'_>) -> bool {
     if style != ConflictStyle::Diff3 {
         return true;
     }
 
-    let tokens_for_hunk = |hunk: &Hunk| -> &[imara_diff::intern::Token] {
-        &tokens_for_side(hunk.side, input, current_tokens)[hunk.after.start as usize..hunk.after.end as usize]
-    };
+    let tokens_for_hunk =
+        |hunk: &Hunk| -> &[imara_diff::intern::Token] { &tokens.side(hunk.side)[usize_range(&hunk.after)] };
 
     a.iter()
         .flat_map(tokens_for_hunk)
@@ -35,12 +28,12 @@ pub fn contains_lines(hunks: &[Hunk]) -> bool {
 /// to understand what's going on there without investing more time than it seemed worth.
 pub fn detect_line_ending(
     hunks: &[Hunk],
-    input: &mut imara_diff::intern::InternedInput<&[u8]>,
+    input: &imara_diff::intern::InternedInput<&[u8]>,
     current_tokens: &[imara_diff::intern::Token],
 ) -> Option<&'static BStr> {
     fn is_eol_crlf(
         hunks: &[Hunk],
-        input: &mut imara_diff::intern::InternedInput<&[u8]>,
+        input: &imara_diff::intern::InternedInput<&[u8]>,
         current_tokens: &[imara_diff::intern::Token],
     ) -> Option<bool> {
         let (range, side) = hunks.iter().rev().find_map(|h| {
@@ -70,7 +63,7 @@ pub fn detect_line_ending(
 
 pub fn detect_line_ending_or_nl(
     hunks: &[Hunk],
-    input: &mut imara_diff::intern::InternedInput<&[u8]>,
+    input: &imara_diff::intern::InternedInput<&[u8]>,
     current_tokens: &[imara_diff::intern::Token],
 ) -> &'static BStr {
     detect_line_ending(hunks, input, current_tokens).unwrap_or(b"\n".into())
@@ -159,8 +152,7 @@ fn ancestor_hunk(start: u32, num_lines: u32) -> Hunk {
 }
 
 /// Reduce the area of `a_hunks` and the hunks in `b_hunks` so that only those lines that are
-/// actually different remain. Note that we have to compare the resolved values, not only the tokens,
-/// so `current_tokens` is expected to be known to the `input` (and its `interner`).
+/// actually different remain, as compared by their `tokens`.
 /// Hunks from all input arrays maybe removed in the process from the front and back, in case they
 /// are entirely equal to what's in `hunk`. Note also that `a_hunks` and `b_hunks` are treated to be consecutive,
 /// so [`fill_ancestor()`] must have been called beforehand, and are assumed to covert the same space in the
@@ -173,17 +165,9 @@ fn ancestor_hunk(start: u32, num_lines: u32) -> Hunk {
 pub fn zealously_contract_hunks(
     a_hunks: &mut Vec<Hunk>,
     b_hunks: &mut Vec<Hunk>,
-    input: &imara_diff::intern::InternedInput<&[u8]>,
-    current_tokens: &[imara_diff::intern::Token],
+    tokens: Tokens<'_>,
 ) -> (Vec<Hunk>, usize) {
-    let line_content = |token_idx: u32, side: Side| {
-        let tokens = match side {
-            Side::Current => current_tokens,
-            Side::Other => &input.after,
-            Side::Ancestor => &input.before,
-        };
-        &input.interner[tokens[token_idx as usize]]
-    };
+    let line_content = |token_idx: u32, side: Side| tokens.side(side)[token_idx as usize];
     let (mut last_a_hunk_idx, mut last_b_hunk_idx) = (0, 0);
     let (mut out, hunks_in_front) = {
         let (mut remove_leading_a_hunks_from, mut remove_leading_b_hunks_from) = (None, None);
@@ -191,8 +175,8 @@ pub fn zealously_contract_hunks(
         for ((a_token_idx, a_hunk_idx, a_hunk_side), (b_token_idx, b_hunk_idx, b_hunk_side)) in
             iterate_hunks(a_hunks).zip(iterate_hunks(b_hunks))
         {
-            let a_line = line_content(a_token_idx, a_hunk_side).as_bstr();
-            let b_line = line_content(b_token_idx, b_hunk_side).as_bstr();
+            let a_line = line_content(a_token_idx, a_hunk_side);
+            let b_line = line_content(b_token_idx, b_hunk_side);
 
             if last_a_hunk_idx != a_hunk_idx {
                 a_hunk_token_equal_till = None;
@@ -235,8 +219,8 @@ pub fn zealously_contract_hunks(
         for ((a_token_idx, a_hunk_idx, a_hunk_side), (b_token_idx, b_hunk_idx, b_hunk_side)) in
             iterate_hunks_rev(a_hunks).zip(iterate_hunks_rev(b_hunks))
         {
-            let a_line = line_content(a_token_idx, a_hunk_side).as_bstr();
-            let b_line = line_content(b_token_idx, b_hunk_side).as_bstr();
+            let a_line = line_content(a_token_idx, a_hunk_side);
+            let b_line = line_content(b_token_idx, b_hunk_side);
 
             if last_a_hunk_idx != a_hunk_idx {
                 a_hunk_token_equal_from = None;
@@ -461,6 +445,24 @@ pub fn tokens(input: &[u8]) -> imara_diff::sources::ByteLines<'_, true> {
     imara_diff::sources::byte_lines_with_terminator(input)
 }
 
+/// The tokens of all sides of a merge, which are known to the same interner so equal tokens are equal lines.
+#[derive(Debug, Copy, Clone)]
+pub struct Tokens<'a> {
+    pub ancestor: &'a [imara_diff::intern::Token],
+    pub current: &'a [imara_diff::intern::Token],
+    pub other: &'a [imara_diff::intern::Token],
+}
+
+impl<'a> Tokens<'a> {
+    pub fn side(&self, side: Side) -> &'a [imara_diff::intern::Token] {
+        match side {
+            Side::Current => self.current,
+            Side::Other => self.other,
+            Side::Ancestor => self.ancestor,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Side {
     Current,
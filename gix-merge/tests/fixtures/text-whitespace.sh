@@ -0,0 +1,86 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# For each case, create `<case>/ours.blob`, `<case>/base.blob` and `<case>/theirs.blob` which differ in whitespace,
+# merge them with `git merge -X<option>` and store the merged file in `<case>/<option>.merged`,
+# or `<case>/none.merged` without option.
+git init -q
+
+function baseline() {
+  local name=${1:?1: the case name}
+  local option=${2:?2: the merge option or 'none'}
+  local args=()
+  if [ "$option" != none ]; then
+    args=("-X$option")
+  fi
+  git checkout -q -B ours "$name-ours"
+  git merge -q --no-edit "${args[@]}" "$name-theirs" >/dev/null 2>&1 || true
+  cp file "$name/$option.merged"
+  git merge --abort 2>/dev/null || git reset -q --hard
+}
+
+function merge_case() {
+  local name=${1:?1: the case name}
+  git checkout -q --orphan "$name-base"
+  git rm -rfq --cached . >/dev/null 2>&1 || true
+  cp "$name/base.blob" file
+  git add file
+  git commit -q -m "$name base"
+  git checkout -q -b "$name-ours"
+  cp "$name/ours.blob" file
+  git commit -q -am "$name ours"
+  git checkout -q -b "$name-theirs" "$name-base"
+  cp "$name/theirs.blob" file
+  git commit -q -am "$name theirs"
+  for option in none ignore-space-change ignore-all-space ignore-space-at-eol ignore-cr-at-eol; do
+    baseline "$name" "$option"
+  done
+}
+
+mkdir their-whitespace-change
+(cd their-whitespace-change
+  printf '1\n2\n  3\n4\n5\n' > base.blob
+  printf '1\n2\n  3\n4\nfive\n' > ours.blob
+  printf '1\n2\n   3 \n4\n5\n' > theirs.blob
+)
+merge_case their-whitespace-change
+
+mkdir our-whitespace-change
+(cd our-whitespace-change
+  printf '1\n2\n3\n4\n5\n6\n7\n' > base.blob
+  printf '1\n\t2\n3\n4\n5\n6\n7\n' > ours.blob
+  printf '1\n2\n3\n4\n5\n6\nseven\n' > theirs.blob
+)
+merge_case our-whitespace-change
+
+mkdir same-line
+(cd same-line
+  printf 'a\nb c\nd\n' > base.blob
+  printf 'a\nB c\nd\n' > ours.blob
+  printf 'a\nb  c\nd\n' > theirs.blob
+)
+merge_case same-line
+
+mkdir line-endings
+(cd line-endings
+  printf 'a\nb\nc\n' > base.blob
+  printf 'a\nb\nC\n' > ours.blob
+  printf 'a\r\nb\r\nc\n' > theirs.blob
+)
+merge_case line-endings
+
+mkdir both-whitespace
+(cd both-whitespace
+  printf 'a\nb c\nd\n' > base.blob
+  printf 'a\nbc \nd\n' > ours.blob
+  printf 'a\nb   c\nd\n' > theirs.blob
+)
+merge_case both-whitespace
+
+mkdir whitespace-and-changes
+(cd whitespace-and-changes
+  printf '1\n2\n3\n4\n5\n6\n7\n' > base.blob
+  printf 'one\n2\n 3\n4\n5\n6\n7\n' > ours.blob
+  printf '1\n2\n3  \n4\n5\n6\nseven\n' > theirs.blob
+)
+merge_case whitespace-and-changes
@@ -119,6 +119,7 @@ mod text {
                 builtin_driver::text::Options {
                     conflict: Conflict::ResolveWithUnion,
                     diff_algorithm: imara_diff::Algorithm::Myers,
+                    whitespace: Default::default(),
                 },
             ),
             (
@@ -197,6 +198,91 @@ mod text {
         Ok(())
     }
 
+    /// Cases where `git` considers changes to adjacent lines a conflict, but we don't.
+    const DIVERGING_WHITESPACE: &[&str] = &["line-endings/none"];
+
+    #[test]
+    fn run_whitespace_baseline() -> crate::Result {
+        let root = gix_testtools::scripted_fixture_read_only("text-whitespace.sh")?;
+        let mut out = Vec::new();
+        for case in [
+            "their-whitespace-change",
+            "our-whitespace-change",
+            "same-line",
+            "line-endings",
+            "both-whitespace",
+            "whitespace-and-changes",
+        ] {
+            let read = |name: &str| std::fs::read(root.join(case).join(name));
+            let (ours, base, theirs) = (read("ours.blob")?, read("base.blob")?, read("theirs.blob")?);
+            let theirs_label = format!("{case}-theirs");
+            for (option, whitespace) in [
+                ("none", Default::default()),
+                (
+                    "ignore-space-change",
+                    gix_diff::blob::whitespace::Options {
+                        ignore_space_change: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "ignore-all-space",
+                    gix_diff::blob::whitespace::Options {
+                        ignore_all_space: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "ignore-space-at-eol",
+                    gix_diff::blob::whitespace::Options {
+                        ignore_space_at_eol: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "ignore-cr-at-eol",
+                    gix_diff::blob::whitespace::Options {
+                        ignore_cr_at_eol: true,
+                        ..Default::default()
+                    },
+                ),
+            ] {
+                if DIVERGING_WHITESPACE.contains(&format!("{case}/{option}").as_str()) {
+                    continue;
+                }
+                let expected = read(&format!("{option}.merged"))?;
+                let mut input = imara_diff::intern::InternedInput::default();
+                let actual = builtin_driver::text(
+                    &mut out,
+                    &mut input,
+                    builtin_driver::text::Labels {
+                        ancestor: None,
+                        current: Some("HEAD".into()),
+                        other: Some(theirs_label.as_str().into()),
+                    },
+                    &ours,
+                    &base,
+                    &theirs,
+                    builtin_driver::text::Options {
+                        whitespace,
+                        ..Default::default()
+                    },
+                );
+                assert_str_eq!(
+                    out.as_bstr().to_str_lossy(),
+                    expected.as_bstr().to_str_lossy(),
+                    "{case}/{option}: output mismatch"
+                );
+                assert_eq!(
+                    actual == Resolution::Conflict,
+                    expected.contains_str("<<<<<<<"),
+                    "{case}/{option}: resolution mismatch"
+                );
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn both_sides_same_changes_are_conflict_free() {
         for conflict in [
@@ -80,6 +80,84 @@ pub mod verify {
     }
 }
 
+/// Types supporting object format verification
+pub mod verify_format {
+    use crate::{bstr::BString, tree::EntryRef, Kind, TreeRef};
+
+    /// Returned by [`crate::Data::verify_format()`]
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Decode(#[from] crate::decode::Error),
+        #[error("Tree entry '{name}' has the invalid mode {mode:o}")]
+        InvalidEntryMode { name: BString, mode: u16 },
+        #[error("Tree entry name '{name}' is not a valid path component")]
+        InvalidEntryName { name: BString },
+        #[error("Tree entry '{name}' is not sorted after '{previous}'")]
+        EntriesNotSorted { previous: BString, name: BString },
+        #[error("Tree entry '{name}' is present more than once")]
+        DuplicateEntry { name: BString },
+    }
+
+    impl crate::Data<'_> {
+        /// Verify that the data of `self` is a well-formed object, which is needed for it to be usable by `git`.
+        ///
+        /// Commits and tags must be decodable, while trees additionally must have entries that are sorted and unique,
+        /// with valid modes and names that are usable as path components, excluding `.`, `..` and `.git`.
+        /// Blobs are always valid.
+        ///
+        /// This is similar to what `git hash-object` checks, but is stricter when it comes to tree entry names.
+        pub fn verify_format(&self) -> Result<(), Error> {
+            match self.kind {
+                Kind::Blob => {}
+                Kind::Tree => verify_tree(&TreeRef::from_bytes(self.data)?)?,
+                Kind::Commit | Kind::Tag => {
+                    self.decode()?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn verify_tree(tree: &TreeRef<'_>) -> Result<(), Error> {
+        let mut previous = None::<&EntryRef<'_>>;
+        for entry in &tree.entries {
+            if !matches!(
+                *entry.mode,
+                0o040000 | 0o100644 | 0o100664 | 0o100755 | 0o120000 | 0o160000
+            ) {
+                return Err(Error::InvalidEntryMode {
+                    name: entry.filename.to_owned(),
+                    mode: *entry.mode,
+                });
+            }
+            let name = entry.filename;
+            if name.is_empty()
+                || name.contains(&b'/')
+                || name == "."
+                || name == ".."
+                || name.eq_ignore_ascii_case(b".git")
+            {
+                return Err(Error::InvalidEntryName { name: name.to_owned() });
+            }
+            if let Some(previous) = previous {
+                if previous.filename == name {
+                    return Err(Error::DuplicateEntry { name: name.to_owned() });
+                }
+                if previous > entry {
+                    return Err(Error::EntriesNotSorted {
+                        previous: previous.filename.to_owned(),
+                        name: name.to_owned(),
+                    });
+                }
+            }
+            previous = Some(entry);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,3 +3,4 @@ mod entries;
 mod entry_mode;
 mod from_bytes;
 mod iter;
+mod verify_format;
@@ -0,0 +1,91 @@
+use gix_object::{data::verify_format::Error, tree, tree::EntryMode, Data, Kind};
+
+/// Serialize `entries` as is, as `Tree::write_to()` would refuse to write unsorted entries.
+fn tree(entries: &[(tree::EntryKind, &str)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (kind, name) in entries {
+        buf.extend_from_slice(EntryMode::from(*kind).as_bytes(&mut Default::default()));
+        buf.push(b' ');
+        buf.extend_from_slice(name.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    }
+    buf
+}
+
+fn verify(data: &[u8]) -> Result<(), Error> {
+    Data::new(Kind::Tree, data).verify_format()
+}
+
+#[test]
+fn valid_trees() -> crate::Result {
+    use tree::EntryKind::*;
+    verify(&[])?;
+    verify(&tree(&[
+        (Blob, "a"),
+        (Tree, "a-c"),
+        (Tree, "a.b"),
+        (BlobExecutable, "b"),
+        (Link, "c"),
+        (Commit, "d"),
+    ]))?;
+    verify(&tree(&[(Blob, "a.b"), (Tree, "a")]))?;
+    Ok(())
+}
+
+#[test]
+fn unsorted_entries() {
+    use tree::EntryKind::*;
+    let err = verify(&tree(&[(Blob, "b"), (Blob, "a")])).unwrap_err();
+    assert!(matches!(err, Error::EntriesNotSorted { .. }));
+    assert_eq!(err.to_string(), "Tree entry 'a' is not sorted after 'b'");
+
+    let err = verify(&tree(&[(Tree, "a"), (Blob, "a.b")])).unwrap_err();
+    assert!(
+        matches!(err, Error::EntriesNotSorted { .. }),
+        "trees sort as if they had a trailing slash"
+    );
+}
+
+#[test]
+fn duplicate_entries() {
+    use tree::EntryKind::*;
+    for (first, second) in [(Blob, Blob), (Blob, Tree)] {
+        let err = verify(&tree(&[(first, "a"), (second, "a")])).unwrap_err();
+        assert!(matches!(err, Error::DuplicateEntry { .. }));
+    }
+}
+
+#[test]
+fn invalid_names() {
+    for name in ["", ".", "..", ".git", ".GIT", "a/b"] {
+        let err = verify(&tree(&[(tree::EntryKind::Blob, name)])).unwrap_err();
+        assert!(matches!(err, Error::InvalidEntryName { .. }), "{name:?}");
+    }
+}
+
+#[test]
+fn invalid_modes() {
+    let mut data = b"100600 a\0".to_vec();
+    data.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    let err = verify(&data).unwrap_err();
+    assert_eq!(err.to_string(), "Tree entry 'a' has the invalid mode 100600");
+
+    let mut data = b"100664 a\0".to_vec();
+    data.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    verify(&data).expect("group-writable files are tolerated, like in git");
+}
+
+#[test]
+fn malformed_objects_of_all_kinds() {
+    assert!(matches!(verify(b"garbage").unwrap_err(), Error::Decode(_)));
+    for kind in [Kind::Commit, Kind::Tag] {
+        assert!(matches!(
+            Data::new(kind, b"garbage").verify_format().unwrap_err(),
+            Error::Decode(_)
+        ));
+    }
+    Data::new(Kind::Blob, b"garbage")
+        .verify_format()
+        .expect("blobs are always valid");
+}
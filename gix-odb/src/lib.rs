@@ -56,19 +56,23 @@ pub mod cache;
 pub struct Sink {
     compressor: Option<RefCell<deflate::Write<std::io::Sink>>>,
     object_hash: gix_hash::Kind,
+    strict: bool,
 }
 
-/// Create a new [`Sink`] with compression disabled.
+/// Create a new [`Sink`] with compression and strict mode disabled.
 pub fn sink(object_hash: gix_hash::Kind) -> Sink {
     Sink {
         compressor: None,
         object_hash,
+        strict: false,
     }
 }
 
 ///
 pub mod memory;
 
+pub mod promisor;
+
 mod sink;
 
 ///
@@ -0,0 +1,59 @@
+//! Support for partial clones, whose objects may be missing locally as they are *promised* by a remote.
+//!
+//! Packs received from a promisor remote are marked with a `.promisor` file next to the pack, and objects that are missing
+//! locally can be fetched on demand by [installing](crate::store::Handle::set_promisor()) an implementation of [`Fetch`].
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+
+/// The error returned by [`Fetch::fetch()`].
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Obtain objects that are missing locally from a promisor remote, making them available in the object database.
+pub trait Fetch: Send + Sync {
+    /// Fetch all objects with the given `ids` and store them in the object database, typically as a new pack.
+    ///
+    /// It's not an error if the remote didn't send some of the objects, lookups will then fail as usual.
+    fn fetch(&self, ids: &[ObjectId]) -> Result<(), Error>;
+}
+
+impl<F> Fetch for F
+where
+    F: Fn(&[ObjectId]) -> Result<(), Error> + Send + Sync,
+{
+    fn fetch(&self, ids: &[ObjectId]) -> Result<(), Error> {
+        self(ids)
+    }
+}
+
+/// Return the path of the `.promisor` file that belongs to the pack or index at `pack_or_index_path`.
+pub fn file_path(pack_or_index_path: &Path) -> PathBuf {
+    pack_or_index_path.with_extension("promisor")
+}
+
+/// Return `true` if the pack or index at `pack_or_index_path` was received from a promisor remote.
+pub fn is_promisor_pack(pack_or_index_path: &Path) -> bool {
+    file_path(pack_or_index_path).is_file()
+}
+
+/// Mark the pack or index at `pack_or_index_path` as received from a promisor remote by writing its `.promisor` file,
+/// with one line per reference `(id, name)` that was advertised when fetching it, similar to what `git` does.
+///
+/// Return the path to the written file.
+pub fn write_file<'a>(
+    pack_or_index_path: &Path,
+    refs: impl IntoIterator<Item = (ObjectId, &'a gix_object::bstr::BStr)>,
+) -> std::io::Result<PathBuf> {
+    let mut content = Vec::new();
+    for (id, name) in refs {
+        write!(content, "{id} ")?;
+        content.extend_from_slice(name);
+        content.push(b'\n');
+    }
+    let path = file_path(pack_or_index_path);
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
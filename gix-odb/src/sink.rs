@@ -1,6 +1,6 @@
 use std::{
     cell::RefCell,
-    io::{self, Write},
+    io::{self, Read, Write},
 };
 
 use gix_features::zlib::stream::deflate;
@@ -17,6 +17,20 @@ impl Sink {
         }
         self
     }
+
+    /// Enable or disable strict mode, which is disabled by default.
+    ///
+    /// In strict mode, all objects but blobs are [verified to be well-formed](gix_object::Data::verify_format()) before
+    /// they are hashed, so malformed trees, commits and tags are rejected with an error.
+    /// Note that this requires these objects to be held in memory entirely.
+    ///
+    /// As a sink has no objects to look at, only the format is checked, and objects referenced by trees,
+    /// commits and tags are *not* required to exist. Use `gix::Repository` with `gitoxide.objects.strictCreation`
+    /// for that.
+    pub fn strict(mut self, enable: bool) -> Self {
+        self.strict = enable;
+        self
+    }
 }
 
 impl gix_object::Write for Sink {
@@ -26,6 +40,25 @@ impl gix_object::Write for Sink {
         mut size: u64,
         from: &mut dyn io::Read,
     ) -> Result<gix_hash::ObjectId, gix_object::write::Error> {
+        let data;
+        let mut verified;
+        let from: &mut dyn io::Read = if self.strict && kind != gix_object::Kind::Blob {
+            let mut buf = Vec::new();
+            from.take(size).read_to_end(&mut buf).map_err(Box::new)?;
+            if buf.len() as u64 != size {
+                return Err(Box::new(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Expected {size} bytes of object data, but got only {}", buf.len()),
+                )));
+            }
+            gix_object::Data::new(kind, &buf).verify_format().map_err(Box::new)?;
+            data = buf;
+            verified = data.as_slice();
+            &mut verified
+        } else {
+            from
+        };
+
         let mut buf = [0u8; u16::MAX as usize];
         let header = gix_object::encode::loose_header(kind, size);
 
@@ -21,6 +21,14 @@ pub(crate) mod error {
         LoadPack(#[from] std::io::Error),
         #[error(transparent)]
         EntryType(#[from] gix_pack::data::entry::decode::Error),
+        #[error("Could not fetch {count} missing object(s) like {id} from the promisor remote")]
+        PromisorFetch {
+            /// The first of the objects that were requested.
+            id: gix_hash::ObjectId,
+            /// The amount of objects that were requested.
+            count: usize,
+            source: crate::promisor::Error,
+        },
         #[error("Reached recursion limit of {} while resolving ref delta bases for {}", .max_depth, .id)]
         DeltaBaseRecursionLimit {
             /// the maximum recursion depth we encountered.
@@ -111,6 +119,7 @@ where
             }
         }
 
+        let mut fetched_from_promisor = false;
         'outer: loop {
             {
                 let marker = snapshot.marker;
@@ -301,7 +310,14 @@ where
                     *snapshot = new_snapshot;
                     self.clear_cache();
                 }
-                None => return Ok(None),
+                None => {
+                    // Only objects that were asked for directly are promised, not the bases of deltas.
+                    if recursion.is_none() && !fetched_from_promisor && self.fetch_promised(id, snapshot)? {
+                        fetched_from_promisor = true;
+                        continue;
+                    }
+                    return Ok(None);
+                }
             }
         }
     }
@@ -256,6 +256,7 @@ impl super::Store {
             refresh: RefreshMode::default(),
             ignore_replacements: false,
             token: Some(token),
+            promisor: None,
             inflate: RefCell::new(Default::default()),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
@@ -273,6 +274,7 @@ impl super::Store {
             refresh: Default::default(),
             ignore_replacements: false,
             token: Some(token),
+            promisor: None,
             inflate: RefCell::new(Default::default()),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
@@ -364,6 +366,7 @@ impl super::Handle<Rc<super::Store>> {
         let mut cache = store.to_handle_arc();
         cache.refresh = self.refresh;
         cache.max_recursion_depth = self.max_recursion_depth;
+        cache.promisor.clone_from(&self.promisor);
         Ok(cache)
     }
 }
@@ -392,6 +395,7 @@ where
                 }
                 .into()
             },
+            promisor: self.promisor.clone(),
             inflate: RefCell::new(Default::default()),
             snapshot: RefCell::new(self.store.collect_snapshot()),
             max_recursion_depth: self.max_recursion_depth,
@@ -25,6 +25,8 @@ where
     pub ignore_replacements: bool,
 
     pub(crate) token: Option<handle::Mode>,
+    /// If set, objects that can't be found locally are fetched with it before reporting them as missing.
+    promisor: Option<std::sync::Arc<dyn crate::promisor::Fetch>>,
     snapshot: RefCell<load_index::Snapshot>,
     inflate: RefCell<zlib::Inflate>,
     packed_object_count: RefCell<Option<u64>>,
@@ -58,6 +60,8 @@ pub mod prefix;
 
 mod header;
 
+mod promisor;
+
 ///
 pub mod iter;
 
@@ -0,0 +1,86 @@
+use std::{ops::Deref, sync::Arc};
+
+use gix_hash::{oid, ObjectId};
+
+use super::find::Error;
+use crate::{
+    promisor,
+    store::{load_index, RefreshMode},
+};
+
+/// Lazy fetching of objects promised by a remote in partial clones.
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    /// Set `fetch` to be used to obtain objects that can't be found locally before reporting them as missing,
+    /// or unset it with `None`, and return the previous value.
+    ///
+    /// This should only be set in partial clones as each missing object will be fetched individually once
+    /// a lookup didn't find it. Note that [existence checks](gix_object::Exists) never fetch missing objects.
+    ///
+    /// `fetch` must not use this handle, as it's called while the handle is in use.
+    pub fn set_promisor(&mut self, fetch: Option<Arc<dyn promisor::Fetch>>) -> Option<Arc<dyn promisor::Fetch>> {
+        std::mem::replace(&mut self.promisor, fetch)
+    }
+
+    /// Return the implementation used to fetch missing objects, if set.
+    pub fn promisor(&self) -> Option<&Arc<dyn promisor::Fetch>> {
+        self.promisor.as_ref()
+    }
+
+    /// Fetch all objects in `ids` that are missing locally in a single batch, which is more efficient than fetching
+    /// each of them on demand, and return the amount of objects that were requested.
+    ///
+    /// Nothing happens if no [promisor](Self::set_promisor()) is set.
+    pub fn fetch_missing(&self, ids: impl IntoIterator<Item = ObjectId>) -> Result<usize, Error> {
+        let Some(promisor) = self.promisor.as_ref() else {
+            return Ok(0);
+        };
+        let mut missing: Vec<_> = ids
+            .into_iter()
+            .filter(|id| !gix_pack::Find::contains(self, id))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        if missing.is_empty() {
+            return Ok(0);
+        }
+        promisor.fetch(&missing).map_err(|source| Error::PromisorFetch {
+            id: missing[0],
+            count: missing.len(),
+            source,
+        })?;
+        self.load_new_indices(&mut self.snapshot.borrow_mut())?;
+        Ok(missing.len())
+    }
+
+    /// Fetch `id`, which couldn't be found in `snapshot`, if a promisor is set, and update `snapshot` so it sees
+    /// the new packs. Return `true` if `id` was fetched and should be looked up again.
+    pub(crate) fn fetch_promised(&self, id: &oid, snapshot: &mut load_index::Snapshot) -> Result<bool, Error> {
+        let Some(promisor) = self.promisor.as_ref() else {
+            return Ok(false);
+        };
+        promisor
+            .fetch(&[id.to_owned()])
+            .map_err(|source| Error::PromisorFetch {
+                id: id.to_owned(),
+                count: 1,
+                source,
+            })?;
+        self.load_new_indices(snapshot)?;
+        Ok(true)
+    }
+
+    /// Pick up packs which were added by a promisor, even if we are configured to never refresh.
+    fn load_new_indices(&self, snapshot: &mut load_index::Snapshot) -> Result<(), Error> {
+        while let Some(new_snapshot) = self
+            .store
+            .load_one_index(RefreshMode::AfterAllIndicesLoaded, snapshot.marker)?
+        {
+            *snapshot = new_snapshot;
+            self.clear_cache();
+        }
+        Ok(())
+    }
+}
@@ -12,3 +12,57 @@ fn write() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn write_strict() -> crate::Result {
+    let mut buf = Vec::new();
+    let sink = gix_odb::sink(gix_hash::Kind::Sha1).strict(true);
+    for oid in object_ids() {
+        let obj = locate_oid(oid, &mut buf);
+        let actual = sink.write(&obj.decode()?)?;
+        assert_eq!(actual, oid, "valid objects are written as usual");
+    }
+
+    let mut unsorted = b"100644 b\0".to_vec();
+    unsorted.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    unsorted.extend_from_slice(b"100644 a\0");
+    unsorted.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    let err = sink
+        .write_buf(gix_object::Kind::Tree, &unsorted)
+        .expect_err("malformed trees are rejected");
+    assert_eq!(err.to_string(), "Tree entry 'a' is not sorted after 'b'");
+    assert!(
+        gix_odb::sink(gix_hash::Kind::Sha1)
+            .write_buf(gix_object::Kind::Tree, &unsorted)
+            .is_ok(),
+        "without strict mode, there is no validation"
+    );
+    assert!(
+        sink.write_buf(gix_object::Kind::Blob, &unsorted).is_ok(),
+        "blobs are always valid"
+    );
+
+    let mut null_target = b"100644 a\0".to_vec();
+    null_target.extend_from_slice(gix_hash::Kind::Sha1.null().as_slice());
+    assert!(
+        sink.write_buf(gix_object::Kind::Tree, &null_target).is_ok(),
+        "only the format is validated, so referenced objects don't have to exist"
+    );
+
+    let err = sink
+        .write_stream(
+            gix_object::Kind::Tree,
+            unsorted.len() as u64 + 1,
+            &mut unsorted.as_slice(),
+        )
+        .expect_err("truncated input is rejected");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Expected {} bytes of object data, but got only {}",
+            unsorted.len() + 1,
+            unsorted.len()
+        )
+    );
+    Ok(())
+}
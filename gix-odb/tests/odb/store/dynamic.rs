@@ -949,6 +949,154 @@ fn auto_refresh_with_and_without_id_stability() -> crate::Result {
     Ok(())
 }
 
+mod promisor {
+    use std::sync::{Arc, Mutex};
+
+    use gix_hash::ObjectId;
+    use gix_object::{Exists, FindExt};
+    use gix_odb::Header;
+    use gix_testtools::fixture_path_standalone;
+
+    const PACK: &str = "pack-11fdfa9e156ab73caae3b6da867192221f2089c2";
+
+    type Requests = Arc<Mutex<Vec<Vec<ObjectId>>>>;
+
+    /// Return an empty object database along with the ids of all objects in [`PACK`], and a log of all requested ids.
+    fn empty_db_with_promisor(
+        fail: bool,
+    ) -> crate::Result<(
+        gix_odb::Handle,
+        Vec<ObjectId>,
+        Requests,
+        gix_testtools::tempfile::TempDir,
+    )> {
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let pack_dir = tmp.path().join("objects").join("pack");
+        std::fs::create_dir_all(&pack_dir)?;
+        let source = fixture_path_standalone("objects/pack").join(PACK);
+        let promised: Vec<_> = gix_odb::pack::index::File::at(source.with_extension("idx"), gix_hash::Kind::Sha1)?
+            .iter()
+            .map(|entry| entry.oid)
+            .collect();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut handle = gix_odb::at(tmp.path().join("objects"))?;
+        handle.refresh_never();
+        handle.set_promisor(Some(Arc::new({
+            let requests = requests.clone();
+            move |ids: &[ObjectId]| -> Result<(), gix_odb::promisor::Error> {
+                requests.lock().expect("not poisoned").push(ids.to_vec());
+                if fail {
+                    return Err("the remote hung up".into());
+                }
+                for ext in ["pack", "idx"] {
+                    std::fs::copy(source.with_extension(ext), pack_dir.join(PACK).with_extension(ext))?;
+                }
+                Ok(())
+            }
+        })));
+        Ok((handle, promised, requests, tmp))
+    }
+
+    #[test]
+    fn missing_objects_are_fetched_on_demand() -> crate::Result {
+        let (handle, promised, requests, _tmp) = empty_db_with_promisor(false)?;
+        let id = promised[0];
+        assert!(!handle.exists(&id), "existence checks never fetch");
+        assert!(requests.lock().expect("not poisoned").is_empty());
+
+        let mut buf = Vec::new();
+        handle.find(&id, &mut buf)?;
+        assert_eq!(
+            *requests.lock().expect("not poisoned"),
+            [vec![id]],
+            "the missing object was fetched individually"
+        );
+        assert!(
+            handle.exists(&id),
+            "the new pack was picked up despite refreshes being disabled"
+        );
+
+        assert!(handle.try_header(&promised[1])?.is_some());
+        assert_eq!(
+            requests.lock().expect("not poisoned").len(),
+            1,
+            "the object was already fetched as part of the pack"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_missing_requests_all_missing_objects_in_one_batch() -> crate::Result {
+        let (handle, promised, requests, _tmp) = empty_db_with_promisor(false)?;
+        let ids = promised.iter().rev().chain(promised.iter()).copied();
+        assert_eq!(handle.fetch_missing(ids)?, promised.len());
+        assert_eq!(
+            *requests.lock().expect("not poisoned"),
+            std::slice::from_ref(&promised),
+            "ids are sorted and deduplicated"
+        );
+        assert!(promised.iter().all(|id| handle.exists(id)));
+
+        assert_eq!(
+            handle.fetch_missing(promised.iter().copied())?,
+            0,
+            "nothing is missing anymore"
+        );
+        assert_eq!(requests.lock().expect("not poisoned").len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn objects_the_promisor_does_not_send_are_requested_once_and_missing() -> crate::Result {
+        let (handle, _promised, requests, _tmp) = empty_db_with_promisor(false)?;
+        let id = crate::hex_to_id("0000000000000000000000000000000000000001");
+        let mut buf = Vec::new();
+        assert!(gix_object::Find::try_find(&handle, &id, &mut buf)?.is_none());
+        assert_eq!(*requests.lock().expect("not poisoned"), [vec![id]]);
+
+        assert!(handle.try_header(&id)?.is_none());
+        assert_eq!(
+            requests.lock().expect("not poisoned").len(),
+            2,
+            "each lookup that fails locally asks the promisor once"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_errors_are_propagated() -> crate::Result {
+        let (handle, promised, requests, _tmp) = empty_db_with_promisor(true)?;
+        let mut buf = Vec::new();
+        let err = handle.find(&promised[0], &mut buf).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Could not fetch 1 missing object(s) like {} from the promisor remote",
+                promised[0]
+            )
+        );
+        assert_eq!(requests.lock().expect("not poisoned").len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn promisor_packs_are_marked_with_a_file() -> crate::Result {
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let pack = tmp.path().join(PACK).with_extension("pack");
+        assert!(!gix_odb::promisor::is_promisor_pack(&pack));
+        let id = crate::hex_to_id("11fdfa9e156ab73caae3b6da867192221f2089c2");
+        let path = gix_odb::promisor::write_file(&pack, Some((id, "refs/heads/main".into())))?;
+        assert_eq!(path, tmp.path().join(PACK).with_extension("promisor"));
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "11fdfa9e156ab73caae3b6da867192221f2089c2 refs/heads/main\n"
+        );
+        assert!(gix_odb::promisor::is_promisor_pack(&pack.with_extension("idx")));
+        Ok(())
+    }
+}
+
 mod verify {
     use std::sync::atomic::AtomicBool;
 
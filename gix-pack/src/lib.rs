@@ -44,6 +44,8 @@ pub mod index;
 ///
 pub mod multi_index;
 
+pub mod mtimes;
+
 ///
 pub mod verify;
 
@@ -0,0 +1,165 @@
+//! Support for `.mtimes` files which accompany cruft packs.
+//!
+//! They store the modification time of each object in a cruft pack, in the same order as the objects in the
+//! corresponding pack index, which is sorted by object id. This allows unreachable objects to age even though they
+//! were packed, and eventually expire just like loose objects would.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+
+const SIGNATURE: &[u8] = b"MTME";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 4 /*signature*/ + 4 /*version*/ + 4 /*hash id*/;
+
+/// A `.mtimes` file, fully read into memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    mtimes: Vec<u32>,
+    pack_checksum: ObjectId,
+    path: PathBuf,
+}
+
+///
+pub mod init {
+    /// The error returned by [File::at()][super::File::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read mtimes file at '{path}'")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+        #[error("{message}")]
+        Corrupt { message: &'static str },
+        #[error("Unsupported mtimes version: {version}")]
+        UnsupportedVersion { version: u32 },
+        #[error("Unsupported hash id: {id}")]
+        UnsupportedObjectHash { id: u32 },
+        #[error("The mtimes file was written for {actual} objects, but {expected} were expected")]
+        ObjectCountMismatch { actual: usize, expected: usize },
+        #[error("The trailing checksum of the mtimes file doesn't match its content")]
+        Checksum,
+    }
+}
+
+/// Initialization
+impl File {
+    /// Open the `.mtimes` file at `path`, which is expected to contain one modification time for each
+    /// of the `num_objects` objects in the pack it belongs to.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind, num_objects: u32) -> Result<Self, init::Error> {
+        use init::Error;
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let hash_len = object_hash.len_in_bytes();
+        if data.len() < HEADER_LEN + 2 * hash_len {
+            return Err(Error::Corrupt {
+                message: "mtimes file is too small to contain header and trailer",
+            });
+        }
+        if &data[..4] != SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "mtimes file doesn't start with the 'MTME' signature",
+            });
+        }
+        let version = be_u32(&data[4..]);
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let hash_id = be_u32(&data[8..]);
+        if hash_id != hash_id_of(object_hash) {
+            return Err(Error::UnsupportedObjectHash { id: hash_id });
+        }
+
+        let (content, trailer) = data.split_at(data.len() - hash_len);
+        let mut hasher = gix_features::hash::hasher(object_hash);
+        hasher.update(content);
+        if ObjectId::from(hasher.digest()).as_slice() != trailer {
+            return Err(Error::Checksum);
+        }
+        let (table, pack_checksum) = content[HEADER_LEN..].split_at(content.len() - HEADER_LEN - hash_len);
+        if table.len() % 4 != 0 {
+            return Err(Error::Corrupt {
+                message: "the mtimes table isn't a multiple of 4 bytes",
+            });
+        }
+        let mtimes: Vec<_> = table.chunks_exact(4).map(be_u32).collect();
+        if mtimes.len() != num_objects as usize {
+            return Err(Error::ObjectCountMismatch {
+                actual: mtimes.len(),
+                expected: num_objects as usize,
+            });
+        }
+        Ok(File {
+            mtimes,
+            pack_checksum: ObjectId::from_bytes_or_panic(pack_checksum),
+            path: path.to_owned(),
+        })
+    }
+}
+
+/// Access
+impl File {
+    /// The path from which this file was read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The checksum of the pack this file belongs to.
+    pub fn pack_checksum(&self) -> &gix_hash::oid {
+        &self.pack_checksum
+    }
+
+    /// The amount of objects for which a modification time is stored.
+    pub fn num_objects(&self) -> u32 {
+        self.mtimes.len() as u32
+    }
+
+    /// Return the modification time of the object at `index`, as seconds since the unix epoch, with `index` being the
+    /// position of the object in the pack index.
+    pub fn mtime_at_index(&self, index: crate::index::EntryIndex) -> u32 {
+        self.mtimes[index as usize]
+    }
+}
+
+/// Writing
+impl File {
+    /// Write a `.mtimes` file to `out` for a pack with `pack_checksum`, storing each of the `mtimes` which must
+    /// be in the order of objects in the pack index, that is sorted by object id.
+    ///
+    /// Return the checksum of the written file.
+    pub fn write_to(
+        out: &mut dyn std::io::Write,
+        mtimes: impl IntoIterator<Item = u32>,
+        pack_checksum: &gix_hash::oid,
+        object_hash: gix_hash::Kind,
+    ) -> std::io::Result<ObjectId> {
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&VERSION.to_be_bytes())?;
+        out.write_all(&hash_id_of(object_hash).to_be_bytes())?;
+        for mtime in mtimes {
+            out.write_all(&mtime.to_be_bytes())?;
+        }
+        out.write_all(pack_checksum.as_bytes())?;
+        let checksum: ObjectId = out.hash.digest().into();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
+
+fn hash_id_of(kind: gix_hash::Kind) -> u32 {
+    match kind {
+        gix_hash::Kind::Sha1 => 1,
+    }
+}
+
+fn be_u32(data: &[u8]) -> u32 {
+    u32::from_be_bytes(data[..4].try_into().expect("at least 4 bytes"))
+}
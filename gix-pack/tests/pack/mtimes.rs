@@ -0,0 +1,75 @@
+use gix_pack::mtimes;
+
+use crate::{
+    hex_to_id,
+    pack::{fixture_path, SMALL_PACK_INDEX},
+};
+
+#[test]
+fn write_and_read_round_trip() -> crate::pack::Result {
+    let index = gix_pack::index::File::at(fixture_path(SMALL_PACK_INDEX), gix_hash::Kind::Sha1)?;
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let path = dir.path().join("pack.mtimes");
+
+    let times: Vec<u32> = (0..index.num_objects()).map(|idx| 1_700_000_000 + idx).collect();
+    let mut buf = Vec::new();
+    let checksum = mtimes::File::write_to(
+        &mut buf,
+        times.iter().copied(),
+        &index.pack_checksum(),
+        gix_hash::Kind::Sha1,
+    )?;
+    assert_eq!(
+        buf.len(),
+        4 + 4 + 4 + times.len() * 4 + 20 + 20,
+        "header, table, pack checksum and trailer"
+    );
+    assert_eq!(&buf[buf.len() - 20..], checksum.as_slice());
+    std::fs::write(&path, &buf)?;
+
+    let file = mtimes::File::at(&path, gix_hash::Kind::Sha1, index.num_objects())?;
+    assert_eq!(file.num_objects(), index.num_objects());
+    assert_eq!(file.pack_checksum(), index.pack_checksum());
+    assert_eq!(file.path(), path);
+    for (idx, expected) in times.iter().enumerate() {
+        assert_eq!(file.mtime_at_index(idx as u32), *expected);
+    }
+    assert_eq!(
+        index.pack_checksum(),
+        hex_to_id("0f3ea84cd1bba10c2a03d736a460635082833e59"),
+        "sanity check"
+    );
+    Ok(())
+}
+
+#[test]
+fn corrupt_files_are_rejected() -> crate::pack::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let path = dir.path().join("pack.mtimes");
+    let pack_checksum = gix_hash::Kind::Sha1.null();
+    let mut buf = Vec::new();
+    mtimes::File::write_to(&mut buf, [1, 2, 3], &pack_checksum, gix_hash::Kind::Sha1)?;
+
+    std::fs::write(&path, &buf)?;
+    assert!(
+        matches!(
+            mtimes::File::at(&path, gix_hash::Kind::Sha1, 2),
+            Err(mtimes::init::Error::ObjectCountMismatch { actual: 3, expected: 2 })
+        ),
+        "the amount of objects must match the pack"
+    );
+
+    buf[14] ^= 1;
+    std::fs::write(&path, &buf)?;
+    assert!(matches!(
+        mtimes::File::at(&path, gix_hash::Kind::Sha1, 3),
+        Err(mtimes::init::Error::Checksum)
+    ));
+
+    std::fs::write(&path, b"MTIM")?;
+    assert!(matches!(
+        mtimes::File::at(&path, gix_hash::Kind::Sha1, 0),
+        Err(mtimes::init::Error::Corrupt { .. })
+    ));
+    Ok(())
+}
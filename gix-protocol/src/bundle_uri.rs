@@ -0,0 +1,253 @@
+//! Support for the [bundle-uri](https://git-scm.com/docs/bundle-uri) protocol, which lets servers advertise
+//! pre-generated bundles that clients download before fetching the remainder of the objects from the server.
+use bstr::{BStr, BString, ByteSlice};
+
+/// How many of the bundles in a [`List`] are needed to obtain all of its objects.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Mode {
+    /// All bundles are needed, as they build on each other.
+    All,
+    /// Any bundle is sufficient, as they contain the same objects, for instance because they are served from different mirrors.
+    Any,
+}
+
+/// A hint on how to select the bundles of a [`List`] to download.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Heuristic {
+    /// Each bundle has a [creation token](Bundle::creation_token), and bundles with greater tokens build on bundles with smaller ones.
+    ///
+    /// This allows to download only the newest bundles and go back in time until all prerequisites are present,
+    /// and to remember the greatest token seen to skip bundles that were already downloaded in future fetches.
+    CreationToken,
+}
+
+/// A single bundle of a [`List`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Bundle {
+    /// The identifier of the bundle within the list.
+    pub id: BString,
+    /// The uri to download the bundle from, which may be relative to the uri of the list itself.
+    ///
+    /// Note that it may point to another list instead of a bundle.
+    pub uri: BString,
+    /// The object filter used to produce the bundle, if it is the result of a partial clone.
+    pub filter: Option<BString>,
+    /// The token to order bundles by if the [`Heuristic::CreationToken`] is used.
+    pub creation_token: Option<u64>,
+}
+
+/// A list of bundles as advertised by a server or as found at a bundle uri.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct List {
+    /// The version of the list format, which is always `1`.
+    pub version: u32,
+    /// Whether all or any of the bundles are needed.
+    pub mode: Mode,
+    /// The way bundles should be selected, if set.
+    pub heuristic: Option<Heuristic>,
+    /// All bundles of the list, in order of appearance.
+    pub bundles: Vec<Bundle>,
+}
+
+///
+pub mod parse {
+    use bstr::BString;
+
+    /// The error returned by [`List::from_key_value_pairs()`](super::List::from_key_value_pairs()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Bundle list line {line:?} isn't of the form 'key=value'")]
+        InvalidLine { line: BString },
+        #[error("Bundle list version {version:?} is unsupported")]
+        UnsupportedVersion { version: BString },
+        #[error("Bundle list mode {mode:?} is neither 'all' nor 'any'")]
+        UnknownMode { mode: BString },
+        #[error("The creation token {value:?} of bundle {id:?} isn't an unsigned integer")]
+        InvalidCreationToken { id: BString, value: BString },
+        #[error("The bundle list lacks the required key 'bundle.{key}'")]
+        MissingKey { key: &'static str },
+        #[error("The bundle {id:?} lacks the required key 'bundle.{id}.uri'")]
+        MissingUri { id: BString },
+    }
+}
+
+impl List {
+    /// Parse a list from `key=value` lines as sent in response to the `bundle-uri` command.
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a BStr>) -> Result<Self, parse::Error> {
+        let mut pairs = Vec::new();
+        for line in lines {
+            let line = line.trim_end_with(|c| c == '\n').as_bstr();
+            let (key, value) = line
+                .split_once_str(b"=")
+                .ok_or_else(|| parse::Error::InvalidLine { line: line.to_owned() })?;
+            pairs.push((key.as_bstr(), value.as_bstr()));
+        }
+        Self::from_key_value_pairs(pairs)
+    }
+
+    /// Parse a list from `key` and `value` pairs, where each key is a configuration key like `bundle.version`
+    /// or `bundle.<id>.uri`, just like in the configuration file format that bundle lists may also be stored in.
+    ///
+    /// Keys that are unknown or aren't in the `bundle` section are ignored.
+    pub fn from_key_value_pairs<'a>(
+        pairs: impl IntoIterator<Item = (&'a BStr, &'a BStr)>,
+    ) -> Result<Self, parse::Error> {
+        let mut version = None;
+        let mut mode = None;
+        let mut heuristic = None;
+        let mut bundles = Vec::<Bundle>::new();
+        for (key, value) in pairs {
+            let Some(key) = key
+                .get(..b"bundle.".len())
+                .filter(|section| section.eq_ignore_ascii_case(b"bundle."))
+                .map(|_| key[b"bundle.".len()..].as_bstr())
+            else {
+                continue;
+            };
+            let Some((id, key)) = key.rsplit_once_str(b".") else {
+                if key.eq_ignore_ascii_case(b"version") {
+                    if value != "1" {
+                        return Err(parse::Error::UnsupportedVersion {
+                            version: value.to_owned(),
+                        });
+                    }
+                    version = Some(1);
+                } else if key.eq_ignore_ascii_case(b"mode") {
+                    mode = Some(match value.as_bytes() {
+                        b"all" => Mode::All,
+                        b"any" => Mode::Any,
+                        _ => return Err(parse::Error::UnknownMode { mode: value.to_owned() }),
+                    });
+                } else if key.eq_ignore_ascii_case(b"heuristic") {
+                    // Like `git`, ignore heuristics we don't know.
+                    heuristic = (value == "creationToken").then_some(Heuristic::CreationToken);
+                }
+                continue;
+            };
+            let id = id.as_bstr();
+            let idx = match bundles.iter().position(|b| b.id == id) {
+                Some(idx) => idx,
+                None => {
+                    bundles.push(Bundle {
+                        id: id.to_owned(),
+                        uri: BString::default(),
+                        filter: None,
+                        creation_token: None,
+                    });
+                    bundles.len() - 1
+                }
+            };
+            let bundle = &mut bundles[idx];
+            if key.eq_ignore_ascii_case(b"uri") {
+                bundle.uri = value.to_owned();
+            } else if key.eq_ignore_ascii_case(b"filter") {
+                bundle.filter = Some(value.to_owned());
+            } else if key.eq_ignore_ascii_case(b"creationToken") {
+                bundle.creation_token = Some(value.to_str().ok().and_then(|value| value.parse().ok()).ok_or_else(
+                    || parse::Error::InvalidCreationToken {
+                        id: id.to_owned(),
+                        value: value.to_owned(),
+                    },
+                )?);
+            }
+        }
+        if let Some(bundle) = bundles.iter().find(|b| b.uri.is_empty()) {
+            return Err(parse::Error::MissingUri { id: bundle.id.clone() });
+        }
+        Ok(List {
+            version: version.ok_or(parse::Error::MissingKey { key: "version" })?,
+            mode: mode.ok_or(parse::Error::MissingKey { key: "mode" })?,
+            heuristic,
+            bundles,
+        })
+    }
+}
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod error {
+    /// The error returned by [`bundle_uri()`](crate::bundle_uri()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The server doesn't support the 'bundle-uri' command")]
+        Unsupported,
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        DecodePacketline(#[from] gix_transport::packetline::decode::Error),
+        #[error(transparent)]
+        Transport(#[from] gix_transport::client::Error),
+        #[error(transparent)]
+        Parse(#[from] super::parse::Error),
+    }
+
+    impl gix_transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                Error::Transport(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use error::Error;
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub(crate) mod function {
+    use bstr::{BString, ByteSlice};
+    use gix_transport::client::{Capabilities, Transport, TransportV2Ext};
+    use maybe_async::maybe_async;
+
+    use super::{Error, List};
+    use crate::Command;
+
+    /// Invoke the `bundle-uri` V2 command on `transport`, which requires a prior handshake that yielded
+    /// server `capabilities`, and return the list of bundles the server advertises.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    ///
+    /// Note that the list may be empty, and that the uris of its bundles are expected to be absolute.
+    #[maybe_async]
+    pub async fn bundle_uri(
+        mut transport: impl Transport,
+        capabilities: &Capabilities,
+        trace: bool,
+    ) -> Result<List, Error> {
+        let _span = gix_features::trace::detail!("gix_protocol::bundle_uri()");
+        let bundle_uri = Command::BundleUri;
+        if !capabilities.contains(bundle_uri.as_str()) {
+            return Err(Error::Unsupported);
+        }
+        let mut response = transport
+            .invoke(
+                bundle_uri.as_str(),
+                bundle_uri
+                    .default_features(gix_transport::Protocol::V2, capabilities)
+                    .into_iter(),
+                None::<std::iter::Empty<BString>>,
+                trace,
+            )
+            .await?;
+        let mut lines = Vec::new();
+        while let Some(line) = response
+            .readline()
+            .await
+            .transpose()?
+            .transpose()?
+            .and_then(|line| line.as_bstr().map(ToOwned::to_owned))
+        {
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            return Ok(List {
+                version: 1,
+                mode: super::Mode::All,
+                heuristic: None,
+                bundles: Vec::new(),
+            });
+        }
+        Ok(List::from_lines(lines.iter().map(|line| line.as_bstr()))?)
+    }
+}
@@ -0,0 +1,60 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::fetch::Filter;
+
+///
+pub mod parse {
+    /// The error returned by [`Filter::from_bytes()`](crate::fetch::Filter::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Filter specification {spec:?} is not supported, use 'blob:none', 'blob:limit=<n>[kmg]', 'tree:<depth>' or 'sparse:oid=<blob-ish>'")]
+        Unsupported { spec: bstr::BString },
+        #[error("Could not parse {value:?} as number in filter specification {spec:?}")]
+        InvalidNumber { spec: bstr::BString, value: bstr::BString },
+    }
+}
+
+impl Filter {
+    /// Parse a filter specification like `blob:none`, `blob:limit=1m`, `tree:0` or `sparse:oid=main:.sparse`,
+    /// just like `git clone --filter` does.
+    pub fn from_bytes(spec: &BStr) -> Result<Self, parse::Error> {
+        let number = |value: &[u8], allow_unit: bool| {
+            let invalid = || parse::Error::InvalidNumber {
+                spec: spec.into(),
+                value: value.into(),
+            };
+            let (digits, factor) = match value.last().map(u8::to_ascii_lowercase) {
+                Some(b'k') if allow_unit => (&value[..value.len() - 1], 1024),
+                Some(b'm') if allow_unit => (&value[..value.len() - 1], 1024 * 1024),
+                Some(b'g') if allow_unit => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+                _ => (value, 1),
+            };
+            digits
+                .to_str()
+                .ok()
+                .and_then(|digits| digits.parse::<u64>().ok())
+                .and_then(|value| value.checked_mul(factor))
+                .ok_or_else(invalid)
+        };
+        Ok(if spec == "blob:none" {
+            Filter::BlobNone
+        } else if let Some(limit) = spec.strip_prefix(b"blob:limit=") {
+            Filter::BlobLimit(number(limit, true)?)
+        } else if let Some(depth) = spec.strip_prefix(b"tree:") {
+            Filter::TreeDepth(number(depth, false)?)
+        } else if let Some(blob) = spec.strip_prefix(b"sparse:oid=").filter(|blob| !blob.is_empty()) {
+            Filter::SparseOid(blob.into())
+        } else {
+            return Err(parse::Error::Unsupported { spec: spec.into() });
+        })
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = parse::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::from_bytes(s.into())
+    }
+}
@@ -45,6 +45,7 @@ pub async fn fetch<P, T, E>(
         shallow,
         tags,
         reject_shallow_remote,
+        filter,
     }: Options<'_>,
 ) -> Result<Option<Outcome>, Error>
 where
@@ -66,6 +67,7 @@ where
 
     crate::fetch::Response::check_required_features(protocol_version, &fetch_features)?;
     let sideband_all = fetch_features.iter().any(|(n, _)| *n == "sideband-all");
+    let no_done = fetch_features.iter().any(|(n, _)| *n == "no-done");
     let mut arguments = Arguments::new(protocol_version, fetch_features, trace_packetlines);
     if matches!(tags, Tags::Included) {
         if !arguments.can_use_include_tag() {
@@ -79,6 +81,15 @@ where
         arguments.use_include_tag();
     }
     let (shallow_commits, mut shallow_lock) = add_shallow_args(&mut arguments, shallow, &shallow_file)?;
+    if let Some(filter) = filter {
+        if !arguments.can_use_filter() {
+            return Err(Error::MissingServerFeature {
+                feature: "filter",
+                description: "partial clones need the server to omit objects, otherwise all objects would be sent",
+            });
+        }
+        arguments.filter(&filter.to_string());
+    }
 
     let negotiate_span = gix_trace::detail!(
         "negotiate",
@@ -120,8 +131,12 @@ where
                 if sideband_all {
                     setup_remote_progress(&mut progress, &mut reader, should_interrupt);
                 }
-                let response =
-                    crate::fetch::Response::from_line_reader(protocol_version, &mut reader, is_done, !is_done).await?;
+                let response = crate::fetch::Response::from_line_reader(
+                    protocol_version,
+                    &mut reader,
+                    crate::fetch::response::V1Round { done: is_done, no_done },
+                )
+                .await?;
                 let has_pack = response.has_pack();
                 previous_response = Some(response);
                 if has_pack {
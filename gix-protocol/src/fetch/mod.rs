@@ -27,6 +27,9 @@ mod error;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 #[cfg(feature = "fetch")]
 pub use error::Error;
+///
+pub mod filter;
+
 ///
 pub mod response;
 
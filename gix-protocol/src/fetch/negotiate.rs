@@ -445,7 +445,7 @@ pub mod one_round {
 /// All [commits we have](crate::fetch::Arguments::have()) are added to `arguments` accordingly.
 ///
 /// Returns information about this round, and `true` if we are done and should stop negotiating *after* the `arguments` have
-/// been sent to the remote one last time.
+/// been sent to the remote one last time. This is also the case if the server signalled that it's `ready` to send a pack.
 pub fn one_round(
     negotiator: &mut dyn gix_negotiate::Negotiator,
     graph: &mut gix_negotiate::Graph<'_, '_>,
@@ -454,6 +454,7 @@ pub fn one_round(
     previous_response: Option<&crate::fetch::Response>,
 ) -> Result<(Round, bool), Error> {
     let mut seen_ack = false;
+    let mut seen_ready = false;
     if let Some(response) = previous_response {
         use crate::fetch::response::Acknowledgement;
         for ack in response.acknowledgements() {
@@ -466,8 +467,9 @@ pub fn one_round(
                     }
                 }
                 Acknowledgement::Ready => {
-                    // NOTE: In git, there is some logic dealing with whether to expect a DELIM or FLUSH package,
-                    //       but we handle this with peeking.
+                    // In V2, the pack follows right away, just like in V1 with `no-done`. Otherwise, the server
+                    // is waiting for us to be done, which is what we do without sending more haves.
+                    seen_ready = true;
                 }
                 Acknowledgement::Nak => {}
             }
@@ -483,7 +485,10 @@ pub fn one_round(
     }
 
     let mut haves_added = 0;
-    for have_id in (0..state.haves_to_send).map_while(|_| negotiator.next_have(graph)) {
+    for have_id in (0..state.haves_to_send)
+        .take_while(|_| !seen_ready)
+        .map_while(|_| negotiator.next_have(graph))
+    {
         arguments.have(have_id?);
         haves_added += 1;
     }
@@ -501,7 +506,7 @@ pub fn one_round(
         haves_to_send: state.haves_to_send,
         previous_response_had_at_least_one_in_common: seen_ack,
     };
-    let is_done = haves_added != state.haves_to_send || (state.seen_ack && state.in_vain >= 256);
+    let is_done = seen_ready || haves_added != state.haves_to_send || (state.seen_ack && state.in_vain >= 256);
     state.adjust_window_size();
 
     Ok((round, is_done))
@@ -5,7 +5,7 @@ use gix_transport::{client, Protocol};
 use crate::fetch::{
     response,
     response::shallow_update_from_line,
-    response::{Acknowledgement, ShallowUpdate, WantedRef},
+    response::{Acknowledgement, ShallowUpdate, V1Next, V1Parser, V1Round, WantedRef},
     Response,
 };
 
@@ -34,42 +34,28 @@ async fn parse_v2_section<T>(
 impl Response {
     /// Parse a response of the given `version` of the protocol from `reader`.
     ///
-    /// `client_expects_pack` is only relevant for V1 stateful connections, and if `false`, causes us to stop parsing when seeing `NAK`,
-    /// and if `true` we will keep parsing until we get a pack as the client already signalled to the server that it's done.
-    /// This way of doing things allows us to exploit knowledge about more recent versions of the protocol, which keeps code easier
-    /// and more localized without having to support all the cruft that there is.
-    ///
-    /// `wants_to_negotiate` should be `false` for clones which is when we don't have sent any haves. The reason for this flag to exist
-    /// is to predict how to parse V1 output only, and neither `client_expects_pack` nor `wants_to_negotiate` are relevant for V2.
-    /// This ugliness is in place to avoid having to resort to an [an even more complex ugliness](https://github.com/git/git/blob/9e49351c3060e1fa6e0d2de64505b7becf157f28/fetch-pack.c#L583-L594)
-    /// that `git` has to use to predict how many acks are supposed to be read. We also genuinely hope that this covers it all….
+    /// `v1_round` describes what was sent in the current negotiation round, and is only relevant for V1, whose responses
+    /// aren't self-describing. Lines are processed as they arrive, and parsing stops exactly where the response of the round ends,
+    /// which is important for stateful connections as reading any further would block forever.
     pub async fn from_line_reader(
         version: Protocol,
         reader: &mut (impl client::ExtendedBufRead<'_> + Unpin),
-        client_expects_pack: bool,
-        wants_to_negotiate: bool,
+        v1_round: V1Round,
     ) -> Result<Response, response::Error> {
         match version {
             Protocol::V0 | Protocol::V1 => {
                 let mut line = String::new();
-                let mut acks = Vec::<Acknowledgement>::new();
-                let mut shallows = Vec::<ShallowUpdate>::new();
-                let mut saw_ready = false;
+                let mut parser = V1Parser::new(v1_round);
                 let has_pack = 'lines: loop {
                     line.clear();
                     let peeked_line = match reader.peek_data_line().await {
                         Some(Ok(Ok(line))) => String::from_utf8_lossy(line),
-                        // This special case (hang/block forever) deals with a single NAK being a legitimate EOF sometimes
-                        // Note that this might block forever in stateful connections as there it's not really clear
-                        // if something will be following or not by just looking at the response. Instead you have to know
-                        // [a lot](https://github.com/git/git/blob/9e49351c3060e1fa6e0d2de64505b7becf157f28/fetch-pack.c#L583-L594)
-                        // to deal with this correctly.
-                        // For now this is acceptable, as V2 can be used as a workaround, which also is the default.
+                        // Stateless connections end the response of a round without pack like this.
                         Some(Err(err)) if err.kind() == io::ErrorKind::UnexpectedEof => break 'lines false,
                         Some(Err(err)) => return Err(err.into()),
                         Some(Ok(Err(err))) => return Err(err.into()),
                         None => {
-                            // maybe we saw a shallow flush packet, let's reset and retry
+                            // The flush packet after shallow updates, let's reset and retry.
                             debug_assert_eq!(
                                 reader.stopped_at(),
                                 Some(client::MessageKind::Flush),
@@ -86,7 +72,8 @@ impl Response {
                         }
                     };
 
-                    if Response::parse_v1_ack_or_shallow_or_assume_pack(&mut acks, &mut shallows, &peeked_line) {
+                    let next = parser.handle(&peeked_line);
+                    if let V1Next::Pack = next {
                         break 'lines true;
                     }
                     assert_ne!(
@@ -94,21 +81,11 @@ impl Response {
                         0,
                         "consuming a peeked line works"
                     );
-                    // When the server sends ready, we know there is going to be a pack so no need to stop early.
-                    saw_ready |= matches!(acks.last(), Some(Acknowledgement::Ready));
-                    if let Some(Acknowledgement::Nak) = acks.last().filter(|_| !client_expects_pack || !saw_ready) {
-                        if !wants_to_negotiate {
-                            continue;
-                        }
-                        break 'lines false;
+                    if let V1Next::Stop { has_pack } = next {
+                        break 'lines has_pack;
                     }
                 };
-                Ok(Response {
-                    acks,
-                    shallows,
-                    wanted_refs: vec![],
-                    has_pack,
-                })
+                Ok(parser.into_response(has_pack))
             }
             Protocol::V2 => {
                 // NOTE: We only read acknowledgements and scrub to the pack file, until we have use for the other features
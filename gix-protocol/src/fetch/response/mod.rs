@@ -200,32 +200,95 @@ impl Response {
     }
 }
 
+/// Information about the V1 negotiation round whose response is to be parsed with [`Response::from_line_reader()`].
+///
+/// V1 responses aren't self-describing, so what was sent to the server determines where a response ends, and if it's followed by a pack.
+/// V2 responses are self-describing, which is why this information isn't used for them.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct V1Round {
+    /// `true` if `done` was sent, which is when the server responds with a pack after its final `ACK` or `NAK`.
+    ///
+    /// This is always the case for clones, as they don't have anything to negotiate.
+    pub done: bool,
+    /// `true` if the `no-done` capability was negotiated, which is when the server responds with a pack right away if
+    /// it signalled that it's `ready` in the current round, without waiting for `done`.
+    pub no_done: bool,
+}
+
+/// What to do after a line of a V1 response was seen.
 #[cfg(any(feature = "async-client", feature = "blocking-client"))]
-impl Response {
-    /// with a friendly server, we just assume that a non-ack line is a pack line
-    /// which is our hint to stop here.
-    fn parse_v1_ack_or_shallow_or_assume_pack(
-        acks: &mut Vec<Acknowledgement>,
-        shallows: &mut Vec<ShallowUpdate>,
-        peeked_line: &str,
-    ) -> bool {
-        match Acknowledgement::from_line(peeked_line) {
-            Ok(ack) => match ack.id() {
-                Some(id) => {
-                    if !acks.iter().any(|a| a.id() == Some(id)) {
-                        acks.push(ack);
+enum V1Next {
+    /// Consume the line and read the next one.
+    Continue,
+    /// Consume the line and stop, with the pack following if `has_pack` is `true`.
+    Stop { has_pack: bool },
+    /// Don't consume the line as it's the beginning of the pack.
+    Pack,
+}
+
+/// The state of parsing a V1 response line by line, which avoids reading past its end so stateful connections don't block
+/// waiting for lines that the server will never send.
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+struct V1Parser {
+    round: V1Round,
+    acks: Vec<Acknowledgement>,
+    shallows: Vec<ShallowUpdate>,
+    saw_ready: bool,
+}
+
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+impl V1Parser {
+    fn new(round: V1Round) -> Self {
+        V1Parser {
+            round,
+            acks: Vec::new(),
+            shallows: Vec::new(),
+            saw_ready: false,
+        }
+    }
+
+    /// Handle `peeked_line` and return what to do next. With a friendly server, we just assume that an unknown line
+    /// is a pack line which is our hint to stop here.
+    fn handle(&mut self, peeked_line: &str) -> V1Next {
+        let ack = match Acknowledgement::from_line(peeked_line) {
+            Ok(ack) => ack,
+            Err(_) => {
+                return match shallow_update_from_line(peeked_line) {
+                    Ok(shallow) => {
+                        self.shallows.push(shallow);
+                        V1Next::Continue
                     }
+                    Err(_) => V1Next::Pack,
                 }
-                None => acks.push(ack),
-            },
-            Err(_) => match shallow_update_from_line(peeked_line) {
-                Ok(shallow) => {
-                    shallows.push(shallow);
-                }
-                Err(_) => return true,
-            },
+            }
+        };
+        // `ACK <id>` without a status is sent last, right before the pack, after `done` or in `no-done` mode.
+        let is_final_ack = ack.id().is_some() && peeked_line.trim_end().split(' ').count() == 2;
+        match ack.id() {
+            Some(id) if self.acks.iter().any(|a| a.id() == Some(id)) => {}
+            _ => self.acks.push(ack),
+        }
+        match ack {
+            _ if is_final_ack => V1Next::Stop { has_pack: true },
+            Acknowledgement::Ready => {
+                self.saw_ready = true;
+                V1Next::Continue
+            }
+            Acknowledgement::Common(_) => V1Next::Continue,
+            // After `done`, or when `ready` in `no-done` mode, the server will send a pack, possibly after a final `ACK`.
+            // Otherwise, `NAK` ends the response of the round.
+            Acknowledgement::Nak if self.round.done || (self.round.no_done && self.saw_ready) => V1Next::Continue,
+            Acknowledgement::Nak => V1Next::Stop { has_pack: false },
+        }
+    }
+
+    fn into_response(self, has_pack: bool) -> Response {
+        Response {
+            acks: self.acks,
+            shallows: self.shallows,
+            wanted_refs: vec![],
+            has_pack,
         }
-        false
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::fetch::response::{Acknowledgement, ShallowUpdate, WantedRef};
+use bstr::BString;
 use std::path::PathBuf;
 
 /// Options for use in [`fetch()`](`crate::fetch()`)
@@ -15,6 +16,10 @@ pub struct Options<'a> {
     /// If `true`, if we fetch from a remote that only offers shallow clones, the operation will fail with an error
     /// instead of writing the shallow boundary to the shallow file.
     pub reject_shallow_remote: bool,
+    /// If set, ask the remote to omit objects matching this filter to produce a partial clone.
+    ///
+    /// The remote must support the `filter` capability.
+    pub filter: Option<&'a Filter>,
 }
 
 /// For use in [`RefMap::new()`] and [`fetch`](crate::fetch()).
@@ -214,6 +219,36 @@ impl Tags {
     }
 }
 
+/// A filter to omit objects when fetching, turning the receiving repository into a partial clone whose
+/// missing objects are promised by the remote.
+///
+/// Use [`Filter::from_bytes()`] to parse it from its textual representation as used by `git`, and
+/// `to_string()` to obtain it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// `blob:none`: Omit all blobs.
+    BlobNone,
+    /// `blob:limit=<n>`: Omit all blobs that are at least the given amount of bytes in size.
+    BlobLimit(u64),
+    /// `tree:<depth>`: Omit all trees and blobs whose depth from the root tree is at least `depth`, with `0` omitting all
+    /// trees and blobs.
+    TreeDepth(u64),
+    /// `sparse:oid=<blob-ish>`: Omit all blobs not matching the sparse-checkout specification in the given blob,
+    /// which is looked up on the remote.
+    SparseOid(BString),
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::BlobNone => f.write_str("blob:none"),
+            Filter::BlobLimit(limit) => write!(f, "blob:limit={limit}"),
+            Filter::TreeDepth(depth) => write!(f, "tree:{depth}"),
+            Filter::SparseOid(spec) => write!(f, "sparse:oid={spec}"),
+        }
+    }
+}
+
 /// A representation of a complete fetch response
 #[derive(Debug, Clone)]
 pub struct Response {
@@ -30,6 +30,8 @@ pub enum Command {
     LsRefs,
     /// Fetch a pack.
     Fetch,
+    /// Obtain a list of bundles to download before fetching.
+    BundleUri,
 }
 pub mod command;
 
@@ -45,6 +47,10 @@ pub use gix_credentials as credentials;
 pub use gix_transport as transport;
 pub use maybe_async;
 
+pub mod bundle_uri;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use bundle_uri::function::bundle_uri;
+
 ///
 pub mod fetch;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
@@ -0,0 +1,90 @@
+mod list {
+    use bstr::ByteSlice;
+    use gix_protocol::bundle_uri::{parse, Bundle, Heuristic, List, Mode};
+
+    fn from_lines(lines: &str) -> Result<List, parse::Error> {
+        List::from_lines(lines.lines().map(|line| line.as_bytes().as_bstr()))
+    }
+
+    #[test]
+    fn from_lines_with_all_keys() -> crate::Result {
+        let list = from_lines(
+            "bundle.version=1
+bundle.mode=all
+bundle.heuristic=creationToken
+bundle.base.uri=https://example.com/base.bundle
+bundle.base.creationToken=1
+bundle.Section.With.Dots.uri=incremental.bundle
+bundle.Section.With.Dots.CREATIONTOKEN=2
+bundle.Section.With.Dots.filter=blob:none
+bundle.base.location=ignored
+other.key=ignored",
+        )?;
+        assert_eq!(
+            list,
+            List {
+                version: 1,
+                mode: Mode::All,
+                heuristic: Some(Heuristic::CreationToken),
+                bundles: vec![
+                    Bundle {
+                        id: "base".into(),
+                        uri: "https://example.com/base.bundle".into(),
+                        filter: None,
+                        creation_token: Some(1),
+                    },
+                    Bundle {
+                        id: "Section.With.Dots".into(),
+                        uri: "incremental.bundle".into(),
+                        filter: Some("blob:none".into()),
+                        creation_token: Some(2),
+                    }
+                ]
+            },
+            "keys are case-insensitive, but identifiers aren't and may contain dots"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_heuristics_are_ignored() -> crate::Result {
+        let list = from_lines("bundle.version=1\nbundle.mode=any\nbundle.heuristic=unknown\n")?;
+        assert_eq!(list.mode, Mode::Any);
+        assert_eq!(list.heuristic, None);
+        assert!(list.bundles.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        for (input, expected) in [
+            (
+                "bundle.version",
+                "Bundle list line \"bundle.version\" isn't of the form 'key=value'",
+            ),
+            ("bundle.version=2", "Bundle list version \"2\" is unsupported"),
+            (
+                "bundle.version=1\nbundle.mode=some",
+                "Bundle list mode \"some\" is neither 'all' nor 'any'",
+            ),
+            (
+                "bundle.mode=all",
+                "The bundle list lacks the required key 'bundle.version'",
+            ),
+            (
+                "bundle.version=1",
+                "The bundle list lacks the required key 'bundle.mode'",
+            ),
+            (
+                "bundle.version=1\nbundle.mode=all\nbundle.a.creationToken=-1",
+                "The creation token \"-1\" of bundle \"a\" isn't an unsigned integer",
+            ),
+            (
+                "bundle.version=1\nbundle.mode=all\nbundle.a.creationToken=1",
+                "The bundle \"a\" lacks the required key 'bundle.a.uri'",
+            ),
+        ] {
+            assert_eq!(from_lines(input).unwrap_err().to_string(), expected, "{input}");
+        }
+    }
+}
@@ -128,6 +128,7 @@ mod fetch_fn {
 
         Response::check_required_features(protocol_version, &fetch_features)?;
         let sideband_all = fetch_features.iter().any(|(n, _)| *n == "sideband-all");
+        let no_done = fetch_features.iter().any(|(n, _)| *n == "no-done");
         fetch_features.push(("agent", Some(Cow::Owned(agent))));
         let mut arguments = Arguments::new(protocol_version, fetch_features, trace);
         let mut previous_response = None::<Response>;
@@ -144,8 +145,10 @@ mod fetch_fn {
             let response = Response::from_line_reader(
                 protocol_version,
                 &mut reader,
-                true,  /* hack, telling us we don't want this delegate approach anymore */
-                false, /* just as much of a hack which causes us to expect a pack immediately */
+                gix_protocol::fetch::response::V1Round {
+                    done: action == Action::Cancel,
+                    no_done,
+                },
             )
             .await?;
             previous_response = if response.has_pack() {
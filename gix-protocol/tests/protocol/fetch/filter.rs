@@ -0,0 +1,65 @@
+use gix_protocol::fetch::Filter;
+
+#[test]
+fn parse_and_display_roundtrip() -> crate::Result {
+    for (spec, expected, display) in [
+        ("blob:none", Filter::BlobNone, "blob:none"),
+        ("blob:limit=0", Filter::BlobLimit(0), "blob:limit=0"),
+        ("blob:limit=42", Filter::BlobLimit(42), "blob:limit=42"),
+        ("blob:limit=1k", Filter::BlobLimit(1024), "blob:limit=1024"),
+        (
+            "blob:limit=2M",
+            Filter::BlobLimit(2 * 1024 * 1024),
+            "blob:limit=2097152",
+        ),
+        (
+            "blob:limit=1g",
+            Filter::BlobLimit(1024 * 1024 * 1024),
+            "blob:limit=1073741824",
+        ),
+        ("tree:0", Filter::TreeDepth(0), "tree:0"),
+        ("tree:3", Filter::TreeDepth(3), "tree:3"),
+        (
+            "sparse:oid=main:.sparse",
+            Filter::SparseOid("main:.sparse".into()),
+            "sparse:oid=main:.sparse",
+        ),
+    ] {
+        let filter: Filter = spec.parse()?;
+        assert_eq!(filter, expected, "{spec}");
+        assert_eq!(filter.to_string(), display);
+        assert_eq!(
+            filter.to_string().parse::<Filter>()?,
+            filter,
+            "display output can be parsed"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid() {
+    for spec in [
+        "",
+        "blob",
+        "blob:all",
+        "tree",
+        "sparse:oid=",
+        "sparse:path=foo",
+        "combine:blob:none+tree:0",
+    ] {
+        let err = spec.parse::<Filter>().unwrap_err();
+        assert!(err.to_string().contains("is not supported"), "{spec}: {err}");
+    }
+    for spec in [
+        "blob:limit=",
+        "blob:limit=1x",
+        "blob:limit=-1",
+        "tree:1k",
+        "tree:",
+        "blob:limit=99999999999g",
+    ] {
+        let err = spec.parse::<Filter>().unwrap_err();
+        assert!(err.to_string().contains("as number"), "{spec}: {err}");
+    }
+}
@@ -39,6 +39,7 @@ mod error {
 pub use error::Error;
 
 mod arguments;
+mod filter;
 
 #[cfg(feature = "blocking-client")]
 type Cursor = std::io::Cursor<Vec<u8>>;
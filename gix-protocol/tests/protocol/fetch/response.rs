@@ -19,17 +19,24 @@ mod v1 {
         use futures_lite::io::AsyncReadExt;
         use gix_protocol::fetch::{
             self,
-            response::{Acknowledgement, ShallowUpdate},
+            response::{Acknowledgement, ShallowUpdate, V1Round},
         };
-        use gix_transport::Protocol;
+        use gix_transport::{client::ReadlineBufRead, Protocol};
 
         use crate::fetch::response::{id, mock_reader};
 
+        fn done() -> V1Round {
+            V1Round {
+                done: true,
+                no_done: false,
+            }
+        }
+
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn clone() -> crate::Result {
             let mut provider = mock_reader("v1/clone-only.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, false).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, done()).await?;
             assert_eq!(r.acknowledgements(), &[Acknowledgement::Nak]);
             assert!(r.has_pack());
             let mut buf = Vec::new();
@@ -42,7 +49,7 @@ mod v1 {
         async fn shallow_clone() -> crate::Result {
             let mut provider = mock_reader("v1/clone-deepen-1.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, false).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, done()).await?;
             assert_eq!(
                 r.shallow_updates(),
                 &[ShallowUpdate::Shallow(id("808e50d724f604f69ab93c6da2919c014667bedb"))]
@@ -59,7 +66,7 @@ mod v1 {
         async fn empty_shallow_clone_due_to_depth_being_too_high() -> crate::Result {
             let mut provider = mock_reader("v1/clone-deepen-5.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, false).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, done()).await?;
             assert!(r.shallow_updates().is_empty());
             assert_eq!(r.acknowledgements(), &[Acknowledgement::Nak]);
             assert!(r.has_pack());
@@ -73,7 +80,7 @@ mod v1 {
         async fn unshallow_fetch() -> crate::Result {
             let mut provider = mock_reader("v1/fetch-unshallow.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, done()).await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -103,9 +110,12 @@ mod v1 {
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn fetch_acks_without_pack() -> crate::Result {
             let mut provider = mock_reader("v1/fetch-no-pack.response");
-            let r =
-                fetch::Response::from_line_reader(Protocol::V1, &mut provider.as_read_without_sidebands(), true, true)
-                    .await?;
+            let r = fetch::Response::from_line_reader(
+                Protocol::V1,
+                &mut provider.as_read_without_sidebands(),
+                V1Round::default(),
+            )
+            .await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -121,7 +131,15 @@ mod v1 {
         async fn fetch_acks_and_pack() -> crate::Result {
             let mut provider = mock_reader("v1/fetch.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(
+                Protocol::V1,
+                &mut reader,
+                V1Round {
+                    done: false,
+                    no_done: true,
+                },
+            )
+            .await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -138,6 +156,26 @@ mod v1 {
             assert_eq!(bytes_read, 9703, "should be able to read the whole pack");
             Ok(())
         }
+
+        #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+        async fn fetch_acks_with_ready_stop_at_nak_without_no_done() -> crate::Result {
+            let mut provider = mock_reader("v1/fetch.response");
+            let mut reader = provider.as_read_without_sidebands();
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, V1Round::default()).await?;
+            assert_eq!(
+                r.acknowledgements().last(),
+                Some(&Acknowledgement::Nak),
+                "the NAK ends the round as the server waits for `done`, so reading on could block forever"
+            );
+            assert!(!r.has_pack());
+            let mut line = String::new();
+            reader.readline_str(&mut line).await?;
+            assert_eq!(
+                line, "ACK f22743895a3024bb0c958335981439f1fa747d57\n",
+                "nothing was read past the NAK"
+            );
+            Ok(())
+        }
     }
 
     mod arguments {
@@ -224,7 +262,7 @@ mod v2 {
                 );
                 let mut provider = mock_reader(&fixture);
                 let mut reader = provider.as_read_without_sidebands();
-                let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+                let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
                 assert!(r.acknowledgements().is_empty(), "it should go straight to the packfile");
                 assert!(r.has_pack());
                 reader.set_progress_handler(Some(Box::new(|_is_err, _text| {
@@ -241,7 +279,7 @@ mod v2 {
         async fn shallow_clone() -> crate::Result {
             let mut provider = mock_reader("v2/clone-deepen-1.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
             assert!(r.acknowledgements().is_empty(), "it should go straight to the packfile");
             assert_eq!(
                 r.shallow_updates(),
@@ -258,7 +296,7 @@ mod v2 {
         async fn unshallow_fetch() -> crate::Result {
             let mut provider = mock_reader("v2/fetch-unshallow.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -290,7 +328,7 @@ mod v2 {
         async fn empty_shallow_clone() -> crate::Result {
             let mut provider = mock_reader("v2/clone-deepen-5.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
             assert!(r.acknowledgements().is_empty(), "it should go straight to the packfile");
             assert!(r.shallow_updates().is_empty(), "it should go straight to the packfile");
             assert!(r.has_pack());
@@ -304,7 +342,7 @@ mod v2 {
         async fn clone_with_sidebands() -> crate::Result {
             let mut provider = mock_reader("v2/clone-only-2.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
             assert!(r.acknowledgements().is_empty(), "it should go straight to the packfile");
             assert!(r.has_pack());
 
@@ -327,9 +365,12 @@ mod v2 {
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn fetch_acks_without_pack() -> crate::Result {
             let mut provider = mock_reader("v2/fetch-no-pack.response");
-            let r =
-                fetch::Response::from_line_reader(Protocol::V2, &mut provider.as_read_without_sidebands(), true, true)
-                    .await?;
+            let r = fetch::Response::from_line_reader(
+                Protocol::V2,
+                &mut provider.as_read_without_sidebands(),
+                Default::default(),
+            )
+            .await?;
             assert_eq!(r.acknowledgements(), &[Acknowledgement::Nak]);
             Ok(())
         }
@@ -339,7 +380,7 @@ mod v2 {
             let mut provider = mock_reader("v2/fetch-err-line.response");
             provider.fail_on_err_lines(true);
             let mut sidebands = provider.as_read_without_sidebands();
-            match fetch::Response::from_line_reader(Protocol::V2, &mut sidebands, true, true).await {
+            match fetch::Response::from_line_reader(Protocol::V2, &mut sidebands, Default::default()).await {
                 Ok(_) => panic!("need error response"),
                 Err(err) => match err {
                     fetch::response::Error::UploadPack(err) => {
@@ -354,7 +395,7 @@ mod v2 {
         async fn fetch_acks_and_pack() -> crate::Result {
             let mut provider = mock_reader("v2/fetch.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V2, &mut reader, Default::default()).await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -5,6 +5,7 @@ pub fn fixture_bytes(path: &str) -> Vec<u8> {
         .expect("fixture to be present and readable")
 }
 
+mod bundle_uri;
 mod command;
 pub mod fetch;
 mod handshake;
@@ -265,8 +265,9 @@ impl file::Store {
         )
     }
 
-    /// Implements the logic required to transform a fully qualified refname into a filesystem path
-    pub(crate) fn reference_path(&self, name: &FullNameRef) -> PathBuf {
+    /// Implements the logic required to transform a fully qualified refname into a filesystem path, returning the path
+    /// at which the loose reference `name` is or would be stored.
+    pub fn reference_path(&self, name: &FullNameRef) -> PathBuf {
         let (base, relative_path) = self.reference_path_with_base(name);
         base.join(relative_path)
     }
@@ -51,9 +51,7 @@ pub mod write {
         ///
         /// If this leaves the list of shallow commits empty, the file is removed.
         ///
-        /// ### Deviation
-        ///
-        /// Git also prunes the set of shallow commits while writing, we don't until we support some sort of pruning.
+        /// To prune shallow commits that are not present anymore, pass only the ones that still exist as `shallow_commits`.
         pub fn write(
             mut file: gix_lock::File,
             shallow_commits: Option<Vec<gix_hash::ObjectId>>,
@@ -82,6 +80,7 @@ pub mod write {
                 }
             } else {
                 shallow_commits.sort();
+                shallow_commits.dedup();
                 let mut buf = Vec::<u8>::new();
                 for commit in shallow_commits {
                     commit.write_hex_to(&mut buf).map_err(Error::Io)?;
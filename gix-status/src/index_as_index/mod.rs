@@ -0,0 +1,350 @@
+//! Changes between two indices along with optional rename tracking.
+mod types;
+pub use types::{Context, Entry, Error, Options, Outcome, Sorting, Summary, VisitEntry};
+
+mod recorder;
+pub use recorder::Recorder;
+
+pub(super) mod function {
+    use std::cmp::Ordering;
+    use std::ops::Range;
+
+    use bstr::BStr;
+    use gix_diff::rewrites::tracker::visit::SourceKind;
+
+    use super::rewrite::TrackedChange;
+    use crate::index_as_index::{Context, Entry, Error, Options, Outcome, VisitEntry};
+    use crate::is_dir_to_mode;
+
+    /// Compare the `lhs` index with the `rhs` index and pass all changes that would turn `lhs` into `rhs` to `collector`,
+    /// while optionally tracking renames and copies.
+    ///
+    /// This is useful to learn what an operation like a checkout or a merge changed in the index, without having to
+    /// turn each index into a tree first.
+    ///
+    /// * `lhs`
+    ///     - the index before the change, typically the one that was previously written to disk.
+    /// * `rhs`
+    ///     - the index after the change.
+    /// * `collector`
+    ///     - A [`VisitEntry`] implementation that sees the results of this operation.
+    /// * `objects`
+    ///     - A way to obtain objects from the git object database, for use in attribute lookups and similarity checks.
+    /// * `ctx`
+    ///    -  Additional information that will be accessed when filtering entries and tracking rewrites.
+    /// * `options`
+    ///    - a way to configure the operation.
+    ///
+    /// Entries that are marked as [intent-to-add](gix_index::entry::Flags::INTENT_TO_ADD) are treated as if they didn't exist,
+    /// while unmerged entries in any of the indices are only reported as [conflicts](Entry::Conflict) if their stages differ.
+    /// Note that sparse indices aren't supported, they must be "unsparsed" before.
+    pub fn index_as_index<'index, Find>(
+        lhs: &'index gix_index::State,
+        rhs: &'index gix_index::State,
+        collector: &mut impl VisitEntry<'index>,
+        objects: &Find,
+        mut ctx: Context,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        Find: gix_object::FindObjectOrHeader,
+    {
+        let _span = gix_features::trace::coarse!("gix_status::index_as_index()");
+        if lhs.is_sparse() || rhs.is_sparse() {
+            return Err(Error::IsSparse);
+        }
+
+        let lhs_paths = paths_matching_pathspec(lhs, &mut ctx, objects);
+        let rhs_paths = paths_matching_pathspec(rhs, &mut ctx, objects);
+        let mut out = Outcome {
+            lhs_paths_processed: lhs_paths.len(),
+            rhs_paths_processed: rhs_paths.len(),
+            rewrites: None,
+        };
+
+        let mut tracker = options.rewrites.map(gix_diff::rewrites::Tracker::<TrackedChange>::new);
+        let mut entries_for_sorting = options.sorting.map(|_| Vec::new());
+        let mut emit =
+            |entry: Entry<'index>, entries_for_sorting: &mut Option<Vec<Entry<'index>>>| match entries_for_sorting
+                .as_mut()
+            {
+                Some(v) => v.push(entry),
+                None => collector.visit_entry(entry),
+            };
+
+        let (mut lhs_iter, mut rhs_iter) = (lhs_paths.into_iter().peekable(), rhs_paths.into_iter().peekable());
+        loop {
+            let order = match (lhs_iter.peek(), rhs_iter.peek()) {
+                (Some(lhs_range), Some(rhs_range)) => path_at(lhs, lhs_range).cmp(path_at(rhs, rhs_range)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let (lhs_range, rhs_range) = match order {
+                Ordering::Less => (lhs_iter.next(), None),
+                Ordering::Equal => (lhs_iter.next(), rhs_iter.next()),
+                Ordering::Greater => (None, rhs_iter.next()),
+            };
+            let lhs_entries = lhs_range.clone().map_or(&[][..], |range| &lhs.entries()[range]);
+            let rhs_entries = rhs_range.clone().map_or(&[][..], |range| &rhs.entries()[range]);
+            let rela_path = match (&lhs_range, &rhs_range) {
+                (_, Some(range)) => path_at(rhs, range),
+                (Some(range), None) => path_at(lhs, range),
+                (None, None) => unreachable!("at least one side is always present"),
+            };
+
+            if is_unmerged(lhs_entries) || is_unmerged(rhs_entries) {
+                let stages_differ = lhs_entries.len() != rhs_entries.len()
+                    || lhs_entries
+                        .iter()
+                        .zip(rhs_entries)
+                        .any(|(a, b)| a.stage_raw() != b.stage_raw() || a.mode != b.mode || a.id != b.id);
+                if stages_differ {
+                    emit(
+                        Entry::Conflict {
+                            previous_entries: lhs_entries,
+                            entries: rhs_entries,
+                            rela_path,
+                        },
+                        &mut entries_for_sorting,
+                    );
+                }
+                continue;
+            }
+
+            let lhs_index = lhs_range
+                .map(|range| range.start)
+                .filter(|idx| exists(&lhs.entries()[*idx]));
+            let rhs_index = rhs_range
+                .map(|range| range.start)
+                .filter(|idx| exists(&rhs.entries()[*idx]));
+            let change = match (lhs_index, rhs_index) {
+                (Some(lhs_index), Some(rhs_index)) => {
+                    let (previous_entry, entry) = (&lhs.entries()[lhs_index], &rhs.entries()[rhs_index]);
+                    if previous_entry.id == entry.id && previous_entry.mode == entry.mode {
+                        continue;
+                    }
+                    TrackedChange::modification(lhs_index, rhs_index, entry)
+                }
+                (Some(lhs_index), None) => TrackedChange::deletion(lhs_index, &lhs.entries()[lhs_index]),
+                (None, Some(rhs_index)) => TrackedChange::addition(rhs_index, &rhs.entries()[rhs_index]),
+                (None, None) => continue,
+            };
+            let change = match tracker.as_mut() {
+                Some(tracker) => tracker.try_push_change(change, rela_path),
+                None => Some(change),
+            };
+            if let Some(change) = change {
+                emit(change.to_entry(lhs, rhs), &mut entries_for_sorting);
+            }
+        }
+
+        if let Some(mut tracker) = tracker {
+            out.rewrites = Some(tracker.emit(
+                |dest, src| {
+                    let entry = match src {
+                        None => dest.change.to_entry(lhs, rhs),
+                        Some(src) => {
+                            let source_entry_index = src.change.lhs_index.expect("sources are always in lhs");
+                            let entry_index = dest.change.rhs_index.expect("destinations are always in rhs");
+                            let source_entry = &lhs.entries()[source_entry_index];
+                            let entry = &rhs.entries()[entry_index];
+                            Entry::Rewrite {
+                                source_entry,
+                                source_entry_index,
+                                source_rela_path: source_entry.path(lhs),
+                                source_id: src.id,
+                                entry,
+                                entry_index,
+                                rela_path: entry.path(rhs),
+                                diff: src.diff,
+                                copy: src.kind == SourceKind::Copy,
+                            }
+                        }
+                    };
+                    emit(entry, &mut entries_for_sorting);
+                    gix_diff::tree::visit::Action::Continue
+                },
+                &mut ctx.resource_cache,
+                objects,
+                |push| {
+                    for (index, entry) in lhs.entries().iter().enumerate() {
+                        if entry.stage_raw() == 0 && exists(entry) {
+                            push(TrackedChange::copy_source(index, entry), entry.path(lhs));
+                        }
+                    }
+                    Ok::<_, std::convert::Infallible>(())
+                },
+            )?);
+        }
+
+        if let Some(mut v) = entries_for_sorting {
+            v.sort_by(|a, b| a.destination_rela_path().cmp(b.destination_rela_path()));
+            for entry in v {
+                collector.visit_entry(entry);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return the ranges of all entries with the same path, one range per path, if they match the pathspec in `ctx`.
+    fn paths_matching_pathspec(
+        state: &gix_index::State,
+        ctx: &mut Context,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Vec<Range<usize>> {
+        let range = state
+            .prefixed_entries_range(ctx.pathspec.common_prefix())
+            .unwrap_or_else(|| 0..state.entries().len());
+        let entries = state.entries();
+        let mut out = Vec::new();
+        let mut start = range.start;
+        while start < range.end {
+            let entry = &entries[start];
+            let path = entry.path(state);
+            let end = start
+                + entries[start..range.end]
+                    .iter()
+                    .take_while(|e| e.path(state) == path)
+                    .count();
+            let stack = &mut ctx.stack;
+            let is_included = ctx
+                .pathspec
+                .pattern_matching_relative_path(
+                    path,
+                    Some(entry.mode.is_submodule()),
+                    &mut |rela_path, case, is_dir, out| {
+                        stack
+                            .set_case(case)
+                            .at_entry(rela_path, Some(is_dir_to_mode(is_dir)), objects)
+                            .is_ok_and(|platform| platform.matching_attributes(out))
+                    },
+                )
+                .is_some_and(|m| !m.is_excluded());
+            if is_included {
+                out.push(start..end);
+            }
+            start = end;
+        }
+        out
+    }
+
+    fn path_at<'a>(state: &'a gix_index::State, range: &Range<usize>) -> &'a BStr {
+        state.entries()[range.start].path(state)
+    }
+
+    fn is_unmerged(entries: &[gix_index::Entry]) -> bool {
+        entries.iter().any(|e| e.stage_raw() != 0)
+    }
+
+    fn exists(entry: &gix_index::Entry) -> bool {
+        !entry.flags.contains(gix_index::entry::Flags::INTENT_TO_ADD)
+    }
+}
+
+mod rewrite {
+    use gix_diff::rewrites::tracker::ChangeKind;
+    use gix_diff::tree::visit::Relation;
+    use gix_hash::oid;
+    use gix_object::tree::EntryMode;
+
+    use crate::index_as_index::Entry;
+
+    /// A change as seen by the rewrite tracker, referring to entries in `lhs` and `rhs` by index.
+    #[derive(Clone)]
+    pub struct TrackedChange {
+        kind: ChangeKind,
+        pub lhs_index: Option<usize>,
+        pub rhs_index: Option<usize>,
+        id: gix_hash::ObjectId,
+        mode: EntryMode,
+    }
+
+    impl TrackedChange {
+        pub fn addition(rhs_index: usize, entry: &gix_index::Entry) -> Self {
+            Self::new(ChangeKind::Addition, None, Some(rhs_index), entry)
+        }
+
+        pub fn deletion(lhs_index: usize, entry: &gix_index::Entry) -> Self {
+            Self::new(ChangeKind::Deletion, Some(lhs_index), None, entry)
+        }
+
+        /// Note that `entry` is the current state of the entry, i.e. as seen in `rhs`.
+        pub fn modification(lhs_index: usize, rhs_index: usize, entry: &gix_index::Entry) -> Self {
+            Self::new(ChangeKind::Modification, Some(lhs_index), Some(rhs_index), entry)
+        }
+
+        /// A possible source for copies, which will never be emitted.
+        pub fn copy_source(lhs_index: usize, entry: &gix_index::Entry) -> Self {
+            Self::new(ChangeKind::Modification, Some(lhs_index), None, entry)
+        }
+
+        fn new(kind: ChangeKind, lhs_index: Option<usize>, rhs_index: Option<usize>, entry: &gix_index::Entry) -> Self {
+            TrackedChange {
+                kind,
+                lhs_index,
+                rhs_index,
+                id: entry.id,
+                mode: entry
+                    .mode
+                    .to_tree_entry_mode()
+                    // Default is for the impossible case - just don't let it participate in rename tracking.
+                    .unwrap_or(gix_object::tree::EntryKind::Tree.into()),
+            }
+        }
+
+        pub fn to_entry<'index>(&self, lhs: &'index gix_index::State, rhs: &'index gix_index::State) -> Entry<'index> {
+            match (self.lhs_index, self.rhs_index) {
+                (Some(previous_entry_index), Some(entry_index)) => {
+                    let entry = &rhs.entries()[entry_index];
+                    Entry::Modification {
+                        previous_entry: &lhs.entries()[previous_entry_index],
+                        previous_entry_index,
+                        entry,
+                        entry_index,
+                        rela_path: entry.path(rhs),
+                    }
+                }
+                (Some(entry_index), None) => {
+                    let entry = &lhs.entries()[entry_index];
+                    Entry::Deletion {
+                        entry,
+                        entry_index,
+                        rela_path: entry.path(lhs),
+                    }
+                }
+                (None, Some(entry_index)) => {
+                    let entry = &rhs.entries()[entry_index];
+                    Entry::Addition {
+                        entry,
+                        entry_index,
+                        rela_path: entry.path(rhs),
+                    }
+                }
+                (None, None) => unreachable!("BUG: changes always refer to at least one entry"),
+            }
+        }
+    }
+
+    impl gix_diff::rewrites::tracker::Change for TrackedChange {
+        fn id(&self) -> &oid {
+            &self.id
+        }
+
+        fn relation(&self) -> Option<Relation> {
+            None
+        }
+
+        fn kind(&self) -> ChangeKind {
+            self.kind
+        }
+
+        fn entry_mode(&self) -> EntryMode {
+            self.mode
+        }
+
+        fn id_and_entry_mode(&self) -> (&oid, EntryMode) {
+            (&self.id, self.mode)
+        }
+    }
+}
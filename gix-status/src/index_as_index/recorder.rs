@@ -0,0 +1,14 @@
+use crate::index_as_index::{Entry, VisitEntry};
+
+/// Convenience implementation of [`VisitEntry`] that collects all changes into a `Vec`.
+#[derive(Debug, Default)]
+pub struct Recorder<'index> {
+    /// The collected changes.
+    pub records: Vec<Entry<'index>>,
+}
+
+impl<'index> VisitEntry<'index> for Recorder<'index> {
+    fn visit_entry(&mut self, entry: Entry<'index>) {
+        self.records.push(entry);
+    }
+}
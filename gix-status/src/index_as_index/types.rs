@@ -0,0 +1,228 @@
+use bstr::BStr;
+
+/// The error returned by [`index_as_index()`](crate::index_as_index()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot compare indices that contain sparse entries")]
+    IsSparse,
+    #[error(transparent)]
+    RewriteTracker(#[from] gix_diff::rewrites::tracker::emit::Error),
+}
+
+/// The way all output should be sorted.
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Sorting {
+    /// The entries are sorted by their path in a case-sensitive fashion.
+    #[default]
+    ByPathCaseSensitive,
+}
+
+/// Provide additional information collected during the runtime of [`index_as_index()`](crate::index_as_index()).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// The amount of distinct paths in the left-hand index that were compared, which excludes those that were
+    /// filtered by pathspecs.
+    pub lhs_paths_processed: usize,
+    /// The amount of distinct paths in the right-hand index that were compared, which excludes those that were
+    /// filtered by pathspecs.
+    pub rhs_paths_processed: usize,
+    /// The result of the rewrite operation, if [rewrites were configured](Options::rewrites).
+    pub rewrites: Option<gix_diff::rewrites::Outcome>,
+}
+
+/// A change between two indices, `lhs` and `rhs`, which would have to be applied to `lhs` to obtain `rhs`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Entry<'index> {
+    /// An entry was added to `rhs`.
+    Addition {
+        /// The newly added entry in `rhs`.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` for lookup in the entries of `rhs`.
+        entry_index: usize,
+        /// The repository-relative path of the entry.
+        rela_path: &'index BStr,
+    },
+    /// An entry of `lhs` doesn't exist in `rhs` anymore.
+    Deletion {
+        /// The deleted entry in `lhs`.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` for lookup in the entries of `lhs`.
+        entry_index: usize,
+        /// The repository-relative path of the entry.
+        rela_path: &'index BStr,
+    },
+    /// An entry changed its content or its mode.
+    Modification {
+        /// The entry as it was in `lhs`.
+        previous_entry: &'index gix_index::Entry,
+        /// The index of `previous_entry` for lookup in the entries of `lhs`.
+        previous_entry_index: usize,
+        /// The entry as it is in `rhs`.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` for lookup in the entries of `rhs`.
+        entry_index: usize,
+        /// The repository-relative path of both entries.
+        rela_path: &'index BStr,
+    },
+    /// The rewrite tracking discovered a match between a deleted or modified entry in `lhs`, and an added entry in `rhs`,
+    /// and considers them equal enough, depending on the tracker settings.
+    Rewrite {
+        /// The source of the rewrite in `lhs`.
+        source_entry: &'index gix_index::Entry,
+        /// The index of `source_entry` for lookup in the entries of `lhs`.
+        source_entry_index: usize,
+        /// The repository-relative path of `source_entry`.
+        source_rela_path: &'index BStr,
+        /// The id of the source content that was compared to the destination.
+        ///
+        /// It's the same as the id of `source_entry` unless this is the copy of an entry which was also modified,
+        /// in which case it's the id of the modified entry in `rhs`.
+        source_id: gix_hash::ObjectId,
+        /// The destination of the rewrite in `rhs`.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` for lookup in the entries of `rhs`.
+        entry_index: usize,
+        /// The repository-relative path of `entry`.
+        rela_path: &'index BStr,
+        /// It's `None` if `source_id` is equal to the id of `entry`, as identity made an actual diff computation unnecessary.
+        /// Otherwise, and if enabled, it's `Some(stats)` to indicate how similar both entries were.
+        diff: Option<gix_diff::blob::DiffLineStats>,
+        /// If true, this rewrite is created by copy, and `source_entry` still exists in `rhs`.
+        /// Otherwise, it's a rename, and `source_entry` was removed.
+        copy: bool,
+    },
+    /// An entry is unmerged in at least one of the indices, and its stages differ between them.
+    ///
+    /// This can happen if a conflict was introduced, for instance by a merge, if it was resolved, or if
+    /// the stages of an existing conflict changed.
+    Conflict {
+        /// All entries at `rela_path` in `lhs`, which is empty if there was none, or a single entry if it was unconflicted.
+        previous_entries: &'index [gix_index::Entry],
+        /// All entries at `rela_path` in `rhs`, which is empty if there is none, or a single entry if it is unconflicted.
+        entries: &'index [gix_index::Entry],
+        /// The repository-relative path of all entries.
+        rela_path: &'index BStr,
+    },
+}
+
+/// An easy to grasp summary of the changes between two indices.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Summary {
+    /// An entry exists in `rhs` but not in `lhs`.
+    Added,
+    /// An entry exists in `lhs` but not in `rhs`.
+    Removed,
+    /// The content or executable bit of an entry changed.
+    Modified,
+    /// The type of an entry changed, for instance if a file was turned into a symlink.
+    TypeChange,
+    /// An entry in `lhs` was moved to a different location in `rhs`, possibly with modifications.
+    ///
+    /// To obtain this state, rewrite-tracking must have been enabled, as otherwise the source would be
+    /// considered `Removed` and the destination would be considered `Added`.
+    Renamed,
+    /// An entry in `lhs` was copied to a new location in `rhs`, possibly with modifications.
+    ///
+    /// To obtain this state, rewrite-and-copy-tracking must have been enabled.
+    Copied,
+    /// The conflict-state of an entry changed.
+    Conflict,
+}
+
+/// Access
+impl Entry<'_> {
+    /// Return a summary of the entry as digest of its change.
+    pub fn summary(&self) -> Summary {
+        match self {
+            Entry::Addition { .. } => Summary::Added,
+            Entry::Deletion { .. } => Summary::Removed,
+            Entry::Modification {
+                previous_entry, entry, ..
+            } => {
+                let (previous_kind, kind) = (
+                    previous_entry.mode.to_tree_entry_mode().map(|m| m.kind()),
+                    entry.mode.to_tree_entry_mode().map(|m| m.kind()),
+                );
+                use gix_object::tree::EntryKind::*;
+                match (previous_kind, kind) {
+                    (Some(Blob | BlobExecutable), Some(Blob | BlobExecutable)) => Summary::Modified,
+                    (a, b) if a == b => Summary::Modified,
+                    _ => Summary::TypeChange,
+                }
+            }
+            Entry::Rewrite { copy, .. } => {
+                if *copy {
+                    Summary::Copied
+                } else {
+                    Summary::Renamed
+                }
+            }
+            Entry::Conflict { .. } => Summary::Conflict,
+        }
+    }
+
+    /// The repository-relative path at which the source of a rewrite is located.
+    ///
+    /// If this isn't a rewrite, the path is the location of the entry itself.
+    pub fn source_rela_path(&self) -> &BStr {
+        match self {
+            Entry::Addition { rela_path, .. }
+            | Entry::Deletion { rela_path, .. }
+            | Entry::Modification { rela_path, .. }
+            | Entry::Conflict { rela_path, .. } => rela_path,
+            Entry::Rewrite { source_rela_path, .. } => source_rela_path,
+        }
+    }
+
+    /// The repository-relative path at which the destination of a rewrite is located.
+    ///
+    /// If this isn't a rewrite, the path is the location of the entry itself.
+    pub fn destination_rela_path(&self) -> &BStr {
+        match self {
+            Entry::Addition { rela_path, .. }
+            | Entry::Deletion { rela_path, .. }
+            | Entry::Modification { rela_path, .. }
+            | Entry::Conflict { rela_path, .. }
+            | Entry::Rewrite { rela_path, .. } => rela_path,
+        }
+    }
+}
+
+/// Options for use in [`index_as_index()`](crate::index_as_index()).
+#[derive(Clone, Default)]
+pub struct Options {
+    /// The way all output should be sorted.
+    ///
+    /// If `None`, entries are passed to the caller as soon as they are known, which is in order of their path
+    /// unless `rewrites` are enabled. Entries that are candidates for rewrites are emitted only after all other changes
+    /// were seen.
+    ///
+    /// If `Some(_)`, all entries are collected beforehand, so they can be sorted before outputting any of them
+    /// to the user.
+    pub sorting: Option<Sorting>,
+    /// The configuration for the rewrite tracking.
+    ///
+    /// If `None`, no tracking will occur, which means that all output becomes visible to the delegate immediately.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// The context for [`index_as_index()`](crate::index_as_index()).
+pub struct Context {
+    /// The pathspec to limit the amount of paths that are compared. Can be empty to allow all paths.
+    ///
+    /// Note that these are expected to have a [common_prefix()](gix_pathspec::Search::common_prefix()) according
+    /// to the prefix of the repository to efficiently limit the scope of the paths we process.
+    pub pathspec: gix_pathspec::Search,
+    /// A stack pre-configured to allow accessing attributes for each entry, as possibly required by pathspecs.
+    pub stack: gix_worktree::Stack,
+    /// A fully-configured platform capable of producing diffable buffers similar to what Git would do, for use
+    /// with rewrite tracking.
+    pub resource_cache: gix_diff::blob::Platform,
+}
+
+/// Observe the changes between two indices.
+pub trait VisitEntry<'index> {
+    /// Observe a single change between two indices.
+    fn visit_entry(&mut self, entry: Entry<'index>);
+}
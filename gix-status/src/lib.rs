@@ -2,6 +2,7 @@
 //! of the repository state, like comparisons between…
 //!
 //! * index and working tree
+//! * index and index
 //! * *tree and index*
 //!
 //! …while also being able to check if the working tree is dirty, quickly, by instructing the operation to stop once the first
@@ -42,6 +43,11 @@ pub mod index_as_worktree_with_renames;
 #[cfg(feature = "worktree-rewrites")]
 pub use index_as_worktree_with_renames::function::index_as_worktree_with_renames;
 
+#[cfg(feature = "index-rewrites")]
+pub mod index_as_index;
+#[cfg(feature = "index-rewrites")]
+pub use index_as_index::function::index_as_index;
+
 /// A stack that validates we are not going through a symlink in a way that is read-only.
 ///
 /// It can efficiently validate paths when these are queried in sort-order, which leads to each component
@@ -0,0 +1,33 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+echo "content" > modified
+seq 10 > renamed-source
+seq 20 > similar-source
+seq 30 > copy-source
+echo "will be removed" > removed
+echo "base" > conflict
+mkdir dir
+echo "executable" > dir/executable
+
+git add -A
+git commit -q -m "base"
+cp .git/index .git/lhs.index
+
+echo "changed content" > modified
+git mv renamed-source renamed-dest
+git mv similar-source similar-dest
+echo 21 >> similar-dest
+git rm -q removed
+echo "added" > added
+cp copy-source copy-dest
+chmod +x dir/executable
+git add -A
+git update-index --chmod=+x dir/executable  # For Windows.
+
+ours=$(echo ours | git hash-object -w --stdin)
+theirs=$(echo theirs | git hash-object -w --stdin)
+base=$(git rev-parse :conflict)
+git rm -q --cached conflict
+printf "100644 %s 1\tconflict\n100644 %s 2\tconflict\n100644 %s 3\tconflict\n" "$base" "$ours" "$theirs" | git update-index --index-info
@@ -0,0 +1,216 @@
+use crate::fixture_path;
+use bstr::ByteSlice;
+use gix_diff::blob::pipeline::WorktreeRoots;
+use gix_status::index_as_index;
+use gix_status::index_as_index::{Context, Entry, Options, Outcome, Recorder, Sorting, Summary};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn changes_without_rewrites() {
+    let out = fixture_filtered(
+        &[],
+        None,
+        &[
+            (Summary::Added, "added", "added"),
+            (Summary::Conflict, "conflict", "conflict"),
+            (Summary::Added, "copy-dest", "copy-dest"),
+            (Summary::Modified, "dir/executable", "dir/executable"),
+            (Summary::Modified, "modified", "modified"),
+            (Summary::Removed, "removed", "removed"),
+            (Summary::Added, "renamed-dest", "renamed-dest"),
+            (Summary::Removed, "renamed-source", "renamed-source"),
+            (Summary::Added, "similar-dest", "similar-dest"),
+            (Summary::Removed, "similar-source", "similar-source"),
+        ],
+    );
+    assert_eq!(out.lhs_paths_processed, 7);
+    assert_eq!(out.rhs_paths_processed, 8);
+    assert_eq!(out.rewrites, None);
+}
+
+#[test]
+fn changes_with_rewrites() {
+    let rewrites = gix_diff::Rewrites::default();
+    let out = fixture_filtered(
+        &[],
+        Some(rewrites),
+        &[
+            (Summary::Added, "added", "added"),
+            (Summary::Conflict, "conflict", "conflict"),
+            (Summary::Added, "copy-dest", "copy-dest"),
+            (Summary::Modified, "dir/executable", "dir/executable"),
+            (Summary::Modified, "modified", "modified"),
+            (Summary::Removed, "removed", "removed"),
+            (Summary::Renamed, "renamed-source", "renamed-dest"),
+            (Summary::Renamed, "similar-source", "similar-dest"),
+        ],
+    );
+    assert_eq!(
+        out.rewrites,
+        Some(gix_diff::rewrites::Outcome {
+            options: rewrites,
+            num_similarity_checks: 3,
+            num_similarity_checks_skipped_for_rename_tracking_due_to_limit: 0,
+            num_similarity_checks_skipped_for_copy_tracking_due_to_limit: 0,
+        })
+    );
+}
+
+#[test]
+fn copies_from_unmodified_sources() {
+    fixture_filtered(
+        &["added", "copy-*", "renamed-*"],
+        Some(gix_diff::Rewrites {
+            copies: Some(gix_diff::rewrites::Copies {
+                source: gix_diff::rewrites::CopySource::FromSetOfModifiedFilesAndAllSources,
+                percentage: None,
+            }),
+            ..Default::default()
+        }),
+        &[
+            (Summary::Added, "added", "added"),
+            (Summary::Copied, "copy-source", "copy-dest"),
+            (Summary::Renamed, "renamed-source", "renamed-dest"),
+        ],
+    );
+}
+
+#[test]
+fn pathspecs_limit_the_compared_paths() {
+    let out = fixture_filtered(
+        &["dir", "conflict"],
+        Some(Default::default()),
+        &[
+            (Summary::Conflict, "conflict", "conflict"),
+            (Summary::Modified, "dir/executable", "dir/executable"),
+        ],
+    );
+    assert_eq!(out.lhs_paths_processed, 2);
+    assert_eq!(out.rhs_paths_processed, 2);
+}
+
+#[test]
+fn conflicts_provide_all_stages() {
+    let mut conflict = None;
+    let lhs = lhs_index();
+    let rhs = rhs_index();
+    run(&lhs, &rhs, &[], None, &mut |entry| {
+        if let Entry::Conflict {
+            previous_entries,
+            entries,
+            rela_path,
+        } = entry
+        {
+            assert_eq!(*rela_path, "conflict");
+            conflict = Some((previous_entries.len(), entries.len()));
+        }
+    });
+    assert_eq!(
+        conflict,
+        Some((1, 3)),
+        "the unconflicted entry turned into three stages"
+    );
+
+    let mut num_changes = 0;
+    run(&rhs, &rhs, &[], None, &mut |_| num_changes += 1);
+    assert_eq!(num_changes, 0, "identical conflicts are not a change");
+}
+
+fn lhs_index() -> gix_index::File {
+    let git_dir = fixture_path("index_as_index").join(".git");
+    gix_index::File::at(
+        git_dir.join("lhs.index"),
+        gix_hash::Kind::Sha1,
+        false,
+        Default::default(),
+    )
+    .unwrap()
+}
+
+fn rhs_index() -> gix_index::File {
+    let git_dir = fixture_path("index_as_index").join(".git");
+    gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default()).unwrap()
+}
+
+fn fixture_filtered(
+    pathspecs: &[&str],
+    rewrites: Option<gix_diff::Rewrites>,
+    expected: &[(Summary, &str, &str)],
+) -> Outcome {
+    let (lhs, rhs) = (lhs_index(), rhs_index());
+    let mut actual = Vec::new();
+    let out = run(&lhs, &rhs, pathspecs, rewrites, &mut |entry| {
+        actual.push((
+            entry.summary(),
+            entry.source_rela_path().to_str().unwrap().to_owned(),
+            entry.destination_rela_path().to_str().unwrap().to_owned(),
+        ));
+    });
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(summary, source, dest)| (*summary, source.to_string(), dest.to_string()))
+        .collect();
+    assert_eq!(actual, expected);
+    out
+}
+
+fn run<'index>(
+    lhs: &'index gix_index::State,
+    rhs: &'index gix_index::State,
+    pathspecs: &[&str],
+    rewrites: Option<gix_diff::Rewrites>,
+    cb: &mut dyn FnMut(&Entry<'index>),
+) -> Outcome {
+    let worktree = fixture_path("index_as_index");
+    let git_dir = worktree.join(".git");
+    let search = gix_pathspec::Search::from_specs(
+        crate::index_as_worktree::to_pathspecs(pathspecs),
+        None,
+        std::path::Path::new(""),
+    )
+    .expect("valid specs can be normalized");
+    let stack = gix_worktree::Stack::from_state_and_ignore_case(
+        worktree.clone(),
+        false,
+        gix_worktree::stack::State::AttributesStack(Default::default()),
+        rhs,
+        rhs.path_backing(),
+    );
+    let resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            WorktreeRoots::default(),
+            gix_filter::Pipeline::new(Default::default(), Default::default()),
+            vec![],
+            gix_diff::blob::pipeline::Options {
+                large_file_threshold_bytes: 0,
+                fs: gix_fs::Capabilities::probe(&git_dir),
+            },
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        stack.clone(),
+    );
+    let objects = gix_odb::at(git_dir.join("objects")).unwrap();
+
+    let mut recorder = Recorder::default();
+    let out = index_as_index(
+        lhs,
+        rhs,
+        &mut recorder,
+        &objects,
+        Context {
+            pathspec: search,
+            stack,
+            resource_cache,
+        },
+        Options {
+            sorting: Some(Sorting::ByPathCaseSensitive),
+            rewrites,
+        },
+    )
+    .unwrap();
+    for entry in &recorder.records {
+        cb(entry);
+    }
+    out
+}
@@ -0,0 +1,76 @@
+//! Support for 'dumb' HTTP servers which only serve the files of a repository.
+use bstr::{BString, ByteSlice};
+
+use super::{append_url, GetResponse, Http, Transport};
+use crate::client;
+
+/// Produce the packetlines of a V1 advertisement from the `info/refs` file of a repository, as produced by
+/// `git update-server-info`, along with the contents of its `HEAD` file, if present, to advertise the symbolic ref.
+pub(super) fn advertisement(info_refs: &[u8], head: Option<&[u8]>) -> Result<Vec<u8>, client::Error> {
+    let mut refs = Vec::new();
+    for line in info_refs.lines().filter(|line| !line.is_empty()) {
+        let (id, name) = line.split_once_str("\t").ok_or_else(|| {
+            client::Error::Http(super::Error::Detail {
+                description: format!(
+                    "Could not parse line {:?} in 'info/refs' of 'dumb' server",
+                    line.as_bstr()
+                ),
+            })
+        })?;
+        refs.push((id, name));
+    }
+    let object_format = match refs.first().map_or(40, |(id, _)| id.len()) {
+        64 => "sha256",
+        _ => "sha1",
+    };
+    let mut capabilities = BString::from(format!("object-format={object_format}"));
+
+    let head = head.map(ByteSlice::trim_end);
+    let head_id = match head.and_then(|head| head.strip_prefix(b"ref: ")) {
+        Some(target) => refs.iter().find(|(_, name)| *name == target).map(|(id, _)| {
+            capabilities.extend_from_slice(b" symref=HEAD:");
+            capabilities.extend_from_slice(target);
+            *id
+        }),
+        None => head.filter(|head| !head.is_empty()),
+    };
+
+    let null = "0".repeat(if object_format == "sha256" { 64 } else { 40 });
+    let mut lines: Vec<_> = head_id.map(|id| (id, b"HEAD".as_slice())).into_iter().collect();
+    lines.extend(refs);
+    if lines.is_empty() {
+        lines.push((null.as_bytes(), b"capabilities^{}"));
+    }
+
+    let mut out = Vec::new();
+    for (idx, (id, name)) in lines.into_iter().enumerate() {
+        let mut line = BString::from(id);
+        line.push(b' ');
+        line.extend_from_slice(name);
+        if idx == 0 {
+            line.push(0);
+            line.extend_from_slice(&capabilities);
+        }
+        gix_packetline::encode::text_to_write(&line, &mut out)?;
+    }
+    gix_packetline::encode::flush_to_write(&mut out)?;
+    Ok(out)
+}
+
+impl<H: Http> client::StaticFiles for Transport<H> {
+    fn open_file(&mut self, path: &str) -> Result<Option<Box<dyn std::io::BufRead + '_>>, client::Error> {
+        let url = append_url(&self.url, path);
+        let static_headers = [std::borrow::Cow::Borrowed(self.user_agent_header)];
+        let mut dynamic_headers = Vec::new();
+        self.add_basic_auth_if_present(&mut dynamic_headers)?;
+        let GetResponse { headers, body } =
+            self.http
+                .get(&url, &self.url, static_headers.iter().chain(&dynamic_headers))?;
+        match std::io::BufRead::lines(headers).collect::<Result<Vec<_>, _>>() {
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        Ok(Some(Box::new(body)))
+    }
+}
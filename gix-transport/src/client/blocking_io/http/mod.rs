@@ -36,6 +36,7 @@ pub mod curl;
 #[cfg(feature = "http-client-reqwest")]
 pub mod reqwest;
 
+mod dumb;
 mod traits;
 
 ///
@@ -228,6 +229,9 @@ pub struct Transport<H: Http> {
     http: H,
     service: Option<Service>,
     line_provider: Option<gix_packetline::StreamingPeekableIter<H::ResponseBody>>,
+    /// The advertisement we produced ourselves from the files of a 'dumb' server, which is also used to indicate that
+    /// the server is 'dumb'.
+    static_advertisement: Option<gix_packetline::StreamingPeekableIter<std::io::Cursor<Vec<u8>>>>,
     identity: Option<gix_sec::identity::Account>,
     trace: bool,
 }
@@ -252,6 +256,7 @@ impl<H: Http> Transport<H> {
             service: None,
             http,
             line_provider: None,
+            static_advertisement: None,
             identity,
             trace,
         }
@@ -277,23 +282,36 @@ impl Transport<Impl> {
 }
 
 impl<H: Http> Transport<H> {
-    fn check_content_type(service: Service, kind: &str, headers: <H as Http>::Headers) -> Result<(), client::Error> {
-        let wanted_content_type = format!("application/x-{}-{}", service.as_str(), kind);
-        if !headers.lines().collect::<Result<Vec<_>, _>>()?.iter().any(|l| {
+    fn has_content_type(service: Service, kind: &str, headers: <H as Http>::Headers) -> Result<bool, client::Error> {
+        let wanted_content_type = Self::content_type(service, kind);
+        Ok(headers.lines().collect::<Result<Vec<_>, _>>()?.iter().any(|l| {
             let mut tokens = l.split(':');
             tokens.next().zip(tokens.next()).is_some_and(|(name, value)| {
                 name.eq_ignore_ascii_case("content-type") && value.trim() == wanted_content_type
             })
-        }) {
-            return Err(client::Error::Http(Error::Detail {
-                description: format!(
-                    "Didn't find '{wanted_content_type}' header to indicate 'smart' protocol, and 'dumb' protocol is not supported."
-                ),
-            }));
+        }))
+    }
+
+    fn check_content_type(service: Service, kind: &str, headers: <H as Http>::Headers) -> Result<(), client::Error> {
+        if !Self::has_content_type(service, kind, headers)? {
+            return Err(Self::missing_content_type(service, kind, "is not supported"));
         }
         Ok(())
     }
 
+    fn content_type(service: Service, kind: &str) -> String {
+        format!("application/x-{}-{}", service.as_str(), kind)
+    }
+
+    fn missing_content_type(service: Service, kind: &str, dumb_protocol: &str) -> client::Error {
+        client::Error::Http(Error::Detail {
+            description: format!(
+                "Didn't find '{}' header to indicate 'smart' protocol, and 'dumb' protocol {dumb_protocol}.",
+                Self::content_type(service, kind)
+            ),
+        })
+    }
+
     #[allow(clippy::unnecessary_wraps, unknown_lints)]
     fn add_basic_auth_if_present(&self, headers: &mut Vec<Cow<'_, str>>) -> Result<(), client::Error> {
         if let Some(gix_sec::identity::Account { username, password }) = &self.identity {
@@ -334,6 +352,11 @@ impl<H: Http> client::TransportWithoutIO for Transport<H> {
         trace: bool,
     ) -> Result<RequestWriter<'_>, client::Error> {
         let service = self.service.ok_or(client::Error::MissingHandshake)?;
+        if self.static_advertisement.is_some() {
+            return Err(client::Error::Http(Error::Detail {
+                description: "The 'dumb' server only serves files and can't respond to requests".into(),
+            }));
+        }
         let url = append_url(&self.url, service.as_str());
         let static_headers = &[
             Cow::Borrowed(self.user_agent_header),
@@ -422,10 +445,20 @@ impl<H: Http> client::Transport for Transport<H> {
             dynamic_headers.push(format!("Git-Protocol: {parameters}").into());
         }
         self.add_basic_auth_if_present(&mut dynamic_headers)?;
-        let GetResponse { headers, body } =
+        let GetResponse { headers, mut body } =
             self.http
                 .get(url.as_ref(), &self.url, static_headers.iter().chain(&dynamic_headers))?;
-        <Transport<H>>::check_content_type(service, "advertisement", headers)?;
+        self.static_advertisement = None;
+        if !<Transport<H>>::has_content_type(service, "advertisement", headers)? {
+            if service != Service::UploadPack {
+                return Err(Self::missing_content_type(
+                    service,
+                    "advertisement",
+                    "is only supported for fetching",
+                ));
+            }
+            return self.handshake_dumb(service, &mut body);
+        }
 
         let line_reader = self.line_provider.get_or_insert_with(|| {
             gix_packetline::StreamingPeekableIter::new(body, &[PacketLineRef::Flush], self.trace)
@@ -466,6 +499,49 @@ impl<H: Http> client::Transport for Transport<H> {
             refs,
         })
     }
+
+    fn static_files(&mut self) -> Option<&mut dyn client::StaticFiles> {
+        if self.static_advertisement.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<H: Http> Transport<H> {
+    /// Produce the handshake response for a 'dumb' server which sent the `info/refs` file as `body`.
+    fn handshake_dumb(
+        &mut self,
+        service: Service,
+        body: &mut H::ResponseBody,
+    ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+        let mut info_refs = Vec::new();
+        body.read_to_end(&mut info_refs)?;
+        let mut head = Vec::new();
+        let has_head = client::StaticFiles::read_file(self, "HEAD", &mut head)?;
+        let advertisement = dumb::advertisement(&info_refs, has_head.then_some(head.as_slice()))?;
+
+        self.actual_version = Protocol::V1;
+        self.service = Some(service);
+        let line_reader = self
+            .static_advertisement
+            .insert(gix_packetline::StreamingPeekableIter::new(
+                std::io::Cursor::new(advertisement),
+                &[PacketLineRef::Flush],
+                self.trace,
+            ));
+        let capabilities::recv::Outcome {
+            capabilities,
+            refs,
+            protocol: actual_protocol,
+        } = Capabilities::from_lines_with_version_detection(line_reader)?;
+        Ok(client::SetServiceResponse {
+            actual_protocol,
+            capabilities,
+            refs,
+        })
+    }
 }
 
 struct HeadersThenBody<H: Http, B: Unpin> {
@@ -145,6 +145,8 @@ impl Default for Remote {
                             Some(status) => {
                                 let kind = if status == reqwest::StatusCode::UNAUTHORIZED {
                                     std::io::ErrorKind::PermissionDenied
+                                } else if status == reqwest::StatusCode::NOT_FOUND {
+                                    std::io::ErrorKind::NotFound
                                 } else if status.is_server_error() {
                                     std::io::ErrorKind::ConnectionAborted
                                 } else {
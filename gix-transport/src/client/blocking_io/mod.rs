@@ -13,8 +13,11 @@ pub use bufread_ext::{ExtendedBufRead, HandleProgress, ReadlineBufRead};
 mod request;
 pub use request::RequestWriter;
 
+///
+pub mod remote_helper;
+
 ///
 pub mod ssh;
 
 mod traits;
-pub use traits::{SetServiceResponse, Transport, TransportV2Ext};
+pub use traits::{SetServiceResponse, StaticFiles, Transport, TransportV2Ext};
@@ -0,0 +1,361 @@
+use std::{
+    ffi::OsString,
+    io::{BufRead, Read, Write},
+    process::{ChildStdin, ChildStdout, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use super::{fetch, list, option, push, Capability, Error, Helper, Options};
+use crate::{Protocol, Service};
+
+/// Lifecycle
+impl Helper {
+    /// Spawn `git-remote-<scheme>` for `url` and obtain its capabilities, configured by `options`.
+    pub fn spawn(url: &gix_url::Url, options: Options) -> Result<Self, Error> {
+        let program = OsString::from(format!("git-remote-{}", url.scheme.as_str()));
+        let url = url.to_bstring();
+        let remote = options.remote_name.unwrap_or_else(|| url.clone());
+        let mut cmd = std::process::Command::from(
+            gix_command::prepare(program.clone())
+                .arg(remote.to_os_str_lossy().into_owned())
+                .arg(url.to_os_str_lossy().into_owned())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped()),
+        );
+        if options.version != Protocol::V1 {
+            cmd.env("GIT_PROTOCOL", format!("version={}", options.version as usize));
+        }
+        if let Some(git_dir) = options.git_dir {
+            cmd.env("GIT_DIR", git_dir);
+        }
+
+        gix_features::trace::debug!(command = ?cmd, "gix_transport::remote_helper::Helper");
+        let mut child = cmd.spawn().map_err(|err| Error::InvokeProgram {
+            source: err,
+            command: program.clone(),
+        })?;
+        let mut helper = Helper {
+            program,
+            capabilities: Vec::new(),
+            stdin: child.stdin.take(),
+            stdout: child.stdout.take().map(std::io::BufReader::new),
+            child,
+        };
+        helper.send_line("capabilities")?;
+        helper.capabilities = helper
+            .read_lines_until_blank()?
+            .into_iter()
+            .map(|line| {
+                let (mandatory, line) = match line.strip_prefix(b"*") {
+                    Some(line) => (true, line),
+                    None => (false, line.as_slice()),
+                };
+                let (name, value) = match line.split_once_str(" ") {
+                    Some((name, value)) => (name, Some(value.into())),
+                    None => (line, None),
+                };
+                Capability {
+                    name: name.into(),
+                    value,
+                    mandatory,
+                }
+            })
+            .collect();
+        Ok(helper)
+    }
+
+    /// Return all capabilities the helper advertised.
+    pub fn capabilities(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// Return `true` if the helper advertised the capability with the given `name`.
+    pub fn has_capability(&self, name: &str) -> bool {
+        self.capabilities.iter().any(|cap| cap.name == name)
+    }
+}
+
+/// Commands
+impl Helper {
+    /// Set the option `name` to `value`, like `depth` to `1`.
+    pub fn option(&mut self, name: &str, value: &BStr) -> Result<option::Response, Error> {
+        self.require("option")?;
+        self.send_line(&format!("option {name} {value}"))?;
+        let line = self.read_line()?;
+        Ok(if line == "ok" {
+            option::Response::Ok
+        } else if line == "unsupported" {
+            option::Response::Unsupported
+        } else if let Some(reason) = line.strip_prefix(b"error") {
+            option::Response::Error(reason.trim_start().into())
+        } else {
+            return Err(Error::ParseLine {
+                command: "option",
+                line,
+            });
+        })
+    }
+
+    /// List all references of the remote, indicating that the listing is used to prepare a push if `for_push` is `true`.
+    pub fn list(&mut self, for_push: bool) -> Result<list::Outcome, Error> {
+        self.send_line(if for_push { "list for-push" } else { "list" })?;
+        let mut out = list::Outcome::default();
+        for line in self.read_lines_until_blank()? {
+            if let Some(keyword) = line.strip_prefix(b":") {
+                out.keywords.push(keyword.into());
+                continue;
+            }
+            let mut tokens = line.split_str(" ");
+            let (Some(value), Some(name)) = (tokens.next(), tokens.next()) else {
+                return Err(Error::ParseLine { command: "list", line });
+            };
+            let value = if value == b"?" {
+                list::Value::Unknown
+            } else if let Some(target) = value.strip_prefix(b"@") {
+                list::Value::Symbolic(target.into())
+            } else {
+                list::Value::Id(gix_hash::ObjectId::from_hex(value).map_err(|_| Error::ParseLine {
+                    command: "list",
+                    line: line.clone(),
+                })?)
+            };
+            out.refs.push(list::Ref {
+                name: name.into(),
+                value,
+                attributes: tokens.map(Into::into).collect(),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Fetch the objects reachable from `refs`, pairs of the object id and the name of the reference as previously [listed](Self::list()).
+    ///
+    /// The helper writes the objects into the repository it was given with [`Options::git_dir`].
+    pub fn fetch<'a>(
+        &mut self,
+        refs: impl IntoIterator<Item = (&'a gix_hash::oid, &'a BStr)>,
+    ) -> Result<fetch::Outcome, Error> {
+        self.require("fetch")?;
+        if !self.send_batch(refs.into_iter().map(|(id, name)| format!("fetch {id} {name}")))? {
+            return Ok(Default::default());
+        }
+        let mut out = fetch::Outcome::default();
+        for line in self.read_lines_until_blank()? {
+            if line == "connectivity-ok" {
+                out.connectivity_ok = true;
+            } else if let Some(path) = line.strip_prefix(b"lock ") {
+                out.lock_files.push(path.to_path_lossy().into_owned());
+            } else {
+                return Err(Error::ParseLine { command: "fetch", line });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Import the references named `refs` by writing the `git fast-import` stream produced by the helper into `out`,
+    /// up to and including the terminating `done` command.
+    pub fn import<'a>(&mut self, refs: impl IntoIterator<Item = &'a BStr>, out: &mut dyn Write) -> Result<(), Error> {
+        self.require("import")?;
+        if !self.send_batch(refs.into_iter().map(|name| format!("import {name}")))? {
+            return Ok(());
+        }
+        let command = self.program.clone();
+        let stdout = self.stdout();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if stdout.read_until(b'\n', &mut line)? == 0 {
+                return Err(Error::UnexpectedEof { command });
+            }
+            out.write_all(&line)?;
+            let text = line.strip_suffix(b"\n").unwrap_or(&line);
+            if text == b"done" {
+                break;
+            }
+            let Some(count) = text.strip_prefix(b"data ") else {
+                continue;
+            };
+            if let Some(delimiter) = count.strip_prefix(b"<<") {
+                let delimiter = delimiter.to_owned();
+                loop {
+                    line.clear();
+                    if stdout.read_until(b'\n', &mut line)? == 0 {
+                        return Err(Error::UnexpectedEof { command });
+                    }
+                    out.write_all(&line)?;
+                    if line.strip_suffix(b"\n").unwrap_or(&line) == delimiter {
+                        break;
+                    }
+                }
+            } else {
+                let count: u64 = count
+                    .to_str()
+                    .ok()
+                    .and_then(|count| count.parse().ok())
+                    .ok_or_else(|| Error::ParseLine {
+                        command: "import",
+                        line: line.as_slice().into(),
+                    })?;
+                if std::io::copy(&mut stdout.take(count), out)? != count {
+                    return Err(Error::UnexpectedEof { command });
+                }
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Push using the given `refspecs` of the form `[+]<src>:<dst>`, returning the status of each remote reference.
+    pub fn push<'a>(&mut self, refspecs: impl IntoIterator<Item = &'a BStr>) -> Result<Vec<push::Status>, Error> {
+        self.require("push")?;
+        if !self.send_batch(refspecs.into_iter().map(|spec| format!("push {spec}")))? {
+            return Ok(Vec::new());
+        }
+        self.read_lines_until_blank()?
+            .into_iter()
+            .map(|line| {
+                if let Some(ref_name) = line.strip_prefix(b"ok ") {
+                    Ok(push::Status::Ok {
+                        ref_name: ref_name.into(),
+                    })
+                } else if let Some(rest) = line.strip_prefix(b"error ") {
+                    let (ref_name, reason) = match rest.split_once_str(" ") {
+                        Some((name, reason)) => (name, Some(reason.into())),
+                        None => (rest, None),
+                    };
+                    Ok(push::Status::Error {
+                        ref_name: ref_name.into(),
+                        reason,
+                    })
+                } else {
+                    Err(Error::ParseLine { command: "push", line })
+                }
+            })
+            .collect()
+    }
+
+    /// Ask the helper to connect to `service` and return the reader and writer to speak the git protocol with it.
+    ///
+    /// Afterward, this instance only keeps the helper process alive and no other command can be sent.
+    pub fn connect(&mut self, service: Service) -> Result<(std::io::BufReader<ChildStdout>, ChildStdin), Error> {
+        self.require("connect")?;
+        self.tunnel("connect", service)
+    }
+
+    /// Ask the helper to connect to `service` in stateless mode and return the reader and writer to speak protocol V2 with it.
+    ///
+    /// Each response is followed by a `response-end` packet line. Afterward, this instance only keeps the helper process alive
+    /// and no other command can be sent.
+    pub fn stateless_connect(
+        &mut self,
+        service: Service,
+    ) -> Result<(std::io::BufReader<ChildStdout>, ChildStdin), Error> {
+        self.require("stateless-connect")?;
+        self.tunnel("stateless-connect", service)
+    }
+}
+
+/// Utilities
+impl Helper {
+    fn require(&self, capability: &'static str) -> Result<(), Error> {
+        if self.has_capability(capability) {
+            Ok(())
+        } else {
+            Err(Error::MissingCapability { capability })
+        }
+    }
+
+    fn tunnel(
+        &mut self,
+        command: &'static str,
+        service: Service,
+    ) -> Result<(std::io::BufReader<ChildStdout>, ChildStdin), Error> {
+        self.send_line(&format!("{command} {}", service.as_str()))?;
+        let line = self.read_line()?;
+        if line == "fallback" {
+            return Err(Error::Fallback {
+                service: service.as_str(),
+            });
+        } else if !line.is_empty() {
+            return Err(Error::ParseLine { command, line });
+        }
+        Ok((
+            self.stdout.take().expect("not yet turned into a tunnel"),
+            self.stdin.take().expect("not yet turned into a tunnel"),
+        ))
+    }
+
+    fn stdout(&mut self) -> &mut std::io::BufReader<ChildStdout> {
+        self.stdout
+            .as_mut()
+            .expect("commands can't be sent after turning the helper into a tunnel")
+    }
+
+    fn stdin(&mut self) -> &mut ChildStdin {
+        self.stdin
+            .as_mut()
+            .expect("commands can't be sent after turning the helper into a tunnel")
+    }
+
+    fn send_line(&mut self, line: &str) -> Result<(), Error> {
+        let stdin = self.stdin();
+        writeln!(stdin, "{line}")?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    /// Write all `lines` followed by a blank line to terminate the batch, unless there are no lines as a blank line
+    /// on its own would end the session. Return `true` if a batch was sent.
+    fn send_batch(&mut self, lines: impl IntoIterator<Item = String>) -> Result<bool, Error> {
+        let stdin = self.stdin();
+        let mut is_empty = true;
+        for line in lines {
+            writeln!(stdin, "{line}")?;
+            is_empty = false;
+        }
+        if !is_empty {
+            writeln!(stdin)?;
+        }
+        stdin.flush()?;
+        Ok(!is_empty)
+    }
+
+    fn read_line(&mut self) -> Result<BString, Error> {
+        let command = self.program.clone();
+        let mut line = Vec::new();
+        if self.stdout().read_until(b'\n', &mut line)? == 0 {
+            return Err(Error::UnexpectedEof { command });
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(line.into())
+    }
+
+    fn read_lines_until_blank(&mut self) -> Result<Vec<BString>, Error> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+}
+
+impl Drop for Helper {
+    fn drop(&mut self) {
+        match self.stdin.take() {
+            // Closing stdin ends the session, and the helper is given the chance to finish its work like `git` would.
+            Some(stdin) => drop(stdin),
+            // The tunnel may still be in use, and we don't want to wait for it to be closed.
+            None => {
+                self.child.kill().ok();
+            }
+        }
+        self.child.wait().ok();
+    }
+}
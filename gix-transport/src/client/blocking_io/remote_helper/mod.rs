@@ -0,0 +1,190 @@
+//! Support for [remote helpers](https://git-scm.com/docs/gitremote-helpers), programs named `git-remote-<transport>`
+//! which are spawned to talk to remotes whose URL scheme isn't natively supported.
+//!
+//! The [`Helper`][crate::client::remote_helper::Helper] type speaks the line-based helper protocol directly and provides access to all of its commands,
+//! while [`SpawnHelperOnDemand`][crate::client::remote_helper::SpawnHelperOnDemand] is a [`Transport`][crate::client::Transport] for helpers which can tunnel
+//! the native git protocol using the `connect` or `stateless-connect` capabilities.
+use std::path::PathBuf;
+
+use bstr::BString;
+
+use crate::Protocol;
+
+mod helper;
+
+mod transport;
+pub use transport::SpawnHelperOnDemand;
+
+/// A remote helper process along with the capabilities it advertised.
+///
+/// Dropping it ends the session and waits for the helper process to exit.
+pub struct Helper {
+    program: std::ffi::OsString,
+    capabilities: Vec<Capability>,
+    /// `None` only after the helper was turned into a tunnel with `connect` or `stateless-connect`.
+    stdin: Option<std::process::ChildStdin>,
+    /// `None` only after the helper was turned into a tunnel with `connect` or `stateless-connect`.
+    stdout: Option<std::io::BufReader<std::process::ChildStdout>>,
+    child: std::process::Child,
+}
+
+/// Options for use in [`Helper::spawn()`].
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// The name of the remote to pass as first argument to the helper, or the URL itself if `None`,
+    /// which is what `git` does for anonymous remotes.
+    pub remote_name: Option<BString>,
+    /// The git directory to pass as `GIT_DIR` to the helper, which is needed by helpers that read or write
+    /// objects themselves, as is the case with the `fetch`, `import` and `push` commands.
+    pub git_dir: Option<PathBuf>,
+    /// The protocol version to ask for via `GIT_PROTOCOL` when tunneling the git protocol.
+    pub version: Protocol,
+}
+
+/// A capability as advertised by a remote helper in response to the `capabilities` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    /// The name of the capability, like `fetch` or `refspec`.
+    pub name: BString,
+    /// The value following the name, like `refs/heads/*:refs/svn/origin/branches/*` for `refspec`.
+    pub value: Option<BString>,
+    /// If `true`, the capability was prefixed with `*` to indicate that it must be understood by the caller.
+    pub mandatory: bool,
+}
+
+///
+pub mod option {
+    use bstr::BString;
+
+    /// The response to the `option` command.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Response {
+        /// The option was set.
+        Ok,
+        /// The helper doesn't know the option.
+        Unsupported,
+        /// The option is known, but its value was rejected for the given reason.
+        Error(BString),
+    }
+}
+
+///
+pub mod list {
+    use bstr::BString;
+
+    /// The value of a reference as listed by the `list` command.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Value {
+        /// The reference points to the given object.
+        Id(gix_hash::ObjectId),
+        /// The reference is symbolic and points to the given reference name.
+        Symbolic(BString),
+        /// The helper doesn't know the value of the reference.
+        Unknown,
+    }
+
+    /// A reference as listed by the `list` command.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Ref {
+        /// The full name of the reference.
+        pub name: BString,
+        /// The value of the reference.
+        pub value: Value,
+        /// Attributes like `unchanged` that followed the reference name.
+        pub attributes: Vec<BString>,
+    }
+
+    /// The result of the `list` command.
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    pub struct Outcome {
+        /// All listed references, in order.
+        pub refs: Vec<Ref>,
+        /// Keywords like `object-format sha256`, without their leading `:`.
+        pub keywords: Vec<BString>,
+    }
+}
+
+///
+pub mod fetch {
+    use std::path::PathBuf;
+
+    /// The result of the `fetch` command.
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    pub struct Outcome {
+        /// The paths to `.keep` files the helper created for the received packs, which are to be deleted once
+        /// references were updated.
+        pub lock_files: Vec<PathBuf>,
+        /// If `true`, the helper made sure that the received objects are connected and no connectivity check is needed.
+        pub connectivity_ok: bool,
+    }
+}
+
+///
+pub mod push {
+    use bstr::BString;
+
+    /// The status of one reference after the `push` command.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Status {
+        /// The remote reference with the given name was updated.
+        Ok {
+            /// The name of the updated remote reference.
+            ref_name: BString,
+        },
+        /// The remote reference with the given name could not be updated.
+        Error {
+            /// The name of the remote reference that wasn't updated.
+            ref_name: BString,
+            /// The reason for the failure, if the helper provided one.
+            reason: Option<BString>,
+        },
+    }
+}
+
+///
+pub mod spawn {
+    /// The error returned by [`Helper::spawn()`][super::Helper::spawn()] and all other methods of the [`Helper`][super::Helper].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Failed to invoke remote helper {command:?}")]
+        InvokeProgram {
+            source: std::io::Error,
+            command: std::ffi::OsString,
+        },
+        #[error("An IO error occurred when communicating with the remote helper")]
+        Io(#[from] std::io::Error),
+        #[error("The remote helper {command:?} exited unexpectedly")]
+        UnexpectedEof { command: std::ffi::OsString },
+        #[error("The remote helper doesn't support the '{capability}' capability")]
+        MissingCapability { capability: &'static str },
+        #[error("The remote helper for '{scheme}' supports neither 'connect' nor 'stateless-connect', and transferring objects with its own commands like 'fetch' or 'import' isn't supported")]
+        MissingTunnel { scheme: String },
+        #[error("Could not parse {line:?} in response to the '{command}' command")]
+        ParseLine { command: &'static str, line: bstr::BString },
+        #[error("The remote helper can't connect to '{service}' and asked to fall back to its own implementation")]
+        Fallback { service: &'static str },
+    }
+
+    impl crate::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+pub use spawn::Error;
+
+/// Connect to `url` by spawning `git-remote-<scheme>`, asking for `desired_version` if the helper tunnels the git protocol.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+///
+/// The helper is only spawned when performing the handshake.
+pub fn connect(
+    url: gix_url::Url,
+    desired_version: Protocol,
+    trace: bool,
+) -> Result<SpawnHelperOnDemand, std::convert::Infallible> {
+    Ok(SpawnHelperOnDemand::new(url, desired_version, trace))
+}
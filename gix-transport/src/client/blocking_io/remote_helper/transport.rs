@@ -0,0 +1,175 @@
+use std::{any::Any, borrow::Cow, error::Error, io::Read, process::ChildStdin};
+
+use bstr::BStr;
+
+use super::Helper;
+use crate::{
+    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    Protocol, Service,
+};
+
+/// A transport which spawns a [remote helper](Helper) to tunnel the git protocol through it.
+///
+/// Helpers with the `connect` capability are preferred, while helpers which are only `stateless-connect` capable
+/// are limited to fetching with protocol V2. Helpers with neither capability can't be used as the objects would have
+/// to be transferred with their own commands, like `fetch` or `import`, which isn't supported.
+/// It can only be instantiated using [`connect()`](super::connect()).
+pub struct SpawnHelperOnDemand {
+    desired_version: Protocol,
+    url: gix_url::Url,
+    /// Declared before the helper so its end of the tunnel is closed first when dropped.
+    connection: Option<git::Connection<Box<dyn Read + Send>, ChildStdin>>,
+    helper: Option<Helper>,
+    stateless: bool,
+    trace: bool,
+}
+
+impl SpawnHelperOnDemand {
+    pub(crate) fn new(url: gix_url::Url, version: Protocol, trace: bool) -> Self {
+        SpawnHelperOnDemand {
+            desired_version: version,
+            url,
+            connection: None,
+            helper: None,
+            stateless: false,
+            trace,
+        }
+    }
+}
+
+impl client::TransportWithoutIO for SpawnHelperOnDemand {
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection
+            .as_mut()
+            .ok_or(client::Error::MissingHandshake)?
+            .request(write_mode, on_into_read, trace)
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Owned(self.url.to_bstring())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        !self.stateless
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl client::Transport for SpawnHelperOnDemand {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        self.connection = None;
+        self.helper = None;
+
+        let mut helper = Helper::spawn(
+            &self.url,
+            super::Options {
+                version: self.desired_version,
+                ..Default::default()
+            },
+        )
+        .map_err(client::Error::RemoteHelper)?;
+        if !helper.has_capability("connect") && !helper.has_capability("stateless-connect") {
+            return Err(client::Error::RemoteHelper(super::Error::MissingTunnel {
+                scheme: self.url.scheme.as_str().into(),
+            }));
+        }
+        let stateless = service == Service::UploadPack
+            && !helper.has_capability("connect")
+            && helper.has_capability("stateless-connect");
+        let (read, write, version): (Box<dyn Read + Send>, _, _) = if stateless {
+            let (read, write) = helper.stateless_connect(service).map_err(client::Error::RemoteHelper)?;
+            (Box::new(SkipResponseEnd::new(read)), write, Protocol::V2)
+        } else {
+            let (read, write) = helper.connect(service).map_err(client::Error::RemoteHelper)?;
+            (Box::new(read), write, self.desired_version)
+        };
+        self.stateless = stateless;
+        self.helper = Some(helper);
+        self.connection = Some(
+            git::Connection::new_for_spawned_process(read, write, version, self.url.path.clone(), self.trace)
+                .custom_url(Some(self.url.to_bstring())),
+        );
+        self.connection
+            .as_mut()
+            .expect("connection to be there right after setting it")
+            .handshake(service, extra_parameters)
+    }
+}
+
+/// A reader of packet lines which drops `response-end` packets, as sent by `stateless-connect` helpers after each response,
+/// which are not expected when reading a response from a persistent connection.
+struct SkipResponseEnd<R> {
+    inner: R,
+    header: [u8; 4],
+    /// The amount of bytes of `header` that were already returned.
+    header_pos: usize,
+    /// The amount of bytes of the current packet's data that still have to be returned.
+    remaining: usize,
+}
+
+impl<R> SkipResponseEnd<R> {
+    fn new(inner: R) -> Self {
+        SkipResponseEnd {
+            inner,
+            header: [0; 4],
+            header_pos: 4,
+            remaining: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for SkipResponseEnd<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if self.header_pos < self.header.len() {
+                let n = buf.len().min(self.header.len() - self.header_pos);
+                buf[..n].copy_from_slice(&self.header[self.header_pos..][..n]);
+                self.header_pos += n;
+                return Ok(n);
+            }
+            if self.remaining > 0 {
+                let max = buf.len().min(self.remaining);
+                let n = self.inner.read(&mut buf[..max])?;
+                if n == 0 {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+                self.remaining -= n;
+                return Ok(n);
+            }
+
+            if self.inner.read(&mut self.header[..1])? == 0 {
+                return Ok(0);
+            }
+            self.inner.read_exact(&mut self.header[1..])?;
+            let len = std::str::from_utf8(&self.header)
+                .ok()
+                .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid packet line header: {:?}", BStr::new(&self.header)),
+                    )
+                })?;
+            if len == 2 {
+                continue;
+            }
+            self.header_pos = 0;
+            self.remaining = len.saturating_sub(4);
+        }
+    }
+}
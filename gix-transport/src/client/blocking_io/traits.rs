@@ -1,4 +1,7 @@
-use std::{io::Write, ops::DerefMut};
+use std::{
+    io::{BufRead, Write},
+    ops::DerefMut,
+};
 
 use bstr::BString;
 
@@ -39,6 +42,34 @@ pub trait Transport: TransportWithoutIO {
         service: Service,
         extra_parameters: &'a [(&'a str, Option<&'a str>)],
     ) -> Result<SetServiceResponse<'_>, Error>;
+
+    /// Return access to the files of the remote repository if the [handshake](Self::handshake()) revealed that it's
+    /// served by a 'dumb' server, which only serves static files and can't run any service, or `None` otherwise.
+    ///
+    /// In that case, no [requests](TransportWithoutIO::request()) can be made and objects have to be obtained by walking
+    /// the history and downloading them, or the packs that contain them.
+    fn static_files(&mut self) -> Option<&mut dyn StaticFiles> {
+        None
+    }
+}
+
+/// Access to the files of a repository as served by a 'dumb' server.
+pub trait StaticFiles {
+    /// Return a reader to stream the file at `path`, relative to the repository like `objects/pack/pack-<hash>.pack`,
+    /// or return `None` if it doesn't exist.
+    fn open_file(&mut self, path: &str) -> Result<Option<Box<dyn BufRead + '_>>, Error>;
+
+    /// Write the file at `path`, relative to the repository like `objects/info/packs`, into `out` and return `true`,
+    /// or return `false` if it doesn't exist.
+    fn read_file(&mut self, path: &str, out: &mut dyn Write) -> Result<bool, Error> {
+        match self.open_file(path)? {
+            Some(mut file) => {
+                std::io::copy(&mut file, out)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 // Would be nice if the box implementation could auto-forward to all implemented traits.
@@ -50,6 +81,10 @@ impl<T: Transport + ?Sized> Transport for Box<T> {
     ) -> Result<SetServiceResponse<'_>, Error> {
         self.deref_mut().handshake(service, extra_parameters)
     }
+
+    fn static_files(&mut self) -> Option<&mut dyn StaticFiles> {
+        self.deref_mut().static_files()
+    }
 }
 
 impl<T: Transport + ?Sized> Transport for &mut T {
@@ -60,6 +95,10 @@ impl<T: Transport + ?Sized> Transport for &mut T {
     ) -> Result<SetServiceResponse<'_>, Error> {
         self.deref_mut().handshake(service, extra_parameters)
     }
+
+    fn static_files(&mut self) -> Option<&mut dyn StaticFiles> {
+        self.deref_mut().static_files()
+    }
 }
 
 /// An extension trait to add more methods to everything implementing [`Transport`].
@@ -1,2 +1,4 @@
 #[cfg(any(feature = "http-client-curl", feature = "http-client-reqwest"))]
 mod http;
+#[cfg(unix)]
+mod remote_helper;
@@ -0,0 +1,279 @@
+use std::{io::BufRead, sync::Once};
+
+use bstr::{BString, ByteSlice};
+use gix_transport::{
+    client::{
+        self,
+        remote_helper::{self, list, option, push, Helper},
+        Transport, TransportV2Ext, TransportWithoutIO,
+    },
+    Protocol, Service,
+};
+
+/// Make `git-remote-test` available to be spawned, which behaves according to the last path component of the URL.
+fn helper_on_path() {
+    static PATH: Once = Once::new();
+    PATH.call_once(|| {
+        let dir = std::env::current_dir()
+            .expect("cwd")
+            .join("tests")
+            .join("fixtures")
+            .join("remote-helper");
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let path = std::env::join_paths(std::iter::once(dir).chain(std::env::split_paths(&path))).expect("valid paths");
+        std::env::set_var("PATH", path);
+    });
+}
+
+fn url(mode: &str) -> gix_url::Url {
+    gix_url::parse(format!("test://example.com/{mode}").as_str().into()).expect("valid url")
+}
+
+fn spawn(mode: &str) -> Result<Helper, remote_helper::Error> {
+    helper_on_path();
+    Helper::spawn(&url(mode), Default::default())
+}
+
+mod helper {
+    use super::*;
+
+    #[test]
+    fn capabilities() -> crate::Result {
+        let helper = spawn("commands")?;
+        assert!(helper.has_capability("fetch"));
+        assert!(!helper.has_capability("connect"));
+        assert_eq!(
+            helper.capabilities().last().expect("present"),
+            &remote_helper::Capability {
+                name: "refspec".into(),
+                value: Some("refs/heads/*:refs/test/*".into()),
+                mandatory: true,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn option() -> crate::Result {
+        let mut helper = spawn("commands")?;
+        assert_eq!(helper.option("verbosity", "1".into())?, option::Response::Ok);
+        assert_eq!(
+            helper.option("depth", "1".into())?,
+            option::Response::Error("depth is not supported".into())
+        );
+        assert_eq!(helper.option("unknown", "1".into())?, option::Response::Unsupported);
+        Ok(())
+    }
+
+    #[test]
+    fn list() -> crate::Result {
+        let mut helper = spawn("commands")?;
+        let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+        let out = helper.list(false)?;
+        assert_eq!(
+            out.refs,
+            [
+                list::Ref {
+                    name: "HEAD".into(),
+                    value: list::Value::Symbolic("refs/heads/main".into()),
+                    attributes: vec![],
+                },
+                list::Ref {
+                    name: "refs/heads/main".into(),
+                    value: list::Value::Id(id),
+                    attributes: vec![],
+                },
+                list::Ref {
+                    name: "refs/heads/unknown".into(),
+                    value: list::Value::Unknown,
+                    attributes: vec![],
+                }
+            ]
+        );
+        assert_eq!(out.keywords, [BString::from("object-format sha1")]);
+
+        let out = helper.list(true)?;
+        assert_eq!(
+            out.refs[1].attributes,
+            [BString::from("unchanged")],
+            "the same helper can be reused"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch() -> crate::Result {
+        let mut helper = spawn("commands")?;
+        let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+        let out = helper.fetch([(id.as_ref(), "refs/heads/main".into()), (id.as_ref(), "HEAD".into())])?;
+        assert!(out.connectivity_ok);
+        assert_eq!(out.lock_files, [std::path::PathBuf::from("objects/pack/pack-1.keep")]);
+
+        assert_eq!(
+            helper.fetch(None)?,
+            Default::default(),
+            "nothing is sent if there is nothing to fetch, as a blank line would end the session"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn import() -> crate::Result {
+        let mut helper = spawn("commands")?;
+        let mut out = Vec::new();
+        helper.import(["refs/heads/main".into()], &mut out)?;
+        assert_eq!(
+            out.as_bstr(),
+            "feature done\nblob\nmark :1\ndata 5\ndone\n\ncommit refs/heads/main\nmark :2\ncommitter a <a@example.com> 0 +0000\ndata <<EOF\ndone\nEOF\nM 644 :1 file\n\ndone\n",
+            "`done` in data isn't mistaken for the end of the stream"
+        );
+        assert!(helper.list(false).is_ok(), "the helper is still usable afterward");
+        Ok(())
+    }
+
+    #[test]
+    fn push() -> crate::Result {
+        let mut helper = spawn("commands")?;
+        let out = helper.push([
+            "refs/heads/main:refs/heads/main".into(),
+            "+HEAD:refs/heads/other".into(),
+        ])?;
+        assert_eq!(
+            out,
+            [
+                push::Status::Ok {
+                    ref_name: "refs/heads/main".into()
+                },
+                push::Status::Error {
+                    ref_name: "refs/heads/other".into(),
+                    reason: Some("non-fast-forward".into())
+                },
+                push::Status::Error {
+                    ref_name: "refs/heads/third".into(),
+                    reason: None
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_commands_are_rejected() -> crate::Result {
+        let mut helper = spawn("connect")?;
+        assert!(matches!(
+            helper.push(None),
+            Err(remote_helper::Error::MissingCapability { capability: "push" })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_helper() {
+        let url = gix_url::parse("does-not-exist-gix-test://host/path".into()).expect("valid url");
+        assert!(matches!(
+            Helper::spawn(&url, Default::default()),
+            Err(remote_helper::Error::InvokeProgram { command, .. }) if command == "git-remote-does-not-exist-gix-test"
+        ));
+    }
+}
+
+mod transport {
+    use super::*;
+
+    #[test]
+    fn connect() -> crate::Result {
+        helper_on_path();
+        let mut transport = client::connect(url("connect"), Default::default())?;
+        assert_eq!(transport.to_url().as_ref(), "test://example.com/connect");
+        assert!(transport.connection_persists_across_multiple_requests());
+
+        let mut res = transport.handshake(Service::UploadPack, &[])?;
+        assert_eq!(res.actual_protocol, Protocol::V1);
+        let refs = res
+            .refs
+            .as_mut()
+            .expect("V1 sends refs")
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            refs,
+            [
+                "808e50d724f604f69ab93c6da2919c014667bedb HEAD",
+                "808e50d724f604f69ab93c6da2919c014667bedb refs/heads/master"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stateless_connect() -> crate::Result {
+        helper_on_path();
+        let mut transport = remote_helper::connect(url("stateless-connect"), Protocol::V2, false)?;
+        let res = transport.handshake(Service::UploadPack, &[])?;
+        assert_eq!(res.actual_protocol, Protocol::V2);
+        assert!(res.capabilities.contains("ls-refs"));
+        drop(res);
+        assert!(
+            !transport.connection_persists_across_multiple_requests(),
+            "each request is handled separately"
+        );
+
+        for _ in 0..2 {
+            let lines = transport
+                .invoke(
+                    "ls-refs",
+                    None::<(&str, Option<&str>)>.into_iter(),
+                    None::<std::iter::Empty<BString>>,
+                    false,
+                )?
+                .lines()
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                lines,
+                ["808e50d724f604f69ab93c6da2919c014667bedb refs/heads/main"],
+                "response-end packets are skipped"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn helpers_without_tunnel_are_rejected_on_handshake() -> crate::Result {
+        helper_on_path();
+        for (mode, service) in [
+            ("commands", Service::UploadPack),
+            ("commands", Service::ReceivePack),
+            ("fetch-only", Service::UploadPack),
+        ] {
+            let mut transport = client::connect(url(mode), Default::default())?;
+            let Err(err) = transport.handshake(service, &[]) else {
+                panic!("{mode}: neither 'connect' nor 'stateless-connect' are supported");
+            };
+            assert!(
+                matches!(
+                    &err,
+                    client::Error::RemoteHelper(remote_helper::Error::MissingTunnel { scheme }) if scheme == "test"
+                ),
+                "{mode}: {err:?}"
+            );
+            assert!(
+                err.to_string().contains("neither 'connect' nor 'stateless-connect'"),
+                "the error explains what's missing"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stateless_connect_cannot_push() -> crate::Result {
+        helper_on_path();
+        let mut transport = client::connect(url("stateless-connect"), Default::default())?;
+        assert!(matches!(
+            transport.handshake(Service::ReceivePack, &[]),
+            Err(client::Error::RemoteHelper(remote_helper::Error::MissingCapability {
+                capability: "connect"
+            }))
+        ));
+        Ok(())
+    }
+}
@@ -63,3 +63,6 @@ pub(crate) type PathIdMapping = (BString, gix_hash::ObjectId);
 
 ///
 pub mod stack;
+
+///
+pub mod sparse;
@@ -0,0 +1,249 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_glob::search::pattern;
+use gix_ignore::search::Ignore;
+use gix_index::entry::Flags;
+
+/// Patterns as read from the `.git/info/sparse-checkout` file, which define which paths are supposed to be present
+/// in the worktree.
+#[derive(Debug, Clone)]
+pub enum Patterns {
+    /// _Cone mode_, where entire directories are included, along with all files of their parent directories
+    /// and of the root of the repository.
+    Cone(Cone),
+    /// _Non-cone mode_, where paths are included if they match patterns just like in `.gitignore` files,
+    /// and are excluded if they don't match or if they match a negated pattern.
+    NonCone(pattern::List<Ignore>),
+}
+
+/// The directories that are part of the sparse-checkout definition in [cone mode](Patterns::Cone).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// If `true`, all paths are included, as the patterns only consisted of `/*`.
+    pub all: bool,
+    /// Repository-relative directories without trailing slash whose content is included recursively.
+    pub recursive: HashSet<BString>,
+    /// Repository-relative directories without trailing slash that lead to a `recursive` directory,
+    /// and whose files are included, without their sub-directories.
+    pub parents: HashSet<BString>,
+}
+
+/// The outcome of [`Patterns::update_skip_worktree()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of entries that are now excluded from the worktree, but weren't before.
+    pub num_excluded: usize,
+    /// The amount of entries that are now included in the worktree, but weren't before.
+    pub num_included: usize,
+}
+
+/// Initialization
+impl Patterns {
+    /// Parse patterns from `bytes` as read from `source`, interpreting them in cone mode if `cone` is `true`.
+    ///
+    /// Note that just like Git, we fall back to non-cone mode if the patterns aren't valid for cone mode.
+    pub fn from_bytes(bytes: &[u8], source: impl Into<PathBuf>, cone: bool) -> Self {
+        if cone {
+            match Cone::from_bytes(bytes) {
+                Ok(cone) => return Patterns::Cone(cone),
+                Err(_line) => {
+                    gix_features::trace::warn!(
+                        "Disabling cone mode as sparse-checkout pattern '{}' is not a cone pattern",
+                        _line.as_bstr()
+                    );
+                }
+            }
+        }
+        Patterns::NonCone(pattern::List::from_bytes(bytes, source.into(), None))
+    }
+
+    /// Read the patterns from the file at `path`, typically `.git/info/sparse-checkout`, using `buf` as buffer, and interpret them
+    /// in cone mode if `cone` is `true`.
+    ///
+    /// Return `None` if the file doesn't exist.
+    pub fn from_file(path: &Path, cone: bool, buf: &mut Vec<u8>) -> std::io::Result<Option<Self>> {
+        buf.clear();
+        match std::fs::File::open(path).and_then(|mut file| std::io::Read::read_to_end(&mut file, buf)) {
+            Ok(_) => Ok(Some(Self::from_bytes(buf, path, cone))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Access
+impl Patterns {
+    /// Return `true` if these patterns are interpreted in cone mode.
+    pub fn is_cone(&self) -> bool {
+        matches!(self, Patterns::Cone(_))
+    }
+
+    /// Return `true` if the repository-relative `rela_path` should be present in the worktree.
+    /// `is_dir` is `true` if `rela_path` is a directory, which must not have a trailing slash.
+    pub fn is_included(&self, rela_path: &BStr, is_dir: bool) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.is_included(rela_path, is_dir),
+            Patterns::NonCone(list) => {
+                // Like Git, use the decision of the closest parent directory if there is no direct match.
+                let (mut path, mut is_dir) = (rela_path, is_dir);
+                loop {
+                    let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+                    if let Some(m) = gix_ignore::search::pattern_matching_relative_path(
+                        list,
+                        path,
+                        basename_pos,
+                        Some(is_dir),
+                        gix_glob::pattern::Case::Sensitive,
+                    ) {
+                        return !m.pattern.is_negative();
+                    }
+                    match basename_pos {
+                        Some(pos) => {
+                            path = path[..pos - 1].as_bstr();
+                            is_dir = true;
+                        }
+                        None => return false,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Mutation
+impl Patterns {
+    /// Set or clear the [`SKIP_WORKTREE`](Flags::SKIP_WORKTREE) flag of all entries in `index` depending on whether they are
+    /// [included](Self::is_included()) or not.
+    ///
+    /// Conflicting entries are always included. [Sparse directory entries](gix_index::entry::Mode::DIR) are left untouched,
+    /// so the index should be [expanded](gix_index::State::expand_sparse_directories()) beforehand if it
+    /// is [sparse](gix_index::State::is_sparse()).
+    ///
+    /// Note that the worktree isn't altered, so files of newly included entries still have to be checked out, while files of
+    /// newly excluded entries may still have to be removed.
+    pub fn update_skip_worktree(&self, index: &mut gix_index::State) -> Outcome {
+        let mut out = Outcome::default();
+        for (entry, path) in index.entries_mut_with_paths() {
+            if entry.mode.is_sparse() {
+                continue;
+            }
+            let should_skip = entry.stage_raw() == 0 && !self.is_included(path, false);
+            let is_skipped = entry.flags.contains(Flags::SKIP_WORKTREE);
+            if should_skip == is_skipped {
+                continue;
+            }
+            if should_skip {
+                entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+                out.num_excluded += 1;
+            } else {
+                entry.flags.remove(Flags::SKIP_WORKTREE);
+                if !entry.flags.contains(Flags::INTENT_TO_ADD) {
+                    entry.flags.remove(Flags::EXTENDED);
+                }
+                out.num_included += 1;
+            }
+        }
+        out
+    }
+}
+
+impl Cone {
+    /// Parse cone-mode patterns from `bytes`, or return the offending line if a pattern isn't valid in cone mode.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, &[u8]> {
+        let mut cone = Cone::default();
+        for line in bytes.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+            let (pattern, is_negative) = match line.strip_prefix(b"!") {
+                Some(pattern) => (pattern, true),
+                None => (line, false),
+            };
+            match (pattern, is_negative) {
+                (b"/*", false) => {
+                    cone.all = true;
+                    continue;
+                }
+                (b"/*/", true) => {
+                    cone.all = false;
+                    continue;
+                }
+                _ => {}
+            }
+            let dir = pattern
+                .strip_prefix(b"/")
+                .and_then(|p| p.strip_suffix(b"/"))
+                .filter(|dir| !dir.is_empty())
+                .ok_or(line)?;
+            let (dir, is_parent) = match dir.strip_suffix(b"/*") {
+                Some(dir) if is_negative => (dir, true),
+                None if !is_negative => (dir, false),
+                _ => return Err(line),
+            };
+            let dir = unescape(dir).ok_or(line)?;
+            if is_parent {
+                if !cone.recursive.remove(&dir) {
+                    return Err(line);
+                }
+                cone.parents.insert(dir);
+            } else {
+                if cone.parents.contains(&dir) {
+                    return Err(line);
+                }
+                let mut parent = dir.as_slice();
+                while let Some(pos) = parent.rfind_byte(b'/') {
+                    parent = &parent[..pos];
+                    cone.parents.insert(parent.into());
+                }
+                cone.recursive.insert(dir);
+            }
+        }
+        Ok(cone)
+    }
+
+    fn is_included(&self, rela_path: &BStr, is_dir: bool) -> bool {
+        if self.all || self.recursive.contains(rela_path) {
+            return true;
+        }
+        let dir = if is_dir {
+            rela_path
+        } else {
+            match rela_path.rfind_byte(b'/') {
+                Some(pos) => rela_path[..pos].as_bstr(),
+                None => return true,
+            }
+        };
+        if self.parents.contains(dir) {
+            return true;
+        }
+        let mut dir = dir;
+        loop {
+            if self.recursive.contains(dir) {
+                return true;
+            }
+            match dir.rfind_byte(b'/') {
+                Some(pos) => dir = dir[..pos].as_bstr(),
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Remove escapes from `dir`, or return `None` if it contains unescaped glob characters.
+fn unescape(dir: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    let mut bytes = dir.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => out.push(*bytes.next()?),
+            b'*' | b'?' | b'[' => return None,
+            _ => out.push(b),
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function init_repo() {
+  git init -q "$1"
+  (cd "$1"
+    mkdir -p a/b/c a/x d/e/f
+    touch root.md a/file a/b/file a/b/c/file a/x/file d/file.md d/e/file d/e/f/file.md d/e/f/other
+    git add .
+    git commit -qm "init"
+  )
+}
+
+init_repo cone
+(cd cone
+  git sparse-checkout set --cone a/b d/e/f
+  git ls-files -t > git-ls-files.baseline
+)
+
+init_repo no-cone
+(cd no-cone
+  git sparse-checkout set --no-cone '/a/b/' '*.md' '!d/e/f/'
+  git ls-files -t > git-ls-files.baseline
+)
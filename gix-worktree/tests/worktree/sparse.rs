@@ -0,0 +1,112 @@
+use bstr::ByteSlice;
+use gix_index::entry::Flags;
+use gix_worktree::sparse::Patterns;
+
+fn fixture(name: &str) -> (Patterns, gix_index::File, Vec<(bool, String)>) {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_sparse_checkout.sh")
+        .unwrap()
+        .join(name);
+    let git_dir = dir.join(".git");
+    let patterns = Patterns::from_file(
+        &git_dir.join("info").join("sparse-checkout"),
+        name == "cone",
+        &mut Vec::new(),
+    )
+    .unwrap()
+    .expect("file is present");
+    let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default()).unwrap();
+    let expected = std::fs::read(dir.join("git-ls-files.baseline"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let (status, path) = line.split_once_str(" ").unwrap();
+            (status == b"H", path.to_str().unwrap().to_owned())
+        })
+        .collect();
+    (patterns, index, expected)
+}
+
+fn included_paths(index: &gix_index::State) -> Vec<(bool, String)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| {
+            (
+                !e.flags.contains(Flags::SKIP_WORKTREE),
+                e.path(index).to_str().unwrap().to_owned(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn cone_mode_matches_git() {
+    let (patterns, mut index, expected) = fixture("cone");
+    let Patterns::Cone(cone) = &patterns else {
+        unreachable!("valid cone patterns are parsed as such")
+    };
+    assert!(!cone.all);
+    assert_eq!(cone.recursive.len(), 2, "a/b and d/e/f");
+    assert_eq!(cone.parents.len(), 3, "a, d and d/e");
+    assert_eq!(included_paths(&index), expected, "sanity check");
+
+    for entry in index.entries_mut() {
+        entry.flags.remove(Flags::SKIP_WORKTREE);
+    }
+    let outcome = patterns.update_skip_worktree(&mut index);
+    assert_eq!(outcome.num_excluded, 1);
+    assert_eq!(outcome.num_included, 0);
+    assert_eq!(included_paths(&index), expected);
+
+    assert!(patterns.is_included("a".into(), true));
+    assert!(patterns.is_included("a/b/c".into(), true));
+    assert!(!patterns.is_included("a/x".into(), true));
+    assert!(!patterns.is_included("new-dir".into(), true));
+    assert!(patterns.is_included("new-file".into(), false));
+}
+
+#[test]
+fn non_cone_mode_matches_git() {
+    let (patterns, mut index, expected) = fixture("no-cone");
+    assert!(!patterns.is_cone());
+
+    for entry in index.entries_mut() {
+        entry.flags.remove(Flags::SKIP_WORKTREE);
+    }
+    let outcome = patterns.update_skip_worktree(&mut index);
+    assert_eq!(outcome.num_excluded, 4);
+    assert_eq!(included_paths(&index), expected);
+
+    let outcome = patterns.update_skip_worktree(&mut index);
+    assert_eq!(outcome, Default::default(), "the update is idempotent");
+}
+
+#[test]
+fn cone_mode_falls_back_to_non_cone_mode_for_invalid_patterns() {
+    let patterns = Patterns::from_bytes(b"/*\n!/*/\n*.md\n", "", true);
+    assert!(!patterns.is_cone());
+    assert!(patterns.is_included("dir/file.md".into(), false));
+    assert!(!patterns.is_included("dir/file".into(), false));
+
+    let patterns = Patterns::from_bytes(b"/*\n!/*/\n!/a/*/\n", "", true);
+    assert!(
+        !patterns.is_cone(),
+        "negative patterns must refer to a previously included directory"
+    );
+}
+
+#[test]
+fn conflicts_are_never_excluded() {
+    let patterns = Patterns::from_bytes(b"/*\n!/*/\n", "", true);
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    for stage in [gix_index::entry::Stage::Base, gix_index::entry::Stage::Ours] {
+        index.dangerously_push_entry(
+            Default::default(),
+            gix_hash::Kind::Sha1.null(),
+            stage.into(),
+            gix_index::entry::Mode::FILE,
+            "dir/conflict".into(),
+        );
+    }
+    assert_eq!(patterns.update_skip_worktree(&mut index).num_excluded, 0);
+}
@@ -35,6 +35,27 @@ impl PrepareFetch {
         self
     }
 
+    /// Make this clone a partial one by asking the remote to omit objects matching `filter`, or set it to `None`
+    /// to receive all objects.
+    ///
+    /// The remote is configured as promisor remote with `filter` as `remote.<name>.partialCloneFilter`, and
+    /// `extensions.partialClone` is set so that missing objects are fetched from it on demand.
+    pub fn with_filter(mut self, filter: Option<crate::remote::fetch::Filter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Download the bundle or bundle list at `uri` and unbundle it before fetching, so that only objects that
+    /// aren't contained in the bundles are fetched from the remote, similar to `git clone --bundle-uri`.
+    ///
+    /// If unset and if `transfer.bundleURI` is `true`, the bundles advertised by the remote are used instead.
+    /// Bundles are ignored for shallow clones.
+    #[cfg(feature = "bundle")]
+    pub fn with_bundle_uri(mut self, uri: Option<impl Into<BString>>) -> Self {
+        self.bundle_uri = uri.map(Into::into);
+        self
+    }
+
     /// Apply the given configuration `values` right before readying the actual fetch from the remote.
     /// The configuration is marked with [source API](gix_config::Source::Api), and will not be written back, it's
     /// retained only in memory.
@@ -28,6 +28,8 @@ pub mod main_worktree {
         #[error(transparent)]
         IndexCheckout(#[from] gix_worktree_state::checkout::Error),
         #[error(transparent)]
+        SparseCheckout(#[from] crate::repository::apply_sparse_checkout::Error),
+        #[error(transparent)]
         Peel(#[from] crate::reference::peel::Error),
         #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
         OpenArcOdb(#[from] std::io::Error),
@@ -35,6 +37,8 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error("Could not fetch the objects to check out from the promisor remote")]
+        FetchMissingObjects(#[from] gix_odb::store::find::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -62,6 +66,8 @@ pub mod main_worktree {
         /// Checkout the main worktree, determining how many threads to use by looking at `checkout.workers`, defaulting to using
         /// on thread per logical core.
         ///
+        /// If a [sparse checkout](Repository::sparse_checkout_patterns()) was configured, only the included files are checked out.
+        ///
         /// Note that this is a no-op if the remote was empty, leaving this repository empty as well. This can be validated by checking
         /// if the `head()` of the returned repository is *not* unborn.
         ///
@@ -111,11 +117,23 @@ pub mod main_worktree {
                 }
             };
 
-            let index = gix_index::State::from_tree(&root_tree, &repo.objects, repo.config.protect_options()?)
+            let mut index = gix_index::State::from_tree(&root_tree, &repo.objects, repo.config.protect_options()?)
                 .map_err(|err| Error::IndexFromTree {
                     id: root_tree,
                     source: err,
                 })?;
+            repo.apply_sparse_checkout(&mut index)?;
+            // In partial clones, get all blobs at once instead of one at a time.
+            repo.objects.fetch_missing(
+                index
+                    .entries()
+                    .iter()
+                    .filter(|entry| {
+                        entry.mode != gix_index::entry::Mode::COMMIT
+                            && !entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE)
+                    })
+                    .map(|entry| entry.id),
+            )?;
             let mut index = gix_index::File::from_state(index, repo.index_path());
 
             let mut opts = repo.checkout_options(gix_worktree::stack::state::attributes::Source::IdMapping)?;
@@ -30,6 +30,14 @@ pub enum Error {
     SaveConfig(#[from] crate::remote::save::AsError),
     #[error("Failed to write repository configuration to disk")]
     SaveConfigIo(#[from] std::io::Error),
+    #[error("Failed to configure the repository as partial clone")]
+    SetPartialCloneConfig(#[from] crate::config::set_value::Error),
+    #[cfg(feature = "bundle")]
+    #[error("Failed to unbundle bundles from the bundle uri")]
+    BundleUri(#[from] crate::repository::bundle::uri::Error),
+    #[cfg(feature = "bundle")]
+    #[error(transparent)]
+    BundleUriConfig(#[from] crate::config::boolean::Error),
     #[error("The remote HEAD points to a reference named {head_ref_name:?} which is invalid.")]
     InvalidHeadRef {
         source: gix_validate::reference::name::Error,
@@ -85,6 +93,16 @@ impl PrepareFetch {
             snapshot.commit()?;
         }
 
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        let use_bundles = matches!(self.shallow, remote::fetch::Shallow::NoChange);
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        let bundles = match self.bundle_uri.as_ref().filter(|_| use_bundles) {
+            Some(uri) => {
+                Some(repo.unbundle_from_uri(uri.as_ref(), &mut progress, should_interrupt, Default::default())?)
+            }
+            None => None,
+        };
+
         let remote_name = match self.remote_name.as_ref() {
             Some(name) => name.to_owned(),
             None => repo
@@ -128,7 +146,8 @@ impl PrepareFetch {
         )
         .expect("valid")
         .to_owned();
-        let pending_pack: remote::fetch::Prepare<'_, '_, _> = {
+        #[cfg_attr(not(all(feature = "bundle", feature = "blocking-network-client")), allow(unused_mut))]
+        let mut pending_pack: remote::fetch::Prepare<'_, '_, _> = {
             let mut connection = remote.connect(remote::Direction::Fetch).await?;
             if let Some(f) = self.configure_connection.as_mut() {
                 f(&mut connection).map_err(Error::RemoteConnection)?;
@@ -178,6 +197,35 @@ impl PrepareFetch {
             }
         };
 
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        if use_bundles
+            && self.bundle_uri.is_none()
+            && repo
+                .config
+                .resolved
+                .boolean(crate::config::tree::Transfer::BUNDLE_URI)
+                .map(|val| crate::config::tree::Transfer::BUNDLE_URI.enrich_error(val))
+                .transpose()?
+                .unwrap_or(false)
+        {
+            // Like `git`, advertised bundles are merely an optimization and the fetch works without them.
+            match pending_pack.bundle_list() {
+                Ok(Some(list)) => {
+                    if let Err(_err) = repo.unbundle_from_list(
+                        list,
+                        self.url.to_bstring().as_ref(),
+                        &mut progress,
+                        should_interrupt,
+                        Default::default(),
+                    ) {
+                        gix_trace::warn!("Ignoring bundles advertised by the remote: {_err}");
+                    }
+                }
+                Ok(None) => {}
+                Err(_err) => gix_trace::warn!("Could not obtain the bundles advertised by the remote: {_err}"),
+            }
+        }
+
         // Assure problems with custom branch names fail early, not after getting the pack or during negotiation.
         if let Some(ref_name) = &self.ref_name {
             util::find_custom_refname(pending_pack.ref_map(), ref_name)?;
@@ -196,10 +244,22 @@ impl PrepareFetch {
                 message: reflog_message.clone(),
             })
             .with_shallow(self.shallow.clone())
+            .with_filter(self.filter.clone())
             .receive(&mut progress, should_interrupt)
             .await?;
 
         util::append_config_to_repo_config(repo, config);
+        if let Some(filter) = &self.filter {
+            util::setup_partial_clone(repo, remote_name.as_ref(), filter)?;
+        }
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        if let Some((uri, creation_token)) = self
+            .bundle_uri
+            .as_ref()
+            .zip(bundles.and_then(|bundles| bundles.creation_token))
+        {
+            util::setup_bundle_uri(repo, uri.as_ref(), creation_token)?;
+        }
         util::update_head(
             repo,
             &outcome.ref_map,
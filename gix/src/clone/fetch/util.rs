@@ -8,6 +8,8 @@ use gix_ref::{
 use super::Error;
 use crate::{
     bstr::{BStr, BString, ByteSlice},
+    config::tree::{Core, Extensions, Fetch, Remote},
+    remote::fetch::Filter,
     Repository,
 };
 
@@ -58,6 +60,32 @@ pub fn append_config_to_repo_config(repo: &mut Repository, config: gix_config::F
     repo_config.append(config);
 }
 
+/// Turn `repo` into a partial clone whose missing objects are promised by the remote named `remote_name`,
+/// so future fetches use `filter` as well, and install the fetcher for missing objects.
+pub fn setup_partial_clone(repo: &mut Repository, remote_name: &BStr, filter: &Filter) -> Result<(), Error> {
+    let mut config = repo.config_snapshot_mut();
+    config.set_value(&Core::REPOSITORY_FORMAT_VERSION, "1")?;
+    config.set_value(&Extensions::PARTIAL_CLONE, remote_name)?;
+    config.set_subsection_value(&Remote::PROMISOR, remote_name, "true")?;
+    config.set_subsection_value(&Remote::PARTIAL_CLONE_FILTER, remote_name, filter.to_string().as_str())?;
+    write_to_local_config(&config, WriteMode::Overwrite)?;
+    config.commit()?;
+    repo.setup_promisor();
+    Ok(())
+}
+
+/// Remember the bundle `uri` that was used for cloning along with the greatest `creation_token` of its bundles, so
+/// later fetches can download only newer bundles.
+#[cfg(feature = "bundle")]
+pub fn setup_bundle_uri(repo: &mut Repository, uri: &BStr, creation_token: u64) -> Result<(), Error> {
+    let mut config = repo.config_snapshot_mut();
+    config.set_value(&Fetch::BUNDLE_URI, uri)?;
+    config.set_value(&Fetch::BUNDLE_CREATION_TOKEN, creation_token.to_string().as_str())?;
+    write_to_local_config(&config, WriteMode::Overwrite)?;
+    config.commit()?;
+    Ok(())
+}
+
 /// HEAD cannot be written by means of refspec by design, so we have to do it manually here. Also create the pointed-to ref
 /// if we have to, as it might not have been naturally included in the ref-specs.
 /// Lastly, use `ref_name` if it was provided instead, and let `HEAD` point to it.
@@ -55,10 +55,12 @@ impl Cache {
             let driver = match out.iter_mut().find(|d| d.name == name) {
                 Some(existing) => existing,
                 None => {
-                    out.push(gix_diff::blob::Driver {
-                        name: name.into(),
-                        ..Default::default()
-                    });
+                    out.push(
+                        gix_diff::blob::Driver::builtin(name).unwrap_or_else(|| gix_diff::blob::Driver {
+                            name: name.into(),
+                            ..Default::default()
+                        }),
+                    );
                     out.last_mut().expect("just pushed")
                 }
             };
@@ -79,6 +81,21 @@ impl Cache {
             if let Some(textconv) = section.value(config::tree::Diff::DRIVER_TEXTCONV.name) {
                 driver.binary_to_text_command = textconv.into_owned().into();
             }
+            if let Some(word_regex) = section.value(config::tree::Diff::DRIVER_WORD_REGEX.name) {
+                driver.word_regex = word_regex.into_owned().into();
+            }
+            if let Some(funcname) = section
+                .value(config::tree::Diff::DRIVER_XFUNCNAME.name)
+                .map(std::borrow::Cow::into_owned)
+                .or_else(|| {
+                    section
+                        .value(config::tree::Diff::DRIVER_FUNCNAME.name)
+                        .map(|funcname| gix_diff::blob::funcname::basic_to_extended(funcname.as_ref()))
+                })
+            {
+                driver.funcname = Some(funcname);
+                driver.funcname_ignore_case = false;
+            }
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
@@ -97,6 +114,10 @@ impl Cache {
                     .into();
             }
         }
+        let builtins: Vec<_> = gix_diff::blob::Driver::builtins()
+            .filter(|builtin| out.iter().all(|driver| driver.name != builtin.name))
+            .collect();
+        out.extend(builtins);
         Ok(out)
     }
 
@@ -265,6 +286,30 @@ impl Cache {
         )
     }
 
+    pub(crate) fn trusted_file_paths(
+        &self,
+        key: impl gix_config::AsKey,
+    ) -> Result<Vec<Cow<'_, std::path::Path>>, gix_config::path::interpolate::Error> {
+        let mut out = Vec::new();
+        let Some(values) = self
+            .resolved
+            .strings_filter(&key, &mut self.filter_config_section.clone())
+        else {
+            return Ok(out);
+        };
+        let install_dir = crate::path::install_dir().ok();
+        let home = home_dir(self.environment);
+        let ctx = config::cache::interpolate_context(install_dir.as_deref(), home.as_deref());
+        for value in values {
+            if value.is_empty() {
+                out.clear();
+                continue;
+            }
+            out.push(gix_config::Path::from(value).interpolate(ctx)?);
+        }
+        Ok(out)
+    }
+
     pub(crate) fn apply_leniency<T, E>(&self, res: Option<Result<T, E>>) -> Result<Option<T>, E> {
         res.transpose().with_leniency(self.lenient_config)
     }
@@ -154,6 +154,7 @@ impl Cache {
             true,
             lenient_config,
         )?;
+        let (strict_object_creation, strict_hash_verification) = util::query_strict_objects(&config, lenient_config)?;
         #[cfg(feature = "revision")]
         let object_kind_hint = util::disambiguate_hint(&config, lenient_config)?;
         let (static_pack_cache_limit_bytes, pack_cache_bytes, object_cache_bytes) =
@@ -162,6 +163,8 @@ impl Cache {
         Ok(Cache {
             resolved: config.into(),
             use_multi_pack_index,
+            strict_object_creation,
+            strict_hash_verification,
             object_hash,
             #[cfg(feature = "revision")]
             object_kind_hint,
@@ -227,8 +230,12 @@ impl Cache {
         }
         let reflog = util::query_refupdates(config, self.lenient_config)?;
         let refs_namespace = util::query_refs_namespace(config, self.lenient_config)?;
+        let (strict_object_creation, strict_hash_verification) =
+            util::query_strict_objects(config, self.lenient_config)?;
 
         self.hex_len = hex_len;
+        self.strict_object_creation = strict_object_creation;
+        self.strict_hash_verification = strict_hash_verification;
         self.ignore_case = ignore_case;
         self.reflog = reflog;
         self.refs_namespace = refs_namespace;
@@ -44,6 +44,29 @@ pub(crate) fn config_bool(
         .with_lenient_default(lenient)
 }
 
+/// Return `(strict_object_creation, strict_hash_verification)`.
+pub(crate) fn query_strict_objects(
+    config: &gix_config::File<'static>,
+    lenient_config: bool,
+) -> Result<(bool, bool), Error> {
+    Ok((
+        config_bool(
+            config,
+            &gitoxide::Objects::STRICT_CREATION,
+            "gitoxide.objects.strictCreation",
+            false,
+            lenient_config,
+        )?,
+        config_bool(
+            config,
+            &gitoxide::Objects::STRICT_HASH_VERIFICATION,
+            "gitoxide.objects.strictHashVerification",
+            false,
+            lenient_config,
+        )?,
+    ))
+}
+
 pub(crate) fn query_refupdates(
     config: &gix_config::File<'static>,
     lenient_config: bool,
@@ -593,6 +593,10 @@ pub(crate) struct Cache {
     pub object_hash: gix_hash::Kind,
     /// If true, multi-pack indices, whether present or not, may be used by the object database.
     pub use_multi_pack_index: bool,
+    /// The representation of `gitoxide.objects.strictCreation`.
+    pub strict_object_creation: bool,
+    /// The representation of `gitoxide.objects.strictHashVerification`.
+    pub strict_hash_verification: bool,
     /// The representation of `core.logallrefupdates`, or `None` if the variable wasn't set.
     pub reflog: Option<gix_ref::store::WriteReflog>,
     /// The representation of `gitoxide.core.refsNamespace`, or `None` if the variable wasn't set.
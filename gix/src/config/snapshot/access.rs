@@ -63,6 +63,16 @@ impl<'repo> Snapshot<'repo> {
         self.repo.config.trusted_file_path(key)
     }
 
+    /// Return all trusted and fully interpolated paths at `key`, in order, or an empty list if there is no such value
+    /// in a trusted file. An empty value clears all paths seen before it, just like Git does for lists of paths.
+    /// An error occurs if any of the paths could not be interpolated to its final value.
+    pub fn trusted_paths(
+        &self,
+        key: impl gix_config::AsKey,
+    ) -> Result<Vec<Cow<'repo, std::path::Path>>, gix_config::path::interpolate::Error> {
+        self.repo.config.trusted_file_paths(key)
+    }
+
     /// Return the trusted string at `key` for launching using [command::prepare()](gix_command::prepare()),
     /// or `None` if there is no such value or if no value was found in a trusted file.
     pub fn trusted_program(&self, key: impl gix_config::AsKey) -> Option<Cow<'repo, OsStr>> {
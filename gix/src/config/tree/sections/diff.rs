@@ -17,6 +17,13 @@ impl Diff {
     );
     /// The `diff.renames` key.
     pub const RENAMES: Renames = Renames::new_renames("renames", &config::Tree::DIFF);
+    /// The `diff.wordRegex` key.
+    pub const WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF);
+    /// The `diff.indentHeuristic` key.
+    pub const INDENT_HEURISTIC: keys::Boolean = keys::Boolean::new_boolean("indentHeuristic", &config::Tree::DIFF);
+    /// The `diff.interHunkContext` key.
+    pub const INTER_HUNK_CONTEXT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("interHunkContext", &config::Tree::DIFF);
 
     /// The `diff.<driver>.command` key.
     pub const DRIVER_COMMAND: keys::Program = keys::Program::new_program("command", &config::Tree::DIFF)
@@ -31,6 +38,15 @@ impl Diff {
     /// The `diff.<driver>.binary` key.
     pub const DRIVER_BINARY: Binary = Binary::new_with_validate("binary", &config::Tree::DIFF, validate::Binary)
         .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `diff.<driver>.wordRegex` key.
+    pub const DRIVER_WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `diff.<driver>.xfuncname` key.
+    pub const DRIVER_XFUNCNAME: keys::String = keys::String::new_string("xfuncname", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `diff.<driver>.funcname` key, which is like `xfuncname` but uses basic regular expressions.
+    pub const DRIVER_FUNCNAME: keys::String = keys::String::new_string("funcname", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
 
     /// The `diff.external` key.
     pub const EXTERNAL: keys::Program =
@@ -47,10 +63,16 @@ impl Section for Diff {
             &Self::ALGORITHM,
             &Self::RENAME_LIMIT,
             &Self::RENAMES,
+            &Self::WORD_REGEX,
+            &Self::INDENT_HEURISTIC,
+            &Self::INTER_HUNK_CONTEXT,
             &Self::DRIVER_COMMAND,
             &Self::DRIVER_TEXTCONV,
             &Self::DRIVER_ALGORITHM,
             &Self::DRIVER_BINARY,
+            &Self::DRIVER_WORD_REGEX,
+            &Self::DRIVER_XFUNCNAME,
+            &Self::DRIVER_FUNCNAME,
             &Self::EXTERNAL,
         ]
     }
@@ -0,0 +1,19 @@
+use crate::config::{
+    tree::{keys, Fsck, Key, Section},
+    Tree,
+};
+
+impl Fsck {
+    /// The `fsck.skipList` key
+    pub const SKIP_LIST: keys::Path = keys::Path::new_path("skipList", &Tree::FSCK);
+}
+
+impl Section for Fsck {
+    fn name(&self) -> &str {
+        "fsck"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::SKIP_LIST]
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    config,
+    config::tree::{keys, Gc, Key, Section},
+};
+
+impl Gc {
+    /// The `gc.auto` key.
+    pub const AUTO: keys::UnsignedInteger = keys::UnsignedInteger::new_unsigned_integer("auto", &config::Tree::GC);
+    /// The `gc.autoPackLimit` key.
+    pub const AUTO_PACK_LIMIT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("autoPackLimit", &config::Tree::GC);
+    /// The `gc.cruftPacks` key.
+    pub const CRUFT_PACKS: keys::Boolean = keys::Boolean::new_boolean("cruftPacks", &config::Tree::GC);
+    /// The `gc.packRefs` key.
+    pub const PACK_REFS: keys::Boolean =
+        keys::Boolean::new_boolean("packRefs", &config::Tree::GC).with_deviation("The value 'notbare' isn't supported");
+    /// The `gc.pruneExpire` key.
+    pub const PRUNE_EXPIRE: Expiry = Expiry::new_with_validate("pruneExpire", &config::Tree::GC, validate::Expiry);
+    /// The `gc.reflogExpire` key.
+    pub const REFLOG_EXPIRE: Expiry = Expiry::new_with_validate("reflogExpire", &config::Tree::GC, validate::Expiry)
+        .with_deviation("Per-pattern values in `gc.<pattern>.reflogExpire` are not supported");
+    /// The `gc.reflogExpireUnreachable` key.
+    pub const REFLOG_EXPIRE_UNREACHABLE: Expiry =
+        Expiry::new_with_validate("reflogExpireUnreachable", &config::Tree::GC, validate::Expiry)
+            .with_deviation("Per-pattern values in `gc.<pattern>.reflogExpireUnreachable` are not supported");
+}
+
+impl Section for Gc {
+    fn name(&self) -> &str {
+        "gc"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[
+            &Self::AUTO,
+            &Self::AUTO_PACK_LIMIT,
+            &Self::CRUFT_PACKS,
+            &Self::PACK_REFS,
+            &Self::PRUNE_EXPIRE,
+            &Self::REFLOG_EXPIRE,
+            &Self::REFLOG_EXPIRE_UNREACHABLE,
+        ]
+    }
+}
+
+/// The `gc.pruneExpire` and `gc.reflogExpire*` keys, which are dates before which items are considered expired.
+pub type Expiry = keys::Any<validate::Expiry>;
+
+mod expiry {
+    use std::{
+        borrow::Cow,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::sections::gc::Expiry,
+    };
+
+    impl Expiry {
+        /// Interpret `value` as point in time before which items expire, with `now` being the reference for relative dates.
+        ///
+        /// Return `None` if nothing should expire, which is the case for `never` or `false`. `now` and `all` cause
+        /// everything to expire. Relative dates may be separated by dots, like in `2.weeks.ago`.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: SystemTime,
+        ) -> Result<Option<SystemTime>, config::key::GenericErrorWithValue<gix_date::parse::Error>> {
+            let make_err = |err: gix_date::parse::Error| {
+                config::key::GenericErrorWithValue::from_value(self, value.as_ref().into()).with_source(err)
+            };
+            let input = value.to_str().map_err(|_| {
+                make_err(gix_date::parse::Error::InvalidDateString {
+                    input: value.to_str_lossy().into_owned(),
+                })
+            })?;
+            Ok(match input {
+                "never" | "false" => None,
+                "now" | "all" => Some(now),
+                _ => {
+                    let time = gix_date::parse(input, Some(now))
+                        .or_else(|err| {
+                            if input.contains('.') {
+                                gix_date::parse(&input.replace('.', " "), Some(now))
+                            } else {
+                                Err(err)
+                            }
+                        })
+                        .map_err(make_err)?;
+                    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(time.seconds.max(0) as u64))
+                }
+            })
+        }
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct Expiry;
+    impl keys::Validate for Expiry {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Gc::PRUNE_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
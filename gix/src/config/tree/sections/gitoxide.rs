@@ -431,6 +431,13 @@ mod subsections {
         /// The `gitoxide.objects.replaceRefBase` key.
         pub const REPLACE_REF_BASE: keys::Any =
             keys::Any::new("replaceRefBase", &Gitoxide::OBJECTS).with_environment_override("GIT_REPLACE_REF_BASE");
+        /// The `gitoxide.objects.strictCreation` key.
+        pub const STRICT_CREATION: keys::Boolean = keys::Boolean::new_boolean("strictCreation", &Gitoxide::OBJECTS)
+            .with_note("Validate objects before writing them, and that all objects they refer to exist");
+        /// The `gitoxide.objects.strictHashVerification` key.
+        pub const STRICT_HASH_VERIFICATION: keys::Boolean =
+            keys::Boolean::new_boolean("strictHashVerification", &Gitoxide::OBJECTS)
+                .with_note("Verify that objects read through the repository actually have the hash they claim to have");
     }
 
     impl Section for Objects {
@@ -439,7 +446,12 @@ mod subsections {
         }
 
         fn keys(&self) -> &[&dyn Key] {
-            &[&Self::CACHE_LIMIT, &Self::REPLACE_REF_BASE]
+            &[
+                &Self::CACHE_LIMIT,
+                &Self::REPLACE_REF_BASE,
+                &Self::STRICT_CREATION,
+                &Self::STRICT_HASH_VERIFICATION,
+            ]
         }
 
         fn parent(&self) -> Option<&dyn Section> {
@@ -47,4 +47,31 @@ pub mod write {
     #[derive(Debug, thiserror::Error)]
     #[error(transparent)]
     pub struct Error(#[from] pub gix_object::write::Error);
+
+    impl From<strict::Error> for Error {
+        fn from(err: strict::Error) -> Self {
+            Error(Box::new(err))
+        }
+    }
+
+    ///
+    pub mod strict {
+        /// The error returned if an object is rejected before writing it, with
+        /// [strict object creation](crate::config::tree::gitoxide::Objects::STRICT_CREATION) enabled.
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("The {kind} to write is malformed")]
+            Format {
+                kind: gix_object::Kind,
+                source: gix_object::data::verify_format::Error,
+            },
+            #[error("The {referenced_kind} {id} referenced by the {kind} to write does not exist")]
+            MissingObject {
+                kind: gix_object::Kind,
+                referenced_kind: gix_object::Kind,
+                id: gix_hash::ObjectId,
+            },
+        }
+    }
 }
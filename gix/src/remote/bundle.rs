@@ -0,0 +1,217 @@
+//! Fetch from bundle files as if they were remote repositories.
+//!
+//! The [`Transport`] pretends to be a server speaking protocol V1 which advertises the references of the bundle and sends
+//! its pack once the client is done negotiating, which allows fetches and clones to work without any special handling.
+use std::{
+    any::Any,
+    borrow::Cow,
+    io::{BufReader, Read},
+    path::PathBuf,
+};
+
+use gix_protocol::transport::{
+    client,
+    client::{Capabilities, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    packetline, Protocol, Service,
+};
+
+use crate::bstr::{BStr, BString};
+
+/// A transport that serves a bundle, which is used by [`Remote::connect()`](crate::Remote::connect()) if the
+/// url of a remote points to a bundle file.
+pub struct Transport {
+    bundle: gix_bundle::File,
+    url: BString,
+    line_provider: packetline::StreamingPeekableIter<Box<dyn Read + Send>>,
+    trace: bool,
+}
+
+/// Initialization
+impl Transport {
+    /// Create a new instance to serve `bundle`, which is reachable by `url`.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub fn new(bundle: gix_bundle::File, url: BString, trace: bool) -> Self {
+        Transport {
+            bundle,
+            url,
+            line_provider: packetline::StreamingPeekableIter::new(
+                Box::new(std::io::empty()),
+                &[packetline::PacketLineRef::Flush],
+                trace,
+            ),
+            trace,
+        }
+    }
+
+    /// Return the bundle we serve.
+    pub fn bundle(&self) -> &gix_bundle::File {
+        &self.bundle
+    }
+
+    /// Return the advertisement of all references of the bundle as packetlines, as a V1 server would send it.
+    fn advertisement(&self) -> std::io::Result<Vec<u8>> {
+        let header = &self.bundle.header;
+        let mut capabilities = BString::from(format!(
+            "multi_ack_detailed side-band-64k ofs-delta include-tag object-format={}",
+            header.object_hash.to_string().to_ascii_lowercase()
+        ));
+        // Like `git`, guess which branch `HEAD` is pointing to by its object.
+        let head = header.find_ref("HEAD".into());
+        if let Some(branch) = head.and_then(|head| {
+            header
+                .refs
+                .iter()
+                .find(|r| r.id == head.id && r.name.starts_with(b"refs/heads/"))
+        }) {
+            capabilities.extend_from_slice(b" symref=HEAD:");
+            capabilities.extend_from_slice(&branch.name);
+        }
+
+        let mut out = Vec::new();
+        let mut refs: Vec<_> = head.into_iter().collect();
+        refs.extend(header.refs.iter().filter(|r| r.name != "HEAD"));
+        if refs.is_empty() {
+            let mut line = BString::from(format!("{} capabilities^{{}}\0", header.object_hash.null()));
+            line.extend_from_slice(&capabilities);
+            packetline::encode::text_to_write(&line, &mut out)?;
+        }
+        for (idx, r) in refs.into_iter().enumerate() {
+            let mut line = BString::from(format!("{} ", r.id));
+            line.extend_from_slice(&r.name);
+            if idx == 0 {
+                line.push(0);
+                line.extend_from_slice(&capabilities);
+            }
+            packetline::encode::text_to_write(&line, &mut out)?;
+        }
+        packetline::encode::flush_to_write(&mut out)?;
+        Ok(out)
+    }
+}
+
+impl client::TransportWithoutIO for Transport {
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        // We can't know which objects the client has, so the only answer to its `haves` is a `NAK`,
+        // and once it's done we send the pack which is all we have.
+        let mut response = Vec::new();
+        packetline::encode::text_to_write(b"NAK", &mut response)?;
+        let response: Box<dyn Read + Send> = if on_into_read == MessageKind::Text(b"done") {
+            Box::new(std::io::Cursor::new(response).chain(SidebandPack {
+                pack: self.bundle.pack_reader()?,
+                data: vec![0; MAX_DATA_LEN],
+                buf: Vec::new(),
+                pos: 0,
+                is_done: false,
+            }))
+        } else {
+            Box::new(std::io::Cursor::new(response))
+        };
+        self.line_provider.replace(response);
+        Ok(RequestWriter::new_from_bufread(
+            std::io::sink(),
+            Box::new(self.line_provider.as_read_without_sidebands()),
+            write_mode,
+            on_into_read,
+            trace,
+        ))
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Borrowed(self.url.as_ref())
+    }
+
+    fn supported_protocol_versions(&self) -> &[Protocol] {
+        &[Protocol::V1]
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl client::Transport for Transport {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        _extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        if service != Service::UploadPack {
+            return Err(client::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "Bundles can only be fetched from, but {} was requested",
+                    service.as_str()
+                ),
+            )));
+        }
+        let advertisement = self.advertisement()?;
+        self.line_provider
+            .replace(Box::new(std::io::Cursor::new(advertisement)));
+        if self.trace {
+            gix_features::trace::debug!(bundle = ?self.bundle.path(), "gix::remote::bundle::Transport");
+        }
+        let client::capabilities::recv::Outcome {
+            capabilities,
+            refs,
+            protocol: actual_protocol,
+        } = Capabilities::from_lines_with_version_detection(&mut self.line_provider)?;
+        Ok(SetServiceResponse {
+            actual_protocol,
+            capabilities,
+            refs,
+        })
+    }
+}
+
+/// The largest amount of data that fits into a packetline along with the side-band channel.
+const MAX_DATA_LEN: usize = 65515;
+
+/// Produce the pack of a bundle as packetlines on the data channel of the side-band, followed by a flush packet.
+struct SidebandPack {
+    pack: BufReader<std::fs::File>,
+    data: Vec<u8>,
+    buf: Vec<u8>,
+    pos: usize,
+    is_done: bool,
+}
+
+impl Read for SidebandPack {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos == self.buf.len() {
+            if self.is_done {
+                return Ok(0);
+            }
+            self.buf.clear();
+            self.pos = 0;
+            let num_read = self.pack.read(&mut self.data)?;
+            if num_read == 0 {
+                packetline::encode::flush_to_write(&mut self.buf)?;
+                self.is_done = true;
+            } else {
+                packetline::encode::band_to_write(packetline::Channel::Data, &self.data[..num_read], &mut self.buf)?;
+            }
+        }
+        let num_written = (self.buf.len() - self.pos).min(out.len());
+        out[..num_written].copy_from_slice(&self.buf[self.pos..][..num_written]);
+        self.pos += num_written;
+        Ok(num_written)
+    }
+}
+
+/// Return the path to the bundle file that `url` points to, or `None` if it doesn't point to a bundle.
+pub(crate) fn path_from_url(url: &gix_url::Url) -> Option<PathBuf> {
+    if url.scheme != gix_url::Scheme::File {
+        return None;
+    }
+    let path = gix_path::from_bstr(&url.path).into_owned();
+    (path.is_file() && gix_bundle::is_bundle(&path)).then_some(path)
+}
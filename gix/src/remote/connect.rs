@@ -34,6 +34,12 @@ mod error {
             source: Box<gix_discover::is_git::Error>,
             url: gix_url::Url,
         },
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        #[error(transparent)]
+        OpenBundle(#[from] gix_bundle::file::open::Error),
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        #[error("The repository lacks {} prerequisite commit(s) of the bundle, like {}", missing.len(), missing[0])]
+        MissingBundlePrerequisites { missing: Vec<gix_hash::ObjectId> },
     }
 
     impl gix_protocol::transport::IsSpuriousError for Error {
@@ -84,6 +90,17 @@ impl<'repo> Remote<'repo> {
         direction: crate::remote::Direction,
     ) -> Result<Connection<'_, 'repo, Box<dyn Transport + Send>>, Error> {
         let (url, version) = self.sanitized_url_and_version(direction)?;
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        if let Some(path) = crate::remote::bundle::path_from_url(&url) {
+            let bundle = gix_bundle::File::at(path)?;
+            let missing = bundle.header.missing_prerequisites(&self.repo.objects);
+            if !missing.is_empty() {
+                return Err(Error::MissingBundlePrerequisites { missing });
+            }
+            let transport =
+                crate::remote::bundle::Transport::new(bundle, url.to_bstring(), self.repo.config.trace_packet());
+            return Ok(self.to_connection_with_transport(Box::new(transport)));
+        }
         #[cfg(feature = "blocking-network-client")]
         let scheme_is_ssh = url.scheme == gix_url::Scheme::Ssh;
         let transport = gix_protocol::transport::connect(
@@ -110,6 +127,10 @@ impl<'repo> Remote<'repo> {
         direction: crate::remote::Direction,
     ) -> Result<(gix_url::Url, gix_protocol::transport::Protocol), Error> {
         fn sanitize(mut url: gix_url::Url) -> Result<gix_url::Url, Error> {
+            #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+            if crate::remote::bundle::path_from_url(&url).is_some() {
+                return Ok(url);
+            }
             if url.scheme == gix_url::Scheme::File {
                 let mut dir = gix_path::to_native_path_on_windows(Cow::Borrowed(url.path.as_ref()));
                 let kind = gix_discover::is_git(dir.as_ref())
@@ -1,7 +1,10 @@
-use super::Error;
+use super::{prepare, Error};
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Pack},
-    Repository,
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Extensions, Key, Pack, Remote},
+    },
+    remote, Repository,
 };
 
 pub fn index_threads(repo: &Repository) -> Result<Option<usize>, Error> {
@@ -24,3 +27,37 @@ pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version,
         .with_leniency(repo.options.lenient_config)?
         .unwrap_or(gix_pack::index::Version::V2))
 }
+
+/// Return `true` if the remote named `name` promises objects which are missing locally, along with the filter to use
+/// when fetching from it.
+pub fn promisor(
+    repo: &Repository,
+    name: Option<&remote::Name<'_>>,
+) -> Result<(bool, Option<remote::fetch::Filter>), prepare::Error> {
+    let Some(name) = name.and_then(remote::Name::as_symbol) else {
+        return Ok((false, None));
+    };
+    let config = &repo.config.resolved;
+    let mut filter = repo.filter_config_section();
+    let is_promisor = config
+        .boolean_filter(format!("remote.{name}.{}", Remote::PROMISOR.name()), &mut filter)
+        .map(|value| Remote::PROMISOR.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?
+        .unwrap_or(false)
+        || config
+            .string(Extensions::PARTIAL_CLONE)
+            .is_some_and(|partial_clone| partial_clone.as_ref() == name);
+    if !is_promisor {
+        return Ok((false, None));
+    }
+    let partial_clone_filter = config
+        .string_filter(
+            format!("remote.{name}.{}", Remote::PARTIAL_CLONE_FILTER.name()),
+            &mut filter,
+        )
+        .map(|value| Remote::PARTIAL_CLONE_FILTER.try_into_filter(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?;
+    Ok((true, partial_clone_filter))
+}
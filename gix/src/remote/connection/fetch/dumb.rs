@@ -0,0 +1,395 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::progress::{Count, DynNestedProgress, Progress};
+use gix_hash::ObjectId;
+use gix_object::{FindExt, Write};
+use gix_protocol::{fetch::RefMap, transport::client::StaticFiles};
+
+use crate::{bstr::ByteSlice, remote};
+
+/// The error returned when fetching from a 'dumb' server as part of [`receive()`](super::Prepare::receive()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("'dumb' servers don't support shallow repositories or changing the shallow boundary")]
+    Shallow,
+    #[error("'dumb' servers don't support filtering objects")]
+    Filter,
+    #[error("Failed to download '{path}' from the 'dumb' server")]
+    Download {
+        path: String,
+        source: gix_protocol::transport::client::Error,
+    },
+    #[error("Could not decompress loose object {id}")]
+    Inflate { id: ObjectId, source: std::io::Error },
+    #[error("Could not decode the header of loose object {id}")]
+    LooseHeader {
+        id: ObjectId,
+        source: gix_object::decode::LooseHeaderDecodeError,
+    },
+    #[error("Loose object {id} claims to be {size} bytes in size, which is more than its {compressed} compressed bytes can hold")]
+    LooseObjectTooLarge { id: ObjectId, size: u64, compressed: usize },
+    #[error("Loose object {id} was expected to be {expected} bytes in size, but was {actual} bytes")]
+    LooseObjectSize {
+        id: ObjectId,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Downloaded loose object {expected} actually hashed to {actual}")]
+    ObjectIdMismatch { expected: ObjectId, actual: ObjectId },
+    #[error("Could not write loose object {id}")]
+    WriteObject {
+        id: ObjectId,
+        source: gix_object::write::Error,
+    },
+    #[error("Could not write pack index downloaded from the 'dumb' server to \"{}\"", path.display())]
+    WriteIndex { path: PathBuf, source: std::io::Error },
+    #[error("Could not open the pack index downloaded from the 'dumb' server")]
+    OpenIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing::Error),
+    #[error("Could not decode object {id} to learn about the objects it refers to")]
+    DecodeObject {
+        id: ObjectId,
+        source: gix_object::decode::Error,
+    },
+    #[error("Object {id} was neither available as loose object nor in any pack of the 'dumb' server")]
+    ObjectNotFound { id: ObjectId },
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+impl gix_protocol::transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Download { source, .. } => source.is_spurious(),
+            _ => false,
+        }
+    }
+}
+
+/// The objects obtained from a 'dumb' server.
+pub(super) struct Outcome {
+    /// The packs that were downloaded as they contained objects we needed.
+    pub write_pack_bundles: Vec<gix_pack::bundle::write::Outcome>,
+    /// The amount of loose objects that were downloaded.
+    pub loose_objects: usize,
+}
+
+/// Download all objects reachable from the remote tips in `ref_map` which aren't yet present in `repo` from a
+/// 'dumb' server that only provides access to its `files`, and write them into the object database of `repo`.
+///
+/// Just like `git`, we walk the history from the tips and try to download each missing object as loose object first,
+/// and if that isn't possible, we download the first pack that contains it according to `objects/info/packs`.
+/// Objects that are present already are assumed to be complete, i.e. all objects they refer to are present as well.
+///
+/// With [included tags](remote::fetch::Tags::Included), annotated tags are only downloaded if the objects they
+/// point to are present after the walk.
+/// Note that alternates of the remote repository, as listed in `objects/info/http-alternates`, aren't supported.
+pub(super) fn fetch(
+    repo: &crate::Repository,
+    files: &mut dyn StaticFiles,
+    ref_map: &RefMap,
+    tags: remote::fetch::Tags,
+    write_pack_options: gix_pack::bundle::write::Options,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    let mut walk = Walk {
+        repo,
+        files,
+        write_pack_options,
+        remote_packs: None,
+        downloaded_packs: Vec::new(),
+        out: Outcome {
+            write_pack_bundles: Vec::new(),
+            loose_objects: 0,
+        },
+    };
+
+    let is_implicit_tag = gix_protocol::fetch::negotiate::make_refmapping_ignore_predicate(tags, ref_map);
+    let wants = ref_map
+        .mappings
+        .iter()
+        .filter(|m| !is_implicit_tag(m))
+        .filter_map(|m| m.remote.as_id().map(ToOwned::to_owned));
+    walk.objects(wants, progress, should_interrupt)?;
+
+    if let Some(tag_map) = super::receive_pack::tags_to_backfill(repo, ref_map) {
+        let tags = tag_map
+            .mappings
+            .iter()
+            .filter_map(|m| m.remote.as_id().map(ToOwned::to_owned));
+        walk.objects(tags, progress, should_interrupt)?;
+    }
+    Ok(walk.out)
+}
+
+struct Walk<'a> {
+    repo: &'a crate::Repository,
+    files: &'a mut dyn StaticFiles,
+    write_pack_options: gix_pack::bundle::write::Options,
+    /// The packs listed in `objects/info/packs` which weren't downloaded yet, loaded on first use.
+    remote_packs: Option<Vec<RemotePack>>,
+    /// The indices of the packs we downloaded, to know which present objects we still have to descend into.
+    downloaded_packs: Vec<TempIndex>,
+    out: Outcome,
+}
+
+/// A pack on the remote, identified by its name without extension, like `pack-<hash>`.
+struct RemotePack {
+    name: String,
+    index: Option<TempIndex>,
+}
+
+/// A pack index downloaded into a temporary file, which is removed when dropped.
+struct TempIndex {
+    path: PathBuf,
+    file: Option<gix_pack::index::File>,
+}
+
+impl TempIndex {
+    fn contains(&self, id: &gix_hash::oid) -> bool {
+        self.file.as_ref().is_some_and(|file| file.lookup(id).is_some())
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        // The file is memory-mapped and has to be closed before it can be removed on all platforms.
+        self.file.take();
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+impl Walk<'_> {
+    /// Obtain all objects reachable from `tips`, stopping at objects that were present before.
+    fn objects(
+        &mut self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), Error> {
+        let mut objects_progress = progress.add_child("download objects".into());
+        objects_progress.init(None, gix_features::progress::count("objects"));
+
+        let mut queue: VecDeque<_> = tips.into_iter().collect();
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut buf = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            let kind = if self.repo.has_object(id) {
+                if !self.downloaded_packs.iter().any(|index| index.contains(&id)) {
+                    continue;
+                }
+                self.repo.objects.find(&id, &mut buf)?.kind
+            } else if let Some(kind) = self.download_loose_object(&id, &mut buf)? {
+                objects_progress.inc();
+                kind
+            } else {
+                self.download_pack_with(&id, progress, should_interrupt)?;
+                objects_progress.inc();
+                self.repo.objects.find(&id, &mut buf)?.kind
+            };
+
+            match gix_object::Data::new(kind, &buf)
+                .decode()
+                .map_err(|source| Error::DecodeObject { id, source })?
+            {
+                gix_object::ObjectRef::Commit(commit) => {
+                    queue.push_back(commit.tree());
+                    queue.extend(commit.parents());
+                }
+                gix_object::ObjectRef::Tree(tree) => queue.extend(
+                    tree.entries
+                        .iter()
+                        .filter(|entry| !entry.mode.is_commit())
+                        .map(|entry| entry.oid.to_owned()),
+                ),
+                gix_object::ObjectRef::Tag(tag) => queue.push_back(tag.target()),
+                gix_object::ObjectRef::Blob(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Download the loose object `id` into `buf`, verify and write it, and return its kind,
+    /// or return `None` if the server doesn't have it as loose object.
+    fn download_loose_object(
+        &mut self,
+        id: &gix_hash::oid,
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<gix_object::Kind>, Error> {
+        let hex = id.to_hex().to_string();
+        let mut compressed = Vec::new();
+        if !read_file(
+            self.files,
+            &format!("objects/{}/{}", &hex[..2], &hex[2..]),
+            &mut compressed,
+        )? {
+            return Ok(None);
+        }
+
+        let inflate_err = |source| Error::Inflate {
+            id: id.to_owned(),
+            source,
+        };
+        let mut input = compressed.as_slice();
+        let mut state = gix_features::zlib::Decompress::new(true);
+        let mut header = [0u8; 64];
+        let header_bytes =
+            gix_features::zlib::stream::inflate::read(&mut input, &mut state, &mut header).map_err(inflate_err)?;
+        let (kind, size, header_size) =
+            gix_object::decode::loose_header(&header[..header_bytes]).map_err(|source| Error::LooseHeader {
+                id: id.to_owned(),
+                source,
+            })?;
+        // Don't trust the header to allocate memory, as even perfectly compressible data can't be compressed more than that.
+        const MAX_DEFLATE_RATIO: u64 = 1032;
+        if (header_size as u64).saturating_add(size) > (compressed.len() as u64).saturating_mul(MAX_DEFLATE_RATIO) {
+            return Err(Error::LooseObjectTooLarge {
+                id: id.to_owned(),
+                size,
+                compressed: compressed.len(),
+            });
+        }
+        let size = size as usize;
+        buf.clear();
+        buf.resize(header_size + size, 0);
+        let mut actual = header_bytes.min(buf.len());
+        buf[..actual].copy_from_slice(&header[..actual]);
+        if header_bytes == header.len() {
+            actual += gix_features::zlib::stream::inflate::read(&mut input, &mut state, &mut buf[actual..])
+                .map_err(inflate_err)?;
+        }
+        if actual != header_size + size {
+            return Err(Error::LooseObjectSize {
+                id: id.to_owned(),
+                expected: size,
+                actual: actual.saturating_sub(header_size),
+            });
+        }
+        buf.drain(..header_size);
+
+        let actual = gix_object::compute_hash(self.repo.object_hash(), kind, buf);
+        if actual != id {
+            return Err(Error::ObjectIdMismatch {
+                expected: id.to_owned(),
+                actual,
+            });
+        }
+        self.repo
+            .objects
+            .write_buf(kind, buf)
+            .map_err(|source| Error::WriteObject {
+                id: id.to_owned(),
+                source,
+            })?;
+        self.out.loose_objects += 1;
+        Ok(Some(kind))
+    }
+
+    /// Download and index the first remote pack that contains `id`.
+    fn download_pack_with(
+        &mut self,
+        id: &gix_hash::oid,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), Error> {
+        let pack_dir = self.repo.objects.store_ref().path().join("pack");
+        if self.remote_packs.is_none() {
+            let mut packs = Vec::new();
+            read_file(self.files, "objects/info/packs", &mut packs)?;
+            // Like `git`, only accept names of the form `pack-<hash>` as they are used as paths, locally and remotely.
+            let hex_len = self.repo.object_hash().len_in_hex();
+            self.remote_packs = Some(
+                packs
+                    .lines()
+                    .filter_map(|line| line.strip_prefix(b"P "))
+                    .filter_map(|name| name.trim_end().strip_suffix(b".pack"))
+                    .filter(|name| {
+                        name.strip_prefix(b"pack-").is_some_and(|hex| {
+                            hex.len() == hex_len && hex.iter().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+                        })
+                    })
+                    .filter_map(|name| name.to_str().ok())
+                    .map(|name| RemotePack {
+                        name: name.to_owned(),
+                        index: None,
+                    })
+                    .collect(),
+            );
+        }
+        let remote_packs = self.remote_packs.as_mut().expect("set above");
+
+        let mut pack_idx = None;
+        for (idx, pack) in remote_packs.iter_mut().enumerate() {
+            if pack.index.is_none() {
+                let index = TempIndex {
+                    path: pack_dir.join(format!("tmp_idx_{}", pack.name)),
+                    file: None,
+                };
+                let exists = {
+                    let mut file = std::fs::File::create(&index.path).map_err(|source| Error::WriteIndex {
+                        path: index.path.clone(),
+                        source,
+                    })?;
+                    read_file(self.files, &format!("objects/pack/{}.idx", pack.name), &mut file)?
+                };
+                if !exists {
+                    continue;
+                }
+                let index = pack.index.insert(index);
+                index.file = Some(gix_pack::index::File::at(&index.path, self.repo.object_hash())?);
+            }
+            if pack.index.as_ref().is_some_and(|index| index.contains(id)) {
+                pack_idx = Some(idx);
+                break;
+            }
+        }
+        let Some(pack) = pack_idx.map(|idx| remote_packs.remove(idx)) else {
+            return Err(Error::ObjectNotFound { id: id.to_owned() });
+        };
+
+        let path = format!("objects/pack/{}.pack", pack.name);
+        let Some(mut data) = self
+            .files
+            .open_file(&path)
+            .map_err(|source| Error::Download { path, source })?
+        else {
+            return Err(Error::ObjectNotFound { id: id.to_owned() });
+        };
+        let bundle = gix_pack::Bundle::write_to_directory(
+            &mut data,
+            Some(&pack_dir),
+            progress,
+            should_interrupt,
+            Some(Box::new({
+                let repo = self.repo.clone();
+                repo.objects
+            })),
+            self.write_pack_options.clone(),
+        )?;
+        self.out.write_pack_bundles.push(bundle);
+        self.downloaded_packs.extend(pack.index);
+        Ok(())
+    }
+}
+
+fn read_file(files: &mut dyn StaticFiles, path: &str, out: &mut dyn std::io::Write) -> Result<bool, Error> {
+    files.read_file(path, out).map_err(|source| Error::Download {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -27,6 +27,11 @@ pub enum Error {
         path: std::path::PathBuf,
         source: std::io::Error,
     },
+    #[error("Failed to write .promisor file at \"{}\"", path.display())]
+    WritePromisorFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
     #[error("None of the refspec(s) {} matched any of the {num_remote_refs} refs on the remote", refspecs.iter().map(|r| r.to_ref().instruction().to_bstring().to_string()).collect::<Vec<_>>().join(", "))]
     NoMapping {
         refspecs: Vec<gix_refspec::RefSpec>,
@@ -36,6 +41,15 @@ pub enum Error {
     RejectShallowRemoteConfig(#[from] config::boolean::Error),
     #[error(transparent)]
     NegotiationAlgorithmConfig(#[from] config::key::GenericErrorWithValue),
+    #[error("Failed to reconnect to the remote to fetch tags that weren't sent along with the pack")]
+    BackfillTagsConnect(#[from] crate::remote::connect::Error),
+    #[error("Failed to perform the handshake to fetch tags that weren't sent along with the pack")]
+    BackfillTagsHandshake(#[from] crate::remote::ref_map::Error),
+    #[error(transparent)]
+    PruneShallow(#[from] crate::shallow::prune::Error),
+    #[cfg(feature = "blocking-network-client")]
+    #[error(transparent)]
+    Dumb(#[from] super::dumb::Error),
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
@@ -43,6 +57,9 @@ impl gix_protocol::transport::IsSpuriousError for Error {
         match self {
             Error::Fetch(err) => err.is_spurious(),
             Error::Client(err) => err.is_spurious(),
+            Error::BackfillTagsHandshake(err) => err.is_spurious(),
+            #[cfg(feature = "blocking-network-client")]
+            Error::Dumb(err) => err.is_spurious(),
             _ => false,
         }
     }
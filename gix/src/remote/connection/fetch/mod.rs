@@ -65,6 +65,16 @@ pub enum Status {
         /// Information collected while updating references.
         update_refs: refs::update::Outcome,
     },
+    /// The remote is a 'dumb' server which only serves files, and the objects we didn't have were downloaded
+    /// individually or as part of the packs that contain them, without any negotiation.
+    ObjectsDownloaded {
+        /// Information collected while writing each downloaded pack and its index.
+        write_pack_bundles: Vec<gix_pack::bundle::write::Outcome>,
+        /// The amount of loose objects that were downloaded.
+        loose_objects: usize,
+        /// Information collected while updating references.
+        update_refs: refs::update::Outcome,
+    },
 }
 
 /// The outcome of receiving a pack via [`Prepare::receive()`].
@@ -104,6 +114,12 @@ pub mod prepare {
         MissingRefSpecs,
         #[error(transparent)]
         RefMap(#[from] crate::remote::ref_map::Error),
+        #[error(transparent)]
+        PromisorConfig(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        PartialCloneFilterConfig(
+            #[from] crate::config::key::GenericErrorWithValue<crate::remote::fetch::filter::parse::Error>,
+        ),
     }
 
     impl gix_protocol::transport::IsSpuriousError for Error {
@@ -142,6 +158,7 @@ where
         if self.remote.refspecs(remote::Direction::Fetch).is_empty() && options.extra_refspecs.is_empty() {
             return Err(prepare::Error::MissingRefSpecs);
         }
+        let (promisor, filter) = config::promisor(self.remote.repo, self.remote.name())?;
         let ref_map = self.ref_map_by_ref(progress, options).await?;
         Ok(Prepare {
             con: Some(self),
@@ -150,6 +167,8 @@ where
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
             shallow: Default::default(),
+            promisor,
+            filter,
         })
     }
 }
@@ -162,9 +181,34 @@ where
     pub fn ref_map(&self) -> &RefMap {
         &self.ref_map
     }
+
+    /// Ask the remote for the bundles it advertises via the `bundle-uri` command, or return `None` if it doesn't
+    /// advertise any, which is always the case if it doesn't speak protocol V2.
+    ///
+    /// With the `bundle` feature, the bundles can be unbundled with `Repository::unbundle_from_list()` before
+    /// [receiving](Self::receive()) the pack, which then only contains the objects that aren't yet present.
+    #[gix_protocol::maybe_async::maybe_async]
+    pub async fn bundle_list(
+        &mut self,
+    ) -> Result<Option<gix_protocol::bundle_uri::List>, gix_protocol::bundle_uri::Error> {
+        let con = self.con.as_mut().expect("receive() wasn't called yet");
+        let handshake = con.handshake.as_ref().expect("receive() wasn't called yet");
+        if handshake.server_protocol_version != gix_protocol::transport::Protocol::V2
+            || !handshake
+                .capabilities
+                .contains(gix_protocol::Command::BundleUri.as_str())
+        {
+            return Ok(None);
+        }
+        let list = gix_protocol::bundle_uri(&mut con.transport.inner, &handshake.capabilities, con.trace).await?;
+        Ok((!list.bundles.is_empty()).then_some(list))
+    }
 }
 
 mod config;
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod dumb;
 mod receive_pack;
 ///
 #[path = "update_refs/mod.rs"]
@@ -181,6 +225,8 @@ where
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
     shallow: remote::fetch::Shallow,
+    promisor: bool,
+    filter: Option<remote::fetch::Filter>,
 }
 
 /// Builder
@@ -222,4 +268,17 @@ where
         self.shallow = shallow;
         self
     }
+
+    /// Ask the remote to omit objects matching `filter`, or set it to `None` to receive all objects.
+    ///
+    /// Packs received with a filter, or from a promisor remote, are marked with a `.promisor` file as objects missing
+    /// from them are promised by the remote. Note that the remote must be configured as promisor remote for
+    /// these objects to be fetched lazily, which [`PrepareFetch::with_filter()`](crate::clone::PrepareFetch::with_filter())
+    /// takes care of.
+    ///
+    /// Defaults to `remote.<name>.partialCloneFilter` if the remote is a promisor remote.
+    pub fn with_filter(mut self, filter: Option<remote::fetch::Filter>) -> Self {
+        self.filter = filter;
+        self
+    }
 }
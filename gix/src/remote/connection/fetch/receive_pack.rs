@@ -11,7 +11,7 @@ use crate::{
     },
 };
 use gix_odb::store::RefreshMode;
-use gix_protocol::fetch::negotiate;
+use gix_protocol::fetch::{negotiate, refmap, RefMap};
 use gix_protocol::{fetch::Arguments, transport::client::Transport};
 use std::ops::DerefMut;
 use std::path::PathBuf;
@@ -46,6 +46,32 @@ where
     /// A known application for this behaviour is in `remote-helper` implementations which should send this path via `lock <path>` to stdout
     /// to inform git about the file that it will remove once it updated the refs accordingly.
     ///
+    /// ### Partial Clones
+    ///
+    /// If a [filter](Self::with_filter()) is set or the remote is a promisor remote, the received pack is marked with
+    /// a `.promisor` file which lists the references that were fetched, just like `git` does.
+    ///
+    /// ### Tags
+    ///
+    /// With [included tags](remote::fetch::Tags::Included), the remote only sends the annotated tags which point to objects
+    /// in the pack it sends. Those pointing to objects that are already present, which is common when the history is shallow,
+    /// are fetched in a follow-up fetch without changing the shallow boundary, just like `git` does.
+    /// If that fetch produces a pack but the first one didn't, it is the one reported in [`Status::Change`].
+    /// Note that with protocol V1, the remote has to be connected to again, which isn't possible if only
+    /// the `async-network-client` feature is enabled, so these tags aren't fetched then.
+    ///
+    /// ### 'Dumb' Servers
+    ///
+    /// If the remote is a 'dumb' HTTP server which only serves the files of a repository, there is nothing to negotiate
+    /// and the history is walked from the remote tips instead, downloading each missing object as loose object or as part
+    /// of the pack that contains it, as reported in [`Status::ObjectsDownloaded`].
+    /// This is only possible with the `blocking-network-client` feature, and neither shallow repositories nor filters are supported.
+    ///
+    /// ### Shallow Clones
+    ///
+    /// Commits in the `shallow` file which aren't present anymore are [removed](crate::Repository::prune_shallow_commits()) from it
+    /// once the refs were updated.
+    ///
     /// ### Deviation
     ///
     /// When **updating refs**, the `git-fetch` docs state the following:
@@ -68,7 +94,7 @@ where
     /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
     ///
     #[gix_protocol::maybe_async::maybe_async]
-    pub async fn receive<P>(mut self, progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
+    pub async fn receive<P>(mut self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
     where
         P: gix_features::progress::NestedProgress,
         P::SubProgress: 'static,
@@ -94,6 +120,58 @@ where
                 remote: ref_map.object_hash,
             });
         }
+        let write_pack_options = gix_pack::bundle::write::Options {
+            thread_limit: config::index_threads(repo)?,
+            index_version: config::pack_index_version(repo)?,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            object_hash: con.remote.repo.object_hash(),
+        };
+
+        #[cfg(feature = "blocking-network-client")]
+        if let Some(files) = con.transport.inner.static_files() {
+            if repo.is_shallow() || !matches!(self.shallow, fetch::Shallow::NoChange) {
+                return Err(super::dumb::Error::Shallow.into());
+            }
+            if self.filter.is_some() {
+                return Err(super::dumb::Error::Filter.into());
+            }
+            let mut downloaded = match self.dry_run {
+                fetch::DryRun::No => Some(super::dumb::fetch(
+                    repo,
+                    files,
+                    ref_map,
+                    con.remote.fetch_tags,
+                    write_pack_options,
+                    &mut progress,
+                    should_interrupt,
+                )?),
+                fetch::DryRun::Yes => None,
+            };
+            let update_refs = self.update_refs(
+                con.remote,
+                downloaded
+                    .iter_mut()
+                    .flat_map(|downloaded| downloaded.write_pack_bundles.iter_mut()),
+            )?;
+            return Ok(Outcome {
+                handshake,
+                ref_map: std::mem::take(&mut self.ref_map),
+                status: match downloaded {
+                    Some(downloaded) if downloaded.loose_objects != 0 || !downloaded.write_pack_bundles.is_empty() => {
+                        Status::ObjectsDownloaded {
+                            write_pack_bundles: downloaded.write_pack_bundles,
+                            loose_objects: downloaded.loose_objects,
+                            update_refs,
+                        }
+                    }
+                    _ => Status::NoPackReceived {
+                        dry_run: matches!(self.dry_run, fetch::DryRun::Yes),
+                        negotiate: None,
+                        update_refs,
+                    },
+                },
+            });
+        }
 
         let fetch_options = gix_protocol::fetch::Options {
             shallow_file: repo.shallow_file(),
@@ -106,6 +184,7 @@ where
                 .map(|val| Clone::REJECT_SHALLOW.enrich_error(val))
                 .transpose()?
                 .unwrap_or(false),
+            filter: self.filter.as_ref(),
         };
         let context = gix_protocol::fetch::Context {
             handshake: &mut handshake,
@@ -114,21 +193,15 @@ where
             trace_packetlines: con.trace,
         };
 
-        let negotiator = repo
-            .config
-            .resolved
-            .string(Fetch::NEGOTIATION_ALGORITHM)
-            .map(|n| Fetch::NEGOTIATION_ALGORITHM.try_into_negotiation_algorithm(n))
-            .transpose()
-            .with_leniency(repo.config.lenient_config)?
-            .unwrap_or(Algorithm::Consecutive)
-            .into_negotiator();
+        let negotiator = negotiator(repo)?;
         let graph_repo = {
             let mut r = repo.clone();
             // assure that checking for unknown server refs doesn't trigger ODB refreshes.
             r.objects.refresh = RefreshMode::Never;
             // we cache everything of importance in the graph and thus don't need an object cache.
             r.objects.unset_object_cache();
+            // objects missing in partial clones must not be fetched while negotiating.
+            r.objects.set_promisor(None);
             r
         };
         let cache = graph_repo.commit_graph_if_enabled().ok().flatten();
@@ -146,12 +219,6 @@ where
             open_options: repo.options.clone(),
         };
 
-        let write_pack_options = gix_pack::bundle::write::Options {
-            thread_limit: config::index_threads(repo)?,
-            index_version: config::pack_index_version(repo)?,
-            iteration_mode: gix_pack::data::input::Mode::Verify,
-            object_hash: con.remote.repo.object_hash(),
-        };
         let mut write_pack_bundle = None;
 
         let res = gix_protocol::fetch(
@@ -168,7 +235,7 @@ where
                             let repo = repo.clone();
                             repo.objects
                         })),
-                        write_pack_options,
+                        write_pack_options.clone(),
                     )?;
                     may_read_to_end = true;
                     Some(res)
@@ -177,7 +244,7 @@ where
                 };
                 Ok(may_read_to_end)
             },
-            progress,
+            &mut progress,
             should_interrupt,
             context,
             fetch_options,
@@ -188,33 +255,79 @@ where
             rounds: v.negotiate.rounds,
         });
 
+        let mut backfilled = None;
+        let tag_map = match self.dry_run {
+            fetch::DryRun::No if matches!(con.remote.fetch_tags, remote::fetch::Tags::Included) => {
+                tags_to_backfill(repo, ref_map)
+            }
+            _ => None,
+        };
+        if let Some(tag_map) = tag_map {
+            let is_v1_connection_used_up = handshake.server_protocol_version == gix_protocol::transport::Protocol::V1
+                && write_pack_bundle.is_some()
+                && con.transport.inner.connection_persists_across_multiple_requests();
+            backfilled = if is_v1_connection_used_up {
+                #[cfg(not(any(feature = "blocking-network-client", feature = "async-network-client-async-std")))]
+                {
+                    None
+                }
+                #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-async-std"))]
+                {
+                    let mut backfill_con = con.remote.connect(remote::Direction::Fetch).await?;
+                    backfill_con.ref_map_by_ref(&mut progress, Default::default()).await?;
+                    let mut backfill_handshake = backfill_con.handshake.take().expect("set by ref_map()");
+                    backfill_tags(
+                        repo,
+                        &graph_repo,
+                        &tag_map,
+                        gix_protocol::fetch::Context {
+                            handshake: &mut backfill_handshake,
+                            transport: &mut backfill_con.transport.inner,
+                            user_agent: repo.config.user_agent_tuple(),
+                            trace_packetlines: backfill_con.trace,
+                        },
+                        self.filter.as_ref(),
+                        write_pack_options,
+                        &mut progress,
+                        should_interrupt,
+                    )
+                    .await?
+                }
+            } else {
+                backfill_tags(
+                    repo,
+                    &graph_repo,
+                    &tag_map,
+                    gix_protocol::fetch::Context {
+                        handshake: &mut handshake,
+                        transport: &mut con.transport.inner,
+                        user_agent: repo.config.user_agent_tuple(),
+                        trace_packetlines: con.trace,
+                    },
+                    self.filter.as_ref(),
+                    write_pack_options,
+                    &mut progress,
+                    should_interrupt,
+                )
+                .await?
+            };
+        }
+
         if matches!(handshake.server_protocol_version, gix_protocol::transport::Protocol::V2) {
             gix_protocol::indicate_end_of_interaction(&mut con.transport.inner, con.trace)
                 .await
                 .ok();
         }
 
-        let update_refs = refs::update(
-            repo,
-            self.reflog_message
-                .take()
-                .unwrap_or_else(|| RefLogMessage::Prefixed { action: "fetch".into() }),
-            &self.ref_map.mappings,
-            con.remote.refspecs(remote::Direction::Fetch),
-            &self.ref_map.extra_refspecs,
-            con.remote.fetch_tags,
-            self.dry_run,
-            self.write_packed_refs,
+        let (mut write_pack_bundle, negotiate, mut backfilled_pack_bundle) = match (write_pack_bundle, backfilled) {
+            (None, Some((bundle, negotiate))) => (Some(bundle), Some(negotiate), None),
+            (bundle, backfilled) => (bundle, negotiate, backfilled.map(|(bundle, _)| bundle)),
+        };
+        let update_refs = self.update_refs(
+            con.remote,
+            write_pack_bundle.iter_mut().chain(backfilled_pack_bundle.iter_mut()),
         )?;
 
-        if let Some(bundle) = write_pack_bundle.as_mut() {
-            if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
-                if let Some(path) = bundle.keep_path.take() {
-                    std::fs::remove_file(&path).map_err(|err| Error::RemovePackKeepFile { path, source: err })?;
-                }
-            }
-        }
-
         let out = Outcome {
             handshake,
             ref_map: std::mem::take(&mut self.ref_map),
@@ -235,6 +348,164 @@ where
     }
 }
 
+impl<T> Prepare<'_, '_, T>
+where
+    T: Transport,
+{
+    /// Update the references of `remote` with what we fetched, and finish the `bundles` that were written to make that possible.
+    fn update_refs<'a>(
+        &mut self,
+        remote: &crate::Remote<'_>,
+        bundles: impl IntoIterator<Item = &'a mut gix_pack::bundle::write::Outcome>,
+    ) -> Result<refs::update::Outcome, Error> {
+        let repo = remote.repo;
+        let update_refs = refs::update(
+            repo,
+            self.reflog_message
+                .take()
+                .unwrap_or_else(|| RefLogMessage::Prefixed { action: "fetch".into() }),
+            &self.ref_map.mappings,
+            remote.refspecs(remote::Direction::Fetch),
+            &self.ref_map.extra_refspecs,
+            remote.fetch_tags,
+            self.dry_run,
+            self.write_packed_refs,
+        )?;
+
+        for bundle in bundles {
+            if self.promisor || self.filter.is_some() {
+                if let Some(index_path) = bundle.index_path.as_deref() {
+                    let refs = self
+                        .ref_map
+                        .mappings
+                        .iter()
+                        .filter_map(|m| Some((m.remote.as_id()?.to_owned(), m.remote.as_name()?)));
+                    gix_odb::promisor::write_file(index_path, refs).map_err(|source| Error::WritePromisorFile {
+                        path: gix_odb::promisor::file_path(index_path),
+                        source,
+                    })?;
+                }
+            }
+            if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
+                if let Some(path) = bundle.keep_path.take() {
+                    std::fs::remove_file(&path).map_err(|err| Error::RemovePackKeepFile { path, source: err })?;
+                }
+            }
+        }
+        if matches!(self.dry_run, fetch::DryRun::No) && repo.is_shallow() {
+            repo.prune_shallow_commits()?;
+        }
+        Ok(update_refs)
+    }
+}
+
+fn negotiator(repo: &crate::Repository) -> Result<Box<dyn gix_negotiate::Negotiator>, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .string(Fetch::NEGOTIATION_ALGORITHM)
+        .map(|n| Fetch::NEGOTIATION_ALGORITHM.try_into_negotiation_algorithm(n))
+        .transpose()
+        .with_leniency(repo.config.lenient_config)?
+        .unwrap_or(Algorithm::Consecutive)
+        .into_negotiator())
+}
+
+/// Return a ref-map with the mappings of implicit tags whose objects weren't sent by the remote even though the objects
+/// they point to are present, or `None` if there is no such tag.
+pub(super) fn tags_to_backfill(repo: &crate::Repository, ref_map: &RefMap) -> Option<RefMap> {
+    let is_implicit_tag = negotiate::make_refmapping_ignore_predicate(remote::fetch::Tags::Included, ref_map);
+    let mappings: Vec<_> = ref_map
+        .mappings
+        .iter()
+        .filter(|m| {
+            is_implicit_tag(m)
+                && match &m.remote {
+                    refmap::Source::Ref(gix_protocol::handshake::Ref::Peeled { tag, object, .. }) => {
+                        !repo.has_object(tag) && repo.has_object(object)
+                    }
+                    _ => false,
+                }
+        })
+        .cloned()
+        .collect();
+    (!mappings.is_empty()).then(|| RefMap {
+        mappings,
+        refspecs: ref_map.refspecs.clone(),
+        extra_refspecs: ref_map.extra_refspecs.clone(),
+        fixes: Vec::new(),
+        remote_refs: Vec::new(),
+        object_hash: ref_map.object_hash,
+    })
+}
+
+/// Fetch the tags in `tag_map` without changing the shallow boundary, and return the pack that was written along with
+/// information about the negotiation, if there was anything to fetch.
+#[allow(clippy::too_many_arguments)]
+#[gix_protocol::maybe_async::maybe_async]
+async fn backfill_tags<T, P>(
+    repo: &crate::Repository,
+    graph_repo: &crate::Repository,
+    tag_map: &RefMap,
+    context: gix_protocol::fetch::Context<'_, T>,
+    filter: Option<&remote::fetch::Filter>,
+    write_pack_options: gix_pack::bundle::write::Options,
+    progress: P,
+    should_interrupt: &AtomicBool,
+) -> Result<Option<(gix_pack::bundle::write::Outcome, outcome::Negotiate)>, Error>
+where
+    T: Transport,
+    P: gix_features::progress::NestedProgress,
+    P::SubProgress: 'static,
+{
+    let cache = graph_repo.commit_graph_if_enabled().ok().flatten();
+    let mut graph = graph_repo.revision_graph(cache.as_ref());
+    let mut negotiate = Negotiate {
+        objects: &graph_repo.objects,
+        refs: &graph_repo.refs,
+        graph: &mut graph,
+        alternates: repo.objects.store_ref().alternate_db_paths()?,
+        ref_map: tag_map,
+        shallow: &gix_protocol::fetch::Shallow::NoChange,
+        tags: remote::fetch::Tags::None,
+        negotiator: negotiator(repo)?,
+        open_options: repo.options.clone(),
+    };
+    let mut write_pack_bundle = None;
+    let res = gix_protocol::fetch(
+        &mut negotiate,
+        |reader, progress, should_interrupt| -> Result<bool, gix_pack::bundle::write::Error> {
+            write_pack_bundle = Some(gix_pack::Bundle::write_to_directory(
+                reader,
+                Some(&repo.objects.store_ref().path().join("pack")),
+                progress,
+                should_interrupt,
+                Some(Box::new({
+                    let repo = repo.clone();
+                    repo.objects
+                })),
+                write_pack_options,
+            )?);
+            Ok(true)
+        },
+        progress,
+        should_interrupt,
+        context,
+        gix_protocol::fetch::Options {
+            shallow_file: repo.shallow_file(),
+            shallow: &gix_protocol::fetch::Shallow::NoChange,
+            tags: remote::fetch::Tags::None,
+            reject_shallow_remote: false,
+            filter,
+        },
+    )
+    .await?;
+    Ok(write_pack_bundle.zip(res.map(|v| outcome::Negotiate {
+        graph: graph.detach(),
+        rounds: v.negotiate.rounds,
+    })))
+}
+
 struct Negotiate<'a, 'b, 'c> {
     objects: &'a crate::OdbHandle,
     refs: &'a gix_ref::file::Store,
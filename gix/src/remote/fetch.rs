@@ -7,6 +7,8 @@ pub mod negotiate {
     pub use gix_protocol::fetch::negotiate::Error;
 }
 
+#[cfg(feature = "blocking-network-client")]
+pub use super::connection::fetch::dumb;
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 pub use super::connection::fetch::{
     outcome, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
@@ -32,6 +34,6 @@ pub(crate) enum WritePackedRefs {
     Only,
 }
 
+pub use gix_protocol::fetch::{filter, Filter, Shallow, Tags};
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 pub use gix_protocol::fetch::{refmap, RefMap};
-pub use gix_protocol::fetch::{Shallow, Tags};
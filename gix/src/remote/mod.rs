@@ -52,6 +52,9 @@ pub mod fetch;
 ///
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 pub mod connect;
+///
+#[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+pub mod bundle;
 
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 mod connection;
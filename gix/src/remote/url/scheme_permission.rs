@@ -110,8 +110,10 @@ impl SchemePermission {
                 use gix_url::Scheme::*;
                 match scheme {
                     File | Git | Ssh | Http | Https => true,
-                    Ext(_) => false,
-                    // TODO: figure out what 'ext' really entails, and what 'other' protocols are which aren't representable for us yet
+                    // `ext` can run arbitrary commands, so it must be allowed explicitly.
+                    Ext(name) if name == "ext" => false,
+                    // Other schemes are handled by remote helpers and are only allowed if the user initiated the operation.
+                    Ext(_) => Allow::User.to_bool(self.user_allowed),
                 }
             },
             |allow| allow.to_bool(self.user_allowed),
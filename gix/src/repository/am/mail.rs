@@ -0,0 +1,350 @@
+use crate::bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// A patch sent by mail, as parsed from a single message of an mbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+    /// The author of the patch, as taken from the `From` and `Date` headers.
+    pub author: gix_actor::Signature,
+    /// The subject without prefixes like `[PATCH 1/2]` or `Re:`.
+    pub subject: BString,
+    /// The message of the commit to create, which is the subject followed by the body of the mail up to the patch.
+    pub message: BString,
+    /// The patches to apply, which are empty if the mail doesn't contain a patch.
+    pub patches: Vec<gix_diff::patch::File>,
+}
+
+///
+pub mod parse {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Mail::from_bytes()`](super::Mail::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The mail has no 'From' header to obtain the author from")]
+        MissingAuthor,
+        #[error("The date '{date}' of the mail could not be parsed")]
+        Date {
+            date: BString,
+            source: gix_date::parse::Error,
+        },
+        #[error(transparent)]
+        Patch(#[from] gix_diff::patch::parse::Error),
+    }
+}
+
+/// Split `mbox` into the mails it contains, each starting with its `From ` line.
+///
+/// If `mbox` doesn't start with a `From ` line, it is assumed to be a single mail.
+pub fn split_mbox(mbox: &[u8]) -> Vec<&[u8]> {
+    let mut mails = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    for line in mbox.lines_with_terminator() {
+        if pos != 0 && is_from_line(line) {
+            mails.push(&mbox[start..pos]);
+            start = pos;
+        }
+        pos += line.len();
+    }
+    if start < mbox.len() {
+        mails.push(&mbox[start..]);
+    }
+    mails
+}
+
+/// Return `true` if `line` separates mails in an mbox, like `From 1234abcd Mon Sep 17 00:00:00 2001`,
+/// using the same heuristic as `git mailsplit`.
+fn is_from_line(line: &[u8]) -> bool {
+    let line = line.trim_end_with(|c| c == '\n' || c == '\r');
+    if line.len() < 20 || !line.starts_with(b"From ") {
+        return false;
+    }
+    let Some(colon) = line[..line.len() - 1].rfind_byte(b':').filter(|pos| *pos >= 9) else {
+        return false;
+    };
+    let digit = |pos: usize| line.get(pos).is_some_and(u8::is_ascii_digit);
+    if ![colon - 4, colon - 2, colon - 1, colon + 1, colon + 2]
+        .into_iter()
+        .all(digit)
+    {
+        return false;
+    }
+    line.get(colon + 3..)
+        .and_then(|year| year.trim_start().split(|b| !b.is_ascii_digit()).next())
+        .and_then(|year| year.to_str().ok()?.parse::<u32>().ok())
+        .is_some_and(|year| year > 90)
+}
+
+impl Mail {
+    /// Parse a single mail as produced by `git format-patch`, similar to `git mailinfo`.
+    ///
+    /// The author is taken from the `From` and `Date` headers, which can be overridden by the same headers at the
+    /// beginning of the body. Encoded words in headers as well as bodies in the `quoted-printable` or `base64`
+    /// transfer encodings are decoded.
+    /// The commit message ends at the `---` line that separates it from the diff statistics, or where the patch begins.
+    pub fn from_bytes(mail: &[u8]) -> Result<Self, parse::Error> {
+        let mut lines = mail.lines_with_terminator().peekable();
+        if lines.peek().is_some_and(|line| is_from_line(line)) {
+            lines.next();
+        }
+
+        let mut headers = Vec::<(BString, BString)>::new();
+        for line in lines.by_ref() {
+            let line = line.trim_end_with(|c| c == '\n' || c == '\r');
+            if line.is_empty() {
+                break;
+            }
+            match (line[0], headers.last_mut()) {
+                (b' ' | b'\t', Some((_, value))) => {
+                    value.push(b'\n');
+                    value.push_str(line);
+                }
+                _ => {
+                    if let Some((name, value)) = line.split_once_str(":") {
+                        headers.push((name.trim().to_ascii_lowercase().into(), value.trim().into()));
+                    }
+                }
+            }
+        }
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| decode_header(value.as_bstr()))
+        };
+
+        let mut body = lines.flat_map(|line| line.iter().copied()).collect::<Vec<u8>>();
+        match header("content-transfer-encoding").map(|enc| enc.to_ascii_lowercase()) {
+            Some(enc) if enc == b"quoted-printable" => body = decode_quoted_printable(&body, false),
+            Some(enc) if enc == b"base64" => body = decode_base64(&body),
+            _ => {}
+        }
+
+        let mut from = header("from");
+        let mut subject = header("subject").unwrap_or_default();
+        let mut date = header("date");
+        let (message, patch) = split_body(&body);
+        let mut message = message.as_bstr();
+        loop {
+            let line = message.lines_with_terminator().next().unwrap_or_default();
+            let Some((name, value)) = line.split_once_str(":") else {
+                break;
+            };
+            let value = decode_header(value.trim().as_bstr());
+            match name.to_ascii_lowercase().as_slice() {
+                b"from" => from = Some(value),
+                b"subject" => subject = value,
+                b"date" => date = Some(value),
+                _ => break,
+            }
+            message = message[line.len()..].as_bstr();
+        }
+
+        let (name, email) = parse_address(from.ok_or(parse::Error::MissingAuthor)?.as_bstr());
+        let time = match date {
+            Some(date) => gix_date::parse(date.to_str_lossy().as_ref(), Some(std::time::SystemTime::now()))
+                .map_err(|source| parse::Error::Date { date, source })?,
+            None => gix_date::Time::now_local_or_utc(),
+        };
+        let subject = cleanup_subject(subject.as_bstr());
+        let body = message.trim();
+        let mut commit_message = subject.clone();
+        commit_message.push(b'\n');
+        if !body.is_empty() {
+            commit_message.push(b'\n');
+            commit_message.push_str(body);
+            commit_message.push(b'\n');
+        }
+
+        Ok(Mail {
+            author: gix_actor::Signature { name, email, time },
+            subject,
+            message: commit_message,
+            patches: gix_diff::patch::parse(patch)?,
+        })
+    }
+}
+
+/// Split `body` into the commit message and the part that contains the patch.
+fn split_body(body: &[u8]) -> (&[u8], &[u8]) {
+    let mut pos = 0;
+    for line in body.lines_with_terminator() {
+        let trimmed = line.trim_end();
+        if trimmed == b"---" {
+            return (&body[..pos], &body[pos + line.len()..]);
+        }
+        if line.starts_with(b"diff -") || line.starts_with(b"Index: ") || line.starts_with(b"--- ") {
+            return (&body[..pos], &body[pos..]);
+        }
+        pos += line.len();
+    }
+    (body, &[])
+}
+
+/// Remove prefixes like `Re:` and `[PATCH 1/2]` from `subject`, along with surrounding whitespace.
+fn cleanup_subject(subject: &BStr) -> BString {
+    let mut subject = subject.trim();
+    loop {
+        if subject.len() >= 3 && subject[..3].eq_ignore_ascii_case(b"re:") {
+            subject = subject[3..].trim_start();
+        } else if subject.starts_with(b"[") {
+            match subject.find_byte(b']') {
+                Some(end) => subject = subject[end + 1..].trim_start(),
+                None => break,
+            }
+        } else {
+            break;
+        }
+    }
+    subject
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(&b' ')
+        .into()
+}
+
+/// Split an address like `Name <email>`, `"Name" <email>` or `email (Name)` into name and email.
+fn parse_address(address: &BStr) -> (BString, BString) {
+    let address = address.trim();
+    if let Some((name, rest)) = address.split_once_str("<") {
+        let email = rest.split_once_str(">").map_or(rest, |(email, _)| email);
+        return (unquote(name.trim().as_bstr()), email.trim().into());
+    }
+    if let Some((email, rest)) = address.split_once_str("(") {
+        let name = rest.rsplit_once_str(")").map_or(rest, |(name, _)| name);
+        return (name.trim().into(), email.trim().into());
+    }
+    (address.into(), address.into())
+}
+
+fn unquote(name: &BStr) -> BString {
+    let Some(inner) = name.strip_prefix(b"\"").and_then(|name| name.strip_suffix(b"\"")) else {
+        return name.into();
+    };
+    let mut out = BString::default();
+    let mut bytes = inner.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => out.extend(bytes.next()),
+            b => out.push(b),
+        }
+    }
+    out
+}
+
+/// Decode a header `value` which may be folded and may contain encoded words as defined in RFC 2047.
+fn decode_header(value: &BStr) -> BString {
+    let mut out = BString::default();
+    let mut rest = value.as_bytes();
+    let mut previous_was_encoded = false;
+    while !rest.is_empty() {
+        let whitespace_len = rest.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        let (whitespace, after_whitespace) = rest.split_at(whitespace_len);
+        if let Some((decoded, consumed)) = decode_encoded_word(after_whitespace) {
+            if !previous_was_encoded && !whitespace.is_empty() {
+                out.push(b' ');
+            }
+            out.extend_from_slice(&decoded);
+            rest = &after_whitespace[consumed..];
+            previous_was_encoded = true;
+            continue;
+        }
+        if !whitespace.is_empty() {
+            out.push(b' ');
+            rest = after_whitespace;
+            previous_was_encoded = false;
+            continue;
+        }
+        let word_len = rest.iter().position(u8::is_ascii_whitespace).unwrap_or(rest.len());
+        out.extend_from_slice(&rest[..word_len]);
+        rest = &rest[word_len..];
+        previous_was_encoded = false;
+    }
+    out
+}
+
+/// Decode an encoded word like `=?UTF-8?q?text?=` at the start of `input`, and return the decoded bytes
+/// along with the amount of bytes that were consumed.
+fn decode_encoded_word(input: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let rest = input.strip_prefix(b"=?")?;
+    let charset_end = rest.find_byte(b'?')?;
+    let rest = &rest[charset_end + 1..];
+    let (&encoding, rest) = rest.split_first()?;
+    let rest = rest.strip_prefix(b"?")?;
+    let text_end = rest.find("?=")?;
+    let text = &rest[..text_end];
+    let consumed = 2 + charset_end + 1 + 2 + text_end + 2;
+    let decoded = match encoding.to_ascii_lowercase() {
+        b'q' => decode_quoted_printable(text, true),
+        b'b' => decode_base64(text),
+        _ => return None,
+    };
+    Some((decoded, consumed))
+}
+
+/// Decode `input` in the quoted-printable encoding, or in the `Q` encoding of RFC 2047 if `in_header` is `true`.
+fn decode_quoted_printable(input: &[u8], in_header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut pos = 0;
+    while pos < input.len() {
+        match input[pos] {
+            b'_' if in_header => out.push(b' '),
+            b'=' => {
+                let hex = input.get(pos + 1..pos + 3);
+                if let Some(byte) = hex
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    out.push(byte);
+                    pos += 3;
+                    continue;
+                }
+                // A soft line break, which consumes exactly one line ending.
+                let rest = &input[pos + 1..];
+                let line_break_len = if rest.starts_with(b"\r\n") {
+                    2
+                } else if rest.starts_with(b"\n") {
+                    1
+                } else {
+                    0
+                };
+                if line_break_len != 0 {
+                    pos += 1 + line_break_len;
+                    continue;
+                }
+                out.push(b'=');
+            }
+            b => out.push(b),
+        }
+        pos += 1;
+    }
+    out
+}
+
+fn decode_base64(input: &[u8]) -> Vec<u8> {
+    let value = |b: u8| -> Option<u32> {
+        Some(match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    };
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for b in input.iter().copied().filter_map(value) {
+        acc = (acc << 6) | b;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    out
+}
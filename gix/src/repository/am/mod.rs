@@ -0,0 +1,392 @@
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+use gix_index::entry::Stage;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    repository::{apply_patches, reset},
+    Repository,
+};
+
+///
+pub mod mail;
+pub use mail::{split_mbox, Mail};
+
+/// The name of the directory in the `.git` directory that holds the state of [`Repository::am()`], just like in `git am`.
+const STATE_DIR: &str = "rebase-apply";
+
+/// Options for use in [`Repository::am()`].
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Options {
+    /// If `true`, patches that don't apply are merged with the file they were made for, similar to `git am --3way`.
+    ///
+    /// If the merge has conflicts, applying patches stops so they can be resolved and committed
+    /// with [`Repository::am_continue()`].
+    #[cfg(feature = "merge")]
+    pub three_way: bool,
+}
+
+/// The reason for applying patches with [`Repository::am()`] to stop before all of them were applied.
+#[derive(Debug)]
+pub enum StopReason {
+    /// The patch could not be applied, and nothing was changed.
+    DoesNotApply(Box<apply_patches::Error>),
+    /// The patch was merged, but the files at the given paths have conflicts.
+    Conflicts {
+        /// The paths of the files with conflicts, as marked in the index.
+        paths: Vec<BString>,
+    },
+    /// The mail doesn't contain a patch.
+    EmptyPatch,
+}
+
+/// The outcome of [`Repository::am()`], [`Repository::am_continue()`] and [`Repository::am_skip()`].
+#[derive(Debug)]
+pub enum Outcome {
+    /// All patches were applied and committed.
+    Applied {
+        /// The ids of the commits that were created, in order.
+        commits: Vec<ObjectId>,
+    },
+    /// A patch could not be applied, and the operation is still in progress.
+    ///
+    /// It can be finished with [`Repository::am_continue()`] after resolving the problem and staging the result
+    /// in the index, with [`Repository::am_skip()`] to skip the patch, or with [`Repository::am_abort()`].
+    Stopped {
+        /// The ids of the commits that were created before stopping, in order.
+        commits: Vec<ObjectId>,
+        /// The 1-based number of the patch that couldn't be applied.
+        patch_number: usize,
+        /// The subject of the patch that couldn't be applied.
+        subject: BString,
+        /// Why the patch couldn't be applied.
+        reason: StopReason,
+    },
+}
+
+/// The error returned by [`Repository::am()`] and its related methods.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot apply patches to the worktree of a bare repository")]
+    BareRepository,
+    #[error("Patches are already being applied, continue, skip or abort first")]
+    InProgress,
+    #[error("No patches are being applied")]
+    NotInProgress,
+    #[error("The state in '{}' is invalid", dir.display())]
+    InvalidState { dir: PathBuf },
+    #[error("There are unresolved conflicts in {}", paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    UnresolvedConflicts { paths: Vec<BString> },
+    #[error("There are no changes to commit, the patch can be skipped instead")]
+    NoChanges,
+    #[error("Could not parse the mail of patch {patch_number}")]
+    ParseMail {
+        patch_number: usize,
+        source: mail::parse::Error,
+    },
+    #[error("Could not access '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    ApplyPatches(apply_patches::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error("Committer identity is not configured")]
+    CommitterMissing,
+    #[error(transparent)]
+    CommitterTime(#[from] crate::config::time::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    Reset(#[from] reset::Error),
+}
+
+/// The progress of applying patches, as stored in the state directory.
+struct State {
+    dir: PathBuf,
+    /// The number of the next patch to apply.
+    next: usize,
+    /// The number of the last patch.
+    last: usize,
+    #[cfg_attr(not(feature = "merge"), allow(dead_code))]
+    three_way: bool,
+}
+
+impl State {
+    fn load(dir: PathBuf) -> Result<Self, Error> {
+        let number = |name: &str| -> Result<usize, Error> {
+            read(&dir.join(name))?
+                .trim()
+                .to_str()
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| Error::InvalidState { dir: dir.clone() })
+        };
+        let next = number("next")?;
+        let last = number("last")?;
+        let three_way = read(&dir.join("threeway"))?.trim() == b"t";
+        Ok(State {
+            next,
+            last,
+            three_way,
+            dir,
+        })
+    }
+
+    fn mail_path(&self, number: usize) -> PathBuf {
+        self.dir.join(format!("{number:04}"))
+    }
+
+    fn mail(&self) -> Result<Mail, Error> {
+        let data = read(&self.mail_path(self.next))?;
+        Mail::from_bytes(&data).map_err(|source| Error::ParseMail {
+            patch_number: self.next,
+            source,
+        })
+    }
+
+    fn advance(&mut self) -> Result<(), Error> {
+        self.next += 1;
+        write(&self.dir.join("next"), format!("{}\n", self.next).as_bytes())
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    std::fs::read(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn write(path: &Path, data: &[u8]) -> Result<(), Error> {
+    std::fs::write(path, data).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Apply patches from mailboxes
+impl Repository {
+    /// Apply the patches in the mails of `mbox` to the index and the worktree and create a commit for each of them,
+    /// similar to `git am`.
+    ///
+    /// The author and the message of each commit are taken from the mail, see [`Mail::from_bytes()`], while
+    /// the committer is the configured one. The files touched by each patch must be the same in the worktree and
+    /// in the index.
+    ///
+    /// If a patch can't be applied, the operation stops and its state is kept in `.git/rebase-apply`, which makes
+    /// [`Repository::state()`] return [`ApplyMailbox`](crate::state::InProgress::ApplyMailbox).
+    /// It can then be continued with [`Repository::am_continue()`], the patch can be skipped with
+    /// [`Repository::am_skip()`], or everything can be undone with [`Repository::am_abort()`].
+    pub fn am(&self, mbox: &[u8], options: Options) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::am()");
+        if self.workdir().is_none() {
+            return Err(Error::BareRepository);
+        }
+        let dir = self.path().join(STATE_DIR);
+        if dir.exists() {
+            return Err(Error::InProgress);
+        }
+        std::fs::create_dir_all(&dir).map_err(|source| Error::Io {
+            path: dir.clone(),
+            source,
+        })?;
+
+        let mails = split_mbox(mbox);
+        #[cfg(feature = "merge")]
+        let three_way = options.three_way;
+        #[cfg(not(feature = "merge"))]
+        let three_way = {
+            let _ = options;
+            false
+        };
+        let state = State {
+            next: 1,
+            last: mails.len(),
+            three_way,
+            dir,
+        };
+        for (number, mail) in (1..).zip(&mails) {
+            write(&state.mail_path(number), mail)?;
+        }
+        write(&state.dir.join("next"), b"1\n")?;
+        write(&state.dir.join("last"), format!("{}\n", state.last).as_bytes())?;
+        write(&state.dir.join("threeway"), if three_way { b"t\n" } else { b"f\n" })?;
+        if let Some(head_id) = self.head()?.id() {
+            write(&self.path().join("ORIG_HEAD"), format!("{head_id}\n").as_bytes())?;
+        }
+        write(&state.dir.join("applying"), b"")?;
+
+        self.am_apply_remaining(state, Vec::new())
+    }
+
+    /// Commit the changes in the index for the patch [`Repository::am()`] stopped at, and continue applying
+    /// the remaining patches, similar to `git am --continue`.
+    ///
+    /// The index must not have conflicts, and must differ from the tree at `HEAD`.
+    pub fn am_continue(&self) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::am_continue()");
+        let mut state = self.am_state()?;
+        let mail = state.mail()?;
+        let commit = self.am_commit(&mail, true)?;
+        state.advance()?;
+        self.am_apply_remaining(state, vec![commit])
+    }
+
+    /// Discard the changes of the patch [`Repository::am()`] stopped at and continue with the next patch,
+    /// similar to `git am --skip`.
+    ///
+    /// This resets the index and the worktree to `HEAD`, discarding all local changes to tracked files.
+    pub fn am_skip(&self) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::am_skip()");
+        let mut state = self.am_state()?;
+        if let Some(head_id) = self.head()?.id() {
+            self.reset(head_id, reset::Mode::Hard)?;
+        }
+        state.advance()?;
+        self.am_apply_remaining(state, Vec::new())
+    }
+
+    /// Stop applying patches with [`Repository::am()`] and reset `HEAD`, the index and the worktree to the state
+    /// before the first patch was applied, similar to `git am --abort`.
+    ///
+    /// Note that all local changes to tracked files are discarded.
+    pub fn am_abort(&self) -> Result<(), Error> {
+        let _span = gix_trace::coarse!("gix::Repository::am_abort()");
+        let state = self.am_state()?;
+        let orig_head = self.path().join("ORIG_HEAD");
+        if orig_head.is_file() {
+            let id = read(&orig_head)?;
+            let id = ObjectId::from_hex(id.trim()).map_err(|_| Error::InvalidState { dir: state.dir.clone() })?;
+            self.reset(id, reset::Mode::Hard)?;
+        }
+        remove_state(&state.dir)
+    }
+
+    fn am_state(&self) -> Result<State, Error> {
+        let dir = self.path().join(STATE_DIR);
+        if !dir.join("applying").is_file() {
+            return Err(Error::NotInProgress);
+        }
+        State::load(dir)
+    }
+
+    fn am_apply_remaining(&self, mut state: State, mut commits: Vec<ObjectId>) -> Result<Outcome, Error> {
+        while state.next <= state.last {
+            let mail = state.mail()?;
+            let stop = |reason| Outcome::Stopped {
+                commits: commits.clone(),
+                patch_number: state.next,
+                subject: mail.subject.clone(),
+                reason,
+            };
+            if mail.patches.is_empty() {
+                return Ok(stop(StopReason::EmptyPatch));
+            }
+            let options = apply_patches::Options {
+                location: apply_patches::Location::WorktreeAndIndex,
+                #[cfg(feature = "merge")]
+                three_way: state.three_way,
+                ..Default::default()
+            };
+            let outcome = match self.apply_patches(&mail.patches, options) {
+                Ok(outcome) => outcome,
+                Err(err) if patch_does_not_apply(&err) => return Ok(stop(StopReason::DoesNotApply(Box::new(err)))),
+                Err(err) => return Err(Error::ApplyPatches(err)),
+            };
+            let conflicts: Vec<_> = outcome
+                .files
+                .into_iter()
+                .filter(|file| file.conflict)
+                .map(|file| file.path)
+                .collect();
+            if !conflicts.is_empty() {
+                return Ok(stop(StopReason::Conflicts { paths: conflicts }));
+            }
+            commits.push(self.am_commit(&mail, false)?);
+            state.advance()?;
+        }
+        remove_state(&state.dir)?;
+        Ok(Outcome::Applied { commits })
+    }
+
+    /// Commit the index with the author and message of `mail` on top of `HEAD`.
+    /// If `require_changes` is `true`, fail if the index is the same as the tree of `HEAD`.
+    fn am_commit(&self, mail: &Mail, require_changes: bool) -> Result<ObjectId, Error> {
+        let mut index = (**self.index_or_empty()?).clone();
+        let mut conflicts: Vec<BString> = index
+            .entries()
+            .iter()
+            .filter(|entry| entry.stage() != Stage::Unconflicted)
+            .map(|entry| entry.path(&index).to_owned())
+            .collect();
+        if !conflicts.is_empty() {
+            conflicts.dedup();
+            return Err(Error::UnresolvedConflicts { paths: conflicts });
+        }
+        let tree = self.write_tree_from_index(&mut index)?.detach();
+        if require_changes && tree == self.head_tree_id_or_empty()? {
+            return Err(Error::NoChanges);
+        }
+
+        let committer = self.committer().ok_or(Error::CommitterMissing)??;
+        let commit = gix_object::Commit {
+            tree,
+            parents: self.head()?.id().map(crate::Id::detach).into_iter().collect(),
+            author: mail.author.clone(),
+            committer: committer.into(),
+            encoding: None,
+            message: mail.message.clone(),
+            extra_headers: Vec::new(),
+        };
+        let id = self.write_object(&commit)?.detach();
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("am: {}", mail.subject).into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Object(id),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: true,
+        })?;
+        Ok(id)
+    }
+}
+
+/// Return `true` if `err` indicates that a patch doesn't apply, instead of a failure to access the repository.
+fn patch_does_not_apply(err: &apply_patches::Error) -> bool {
+    use apply_patches::Error::*;
+    matches!(
+        err,
+        Missing { .. }
+            | AlreadyExists { .. }
+            | WorktreeMismatch { .. }
+            | Rejected { .. }
+            | DeletionLeavesContent { .. }
+            | Apply { .. }
+    )
+}
+
+fn remove_state(dir: &Path) -> Result<(), Error> {
+    std::fs::remove_dir_all(dir).map_err(|source| Error::Io {
+        path: dir.to_owned(),
+        source,
+    })
+}
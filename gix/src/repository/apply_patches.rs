@@ -0,0 +1,476 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::atomic::AtomicBool,
+};
+
+use gix_diff::patch::{apply::HunkOutcome, File, Operation};
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Stage};
+use gix_object::tree::EntryKind;
+
+use super::checkout::{checkout_selected_entries, remove_file_for_recreation, remove_from_worktree};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+/// Where to apply patches with [`Repository::apply_patches()`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// Apply patches to files in the worktree only, like `git apply`.
+    #[default]
+    Worktree,
+    /// Apply patches to the index only, like `git apply --cached`.
+    Index,
+    /// Apply patches to the index and the worktree, like `git apply --index`.
+    ///
+    /// Files touched by the patches must be the same in the worktree and in the index.
+    WorktreeAndIndex,
+}
+
+/// Options for use in [`Repository::apply_patches()`].
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Options {
+    /// Where to apply the patches.
+    pub location: Location,
+    /// Options to control how hunks are placed, like the allowed fuzz.
+    pub apply: gix_diff::patch::apply::Options,
+    /// If `true`, apply all hunks that apply, and report those that don't, similar to `git apply --reject`.
+    /// Otherwise, nothing is changed if a single hunk doesn't apply.
+    pub reject: bool,
+    /// If `true`, files whose patch doesn't apply are merged with the changes of the patch, similar to `git apply --3way`.
+    ///
+    /// This works if the blob the patch was made for, as identified in its `index` line, is in the object database.
+    /// If the merge has conflicts, the index contains the base, our and their version of the file.
+    /// Just like in Git, this implies applying to the index as well if the location is the worktree.
+    #[cfg(feature = "merge")]
+    pub three_way: bool,
+}
+
+/// The outcome of applying a patch to a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOutcome {
+    /// The path of the file after the patch was applied, or the path of the deleted file.
+    pub path: BString,
+    /// The outcome of applying each hunk of the patch, which is empty for binary files.
+    pub hunks: Vec<HunkOutcome>,
+    /// If `true`, not all hunks applied and the file was merged with the changes of the patch instead.
+    pub merged: bool,
+    /// If `true`, the merge had conflicts which are marked in the file.
+    pub conflict: bool,
+}
+
+impl FileOutcome {
+    /// Return `true` if not all hunks applied and the file wasn't merged instead.
+    pub fn has_rejected_hunks(&self) -> bool {
+        !self.merged && self.hunks.iter().any(|h| matches!(h, HunkOutcome::Rejected))
+    }
+}
+
+/// The outcome of [`Repository::apply_patches()`].
+#[derive(Debug, Default, Clone)]
+pub struct Outcome {
+    /// The outcome for each patched file, in order of the patches.
+    pub files: Vec<FileOutcome>,
+}
+
+/// The error returned by [`Repository::apply_patches()`] and [`Repository::apply_patches_to_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot apply patches to the worktree of a bare repository")]
+    BareRepository,
+    #[error("'{path}' does not exist")]
+    Missing { path: BString },
+    #[error("'{path}' already exists")]
+    AlreadyExists { path: BString },
+    #[error("'{path}' in the worktree does not match the index")]
+    WorktreeMismatch { path: BString },
+    #[error("The patch does not apply to '{path}' as the hunks at indices {hunks:?} were rejected")]
+    Rejected { path: BString, hunks: Vec<usize> },
+    #[error("The patch deleting '{path}' does not remove all of its content")]
+    DeletionLeavesContent { path: BString },
+    #[error("Could not apply the patch to '{path}'")]
+    Apply {
+        path: BString,
+        source: gix_diff::patch::apply::Error,
+    },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    FindBlob(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    Peel(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+    #[error(transparent)]
+    FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+    #[error(transparent)]
+    WorktreeFileToObject(#[from] crate::filter::pipeline::worktree_file_to_object::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error("Could not remove '{rela_path}' from the worktree")]
+    RemoveFile { rela_path: BString, source: std::io::Error },
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    BlobMergeOptions(#[from] crate::repository::blob_merge_options::Error),
+    #[cfg(feature = "tree-editor")]
+    #[error(transparent)]
+    EditTree(#[from] crate::repository::edit_tree::Error),
+    #[cfg(feature = "tree-editor")]
+    #[error(transparent)]
+    TreeEditor(#[from] gix_object::tree::editor::Error),
+    #[cfg(feature = "tree-editor")]
+    #[error(transparent)]
+    WriteTree(#[from] crate::object::tree::editor::write::Error),
+}
+
+/// The state of a file after applying all patches.
+struct Change {
+    /// The content and kind of the file, or `None` if it was deleted.
+    new: Option<(Vec<u8>, EntryKind)>,
+    /// The ids of the base, our and their version of the file if merging it had conflicts.
+    conflict: Option<[ObjectId; 3]>,
+}
+
+/// The merged content of a file, along with the ids of the base, our and their version if there were conflicts.
+#[cfg(feature = "merge")]
+type Merged = (Vec<u8>, Option<[ObjectId; 3]>);
+
+/// Apply patches
+impl Repository {
+    /// Apply `patches`, as obtained with [`gix_diff::patch::parse()`], to the worktree, the index, or both,
+    /// depending on `options`, similar to `git apply`.
+    ///
+    /// Files are read from and written to the worktree through the filter pipeline.
+    /// Unless [`reject`](Options::reject) is set, all patches are applied or none at all, and the
+    /// first patch that doesn't apply is returned as error.
+    pub fn apply_patches(&self, patches: &[File], options: Options) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::apply_patches()");
+        #[allow(unused_mut)]
+        let mut location = options.location;
+        #[cfg(feature = "merge")]
+        if options.three_way && location == Location::Worktree {
+            location = Location::WorktreeAndIndex;
+        }
+        let workdir = match location {
+            Location::Index => None,
+            Location::Worktree | Location::WorktreeAndIndex => Some(self.workdir().ok_or(Error::BareRepository)?),
+        };
+
+        let mut index = (**self.index_or_empty()?).clone();
+        if index.is_sparse() {
+            index.expand_sparse_directories(&self.objects)?;
+        }
+        let mut pipeline = workdir.map(|_| self.filter_pipeline(None)).transpose()?.map(|t| t.0);
+        let (changes, files) = self.apply_patches_to_files(patches, options, |path| {
+            let entry = index
+                .entry_by_path_and_stage(path, Stage::Unconflicted)
+                .map(|e| (e.id, e.mode.to_tree_entry_mode().map(|m| m.kind())));
+            let in_worktree = match pipeline.as_mut() {
+                Some(pipeline) => pipeline.worktree_file_to_object(path, &index)?,
+                None => None,
+            };
+            let (id, kind) = match location {
+                Location::Worktree => match in_worktree {
+                    Some((id, kind, _md)) => (id, kind),
+                    None => return Ok(None),
+                },
+                Location::Index | Location::WorktreeAndIndex => match entry {
+                    Some((id, Some(kind))) => {
+                        if location == Location::WorktreeAndIndex && in_worktree.map(|(id, ..)| id) != Some(id) {
+                            return Err(Error::WorktreeMismatch { path: path.to_owned() });
+                        }
+                        (id, kind)
+                    }
+                    _ => return Ok(None),
+                },
+            };
+            Ok(Some((self.find_blob(id)?.take_data(), kind)))
+        })?;
+
+        for (path, change) in &changes {
+            match &change.new {
+                None => index.remove_entries(|_, entry_path, _| entry_path == path),
+                Some((data, kind)) => {
+                    let id = self.write_blob(data)?.detach();
+                    index.remove_entries(|_, entry_path, e| entry_path == path && e.stage() != Stage::Unconflicted);
+                    match index.entry_mut_by_path_and_stage(path.as_ref(), Stage::Unconflicted) {
+                        Some(entry) => {
+                            entry.id = id;
+                            entry.mode = (*kind).into();
+                            entry.stat = Default::default();
+                        }
+                        None => {
+                            index.dangerously_push_entry(
+                                Default::default(),
+                                id,
+                                Flags::empty(),
+                                (*kind).into(),
+                                path.as_ref(),
+                            );
+                            index.sort_entries();
+                        }
+                    }
+                }
+            }
+            if let Some(tree) = index.tree_mut() {
+                tree.invalidate_path(path.as_ref());
+            }
+        }
+
+        if let Some(workdir) = workdir {
+            let mut selected = BTreeSet::new();
+            for (path, change) in &changes {
+                let res = if change.new.is_some() {
+                    selected.insert(path.as_bstr());
+                    remove_file_for_recreation(workdir, path.as_ref())
+                } else {
+                    remove_from_worktree(workdir, path.as_ref()).map(|_| ())
+                };
+                res.map_err(|source| Error::RemoveFile {
+                    rela_path: path.clone(),
+                    source,
+                })?;
+            }
+            let opts = self.checkout_options(gix_worktree::stack::state::attributes::Source::IdMapping)?;
+            checkout_selected_entries(
+                &mut index,
+                workdir,
+                self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+                |path| selected.contains(path),
+                &gix_features::progress::Discard,
+                &gix_features::progress::Discard,
+                &AtomicBool::default(),
+                opts,
+            )?;
+        }
+
+        let mut has_conflicts = false;
+        for (path, conflict) in changes.iter().filter_map(|(path, c)| c.conflict.map(|ids| (path, ids))) {
+            let Some(idx) = index.entry_index_by_path_and_stage(path.as_ref(), Stage::Unconflicted) else {
+                continue;
+            };
+            let entry = index.remove_entry_at_index(idx);
+            for (stage, id) in [Stage::Base, Stage::Ours, Stage::Theirs].into_iter().zip(conflict) {
+                index.dangerously_push_entry(Default::default(), id, stage.into(), entry.mode, path.as_ref());
+            }
+            has_conflicts = true;
+        }
+        if has_conflicts {
+            index.sort_entries();
+        }
+        if location != Location::Worktree {
+            index.write(Default::default())?;
+        }
+        Ok(Outcome { files })
+    }
+
+    /// Apply `patches`, as obtained with [`gix_diff::patch::parse()`], to the files in the tree-ish `tree`,
+    /// and return the id of the written tree that contains the changes.
+    ///
+    /// Use `options` to control how hunks are placed. All patches must apply, or the first one that doesn't is returned as error.
+    #[cfg(feature = "tree-editor")]
+    pub fn apply_patches_to_tree(
+        &self,
+        tree: impl Into<ObjectId>,
+        patches: &[File],
+        options: gix_diff::patch::apply::Options,
+    ) -> Result<crate::Id<'_>, Error> {
+        let tree = self.find_object(tree)?.peel_to_tree()?;
+        let options = Options {
+            apply: options,
+            ..Default::default()
+        };
+        let (changes, _files) = self.apply_patches_to_files(patches, options, |path| {
+            let Some(entry) = tree.lookup_entry_by_path(gix_path::from_bstr(path))? else {
+                return Ok(None);
+            };
+            let kind = entry.mode().kind();
+            if !matches!(kind, EntryKind::Blob | EntryKind::BlobExecutable | EntryKind::Link) {
+                return Ok(None);
+            }
+            Ok(Some((self.find_blob(entry.object_id())?.take_data(), kind)))
+        })?;
+
+        let mut editor = self.edit_tree(tree.id)?;
+        for (path, change) in changes {
+            match change.new {
+                None => editor.remove(path)?,
+                Some((data, kind)) => editor.upsert(path, kind, self.write_blob(data)?)?,
+            };
+        }
+        Ok(editor.write()?)
+    }
+
+    /// Apply `patches` to the files obtained with `read(rela_path)`, returning `None` if the file doesn't exist,
+    /// and return the new state of all changed files, along with the outcome for each patch.
+    fn apply_patches_to_files(
+        &self,
+        patches: &[File],
+        options: Options,
+        mut read: impl FnMut(&BStr) -> Result<Option<(Vec<u8>, EntryKind)>, Error>,
+    ) -> Result<(BTreeMap<BString, Change>, Vec<FileOutcome>), Error> {
+        let mut changes = BTreeMap::<BString, Change>::new();
+        let mut current = |changes: &BTreeMap<BString, Change>, path: &BString| match changes.get(path) {
+            Some(change) => Ok(change.new.clone()),
+            None => read(path.as_ref()),
+        };
+        let mut files = Vec::with_capacity(patches.len());
+        for patch in patches {
+            let path = patch
+                .new_path
+                .as_ref()
+                .or(patch.old_path.as_ref())
+                .expect("parsing assures there is at least one path")
+                .clone();
+            let old = match &patch.old_path {
+                Some(old_path) => {
+                    Some(current(&changes, old_path)?.ok_or_else(|| Error::Missing { path: old_path.clone() })?)
+                }
+                None => None,
+            };
+            if patch.operation != Operation::Modification && patch.operation != Operation::Deletion {
+                if let Some(new_path) = &patch.new_path {
+                    if current(&changes, new_path)?.is_some() {
+                        return Err(Error::AlreadyExists { path: new_path.clone() });
+                    }
+                }
+            }
+            let (old_data, old_kind) = old.map_or((Vec::new(), None), |(data, kind)| (data, Some(kind)));
+            let mut outcome =
+                gix_diff::patch::apply(&old_data, patch, options.apply).map_err(|source| Error::Apply {
+                    path: path.clone(),
+                    source,
+                })?;
+
+            #[allow(unused_mut)]
+            let mut merged = None;
+            if outcome.has_rejected_hunks() {
+                #[cfg(feature = "merge")]
+                if options.three_way {
+                    merged = self.merge_patch(patch, &old_data, options.apply)?;
+                }
+                if merged.is_none() && !options.reject {
+                    return Err(Error::Rejected {
+                        path,
+                        hunks: outcome.rejected_hunks().collect(),
+                    });
+                }
+            }
+            let was_merged = merged.is_some();
+            let conflict = match merged {
+                Some((data, conflict)) => {
+                    outcome.data = data;
+                    conflict
+                }
+                None => None,
+            };
+            let kind = patch.new_mode.map(|m| m.kind()).or(old_kind).unwrap_or(EntryKind::Blob);
+            let new = Some((outcome.data, kind));
+            let file_outcome = FileOutcome {
+                path: path.clone(),
+                hunks: outcome.hunks,
+                merged: was_merged,
+                conflict: conflict.is_some(),
+            };
+            match (patch.operation, &patch.old_path, &patch.new_path) {
+                (Operation::Deletion, Some(old_path), _) => {
+                    let is_empty = new.as_ref().map_or(true, |(data, _)| data.is_empty());
+                    if !is_empty && !file_outcome.has_rejected_hunks() {
+                        return Err(Error::DeletionLeavesContent { path });
+                    }
+                    changes.insert(
+                        old_path.clone(),
+                        Change {
+                            // Keep what's left if hunks were rejected.
+                            new: new.filter(|_| !is_empty),
+                            conflict: None,
+                        },
+                    );
+                }
+                (Operation::Rename { .. }, Some(old_path), Some(new_path)) => {
+                    changes.insert(
+                        old_path.clone(),
+                        Change {
+                            new: None,
+                            conflict: None,
+                        },
+                    );
+                    changes.insert(new_path.clone(), Change { new, conflict });
+                }
+                (_, _, Some(new_path)) => {
+                    changes.insert(new_path.clone(), Change { new, conflict });
+                }
+                (_, _, None) => unreachable!("only deletions don't have a new path"),
+            }
+            files.push(file_outcome);
+        }
+        Ok((changes, files))
+    }
+
+    /// Merge the changes of `patch` into `ours`, using the blob the patch was made for as base,
+    /// and return the merged content along with the ids of base, ours and theirs if there were conflicts.
+    /// Return `None` if this isn't possible.
+    #[cfg(feature = "merge")]
+    fn merge_patch(
+        &self,
+        patch: &File,
+        ours: &[u8],
+        options: gix_diff::patch::apply::Options,
+    ) -> Result<Option<Merged>, Error> {
+        let Some(prefix) = patch.old_id.filter(|id| !id.as_oid().is_null()) else {
+            return Ok(None);
+        };
+        if !matches!(patch.content, gix_diff::patch::Content::Text(_)) {
+            return Ok(None);
+        }
+        let Some(Ok(base_id)) = self.objects.lookup_prefix(prefix, None)? else {
+            return Ok(None);
+        };
+        let base = self.find_blob(base_id)?.take_data();
+        let theirs = match gix_diff::patch::apply(&base, patch, options) {
+            Ok(theirs) if !theirs.has_rejected_hunks() => theirs.data,
+            _ => return Ok(None),
+        };
+
+        let labels = gix_merge::blob::builtin_driver::text::Labels {
+            ancestor: Some("base".into()),
+            current: Some("ours".into()),
+            other: Some("theirs".into()),
+        };
+        let mut input = gix_diff::blob::intern::InternedInput::new(&[][..], &[]);
+        let mut out = Vec::new();
+        let resolution = gix_merge::blob::builtin_driver::text(
+            &mut out,
+            &mut input,
+            labels,
+            ours,
+            &base,
+            &theirs,
+            self.blob_merge_options()?.text,
+        );
+        let conflict = match resolution {
+            gix_merge::blob::Resolution::Conflict => Some([
+                base_id,
+                self.write_blob(ours)?.detach(),
+                self.write_blob(&theirs)?.detach(),
+            ]),
+            _ => None,
+        };
+        Ok(Some((out, conflict)))
+    }
+}
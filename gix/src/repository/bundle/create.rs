@@ -0,0 +1,241 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::{
+    parallel::InOrderIter,
+    progress::{Count, DynNestedProgress, Progress},
+};
+use gix_hash::ObjectId;
+use gix_pack::data::output;
+
+use super::OdbArc;
+use crate::{
+    bstr::{BStr, BString},
+    Repository,
+};
+
+/// Options for use in [`Repository::create_bundle()`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// The amount of threads to use at most, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The outcome of [`Repository::create_bundle()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The header that was written, with all prerequisites and references of the bundle.
+    pub header: gix_bundle::Header,
+    /// The amount of objects in the pack of the bundle.
+    pub num_objects: usize,
+}
+
+/// The error returned by [`Repository::create_bundle()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::Error),
+    #[error("Revision {spec:?} is neither a single revision nor a range like 'a..b'")]
+    UnsupportedSpec { spec: BString },
+    #[error("Refusing to create an empty bundle as none of the given revisions is a reference")]
+    Empty,
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelObject(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    WalkIter(#[from] crate::revision::walk::iter::Error),
+    #[error(transparent)]
+    CountObjects(#[from] output::count::objects::Error),
+    #[error(transparent)]
+    WritePack(#[from] output::bytes::Error<output::entry::iter_from_counts::Error>),
+    #[error("Could not write the bundle header")]
+    WriteHeader(#[source] std::io::Error),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+/// Bundles
+impl Repository {
+    /// Write a bundle to `out` with all references and objects described by revision `specs`, similar to `git bundle create`.
+    ///
+    /// Each of the `specs` is either a single revision like `main`, a range like `main~2..main` or an exclusion like `^v1.0`.
+    /// Revisions that are references, like `main` or `HEAD`, are listed in the bundle, while all commits reachable from them
+    /// are contained in its pack unless they are reachable from an excluded revision. The parents of contained commits that
+    /// aren't contained themselves are recorded as prerequisites, which the receiving repository has to have.
+    ///
+    /// Note that objects reachable from the trees of prerequisites are assumed to be present in the receiving repository
+    /// and are not included.
+    pub fn create_bundle<'a>(
+        &self,
+        specs: impl IntoIterator<Item = &'a BStr>,
+        out: &mut dyn Write,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let mut header = gix_bundle::Header::new(self.object_hash(), None);
+        let mut tips = Vec::new();
+        let mut excluded = Vec::new();
+        for spec in specs {
+            let parsed = self.rev_parse(spec)?;
+            let (first, second) = (parsed.first_reference().cloned(), parsed.second_reference().cloned());
+            let (tip, reference) = match parsed.detach() {
+                gix_revision::Spec::Include(id) => (id, first),
+                gix_revision::Spec::Exclude(id) => {
+                    excluded.push(id);
+                    continue;
+                }
+                gix_revision::Spec::Range { from, to } => {
+                    excluded.push(from);
+                    (to, second)
+                }
+                _ => return Err(Error::UnsupportedSpec { spec: spec.to_owned() }),
+            };
+            tips.push(tip);
+            let Some(reference) = reference else { continue };
+            let id = reference.target.try_id().map_or(tip, ToOwned::to_owned);
+            if header.find_ref(reference.name.as_bstr()).is_none() {
+                header.refs.push(gix_bundle::Ref {
+                    id,
+                    name: reference.name.into_inner(),
+                });
+            }
+            tips.push(id);
+        }
+        if header.refs.is_empty() {
+            return Err(Error::Empty);
+        }
+        tips.sort();
+        tips.dedup();
+
+        let mut odb = self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?.into_inner();
+        odb.prevent_pack_unload();
+        let mut hidden = gix_hashtable::HashSet::default();
+        for info in self.rev_walk(self.peel_to_commits(excluded, &mut Vec::new())?).all()? {
+            hidden.insert(info?.id);
+        }
+
+        let mut input = Vec::new();
+        let tip_commits = self.peel_to_commits(tips, &mut input)?;
+        let mut walk_progress = progress.add_child("traverse commits".into());
+        walk_progress.init(None, gix_features::progress::count("commits"));
+        let mut contained = gix_hashtable::HashSet::default();
+        let mut parents = Vec::new();
+        for info in self.rev_walk(tip_commits).selected(move |id| !hidden.contains(id))? {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let info = info?;
+            parents.extend(info.parent_ids.iter().copied());
+            contained.insert(info.id);
+            input.push(info.id);
+            walk_progress.inc();
+        }
+        parents.sort();
+        parents.dedup();
+        parents.retain(|id| !contained.contains(id));
+        for id in parents {
+            let comment = self
+                .find_commit(id)
+                .ok()
+                .and_then(|commit| commit.message().ok().map(|message| message.summary().into_owned()))
+                .unwrap_or_default();
+            header.prerequisites.push(gix_bundle::Prerequisite { id, comment });
+        }
+
+        let mut count_objects = |input: Vec<ObjectId>, name: &str| {
+            let mut count_progress = progress.add_child(name.into());
+            count_progress.init(None, gix_features::progress::count("objects"));
+            output::count::objects(
+                odb.clone(),
+                Box::new(input.into_iter().map(Ok)),
+                &count_progress,
+                should_interrupt,
+                output::count::objects::Options {
+                    thread_limit: options.thread_limit,
+                    input_object_expansion: output::count::objects::ObjectExpansion::TreeContents,
+                    ..Default::default()
+                },
+            )
+            .map(|(counts, _outcome)| counts)
+        };
+        let mut counts = count_objects(input, "count objects")?;
+        if !header.prerequisites.is_empty() {
+            let prerequisites = header.prerequisites.iter().map(|p| p.id).collect();
+            let present: gix_hashtable::HashSet<_> = count_objects(prerequisites, "count prerequisite objects")?
+                .into_iter()
+                .map(|count| count.id)
+                .collect();
+            counts.retain(|count| !present.contains(&count.id));
+        }
+
+        header.write_to(out).map_err(Error::WriteHeader)?;
+        let num_objects = counts.len();
+        write_pack(&odb, counts, out, progress, should_interrupt, options.thread_limit)?;
+        Ok(Outcome { header, num_objects })
+    }
+
+    /// Peel all `ids` to commits and return them, while adding annotated tags and objects that aren't commits to `non_commits`.
+    fn peel_to_commits(&self, ids: Vec<ObjectId>, non_commits: &mut Vec<ObjectId>) -> Result<Vec<ObjectId>, Error> {
+        let mut commits = Vec::new();
+        for id in ids {
+            match self.find_header(id)?.kind() {
+                gix_object::Kind::Commit => commits.push(id),
+                gix_object::Kind::Tag => {
+                    non_commits.push(id);
+                    let target = self.find_object(id)?.peel_tags_to_end()?;
+                    match target.kind {
+                        gix_object::Kind::Commit => commits.push(target.id),
+                        _ => non_commits.push(target.id),
+                    }
+                }
+                gix_object::Kind::Tree | gix_object::Kind::Blob => non_commits.push(id),
+            }
+        }
+        Ok(commits)
+    }
+}
+
+/// Write all objects in `counts` as pack to `out`.
+fn write_pack(
+    odb: &OdbArc,
+    counts: Vec<output::Count>,
+    out: &mut dyn Write,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    thread_limit: Option<usize>,
+) -> Result<(), Error> {
+    let num_objects = counts.len();
+    let entries = InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        odb.clone(),
+        Box::new(progress.add_child("create entries".into())),
+        output::entry::iter_from_counts::Options {
+            thread_limit,
+            ..Default::default()
+        },
+    ));
+    let mut write_progress = progress.add_child("write pack".into());
+    write_progress.init(None, gix_features::progress::bytes());
+    for written in output::bytes::FromEntriesIter::new(
+        entries,
+        out,
+        num_objects as u32,
+        gix_pack::data::Version::V2,
+        odb.store_ref().object_hash(),
+    ) {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        write_progress.inc_by(written? as usize);
+    }
+    Ok(())
+}
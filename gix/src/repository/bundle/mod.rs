@@ -0,0 +1,18 @@
+//! Create bundles from revision ranges, verify them and receive their packs, similar to what `git bundle` does.
+//!
+//! Use [`gix_bundle::File`] to read the header of a bundle, to list its references for example.
+//! With the `blocking-network-client` feature, bundles can also be [fetched from](crate::remote::bundle) like any other remote.
+//!
+//! Bundles can also be obtained from [bundle uris](crate::Repository::unbundle_from_uri()), which may point to lists of bundles.
+
+///
+pub mod create;
+///
+pub mod unbundle;
+///
+pub mod uri;
+///
+pub mod verify;
+
+/// A handle to the object database that can be shared across threads, as needed to create packs.
+type OdbArc = gix_odb::Cache<gix_odb::store::Handle<std::sync::Arc<gix_odb::Store>>>;
@@ -0,0 +1,77 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_features::progress::DynNestedProgress;
+use gix_hash::ObjectId;
+
+use crate::Repository;
+
+/// The outcome of [`Repository::unbundle()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The header of the bundle, with the references that can now be created as all of their objects are present.
+    pub header: gix_bundle::Header,
+    /// Information about the pack that was written into the object database.
+    pub pack: gix_pack::bundle::write::Outcome,
+}
+
+/// The error returned by [`Repository::unbundle()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Open(#[from] gix_bundle::file::open::Error),
+    #[error("The repository lacks {} prerequisite commit(s) of the bundle, like {}", missing.len(), missing[0])]
+    MissingPrerequisites { missing: Vec<ObjectId> },
+    #[error(transparent)]
+    WritePack(#[from] gix_bundle::file::write_pack::Error),
+    #[error("Could not remove the keep file at '{}'", path.display())]
+    RemoveKeepFile { path: PathBuf, source: std::io::Error },
+}
+
+/// Bundles
+impl Repository {
+    /// Write the pack of the bundle at `path` into the object database of this repository, similar to `git bundle unbundle`.
+    ///
+    /// No reference is changed, instead the references of the bundle are returned as part of its header so they can be
+    /// created or updated by the caller. To do that automatically, [fetch](crate::Remote) from the bundle instead.
+    pub fn unbundle(
+        &self,
+        path: impl Into<PathBuf>,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let bundle = gix_bundle::File::at(path)?;
+        let missing = bundle.header.missing_prerequisites(&self.objects);
+        if !missing.is_empty() {
+            return Err(Error::MissingPrerequisites { missing });
+        }
+        let pack_dir = self.objects.store_ref().path().join("pack");
+        let pack = bundle.write_pack_to_directory(
+            Some(&pack_dir),
+            progress,
+            should_interrupt,
+            Some(&self.objects),
+            gix_pack::bundle::write::Options {
+                thread_limit: None,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: bundle.header.object_hash,
+            },
+        )?;
+        if let Some(path) = &pack.keep_path {
+            std::fs::remove_file(path).map_err(|source| Error::RemoveKeepFile {
+                path: path.clone(),
+                source,
+            })?;
+        }
+        // Looking up a new object refreshes the shared store, which makes the new pack visible to handles
+        // that never refresh on their own, like the ones used during negotiation.
+        if let Some(r) = bundle.header.refs.first() {
+            self.has_object(r.id);
+        }
+        Ok(Outcome {
+            header: bundle.header,
+            pack,
+        })
+    }
+}
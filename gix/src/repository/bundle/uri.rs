@@ -0,0 +1,400 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::progress::DynNestedProgress;
+use gix_protocol::bundle_uri::{Heuristic, List, Mode};
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::unbundle;
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    Repository,
+};
+
+/// Options for use in [`Repository::unbundle_from_uri()`] and [`Repository::unbundle_from_list()`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// If set, and if bundles are selected by [creation token](Heuristic::CreationToken), only bundles
+    /// with a greater token are downloaded as all others were downloaded before.
+    ///
+    /// This is typically the value of `fetch.bundleCreationToken`.
+    pub min_creation_token: Option<u64>,
+}
+
+/// The outcome of [`Repository::unbundle_from_uri()`] and [`Repository::unbundle_from_list()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The list of bundles that was used, or `None` if the uri pointed to a bundle directly.
+    pub list: Option<List>,
+    /// All bundles that were written into the object database, in order.
+    pub bundles: Vec<unbundle::Outcome>,
+    /// The greatest creation token of all bundles that were unbundled, if bundles were selected by creation token.
+    ///
+    /// It should be stored as `fetch.bundleCreationToken` to avoid downloading the same bundles again.
+    pub creation_token: Option<u64>,
+}
+
+/// The error returned by [`Repository::unbundle_from_uri()`] and [`Repository::unbundle_from_list()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    ParseUrl(#[from] gix_url::parse::Error),
+    #[error("Bundles can only be obtained from files or via http(s), which isn't the case for {uri:?}")]
+    UnsupportedScheme { uri: BString },
+    #[error("Could not read bundle or bundle list from {uri:?}")]
+    Io { uri: BString, source: std::io::Error },
+    #[cfg(any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    ))]
+    #[error("Could not download bundle or bundle list from {uri:?}")]
+    Http {
+        uri: BString,
+        source: gix_transport::client::http::Error,
+    },
+    #[error("{uri:?} is neither a bundle nor a bundle list in the configuration file format")]
+    ParseListConfig {
+        uri: BString,
+        source: gix_config::file::init::Error,
+    },
+    #[error("The bundle list at {uri:?} is invalid")]
+    ParseList {
+        uri: BString,
+        source: gix_protocol::bundle_uri::parse::Error,
+    },
+    #[error("Bundle lists are nested more than {max} levels deep at {uri:?}")]
+    MaxDepthExceeded { uri: BString, max: usize },
+    #[error(transparent)]
+    Open(#[from] gix_bundle::file::open::Error),
+    #[error(transparent)]
+    Unbundle(#[from] unbundle::Error),
+    #[error(transparent)]
+    EditReferences(#[from] crate::reference::edit::Error),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+/// The maximum amount of lists that may point to each other, like in `git`.
+const MAX_DEPTH: usize = 4;
+
+/// Bundles
+impl Repository {
+    /// Obtain the bundle or bundle list at `uri`, which is either a path or a `file://` or `http(s)://` url, and write the
+    /// packs of all bundles into the object database, similar to what `git clone --bundle-uri` does before fetching.
+    ///
+    /// For each reference in `refs/heads/` of an unbundled bundle, a reference in `refs/bundles/` is created so the
+    /// objects can be used during negotiation of a subsequent fetch.
+    ///
+    /// Note that bundles produced with an object filter are ignored, and that downloads via http(s) don't use credentials
+    /// or any of the `http.*` configuration.
+    pub fn unbundle_from_uri(
+        &self,
+        uri: &BStr,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        match self.download_bundle_or_list(uri)? {
+            Download::Bundle(bundle) => {
+                let bundle = self.unbundle_and_write_refs(&bundle, progress, should_interrupt)?;
+                Ok(Outcome {
+                    list: None,
+                    bundles: vec![bundle],
+                    creation_token: None,
+                })
+            }
+            Download::List(list) => self.unbundle_from_list_inner(list, uri, progress, should_interrupt, options, 1),
+        }
+    }
+
+    /// Like [`unbundle_from_uri()`](Self::unbundle_from_uri()), but starts from `list`, which was obtained
+    /// from `base_uri`, for instance by [advertisement of the server](gix_protocol::bundle_uri()).
+    ///
+    /// Bundles are selected according to the [mode](Mode) and [heuristic](Heuristic) of the list, and
+    /// relative uris of bundles are resolved against `base_uri`.
+    pub fn unbundle_from_list(
+        &self,
+        list: List,
+        base_uri: &BStr,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        self.unbundle_from_list_inner(list, base_uri, progress, should_interrupt, options, 0)
+    }
+
+    fn unbundle_from_list_inner(
+        &self,
+        list: List,
+        base_uri: &BStr,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+        depth: usize,
+    ) -> Result<Outcome, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::MaxDepthExceeded {
+                uri: base_uri.to_owned(),
+                max: MAX_DEPTH,
+            });
+        }
+        let by_creation_token = list.heuristic == Some(Heuristic::CreationToken);
+        let mut bundles: Vec<_> = list
+            .bundles
+            .iter()
+            .filter(|b| b.filter.is_none())
+            .filter(|b| {
+                !by_creation_token
+                    || options
+                        .min_creation_token
+                        .map_or(true, |min| b.creation_token.unwrap_or_default() > min)
+            })
+            .collect();
+        let mut out = Outcome {
+            list: Some(list.clone()),
+            bundles: Vec::new(),
+            creation_token: None,
+        };
+
+        let mut downloaded = Vec::new();
+        if by_creation_token {
+            // Download the newest bundles first and stop once all prerequisites are present,
+            // so only the bundles that are needed are downloaded.
+            bundles.sort_by_key(|b| std::cmp::Reverse(b.creation_token.unwrap_or_default()));
+            for bundle in bundles {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let uri = resolve(base_uri, bundle.uri.as_ref());
+                let file = match self.download_bundle_or_list(uri.as_ref())? {
+                    Download::Bundle(file) => file,
+                    Download::List(list) => {
+                        let nested = self.unbundle_from_list_inner(
+                            list,
+                            uri.as_ref(),
+                            progress,
+                            should_interrupt,
+                            options,
+                            depth + 1,
+                        )?;
+                        out.bundles.extend(nested.bundles);
+                        continue;
+                    }
+                };
+                let is_complete = gix_bundle::File::at(file.path())?
+                    .header
+                    .missing_prerequisites(&self.objects)
+                    .is_empty();
+                downloaded.push((bundle.creation_token, file));
+                if is_complete {
+                    break;
+                }
+            }
+            downloaded.reverse();
+        } else {
+            for bundle in bundles {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let uri = resolve(base_uri, bundle.uri.as_ref());
+                let res = self
+                    .download_bundle_or_list(uri.as_ref())
+                    .and_then(|download| match download {
+                        Download::Bundle(file) => {
+                            downloaded.push((bundle.creation_token, file));
+                            Ok(())
+                        }
+                        Download::List(list) => {
+                            let nested = self.unbundle_from_list_inner(
+                                list,
+                                uri.as_ref(),
+                                progress,
+                                should_interrupt,
+                                options,
+                                depth + 1,
+                            )?;
+                            out.bundles.extend(nested.bundles);
+                            Ok(())
+                        }
+                    });
+                match list.mode {
+                    Mode::All => res?,
+                    // Any bundle will do, so try the next one on error, and stop once we have one.
+                    Mode::Any => {
+                        if res.is_ok() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Unbundle in an order that satisfies the prerequisites, which may be provided by other bundles.
+        while let Some(idx) = downloaded.iter().position(|(_, file)| {
+            gix_bundle::File::at(file.path()).map_or(true, |bundle| {
+                bundle.header.missing_prerequisites(&self.objects).is_empty()
+            })
+        }) {
+            let (creation_token, file) = downloaded.remove(idx);
+            out.bundles
+                .push(self.unbundle_and_write_refs(&file, progress, should_interrupt)?);
+            if by_creation_token {
+                out.creation_token = out.creation_token.max(creation_token);
+            }
+        }
+        if let Some((_, file)) = downloaded.first() {
+            // The remaining bundles lack prerequisites, which is reported by trying to unbundle them.
+            self.unbundle_and_write_refs(file, progress, should_interrupt)?;
+        }
+        Ok(out)
+    }
+
+    fn unbundle_and_write_refs(
+        &self,
+        file: &BundleFile,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<unbundle::Outcome, Error> {
+        let outcome = self.unbundle(file.path(), progress, should_interrupt)?;
+        let edits: Vec<_> = outcome
+            .header
+            .refs
+            .iter()
+            .filter_map(|r| {
+                let name = r.name.strip_prefix(b"refs/heads/")?;
+                let mut full_name = BString::from("refs/bundles/");
+                full_name.push_str(name);
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: "bundle-uri: unbundle".into(),
+                        },
+                        expected: PreviousValue::Any,
+                        new: gix_ref::Target::Object(r.id),
+                    },
+                    name: full_name.try_into().ok()?,
+                    deref: false,
+                })
+            })
+            .collect();
+        if !edits.is_empty() {
+            self.edit_references(edits)?;
+        }
+        Ok(outcome)
+    }
+
+    fn download_bundle_or_list(&self, uri: &BStr) -> Result<Download, Error> {
+        let io_err = |source| Error::Io {
+            uri: uri.to_owned(),
+            source,
+        };
+        let url = gix_url::parse(uri)?;
+        let file = match url.scheme {
+            gix_url::Scheme::File => BundleFile::Existing(gix_path::from_bstr(url.path.as_bstr()).into_owned()),
+            #[cfg(any(
+                feature = "blocking-http-transport-curl",
+                feature = "blocking-http-transport-reqwest"
+            ))]
+            gix_url::Scheme::Http | gix_url::Scheme::Https => {
+                use gix_transport::client::http::Http;
+
+                let mut tempfile = gix_tempfile::new(
+                    self.common_dir(),
+                    gix_tempfile::ContainingDirectory::Exists,
+                    gix_tempfile::AutoRemove::Tempfile,
+                )
+                .map_err(io_err)?;
+                let url = uri.to_str_lossy();
+                let mut response = gix_transport::client::http::Impl::default()
+                    .get(&url, &url, None::<&str>)
+                    .map_err(|source| Error::Http {
+                        uri: uri.to_owned(),
+                        source,
+                    })?;
+                std::io::copy(&mut response.body, &mut tempfile).map_err(io_err)?;
+                let path = tempfile.with_mut(|file| file.path().to_owned()).map_err(io_err)?;
+                BundleFile::Downloaded {
+                    path,
+                    _tempfile: tempfile,
+                }
+            }
+            _ => return Err(Error::UnsupportedScheme { uri: uri.to_owned() }),
+        };
+        if gix_bundle::is_bundle(file.path()) {
+            return Ok(Download::Bundle(file));
+        }
+        let data = std::fs::read(file.path()).map_err(io_err)?;
+        let config =
+            gix_config::File::from_bytes_no_includes(&data, gix_config::file::Metadata::api(), Default::default())
+                .map_err(|source| Error::ParseListConfig {
+                    uri: uri.to_owned(),
+                    source,
+                })?;
+        let mut pairs = Vec::new();
+        for section in config.sections_by_name("bundle").into_iter().flatten() {
+            for value_name in section.value_names() {
+                let mut key = BString::from("bundle.");
+                if let Some(subsection) = section.header().subsection_name() {
+                    key.push_str(subsection);
+                    key.push(b'.');
+                }
+                key.push_str(value_name.as_ref());
+                let value = section.value(value_name).unwrap_or_default().into_owned();
+                pairs.push((key, value));
+            }
+        }
+        let list = List::from_key_value_pairs(pairs.iter().map(|(key, value)| (key.as_bstr(), value.as_bstr())))
+            .map_err(|source| Error::ParseList {
+                uri: uri.to_owned(),
+                source,
+            })?;
+        Ok(Download::List(list))
+    }
+}
+
+/// A bundle file that was downloaded or that exists locally.
+enum BundleFile {
+    Existing(PathBuf),
+    #[cfg(any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    ))]
+    Downloaded {
+        path: PathBuf,
+        /// Keeps the file alive until it was unbundled.
+        _tempfile: gix_tempfile::Handle<gix_tempfile::handle::Writable>,
+    },
+}
+
+impl BundleFile {
+    fn path(&self) -> &Path {
+        match self {
+            BundleFile::Existing(path) => path,
+            #[cfg(any(
+                feature = "blocking-http-transport-curl",
+                feature = "blocking-http-transport-reqwest"
+            ))]
+            BundleFile::Downloaded { path, .. } => path,
+        }
+    }
+}
+
+enum Download {
+    Bundle(BundleFile),
+    List(List),
+}
+
+/// Resolve `uri` relative to `base`, the uri of the list it was found in, unless it's absolute.
+fn resolve(base: &BStr, uri: &BStr) -> BString {
+    if uri.contains_str("://") || gix_path::from_bstr(uri).is_absolute() {
+        return uri.to_owned();
+    }
+    let mut out: BString = base.rfind_byte(b'/').map(|pos| base[..=pos].into()).unwrap_or_default();
+    out.push_str(uri);
+    out
+}
@@ -0,0 +1,33 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_features::progress::DynNestedProgress;
+
+use crate::Repository;
+
+/// The error returned by [`Repository::verify_bundle()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Open(#[from] gix_bundle::file::open::Error),
+    #[error(transparent)]
+    Verify(#[from] gix_bundle::file::verify::Error),
+}
+
+/// Bundles
+impl Repository {
+    /// Open the bundle at `path` and assure that this repository has all of its prerequisites, and that its pack is valid,
+    /// similar to `git bundle verify`.
+    ///
+    /// Return the opened bundle, which can be used to list its references.
+    pub fn verify_bundle(
+        &self,
+        path: impl Into<PathBuf>,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_bundle::File, Error> {
+        let bundle = gix_bundle::File::at(path)?;
+        bundle.verify(&self.objects, progress, should_interrupt)?;
+        Ok(bundle)
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{config, Repository};
+use std::{path::Path, sync::atomic::AtomicBool};
+
+use gix_index::entry::Flags;
+
+use crate::{bstr::BStr, config, Repository};
 
 impl Repository {
     /// Return options that can be used to drive a low-level checkout operation.
@@ -12,3 +16,78 @@ impl Repository {
         self.config.checkout_options(self, attributes_source)
     }
 }
+
+/// Check out all entries of `index` into `workdir` for which `is_selected(rela_path)` returns `true`, overwriting
+/// everything that is in their way, and leave all other entries untouched while still using them for attribute lookups.
+///
+/// Note that the [`files_updated`](gix_worktree_state::checkout::Outcome::files_updated) only count the selected entries.
+#[cfg(feature = "status")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn checkout_selected_entries(
+    index: &mut gix_index::State,
+    workdir: &Path,
+    objects: impl gix_object::Find + Send + Clone,
+    mut is_selected: impl FnMut(&BStr) -> bool,
+    files: &dyn gix_features::progress::Count,
+    bytes: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+    mut options: gix_worktree_state::checkout::Options,
+) -> Result<gix_worktree_state::checkout::Outcome, gix_worktree_state::checkout::Error> {
+    options.destination_is_initially_empty = false;
+    options.overwrite_existing = true;
+
+    let mut skipped_temporarily = Vec::new();
+    for (idx, (entry, path)) in index.entries_mut_with_paths().enumerate() {
+        if !entry.flags.contains(Flags::SKIP_WORKTREE) && !is_selected(path) {
+            entry.flags.insert(Flags::SKIP_WORKTREE);
+            skipped_temporarily.push(idx);
+        }
+    }
+    let res = gix_worktree_state::checkout(index, workdir, objects, files, bytes, should_interrupt, options);
+    for idx in &skipped_temporarily {
+        index.entries_mut()[*idx].flags.remove(Flags::SKIP_WORKTREE);
+    }
+    let mut outcome = res?;
+    // Skipped entries are counted as processed, but we only want to report what was actually written.
+    outcome.files_updated -= skipped_temporarily.len();
+    Ok(outcome)
+}
+
+/// Remove the file at `rela_path` in `workdir` along with all parent directories that are empty afterwards, and return `true`
+/// if something was removed.
+#[cfg(feature = "status")]
+pub(crate) fn remove_from_worktree(workdir: &Path, rela_path: &BStr) -> std::io::Result<bool> {
+    let path = workdir.join(gix_path::from_bstr(rela_path));
+    let res = match std::fs::symlink_metadata(&path) {
+        // Submodules are only removed if they are empty, just like Git does it.
+        Ok(md) if md.is_dir() => return Ok(std::fs::remove_dir(&path).is_ok()),
+        Ok(_) => std::fs::remove_file(&path),
+        Err(err) => Err(err),
+    };
+    match res {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    }
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(true)
+}
+
+/// Remove the file at `rela_path` in `workdir` so it can be recreated with the permissions of its new mode, as existing
+/// files keep their permissions when overwritten. Directories and files that don't exist are ignored.
+#[cfg(feature = "status")]
+pub(crate) fn remove_file_for_recreation(workdir: &Path, rela_path: &BStr) -> std::io::Result<()> {
+    let path = workdir.join(gix_path::from_bstr(rela_path));
+    match std::fs::symlink_metadata(&path) {
+        Ok(md) if md.is_dir() => Ok(()),
+        Ok(_) => std::fs::remove_file(&path),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
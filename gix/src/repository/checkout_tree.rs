@@ -0,0 +1,652 @@
+use std::{collections::BTreeSet, path::Path, sync::atomic::AtomicBool};
+
+use gix_index::entry::{Flags, Mode, Stage};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use super::checkout::{checkout_selected_entries, remove_file_for_recreation, remove_from_worktree};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Progress, Repository,
+};
+
+/// Determine what should happen to `HEAD` after [checking out a tree](Repository::checkout_tree()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateHead {
+    /// Let `HEAD` point to the given branch, which is expected to point to the commit that was checked out,
+    /// similar to `git switch <branch>`.
+    Branch(FullName),
+    /// Let `HEAD` point to the commit that was checked out directly, similar to `git switch --detach <commit>`.
+    Detached,
+}
+
+/// Options for use in [`Repository::checkout_tree()`].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// If `Some`, update `HEAD` in the way described, along with its reflog, once the worktree and index were updated.
+    /// This requires the checked-out object to be a commit.
+    /// If `None`, `HEAD` is left untouched, similar to `git read-tree -m -u`.
+    pub update_head: Option<UpdateHead>,
+    /// If `true`, perform a three-way merge between the tree at `HEAD`, the target tree and the local changes of files
+    /// that were changed between both trees, similar to `git checkout --merge`.
+    /// Otherwise, local changes in these files cause the operation to fail.
+    ///
+    /// Paths that could be merged, but with conflicts, are [listed](Outcome::conflicts).
+    #[cfg(feature = "merge")]
+    pub merge: bool,
+}
+
+/// The outcome of [`Repository::checkout_tree()`].
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome of writing all files that differ between the tree at `HEAD` and the target tree.
+    pub checkout: gix_worktree_state::checkout::Outcome,
+    /// The amount of files that were removed as they don't exist in the target tree.
+    pub files_removed: usize,
+    /// Paths whose local changes were merged with the changes in the target tree, but with conflicts.
+    /// Their worktree file contains conflict markers, and the index contains their base, the target version
+    /// as *ours* and the local version as *theirs*.
+    pub conflicts: Vec<BString>,
+}
+
+/// The progress ids used in [`Repository::checkout_tree()`].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of files checked out thus far.
+    CheckoutFiles,
+    /// The amount of bytes written in total, the aggregate of the size of the content of all files thus far.
+    BytesWritten,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CheckoutFiles => *b"CTCF",
+            ProgressId::BytesWritten => *b"CTCB",
+        }
+    }
+}
+
+/// The error returned by [`Repository::checkout_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot checkout a tree in a bare repository")]
+    BareRepository,
+    #[error("Entry '{path}' is unmerged and must be resolved before checking out another tree")]
+    Unmerged { path: BString },
+    #[error("Local changes would be overwritten by checkout: {}", paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    LocalChanges { paths: Vec<BString> },
+    #[error(
+        "The checkout was interrupted, leaving the worktree partially updated while the index and HEAD are unchanged"
+    )]
+    Interrupted,
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Peel(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    IndexFromTree(#[from] crate::repository::index_from_tree::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+    #[error(transparent)]
+    SparseCheckout(#[from] crate::repository::apply_sparse_checkout::Error),
+    #[error(transparent)]
+    StatusPlatform(#[from] crate::status::Error),
+    #[error(transparent)]
+    CreateStatusIterator(#[from] crate::status::into_iter::Error),
+    #[error(transparent)]
+    Status(#[from] crate::status::index_worktree::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error("Could not remove '{rela_path}' from the worktree")]
+    RemoveFile { rela_path: BString, source: std::io::Error },
+    #[error("Could not acquire lock for index file")]
+    AcquireIndexLock(#[from] gix_lock::acquire::Error),
+    #[error("Could not write the index to its lock file")]
+    WriteIndex(#[source] std::io::Error),
+    #[error("Could not commit lock for index file")]
+    CommitIndexLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    EditHead(#[from] crate::reference::edit::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    WorktreeFileToObject(#[from] crate::filter::pipeline::worktree_file_to_object::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    FindBlob(#[from] crate::object::find::existing::with_conversion::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    BlobMergeOptions(#[from] crate::repository::blob_merge_options::Error),
+    #[cfg(feature = "merge")]
+    #[error(transparent)]
+    WriteBlob(#[from] crate::object::write::Error),
+}
+
+/// A path whose entry differs between the tree at `HEAD` (`old`) and the target tree (`new`).
+struct TreeChange {
+    path: BString,
+    old: Option<(gix_hash::ObjectId, Mode)>,
+    new: Option<(gix_hash::ObjectId, Mode)>,
+}
+
+/// A path whose local changes were merged with the target version.
+#[cfg(feature = "merge")]
+struct Merged {
+    path: BString,
+    merged_id: gix_hash::ObjectId,
+    /// The ids of the base and local versions if the merge had conflicts.
+    conflict: Option<(gix_hash::ObjectId, gix_hash::ObjectId)>,
+}
+
+/// Checkout
+impl Repository {
+    /// Update the worktree and the index so they match the tree of `target`, a tree-ish, by applying only the changes between
+    /// the tree at `HEAD` and the target tree. Finally, update `HEAD` as configured in `options`.
+    ///
+    /// This is similar to a *two-way merge* performed by `git checkout` or `git switch`, which means that:
+    ///
+    /// * files that don't differ between both trees are left untouched, along with their local changes, staged or unstaged.
+    /// * files that differ between both trees are written or removed, unless they have local changes, as detected
+    ///   by a [status](Repository::status()) limited to these paths, or unless untracked files are in the way.
+    ///   In that case, nothing is changed and [`Error::LocalChanges`] lists the affected paths.
+    ///   With `options.merge`, local changes are merged instead, as [listed](Outcome::conflicts) if this leads to conflicts.
+    /// * a [sparse checkout](Repository::sparse_checkout_patterns()) is respected.
+    ///
+    /// The index and `HEAD` are only changed once the worktree was successfully updated, with the index being locked
+    /// before it is read and for the entire duration of the operation. The index is written first, and `HEAD` is
+    /// updated afterward.
+    ///
+    /// Use `progress` to learn about the amount of files and bytes written, and `should_interrupt` to stop the operation
+    /// before the index or `HEAD` are changed.
+    pub fn checkout_tree<P>(
+        &self,
+        target: impl Into<gix_hash::ObjectId>,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        self.checkout_tree_inner(target.into(), options, &mut progress, should_interrupt)
+    }
+
+    fn checkout_tree_inner(
+        &self,
+        target: gix_hash::ObjectId,
+        options: Options,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::checkout_tree()");
+        let workdir = self.workdir().ok_or(Error::BareRepository)?;
+        let target_commit = match options.update_head {
+            Some(_) => Some(self.find_object(target)?.peel_to_commit()?.id),
+            None => None,
+        };
+        let old_tree = self.head_tree_id_or_empty()?.detach();
+        let new_tree = self.find_object(target)?.peel_to_tree()?.id;
+        let from_name = self.head_name_for_reflog()?;
+        let to_name = match &options.update_head {
+            Some(UpdateHead::Branch(name)) => name.shorten().to_owned(),
+            _ => target.to_hex().to_string().into(),
+        };
+
+        // Lock the index before reading it so changes to it can't be lost while we use it to detect local changes.
+        let index_path = self.index_path();
+        let mut lock = std::io::BufWriter::with_capacity(
+            64 * 1024,
+            gix_lock::File::acquire_to_update_resource(&index_path, gix_lock::acquire::Fail::Immediately, None)?,
+        );
+
+        let mut index = (**self.index_or_empty()?).clone();
+        if index.is_sparse() {
+            index.expand_sparse_directories(&self.objects)?;
+        }
+        if let Some(entry) = index.entries().iter().find(|e| e.stage() != Stage::Unconflicted) {
+            return Err(Error::Unmerged {
+                path: entry.path(&index).to_owned(),
+            });
+        }
+
+        let (old_index, new_index) = (self.index_from_tree(&old_tree)?, self.index_from_tree(&new_tree)?);
+        let mut changes = tree_changes(&old_index, &new_index);
+        changes.retain(|change| current_entry(&index, change.path.as_ref()) != change.new);
+
+        let local_changes = self.local_changes(&changes)?;
+        #[cfg(feature = "merge")]
+        let mut merged = Vec::new();
+        let mut clobbered = Vec::new();
+        for change in &changes {
+            let current = current_entry(&index, change.path.as_ref());
+            let has_staged_changes = current != change.old;
+            if !has_staged_changes && !is_in_the_way(change, &local_changes, &index, workdir) {
+                continue;
+            }
+            #[cfg(feature = "merge")]
+            if options.merge && current.is_some() && !has_untracked_in_the_way(change, &local_changes, &index, workdir)
+            {
+                if let Some(m) = self.merge_local_changes(change, &index, [from_name.as_ref(), to_name.as_ref()])? {
+                    merged.push(m);
+                    continue;
+                }
+            }
+            clobbered.push(change.path.clone());
+        }
+        if !clobbered.is_empty() {
+            return Err(Error::LocalChanges { paths: clobbered });
+        }
+
+        let mut files_removed = 0;
+        let mut additions = false;
+        for change in &changes {
+            match change.new {
+                Some((id, mode)) => {
+                    match index.entry_index_by_path_and_stage(change.path.as_ref(), Stage::Unconflicted) {
+                        Some(idx) => {
+                            let entry = &mut index.entries_mut()[idx];
+                            if entry.mode != mode && !entry.flags.contains(Flags::SKIP_WORKTREE) {
+                                remove_file_for_recreation(workdir, change.path.as_ref()).map_err(|source| {
+                                    Error::RemoveFile {
+                                        rela_path: change.path.clone(),
+                                        source,
+                                    }
+                                })?;
+                            }
+                            entry.id = id;
+                            entry.mode = mode;
+                            entry.stat = Default::default();
+                            if let Some(tree) = index.tree_mut() {
+                                tree.invalidate_path(change.path.as_ref());
+                            }
+                        }
+                        None => {
+                            index.dangerously_push_entry(
+                                Default::default(),
+                                id,
+                                Flags::empty(),
+                                mode,
+                                change.path.as_ref(),
+                            );
+                            if let Some(tree) = index.tree_mut() {
+                                tree.invalidate_path(change.path.as_ref());
+                            }
+                            additions = true;
+                        }
+                    }
+                }
+                None => {
+                    let Some(idx) = index.entry_index_by_path_and_stage(change.path.as_ref(), Stage::Unconflicted)
+                    else {
+                        continue;
+                    };
+                    let entry = index.remove_entry_at_index(idx);
+                    if !entry.flags.contains(Flags::SKIP_WORKTREE)
+                        && remove_from_worktree(workdir, change.path.as_ref()).map_err(|source| Error::RemoveFile {
+                            rela_path: change.path.clone(),
+                            source,
+                        })?
+                    {
+                        files_removed += 1;
+                    }
+                }
+            }
+        }
+        if additions {
+            index.sort_entries();
+        }
+        self.apply_sparse_checkout(&mut index)?;
+
+        #[cfg(feature = "merge")]
+        for m in &merged {
+            if let Some(entry) = index.entry_mut_by_path_and_stage(m.path.as_ref(), Stage::Unconflicted) {
+                entry.id = m.merged_id;
+            }
+        }
+        let opts = self.checkout_options(gix_worktree::stack::state::attributes::Source::IdMapping)?;
+        let mut files = progress.add_child_with_id("checkout".to_string(), ProgressId::CheckoutFiles.into());
+        let mut bytes = progress.add_child_with_id("writing".to_string(), ProgressId::BytesWritten.into());
+        files.init(Some(index.entries().len()), crate::progress::count("files"));
+        bytes.init(None, crate::progress::bytes());
+
+        let start = std::time::Instant::now();
+        let checkout = checkout_selected_entries(
+            &mut index,
+            workdir,
+            self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+            |path| {
+                changes
+                    .binary_search_by(|change| change.path.as_bstr().cmp(path))
+                    .is_ok()
+            },
+            &files,
+            &bytes,
+            should_interrupt,
+            opts,
+        )?;
+        files.show_throughput(start);
+        bytes.show_throughput(start);
+        if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+
+        #[allow(unused_mut)]
+        let mut conflicts = Vec::new();
+        #[cfg(feature = "merge")]
+        if !merged.is_empty() {
+            for m in merged {
+                let Some(idx) = index.entry_index_by_path_and_stage(m.path.as_ref(), Stage::Unconflicted) else {
+                    continue;
+                };
+                let new_id = changes
+                    .binary_search_by(|change| change.path.cmp(&m.path))
+                    .ok()
+                    .and_then(|pos| changes[pos].new)
+                    .map(|(id, _)| id)
+                    .expect("merged paths exist in the target tree");
+                match m.conflict {
+                    None => {
+                        // The merged content differs from what's in the index, so it must appear as modification.
+                        let entry = &mut index.entries_mut()[idx];
+                        entry.id = new_id;
+                        entry.stat = Default::default();
+                    }
+                    Some((base_id, local_id)) => {
+                        let entry = index.remove_entry_at_index(idx);
+                        for (stage, id) in [(Stage::Base, base_id), (Stage::Ours, new_id), (Stage::Theirs, local_id)] {
+                            index.dangerously_push_entry(
+                                Default::default(),
+                                id,
+                                stage.into(),
+                                entry.mode,
+                                m.path.as_ref(),
+                            );
+                        }
+                        conflicts.push(m.path);
+                    }
+                }
+            }
+            index.sort_entries();
+        }
+
+        index
+            .write_to(&mut lock, Default::default())
+            .map_err(Error::WriteIndex)?;
+        // Only move `HEAD` once the index is in place, so a failure can't leave it pointing to the new commit
+        // while the index still matches the previous one.
+        lock.into_inner()
+            .map_err(|err| Error::WriteIndex(err.into_error()))?
+            .commit()?;
+        if let (Some(update_head), Some(commit)) = (options.update_head, target_commit) {
+            let mut message = BString::from("checkout: moving from ");
+            message.extend_from_slice(&from_name);
+            message.extend_from_slice(b" to ");
+            message.extend_from_slice(&to_name);
+            self.update_head_after_checkout(update_head, commit, message)?;
+        }
+
+        Ok(Outcome {
+            checkout,
+            files_removed,
+            conflicts,
+        })
+    }
+
+    /// Return all paths of `changes`, along with their untracked content, which have changes in the worktree
+    /// compared to the index.
+    fn local_changes(&self, changes: &[TreeChange]) -> Result<BTreeSet<BString>, Error> {
+        let mut out = BTreeSet::new();
+        if changes.is_empty() {
+            return Ok(out);
+        }
+        let patterns = changes.iter().map(|change| {
+            let mut pattern = BString::from(":(literal)");
+            pattern.extend_from_slice(&change.path);
+            pattern
+        });
+        for item in self
+            .status(gix_features::progress::Discard)?
+            .untracked_files(crate::status::UntrackedFiles::Files)
+            .index_worktree_rewrites(None)
+            .index_worktree_submodules(None)
+            .into_index_worktree_iter(patterns.collect::<Vec<_>>())?
+        {
+            let item = item?;
+            match item.summary() {
+                // Like Git, don't consider files that were deleted locally as they can be restored.
+                None | Some(crate::status::index_worktree::iter::Summary::Removed) => {}
+                Some(_) => {
+                    out.insert(item.rela_path().to_owned());
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    #[cfg(feature = "merge")]
+    fn merge_local_changes(
+        &self,
+        change: &TreeChange,
+        index: &gix_index::State,
+        [from_name, to_name]: [&BStr; 2],
+    ) -> Result<Option<Merged>, Error> {
+        let (Some((base_id, base_mode)), Some((new_id, new_mode))) = (change.old, change.new) else {
+            return Ok(None);
+        };
+        if !is_blob(base_mode) || !is_blob(new_mode) {
+            return Ok(None);
+        }
+        let local_id = match self
+            .filter_pipeline(None)?
+            .0
+            .worktree_file_to_object(change.path.as_ref(), index)?
+        {
+            Some((id, gix_object::tree::EntryKind::Blob | gix_object::tree::EntryKind::BlobExecutable, _)) => id,
+            Some(_) => return Ok(None),
+            None => match current_entry(index, change.path.as_ref()) {
+                Some((id, mode)) if is_blob(mode) => id,
+                _ => return Ok(None),
+            },
+        };
+
+        let base = self.find_blob(base_id)?.take_data();
+        let new = self.find_blob(new_id)?.take_data();
+        let local = self.find_blob(local_id)?.take_data();
+        if [&base, &new, &local]
+            .iter()
+            .any(|data| gix_filter::eol::Stats::from_bytes(data).is_binary())
+        {
+            return Ok(None);
+        }
+
+        let labels = gix_merge::blob::builtin_driver::text::Labels {
+            ancestor: Some(from_name),
+            current: Some(to_name),
+            other: Some("local".into()),
+        };
+        let mut input = gix_diff::blob::intern::InternedInput::new(&[][..], &[]);
+        let mut out = Vec::new();
+        let resolution = gix_merge::blob::builtin_driver::text(
+            &mut out,
+            &mut input,
+            labels,
+            &new,
+            &base,
+            &local,
+            self.blob_merge_options()?.text,
+        );
+        Ok(Some(Merged {
+            path: change.path.clone(),
+            merged_id: self.write_blob(&out)?.detach(),
+            conflict: matches!(resolution, gix_merge::blob::Resolution::Conflict).then_some((base_id, local_id)),
+        }))
+    }
+
+    fn head_name_for_reflog(&self) -> Result<BString, Error> {
+        let head = self.head()?;
+        Ok(match head.referent_name() {
+            Some(name) => name.shorten().to_owned(),
+            None => head
+                .id()
+                .map_or_else(|| "HEAD".into(), |id| id.to_hex().to_string().into()),
+        })
+    }
+
+    fn update_head_after_checkout(
+        &self,
+        update_head: UpdateHead,
+        commit: gix_hash::ObjectId,
+        message: BString,
+    ) -> Result<(), Error> {
+        let log = LogChange {
+            mode: RefLog::AndReference,
+            force_create_reflog: false,
+            message,
+        };
+        let head: FullName = "HEAD".try_into().expect("valid");
+        match update_head {
+            UpdateHead::Branch(name) => {
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log: log.clone(),
+                        expected: PreviousValue::Any,
+                        new: Target::Symbolic(name),
+                    },
+                    name: head.clone(),
+                    deref: false,
+                })?;
+                // Symbolic ref changes don't produce a reflog entry by themselves.
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::Only,
+                            ..log
+                        },
+                        expected: PreviousValue::Any,
+                        new: Target::Object(commit),
+                    },
+                    name: head,
+                    deref: false,
+                })?;
+            }
+            UpdateHead::Detached => {
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log,
+                        expected: PreviousValue::Any,
+                        new: Target::Object(commit),
+                    },
+                    name: head,
+                    deref: false,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Produce all changes between `old` and `new`, both of which are expected to be created from a tree, ordered by path.
+fn tree_changes(old: &gix_index::State, new: &gix_index::State) -> Vec<TreeChange> {
+    let mut out = Vec::new();
+    let (mut lhs, mut rhs) = (old.entries().iter().peekable(), new.entries().iter().peekable());
+    loop {
+        let ordering = match (lhs.peek(), rhs.peek()) {
+            (None, None) => break,
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (Some(l), Some(r)) => l.path(old).cmp(r.path(new)),
+        };
+        let (l, r) = match ordering {
+            std::cmp::Ordering::Less => (lhs.next(), None),
+            std::cmp::Ordering::Greater => (None, rhs.next()),
+            std::cmp::Ordering::Equal => (lhs.next(), rhs.next()),
+        };
+        let (old_entry, new_entry) = (l.map(|e| (e.id, e.mode)), r.map(|e| (e.id, e.mode)));
+        if old_entry != new_entry {
+            out.push(TreeChange {
+                path: l
+                    .map_or_else(|| r.expect("one is set").path(new), |e| e.path(old))
+                    .to_owned(),
+                old: old_entry,
+                new: new_entry,
+            });
+        }
+    }
+    out
+}
+
+#[cfg(feature = "merge")]
+fn is_blob(mode: Mode) -> bool {
+    mode == Mode::FILE || mode == Mode::FILE_EXECUTABLE
+}
+
+fn current_entry(index: &gix_index::State, path: &BStr) -> Option<(gix_hash::ObjectId, Mode)> {
+    index
+        .entry_by_path_and_stage(path, Stage::Unconflicted)
+        .map(|e| (e.id, e.mode))
+}
+
+/// Return `true` if the path of `change` has local changes in the worktree, or if untracked files are in the way.
+fn is_in_the_way(
+    change: &TreeChange,
+    local_changes: &BTreeSet<BString>,
+    index: &gix_index::State,
+    workdir: &Path,
+) -> bool {
+    local_changes.contains(&change.path) || has_untracked_in_the_way(change, local_changes, index, workdir)
+}
+
+/// Return `true` if untracked files would be overwritten when writing the new version of `change`, either because they
+/// are located inside a directory at its location, or because they are located where one of its parent directories would be.
+fn has_untracked_in_the_way(
+    change: &TreeChange,
+    local_changes: &BTreeSet<BString>,
+    index: &gix_index::State,
+    workdir: &Path,
+) -> bool {
+    if change.new.is_none() {
+        return false;
+    }
+    let mut dir_prefix = change.path.clone();
+    dir_prefix.push(b'/');
+    if local_changes
+        .range(dir_prefix.clone()..)
+        .next()
+        .is_some_and(|path| path.starts_with(&dir_prefix))
+    {
+        return true;
+    }
+    if index.entry_by_path(change.path.as_ref()).is_none() && local_changes.contains(&change.path) {
+        return true;
+    }
+
+    let mut parent = change.path.as_bstr();
+    while let Some(pos) = parent.rfind_byte(b'/') {
+        parent = parent[..pos].as_bstr();
+        let is_non_directory =
+            std::fs::symlink_metadata(workdir.join(gix_path::from_bstr(parent))).is_ok_and(|md| !md.is_dir());
+        if is_non_directory && index.entry_by_path(parent).is_none() {
+            return true;
+        }
+    }
+    false
+}
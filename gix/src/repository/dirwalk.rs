@@ -26,6 +26,10 @@ impl Repository {
     /// This is intentionally not the case when deletion is not intended so they look like
     /// untracked repositories instead.
     ///
+    /// Directories that are sparse directory entries in `index` are treated as tracked and aren't entered,
+    /// so untracked files within them aren't emitted. This differs from Git, which looks inside such directories
+    /// if they exist in the worktree.
+    ///
     /// See [`gix_dir::walk::delegate::Collect`] for a delegate that collects all seen entries.
     pub fn dirwalk(
         &self,
@@ -0,0 +1,596 @@
+use gix_diff::{
+    blob::{
+        stats::{self, stat, Counts},
+        Stats,
+    },
+    patch::{Binary, BinaryData, Content, File, Line, Operation},
+};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::{cache::util::ApplyLeniency, tree::Diff},
+    object::tree::diff::ChangeDetached,
+    Repository,
+};
+
+/// The maximum width of lines in the diff statistics, just like in `git format-patch`.
+const STAT_WIDTH: usize = 72;
+/// The maximum width of header lines.
+const HEADER_WIDTH: usize = 78;
+/// The maximum width of header lines with encoded words, as defined by RFC 2047.
+const ENCODED_HEADER_WIDTH: usize = 76;
+/// The amount of bytes to look at to determine if a blob is binary, just like `git` does.
+const BINARY_DETECTION_LIMIT: usize = 8000;
+/// The maximum length of the file name of a patch, including its suffix.
+const FILE_NAME_MAX_LEN: usize = 64;
+
+/// Options for use in [`Repository::format_patch()`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The text between the brackets at the start of the subject, like `PATCH` in `[PATCH 1/2]`.
+    pub subject_prefix: BString,
+    /// If `Some(true)`, the subject contains the number of the patch along with the total amount of patches,
+    /// like `[PATCH 1/2]`. If `None`, patches are numbered if there is more than one.
+    pub numbered: Option<bool>,
+    /// The number of the first patch.
+    pub start_number: usize,
+    /// The amount of unchanged lines to show around each change.
+    pub context_lines: u32,
+    /// If `true`, changes to binary files are included so they can be applied, like `git format-patch --binary`,
+    /// which is the default. Otherwise, only the fact that binary files differ is recorded.
+    pub binary: bool,
+    /// The text to place after the diff of each patch, separated by a `-- ` line, or `None` to not add a signature.
+    pub signature: Option<BString>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            subject_prefix: "PATCH".into(),
+            numbered: None,
+            start_number: 1,
+            context_lines: 3,
+            binary: true,
+            signature: None,
+        }
+    }
+}
+
+/// A single patch as produced by [`Repository::format_patch()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// The id of the commit the patch was created from.
+    pub commit_id: ObjectId,
+    /// The name `git format-patch` would write the patch to, like `0001-Fix-a-bug.patch`.
+    pub file_name: String,
+    /// The patch as mail in the mbox format, ready to be written to a file or to be sent.
+    ///
+    /// The data of multiple patches can be concatenated to form a single mbox.
+    pub data: Vec<u8>,
+}
+
+/// The error returned by [`Repository::format_patch()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    CommitTree(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    DiffTreeToTree(#[from] crate::repository::diff_tree_to_tree::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    ConfigUnsignedInteger(#[from] crate::config::unsigned_integer::Error),
+    #[error(transparent)]
+    ShortenId(#[from] crate::id::shorten::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Format patches
+impl Repository {
+    /// Turn each of the `commits` into a patch in the mbox format, similar to `git format-patch`.
+    ///
+    /// Each patch has a `From`, `Date` and `Subject` header derived from the author and the message of the commit,
+    /// followed by the rest of the commit message, the diff statistics and the diff to its first parent.
+    /// Renames are detected as configured for `git diff`, and merge commits are skipped.
+    /// Hunks are shaped by `diff.indentHeuristic` and `diff.interHunkContext`.
+    /// Hunk headers show the function or section each hunk belongs to as [found by default](gix_diff::blob::Funcname::default()),
+    /// as diff drivers aren't considered.
+    ///
+    /// Patches are produced in the order of `commits`, which should be applied oldest first. Note that a
+    /// [revision walk](crate::revision::Walk) yields the newest commits first, so its ids typically have to be reversed.
+    pub fn format_patch(
+        &self,
+        commits: impl IntoIterator<Item = impl Into<ObjectId>>,
+        options: &Options,
+    ) -> Result<Vec<Patch>, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::format_patch()");
+        let mut selected = Vec::new();
+        for id in commits {
+            let commit = self.find_commit(id)?;
+            if commit.parent_ids().count() <= 1 {
+                selected.push(commit);
+            }
+        }
+        let numbered = options.numbered.unwrap_or(selected.len() > 1);
+        let total = selected.len() + options.start_number.saturating_sub(1);
+        let algorithm = self.diff_algorithm()?;
+        let funcname = gix_diff::blob::Funcname::default();
+        let hunk_options = gix_diff::patch::hunks::Options {
+            context_lines: options.context_lines,
+            inter_hunk_context: self
+                .config
+                .resolved
+                .integer(Diff::INTER_HUNK_CONTEXT)
+                .map(|value| Diff::INTER_HUNK_CONTEXT.try_into_u32(value))
+                .transpose()
+                .with_leniency(self.config.lenient_config)?
+                .unwrap_or_default(),
+            indent_heuristic: self
+                .config
+                .resolved
+                .boolean(Diff::INDENT_HEURISTIC)
+                .map(|res| Diff::INDENT_HEURISTIC.enrich_error(res))
+                .transpose()
+                .with_leniency(self.config.lenient_config)?
+                .unwrap_or(true),
+            funcname: Some(&funcname),
+            whitespace: Default::default(),
+        };
+
+        let mut patches = Vec::with_capacity(selected.len());
+        for (number, commit) in (options.start_number..).zip(selected) {
+            let commit_ref = commit.decode()?;
+            let (subject, body) = split_message(commit_ref.message);
+
+            let mut out = Vec::new();
+            out.extend_from_slice(format!("From {} Mon Sep 17 00:00:00 2001\n", commit.id).as_bytes());
+            write_from_header(&mut out, commit_ref.author.name, commit_ref.author.email);
+            out.extend_from_slice(b"Date: ");
+            out.extend_from_slice(
+                commit_ref
+                    .author
+                    .time
+                    .format(gix_date::time::format::GIT_RFC2822)
+                    .as_bytes(),
+            );
+            out.extend_from_slice(b"\nSubject: ");
+            if numbered {
+                let width = total.to_string().len();
+                out.push(b'[');
+                if !options.subject_prefix.is_empty() {
+                    out.extend_from_slice(&options.subject_prefix);
+                    out.push(b' ');
+                }
+                out.extend_from_slice(format!("{number:0width$}/{total}] ").as_bytes());
+            } else if !options.subject_prefix.is_empty() {
+                out.push(b'[');
+                out.extend_from_slice(&options.subject_prefix);
+                out.extend_from_slice(b"] ");
+            }
+            if needs_rfc2047_encoding(subject.as_ref()) {
+                add_rfc2047(&mut out, subject.as_ref(), false);
+            } else {
+                let first_line_len = last_line_len(&out);
+                add_wrapped_text(&mut out, subject.as_ref(), first_line_len, 1, HEADER_WIDTH);
+            }
+            out.push(b'\n');
+            if !subject.is_ascii() || !body.is_ascii() {
+                out.extend_from_slice(
+                    b"MIME-Version: 1.0\nContent-Type: text/plain; charset=UTF-8\nContent-Transfer-Encoding: 8bit\n",
+                );
+            }
+            out.push(b'\n');
+            if !body.is_empty() {
+                out.extend_from_slice(&body);
+                out.push(b'\n');
+            }
+            out.extend_from_slice(b"---\n");
+
+            let old_tree = commit
+                .parent_ids()
+                .next()
+                .map(|id| -> Result<_, Error> { Ok(self.find_commit(id)?.tree()?) })
+                .transpose()?;
+            let new_tree = commit.tree()?;
+            let mut changes = self.diff_tree_to_tree(old_tree.as_ref(), &new_tree, None)?;
+            changes.retain(|change| !change.entry_mode().is_tree());
+            changes.sort_by(|a, b| a.location().cmp(b.location()));
+
+            let mut files = Vec::with_capacity(changes.len());
+            let mut stats = Stats::default();
+            for change in changes {
+                let (file, stat) = self.change_to_patch(change, algorithm, &hunk_options, options)?;
+                files.push(file);
+                stats.files.push(stat);
+            }
+            stats.write_stat(
+                &mut out,
+                &stat::Options {
+                    width: STAT_WIDTH,
+                    ..Default::default()
+                },
+            )?;
+            write_summary(&mut out, &files);
+            out.push(b'\n');
+            for file in &files {
+                file.write_to(&mut out)?;
+            }
+            if let Some(signature) = &options.signature {
+                out.extend_from_slice(b"-- \n");
+                out.extend_from_slice(signature);
+                out.extend_from_slice(b"\n\n");
+            }
+
+            patches.push(Patch {
+                commit_id: commit.id,
+                file_name: file_name(number, subject.as_ref()),
+                data: out,
+            });
+        }
+        Ok(patches)
+    }
+
+    fn change_to_patch(
+        &self,
+        change: ChangeDetached,
+        algorithm: gix_diff::blob::Algorithm,
+        hunk_options: &gix_diff::patch::hunks::Options<'_>,
+        options: &Options,
+    ) -> Result<(File, stats::File), Error> {
+        type Side = Option<(BString, EntryMode, ObjectId)>;
+        let (old, new, operation): (Side, Side, _) = match change {
+            ChangeDetached::Addition {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => (None, Some((location, entry_mode, id)), Operation::Addition),
+            ChangeDetached::Deletion {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => (Some((location, entry_mode, id)), None, Operation::Deletion),
+            ChangeDetached::Modification {
+                location,
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => (
+                Some((location.clone(), previous_entry_mode, previous_id)),
+                Some((location, entry_mode, id)),
+                Operation::Modification,
+            ),
+            ChangeDetached::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                diff,
+                entry_mode,
+                id,
+                location,
+                copy,
+                ..
+            } => {
+                let similarity = Some(diff.map_or(100, |diff| (diff.similarity * 100.0) as u8));
+                (
+                    Some((source_location, source_entry_mode, source_id)),
+                    Some((location, entry_mode, id)),
+                    if copy {
+                        Operation::Copy { similarity }
+                    } else {
+                        Operation::Rename { similarity }
+                    },
+                )
+            }
+        };
+
+        let old_data = old
+            .as_ref()
+            .map(|(_, mode, id)| self.data_for_diff(*mode, *id))
+            .transpose()?;
+        let new_data = new
+            .as_ref()
+            .map(|(_, mode, id)| self.data_for_diff(*mode, *id))
+            .transpose()?;
+        let (old_data, new_data) = (old_data.unwrap_or_default(), new_data.unwrap_or_default());
+        let is_binary = [&old_data, &new_data]
+            .iter()
+            .any(|data| data[..data.len().min(BINARY_DETECTION_LIMIT)].contains(&0));
+        let old_id = old.as_ref().map(|(_, _, id)| *id);
+        let new_id = new.as_ref().map(|(_, _, id)| *id);
+
+        let (old_prefix, new_prefix, content) = if old_id == new_id {
+            (None, None, Content::Text(Vec::new()))
+        } else {
+            let null = ObjectId::null(self.object_hash());
+            let (old_id, new_id) = (old_id.unwrap_or(null), new_id.unwrap_or(null));
+            let (old_prefix, new_prefix) = if is_binary {
+                (old_id.into(), new_id.into())
+            } else {
+                let shorten = |id: ObjectId| -> Result<Option<gix_hash::Prefix>, Error> {
+                    Ok((!id.is_null())
+                        .then(|| crate::Id::from_id(id, self).shorten())
+                        .transpose()?)
+                };
+                match (shorten(old_id)?, shorten(new_id)?) {
+                    (Some(old), Some(new)) => (old, new),
+                    (Some(prefix), None) => (prefix, null_prefix(null, prefix.hex_len())),
+                    (None, Some(prefix)) => (null_prefix(null, prefix.hex_len()), prefix),
+                    (None, None) => unreachable!("both ids differ so they can't both be null"),
+                }
+            };
+            let content = if !is_binary {
+                Content::Text(gix_diff::patch::hunks_with_options(
+                    &old_data,
+                    &new_data,
+                    algorithm,
+                    hunk_options,
+                ))
+            } else if options.binary {
+                Content::Binary(Binary {
+                    forward: BinaryData::Literal(new_data.clone()),
+                    reverse: Some(BinaryData::Literal(old_data.clone())),
+                })
+            } else {
+                Content::BinaryWithoutData
+            };
+            (Some(old_prefix), Some(new_prefix), content)
+        };
+
+        let counts = match &content {
+            _ if is_binary => {
+                let unchanged = old_id == new_id;
+                Counts::Binary {
+                    old_size: if unchanged { 0 } else { old_data.len() as u64 },
+                    new_size: if unchanged { 0 } else { new_data.len() as u64 },
+                }
+            }
+            Content::Text(hunks) => {
+                let (mut insertions, mut removals) = (0, 0);
+                for line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+                    match line {
+                        Line::Added(_) => insertions += 1,
+                        Line::Removed(_) => removals += 1,
+                        Line::Context(_) => {}
+                    }
+                }
+                Counts::Lines { insertions, removals }
+            }
+            Content::Binary(_) | Content::BinaryWithoutData => {
+                unreachable!("binary content is only produced for binary files")
+            }
+        };
+        let stat = stats::File {
+            source_location: old
+                .as_ref()
+                .zip(new.as_ref())
+                .map(|((old, ..), _)| old.clone())
+                .filter(|_| matches!(operation, Operation::Rename { .. } | Operation::Copy { .. })),
+            location: new
+                .as_ref()
+                .or(old.as_ref())
+                .map(|(path, ..)| path.clone())
+                .expect("a change has at least one side"),
+            counts,
+        };
+
+        let file = File {
+            old_mode: old.as_ref().map(|(_, mode, _)| *mode),
+            new_mode: new.as_ref().map(|(_, mode, _)| *mode),
+            old_path: old.map(|(path, ..)| path),
+            new_path: new.map(|(path, ..)| path),
+            old_id: old_prefix,
+            new_id: new_prefix,
+            operation,
+            content,
+        };
+        Ok((file, stat))
+    }
+
+    /// Return the data of the object with `id` as it is diffed, which for submodules is a description of the commit they point to.
+    fn data_for_diff(&self, mode: EntryMode, id: ObjectId) -> Result<Vec<u8>, Error> {
+        Ok(if mode.is_commit() {
+            format!("Subproject commit {id}\n").into_bytes()
+        } else {
+            self.find_blob(id)?.take_data()
+        })
+    }
+}
+
+fn null_prefix(null: ObjectId, hex_len: usize) -> gix_hash::Prefix {
+    gix_hash::Prefix::new(&null, hex_len).expect("the length is valid as it was taken from another prefix")
+}
+
+/// Split `message` into its subject, which is its first paragraph on a single line, and its body
+/// without leading empty lines and trailing whitespace.
+fn split_message(message: &BStr) -> (BString, BString) {
+    let subject = gix_object::commit::MessageRef::from_bytes(message)
+        .summary()
+        .into_owned();
+    let message = message.trim_start();
+    let mut body_start = message.len();
+    let mut lines = message.lines_with_terminator();
+    let mut pos = 0;
+    for line in lines.by_ref() {
+        pos += line.len();
+        if line.trim().is_empty() {
+            body_start = pos;
+            break;
+        }
+    }
+    let body = message[body_start.min(message.len())..].trim_end();
+    let body = body
+        .lines_with_terminator()
+        .skip_while(|line| line.trim().is_empty())
+        .flat_map(|line| line.iter().copied())
+        .collect::<Vec<_>>();
+    (subject, body.into())
+}
+
+/// Produce the name of the file `git format-patch` writes the patch with `number` and `subject` to.
+fn file_name(number: usize, subject: &BStr) -> String {
+    let mut name = format!("{number:04}-");
+    let start = name.len();
+    let mut separate = false;
+    let mut bytes = subject.iter().peekable();
+    while let Some(&b) = bytes.next() {
+        if b.is_ascii_alphanumeric() || b == b'.' || b == b'_' {
+            if separate && name.len() > start {
+                name.push('-');
+            }
+            separate = false;
+            name.push(b as char);
+            if b == b'.' {
+                while bytes.next_if_eq(&&b'.').is_some() {}
+            }
+        } else {
+            separate = true;
+        }
+    }
+    let trimmed_len = name[start..].trim_end_matches(['.', '-']).len();
+    name.truncate((start + trimmed_len).min(FILE_NAME_MAX_LEN - ".patch".len() - 1));
+    name.push_str(".patch");
+    name
+}
+
+/// Write the summary of created, deleted, renamed and copied files as well as mode changes, like `git diff --summary` does.
+fn write_summary(out: &mut Vec<u8>, files: &[File]) {
+    for file in files {
+        let old_path = file.old_path.as_ref().map(|path| path.as_bstr());
+        let new_path = file.new_path.as_ref().map(|path| path.as_bstr());
+        match (file.operation, old_path, new_path) {
+            (Operation::Addition, _, Some(path)) | (Operation::Deletion, Some(path), _) => {
+                let (verb, mode) = if file.operation == Operation::Addition {
+                    ("create", file.new_mode)
+                } else {
+                    ("delete", file.old_mode)
+                };
+                out.extend_from_slice(format!(" {verb} mode {:06o} ", mode.map_or(0, |mode| *mode)).as_bytes());
+                out.extend_from_slice(&gix_diff::patch::quote_path(path));
+                out.push(b'\n');
+            }
+            (Operation::Rename { similarity } | Operation::Copy { similarity }, Some(old), Some(new)) => {
+                let verb = if matches!(file.operation, Operation::Rename { .. }) {
+                    "rename"
+                } else {
+                    "copy"
+                };
+                out.extend_from_slice(format!(" {verb} ").as_bytes());
+                out.extend_from_slice(&stats::rename_name(old, new));
+                out.extend_from_slice(format!(" ({}%)\n", similarity.unwrap_or(100)).as_bytes());
+                if let Some((old_mode, new_mode)) = file.old_mode.zip(file.new_mode).filter(|(old, new)| old != new) {
+                    out.extend_from_slice(format!(" mode change {:06o} => {:06o}\n", *old_mode, *new_mode).as_bytes());
+                }
+            }
+            (Operation::Modification, _, Some(path)) => {
+                if let Some((old_mode, new_mode)) = file.old_mode.zip(file.new_mode).filter(|(old, new)| old != new) {
+                    out.extend_from_slice(format!(" mode change {:06o} => {:06o} ", *old_mode, *new_mode).as_bytes());
+                    out.extend_from_slice(&gix_diff::patch::quote_path(path));
+                    out.push(b'\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write the `From` header with the `name` and `email` of the author, encoding or quoting the name as needed.
+fn write_from_header(out: &mut Vec<u8>, name: &BStr, email: &BStr) {
+    out.extend_from_slice(b"From: ");
+    let mut max_len = HEADER_WIDTH;
+    if needs_rfc2047_encoding(name) {
+        add_rfc2047(out, name, true);
+        max_len = ENCODED_HEADER_WIDTH;
+    } else if name.iter().any(|b| b"()<>@,;:\\\".[]".contains(b)) {
+        let mut quoted = Vec::with_capacity(name.len() + 2);
+        quoted.push(b'"');
+        for &b in name.iter() {
+            if b == b'"' || b == b'\\' {
+                quoted.push(b'\\');
+            }
+            quoted.push(b);
+        }
+        quoted.push(b'"');
+        add_wrapped_text(out, quoted.as_bstr(), 6, 1, HEADER_WIDTH);
+    } else {
+        add_wrapped_text(out, name, 6, 1, HEADER_WIDTH);
+    }
+    if max_len < last_line_len(out) + " <".len() + email.len() + ">".len() {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(b" <");
+    out.extend_from_slice(email);
+    out.extend_from_slice(b">\n");
+}
+
+fn last_line_len(out: &[u8]) -> usize {
+    out.len() - out.rfind_byte(b'\n').map_or(0, |pos| pos + 1)
+}
+
+fn needs_rfc2047_encoding(text: &BStr) -> bool {
+    text.iter().any(|b| !b.is_ascii() || *b == b'\n') || text.find("=?").is_some()
+}
+
+/// Append `text` as RFC 2047 encoded words in the `Q` encoding to `out`, splitting them so no line gets longer than 76 characters.
+/// If `is_address` is `true`, `text` is the name in an address, which allows less characters to remain unencoded.
+fn add_rfc2047(out: &mut Vec<u8>, text: &BStr, is_address: bool) {
+    const START: &str = "=?UTF-8?q?";
+    let mut line_len = last_line_len(out) + START.len();
+    out.extend_from_slice(START.as_bytes());
+    for (start, end, _char) in text.char_indices() {
+        let bytes = &text[start..end];
+        let b = bytes[0];
+        let is_special = bytes.len() > 1
+            || !b.is_ascii()
+            || b.is_ascii_control()
+            || b" =?_".contains(&b)
+            || (is_address && !(b.is_ascii_alphanumeric() || b"!*+-/".contains(&b)));
+        let encoded_len = if is_special { 3 * bytes.len() } else { 1 };
+        if line_len + encoded_len + 2 > ENCODED_HEADER_WIDTH {
+            out.extend_from_slice(b"?=\n ");
+            out.extend_from_slice(START.as_bytes());
+            line_len = START.len() + 1;
+        }
+        if is_special {
+            for b in bytes.iter() {
+                out.extend_from_slice(format!("={b:02X}").as_bytes());
+            }
+        } else {
+            out.push(b);
+        }
+        line_len += encoded_len;
+    }
+    out.extend_from_slice(b"?=");
+}
+
+/// Append `text` to `out`, wrapped at whitespace so lines are at most `width` characters long if possible.
+/// The first line is assumed to already have `first_line_len` characters, and subsequent lines are indented by `indent`.
+fn add_wrapped_text(out: &mut Vec<u8>, text: &BStr, first_line_len: usize, indent: usize, width: usize) {
+    let mut line_len = first_line_len;
+    for (index, word) in text.split(|b| *b == b' ').filter(|word| !word.is_empty()).enumerate() {
+        let word_len = word.chars().count();
+        let separator_len = usize::from(index != 0);
+        if line_len + separator_len + word_len > width {
+            out.push(b'\n');
+            out.extend(std::iter::repeat(b' ').take(indent));
+            line_len = indent;
+        } else if separator_len != 0 {
+            out.push(b' ');
+            line_len += 1;
+        }
+        out.extend_from_slice(word);
+        line_len += word_len;
+    }
+}
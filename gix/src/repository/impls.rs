@@ -46,7 +46,7 @@ impl PartialEq<crate::Repository> for crate::Repository {
 
 impl From<&crate::ThreadSafeRepository> for crate::Repository {
     fn from(repo: &crate::ThreadSafeRepository) -> Self {
-        crate::Repository::from_refs_and_objects(
+        let mut out = crate::Repository::from_refs_and_objects(
             repo.refs.clone(),
             gix_odb::memory::Proxy::from(gix_odb::Cache::from(repo.objects.to_handle())).with_write_passthrough(),
             repo.work_tree.clone(),
@@ -58,13 +58,15 @@ impl From<&crate::ThreadSafeRepository> for crate::Repository {
             repo.shallow_commits.clone(),
             #[cfg(feature = "attributes")]
             repo.modules.clone(),
-        )
+        );
+        out.setup_promisor();
+        out
     }
 }
 
 impl From<crate::ThreadSafeRepository> for crate::Repository {
     fn from(repo: crate::ThreadSafeRepository) -> Self {
-        crate::Repository::from_refs_and_objects(
+        let mut out = crate::Repository::from_refs_and_objects(
             repo.refs,
             gix_odb::memory::Proxy::from(gix_odb::Cache::from(repo.objects.to_handle())).with_write_passthrough(),
             repo.work_tree,
@@ -76,7 +78,9 @@ impl From<crate::ThreadSafeRepository> for crate::Repository {
             repo.shallow_commits,
             #[cfg(feature = "attributes")]
             repo.modules.clone(),
-        )
+        );
+        out.setup_promisor();
+        out
     }
 }
 
@@ -110,6 +114,7 @@ impl gix_object::Write for crate::Repository {
         if self.objects.exists(&oid) {
             return Ok(oid);
         }
+        self.verify_object_if_strict(object, from).map_err(Box::new)?;
         self.objects.write_buf(object, from)
     }
 
@@ -153,7 +158,11 @@ impl gix_object::Find for crate::Repository {
                 data: &[],
             }));
         }
-        self.objects.try_find(id, buffer)
+        let data = self.objects.try_find(id, buffer)?;
+        if let Some(data) = &data {
+            self.verify_hash_if_strict(id, data)?;
+        }
+        Ok(data)
     }
 }
 
@@ -149,6 +149,19 @@ impl crate::Repository {
             self.git_dir().join("index"),
         ))
     }
+
+    /// Write all trees of `index` which aren't cached in its `TREE` extension to the object database,
+    /// and return the id of the root tree, the tree that represents the whole `index`.
+    ///
+    /// Note that the `TREE` extension of `index` is updated along the way, so writing `index` back to disk
+    /// will speed up the next invocation.
+    pub fn write_tree_from_index(
+        &self,
+        index: &mut gix_index::State,
+    ) -> Result<crate::Id<'_>, gix_index::write_tree::Error> {
+        let outcome = index.write_tree(|tree| self.write_object(tree).map(crate::Id::detach))?;
+        Ok(crate::Id::from_id(outcome.tree_id, self))
+    }
 }
 
 impl std::ops::Deref for IndexPersistedOrInMemory {
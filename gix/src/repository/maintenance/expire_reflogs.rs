@@ -0,0 +1,160 @@
+use std::{io::Write, path::PathBuf, time::SystemTime};
+
+use gix_hash::ObjectId;
+
+use super::inventory::seconds_since_epoch;
+use crate::{bstr::ByteSlice, Repository};
+
+/// Options for use in [`Repository::expire_reflogs()`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// Remove all reflog entries that were created before this time. If `None`, entries don't expire by age alone.
+    pub expire_before: Option<SystemTime>,
+    /// Remove all reflog entries that were created before this time and whose commit isn't reachable from the current
+    /// value of their reference. If `None`, unreachable entries are treated like any other entry.
+    pub expire_unreachable_before: Option<SystemTime>,
+    /// If `true`, determine which entries would be removed without actually removing them.
+    pub dry_run: bool,
+}
+
+/// The outcome of [`Repository::expire_reflogs()`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of reflogs from which entries were removed.
+    pub rewritten_reflogs: usize,
+    /// The amount of reflog entries that were removed.
+    pub removed_entries: usize,
+}
+
+/// The error returned by [`Repository::expire_reflogs()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    InitReferenceIter(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    WalkIter(#[from] crate::revision::walk::iter::Error),
+    #[error("Could not read reflog at '{path}'")]
+    ReadReflog { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    LockReflog(#[from] gix_lock::acquire::Error),
+    #[error("Could not write reflog at '{path}'")]
+    WriteReflog { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    CommitReflog(#[from] gix_lock::commit::Error<gix_lock::File>),
+}
+
+/// Expiring reflogs
+impl Repository {
+    /// Remove old entries from the reflogs of all references and of `HEAD`, similar to `git reflog expire --all`.
+    ///
+    /// An entry is removed if it was created before [`Options::expire_before`], or if it was created before
+    /// [`Options::expire_unreachable_before`] and its commit can't be reached from the current value of the reference.
+    pub fn expire_reflogs(&self, options: Options) -> Result<Outcome, Error> {
+        let mut out = Outcome::default();
+        if options.expire_before.is_none() && options.expire_unreachable_before.is_none() {
+            return Ok(out);
+        }
+
+        let mut reflogs = vec![(
+            gix_ref::FullName::try_from("HEAD").expect("valid"),
+            self.head()?.id().map(crate::Id::detach),
+        )];
+        for reference in self.references()?.all()? {
+            let mut reference = reference.map_err(Error::ReadReference)?;
+            let tip = reference.peel_to_id_in_place().ok().map(crate::Id::detach);
+            reflogs.push((reference.inner.name, tip));
+        }
+
+        let expire_before = options.expire_before.map(seconds_since_epoch);
+        let expire_unreachable_before = options.expire_unreachable_before.map(seconds_since_epoch);
+        for (name, tip) in reflogs {
+            let path = self.refs.reflog_path(name.as_ref());
+            if !path.is_file() {
+                continue;
+            }
+            // Lock before reading so entries that are appended concurrently aren't lost when writing. Like Git, we hold
+            // the lock of the reference as well, as reflog entries are appended while it is locked.
+            let (_reference_lock, lock) = if options.dry_run {
+                (None, None)
+            } else {
+                (
+                    Some(gix_lock::Marker::acquire_to_hold_resource(
+                        self.refs.reference_path(name.as_ref()),
+                        gix_lock::acquire::Fail::Immediately,
+                        Some(self.common_dir().to_owned()),
+                    )?),
+                    Some(gix_lock::File::acquire_to_update_resource(
+                        &path,
+                        gix_lock::acquire::Fail::Immediately,
+                        None,
+                    )?),
+                )
+            };
+            let content = match std::fs::read(&path) {
+                Ok(content) => content,
+                Err(err) if matches!(err.kind(), std::io::ErrorKind::NotFound) || path.is_dir() => continue,
+                Err(source) => return Err(Error::ReadReflog { path, source }),
+            };
+
+            let mut reachable = None::<gix_hashtable::HashSet<ObjectId>>;
+            let mut retained = Vec::with_capacity(content.len());
+            let mut removed = 0;
+            for line in content.lines_with_terminator() {
+                let Ok(entry) = gix_ref::file::log::LineRef::from_bytes(line.strip_suffix(b"\n").unwrap_or(line))
+                else {
+                    retained.extend_from_slice(line);
+                    continue;
+                };
+                let time = u32::try_from(entry.signature.time.seconds.max(0)).unwrap_or(u32::MAX);
+                let expired = expire_before.is_some_and(|expire_before| time < expire_before)
+                    || (expire_unreachable_before.is_some_and(|expire_before| time < expire_before) && {
+                        if reachable.is_none() {
+                            reachable = Some(self.ancestors_of(tip)?);
+                        }
+                        !reachable.as_ref().expect("just set").contains(&entry.new_oid())
+                    });
+                if expired {
+                    removed += 1;
+                } else {
+                    retained.extend_from_slice(line);
+                }
+            }
+
+            if removed == 0 {
+                continue;
+            }
+            out.rewritten_reflogs += 1;
+            out.removed_entries += removed;
+            if let Some(mut lock) = lock {
+                lock.write_all(&retained)
+                    .map_err(|source| Error::WriteReflog { path, source })?;
+                lock.commit()?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return all commits reachable from `tip`, or nothing if it's unset or not a commit.
+    fn ancestors_of(&self, tip: Option<ObjectId>) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+        let mut out = gix_hashtable::HashSet::default();
+        let Some(tip) = tip.filter(|tip| {
+            self.find_header(*tip)
+                .is_ok_and(|header| header.kind() == gix_object::Kind::Commit)
+        }) else {
+            return Ok(out);
+        };
+        for info in self.rev_walk([tip]).all()? {
+            out.insert(info?.id);
+        }
+        Ok(out)
+    }
+}
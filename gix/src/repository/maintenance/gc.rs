@@ -0,0 +1,215 @@
+use std::{sync::atomic::AtomicBool, time::SystemTime};
+
+use gix_features::progress::DynNestedProgress;
+
+use super::{expire_reflogs, pack_refs, prune, repack};
+use crate::{
+    config::{cache::util::ApplyLeniency, tree::Gc},
+    Repository,
+};
+
+/// Options for use in [`Repository::gc()`], typically obtained with [`Repository::gc_options()`].
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// If `true`, only collect garbage if there are more than [`auto_threshold`](Self::auto_threshold) loose objects
+    /// or more than [`auto_pack_limit`](Self::auto_pack_limit) packs, similar to `git gc --auto`.
+    pub auto: bool,
+    /// The approximate amount of loose objects that trigger a collection in [`auto`](Self::auto) mode,
+    /// with `0` disabling automatic collection entirely. Defaults to `gc.auto`.
+    pub auto_threshold: usize,
+    /// The amount of packs that trigger a collection in [`auto`](Self::auto) mode, with `0` disabling this check.
+    /// Defaults to `gc.autoPackLimit`.
+    pub auto_pack_limit: usize,
+    /// If `true`, pack all references. Defaults to `gc.packRefs`.
+    pub pack_refs: bool,
+    /// How to expire reflog entries. Defaults to `gc.reflogExpire` and `gc.reflogExpireUnreachable`.
+    pub reflogs: expire_reflogs::Options,
+    /// Unreachable objects that were last modified before this time are removed. If `None`, unreachable
+    /// objects are never removed. Defaults to `gc.pruneExpire`.
+    pub prune_expire_before: Option<SystemTime>,
+    /// If `true`, unreachable objects are written into a cruft pack, otherwise they are turned into loose objects.
+    /// Defaults to `gc.cruftPacks`.
+    pub cruft_packs: bool,
+    /// The amount of threads to use at most, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The outcome of [`Repository::gc()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The outcome of packing references, if [`Options::pack_refs`] was set.
+    pub pack_refs: Option<pack_refs::Outcome>,
+    /// The outcome of expiring reflog entries.
+    pub reflogs: expire_reflogs::Outcome,
+    /// The outcome of repacking all objects.
+    pub repack: repack::Outcome,
+    /// The outcome of pruning unreachable loose objects, if [`Options::prune_expire_before`] was set.
+    pub prune: Option<prune::Outcome>,
+}
+
+/// The error returned by [`Repository::gc()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not count loose objects or packs")]
+    CountObjects(#[source] std::io::Error),
+    #[error(transparent)]
+    PackRefs(#[from] pack_refs::Error),
+    #[error(transparent)]
+    ExpireReflogs(#[from] expire_reflogs::Error),
+    #[error(transparent)]
+    Repack(#[from] repack::Error),
+    #[error(transparent)]
+    Prune(#[from] prune::Error),
+}
+
+///
+pub mod options {
+    /// The error returned by [`Repository::gc_options()`](crate::Repository::gc_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigInteger(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        ConfigExpiry(#[from] crate::config::key::GenericErrorWithValue<gix_date::parse::Error>),
+    }
+}
+
+/// Garbage collection
+impl Repository {
+    /// Return the options for use in [`gc()`](Self::gc()) as configured, with `now` as reference for relative dates.
+    pub fn gc_options(&self, now: SystemTime) -> Result<Options, options::Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let integer = |key: &'static crate::config::tree::keys::UnsignedInteger, default: usize| {
+            config
+                .integer(key)
+                .map(|value| key.try_into_usize(value))
+                .transpose()
+                .with_leniency(lenient)
+                .map(|value| value.unwrap_or(default))
+        };
+        let boolean = |key: &'static crate::config::tree::keys::Boolean, default: bool| {
+            config
+                .boolean(key)
+                .map(|value| key.enrich_error(value))
+                .transpose()
+                .with_leniency(lenient)
+                .map(|value| value.unwrap_or(default))
+        };
+        let expiry = |key: &'static crate::config::tree::gc::Expiry, default: &str| {
+            key.try_into_expiry(
+                config
+                    .string(key)
+                    .unwrap_or_else(|| crate::bstr::BStr::new(default).into()),
+                now,
+            )
+        };
+        Ok(Options {
+            auto: false,
+            auto_threshold: integer(&Gc::AUTO, 6700)?,
+            auto_pack_limit: integer(&Gc::AUTO_PACK_LIMIT, 50)?,
+            pack_refs: boolean(&Gc::PACK_REFS, true)?,
+            reflogs: expire_reflogs::Options {
+                expire_before: expiry(&Gc::REFLOG_EXPIRE, "90.days.ago")?,
+                expire_unreachable_before: expiry(&Gc::REFLOG_EXPIRE_UNREACHABLE, "30.days.ago")?,
+                dry_run: false,
+            },
+            prune_expire_before: expiry(&Gc::PRUNE_EXPIRE, "2.weeks.ago")?,
+            cruft_packs: boolean(&Gc::CRUFT_PACKS, true)?,
+            thread_limit: None,
+        })
+    }
+
+    /// Return `true` if [`gc()`](Self::gc()) would collect garbage in [`auto`](Options::auto) mode, as there are
+    /// too many loose objects or packs.
+    ///
+    /// Like `git`, the amount of loose objects is estimated by only counting the objects in a single fan-out directory.
+    pub fn gc_is_needed(&self, options: &Options) -> std::io::Result<bool> {
+        if options.auto_threshold == 0 {
+            return Ok(false);
+        }
+        let objects_dir = self.objects.store_ref().path();
+        let hex_len = self.object_hash().len_in_hex();
+        let read_dir = |dir: std::path::PathBuf| match std::fs::read_dir(dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            res => res?.map(|entry| entry.map(|entry| entry.file_name())).collect(),
+        };
+
+        let loose_in_sample_dir = read_dir(objects_dir.join("17"))?
+            .iter()
+            .filter(|name| {
+                name.to_str()
+                    .is_some_and(|name| name.len() == hex_len - 2 && name.bytes().all(|b| b.is_ascii_hexdigit()))
+            })
+            .count();
+        if loose_in_sample_dir > (options.auto_threshold + 255) / 256 {
+            return Ok(true);
+        }
+
+        if options.auto_pack_limit == 0 {
+            return Ok(false);
+        }
+        let pack_dir = objects_dir.join("pack");
+        let packs = read_dir(pack_dir.clone())?
+            .iter()
+            .filter_map(|name| name.to_str()?.strip_suffix(".pack").map(ToOwned::to_owned))
+            .filter(|stem| !pack_dir.join(format!("{stem}.keep")).exists())
+            .count();
+        Ok(packs > options.auto_pack_limit)
+    }
+
+    /// Collect garbage similar to `git gc`, by packing references, expiring reflog entries, writing all
+    /// reachable objects into a single pack and pruning unreachable objects which expired.
+    ///
+    /// Return `None` if [`Options::auto`] is set and there was no need to collect garbage.
+    pub fn gc(
+        &self,
+        options: Options,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<Outcome>, Error> {
+        if options.auto && !self.gc_is_needed(&options).map_err(Error::CountObjects)? {
+            return Ok(None);
+        }
+
+        let pack_refs = options.pack_refs.then(|| self.pack_refs()).transpose()?;
+        let reflogs = self.expire_reflogs(options.reflogs)?;
+        let expire_before = options.prune_expire_before;
+        let repack = self.repack(
+            repack::Options {
+                geometric_factor: None,
+                unreachable: if options.cruft_packs {
+                    repack::Unreachable::Cruft { expire_before }
+                } else {
+                    repack::Unreachable::Loosen { expire_before }
+                },
+                thread_limit: options.thread_limit,
+            },
+            progress,
+            should_interrupt,
+        )?;
+        let prune = expire_before
+            .map(|expire_before| {
+                self.prune(
+                    prune::Options {
+                        expire_before: Some(expire_before),
+                        dry_run: false,
+                        thread_limit: options.thread_limit,
+                    },
+                    progress,
+                    should_interrupt,
+                )
+            })
+            .transpose()?;
+        Ok(Some(Outcome {
+            pack_refs,
+            reflogs,
+            repack,
+            prune,
+        }))
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gix_hash::ObjectId;
+
+/// The error returned when listing the loose objects and packs of the object database.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not access '{path}'")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    IterLooseObjects(#[from] gix_odb::loose::iter::Error),
+    #[error(transparent)]
+    OpenPackIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    OpenMtimes(#[from] gix_pack::mtimes::init::Error),
+}
+
+/// A pack in the `objects/pack` directory.
+pub(super) struct Pack {
+    /// The path to the `.idx` file of the pack.
+    pub index_path: PathBuf,
+    pub index: gix_pack::index::File,
+    /// The modification time of the pack data file, in seconds since the unix epoch.
+    pub mtime: u32,
+    /// If `true`, the pack must not be altered as it has a `.keep` or `.promisor` file.
+    pub keep: bool,
+    /// If `true`, the pack was received from a promisor remote, so objects it refers to may be missing.
+    pub promisor: bool,
+    /// The modification times of all objects, if this is a cruft pack.
+    pub cruft: Option<gix_pack::mtimes::File>,
+}
+
+impl Pack {
+    /// Return the modification time of the object at `index` in the pack index, in seconds since the unix epoch.
+    pub fn mtime_at_index(&self, index: u32) -> u32 {
+        self.cruft
+            .as_ref()
+            .map_or(self.mtime, |mtimes| mtimes.mtime_at_index(index))
+    }
+
+    /// Remove all files belonging to this pack.
+    pub fn remove(&self) -> Result<(), Error> {
+        for extension in ["pack", "rev", "bitmap", "mtimes", "idx"] {
+            let path = self.index_path.with_extension(extension);
+            super::remove_file_if_present(&path).map_err(|source| Error::Io { path, source })?;
+        }
+        Ok(())
+    }
+}
+
+/// All packs and loose objects in the object database of a repository, but not of its alternates.
+pub(super) struct Inventory {
+    pub pack_dir: PathBuf,
+    pub packs: Vec<Pack>,
+    pub loose_store: gix_odb::loose::Store,
+    /// All loose objects along with their modification time in seconds since the unix epoch.
+    pub loose: Vec<(ObjectId, u32)>,
+}
+
+impl Inventory {
+    /// List all objects in `objects_dir`.
+    pub fn load(objects_dir: &Path, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        let pack_dir = objects_dir.join("pack");
+        let mut index_paths = Vec::new();
+        match std::fs::read_dir(&pack_dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry
+                        .map_err(|source| Error::Io {
+                            path: pack_dir.clone(),
+                            source,
+                        })?
+                        .path();
+                    let is_pack_index = path.extension().is_some_and(|ext| ext == "idx")
+                        && path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.starts_with("pack-"));
+                    if is_pack_index && path.with_extension("pack").is_file() {
+                        index_paths.push(path);
+                    }
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(source) => return Err(Error::Io { path: pack_dir, source }),
+        }
+        index_paths.sort();
+
+        let mut packs = Vec::with_capacity(index_paths.len());
+        for index_path in index_paths {
+            let index = gix_pack::index::File::at(&index_path, object_hash)?;
+            let data_path = index_path.with_extension("pack");
+            let mtime = mtime_of(&data_path)?;
+            let mtimes_path = index_path.with_extension("mtimes");
+            let cruft = mtimes_path
+                .is_file()
+                .then(|| gix_pack::mtimes::File::at(&mtimes_path, object_hash, index.num_objects()))
+                .transpose()?;
+            let promisor = gix_odb::promisor::is_promisor_pack(&index_path);
+            packs.push(Pack {
+                keep: promisor || index_path.with_extension("keep").exists(),
+                promisor,
+                index_path,
+                index,
+                mtime,
+                cruft,
+            });
+        }
+
+        let loose_store = gix_odb::loose::Store::at(objects_dir, object_hash);
+        let mut loose = Vec::new();
+        for id in loose_store.iter() {
+            let id = id?;
+            loose.push((id, mtime_of(&loose_store.object_path(&id))?));
+        }
+        loose.sort();
+
+        Ok(Inventory {
+            pack_dir,
+            packs,
+            loose_store,
+            loose,
+        })
+    }
+
+    /// Return the ids of all objects in packs received from a promisor remote.
+    pub fn promisor_objects(&self) -> gix_hashtable::HashSet<ObjectId> {
+        let mut out = gix_hashtable::HashSet::default();
+        for pack in self.packs.iter().filter(|pack| pack.promisor) {
+            out.extend((0..pack.index.num_objects()).map(|idx| pack.index.oid_at_index(idx).to_owned()));
+        }
+        out
+    }
+
+    /// Return `true` if a multi-pack index is present.
+    pub fn has_multi_pack_index(&self) -> bool {
+        self.multi_pack_index_path().is_file()
+    }
+
+    pub fn multi_pack_index_path(&self) -> PathBuf {
+        self.pack_dir.join("multi-pack-index")
+    }
+
+    /// Remove the loose object with `id`, and its fan-out directory if it became empty.
+    pub fn remove_loose_object(&self, id: &gix_hash::oid) -> Result<(), Error> {
+        let path = self.loose_store.object_path(id);
+        super::remove_file_if_present(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        if let Some(dir) = path.parent() {
+            // Fails if the directory isn't empty, which is expected.
+            std::fs::remove_dir(dir).ok();
+        }
+        Ok(())
+    }
+}
+
+/// Return the modification time of the file at `path` in seconds since the unix epoch.
+fn mtime_of(path: &Path) -> Result<u32, Error> {
+    let mtime = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+    Ok(seconds_since_epoch(mtime))
+}
+
+/// Convert `time` into seconds since the unix epoch, as stored in `.mtimes` files.
+pub(super) fn seconds_since_epoch(time: SystemTime) -> u32 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs().min(u32::MAX as u64) as u32)
+}
@@ -0,0 +1,33 @@
+//! Keep a repository in shape by consolidating objects into packs, removing unreachable objects, packing references
+//! and expiring reflog entries, similar to what `git gc` does with the help of `git repack`, `git prune`,
+//! `git pack-refs` and `git reflog expire`.
+//!
+//! Objects from alternate object databases are never touched, and packs with a `.keep` or `.promisor` file are left as is.
+//! In partial clones, missing objects are never fetched from the promisor remote.
+use std::path::Path;
+
+///
+pub mod expire_reflogs;
+///
+pub mod gc;
+///
+pub mod inventory;
+///
+pub mod pack_refs;
+///
+pub mod prune;
+///
+pub mod reachable;
+///
+pub mod repack;
+
+/// A handle to the object database that can be shared across threads, as needed to create packs.
+type OdbArc = gix_odb::Cache<gix_odb::store::Handle<std::sync::Arc<gix_odb::Store>>>;
+
+/// Remove `path`, treating files that are already gone as success.
+fn remove_file_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
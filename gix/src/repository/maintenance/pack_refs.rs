@@ -0,0 +1,75 @@
+use gix_ref::{
+    file::transaction::PackedRefs,
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+};
+
+use crate::Repository;
+
+/// The outcome of [`Repository::pack_refs()`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of loose references that were moved into the `packed-refs` file.
+    pub packed_refs: usize,
+}
+
+/// The error returned by [`Repository::pack_refs()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not list loose references")]
+    IterLooseReferences(#[source] std::io::Error),
+    #[error(transparent)]
+    ReadLooseReference(#[from] gix_ref::file::iter::loose_then_packed::Error),
+    #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
+    LockTimeoutConfiguration(#[from] crate::config::lock_timeout::Error),
+    #[error(transparent)]
+    FileTransactionPrepare(#[from] gix_ref::file::transaction::prepare::Error),
+    #[error(transparent)]
+    FileTransactionCommit(#[from] gix_ref::file::transaction::commit::Error),
+}
+
+/// Packing references
+impl Repository {
+    /// Move all loose references that point to an object into the `packed-refs` file and remove their loose files,
+    /// similar to `git pack-refs --all`.
+    ///
+    /// Symbolic references and references that are private to a worktree, like `refs/bisect/*`, remain loose.
+    pub fn pack_refs(&self) -> Result<Outcome, Error> {
+        let mut edits = Vec::new();
+        for reference in self.refs.loose_iter().map_err(Error::IterLooseReferences)? {
+            let reference = reference?;
+            let is_shared = reference
+                .name
+                .category()
+                .is_some_and(|category| !category.is_worktree_private());
+            if !is_shared || !matches!(reference.target, gix_ref::Target::Object(_)) {
+                continue;
+            }
+            edits.push(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::MustExistAndMatch(reference.target.clone()),
+                    new: reference.target,
+                },
+                name: reference.name,
+                deref: false,
+            });
+        }
+        if edits.is_empty() {
+            return Ok(Outcome::default());
+        }
+
+        let (file_lock_fail, packed_refs_lock_fail) = self.config.lock_timeout()?;
+        let edits = self
+            .refs
+            .transaction()
+            .packed_refs(PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(
+                Box::new(&self.objects),
+            ))
+            .prepare(edits, file_lock_fail, packed_refs_lock_fail)?
+            .commit(None)?;
+        Ok(Outcome {
+            packed_refs: edits.len(),
+        })
+    }
+}
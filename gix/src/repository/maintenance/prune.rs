@@ -0,0 +1,165 @@
+use std::{sync::atomic::AtomicBool, time::SystemTime};
+
+use gix_features::progress::DynNestedProgress;
+use gix_hash::ObjectId;
+
+use super::{
+    inventory::{self, seconds_since_epoch, Inventory},
+    reachable,
+};
+use crate::Repository;
+
+/// Options for use in [`Repository::prune()`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// Only remove unreachable loose objects that were last modified before this time.
+    /// If `None`, all unreachable loose objects are removed.
+    pub expire_before: Option<SystemTime>,
+    /// If `true`, determine which objects would be removed without actually removing them.
+    pub dry_run: bool,
+    /// The amount of threads to use at most, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The outcome of [`Repository::prune()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The ids of the unreachable loose objects that were removed, sorted by id.
+    pub pruned_objects: Vec<ObjectId>,
+    /// The amount of loose objects that were removed as they are also present in a pack.
+    pub removed_packed_objects: usize,
+    /// The commits that were removed from the `shallow` file as they are not present anymore, sorted by id.
+    ///
+    /// In a dry-run, these are the ones that are not present even before pruning.
+    pub pruned_shallow_commits: Vec<ObjectId>,
+}
+
+/// The error returned by [`Repository::prune()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Inventory(#[from] inventory::Error),
+    #[error(transparent)]
+    Reachable(#[from] reachable::Error),
+    #[error(transparent)]
+    PruneShallow(#[from] crate::shallow::prune::Error),
+}
+
+/// Pruning
+impl Repository {
+    /// Remove all loose objects that are unreachable and older than [`Options::expire_before`], similar to `git prune`,
+    /// along with all loose objects which are also present in a pack, similar to `git prune-packed`.
+    ///
+    /// Objects are reachable if they can be reached from a reference, its reflog, or from `HEAD`, the reflog of `HEAD`
+    /// or the index of any worktree. Objects that can be reached from unreachable objects which aren't expired yet
+    /// are kept as well.
+    ///
+    /// Commits that are listed in the `shallow` file but aren't present anymore are removed from it as well.
+    ///
+    /// In partial clones, missing objects are never fetched, and objects in packs received from the promisor remote
+    /// aren't traversed, as objects they refer to are expected to be missing.
+    pub fn prune(
+        &self,
+        options: Options,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        if self.objects.promisor().is_some() {
+            // Like `git`, never fetch missing objects as these are expected to be missing in partial clones.
+            let mut repo = self.clone();
+            repo.objects.set_promisor(None);
+            return repo.prune(options, progress, should_interrupt);
+        }
+        let inventory = Inventory::load(self.objects.store_ref().path(), self.object_hash())?;
+        let mut packed = gix_hashtable::HashSet::default();
+        for pack in &inventory.packs {
+            packed.extend((0..pack.index.num_objects()).map(|idx| pack.index.oid_at_index(idx).to_owned()));
+        }
+
+        let mut out = Outcome::default();
+        let expire_before = options.expire_before.map(seconds_since_epoch);
+        let candidates: Vec<_> = inventory
+            .loose
+            .iter()
+            .filter(|(id, mtime)| {
+                !packed.contains(id) && expire_before.map_or(true, |expire_before| *mtime < expire_before)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        let mut reachable: gix_hashtable::HashSet<_> = Default::default();
+        if !candidates.is_empty() {
+            let mut odb = self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?.into_inner();
+            odb.prevent_pack_unload();
+            odb.ignore_replacements = true;
+            let promisor_objects = inventory.promisor_objects();
+            reachable.extend(
+                reachable::objects(
+                    self,
+                    &promisor_objects,
+                    &odb,
+                    progress,
+                    should_interrupt,
+                    options.thread_limit,
+                )?
+                .into_iter()
+                .map(|count| count.id),
+            );
+
+            // Unreachable objects that aren't expired yet keep all objects they refer to, so these don't
+            // go missing while they are still around.
+            if let Some(expire_before) = expire_before {
+                let mut recent: Vec<_> = inventory
+                    .loose
+                    .iter()
+                    .filter(|(id, mtime)| *mtime >= expire_before && !reachable.contains(id))
+                    .map(|(id, _)| *id)
+                    .collect();
+                for pack in &inventory.packs {
+                    recent.extend((0..pack.index.num_objects()).filter_map(|idx| {
+                        let id = pack.index.oid_at_index(idx).to_owned();
+                        (pack.mtime_at_index(idx) >= expire_before && !reachable.contains(&id)).then_some(id)
+                    }));
+                }
+                if !recent.is_empty() {
+                    let referenced_by_recent = reachable::objects_from(
+                        self,
+                        recent,
+                        &|id| reachable.contains(id) || promisor_objects.contains(id),
+                        &odb,
+                        progress,
+                        should_interrupt,
+                        options.thread_limit,
+                    )?;
+                    reachable.extend(referenced_by_recent.into_iter().map(|count| count.id));
+                }
+            }
+        }
+
+        for (id, _) in &inventory.loose {
+            if packed.contains(id) {
+                if !options.dry_run {
+                    inventory.remove_loose_object(id)?;
+                }
+                out.removed_packed_objects += 1;
+            }
+        }
+        for id in candidates.into_iter().filter(|id| !reachable.contains(id)) {
+            if !options.dry_run {
+                inventory.remove_loose_object(&id)?;
+            }
+            out.pruned_objects.push(id);
+        }
+        out.pruned_shallow_commits = if options.dry_run {
+            self.shallow_commits()
+                .map_err(crate::shallow::prune::Error::from)?
+                .map(|commits| commits.iter().filter(|id| !self.has_object(*id)).copied().collect())
+                .unwrap_or_default()
+        } else {
+            self.prune_shallow_commits()?
+        };
+        Ok(out)
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_features::progress::{Count, DynNestedProgress, Progress};
+use gix_hash::ObjectId;
+use gix_pack::data::output;
+
+use super::OdbArc;
+use crate::{bstr::BStr, Repository};
+
+/// The error returned when determining which objects are reachable.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    OpenMainRepository(#[from] crate::open::Error),
+    #[error("Could not list the worktrees of the repository")]
+    ListWorktrees(#[source] std::io::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    InitReferenceIter(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    ReadReflog(#[from] gix_ref::file::log::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    WalkIter(#[from] crate::revision::walk::iter::Error),
+    #[error(transparent)]
+    CountObjects(#[from] output::count::objects::Error),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+/// Return the ids of all existing objects that are directly referenced by the repository, which are the targets of all
+/// references and their reflog entries, as well as `HEAD`, its reflog and the index of each worktree.
+pub(super) fn tips(repo: &Repository) -> Result<Vec<ObjectId>, Error> {
+    let mut tips = Vec::new();
+    let mut buf = Vec::new();
+    let main_repo;
+    let main = if matches!(repo.kind(), crate::repository::Kind::WorkTree { is_linked: true }) {
+        main_repo = repo.main_repo()?;
+        &main_repo
+    } else {
+        repo
+    };
+
+    for reference in main.references()?.all()? {
+        let reference = reference.map_err(Error::ReadReference)?;
+        tips.extend(reference.inner.target.try_id().map(ToOwned::to_owned));
+        add_reflog_ids(main, reference.name().as_bstr(), &mut buf, &mut tips)?;
+    }
+
+    let worktrees = main
+        .worktrees()
+        .map_err(Error::ListWorktrees)?
+        .into_iter()
+        .map(crate::worktree::Proxy::into_repo_with_possibly_inaccessible_worktree)
+        .collect::<Result<Vec<_>, _>>()?;
+    for worktree in std::iter::once(main).chain(worktrees.iter()) {
+        tips.extend(worktree.head()?.id().map(crate::Id::detach));
+        add_reflog_ids(worktree, "HEAD".into(), &mut buf, &mut tips)?;
+        if let Some(index) = worktree.try_index()? {
+            tips.extend(
+                index
+                    .entries()
+                    .iter()
+                    .filter(|entry| entry.mode != gix_index::entry::Mode::COMMIT)
+                    .map(|entry| entry.id),
+            );
+            let mut trees: Vec<_> = index.tree().into_iter().collect();
+            while let Some(tree) = trees.pop() {
+                if tree.num_entries.is_some() {
+                    tips.push(tree.id);
+                }
+                trees.extend(tree.children.iter());
+            }
+        }
+    }
+
+    tips.sort();
+    tips.dedup();
+    tips.retain(|id| !id.is_null() && repo.has_object(id));
+    Ok(tips)
+}
+
+fn add_reflog_ids(repo: &Repository, name: &BStr, buf: &mut Vec<u8>, out: &mut Vec<ObjectId>) -> Result<(), Error> {
+    let Ok(name) = <&gix_ref::FullNameRef>::try_from(name) else {
+        return Ok(());
+    };
+    if let Some(log) = repo.refs.reflog_iter(name, buf)? {
+        for line in log.filter_map(Result::ok) {
+            out.extend([line.previous_oid(), line.new_oid()]);
+        }
+    }
+    Ok(())
+}
+
+/// Count all objects reachable from [`tips()`], using `odb` for lookups, along with the location of their pack entry.
+///
+/// Like `git`, objects in `promisor_objects` aren't traversed as objects they refer to are expected to be missing.
+pub(super) fn objects(
+    repo: &Repository,
+    promisor_objects: &gix_hashtable::HashSet<ObjectId>,
+    odb: &OdbArc,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    thread_limit: Option<usize>,
+) -> Result<Vec<output::Count>, Error> {
+    objects_from(
+        repo,
+        tips(repo)?,
+        &|id| promisor_objects.contains(id),
+        odb,
+        progress,
+        should_interrupt,
+        thread_limit,
+    )
+}
+
+/// Count all objects reachable from the existing objects `tips`, using `odb` for lookups, along with the location
+/// of their pack entry. Tips and commits for which `is_known` returns `true` aren't traversed, as all objects
+/// reachable from them are already known to the caller or are not expected to be present.
+pub(super) fn objects_from(
+    repo: &Repository,
+    mut tips: Vec<ObjectId>,
+    is_known: &dyn Fn(&gix_hash::oid) -> bool,
+    odb: &OdbArc,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    thread_limit: Option<usize>,
+) -> Result<Vec<output::Count>, Error> {
+    tips.retain(|id| !is_known(id));
+    let mut commits = Vec::new();
+    let mut input = Vec::new();
+    for tip in tips {
+        match repo.find_header(tip)?.kind() {
+            gix_object::Kind::Commit => commits.push(tip),
+            gix_object::Kind::Tag => {
+                input.push(tip);
+                let target = repo.find_object(tip)?.peel_tags_to_end()?;
+                if target.kind == gix_object::Kind::Commit {
+                    commits.push(target.id);
+                }
+            }
+            gix_object::Kind::Tree | gix_object::Kind::Blob => input.push(tip),
+        }
+    }
+
+    let mut walk_progress = progress.add_child("traverse commits".into());
+    walk_progress.init(None, gix_features::progress::count("commits"));
+    for info in repo.rev_walk(commits).selected(|id| !is_known(id))? {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        input.push(info?.id);
+        walk_progress.inc();
+    }
+
+    let mut count_progress = progress.add_child("count reachable objects".into());
+    count_progress.init(None, gix_features::progress::count("objects"));
+    let (counts, _outcome) = output::count::objects(
+        odb.clone(),
+        Box::new(input.into_iter().map(Ok)),
+        &count_progress,
+        should_interrupt,
+        output::count::objects::Options {
+            thread_limit,
+            input_object_expansion: output::count::objects::ObjectExpansion::TreeContents,
+            ..Default::default()
+        },
+    )?;
+    Ok(counts)
+}
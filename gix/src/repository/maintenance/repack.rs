@@ -0,0 +1,592 @@
+use std::{
+    io::{Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::{
+    parallel::InOrderIter,
+    progress::{Count, DynNestedProgress, Progress},
+};
+use gix_hash::ObjectId;
+use gix_pack::data::output;
+
+use super::{
+    inventory::{self, seconds_since_epoch, Inventory},
+    reachable, OdbArc,
+};
+use crate::Repository;
+
+/// Determine what happens to objects that can't be reached from any reference, index or reflog entry
+/// during a full [repack](Repository::repack()).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Unreachable {
+    /// Unreachable objects in packs are removed along with their packs, while unreachable loose objects
+    /// are left untouched, just like `git repack -a -d` does.
+    #[default]
+    Drop,
+    /// Unreachable objects in packs are written as loose objects so they can expire like any other loose object,
+    /// similar to `git repack -A -d`.
+    ///
+    /// Note that the loose objects receive the current time as modification time, which delays their expiry
+    /// compared to `git`, which uses the modification time of the pack they came from.
+    Loosen {
+        /// Objects in packs that were last modified before this time are dropped instead of being loosened.
+        /// If `None`, all of them are loosened.
+        expire_before: Option<SystemTime>,
+    },
+    /// Write all unreachable objects, packed or loose, into a cruft pack along with a `.mtimes` file which
+    /// records the modification time of each object, just like `git repack --cruft -d`.
+    ///
+    /// Loose objects which were written into the cruft pack are removed.
+    Cruft {
+        /// Objects that were last modified before this time are not written into the cruft pack. If `None`,
+        /// all unreachable objects are kept.
+        expire_before: Option<SystemTime>,
+    },
+}
+
+/// Options for use in [`Repository::repack()`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// If set, instead of writing all reachable objects into a single pack, combine small packs and all loose objects
+    /// so that each remaining pack has at least `factor` times as many objects as the next smaller one, just like
+    /// `git repack --geometric=<factor> -d`.
+    ///
+    /// This doesn't require determining which objects are reachable, which is why loose objects are packed without
+    /// regard for their reachability. Cruft packs and packs that are kept are never combined.
+    pub geometric_factor: Option<u32>,
+    /// What to do with unreachable objects. Ignored if `geometric_factor` is set.
+    pub unreachable: Unreachable,
+    /// The amount of threads to use at most, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The outcome of [`Repository::repack()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The path to the index of the newly written pack, if there were objects to pack.
+    pub pack: Option<PathBuf>,
+    /// The amount of objects in the newly written pack.
+    pub packed_objects: usize,
+    /// The path to the index of the newly written cruft pack, if unreachable objects were written into one.
+    pub cruft_pack: Option<PathBuf>,
+    /// The amount of objects in the newly written cruft pack.
+    pub cruft_objects: usize,
+    /// The amount of unreachable objects which were turned into loose objects.
+    pub loosened_objects: usize,
+    /// The amount of unreachable objects in packs that were removed.
+    pub dropped_objects: usize,
+    /// The paths to the indices of the packs that were removed, as all their objects are now in other packs.
+    pub removed_packs: Vec<PathBuf>,
+    /// The amount of loose objects that were removed as they are now in a pack.
+    pub removed_loose_objects: usize,
+}
+
+/// The error returned by [`Repository::repack()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Inventory(#[from] inventory::Error),
+    #[error(transparent)]
+    Reachable(#[from] reachable::Error),
+    #[error(transparent)]
+    CountObjects(#[from] output::count::objects::Error),
+    #[error(transparent)]
+    WritePack(#[from] output::bytes::Error<output::entry::iter_from_counts::Error>),
+    #[error(transparent)]
+    WriteBundle(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    OpenPackIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    WriteMultiPackIndex(#[from] gix_pack::multi_index::write::Error),
+    #[error(transparent)]
+    LockMultiPackIndex(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    CommitMultiPackIndex(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing::Error),
+    #[error(transparent)]
+    WriteLooseObject(#[from] gix_object::write::Error),
+    #[error("Could not write or remove '{path}'")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+/// Repacking
+impl Repository {
+    /// Consolidate the objects of this repository into fewer packs, similar to `git repack -d`, and remove
+    /// all packs and loose objects that became redundant.
+    ///
+    /// By default, all reachable objects are written into a single pack, and unreachable objects are handled according
+    /// to [`Options::unreachable`]. With [`Options::geometric_factor`] set, only small packs are combined instead.
+    /// An existing multi-pack index is rewritten to reflect the remaining packs.
+    ///
+    /// Unreachable objects that are expired are kept nonetheless if they can be reached from unreachable objects
+    /// which aren't expired yet.
+    ///
+    /// In partial clones, missing objects are never fetched, and objects in packs received from the promisor remote
+    /// aren't traversed, as objects they refer to are expected to be missing.
+    pub fn repack(
+        &self,
+        options: Options,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        if self.objects.promisor().is_some() {
+            // Like `git`, never fetch missing objects as these are expected to be missing in partial clones.
+            let mut repo = self.clone();
+            repo.objects.set_promisor(None);
+            return repo.repack(options, progress, should_interrupt);
+        }
+        let inventory = Inventory::load(self.objects.store_ref().path(), self.object_hash())?;
+        let mut odb = self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?.into_inner();
+        odb.prevent_pack_unload();
+        odb.ignore_replacements = true;
+
+        let had_multi_pack_index = inventory.has_multi_pack_index();
+        let outcome = match options.geometric_factor {
+            Some(factor) => self.repack_geometric(&inventory, &odb, factor, options, progress, should_interrupt)?,
+            None => self.repack_all(&inventory, &odb, options, progress, should_interrupt)?,
+        };
+        let packs_changed = outcome.pack.is_some() || outcome.cruft_pack.is_some() || !outcome.removed_packs.is_empty();
+        if had_multi_pack_index && packs_changed {
+            write_multi_pack_index(&inventory, self.object_hash(), progress, should_interrupt)?;
+        }
+        Ok(outcome)
+    }
+
+    fn repack_all(
+        &self,
+        inventory: &Inventory,
+        odb: &OdbArc,
+        options: Options,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let mut kept = gix_hashtable::HashSet::default();
+        for pack in inventory.packs.iter().filter(|pack| pack.keep) {
+            kept.extend((0..pack.index.num_objects()).map(|idx| pack.index.oid_at_index(idx).to_owned()));
+        }
+        // All objects we may rewrite along with their modification time, where the most recent one wins.
+        let mut local = gix_hashtable::HashMap::<ObjectId, u32>::default();
+        let mut packed = gix_hashtable::HashSet::default();
+        for pack in inventory.packs.iter().filter(|pack| !pack.keep) {
+            for idx in 0..pack.index.num_objects() {
+                let id = pack.index.oid_at_index(idx).to_owned();
+                let mtime = pack.mtime_at_index(idx);
+                local
+                    .entry(id)
+                    .and_modify(|existing| *existing = (*existing).max(mtime))
+                    .or_insert(mtime);
+                packed.insert(id);
+            }
+        }
+        for (id, mtime) in &inventory.loose {
+            local
+                .entry(*id)
+                .and_modify(|existing| *existing = (*existing).max(*mtime))
+                .or_insert(*mtime);
+        }
+        local.retain(|id, _| !kept.contains(id));
+
+        let promisor_objects = inventory.promisor_objects();
+        let mut counts = reachable::objects(
+            self,
+            &promisor_objects,
+            odb,
+            progress,
+            should_interrupt,
+            options.thread_limit,
+        )?;
+        counts.retain(|count| local.contains_key(&count.id));
+        let reachable: gix_hashtable::HashSet<_> = counts.iter().map(|count| count.id).collect();
+
+        let mut out = Outcome {
+            packed_objects: counts.len(),
+            ..Default::default()
+        };
+        let mut new_packs = Vec::new();
+        if let Some(pack) = write_pack(
+            odb,
+            counts,
+            &inventory.pack_dir,
+            progress,
+            should_interrupt,
+            options.thread_limit,
+        )? {
+            remove_keep_file(&pack)?;
+            out.pack.clone_from(&pack.index_path);
+            new_packs.push(pack);
+        }
+
+        let mut unreachable: Vec<_> = local
+            .iter()
+            .filter(|(id, _)| !reachable.contains(*id))
+            .map(|(id, mtime)| (*id, *mtime))
+            .collect();
+        unreachable.sort();
+        let is_expired = |mtime: u32, expire_before: Option<SystemTime>| {
+            expire_before.is_some_and(|cutoff| mtime < seconds_since_epoch(cutoff))
+        };
+        // Unreachable objects that aren't expired yet keep all objects they refer to, so these don't go missing
+        // while they are still around.
+        let mut referenced_by_recent = gix_hashtable::HashSet::default();
+        if let Unreachable::Loosen { expire_before } | Unreachable::Cruft { expire_before } = options.unreachable {
+            let recent: Vec<_> = unreachable
+                .iter()
+                .filter(|(_, mtime)| !is_expired(*mtime, expire_before))
+                .map(|(id, _)| *id)
+                .collect();
+            if expire_before.is_some() && !recent.is_empty() {
+                let counts = reachable::objects_from(
+                    self,
+                    recent,
+                    &|id| reachable.contains(id) || promisor_objects.contains(id),
+                    odb,
+                    progress,
+                    should_interrupt,
+                    options.thread_limit,
+                )?;
+                referenced_by_recent.extend(counts.into_iter().map(|count| count.id));
+            }
+        }
+        let can_be_dropped = |id: &ObjectId, mtime: u32, expire_before| {
+            is_expired(mtime, expire_before) && !referenced_by_recent.contains(id)
+        };
+        let mut in_cruft_pack = gix_hashtable::HashSet::default();
+        match options.unreachable {
+            Unreachable::Drop => {
+                out.dropped_objects = unreachable.iter().filter(|(id, _)| packed.contains(id)).count();
+            }
+            Unreachable::Loosen { expire_before } => {
+                let mut buf = Vec::new();
+                for (id, mtime) in unreachable.iter().filter(|(id, _)| packed.contains(id)) {
+                    if can_be_dropped(id, *mtime, expire_before) {
+                        out.dropped_objects += 1;
+                        continue;
+                    }
+                    if inventory.loose.binary_search_by(|(loose, _)| loose.cmp(id)).is_err() {
+                        let data = gix_object::FindExt::find(odb, id, &mut buf)?;
+                        gix_object::Write::write_buf(&inventory.loose_store, data.kind, data.data)?;
+                    }
+                    out.loosened_objects += 1;
+                }
+            }
+            Unreachable::Cruft { expire_before } => {
+                let (cruft, expired): (Vec<_>, Vec<_>) = unreachable
+                    .iter()
+                    .partition(|(id, mtime)| !can_be_dropped(id, *mtime, expire_before));
+                out.dropped_objects = expired.iter().filter(|(id, _)| packed.contains(id)).count();
+                if !cruft.is_empty() {
+                    let ids: Vec<_> = cruft.iter().map(|(id, _)| *id).collect();
+                    let mut count_progress = progress.add_child("count unreachable objects".into());
+                    count_progress.init(Some(ids.len()), gix_features::progress::count("objects"));
+                    let (counts, _outcome) = output::count::objects(
+                        odb.clone(),
+                        Box::new(ids.into_iter().map(Ok)),
+                        &count_progress,
+                        should_interrupt,
+                        output::count::objects::Options {
+                            thread_limit: options.thread_limit,
+                            ..Default::default()
+                        },
+                    )?;
+                    out.cruft_objects = counts.len();
+                    if let Some(pack) = write_pack(
+                        odb,
+                        counts,
+                        &inventory.pack_dir,
+                        progress,
+                        should_interrupt,
+                        options.thread_limit,
+                    )? {
+                        write_mtimes(&pack, |id| local.get(id).copied().unwrap_or_default())?;
+                        remove_keep_file(&pack)?;
+                        out.cruft_pack.clone_from(&pack.index_path);
+                        new_packs.push(pack);
+                    }
+                    in_cruft_pack.extend(cruft.iter().map(|(id, _)| *id));
+                }
+            }
+        }
+
+        let new_pack_paths: Vec<_> = new_packs.iter().filter_map(|pack| pack.index_path.as_deref()).collect();
+        for pack in inventory.packs.iter().filter(|pack| !pack.keep) {
+            if !new_pack_paths.contains(&pack.index_path.as_path()) {
+                pack.remove()?;
+                out.removed_packs.push(pack.index_path.clone());
+            }
+        }
+        for (id, _) in &inventory.loose {
+            if reachable.contains(id) || in_cruft_pack.contains(id) || kept.contains(id) {
+                inventory.remove_loose_object(id)?;
+                out.removed_loose_objects += 1;
+            }
+        }
+        Ok(out)
+    }
+
+    fn repack_geometric(
+        &self,
+        inventory: &Inventory,
+        odb: &OdbArc,
+        factor: u32,
+        options: Options,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let mut packs: Vec<_> = inventory
+            .packs
+            .iter()
+            .filter(|pack| !pack.keep && pack.cruft.is_none())
+            .collect();
+        packs.sort_by_key(|pack| pack.index.num_objects());
+        let split = geometric_split(
+            &packs
+                .iter()
+                .map(|pack| u64::from(pack.index.num_objects()))
+                .collect::<Vec<_>>(),
+            factor.into(),
+        );
+        let packs_to_combine = &packs[..split];
+        if packs_to_combine.len() <= 1 && inventory.loose.is_empty() {
+            return Ok(Outcome::default());
+        }
+
+        let mut ids: Vec<_> = inventory.loose.iter().map(|(id, _)| *id).collect();
+        for pack in packs_to_combine {
+            ids.extend((0..pack.index.num_objects()).map(|idx| pack.index.oid_at_index(idx).to_owned()));
+        }
+        ids.sort();
+        ids.dedup();
+
+        let mut count_progress = progress.add_child("count objects".into());
+        count_progress.init(Some(ids.len()), gix_features::progress::count("objects"));
+        let (counts, _outcome) = output::count::objects(
+            odb.clone(),
+            Box::new(ids.into_iter().map(Ok)),
+            &count_progress,
+            should_interrupt,
+            output::count::objects::Options {
+                thread_limit: options.thread_limit,
+                ..Default::default()
+            },
+        )?;
+        let mut out = Outcome {
+            packed_objects: counts.len(),
+            ..Default::default()
+        };
+        let new_pack = write_pack(
+            odb,
+            counts,
+            &inventory.pack_dir,
+            progress,
+            should_interrupt,
+            options.thread_limit,
+        )?;
+        if let Some(pack) = &new_pack {
+            remove_keep_file(pack)?;
+            out.pack.clone_from(&pack.index_path);
+        }
+
+        for pack in packs_to_combine {
+            if out.pack.as_ref() != Some(&pack.index_path) {
+                pack.remove()?;
+                out.removed_packs.push(pack.index_path.clone());
+            }
+        }
+        for (id, _) in &inventory.loose {
+            inventory.remove_loose_object(id)?;
+            out.removed_loose_objects += 1;
+        }
+        Ok(out)
+    }
+}
+
+/// Return the amount of packs, sorted ascending by their amount of objects as given by `weights`, which have to be combined
+/// so that each pack has at least `factor` times as many objects as the next smaller one afterwards.
+///
+/// This is the same algorithm that `git repack --geometric` uses.
+fn geometric_split(weights: &[u64], factor: u64) -> usize {
+    if weights.is_empty() {
+        return 0;
+    }
+    let mut split = weights.len() - 1;
+    while split > 0 {
+        if weights[split] < factor.saturating_mul(weights[split - 1]) {
+            break;
+        }
+        split -= 1;
+    }
+    if split > 0 {
+        // The larger pack of the pair that violated the progression can't be part of it either.
+        split += 1;
+    }
+    let mut total: u64 = weights[..split].iter().sum();
+    while split < weights.len() && weights[split] < factor.saturating_mul(total) {
+        total += weights[split];
+        split += 1;
+    }
+    split
+}
+
+/// Write all objects in `counts` into a new pack along with its index in `pack_dir`, and return the outcome,
+/// or `None` if there was nothing to write.
+fn write_pack(
+    odb: &OdbArc,
+    counts: Vec<output::Count>,
+    pack_dir: &Path,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    thread_limit: Option<usize>,
+) -> Result<Option<gix_pack::bundle::write::Outcome>, Error> {
+    if counts.is_empty() {
+        return Ok(None);
+    }
+    let num_objects = counts.len();
+    let object_hash = odb.store_ref().object_hash();
+    let entries = InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        odb.clone(),
+        Box::new(progress.add_child("create entries".into())),
+        output::entry::iter_from_counts::Options {
+            thread_limit,
+            ..Default::default()
+        },
+    ));
+
+    let io_err = |source| Error::Io {
+        path: pack_dir.to_owned(),
+        source,
+    };
+    let mut pack_file = gix_tempfile::new(
+        pack_dir,
+        gix_tempfile::ContainingDirectory::CreateAllRaceProof(Default::default()),
+        gix_tempfile::AutoRemove::Tempfile,
+    )
+    .map_err(io_err)?;
+    let mut write_progress = progress.add_child("write pack".into());
+    write_progress.init(None, gix_features::progress::bytes());
+    for written in output::bytes::FromEntriesIter::new(
+        entries,
+        &mut pack_file,
+        num_objects as u32,
+        gix_pack::data::Version::V2,
+        object_hash,
+    ) {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        write_progress.inc_by(written? as usize);
+    }
+    pack_file.seek(SeekFrom::Start(0)).map_err(io_err)?;
+
+    let outcome = gix_pack::Bundle::write_to_directory(
+        &mut std::io::BufReader::new(pack_file),
+        Some(pack_dir),
+        progress,
+        should_interrupt,
+        None::<gix_object::find::Never>,
+        gix_pack::bundle::write::Options {
+            thread_limit,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            index_version: Default::default(),
+            object_hash,
+        },
+    )?;
+    Ok(Some(outcome))
+}
+
+/// Write the `.mtimes` file for the cruft `pack`, using `mtime_of` to obtain the modification time of each of its objects.
+fn write_mtimes(
+    pack: &gix_pack::bundle::write::Outcome,
+    mtime_of: impl Fn(&gix_hash::oid) -> u32,
+) -> Result<(), Error> {
+    let Some(index_path) = &pack.index_path else {
+        return Ok(());
+    };
+    let index = gix_pack::index::File::at(index_path, pack.object_hash)?;
+    let path = index_path.with_extension("mtimes");
+    let io_err = |source| Error::Io {
+        path: path.clone(),
+        source,
+    };
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path).map_err(io_err)?);
+    gix_pack::mtimes::File::write_to(
+        &mut file,
+        (0..index.num_objects()).map(|idx| mtime_of(index.oid_at_index(idx))),
+        &index.pack_checksum(),
+        pack.object_hash,
+    )
+    .map_err(io_err)?;
+    std::io::Write::flush(&mut file).map_err(io_err)?;
+    Ok(())
+}
+
+fn remove_keep_file(pack: &gix_pack::bundle::write::Outcome) -> Result<(), Error> {
+    if let Some(path) = &pack.keep_path {
+        super::remove_file_if_present(path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// Rewrite the multi-pack index to contain all packs that are currently present.
+fn write_multi_pack_index(
+    inventory: &Inventory,
+    object_hash: gix_hash::Kind,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let path = inventory.multi_pack_index_path();
+    let index_paths = Inventory::load(inventory.pack_dir.parent().expect("objects dir"), object_hash)?
+        .packs
+        .into_iter()
+        .map(|pack| pack.index_path)
+        .collect::<Vec<_>>();
+    if index_paths.is_empty() {
+        return super::remove_file_if_present(&path).map_err(|source| Error::Io { path, source });
+    }
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+    gix_pack::multi_index::File::write_from_index_paths(
+        index_paths,
+        &mut lock,
+        progress,
+        should_interrupt,
+        gix_pack::multi_index::write::Options { object_hash },
+    )?;
+    lock.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::geometric_split;
+
+    #[test]
+    fn geometric_split_matches_git() {
+        assert_eq!(geometric_split(&[], 2), 0);
+        assert_eq!(geometric_split(&[5], 2), 0, "a single pack is always in progression");
+        assert_eq!(geometric_split(&[1, 2, 4, 8], 2), 0, "already a geometric progression");
+        assert_eq!(geometric_split(&[1, 1, 1, 8], 2), 3, "the small packs are combined");
+        assert_eq!(
+            geometric_split(&[1, 1, 2, 2, 100], 2),
+            4,
+            "combining small packs may require more packs to be combined"
+        );
+        assert_eq!(
+            geometric_split(&[3, 3, 5, 100], 2),
+            3,
+            "the combined packs have to be smaller than factor times the next one"
+        );
+        assert_eq!(geometric_split(&[10, 10], 2), 2);
+    }
+}
@@ -82,6 +82,7 @@ impl Repository {
                         .unwrap_or_default(),
                     marker_size: text::Conflict::DEFAULT_MARKER_SIZE.try_into().unwrap(),
                 },
+                whitespace: Default::default(),
             },
         })
     }
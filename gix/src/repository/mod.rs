@@ -17,11 +17,23 @@ pub enum Kind {
     },
 }
 
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod am;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod apply_patches;
 #[cfg(any(feature = "attributes", feature = "excludes"))]
 pub mod attributes;
+///
+#[cfg(feature = "bundle")]
+pub mod bundle;
 mod cache;
 #[cfg(feature = "worktree-mutation")]
 mod checkout;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod checkout_tree;
 mod config;
 
 ///
@@ -34,6 +46,9 @@ mod dirwalk;
 #[cfg(feature = "attributes")]
 pub mod filter;
 ///
+#[cfg(feature = "blob-diff")]
+pub mod format_patch;
+///
 pub mod freelist;
 mod graph;
 pub(crate) mod identity;
@@ -46,15 +61,27 @@ mod location;
 #[cfg(feature = "mailmap")]
 mod mailmap;
 ///
+#[cfg(feature = "maintenance")]
+pub mod maintenance;
+///
 #[cfg(feature = "merge")]
 mod merge;
 mod object;
 #[cfg(feature = "attributes")]
 mod pathspec;
+mod promisor;
 mod reference;
 mod remote;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod reset;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod restore;
 mod revision;
 mod shallow;
+#[cfg(feature = "excludes")]
+mod sparse;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
@@ -331,6 +358,41 @@ pub mod index_from_tree {
     }
 }
 
+///
+#[cfg(feature = "excludes")]
+pub mod sparse_checkout_patterns {
+    /// The error returned by [Repository::sparse_checkout_patterns()](crate::Repository::sparse_checkout_patterns()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error("Could not read sparse-checkout patterns from \"{}\"", path.display())]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+    }
+}
+
+///
+#[cfg(feature = "excludes")]
+pub mod apply_sparse_checkout {
+    /// The error returned by [Repository::apply_sparse_checkout()](crate::Repository::apply_sparse_checkout()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        Patterns(#[from] super::sparse_checkout_patterns::Error),
+        #[error(transparent)]
+        ExpandSparseDirectories(#[from] gix_index::sparse::expand::Error),
+        #[error(transparent)]
+        WriteTree(#[from] gix_index::write_tree::Error),
+    }
+}
+
 ///
 pub mod branch_remote_ref_name {
     /// The error returned by [Repository::branch_remote_ref_name()](crate::Repository::branch_remote_ref_name()).
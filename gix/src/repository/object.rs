@@ -50,7 +50,10 @@ impl crate::Repository {
             });
         }
         let mut buf = self.free_buf();
-        let kind = self.objects.find(&id, &mut buf)?.kind;
+        let data = self.objects.find(&id, &mut buf)?;
+        self.verify_hash_if_strict(&id, &data)
+            .map_err(object::find::existing::Error::Find)?;
+        let kind = data.kind;
         Ok(Object::from_data(id, kind, buf, self))
     }
 
@@ -148,6 +151,7 @@ impl crate::Repository {
         let mut buf = self.free_buf();
         match self.objects.try_find(&id, &mut buf)? {
             Some(obj) => {
+                self.verify_hash_if_strict(&id, &obj)?;
                 let kind = obj.kind;
                 Ok(Some(Object::from_data(id, kind, buf, self)))
             }
@@ -162,6 +166,10 @@ impl crate::Repository {
     ///
     /// Note that we hash the object in memory to avoid storing objects that are already present. That way,
     /// we avoid writing duplicate objects using slow disks that will eventually have to be garbage collected.
+    ///
+    /// With [`gitoxide.objects.strictCreation`](crate::config::tree::gitoxide::Objects::STRICT_CREATION) enabled,
+    /// the object is rejected with an [error](object::write::strict::Error) if it is malformed or refers to objects
+    /// that don't exist.
     pub fn write_object(&self, object: impl gix_object::WriteTo) -> Result<Id<'_>, object::write::Error> {
         let mut buf = self.empty_reusable_buffer();
         object
@@ -176,6 +184,7 @@ impl crate::Repository {
         if self.objects.exists(&oid) {
             return Ok(oid.attach(self));
         }
+        self.verify_object_if_strict(kind, buf)?;
 
         self.objects
             .write_buf(kind, buf)
@@ -226,6 +235,67 @@ impl crate::Repository {
     }
 }
 
+/// Strict object handling
+impl crate::Repository {
+    /// If [strict object creation](crate::config::tree::gitoxide::Objects::STRICT_CREATION) is enabled, assure that
+    /// the object of `kind` with `data` is well-formed and that all objects it refers to exist.
+    pub(crate) fn verify_object_if_strict(
+        &self,
+        kind: gix_object::Kind,
+        data: &[u8],
+    ) -> Result<(), object::write::strict::Error> {
+        use object::write::strict::Error;
+        if !self.config.strict_object_creation || kind == gix_object::Kind::Blob {
+            return Ok(());
+        }
+        let object = gix_object::Data::new(kind, data);
+        object
+            .verify_format()
+            .map_err(|source| Error::Format { kind, source })?;
+        let references: SmallVec<[_; 2]> = match object.decode().expect("verified before") {
+            gix_object::ObjectRef::Commit(commit) => std::iter::once((commit.tree(), gix_object::Kind::Tree))
+                .chain(commit.parents().map(|id| (id, gix_object::Kind::Commit)))
+                .collect(),
+            gix_object::ObjectRef::Tree(tree) => tree
+                .entries
+                .iter()
+                .filter(|entry| !entry.mode.is_commit())
+                .map(|entry| {
+                    let kind = if entry.mode.is_tree() {
+                        gix_object::Kind::Tree
+                    } else {
+                        gix_object::Kind::Blob
+                    };
+                    (entry.oid.to_owned(), kind)
+                })
+                .collect(),
+            gix_object::ObjectRef::Tag(tag) => std::iter::once((tag.target(), tag.target_kind)).collect(),
+            gix_object::ObjectRef::Blob(_) => unreachable!("blobs were skipped"),
+        };
+        match references.into_iter().find(|(id, _)| !self.has_object(id)) {
+            Some((id, referenced_kind)) => Err(Error::MissingObject {
+                kind,
+                referenced_kind,
+                id,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// If [strict hash verification](crate::config::tree::gitoxide::Objects::STRICT_HASH_VERIFICATION) is enabled,
+    /// assure that `data` actually hashes to `id`.
+    pub(crate) fn verify_hash_if_strict(
+        &self,
+        id: &gix_hash::oid,
+        data: &gix_object::Data<'_>,
+    ) -> Result<(), gix_object::find::Error> {
+        if self.config.strict_hash_verification {
+            data.verify_checksum(id).map_err(Box::new)?;
+        }
+        Ok(())
+    }
+}
+
 /// Create commits and tags
 impl crate::Repository {
     /// Create a tag reference named `name` (without `refs/tags/` prefix) pointing to a newly created tag object
@@ -0,0 +1,90 @@
+use std::borrow::Cow;
+
+use crate::{bstr::BStr, config::tree::Extensions, Repository};
+
+/// Partial clones
+impl Repository {
+    /// Return the name of the remote that promises objects which are missing locally as configured in
+    /// `extensions.partialClone`, or `None` if this repository isn't a partial clone.
+    pub fn promisor_remote_name(&self) -> Option<Cow<'_, BStr>> {
+        self.config.resolved.string(Extensions::PARTIAL_CLONE)
+    }
+
+    /// Return `true` if this repository is a partial clone, whose missing objects are promised by a remote.
+    pub fn is_partial_clone(&self) -> bool {
+        self.promisor_remote_name().is_some()
+    }
+
+    /// Install a fetcher for missing objects into our object database if this is a partial clone, so they are
+    /// fetched on demand from the promisor remote.
+    ///
+    /// Use [`Handle::set_promisor()`](gix_odb::store::Handle::set_promisor()) on [`objects`](Self::objects) to
+    /// use a custom implementation instead.
+    pub(crate) fn setup_promisor(&mut self) {
+        #[cfg(feature = "blocking-network-client")]
+        if let Some(remote) = self.promisor_remote_name() {
+            let fetcher = fetch::FromRemote {
+                git_dir: self.git_dir().to_owned(),
+                remote: remote.into_owned(),
+                options: self.options.clone(),
+            };
+            self.objects.set_promisor(Some(std::sync::Arc::new(fetcher)));
+        }
+    }
+}
+
+#[cfg(feature = "blocking-network-client")]
+mod fetch {
+    use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+    use gix_hash::ObjectId;
+
+    use crate::{
+        bstr::{BString, ByteSlice},
+        config::tree::Fetch,
+        remote,
+    };
+
+    /// Fetch missing objects from the promisor remote, similar to what `git` does.
+    ///
+    /// It only knows the location of the repository so it can be shared without keeping it alive.
+    pub(super) struct FromRemote {
+        pub git_dir: PathBuf,
+        pub remote: BString,
+        pub options: crate::open::Options,
+    }
+
+    impl gix_odb::promisor::Fetch for FromRemote {
+        fn fetch(&self, ids: &[ObjectId]) -> Result<(), gix_odb::promisor::Error> {
+            let mut repo = crate::open_opts(&self.git_dir, self.options.clone())?;
+            // Objects that are missing while fetching must not be fetched recursively.
+            repo.objects.set_promisor(None);
+            // We want exactly the given objects, and there is nothing to negotiate.
+            repo.config_snapshot_mut()
+                .set_value(&Fetch::NEGOTIATION_ALGORITHM, "noop")?;
+
+            let mut remote = repo
+                .find_remote(self.remote.as_bstr())?
+                .with_fetch_tags(remote::fetch::Tags::None);
+            remote.replace_refspecs(
+                ids.iter().map(|id| BString::from(id.to_string())),
+                remote::Direction::Fetch,
+            )?;
+            let outcome = remote
+                .connect(remote::Direction::Fetch)?
+                .prepare_fetch(gix_features::progress::Discard, Default::default())?
+                .with_filter(Some(remote::fetch::Filter::BlobNone))
+                .receive(gix_features::progress::Discard, &AtomicBool::default())?;
+            // No reference is updated, so the packs must be unlocked by us.
+            let write_pack_bundles = match outcome.status {
+                remote::fetch::Status::Change { write_pack_bundle, .. } => vec![write_pack_bundle],
+                remote::fetch::Status::ObjectsDownloaded { write_pack_bundles, .. } => write_pack_bundles,
+                remote::fetch::Status::NoPackReceived { .. } => Vec::new(),
+            };
+            for keep_path in write_pack_bundles.into_iter().filter_map(|bundle| bundle.keep_path) {
+                std::fs::remove_file(keep_path)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::BTreeSet, sync::atomic::AtomicBool};
+
+use gix_index::entry::{Flags, Stage};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use super::checkout::{checkout_selected_entries, remove_file_for_recreation, remove_from_worktree};
+use crate::{bstr::BString, Repository};
+
+/// Determine how [`Repository::reset()`] affects the index and the worktree, besides `HEAD`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Only set `HEAD` to the target commit, leaving the index and the worktree untouched.
+    Soft,
+    /// Set `HEAD` to the target commit and reset the index to its tree, but leave the worktree untouched.
+    Mixed,
+    /// Set `HEAD` to the target commit, reset the index to its tree and make the worktree match it, discarding
+    /// all local changes to tracked files.
+    Hard,
+    /// Set `HEAD` to the target commit and update the index and worktree for paths that differ between the tree
+    /// at `HEAD` and the target tree, while keeping local changes of all other paths.
+    /// Fail if one of these paths has local changes.
+    Keep,
+    /// Set `HEAD` to the target commit, reset the index to its tree and update the worktree for paths that differ between
+    /// the index and the target tree, while keeping unstaged changes of all other paths.
+    /// Fail if one of these paths has unstaged changes.
+    /// This is useful to abort a merge with conflicts.
+    Merge,
+}
+
+/// The error returned by [`Repository::reset()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot do a {mode:?} reset in a bare repository")]
+    BareRepository { mode: Mode },
+    #[error("Local changes would be overwritten by reset: {}", paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    LocalChanges { paths: Vec<BString> },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Peel(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    IndexFromTree(#[from] crate::repository::index_from_tree::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+    #[error(transparent)]
+    SparseCheckout(#[from] crate::repository::apply_sparse_checkout::Error),
+    #[error(transparent)]
+    StatusPlatform(#[from] crate::status::Error),
+    #[error(transparent)]
+    CreateStatusIterator(#[from] crate::status::into_iter::Error),
+    #[error(transparent)]
+    Status(#[from] crate::status::index_worktree::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error(transparent)]
+    CheckoutTree(#[from] crate::repository::checkout_tree::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error("Could not remove '{rela_path}' from the worktree")]
+    RemoveFile { rela_path: BString, source: std::io::Error },
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    EditHead(#[from] crate::reference::edit::Error),
+}
+
+/// Reset
+impl Repository {
+    /// Set the branch `HEAD` points to, or `HEAD` itself if it's detached, to the commit that `target` peels to,
+    /// and update the index and the worktree according to `mode`, just like `git reset --<mode> <target>`.
+    ///
+    /// The reflog of `HEAD` and of the branch receives a `reset: moving to <target>` message.
+    /// A [sparse checkout](Repository::sparse_checkout_patterns()) is respected when updating the index and worktree.
+    /// Like in `git`, only a [soft](Mode::Soft) reset is possible in a bare repository.
+    pub fn reset(&self, target: impl Into<gix_hash::ObjectId>, mode: Mode) -> Result<(), Error> {
+        let _span = gix_trace::coarse!("gix::Repository::reset()", mode = ?mode);
+        let target = target.into();
+        let commit = self.find_object(target)?.peel_to_commit()?;
+        if mode != Mode::Soft && self.workdir().is_none() {
+            return Err(Error::BareRepository { mode });
+        }
+        match mode {
+            Mode::Soft => {}
+            Mode::Keep => {
+                self.checkout_tree(
+                    commit.id,
+                    Default::default(),
+                    gix_features::progress::Discard,
+                    &AtomicBool::default(),
+                )?;
+            }
+            Mode::Mixed | Mode::Hard | Mode::Merge => {
+                self.reset_index_and_worktree(commit.tree_id()?.detach(), mode)?;
+            }
+        }
+
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("reset: moving to {target}").into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Object(commit.id),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: true,
+        })?;
+        Ok(())
+    }
+
+    fn reset_index_and_worktree(&self, tree: gix_hash::ObjectId, mode: Mode) -> Result<(), Error> {
+        let mut current = (**self.index_or_empty()?).clone();
+        if current.is_sparse() {
+            current.expand_sparse_directories(&self.objects)?;
+        }
+        let mut index = self.index_from_tree(&tree)?;
+        index.set_path(self.index_path());
+        let mut changed = BTreeSet::new();
+        for (entry, path) in index.entries_mut_with_paths() {
+            match current.entry_by_path_and_stage(path, Stage::Unconflicted) {
+                // Keep the stat information to know that the worktree is unchanged, and the worktree-related flags.
+                Some(cur) if cur.id == entry.id && cur.mode == entry.mode => {
+                    entry.stat = cur.stat;
+                    entry.flags = cur.flags;
+                }
+                _ => {
+                    changed.insert(path.to_owned());
+                }
+            }
+        }
+        let mut removed = BTreeSet::new();
+        for entry in current.entries() {
+            let path = entry.path(&current);
+            if index.entry_by_path(path).is_none() && !entry.flags.contains(Flags::SKIP_WORKTREE) {
+                removed.insert(path.to_owned());
+            }
+        }
+        self.apply_sparse_checkout(&mut index)?;
+
+        if mode != Mode::Mixed {
+            let workdir = self.workdir().expect("checked by caller");
+            let modified = self.modified_tracked_files(mode)?;
+            if mode == Mode::Merge {
+                let mut paths: Vec<_> = changed
+                    .iter()
+                    .chain(removed.iter())
+                    .filter(|path| {
+                        modified.contains(*path)
+                            || (current.entry_by_path(path.as_ref()).is_none()
+                                && std::fs::symlink_metadata(workdir.join(gix_path::from_bstr(*path))).is_ok())
+                    })
+                    .cloned()
+                    .collect();
+                if !paths.is_empty() {
+                    paths.sort();
+                    return Err(Error::LocalChanges { paths });
+                }
+            } else {
+                changed.extend(
+                    modified
+                        .into_iter()
+                        .filter(|path| index.entry_by_path(path.as_ref()).is_some()),
+                );
+            }
+
+            for rela_path in removed {
+                remove_from_worktree(workdir, rela_path.as_ref())
+                    .map_err(|source| Error::RemoveFile { rela_path, source })?;
+            }
+            for rela_path in &changed {
+                if index
+                    .entry_by_path(rela_path.as_ref())
+                    .is_some_and(|e| !e.flags.contains(Flags::SKIP_WORKTREE))
+                {
+                    remove_file_for_recreation(workdir, rela_path.as_ref()).map_err(|source| Error::RemoveFile {
+                        rela_path: rela_path.clone(),
+                        source,
+                    })?;
+                }
+            }
+            checkout_selected_entries(
+                &mut index,
+                workdir,
+                self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+                |path| changed.contains(path),
+                &gix_features::progress::Discard,
+                &gix_features::progress::Discard,
+                &AtomicBool::default(),
+                self.checkout_options(gix_worktree::stack::state::attributes::Source::IdMapping)?,
+            )?;
+        }
+        index.write(Default::default())?;
+        Ok(())
+    }
+
+    /// Return the paths of all tracked files that have unstaged changes.
+    /// In `Merge` mode, files that were deleted or are conflicting are ignored.
+    fn modified_tracked_files(&self, mode: Mode) -> Result<BTreeSet<BString>, Error> {
+        use crate::status::index_worktree::iter::Summary;
+        let mut out = BTreeSet::new();
+        for item in self
+            .status(gix_features::progress::Discard)?
+            .untracked_files(crate::status::UntrackedFiles::None)
+            .index_worktree_rewrites(None)
+            .index_worktree_submodules(None)
+            .into_index_worktree_iter(Vec::<BString>::new())?
+        {
+            let item = item?;
+            match item.summary() {
+                None | Some(Summary::Added) => {}
+                Some(Summary::Removed | Summary::Conflict) if mode == Mode::Merge => {}
+                Some(_) => {
+                    out.insert(item.rela_path().to_owned());
+                }
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::BTreeSet, sync::atomic::AtomicBool};
+
+use gix_index::entry::{Flags, Stage};
+
+use super::checkout::{checkout_selected_entries, remove_file_for_recreation, remove_from_worktree};
+use crate::{
+    bstr::{BStr, BString},
+    Repository,
+};
+
+/// Options for use in [`Repository::restore()`](Repository::restore()).
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The tree-ish to restore the content from, or `None` to use the index when restoring the worktree,
+    /// and the tree at `HEAD` when restoring the index.
+    pub source: Option<gix_hash::ObjectId>,
+    /// If `true`, default `true`, write the selected entries into the worktree.
+    pub worktree: bool,
+    /// If `true`, default `false`, write the selected entries into the index, similar to `git restore --staged`.
+    pub staged: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            source: None,
+            worktree: true,
+            staged: false,
+        }
+    }
+}
+
+/// The outcome of [`Repository::restore()`](Repository::restore()).
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome of writing the selected entries into the worktree.
+    pub checkout: gix_worktree_state::checkout::Outcome,
+    /// The amount of files removed from the worktree as they are tracked, but don't exist in the source.
+    pub files_removed: usize,
+    /// The amount of index entries that were changed, added or removed.
+    pub index_entries_changed: usize,
+}
+
+/// The error returned by [`Repository::restore()`](Repository::restore()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("At least one pathspec is needed to select the paths to restore")]
+    MissingPathspec,
+    #[error("Cannot restore the worktree of a bare repository")]
+    BareRepository,
+    #[error("Path '{path}' is unmerged")]
+    Unmerged { path: BString },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Peel(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    IndexFromTree(#[from] crate::repository::index_from_tree::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+    #[error(transparent)]
+    Pathspec(#[from] crate::pathspec::init::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error("Could not remove '{rela_path}' from the worktree")]
+    RemoveFile { rela_path: BString, source: std::io::Error },
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+/// Restore
+impl Repository {
+    /// Restore the content of all paths matching `patterns` from the source configured in `options`, similar to `git restore`.
+    ///
+    /// By default, files in the worktree are overwritten with what's in the index, passing their content through the
+    /// filter pipeline. If a `source` tree-ish is set, tracked files that don't exist in it are removed.
+    /// With `staged`, the index is updated instead or in addition.
+    ///
+    /// Note that entries excluded by a [sparse checkout](Repository::sparse_checkout_patterns()) aren't written to the worktree.
+    pub fn restore(
+        &self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::restore()");
+        let patterns: Vec<BString> = patterns.into_iter().map(|p| p.as_ref().to_owned()).collect();
+        if patterns.is_empty() {
+            return Err(Error::MissingPathspec);
+        }
+        let workdir = match (options.worktree, self.workdir()) {
+            (true, None) => return Err(Error::BareRepository),
+            (true, Some(workdir)) => Some(workdir),
+            (false, _) => None,
+        };
+
+        let mut index = (**self.index_or_empty()?).clone();
+        if index.is_sparse() {
+            index.expand_sparse_directories(&self.objects)?;
+        }
+        let source = match options.source {
+            Some(id) => Some(self.find_object(id)?.peel_to_tree()?.id),
+            None if options.staged => Some(self.head_tree_id_or_empty()?.detach()),
+            None => None,
+        };
+        let mut source = source.map(|tree| self.index_from_tree(&tree)).transpose()?;
+        let mut pathspec = self.pathspec(
+            false,
+            &patterns,
+            true,
+            &index,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+        )?;
+        let selected_in_index: BTreeSet<BString> = index
+            .entries()
+            .iter()
+            .map(|e| e.path(&index))
+            .filter(|path| pathspec.is_included(*path, Some(false)))
+            .map(ToOwned::to_owned)
+            .collect();
+        let selected_in_source: BTreeSet<BString> = match &source {
+            Some(source) => source
+                .entries()
+                .iter()
+                .map(|e| e.path(source))
+                .filter(|path| pathspec.is_included(*path, Some(false)))
+                .map(ToOwned::to_owned)
+                .collect(),
+            None => {
+                if let Some(entry) = index
+                    .entries()
+                    .iter()
+                    .find(|e| e.stage() != Stage::Unconflicted && selected_in_index.contains(e.path(&index)))
+                {
+                    return Err(Error::Unmerged {
+                        path: entry.path(&index).to_owned(),
+                    });
+                }
+                selected_in_index.clone()
+            }
+        };
+
+        let mut out = Outcome::default();
+        let opts = self.checkout_options(gix_worktree::stack::state::attributes::Source::IdMapping)?;
+        if let Some(workdir) = workdir {
+            let excluded: BTreeSet<BString> = index
+                .entries()
+                .iter()
+                .filter(|e| e.flags.contains(Flags::SKIP_WORKTREE))
+                .map(|e| e.path(&index).to_owned())
+                .collect();
+            let is_excluded = |path: &BStr| excluded.contains(path);
+            if source.is_some() {
+                for rela_path in selected_in_index.difference(&selected_in_source) {
+                    if is_excluded(rela_path.as_ref()) {
+                        continue;
+                    }
+                    if remove_from_worktree(workdir, rela_path.as_ref()).map_err(|source| Error::RemoveFile {
+                        rela_path: rela_path.clone(),
+                        source,
+                    })? {
+                        out.files_removed += 1;
+                    }
+                }
+            }
+            for rela_path in selected_in_source.iter().filter(|path| !is_excluded(path.as_ref())) {
+                remove_file_for_recreation(workdir, rela_path.as_ref()).map_err(|source| Error::RemoveFile {
+                    rela_path: rela_path.clone(),
+                    source,
+                })?;
+            }
+            let target = source.as_mut().map_or(&mut *index, |source| &mut **source);
+            out.checkout = checkout_selected_entries(
+                target,
+                workdir,
+                self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+                |path| selected_in_source.contains(path) && !is_excluded(path),
+                &gix_features::progress::Discard,
+                &gix_features::progress::Discard,
+                &AtomicBool::default(),
+                opts,
+            )?;
+        }
+
+        if let (true, Some(source)) = (options.staged, &source) {
+            index.remove_entries(|_, path, _| {
+                let remove = selected_in_index.contains(path) && !selected_in_source.contains(path);
+                if remove {
+                    out.index_entries_changed += 1;
+                }
+                remove
+            });
+            let mut added = Vec::new();
+            for rela_path in &selected_in_source {
+                let src = source.entry_by_path(rela_path.as_ref()).expect("selected from source");
+                let stat = if options.worktree { src.stat } else { Default::default() };
+                match index.entry_mut_by_path_and_stage(rela_path.as_ref(), Stage::Unconflicted) {
+                    Some(entry) if entry.id == src.id && entry.mode == src.mode => {
+                        if options.worktree {
+                            entry.stat = stat;
+                        }
+                        continue;
+                    }
+                    Some(entry) => {
+                        entry.id = src.id;
+                        entry.mode = src.mode;
+                        entry.stat = stat;
+                    }
+                    None => added.push((rela_path, stat, src.id, src.mode)),
+                }
+                if let Some(tree) = index.tree_mut() {
+                    tree.invalidate_path(rela_path.as_ref());
+                }
+                out.index_entries_changed += 1;
+            }
+            if !added.is_empty() {
+                // Conflicting entries are replaced, and pushing entries only after all lookups keeps them working.
+                index.remove_entries(|_, path, _| added.iter().any(|(rela_path, ..)| *rela_path == path));
+                for (rela_path, stat, id, mode) in added {
+                    index.dangerously_push_entry(stat, id, Flags::empty(), mode, rela_path.as_ref());
+                }
+                index.sort_entries();
+            }
+        }
+        if options.staged || (options.worktree && source.is_none()) {
+            index.write(Default::default())?;
+        }
+        Ok(out)
+    }
+}
@@ -35,4 +35,33 @@ impl Repository {
             .unwrap_or_else(|| Cow::Borrowed("shallow".into()));
         self.common_dir().join(gix_path::from_bstr(shallow_name))
     }
+
+    /// Rewrite the `shallow` file so that it only lists commits that are present in the object database, similar to what
+    /// `git prune` does, and return the ones that were removed from it.
+    ///
+    /// The file is removed entirely if none of its commits remain, and it's left untouched if nothing is to be removed.
+    pub fn prune_shallow_commits(&self) -> Result<Vec<gix_hash::ObjectId>, crate::shallow::prune::Error> {
+        let shallow_file = self.shallow_file();
+        if !shallow_file.is_file() {
+            return Ok(Vec::new());
+        }
+        // Lock before reading so commits that are added concurrently aren't lost when writing.
+        let lock =
+            gix_lock::File::acquire_to_update_resource(&shallow_file, gix_lock::acquire::Fail::Immediately, None)?;
+        let Some(mut commits) = gix_shallow::read(&shallow_file)? else {
+            return Ok(Vec::new());
+        };
+        let mut pruned = Vec::new();
+        commits.retain(|id| {
+            let keep = self.has_object(*id);
+            if !keep {
+                pruned.push(*id);
+            }
+            keep
+        });
+        if !pruned.is_empty() {
+            gix_shallow::write(lock, Some(commits), &[])?;
+        }
+        Ok(pruned)
+    }
 }
@@ -0,0 +1,84 @@
+use crate::{
+    config::{
+        cache::util::ApplyLeniencyDefault,
+        tree::{keys, Core, Index},
+    },
+    Repository,
+};
+
+/// Sparse checkouts
+impl Repository {
+    /// Return the configuration of the sparse checkout, as obtained from `core.sparseCheckout`, `core.sparseCheckoutCone`
+    /// and `index.sparse`.
+    pub fn sparse_checkout_options(&self) -> Result<gix_index::sparse::Options, crate::config::boolean::Error> {
+        let boolean = |key: &'static keys::Boolean, default: bool| -> Result<bool, crate::config::boolean::Error> {
+            Ok(self
+                .config
+                .resolved
+                .boolean(key)
+                .map(|res| key.enrich_error(res))
+                .transpose()
+                .with_lenient_default(self.config.lenient_config)?
+                .unwrap_or(default))
+        };
+        Ok(gix_index::sparse::Options {
+            sparse_checkout: boolean(&Core::SPARSE_CHECKOUT, false)?,
+            directory_patterns_only: boolean(&Core::SPARSE_CHECKOUT_CONE, false)?,
+            write_sparse_index: boolean(&Index::SPARSE, false)?,
+        })
+    }
+
+    /// Read the patterns of the sparse checkout from `$GIT_DIR/info/sparse-checkout`, interpreted in cone mode
+    /// if `core.sparseCheckoutCone` is enabled.
+    ///
+    /// Return `None` if `core.sparseCheckout` is disabled, or if the file doesn't exist, which also disables
+    /// the sparse checkout just like in Git.
+    pub fn sparse_checkout_patterns(
+        &self,
+    ) -> Result<Option<gix_worktree::sparse::Patterns>, super::sparse_checkout_patterns::Error> {
+        let options = self.sparse_checkout_options()?;
+        if !options.sparse_checkout {
+            return Ok(None);
+        }
+        let path = self.git_dir().join("info").join("sparse-checkout");
+        gix_worktree::sparse::Patterns::from_file(&path, options.directory_patterns_only, &mut Vec::new())
+            .map_err(|err| super::sparse_checkout_patterns::Error::Io { source: err, path })
+    }
+
+    /// Apply the sparse checkout to `index` if it is enabled, and return the outcome, or `None` if there are
+    /// no [sparse checkout patterns](Self::sparse_checkout_patterns()).
+    ///
+    /// This means sparse directory entries are expanded, the [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE)
+    /// flag is set for all entries that are excluded and cleared for all that are included. Finally, if `index.sparse`
+    /// is enabled in cone mode, all excluded directories are collapsed into sparse directory entries, which may
+    /// involve writing trees to the object database.
+    ///
+    /// Note that the worktree isn't altered.
+    ///
+    /// The `status()` and `dirwalk()` methods don't read the sparse checkout patterns,
+    /// but rely on the flags and sparse directory entries set here, just like Git does. Hence, changed patterns
+    /// must be applied to the index, which must then be written, for them to be respected.
+    /// Unlike Git though, they don't look for untracked files in directories that were collapsed into sparse
+    /// directory entries, even if these exist in the worktree.
+    pub fn apply_sparse_checkout(
+        &self,
+        index: &mut gix_index::State,
+    ) -> Result<Option<gix_worktree::sparse::Outcome>, super::apply_sparse_checkout::Error> {
+        let Some(patterns) = self.sparse_checkout_patterns()? else {
+            return Ok(None);
+        };
+        index.expand_sparse_directories(&self.objects)?;
+        let outcome = patterns.update_skip_worktree(index);
+
+        let options = self.sparse_checkout_options()?;
+        if patterns.is_cone()
+            && options.sparse_mode() == gix_index::sparse::Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs
+        {
+            self.write_tree_from_index(index)?;
+            index.collapse_sparse_directories(|dir| {
+                !patterns.is_included(dir.strip_suffix(b"/").unwrap_or(dir).into(), true)
+            });
+        }
+        Ok(Some(outcome))
+    }
+}
@@ -13,3 +13,18 @@ pub mod read {
 pub mod write {
     pub use gix_shallow::write::Error;
 }
+
+///
+pub mod prune {
+    /// The error returned by [`Repository::prune_shallow_commits()`](crate::Repository::prune_shallow_commits()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Read(#[from] gix_shallow::read::Error),
+        #[error("Could not lock the shallow file for writing")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        Write(#[from] gix_shallow::write::Error),
+    }
+}
@@ -96,6 +96,10 @@ impl Repository {
     /// Whereas Git runs the index-modified check before the directory walk to set entries
     /// as up-to-date to (potentially) safe some disk-access, we run both in parallel which
     /// ultimately is much faster.
+    ///
+    /// With a [sparse index](gix_index::State::is_sparse()), untracked files in directories that are represented
+    /// by sparse directory entries aren't found as the directory walk doesn't enter them, whereas Git looks
+    /// for untracked files in them if they exist in the worktree.
     pub fn status<P>(&self, progress: P) -> Result<Platform<'_, P>, Error>
     where
         P: gix_features::progress::Progress + 'static,
@@ -13,6 +13,8 @@ pub enum Error {
     DiffResourceCache(#[from] crate::repository::diff_resource_cache::Error),
     #[error(transparent)]
     TreeIndexDiff(#[from] gix_diff::index::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
 }
 
 /// Specify how to perform rewrite tracking [Repository::tree_index_status()].
@@ -49,6 +51,9 @@ impl Repository {
     /// *(It's notable that internally, the `tree_id` is converted into an index before diffing these)*.
     /// Set `pathspec` to `Some(_)` to further reduce the set of files to check.
     ///
+    /// If `worktree_index` is [sparse](gix_index::State::is_sparse()), a copy of it with all sparse directories expanded
+    /// is passed to `cb` instead.
+    ///
     /// ### Notes
     ///
     /// * This is a low-level method - prefer the [`Repository::status()`] platform instead for access to various iterators
@@ -70,6 +75,15 @@ impl Repository {
     {
         let _span = gix_trace::coarse!("gix::tree_index_status");
         let tree_index: gix_index::State = self.index_from_tree(tree_id)?.into();
+        let expanded_index;
+        let worktree_index = if worktree_index.is_sparse() {
+            let mut index = worktree_index.clone();
+            index.expand_sparse_directories(&self.objects)?;
+            expanded_index = index;
+            &expanded_index
+        } else {
+            worktree_index
+        };
         let rewrites = match renames {
             TrackRenames::AsConfigured => {
                 let (mut rewrites, mut is_configured) = crate::diff::utils::new_rewrites_inner(
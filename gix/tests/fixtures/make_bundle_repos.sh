@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# A repository with two branches and an annotated tag, bundles created from it by `git`,
+# and a repository which only has the commits that are prerequisites of the incremental bundle.
+git init -q base
+(cd base
+  echo 1 > a && git add a && git commit -qm c1
+  echo 2 > a && git commit -qam c2
+  git tag -am "annotated" v1
+  mkdir dir && echo b > dir/b && git add dir && git commit -qm c3
+  git checkout -qb other HEAD~1
+  echo o > o && git add o && git commit -qm other
+  git checkout -q main
+
+  git bundle create -q ../all.bundle --all
+  git bundle create -q ../incremental.bundle main~1..main
+)
+
+git init -q partial
+(cd partial
+  git fetch -q ../base "$(git -C ../base rev-parse main~1):refs/tags/base"
+  git checkout -q -b main base
+  git tag -d base >/dev/null
+)
+
+git init -q empty
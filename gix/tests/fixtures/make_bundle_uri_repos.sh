@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# A repository whose history is partially available in bundles, along with bundle lists in the configuration format
+# that refer to them, as they would be served via bundle URIs.
+git init -q base
+mkdir bundles
+(cd base
+  echo 1 > a && git add a && git commit -qm c1
+  git bundle create -q ../bundles/1.bundle main
+  echo 2 > a && git commit -qam c2
+  git bundle create -q ../bundles/2.bundle main~1..main
+  echo 3 > a && git commit -qam c3
+)
+
+cat <<EOT > bundles/creation-token.list
+[bundle]
+	version = 1
+	mode = all
+	heuristic = creationToken
+[bundle "one"]
+	uri = 1.bundle
+	creationToken = 1
+[bundle "two"]
+	uri = 2.bundle
+	creationToken = 2
+EOT
+
+cat <<EOT > bundles/all.list
+[bundle]
+	version = 1
+	mode = all
+[bundle "two"]
+	uri = 2.bundle
+[bundle "one"]
+	uri = 1.bundle
+EOT
+
+cat <<EOT > bundles/any.list
+[bundle]
+	version = 1
+	mode = any
+[bundle "missing"]
+	uri = does-not-exist.bundle
+[bundle "one"]
+	uri = 1.bundle
+EOT
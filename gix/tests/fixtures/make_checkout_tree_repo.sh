@@ -0,0 +1,25 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+printf '1\n2\n3\n4\n5\n' > m
+echo a > a
+echo b > b
+echo unchanged > unchanged
+mkdir dir
+echo c > dir/c
+git add .
+git commit -qm "main"
+
+git checkout -q -b other
+printf '1\nTWO\n3\n4\n5\n' > m
+echo a-other > a
+chmod +x b
+git rm -q dir/c
+mkdir new-dir
+echo new > new-dir/new
+git add .
+git commit -qm "other"
+
+git checkout -q main
@@ -19,6 +19,10 @@ cat <<EOF >>.git/config
   textconv = textconv
   algorithm = histogram
   binary = auto
+  wordRegex = [a-z]+
+  xfuncname = "^fn (.*)$"
+[diff "python"]
+  funcname = "^def \\\\(.*\\\\)$"
 EOF
 
 git checkout -b main
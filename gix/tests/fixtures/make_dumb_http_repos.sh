@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function tick() {
+  if test -z "${tick+set}"; then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function commit_file() {
+  tick
+  mkdir -p "$(dirname "${1:?}")"
+  echo "${1:?}" > "$1"
+  git add "$1"
+  git commit -q -m "$1"
+}
+
+git init -q base
+(cd base
+  commit_file a
+  commit_file dir/b
+  git tag -a -m v1 v1
+)
+
+# The repository as served by a 'dumb' server, with all objects in a single pack to begin with.
+git clone -q --bare base served.git
+(cd served.git
+  git repack -adq
+  git update-server-info
+)
+
+# A clone with everything that is currently in the pack.
+git clone -q --bare base client.git
+
+# New commits and an annotated tag are pushed afterward, and are only available as loose objects.
+(cd base
+  commit_file dir/c
+  commit_file d
+  tick
+  git tag -a -m v2 v2
+  git push -q ../served.git main v2
+)
+(cd served.git
+  git update-server-info
+)
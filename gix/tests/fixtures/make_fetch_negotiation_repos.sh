@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function commit_at() {
+  local seconds="${1:?}" message="${2:?}"
+  GIT_AUTHOR_DATE="@$seconds +0000" GIT_COMMITTER_DATE="@$seconds +0000" git commit -q --allow-empty -m "$message"
+}
+
+# A server with a history the clients share, and new commits on top of it.
+git init -q server
+(cd server
+  for i in $(seq 1 5); do commit_at $((2000000000 + i)) "base $i"; done
+)
+
+# A client with many local commits that are more recent than the shared history, which take multiple rounds
+# of negotiation to get through, and an older unrelated history it would keep sending as `have` after the server
+# knows enough to send a pack.
+git clone -q server client
+(cd client
+  git checkout -q --orphan old
+  for i in $(seq 1 200); do commit_at $((1000000000 + i)) "old $i"; done
+  git checkout -q -b local main
+  for i in $(seq 1 100); do commit_at $((3000000000 + i)) "local $i"; done
+  git checkout -q main
+)
+
+# A client whose history is unrelated to the one of the server, so all of its `have`s are sent in vain.
+git init -q unrelated
+(cd unrelated
+  for i in $(seq 1 20); do commit_at $((2000000000 + i)) "unrelated $i"; done
+  git remote add origin ../server
+)
+
+(cd server
+  for i in $(seq 1 3); do commit_at $((3000000000 + i)) "new $i"; done
+)
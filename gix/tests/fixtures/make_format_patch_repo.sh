@@ -0,0 +1,35 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create commits along with the patches `git format-patch` produces for them in `patches/`,
+# and a single patch for the last commit in `single/`.
+git init -q repo
+cd repo
+git config core.fileMode true
+
+seq 1 10 > a
+seq 1 20 > r
+echo "to be deleted" > del
+echo "#!/bin/sh" > script
+git add . && git commit -qm "init"
+
+sed -i -e 's/^2$/two/' -e 's/^9$/nine/' a
+printf '\x00\x01\x02binary' > bin
+git add . && git commit -qm "Modify a and add a binary file
+
+This is the body
+of the commit message."
+
+git mv r renamed
+git rm -q del
+chmod +x script
+git add . && git commit -qm "Rename, delete and change the mode"
+
+echo "new" > "ünïcode file"
+git add . && GIT_AUTHOR_NAME="A. Ü. Thor" git commit -qm "Ünïcode subject"
+
+seq 21 25 >> renamed
+git add . && git commit -qm "A subject that is long enough so that it needs to be wrapped somewhere, as it is long" -m "Body"
+
+git format-patch -q --no-signature -o ../patches HEAD~4
+git format-patch -q --no-signature -o ../single -1 HEAD
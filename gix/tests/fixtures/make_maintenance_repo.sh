@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create a repository with two packs, loose objects, an annotated tag and unreachable objects,
+# one of which is old, along with reflog entries which are partially unreachable.
+# Executed in place as modification times must be retained.
+git init -q
+git config gc.auto 0
+
+echo 1 > a && git add a && git commit -qm c1
+git checkout -qb side
+echo side > s && git add s && git commit -qm side
+git checkout -q main
+git branch -qD side
+echo 2 > a && git commit -qam c2
+git tag -am "annotated" v1
+git repack -qd
+
+export GIT_COMMITTER_DATE="2020-01-02 00:00:00 +0000"
+echo 3 > a && git commit -qam c3
+git repack -qd
+
+echo 4 > a && git commit -qam c4
+
+old=$(echo "old unreachable" | git hash-object -w --stdin)
+touch -d "2000-01-01" ".git/objects/${old:0:2}/${old:2}"
+echo "new unreachable" | git hash-object -w --stdin >/dev/null
@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create a treeless clone with a local commit on top and a recent unreachable object, whose
+# filtered trees and blobs must not be fetched while maintaining it.
+git init -q server
+(cd server
+  git config uploadpack.allowFilter true
+  echo 1 > a && git add a && git commit -qm c1
+  echo 2 > a && mkdir dir && echo b > dir/b && git add . && git commit -qm c2
+)
+
+git clone -q --no-local --filter=tree:0 server partial
+(cd partial
+  git config gc.auto 0
+  echo local > c && git add c && git commit -qm local
+  echo "new unreachable" | git hash-object -w --stdin >/dev/null
+)
@@ -381,3 +381,13 @@ git init empty-core-askpass
 (cd empty-core-askpass
   echo "    askpass =" >> .git/config
 )
+
+git init partial-clone-server
+(cd partial-clone-server
+  git config uploadpack.allowFilter true
+  git config uploadpack.allowAnySHA1InWant true
+  mkdir dir
+  echo a > a && echo b > dir/b && echo c > dir/c
+  git add . && git commit -m "first"
+  echo a2 > a && git commit -am "second"
+)
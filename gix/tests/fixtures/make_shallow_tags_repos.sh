@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function tick() {
+  if test -z "${tick+set}"; then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function commit() {
+  tick
+  git commit -q --allow-empty -m "${1:?}"
+}
+
+git init -q base
+(cd base
+  commit c1
+  git tag -a -m v1 v1
+  commit c2
+  commit c3
+)
+
+# A clone of `base` whose annotated tags point to commits that are already present in `client`,
+# and one that points beyond its shallow boundary.
+git clone -q --bare base tagged
+(cd tagged
+  tick
+  git tag -a -m v2 v2 main~1
+  git tag -a -m v3 v3 main
+)
+
+# Like `tagged`, but with a new commit on `main` that is tagged as well.
+git clone -q --bare tagged advanced
+(cd advanced
+  tick
+  git commit-tree -p main -m c4 "main^{tree}" > new-commit
+  git update-ref refs/heads/main "$(cat new-commit)"
+  rm new-commit
+  git tag -a -m v4 v4 main
+)
+
+# A shallow clone of `base` with the two most recent commits and without tags.
+git clone -q --no-tags --depth 2 "file://$PWD/base" client
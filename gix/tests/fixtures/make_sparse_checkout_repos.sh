@@ -0,0 +1,31 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function add_untracked_files() {
+  mkdir -p d/e x
+  touch d/untracked d/e/untracked x/untracked
+}
+
+function init_repo() {
+  git init -q "$1"
+  (cd "$1"
+    mkdir -p a/b/c a/x d/e
+    touch root a/file a/b/file a/b/c/file a/x/file d/file d/e/file
+    git add .
+    git commit -qm "init"
+  )
+}
+
+init_repo cone-sparse-index
+(cd cone-sparse-index
+  git sparse-checkout set --cone --sparse-index a/b
+  add_untracked_files
+)
+
+init_repo non-cone
+(cd non-cone
+  git sparse-checkout set --no-cone '/a/b/' '/d/'
+  add_untracked_files
+)
+
+init_repo disabled
@@ -119,8 +119,8 @@ mod blocking_io {
             vec![
                 hex_to_id("2d9d136fb0765f2e24c44a0f91984318d580d03b"),
                 hex_to_id("dfd0954dabef3b64f458321ef15571cc1a46d552"),
-                hex_to_id("dfd0954dabef3b64f458321ef15571cc1a46d552"),
-            ]
+            ],
+            "the same shallow commit is only listed once"
         );
         assert_eq!(
             repo.config_snapshot().boolean("my.marker"),
@@ -719,6 +719,186 @@ mod blocking_io {
         }
         Ok(())
     }
+
+    #[test]
+    fn partial_clone_fetches_missing_blobs_on_demand() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (mut checkout, out) = gix::clone::PrepareFetch::new(
+            remote::repo("partial-clone-server").path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(Some("blob:none".parse()?))
+        .fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let gix::remote::fetch::Status::Change { write_pack_bundle, .. } = out.status else {
+            unreachable!("a clone always carries a change")
+        };
+        let index_path = write_pack_bundle.index_path.expect("pack was written");
+        assert!(
+            gix::odb::promisor::is_promisor_pack(&index_path),
+            "packs received with a filter are marked"
+        );
+        let pack = gix::odb::pack::index::File::at(&index_path, gix::hash::Kind::Sha1)?;
+        assert_eq!(
+            pack.num_objects(),
+            2 + 3,
+            "only commits and trees were received, with the tree of `dir` being unchanged"
+        );
+
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        {
+            let config = repo.config_snapshot();
+            assert_eq!(config.boolean("remote.origin.promisor"), Some(true));
+            assert_eq!(
+                config.string("remote.origin.partialCloneFilter").as_deref(),
+                Some("blob:none".into())
+            );
+            assert_eq!(config.integer("core.repositoryFormatVersion"), Some(1));
+        }
+        assert_eq!(repo.promisor_remote_name().as_deref(), Some("origin".into()));
+
+        let workdir = repo.workdir().expect("non-bare");
+        assert_eq!(std::fs::read(workdir.join("a"))?, b"a2\n");
+        assert_eq!(std::fs::read(workdir.join("dir").join("c"))?, b"c\n");
+        let promisor_packs = |repo: &gix::Repository| -> std::io::Result<usize> {
+            Ok(std::fs::read_dir(repo.objects.store_ref().path().join("pack"))?
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "promisor"))
+                .count())
+        };
+        assert_eq!(
+            promisor_packs(&repo)?,
+            2,
+            "all blobs to check out were fetched in one batch"
+        );
+
+        let repo = gix::open_opts(repo.git_dir(), restricted())?;
+        assert!(repo.is_partial_clone());
+        let old_blob = repo
+            .head_commit()?
+            .parent_ids()
+            .next()
+            .expect("one parent")
+            .object()?
+            .peel_to_tree()?
+            .find_entry("a")
+            .expect("present")
+            .object_id();
+        assert!(!repo.has_object(old_blob), "existence checks don't fetch");
+        assert_eq!(
+            repo.find_object(old_blob)?.data,
+            b"a\n",
+            "missing blobs are fetched lazily"
+        );
+        assert!(repo.has_object(old_blob));
+        assert_eq!(promisor_packs(&repo)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "bundle")]
+    fn from_bundle() -> crate::Result {
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundle_repos.sh")?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (mut checkout, out) = gix::clone::PrepareFetch::new(
+            fixture.join("all.bundle"),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?
+        .fetch_then_checkout(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(matches!(out.status, gix::remote::fetch::Status::Change { .. }));
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &AtomicBool::default())?;
+
+        let base = gix::open_opts(fixture.join("base"), restricted())?;
+        let head = repo.head()?;
+        assert_eq!(
+            head.referent_name().map(|name| name.as_bstr().to_owned()),
+            Some("refs/heads/main".into()),
+            "the branch of HEAD is deduced from the references in the bundle"
+        );
+        assert_eq!(repo.head_id()?, base.head_id()?);
+        for name in ["refs/remotes/origin/other", "refs/tags/v1"] {
+            assert_eq!(
+                repo.find_reference(name)?.id(),
+                base.find_reference(name.replace("remotes/origin", "heads").as_str())?
+                    .id()
+            );
+        }
+        let workdir = repo.workdir().expect("non-bare");
+        assert_eq!(std::fs::read(workdir.join("dir").join("b"))?, b"b\n");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "bundle")]
+    fn from_bundle_uri_then_fetch_the_remainder() -> crate::Result {
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundle_uri_repos.sh")?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let list = fixture.join("bundles").join("creation-token.list");
+        let (repo, out) = gix::clone::PrepareFetch::new(
+            fixture.join("base"),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_bundle_uri(Some(gix::path::into_bstr(&list).into_owned()))
+        .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        let base = gix::open_opts(fixture.join("base"), restricted())?;
+        assert_eq!(
+            repo.find_reference("refs/bundles/main")?.id(),
+            base.rev_parse_single("main~1")?,
+            "the bundles are unbundled into their own namespace"
+        );
+        assert_eq!(repo.find_reference("refs/heads/main")?.id(), base.head_id()?);
+        let gix::remote::fetch::Status::Change { write_pack_bundle, .. } = out.status else {
+            unreachable!("the last commit is missing from the bundles")
+        };
+        assert_eq!(
+            write_pack_bundle.index.num_objects, 3,
+            "only the objects that aren't in the bundles are fetched: a commit, a tree and a blob"
+        );
+
+        let config = repo.config_snapshot();
+        assert_eq!(
+            config.string("fetch.bundleURI").as_deref(),
+            Some(gix::path::into_bstr(&list).as_ref()),
+            "like `git`, the uri is remembered…"
+        );
+        assert_eq!(
+            config.integer("fetch.bundleCreationToken"),
+            Some(2),
+            "…along with the greatest creation token to skip these bundles in future"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "bundle")]
+    fn from_bundle_uri_is_ignored_when_shallow() -> crate::Result {
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundle_uri_repos.sh")?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, _out) = gix::clone::PrepareFetch::new(
+            fixture.join("base"),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_bundle_uri(Some(
+            gix::path::into_bstr(fixture.join("bundles").join("1.bundle")).into_owned(),
+        ))
+        .with_shallow(gix::remote::fetch::Shallow::DepthAtRemote(1.try_into()?))
+        .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(repo.try_find_reference("refs/bundles/main")?.is_none());
+        assert!(repo.config_snapshot().string("fetch.bundleURI").is_none());
+        Ok(())
+    }
 }
 
 #[test]
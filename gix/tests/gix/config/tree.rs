@@ -727,6 +727,42 @@ mod checkout {
     }
 }
 
+mod gc {
+    use std::time::{Duration, SystemTime};
+
+    use gix::config::tree::{Gc, Key};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn expiry() -> crate::Result {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("never"), now)?, None);
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("false"), now)?, None);
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("now"), now)?, Some(now));
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("all"), now)?, Some(now));
+        assert_eq!(
+            Gc::PRUNE_EXPIRE.try_into_expiry(bcow("2.weeks.ago"), now)?,
+            Some(now - Duration::from_secs(14 * 24 * 60 * 60)),
+            "dots can be used instead of spaces"
+        );
+        assert_eq!(
+            Gc::REFLOG_EXPIRE.try_into_expiry(bcow("90 days ago"), now)?,
+            Some(now - Duration::from_secs(90 * 24 * 60 * 60))
+        );
+        assert!(Gc::REFLOG_EXPIRE_UNREACHABLE.validate("30.days.ago".into()).is_ok());
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry(bcow("invalid"), now)
+                .unwrap_err()
+                .to_string(),
+            "The key \"gc.pruneExpire=invalid\" was invalid"
+        );
+        assert!(Gc::PRUNE_EXPIRE.validate("invalid".into()).is_err());
+        Ok(())
+    }
+}
+
 mod pack {
     use gix::config::tree::{Key, Pack};
 
@@ -1096,6 +1132,25 @@ mod remote {
         Ok(())
     }
 
+    #[test]
+    fn partial_clone_filter() -> crate::Result {
+        assert_eq!(
+            Remote::PARTIAL_CLONE_FILTER.try_into_filter(bcow("blob:limit=1k"))?,
+            remote::fetch::Filter::BlobLimit(1024)
+        );
+        assert!(Remote::PARTIAL_CLONE_FILTER.validate("tree:0".into()).is_ok());
+
+        assert_eq!(
+            Remote::PARTIAL_CLONE_FILTER
+                .try_into_filter(bcow("blob:all"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"remote.<name>.partialCloneFilter=blob:all\" was invalid"
+        );
+        assert!(Remote::PARTIAL_CLONE_FILTER.validate("blob".into()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn url_and_push_url() {
         assert!(Remote::URL.try_into_url(bcow("http://example.org")).is_ok());
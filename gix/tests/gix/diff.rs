@@ -13,15 +13,23 @@ fn resource_cache() -> crate::Result {
             .detach(),
         Default::default(),
     )?;
+    let drivers = cache.filter.drivers();
     assert_eq!(
-        cache.filter.drivers(),
+        drivers
+            .iter()
+            .filter(|d| Driver::builtin(d.name.as_ref()).is_none())
+            .cloned()
+            .collect::<Vec<_>>(),
         &[
             Driver {
                 name: "all-but-binary".into(),
                 command: Some("command".into()),
                 algorithm: Some(Algorithm::Histogram),
                 binary_to_text_command: Some("textconv".into()),
-                is_binary: None
+                is_binary: None,
+                word_regex: Some("[a-z]+".into()),
+                funcname: Some("^fn (.*)$".into()),
+                funcname_ignore_case: false,
             },
             Driver {
                 name: "binary-false".into(),
@@ -35,6 +43,20 @@ fn resource_cache() -> crate::Result {
             }
         ]
     );
+    let builtin_python = Driver::builtin("python".into()).expect("known");
+    assert_eq!(
+        drivers.iter().find(|d| d.name == "python"),
+        Some(&Driver {
+            funcname: Some("^def (.*)$".into()),
+            ..builtin_python
+        }),
+        "configuration overrides builtin drivers, and basic regular expressions are converted"
+    );
+    assert_eq!(
+        drivers.len(),
+        3 + Driver::builtins().count(),
+        "all builtin drivers are available, even if not configured"
+    );
     assert_eq!(cache.options.algorithm, Some(Algorithm::Histogram));
     assert!(
         !cache.options.skip_internal_diff_if_external_is_configured,
@@ -21,6 +21,30 @@ mod blocking_io {
             }
         }
 
+        #[test]
+        #[serial]
+        fn remote_helper_schemes() -> crate::Result {
+            let repo = remote::repo("base");
+            let remote = repo.remote_at("ext://host/repo")?;
+            assert!(
+                matches!(
+                    remote.connect(Fetch).err(),
+                    Some(gix::remote::connect::Error::ProtocolDenied {
+                        url: _,
+                        scheme: gix::url::Scheme::Ext(name)
+                    }) if name == "ext"
+                ),
+                "the 'ext' helper can run arbitrary commands and is denied by default, like in Git"
+            );
+
+            let remote = repo.remote_at("custom-helper://host/repo")?;
+            assert!(
+                remote.connect(Fetch).is_ok(),
+                "all other remote helpers are allowed, and only spawned when the handshake is performed"
+            );
+            Ok(())
+        }
+
         #[test]
         #[serial]
         fn user() -> crate::Result {
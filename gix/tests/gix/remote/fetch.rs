@@ -10,6 +10,15 @@ mod shallow {
     }
 }
 
+/// Return the shortened names of all tags in `repo`.
+#[cfg(feature = "blocking-network-client")]
+fn tag_names(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+    repo.references()?
+        .tags()?
+        .map(|r| r.map(|r| r.name().shorten().to_string()))
+        .collect()
+}
+
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-async-std"))]
 mod blocking_and_async_io {
     use std::sync::atomic::AtomicBool;
@@ -309,6 +318,224 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_from_negotiation_fixture(
+        name: &str,
+        version: gix::protocol::transport::Protocol,
+    ) -> crate::Result<(gix::remote::fetch::Outcome, TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable("make_fetch_negotiation_repos.sh")?;
+        let mut repo = gix::open_opts(dir.path().join(name), crate::restricted())?;
+        repo.config_snapshot_mut()
+            .set_value(&Protocol::VERSION, (version as u8).to_string().as_str())?;
+        let url = format!("file://{}", dir.path().join("server").display());
+        let out = repo
+            .remote_at(url.as_str())?
+            .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), Fetch)?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        Ok((out, dir))
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_with_multi_round_negotiation_is_done_once_the_server_is_ready() -> crate::Result {
+        for version in [
+            gix::protocol::transport::Protocol::V1,
+            gix::protocol::transport::Protocol::V2,
+        ] {
+            let (out, _dir) = fetch_from_negotiation_fixture("client", version)?;
+            let Status::Change {
+                negotiate,
+                write_pack_bundle,
+                ..
+            } = out.status
+            else {
+                unreachable!("{version:?}: the new commits are missing")
+            };
+            assert_eq!(
+                write_pack_bundle.index.num_objects, 3,
+                "{version:?}: only the new commits are sent"
+            );
+            let rounds: Vec<_> = negotiate.rounds.iter().map(|r| r.haves_sent).collect();
+            match version {
+                gix::protocol::transport::Protocol::V1 => assert_eq!(
+                    rounds,
+                    [16, 32, 64, 0],
+                    "the local commits take multiple rounds, and once the server is ready \
+                     we are done without sending the old history"
+                ),
+                _ => assert_eq!(rounds, [16, 32, 64], "the server sends the pack as soon as it's ready"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_with_all_haves_sent_in_vain() -> crate::Result {
+        for version in [
+            gix::protocol::transport::Protocol::V1,
+            gix::protocol::transport::Protocol::V2,
+        ] {
+            let (out, _dir) = fetch_from_negotiation_fixture("unrelated", version)?;
+            let Status::Change {
+                negotiate,
+                write_pack_bundle,
+                ..
+            } = out.status
+            else {
+                unreachable!("{version:?}: nothing is in common")
+            };
+            assert_eq!(
+                write_pack_bundle.index.num_objects,
+                8 + 1,
+                "{version:?}: all commits and the empty tree are sent"
+            );
+            assert_eq!(
+                negotiate.rounds.iter().map(|r| r.haves_sent).collect::<Vec<_>>(),
+                [16, 4],
+                "{version:?}: the final `NAK` after `done` is followed by the pack"
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_from_shallow_tags_fixture(
+        remote_name: &str,
+        version: gix::protocol::transport::Protocol,
+        tags: fetch::Tags,
+        missing_shallow_commit: Option<gix::ObjectId>,
+    ) -> crate::Result<(gix::Repository, gix::remote::fetch::Outcome, TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable("make_shallow_tags_repos.sh")?;
+        let mut repo = gix::open_opts(dir.path().join("client"), crate::restricted())?;
+        repo.config_snapshot_mut()
+            .set_value(&Protocol::VERSION, (version as u8).to_string().as_str())?;
+        if let Some(id) = missing_shallow_commit {
+            let mut shallow = std::fs::read(repo.shallow_file())?;
+            shallow.extend_from_slice(format!("{id}\n").as_bytes());
+            std::fs::write(repo.shallow_file(), shallow)?;
+        }
+        let url = format!("file://{}", dir.path().join(remote_name).display());
+        let out = repo
+            .remote_at(url.as_str())?
+            .with_refspecs(Some("+refs/heads/main:refs/remotes/origin/main"), Fetch)?
+            .with_fetch_tags(tags)
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        Ok((repo, out, dir))
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_tags_pointing_into_shallow_history_without_new_commits() -> crate::Result {
+        for version in [
+            gix::protocol::transport::Protocol::V1,
+            gix::protocol::transport::Protocol::V2,
+        ] {
+            let (repo, out, _dir) = fetch_from_shallow_tags_fixture("tagged", version, fetch::Tags::Included, None)?;
+            let Status::Change {
+                write_pack_bundle,
+                update_refs,
+                ..
+            } = out.status
+            else {
+                unreachable!("{version:?}: the tags are fetched separately")
+            };
+            assert_eq!(
+                write_pack_bundle.index.num_objects, 2,
+                "{version:?}: only the tag objects are sent"
+            );
+            assert_eq!(
+                update_refs.edits.len(),
+                1 + 2,
+                "{version:?}: the unchanged branch and the two tags"
+            );
+            assert_eq!(
+                super::tag_names(&repo)?,
+                ["v2", "v3"],
+                "{version:?}: the tag pointing beyond the shallow boundary isn't fetched"
+            );
+            assert_eq!(
+                repo.shallow_commits()?.expect("still shallow").as_slice(),
+                [repo.rev_parse_single("main~1")?.detach()],
+                "{version:?}: the shallow boundary isn't changed"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_tags_pointing_into_shallow_history_after_receiving_new_commits() -> crate::Result {
+        for version in [
+            gix::protocol::transport::Protocol::V1,
+            gix::protocol::transport::Protocol::V2,
+        ] {
+            let (repo, out, _dir) = fetch_from_shallow_tags_fixture("advanced", version, fetch::Tags::Included, None)?;
+            let Status::Change {
+                write_pack_bundle,
+                update_refs,
+                ..
+            } = out.status
+            else {
+                unreachable!("{version:?}: there is a new commit")
+            };
+            assert_eq!(
+                write_pack_bundle.index.num_objects, 2,
+                "{version:?}: the new commit and the tag pointing to it"
+            );
+            assert_eq!(
+                update_refs.edits.len(),
+                1 + 3,
+                "{version:?}: the branch and all tags but one"
+            );
+            assert_eq!(
+                super::tag_names(&repo)?,
+                ["v2", "v3", "v4"],
+                "{version:?}: tags pointing to present commits were fetched separately, V1 needs a new connection for that"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_without_included_tags_does_not_fetch_tags_separately() -> crate::Result {
+        let (repo, out, _dir) = fetch_from_shallow_tags_fixture(
+            "tagged",
+            gix::protocol::transport::Protocol::V2,
+            fetch::Tags::None,
+            None,
+        )?;
+        assert!(
+            matches!(out.status, Status::NoPackReceived { .. }),
+            "nothing changed on the branch"
+        );
+        assert!(super::tag_names(&repo)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_prunes_shallow_commits_that_are_not_present() -> crate::Result {
+        let missing = hex_to_id("0000000000000000000000000000000000000001");
+        let (repo, _out, _dir) = fetch_from_shallow_tags_fixture(
+            "advanced",
+            gix::protocol::transport::Protocol::V2,
+            fetch::Tags::None,
+            Some(missing),
+        )?;
+        assert_eq!(
+            repo.shallow_commits()?.expect("still shallow").as_slice(),
+            [repo.rev_parse_single("main~1")?.detach()],
+            "the commit that isn't present was removed from the shallow file"
+        );
+        Ok(())
+    }
+
     #[maybe_async::test(
         feature = "blocking-network-client",
         async(feature = "async-network-client-async-std", async_std::test)
@@ -663,6 +890,9 @@ mod blocking_and_async_io {
                         );
                         update_refs
                     }
+                    fetch::Status::ObjectsDownloaded { .. } => {
+                        unreachable!("the remote isn't a 'dumb' server")
+                    }
                 };
 
                 assert_eq!(
@@ -720,3 +950,217 @@ mod blocking_and_async_io {
         Ok(())
     }
 }
+
+#[cfg(any(
+    feature = "blocking-http-transport-curl",
+    feature = "blocking-http-transport-reqwest"
+))]
+mod dumb_http {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        path::PathBuf,
+        sync::atomic::AtomicBool,
+    };
+
+    use gix::remote::{
+        fetch::{self, Status},
+        Direction::Fetch,
+    };
+    use gix_testtools::tempfile::TempDir;
+
+    /// Serve all files below `root` via HTTP like a static file server would, and return the URL to it.
+    fn serve_directory(root: PathBuf) -> std::io::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().expect("can clone"));
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).ok();
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).map_or(true, |n| n == 0) || header.trim().is_empty() {
+                        break;
+                    }
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let path = path.split('?').next().unwrap_or_default().trim_start_matches('/');
+                let response = match std::fs::read(root.join(path)) {
+                    Ok(data) => {
+                        let mut response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            data.len()
+                        )
+                        .into_bytes();
+                        response.extend(data);
+                        response
+                    }
+                    Err(_) => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                stream.write_all(&response).ok();
+            }
+        });
+        Ok(url)
+    }
+
+    fn fetch_from_served(
+        repo: &gix::Repository,
+        dir: &TempDir,
+        shallow: fetch::Shallow,
+    ) -> Result<gix::remote::fetch::Outcome, gix::remote::fetch::Error> {
+        let url = serve_directory(dir.path().to_owned()).expect("can bind");
+        repo.remote_at(format!("{url}/served.git").as_str())
+            .expect("valid url")
+            .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), Fetch)
+            .expect("valid refspec")
+            .with_fetch_tags(fetch::Tags::Included)
+            .connect(Fetch)
+            .expect("connect is lazy")
+            .prepare_fetch(gix::progress::Discard, Default::default())
+            .expect("the 'dumb' server advertises its refs")
+            .with_shallow(shallow)
+            .receive(gix::progress::Discard, &AtomicBool::default())
+    }
+
+    #[test]
+    fn fetch_into_empty_repository_downloads_packs_and_loose_objects() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable("make_dumb_http_repos.sh")?;
+        let repo = gix::init_bare(dir.path().join("empty.git"))?;
+        let out = fetch_from_served(&repo, &dir, Default::default())?;
+        assert_eq!(
+            out.handshake.server_protocol_version,
+            gix::protocol::transport::Protocol::V1,
+            "'dumb' servers are represented as V1"
+        );
+        let Status::ObjectsDownloaded {
+            write_pack_bundles,
+            loose_objects,
+            update_refs,
+        } = out.status
+        else {
+            unreachable!("objects are downloaded as nothing is present")
+        };
+        assert_eq!(write_pack_bundles.len(), 1, "there is only one pack");
+        assert_eq!(
+            write_pack_bundles[0].index.num_objects, 8,
+            "the whole pack is downloaded, with the first two commits and the first tag"
+        );
+        assert!(
+            write_pack_bundles[0].keep_path.is_none(),
+            "the pack is unlocked as references were updated"
+        );
+        assert_eq!(
+            loose_objects,
+            4 + 3 + 1,
+            "the objects of the last two commits and the last tag are loose"
+        );
+        assert_eq!(update_refs.edits.len(), 1 + 2, "one branch and two tags");
+        assert_eq!(super::tag_names(&repo)?, ["v1", "v2"]);
+
+        let served = gix::open_opts(dir.path().join("served.git"), crate::restricted())?;
+        let tip = repo.find_reference("refs/remotes/origin/main")?.id().detach();
+        assert_eq!(tip, served.rev_parse_single("main")?.detach());
+        assert_eq!(repo.rev_walk([tip]).all()?.count(), 4, "all commits are present");
+        assert!(
+            !std::fs::read_dir(repo.objects.store_ref().path().join("pack"))?.any(|entry| entry
+                .expect("readable")
+                .file_name()
+                .to_string_lossy()
+                .starts_with("tmp_")),
+            "downloaded pack indices are removed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_fetch_only_downloads_missing_objects() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable("make_dumb_http_repos.sh")?;
+        let repo = gix::open_opts(dir.path().join("client.git"), crate::restricted())?;
+        let out = fetch_from_served(&repo, &dir, Default::default())?;
+        let Status::ObjectsDownloaded {
+            write_pack_bundles,
+            loose_objects,
+            ..
+        } = out.status
+        else {
+            unreachable!("new objects are available")
+        };
+        assert!(write_pack_bundles.is_empty(), "the pack contains nothing new");
+        assert_eq!(loose_objects, 4 + 3 + 1, "only the loose objects are needed");
+        assert_eq!(super::tag_names(&repo)?, ["v1", "v2"], "the new tag is included");
+
+        let out = fetch_from_served(&repo, &dir, Default::default())?;
+        assert!(
+            matches!(out.status, Status::NoPackReceived { dry_run: false, .. }),
+            "nothing is downloaded if everything is present"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pack_names_which_are_not_plain_pack_hashes_are_ignored() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable("make_dumb_http_repos.sh")?;
+        let packs_path = dir.path().join("served.git/objects/info/packs");
+        let packs = std::fs::read(&packs_path)?;
+        let mut malicious = b"P ../../../../victim.pack\nP pack-../../victim.pack\nP pack-1234.pack\n".to_vec();
+        malicious.extend(packs);
+        std::fs::write(&packs_path, malicious)?;
+
+        let repo = gix::init_bare(dir.path().join("empty.git"))?;
+        let out = fetch_from_served(&repo, &dir, Default::default())?;
+        let Status::ObjectsDownloaded { write_pack_bundles, .. } = out.status else {
+            unreachable!("objects are downloaded as nothing is present")
+        };
+        assert_eq!(
+            write_pack_bundles.len(),
+            1,
+            "only the valid pack is used, and the others are skipped without being used as paths"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn loose_objects_claiming_more_than_their_compressed_size_allows_are_rejected() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable("make_dumb_http_repos.sh")?;
+        let served = gix::open_opts(dir.path().join("served.git"), crate::restricted())?;
+        let tip = served.rev_parse_single("main")?.detach();
+        let hex = tip.to_hex().to_string();
+        let mut object = gix::features::zlib::stream::deflate::Write::new(Vec::new());
+        object.write_all(b"commit 1000000000000\0")?;
+        object.flush()?;
+        std::fs::write(
+            served.objects.store_ref().path().join(&hex[..2]).join(&hex[2..]),
+            object.into_inner(),
+        )?;
+
+        let repo = gix::open_opts(dir.path().join("client.git"), crate::restricted())?;
+        let err = fetch_from_served(&repo, &dir, Default::default()).expect_err("the object is corrupt");
+        assert!(
+            matches!(
+                err,
+                gix::remote::fetch::Error::Dumb(gix::remote::fetch::dumb::Error::LooseObjectTooLarge {
+                    id,
+                    size: 1000000000000,
+                    ..
+                }) if id == tip
+            ),
+            "the claimed size is rejected before allocating memory for it"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shallow_fetches_are_rejected() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable("make_dumb_http_repos.sh")?;
+        let repo = gix::open_opts(dir.path().join("client.git"), crate::restricted())?;
+        let err = fetch_from_served(&repo, &dir, fetch::Shallow::DepthAtRemote(1.try_into()?))
+            .expect_err("shallow fetches aren't possible");
+        assert!(matches!(
+            err,
+            gix::remote::fetch::Error::Dumb(gix::remote::fetch::dumb::Error::Shallow)
+        ));
+        Ok(())
+    }
+}
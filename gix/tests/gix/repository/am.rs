@@ -0,0 +1,266 @@
+use gix::repository::am::{Error, Mail, Options, Outcome, StopReason};
+use gix_index::entry::{Flags, Mode, Stage};
+
+/// Return a writable copy of the repository and the mbox with all of its patches, with `HEAD` reset to the commit
+/// before the first patch.
+fn repo() -> crate::Result<(
+    gix::Repository,
+    Vec<u8>,
+    Vec<gix::ObjectId>,
+    gix_testtools::tempfile::TempDir,
+)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_format_patch_repo.sh")?;
+    let repo = gix::open_opts(tmp.path().join("repo"), crate::restricted())?;
+    let mut names = std::fs::read_dir(tmp.path().join("patches"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    let mut mbox = Vec::new();
+    for name in names {
+        mbox.extend(std::fs::read(name)?);
+    }
+
+    let mut commits = repo
+        .head_id()?
+        .ancestors()
+        .all()?
+        .take(5)
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    commits.reverse();
+    repo.reset(commits[0], gix::repository::reset::Mode::Hard)?;
+    Ok((repo, mbox, commits[1..].to_vec(), tmp))
+}
+
+fn three_way() -> Options {
+    Options { three_way: true }
+}
+
+/// Make the next patch conflict in `a` by changing the line it changes as well.
+fn commit_conflicting_change(repo: &gix::Repository) -> crate::Result {
+    let path = repo.workdir().expect("non-bare").join("a");
+    let content = std::fs::read_to_string(&path)?.replace("\n2\n", "\nzwei\n");
+    std::fs::write(&path, &content)?;
+    stage(repo, "a", content.as_bytes())?;
+    let mut index = repo.open_index()?;
+    let tree = repo.write_tree_from_index(&mut index)?;
+    repo.commit("HEAD", "conflicting change", tree, [repo.head_id()?])?;
+    Ok(())
+}
+
+fn stage(repo: &gix::Repository, path: &str, content: &[u8]) -> crate::Result {
+    let id = repo.write_blob(content)?.detach();
+    let mut index = repo.open_index()?;
+    index.remove_entries(|_, entry_path, _| entry_path == path);
+    index.dangerously_push_entry(Default::default(), id, Flags::empty(), Mode::FILE, path.into());
+    index.sort_entries();
+    index.write(Default::default())?;
+    Ok(())
+}
+
+fn assert_same_commit(repo: &gix::Repository, actual: gix::ObjectId, expected: gix::ObjectId) -> crate::Result {
+    let (actual, expected) = (repo.find_commit(actual)?, repo.find_commit(expected)?);
+    assert_eq!(actual.tree_id()?, expected.tree_id()?);
+    assert_eq!(actual.message_raw()?, expected.message_raw()?);
+    assert_eq!(actual.author()?, expected.author()?);
+    Ok(())
+}
+
+#[test]
+fn patches_by_format_patch_are_applied_and_committed() -> crate::Result {
+    let (repo, mbox, expected, _tmp) = repo()?;
+    let Outcome::Applied { commits } = repo.am(&mbox, Options::default())? else {
+        panic!("all patches apply")
+    };
+    assert_eq!(commits.len(), 4);
+    for (actual, expected) in commits.iter().zip(&expected) {
+        assert_same_commit(&repo, *actual, *expected)?;
+    }
+    assert_eq!(repo.head_id()?, commits[3]);
+    assert_eq!(repo.state(), None, "the state is removed when done");
+    assert!(repo.index()?.entries().iter().all(|e| e.stage() == Stage::Unconflicted));
+
+    let reflog_message = repo
+        .find_reference("HEAD")?
+        .log_iter()
+        .rev()?
+        .expect("reflog exists")
+        .next()
+        .expect("an entry")?
+        .message;
+    assert_eq!(
+        reflog_message,
+        "am: A subject that is long enough so that it needs to be wrapped somewhere, as it is long"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_stop_and_can_be_resolved_to_continue() -> crate::Result {
+    let (repo, mbox, expected, _tmp) = repo()?;
+    commit_conflicting_change(&repo)?;
+    let Outcome::Stopped {
+        commits,
+        patch_number,
+        subject,
+        reason: StopReason::Conflicts { paths },
+    } = repo.am(&mbox, three_way())?
+    else {
+        panic!("the first patch conflicts")
+    };
+    assert!(commits.is_empty());
+    assert_eq!(patch_number, 1);
+    assert_eq!(subject, "Modify a and add a binary file");
+    assert_eq!(paths, ["a"]);
+    assert_eq!(repo.state(), Some(gix::state::InProgress::ApplyMailbox));
+    assert!(matches!(repo.am(&mbox, three_way()), Err(Error::InProgress)));
+    assert!(matches!(
+        repo.am_continue(),
+        Err(Error::UnresolvedConflicts { paths }) if paths == ["a"]
+    ));
+
+    let path = repo.workdir().expect("non-bare").join("a");
+    let content = std::fs::read_to_string(&path)?;
+    assert!(content.contains("<<<<<<<"), "the conflict is marked in the worktree");
+    let resolved = "1\ntwo\n3\n4\n5\n6\n7\n8\nnine\n10\n";
+    std::fs::write(&path, resolved)?;
+    stage(&repo, "a", resolved.as_bytes())?;
+
+    let Outcome::Applied { commits } = repo.am_continue()? else {
+        panic!("the remaining patches apply")
+    };
+    assert_eq!(commits.len(), 4, "the resolved patch is committed as well");
+    for (actual, expected) in commits.iter().zip(&expected) {
+        assert_same_commit(&repo, *actual, *expected)?;
+    }
+    assert_eq!(repo.state(), None);
+    Ok(())
+}
+
+#[test]
+fn patches_that_do_not_apply_can_be_skipped() -> crate::Result {
+    let (repo, mbox, expected, _tmp) = repo()?;
+    commit_conflicting_change(&repo)?;
+    let Outcome::Stopped {
+        patch_number: 1,
+        reason: StopReason::DoesNotApply(_),
+        ..
+    } = repo.am(&mbox, Options::default())?
+    else {
+        panic!("without merging, the first patch doesn't apply")
+    };
+    assert!(
+        matches!(repo.am_continue(), Err(Error::NoChanges)),
+        "nothing was changed by the patch"
+    );
+
+    let Outcome::Applied { commits } = repo.am_skip()? else {
+        panic!("the remaining patches apply")
+    };
+    assert_eq!(commits.len(), 3);
+    let tree = repo.find_commit(commits[0])?.tree()?;
+    assert!(
+        tree.find_entry("bin").is_none(),
+        "the skipped patch isn't applied, only the following ones"
+    );
+    assert_eq!(
+        repo.find_commit(commits[2])?.message_raw()?,
+        repo.find_commit(expected[3])?.message_raw()?
+    );
+    Ok(())
+}
+
+#[test]
+fn abort_restores_the_previous_state() -> crate::Result {
+    let (repo, mbox, _expected, _tmp) = repo()?;
+    commit_conflicting_change(&repo)?;
+    let head = repo.head_id()?.detach();
+    assert!(matches!(repo.am(&mbox, three_way())?, Outcome::Stopped { .. }));
+    repo.am_abort()?;
+    assert_eq!(repo.head_id()?, head);
+    assert_eq!(repo.state(), None);
+    let content = std::fs::read_to_string(repo.workdir().expect("non-bare").join("a"))?;
+    assert!(!content.contains("<<<<<<<"), "the worktree is reset");
+    assert!(matches!(repo.am_abort(), Err(Error::NotInProgress)));
+    Ok(())
+}
+
+#[test]
+fn mails() -> crate::Result {
+    let mail = Mail::from_bytes(
+        b"From 1234567890123456789012345678901234567890 Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?A=2E=20=C3=9C=2E=20Thor?= <author@example.com>
+Date: Sat, 1 Jan 2000 01:00:00 +0100
+Subject: [PATCH 3/4] =?UTF-8?q?=C3=9Cn=C3=AFcode=20subject=20that=20is=20long=20enough=20to?=
+ =?UTF-8?q?=20wrap?=
+MIME-Version: 1.0
+Content-Type: text/plain; charset=UTF-8
+Content-Transfer-Encoding: quoted-printable
+
+From: \"In, Body\" <in-body@example.com>
+
+The b=C3=B6dy.
+---
+ a | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/a b/a
+--- a/a
++++ b/a
+@@ -1 +1 @@
+-1
++one
+-- 
+signature
+
+",
+    )?;
+    assert_eq!(mail.author.name, "In, Body", "in-body headers take precedence");
+    assert_eq!(mail.author.email, "in-body@example.com");
+    assert_eq!(mail.author.time.seconds, 946_684_800);
+    assert_eq!(mail.author.time.offset, 3600);
+    assert_eq!(mail.subject, "Ünïcode subject that is long enough to wrap");
+    assert_eq!(
+        mail.message,
+        "Ünïcode subject that is long enough to wrap\n\nThe bödy.\n"
+    );
+    assert_eq!(mail.patches.len(), 1);
+
+    assert_eq!(
+        gix::repository::am::split_mbox(
+            b"From a Mon Sep 17 00:00:00 2001\nbody\nFrom is not a separator\nFrom b Mon Sep 17 00:00:00 2001\nbody\n"
+        ),
+        [
+            b"From a Mon Sep 17 00:00:00 2001\nbody\nFrom is not a separator\n".as_slice(),
+            b"From b Mon Sep 17 00:00:00 2001\nbody\n".as_slice()
+        ]
+    );
+    assert!(matches!(
+        Mail::from_bytes(b"Subject: no author\n\nbody\n"),
+        Err(gix::repository::am::mail::parse::Error::MissingAuthor)
+    ));
+    Ok(())
+}
+
+#[test]
+fn quoted_printable_soft_line_breaks_consume_exactly_one_line_ending() -> crate::Result {
+    let mail = Mail::from_bytes(
+        b"From: A U Thor <author@example.com>
+Date: Sat, 1 Jan 2000 01:00:00 +0100
+Subject: [PATCH] soft line breaks
+Content-Type: text/plain; charset=UTF-8
+Content-Transfer-Encoding: quoted-printable
+
+A soft=
+ break, and one at the end of a paragraph=
+
+Then one with a CRLF=\r\n line ending.
+",
+    )?;
+    assert_eq!(
+        mail.message,
+        "soft line breaks\n\nA soft break, and one at the end of a paragraph\nThen one with a CRLF line ending.\n",
+        "the empty line after the soft break remains as line ending"
+    );
+    Ok(())
+}
@@ -0,0 +1,308 @@
+use gix::repository::apply_patches::{Error, Location, Options};
+use gix_index::entry::Stage;
+
+fn repo() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    crate::util::repo_rw("make_checkout_tree_repo.sh")
+}
+
+fn path(repo: &gix::Repository, rela_path: &str) -> std::path::PathBuf {
+    repo.workdir().expect("non-bare").join(rela_path)
+}
+
+fn read(repo: &gix::Repository, rela_path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(path(repo, rela_path))
+}
+
+fn index_content(repo: &gix::Repository, rela_path: &str) -> crate::Result<Option<String>> {
+    let index = repo.open_index()?;
+    let Some(entry) = index.entry_by_path_and_stage(rela_path.into(), Stage::Unconflicted) else {
+        return Ok(None);
+    };
+    Ok(Some(String::from_utf8(repo.find_blob(entry.id)?.take_data())?))
+}
+
+fn patches(patch: &str) -> crate::Result<Vec<gix::diff::patch::File>> {
+    Ok(gix::diff::patch::parse(patch.as_bytes())?)
+}
+
+const PATCH: &str = "\
+diff --git a/m b/m
+--- a/m
++++ b/m
+@@ -1,3 +1,3 @@
+ 1
+-2
++TWO
+ 3
+diff --git a/added b/added
+new file mode 100644
+--- /dev/null
++++ b/added
+@@ -0,0 +1 @@
++added
+diff --git a/unchanged b/unchanged
+deleted file mode 100644
+--- a/unchanged
++++ /dev/null
+@@ -1 +0,0 @@
+-unchanged
+diff --git a/a b/dir/renamed
+old mode 100644
+new mode 100755
+similarity index 100%
+rename from a
+rename to dir/renamed
+";
+
+#[test]
+fn worktree() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let outcome = repo.apply_patches(&patches(PATCH)?, Options::default())?;
+    assert_eq!(
+        outcome.files.iter().map(|f| f.path.to_string()).collect::<Vec<_>>(),
+        ["m", "added", "unchanged", "dir/renamed"]
+    );
+    assert!(outcome.files.iter().all(|f| !f.has_rejected_hunks() && !f.merged));
+
+    assert_eq!(read(&repo, "m")?, "1\nTWO\n3\n4\n5\n");
+    assert_eq!(read(&repo, "added")?, "added\n");
+    assert!(!path(&repo, "unchanged").exists());
+    assert!(!path(&repo, "a").exists());
+    assert_eq!(read(&repo, "dir/renamed")?, "a\n");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path(&repo, "dir/renamed"))?.permissions().mode();
+        assert_ne!(mode & 0o100, 0, "the new mode is applied");
+    }
+
+    assert_eq!(
+        index_content(&repo, "m")?.as_deref(),
+        Some("1\n2\n3\n4\n5\n"),
+        "the index isn't touched"
+    );
+    assert_eq!(index_content(&repo, "a")?.as_deref(), Some("a\n"));
+    assert_eq!(index_content(&repo, "added")?, None);
+    Ok(())
+}
+
+#[test]
+fn index() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    repo.apply_patches(
+        &patches(PATCH)?,
+        Options {
+            location: Location::Index,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(index_content(&repo, "m")?.as_deref(), Some("1\nTWO\n3\n4\n5\n"));
+    assert_eq!(index_content(&repo, "added")?.as_deref(), Some("added\n"));
+    assert_eq!(index_content(&repo, "unchanged")?, None);
+    assert_eq!(index_content(&repo, "a")?, None);
+    assert_eq!(index_content(&repo, "dir/renamed")?.as_deref(), Some("a\n"));
+
+    assert_eq!(read(&repo, "m")?, "1\n2\n3\n4\n5\n", "the worktree isn't touched");
+    assert!(path(&repo, "unchanged").exists());
+    assert!(!path(&repo, "added").exists());
+    Ok(())
+}
+
+#[test]
+fn worktree_and_index() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let options = Options {
+        location: Location::WorktreeAndIndex,
+        ..Default::default()
+    };
+    std::fs::write(path(&repo, "m"), "1\n2\n3\n4\nfive\n")?;
+    let err = repo.apply_patches(&patches(PATCH)?, options).unwrap_err();
+    assert!(
+        matches!(err, Error::WorktreeMismatch { ref path } if path == "m"),
+        "files must match the index: {err:?}"
+    );
+    assert!(!path(&repo, "added").exists(), "nothing was changed");
+
+    std::fs::write(path(&repo, "m"), "1\n2\n3\n4\n5\n")?;
+    repo.apply_patches(&patches(PATCH)?, options)?;
+    for (rela_path, expected) in [("m", "1\nTWO\n3\n4\n5\n"), ("added", "added\n"), ("dir/renamed", "a\n")] {
+        assert_eq!(read(&repo, rela_path)?, expected);
+        assert_eq!(index_content(&repo, rela_path)?.as_deref(), Some(expected));
+    }
+    let index = repo.open_index()?;
+    assert_ne!(
+        index
+            .entry_by_path_and_stage("m".into(), Stage::Unconflicted)
+            .expect("present")
+            .stat,
+        Default::default(),
+        "the index knows the stat of the written file"
+    );
+    Ok(())
+}
+
+#[test]
+fn rejected_hunks() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let patch = "\
+--- a/b
++++ b/b
+@@ -1 +1 @@
+-b
++B
+--- a/m
++++ b/m
+@@ -1,3 +1,3 @@
+ 1
+-2
++TWO
+ 3
+@@ -3,3 +3,3 @@
+ 3
+ 4
+-not-there
++FIVE
+";
+    let err = repo.apply_patches(&patches(patch)?, Options::default()).unwrap_err();
+    assert!(matches!(err, Error::Rejected { ref path, ref hunks } if path == "m" && hunks == &[1]));
+    assert_eq!(read(&repo, "b")?, "b\n", "nothing is applied if one hunk doesn't apply");
+
+    let outcome = repo.apply_patches(
+        &patches(patch)?,
+        Options {
+            reject: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(!outcome.files[0].has_rejected_hunks());
+    assert!(outcome.files[1].has_rejected_hunks());
+    assert_eq!(read(&repo, "b")?, "B\n");
+    assert_eq!(read(&repo, "m")?, "1\nTWO\n3\n4\n5\n", "hunks that apply are applied");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "merge")]
+fn three_way() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let base_id = repo.rev_parse_single("main:m")?;
+    let patch = |from: &str, to: &str| {
+        format!(
+            "diff --git a/m b/m\nindex {base_id}..0000000 100644\n--- a/m\n+++ b/m\n@@ -3,3 +3,3 @@\n 3\n 4\n-{from}\n+{to}\n"
+        )
+    };
+    let mut index = repo.open_index()?;
+    let entry = index
+        .entry_mut_by_path_and_stage("m".into(), Stage::Unconflicted)
+        .expect("present");
+    entry.id = repo.write_blob("ONE\n2\n3\n4\n5\n")?.detach();
+    index.write(Default::default())?;
+    std::fs::write(path(&repo, "m"), "ONE\n2\n3\n4\n5\n")?;
+
+    let options = Options {
+        three_way: true,
+        ..Default::default()
+    };
+    let outcome = repo.apply_patches(&patches(&patch("5", "five"))?, options)?;
+    assert!(!outcome.files[0].merged, "the patch applies without merging");
+    assert_eq!(read(&repo, "m")?, "ONE\n2\n3\n4\nfive\n");
+    assert_eq!(
+        index_content(&repo, "m")?.as_deref(),
+        Some("ONE\n2\n3\n4\nfive\n"),
+        "--3way implies --index"
+    );
+
+    let outcome = repo.apply_patches(&patches(&patch("5", "FIVE"))?, options)?;
+    assert!(outcome.files[0].merged, "the context doesn't match anymore");
+    assert!(outcome.files[0].conflict);
+    assert!(read(&repo, "m")?.contains("<<<<<<< ours\n"), "conflicts are marked");
+    let index = repo.open_index()?;
+    let stages: Vec<_> = index
+        .entries()
+        .iter()
+        .filter(|e| e.path(&index) == "m")
+        .map(gix_index::Entry::stage)
+        .collect();
+    assert_eq!(stages, [Stage::Base, Stage::Ours, Stage::Theirs]);
+    assert_eq!(
+        index.entries()[index
+            .entry_index_by_path_and_stage("m".into(), Stage::Base)
+            .expect("present")]
+        .id,
+        base_id
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "merge")]
+fn three_way_without_conflicts() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let base_id = repo.rev_parse_single("main:m")?;
+    let patch = format!(
+        "diff --git a/m b/m\nindex {base_id}..0000000 100644\n--- a/m\n+++ b/m\n@@ -1,3 +1,3 @@\n 1\n-2\n+TWO\n 3\n"
+    );
+    std::fs::write(path(&repo, "m"), "ONE\n2\n3\n4\n5\n")?;
+    repo.apply_patches(
+        &patches(&patch)?,
+        Options {
+            location: Location::Worktree,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+
+    let mut index = repo.open_index()?;
+    index
+        .entry_mut_by_path_and_stage("m".into(), Stage::Unconflicted)
+        .expect("present")
+        .id = repo.write_blob("ONE\n2\n3\n4\n5\n")?.detach();
+    index.write(Default::default())?;
+    let outcome = repo.apply_patches(
+        &patches(&patch)?,
+        Options {
+            three_way: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(outcome.files[0].merged && !outcome.files[0].conflict);
+    assert_eq!(read(&repo, "m")?, "ONE\nTWO\n3\n4\n5\n");
+    assert_eq!(index_content(&repo, "m")?.as_deref(), Some("ONE\nTWO\n3\n4\n5\n"));
+    Ok(())
+}
+
+#[test]
+fn to_tree() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let tree_id = repo.apply_patches_to_tree(repo.rev_parse_single("main")?, &patches(PATCH)?, Default::default())?;
+    let tree = repo.find_tree(tree_id)?;
+    let content = |rela_path: &str| -> crate::Result<Option<String>> {
+        Ok(match tree.lookup_entry_by_path(rela_path)? {
+            Some(entry) => Some(String::from_utf8(entry.object()?.detach().data)?),
+            None => None,
+        })
+    };
+    assert_eq!(content("m")?.as_deref(), Some("1\nTWO\n3\n4\n5\n"));
+    assert_eq!(content("added")?.as_deref(), Some("added\n"));
+    assert_eq!(content("unchanged")?, None);
+    assert_eq!(content("a")?, None);
+    assert_eq!(content("dir/renamed")?.as_deref(), Some("a\n"));
+    assert_eq!(content("dir/c")?.as_deref(), Some("c\n"));
+    assert_eq!(
+        tree.lookup_entry_by_path("dir/renamed")?
+            .expect("present")
+            .mode()
+            .kind(),
+        gix::object::tree::EntryKind::BlobExecutable
+    );
+
+    let err = repo
+        .apply_patches_to_tree(
+            repo.rev_parse_single("main")?,
+            &patches("--- a/added\n+++ b/added\n@@ -1 +1 @@\n-a\n+b\n")?,
+            Default::default(),
+        )
+        .unwrap_err();
+    assert!(matches!(err, Error::Missing { ref path } if path == "added"));
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix::{bstr::ByteSlice, repository::bundle::create};
+use gix_testtools::tempfile;
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_bundle_repos.sh")
+}
+
+fn fixture_rw() -> crate::Result<tempfile::TempDir> {
+    gix_testtools::scripted_fixture_writable("make_bundle_repos.sh")
+}
+
+fn open(path: impl Into<PathBuf>) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(path, crate::restricted())?)
+}
+
+fn create_bundle(repo: &gix::Repository, specs: &[&str]) -> Result<(Vec<u8>, create::Outcome), create::Error> {
+    let mut out = Vec::new();
+    let outcome = repo.create_bundle(
+        specs.iter().map(|spec| (*spec).into()),
+        &mut out,
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+        Default::default(),
+    )?;
+    Ok((out, outcome))
+}
+
+fn id(repo: &gix::Repository, spec: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(spec)?.detach())
+}
+
+mod create_bundle {
+    use super::*;
+
+    #[test]
+    fn all_references_can_be_received_by_an_empty_repository() -> crate::Result {
+        let base = open(fixture()?.join("base"))?;
+        let (bundle, outcome) = create_bundle(&base, &["HEAD", "main", "other", "v1"])?;
+        assert!(outcome.header.prerequisites.is_empty());
+        assert_eq!(
+            outcome
+                .header
+                .refs
+                .iter()
+                .map(|r| (r.name.to_string(), r.id))
+                .collect::<Vec<_>>(),
+            [
+                ("HEAD".to_string(), id(&base, "main")?),
+                ("refs/heads/main".into(), id(&base, "main")?),
+                ("refs/heads/other".into(), id(&base, "other")?),
+                ("refs/tags/v1".into(), base.find_reference("v1")?.id().detach()),
+            ],
+            "the tag isn't peeled"
+        );
+        assert_eq!(
+            outcome.num_objects,
+            4 + 5 + 4 + 1,
+            "commits, trees including `dir`, blobs and the tag"
+        );
+
+        let tmp = tempfile::TempDir::new()?;
+        let path = tmp.path().join("all.bundle");
+        std::fs::write(&path, &bundle)?;
+        let dir = fixture_rw()?;
+        let empty = open(dir.path().join("empty"))?;
+        let verified = empty.verify_bundle(&path, &mut gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(verified.header, outcome.header);
+
+        let unbundled = empty.unbundle(&path, &mut gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(unbundled.pack.index.num_objects as usize, outcome.num_objects);
+        assert!(unbundled.pack.keep_path.map_or(true, |path| !path.exists()));
+        for r in &unbundled.header.refs {
+            assert!(empty.has_object(r.id), "{}: objects can be found right away", r.name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_have_prerequisites_and_can_be_verified_by_git() -> crate::Result {
+        let base = open(fixture()?.join("base"))?;
+        let (bundle, outcome) = create_bundle(&base, &["main~1..main"])?;
+        assert_eq!(outcome.header.prerequisites.len(), 1);
+        assert_eq!(outcome.header.prerequisites[0].id, id(&base, "main~1")?);
+        assert_eq!(
+            outcome.header.prerequisites[0].comment, "c2",
+            "the summary of the commit"
+        );
+        assert_eq!(outcome.header.refs.len(), 1);
+        assert_eq!(outcome.header.refs[0].name, "refs/heads/main");
+        assert_eq!(
+            outcome.num_objects, 4,
+            "commit, root tree, new tree and blob, but not the unchanged blob of the prerequisite"
+        );
+
+        let expected = std::fs::read(fixture()?.join("incremental.bundle"))?;
+        let expected = gix::bundle::Header::from_read(&mut expected.as_slice())?;
+        assert_eq!(
+            outcome.header, expected,
+            "the header is the same as the one produced by git"
+        );
+
+        let tmp = tempfile::TempDir::new()?;
+        let path = tmp.path().join("incremental.bundle");
+        std::fs::write(&path, &bundle)?;
+        let output = std::process::Command::new(gix::path::env::exe_invocation())
+            .args(["bundle", "verify", "-q"])
+            .arg(&path)
+            .current_dir(fixture()?.join("partial"))
+            .output()?;
+        assert!(
+            output.status.success(),
+            "git can read our bundles: {}",
+            output.stderr.as_bstr()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exclusions_and_unsupported_specs() -> crate::Result {
+        let base = open(fixture()?.join("base"))?;
+        let (_, outcome) = create_bundle(&base, &["other", "^v1"])?;
+        assert_eq!(outcome.header.prerequisites.len(), 1);
+        assert_eq!(outcome.header.prerequisites[0].id, id(&base, "v1^{commit}")?);
+        assert_eq!(outcome.num_objects, 3, "commit, tree and blob");
+
+        assert!(matches!(
+            create_bundle(&base, &["main...other"]),
+            Err(create::Error::UnsupportedSpec { .. })
+        ));
+        let main = id(&base, "main")?.to_string();
+        assert!(
+            matches!(create_bundle(&base, &[main.as_str()]), Err(create::Error::Empty)),
+            "revisions that aren't references can't be listed"
+        );
+        Ok(())
+    }
+}
+
+mod verify_and_unbundle {
+    use super::*;
+
+    #[test]
+    fn missing_prerequisites_are_detected() -> crate::Result {
+        let dir = fixture_rw()?;
+        let bundle = dir.path().join("incremental.bundle");
+        let empty = open(dir.path().join("empty"))?;
+        assert!(matches!(
+            empty.verify_bundle(&bundle, &mut gix::progress::Discard, &AtomicBool::default()),
+            Err(gix::repository::bundle::verify::Error::Verify(
+                gix::bundle::file::verify::Error::MissingPrerequisites { .. }
+            ))
+        ));
+        assert!(matches!(
+            empty.unbundle(&bundle, &mut gix::progress::Discard, &AtomicBool::default()),
+            Err(gix::repository::bundle::unbundle::Error::MissingPrerequisites { .. })
+        ));
+
+        let partial = open(dir.path().join("partial"))?;
+        let outcome = partial.unbundle(&bundle, &mut gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(outcome.pack.index.num_objects, 4, "the thin pack was completed");
+        assert!(partial.has_object(outcome.header.refs[0].id));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "blocking-network-client")]
+mod fetch {
+    use gix::remote::Direction;
+
+    use super::*;
+
+    #[test]
+    fn bundles_can_be_used_as_remote() -> crate::Result {
+        let dir = fixture_rw()?;
+        let partial = open(dir.path().join("partial"))?;
+        let remote = partial
+            .remote_at(dir.path().join("incremental.bundle").as_path())?
+            .with_refspecs(Some("+refs/heads/*:refs/remotes/bundle/*"), Direction::Fetch)?;
+        let outcome = remote
+            .connect(Direction::Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(matches!(outcome.status, gix::remote::fetch::Status::Change { .. }));
+
+        let base = open(dir.path().join("base"))?;
+        assert_eq!(
+            partial.find_reference("refs/remotes/bundle/main")?.id(),
+            id(&base, "main")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_prerequisites_prevent_connecting() -> crate::Result {
+        let dir = fixture_rw()?;
+        let empty = open(dir.path().join("empty"))?;
+        let remote = empty.remote_at(dir.path().join("incremental.bundle").as_path())?;
+        assert!(matches!(
+            remote.connect(Direction::Fetch),
+            Err(gix::remote::connect::Error::MissingBundlePrerequisites { .. })
+        ));
+        Ok(())
+    }
+}
+
+mod unbundle_from_uri {
+    use gix::repository::bundle::uri;
+
+    use super::*;
+
+    fn unbundle(uri: &std::path::Path, options: uri::Options) -> crate::Result<(tempfile::TempDir, uri::Outcome)> {
+        let dir = gix_testtools::scripted_fixture_writable("make_bundle_uri_repos.sh")?;
+        let repo = gix::init_bare(dir.path().join("clone"))?;
+        let outcome = repo.unbundle_from_uri(
+            gix::path::into_bstr(dir.path().join(uri)).as_ref(),
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+            options,
+        )?;
+        Ok((dir, outcome))
+    }
+
+    fn bundle_main(dir: &tempfile::TempDir) -> crate::Result<gix::ObjectId> {
+        Ok(open(dir.path().join("clone"))?
+            .find_reference("refs/bundles/main")?
+            .id()
+            .detach())
+    }
+
+    #[test]
+    fn single_bundle() -> crate::Result {
+        let (dir, outcome) = unbundle("bundles/1.bundle".as_ref(), Default::default())?;
+        assert!(outcome.list.is_none(), "the uri points to a bundle directly");
+        assert_eq!(outcome.bundles.len(), 1);
+        assert_eq!(outcome.creation_token, None);
+        let base = open(dir.path().join("base"))?;
+        assert_eq!(bundle_main(&dir)?, id(&base, "main~2")?);
+        Ok(())
+    }
+
+    #[test]
+    fn list_with_all_bundles_is_unbundled_in_order_of_prerequisites() -> crate::Result {
+        let (dir, outcome) = unbundle("bundles/all.list".as_ref(), Default::default())?;
+        assert_eq!(outcome.list.expect("a list").bundles.len(), 2);
+        assert_eq!(
+            outcome
+                .bundles
+                .iter()
+                .map(|b| b.header.prerequisites.len())
+                .collect::<Vec<_>>(),
+            [0, 1],
+            "the bundle without prerequisites comes first even though it's listed last"
+        );
+        assert_eq!(outcome.creation_token, None);
+        let base = open(dir.path().join("base"))?;
+        assert_eq!(bundle_main(&dir)?, id(&base, "main~1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn list_with_any_bundle_skips_bundles_that_fail_to_download() -> crate::Result {
+        let (dir, outcome) = unbundle("bundles/any.list".as_ref(), Default::default())?;
+        assert_eq!(outcome.bundles.len(), 1);
+        let base = open(dir.path().join("base"))?;
+        assert_eq!(bundle_main(&dir)?, id(&base, "main~2")?);
+        Ok(())
+    }
+
+    #[test]
+    fn list_with_creation_tokens() -> crate::Result {
+        let (dir, outcome) = unbundle("bundles/creation-token.list".as_ref(), Default::default())?;
+        assert_eq!(outcome.bundles.len(), 2);
+        assert_eq!(outcome.creation_token, Some(2));
+        let base = open(dir.path().join("base"))?;
+        assert_eq!(bundle_main(&dir)?, id(&base, "main~1")?);
+
+        let (_dir, outcome) = unbundle(
+            "bundles/creation-token.list".as_ref(),
+            uri::Options {
+                min_creation_token: Some(2),
+            },
+        )?;
+        assert!(
+            outcome.bundles.is_empty(),
+            "bundles with tokens that aren't greater than the minimum are skipped"
+        );
+        assert_eq!(outcome.creation_token, None);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_bundle_is_an_error() -> crate::Result {
+        let err = unbundle("bundles/does-not-exist.bundle".as_ref(), Default::default()).unwrap_err();
+        assert!(
+            matches!(err.downcast_ref::<uri::Error>(), Some(uri::Error::Io { .. })),
+            "{err:?}"
+        );
+        Ok(())
+    }
+}
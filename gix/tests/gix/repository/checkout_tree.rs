@@ -0,0 +1,214 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::{
+    bstr::BString,
+    repository::checkout_tree::{Error, Options, UpdateHead},
+};
+
+fn repo() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    crate::util::repo_rw("make_checkout_tree_repo.sh")
+}
+
+fn read(repo: &gix::Repository, rela_path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.workdir().expect("non-bare").join(rela_path))
+}
+
+fn write(repo: &gix::Repository, rela_path: &str, content: &str) -> std::io::Result<()> {
+    std::fs::write(repo.workdir().expect("non-bare").join(rela_path), content)
+}
+
+fn index_entries(index: &gix_index::State) -> Vec<(BString, gix_hash::ObjectId, gix_index::entry::Stage)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index).to_owned(), e.id, e.stage()))
+        .collect()
+}
+
+fn checkout(
+    repo: &gix::Repository,
+    target: &str,
+    options: Options,
+) -> Result<gix::repository::checkout_tree::Outcome, Error> {
+    let target = repo.rev_parse_single(target).expect("valid spec").detach();
+    repo.checkout_tree(target, options, gix::progress::Discard, &AtomicBool::default())
+}
+
+#[test]
+fn switch_branch_only_touches_changed_paths() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    write(&repo, "unchanged", "local\n")?;
+    write(&repo, "untracked", "untracked\n")?;
+
+    let outcome = checkout(
+        &repo,
+        "other",
+        Options {
+            update_head: Some(UpdateHead::Branch("refs/heads/other".try_into()?)),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 4, "a, b, m and new-dir/new");
+    assert_eq!(outcome.files_removed, 1, "dir/c");
+    assert!(outcome.conflicts.is_empty());
+
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(read(&repo, "m")?, "1\nTWO\n3\n4\n5\n");
+    assert_eq!(read(&repo, "new-dir/new")?, "new\n");
+    assert!(
+        !repo.workdir().expect("non-bare").join("dir").exists(),
+        "empty directories are removed"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(repo.workdir().expect("non-bare").join("b"))?
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o100, 0, "the executable bit was set");
+    }
+    assert_eq!(read(&repo, "unchanged")?, "local\n", "local changes are kept");
+    assert_eq!(read(&repo, "untracked")?, "untracked\n");
+
+    let expected = repo.index_from_tree(&repo.rev_parse_single("other^{tree}")?)?;
+    let index = repo.open_index()?;
+    assert_eq!(index_entries(&index), index_entries(&expected));
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/other");
+    let head = repo.find_reference("HEAD")?;
+    let mut log = head.log_iter();
+    let last = log.rev()?.expect("log present").next().expect("one line")?;
+    assert_eq!(last.message, "checkout: moving from main to other");
+    assert!(repo.is_dirty()?, "'unchanged' is still modified");
+    Ok(())
+}
+
+#[test]
+fn local_changes_and_untracked_files_in_the_way_are_not_overwritten() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    write(&repo, "m", "1\n2\n3\n4\nFIVE\n")?;
+    write(&repo, "new-dir", "untracked file where a directory should go\n")?;
+
+    let err = checkout(&repo, "other", Default::default()).unwrap_err();
+    let Error::LocalChanges { paths } = err else {
+        unreachable!("unexpected error: {err:?}")
+    };
+    assert_eq!(paths, ["m", "new-dir/new"]);
+    assert_eq!(read(&repo, "a")?, "a\n", "nothing was changed");
+    assert_eq!(read(&repo, "m")?, "1\n2\n3\n4\nFIVE\n");
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+    Ok(())
+}
+
+#[test]
+fn staged_changes_matching_the_target_are_kept() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let mut index = repo.open_index()?;
+    let a_other = repo.rev_parse_single("other:a")?.detach();
+    index
+        .entry_mut_by_path_and_stage("a".into(), gix_index::entry::Stage::Unconflicted)
+        .expect("present")
+        .id = a_other;
+    index.write(Default::default())?;
+    write(&repo, "a", "a-other\n")?;
+
+    checkout(&repo, "other", Default::default())?;
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/main",
+        "HEAD isn't touched without being asked to"
+    );
+    Ok(())
+}
+
+#[test]
+fn detach_head() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let other = repo.rev_parse_single("other")?.detach();
+    checkout(
+        &repo,
+        "other",
+        Options {
+            update_head: Some(UpdateHead::Detached),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(repo.head_id()?, other);
+    assert!(repo.head_name()?.is_none(), "HEAD is detached");
+    assert!(!repo.is_dirty()?);
+
+    checkout(
+        &repo,
+        "main",
+        Options {
+            update_head: Some(UpdateHead::Branch("refs/heads/main".try_into()?)),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(read(&repo, "dir/c")?, "c\n");
+    assert!(!repo.workdir().expect("non-bare").join("new-dir").exists());
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[cfg(feature = "merge")]
+mod merge {
+    use gix::repository::checkout_tree::Options;
+    use gix_index::entry::Stage;
+
+    use super::{checkout, index_entries, read, repo, write};
+
+    fn merge() -> Options {
+        Options {
+            merge: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clean() -> crate::Result {
+        let (repo, _tmp) = repo()?;
+        write(&repo, "m", "1\n2\n3\n4\nFIVE\n")?;
+
+        let outcome = checkout(&repo, "other", merge())?;
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(read(&repo, "m")?, "1\nTWO\n3\n4\nFIVE\n", "local changes are merged");
+
+        let index = repo.open_index()?;
+        let m_other = repo.rev_parse_single("other:m")?.detach();
+        assert!(
+            index_entries(&index).contains(&("m".into(), m_other, Stage::Unconflicted)),
+            "the index receives the target version"
+        );
+        assert!(repo.is_dirty()?, "the local changes remain as such");
+        Ok(())
+    }
+
+    #[test]
+    fn with_conflicts() -> crate::Result {
+        let (repo, _tmp) = repo()?;
+        write(&repo, "m", "1\nzwei\n3\n4\n5\n")?;
+
+        let outcome = checkout(&repo, "other", merge())?;
+        assert_eq!(outcome.conflicts, ["m"]);
+        let content = read(&repo, "m")?;
+        assert!(content.contains("<<<<<<< "), "{content}");
+        assert!(content.contains(">>>>>>> local"), "{content}");
+
+        let index = repo.open_index()?;
+        let stages: Vec<_> = index_entries(&index)
+            .into_iter()
+            .filter(|(path, _, _)| path == "m")
+            .map(|(_, id, stage)| (id, stage))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                (repo.rev_parse_single("main:m")?.detach(), Stage::Base),
+                (repo.rev_parse_single("other:m")?.detach(), Stage::Ours),
+                (repo.write_blob("1\nzwei\n3\n4\n5\n")?.detach(), Stage::Theirs),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -143,3 +143,25 @@ fn apply_cli_overrides() -> crate::Result {
 
     Ok(())
 }
+
+#[test]
+fn trusted_paths_are_all_values_with_empty_ones_clearing_the_list() -> crate::Result {
+    let mut repo = named_repo("make_config_repo.sh").unwrap();
+    assert!(repo.config_snapshot().trusted_paths("blame.ignoreRevsFile")?.is_empty());
+
+    repo.config_snapshot_mut().append_config(
+        [
+            "blame.ignoreRevsFile=cleared",
+            "blame.ignoreRevsFile=",
+            "blame.ignoreRevsFile=first",
+            "blame.ignoreRevsFile=/second",
+        ],
+        gix_config::Source::Cli,
+    )?;
+    assert_eq!(
+        repo.config_snapshot().trusted_paths("blame.ignoreRevsFile")?,
+        [std::path::Path::new("first"), std::path::Path::new("/second")],
+        "all values are returned in order, but only after the last empty one"
+    );
+    Ok(())
+}
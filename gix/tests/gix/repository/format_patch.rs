@@ -0,0 +1,81 @@
+use gix::{
+    bstr::ByteSlice,
+    repository::format_patch::{Options, Patch},
+};
+
+fn repo() -> crate::Result<(gix::Repository, std::path::PathBuf)> {
+    let root = gix_testtools::scripted_fixture_read_only("make_format_patch_repo.sh")?;
+    let repo = gix::open_opts(root.join("repo"), gix::open::Options::isolated())?;
+    Ok((repo, root))
+}
+
+/// Return the ids of the last `count` commits reachable from `HEAD`, oldest first.
+fn last_commits(repo: &gix::Repository, count: usize) -> crate::Result<Vec<gix::ObjectId>> {
+    let mut ids = repo
+        .head_id()?
+        .ancestors()
+        .all()?
+        .take(count)
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    ids.reverse();
+    Ok(ids)
+}
+
+fn assert_same_as_git(patches: &[Patch], dir: &std::path::Path) -> crate::Result {
+    let mut expected_names = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.file_name().into_string().expect("valid UTF-8")))
+        .collect::<Result<Vec<_>, _>>()?;
+    expected_names.sort();
+    assert_eq!(
+        patches.iter().map(|p| p.file_name.clone()).collect::<Vec<_>>(),
+        expected_names
+    );
+    for patch in patches {
+        let expected = std::fs::read(dir.join(&patch.file_name))?;
+        assert_eq!(patch.data.as_bstr(), expected.as_bstr(), "{}", patch.file_name);
+    }
+    Ok(())
+}
+
+#[test]
+fn numbered_patches_are_the_same_as_the_ones_produced_by_git() -> crate::Result {
+    let (repo, root) = repo()?;
+    let commits = last_commits(&repo, 4)?;
+    let patches = repo.format_patch(commits.iter().copied(), &Options::default())?;
+    assert_eq!(
+        patches.iter().map(|p| p.commit_id).collect::<Vec<_>>(),
+        commits,
+        "patches are produced in the order of the commits"
+    );
+    assert_same_as_git(&patches, &root.join("patches"))
+}
+
+#[test]
+fn a_single_patch_is_not_numbered() -> crate::Result {
+    let (repo, root) = repo()?;
+    let patches = repo.format_patch(last_commits(&repo, 1)?, &Options::default())?;
+    assert_same_as_git(&patches, &root.join("single"))
+}
+
+#[test]
+fn options() -> crate::Result {
+    let (repo, _root) = repo()?;
+    let patches = repo.format_patch(
+        last_commits(&repo, 4)?.into_iter().take(1),
+        &Options {
+            subject_prefix: "RFC".into(),
+            numbered: Some(true),
+            start_number: 3,
+            binary: false,
+            signature: Some("signature".into()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(patches[0].file_name, "0003-Modify-a-and-add-a-binary-file.patch");
+    let data = patches[0].data.as_bstr();
+    assert!(data.contains_str("\nSubject: [RFC 3/3] Modify a and add a binary file\n"));
+    assert!(data.contains_str("\nBinary files /dev/null and b/bin differ\n"));
+    assert!(data.ends_with(b"\n-- \nsignature\n\n"));
+    Ok(())
+}
@@ -0,0 +1,516 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+    time::{Duration, SystemTime},
+};
+
+use gix::repository::maintenance::{expire_reflogs, gc, prune, repack};
+use gix_testtools::tempfile;
+
+use crate::util::hex_to_id;
+
+fn repo_rw() -> crate::Result<(gix::Repository, tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable_with_args(
+        "make_maintenance_repo.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )?;
+    let repo = gix::open_opts(dir.path(), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+/// The loose object written with a modification time in the year 2000.
+fn old_unreachable_blob() -> gix_hash::ObjectId {
+    hex_to_id("00fddf251cafd02b035b4dee6a8cbc64dbe57c4f")
+}
+
+/// The loose object written with the current time as modification time.
+fn new_unreachable_blob() -> gix_hash::ObjectId {
+    hex_to_id("b09f8f9741540807ab21c850875d68cdf577879a")
+}
+
+/// The commit of the deleted `side` branch, which is only reachable through the reflog of `HEAD`.
+fn side_commit() -> gix_hash::ObjectId {
+    hex_to_id("dc8f267e9b78bc592d7fd45f382daee98b5304d1")
+}
+
+fn year_2010() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_262_304_000)
+}
+
+fn pack_indices(repo: &gix::Repository) -> crate::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for entry in std::fs::read_dir(repo.objects.store_ref().path().join("pack"))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "idx") {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+fn loose_objects(repo: &gix::Repository) -> crate::Result<Vec<gix_hash::ObjectId>> {
+    let store = gix_odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash());
+    let mut out = store.iter().collect::<Result<Vec<_>, _>>()?;
+    out.sort();
+    Ok(out)
+}
+
+fn reopen(repo: &gix::Repository) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(repo.git_dir(), crate::restricted())?)
+}
+
+#[test]
+fn repack_writes_all_reachable_objects_into_a_single_pack() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let out = repo.repack(
+        repack::Options::default(),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(
+        out.packed_objects, 16,
+        "13 objects reachable from references, and 3 more from the reflog of HEAD"
+    );
+    assert_eq!(out.removed_packs.len(), 2);
+    assert_eq!(out.removed_loose_objects, 3, "the objects of the last commit");
+    assert_eq!(out.dropped_objects, 0, "all packed objects are reachable");
+    assert_eq!(out.cruft_pack, None);
+
+    assert_eq!(pack_indices(&repo)?, vec![out.pack.expect("written")]);
+    assert_eq!(
+        loose_objects(&repo)?,
+        [old_unreachable_blob(), new_unreachable_blob()],
+        "unreachable loose objects are left untouched"
+    );
+    let repo = reopen(&repo)?;
+    assert!(repo.has_object(side_commit()));
+    assert_eq!(
+        repo.head_commit()?
+            .tree()?
+            .find_entry("a")
+            .expect("present")
+            .object()?
+            .data,
+        b"4\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn repack_with_cruft_pack_after_expiring_reflogs() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let out = repo.expire_reflogs(expire_reflogs::Options {
+        expire_before: Some(year_2010()),
+        ..Default::default()
+    })?;
+    assert_eq!(
+        out,
+        expire_reflogs::Outcome {
+            rewritten_reflogs: 2,
+            removed_entries: 7,
+        },
+        "5 entries of HEAD and 2 entries of main were old enough"
+    );
+
+    let out = repo.repack(
+        repack::Options {
+            unreachable: repack::Unreachable::Cruft {
+                expire_before: Some(year_2010()),
+            },
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(out.packed_objects, 13);
+    assert_eq!(
+        out.cruft_objects, 4,
+        "the side commit with its tree and blob, along with the new unreachable blob"
+    );
+    assert_eq!(out.dropped_objects, 0);
+    assert_eq!(out.removed_loose_objects, 4);
+    assert_eq!(
+        loose_objects(&repo)?,
+        [old_unreachable_blob()],
+        "expired loose objects are left to be pruned"
+    );
+
+    let cruft_index = out.cruft_pack.expect("written");
+    let index = gix_pack::index::File::at(&cruft_index, repo.object_hash())?;
+    let mtimes = gix_pack::mtimes::File::at(
+        cruft_index.with_extension("mtimes"),
+        repo.object_hash(),
+        index.num_objects(),
+    )?;
+    assert_eq!(mtimes.pack_checksum(), index.pack_checksum());
+    let idx = index
+        .lookup(new_unreachable_blob())
+        .expect("the new unreachable blob is in the cruft pack");
+    assert!(
+        mtimes.mtime_at_index(idx) > 1_262_304_000,
+        "the modification time of the loose object is retained"
+    );
+
+    let repo = reopen(&repo)?;
+    assert!(repo.has_object(side_commit()));
+    assert!(repo.has_object(new_unreachable_blob()));
+    Ok(())
+}
+
+#[test]
+fn geometric_repack_only_combines_small_packs() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let packs_before = pack_indices(&repo)?;
+    let out = repo.repack(
+        repack::Options {
+            geometric_factor: Some(2),
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(out.packed_objects, 5, "only loose objects are packed");
+    assert!(out.removed_packs.is_empty(), "the packs already form a progression");
+    assert!(loose_objects(&repo)?.is_empty());
+    assert_eq!(pack_indices(&repo)?.len(), packs_before.len() + 1);
+
+    let out = repo.repack(
+        repack::Options {
+            geometric_factor: Some(4),
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(
+        out.packed_objects, 18,
+        "all packs are combined, without regard for reachability"
+    );
+    assert_eq!(out.removed_packs.len(), 3);
+    assert_eq!(pack_indices(&repo)?, vec![out.pack.expect("written")]);
+    let repo = reopen(&repo)?;
+    assert!(repo.has_object(old_unreachable_blob()));
+    Ok(())
+}
+
+#[test]
+fn prune_removes_expired_unreachable_loose_objects() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let options = prune::Options {
+        expire_before: Some(year_2010()),
+        dry_run: true,
+        thread_limit: None,
+    };
+    let out = repo.prune(options, &mut gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(out.pruned_objects, [old_unreachable_blob()]);
+    assert_eq!(out.removed_packed_objects, 0);
+    assert_eq!(loose_objects(&repo)?.len(), 5, "nothing is removed in a dry-run");
+
+    let out = repo.prune(
+        prune::Options {
+            dry_run: false,
+            ..options
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(out.pruned_objects, [old_unreachable_blob()]);
+    assert_eq!(loose_objects(&repo)?.len(), 4);
+
+    let out = repo.prune(
+        prune::Options::default(),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(
+        out.pruned_objects,
+        [new_unreachable_blob()],
+        "without expiry, all unreachable loose objects are pruned"
+    );
+    assert_eq!(loose_objects(&repo)?.len(), 3);
+    Ok(())
+}
+
+/// Write a loose tree with the current time as modification time which refers to the old unreachable blob.
+fn write_new_tree_referring_to_old_blob(repo: &gix::Repository) -> crate::Result<gix_hash::ObjectId> {
+    let tree = gix::objs::Tree {
+        entries: vec![gix::objs::tree::Entry {
+            mode: gix::objs::tree::EntryKind::Blob.into(),
+            filename: "old".into(),
+            oid: old_unreachable_blob(),
+        }],
+    };
+    Ok(repo.write_object(&tree)?.detach())
+}
+
+#[test]
+fn prune_keeps_expired_objects_referenced_by_unexpired_unreachable_objects() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let tree = write_new_tree_referring_to_old_blob(&repo)?;
+    let out = repo.prune(
+        prune::Options {
+            expire_before: Some(year_2010()),
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(
+        out.pruned_objects.is_empty(),
+        "the old blob is still referenced by the new unreachable tree"
+    );
+    let repo = reopen(&repo)?;
+    assert!(repo.has_object(tree));
+    assert!(repo.has_object(old_unreachable_blob()));
+    Ok(())
+}
+
+#[test]
+fn repack_with_cruft_pack_keeps_expired_objects_referenced_by_unexpired_unreachable_objects() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let tree = write_new_tree_referring_to_old_blob(&repo)?;
+    let out = repo.repack(
+        repack::Options {
+            unreachable: repack::Unreachable::Cruft {
+                expire_before: Some(year_2010()),
+            },
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    let cruft_index = out.cruft_pack.expect("written");
+    let index = gix_pack::index::File::at(&cruft_index, repo.object_hash())?;
+    assert!(index.lookup(tree).is_some());
+    assert!(
+        index.lookup(old_unreachable_blob()).is_some(),
+        "the old blob is kept along with the new tree referring to it"
+    );
+    assert!(loose_objects(&repo)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn prune_removes_missing_shallow_commits() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let present = repo.head_id()?.detach();
+    let missing = hex_to_id("0000000000000000000000000000000000000001");
+    std::fs::write(repo.shallow_file(), format!("{present}\n{missing}\n"))?;
+
+    let options = prune::Options {
+        dry_run: true,
+        ..Default::default()
+    };
+    let out = repo.prune(options, &mut gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(out.pruned_shallow_commits, [missing]);
+    assert_eq!(
+        repo.shallow_commits()?.expect("shallow").len(),
+        2,
+        "nothing is changed in a dry-run"
+    );
+
+    let out = repo.prune(
+        prune::Options::default(),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(out.pruned_shallow_commits, [missing]);
+    assert_eq!(repo.shallow_commits()?.expect("shallow").as_slice(), [present]);
+    Ok(())
+}
+
+#[test]
+fn pack_refs_moves_loose_references_into_packed_refs() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let head_before = repo.head_id()?.detach();
+    let out = repo.pack_refs()?;
+    assert_eq!(out.packed_refs, 2, "main and the annotated tag");
+    assert!(!repo.git_dir().join("refs/heads/main").exists());
+    assert!(!repo.git_dir().join("refs/tags/v1").exists());
+    assert!(repo.git_dir().join("HEAD").is_file(), "symbolic refs remain loose");
+
+    let repo = reopen(&repo)?;
+    assert_eq!(repo.head_id()?, head_before);
+    assert_eq!(
+        repo.find_reference("v1")?.id(),
+        hex_to_id("dcca76ec95b5accc95253a2b5aa633e8e82149f8")
+    );
+    assert_eq!(repo.pack_refs()?.packed_refs, 0, "nothing left to do");
+    Ok(())
+}
+
+#[test]
+fn expire_reflogs_removes_unreachable_entries() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let head_log = || std::fs::read_to_string(repo.git_dir().join("logs/HEAD"));
+    let before = head_log()?;
+    let options = expire_reflogs::Options {
+        expire_unreachable_before: Some(year_2010()),
+        dry_run: true,
+        ..Default::default()
+    };
+    let out = repo.expire_reflogs(options)?;
+    assert_eq!(
+        out,
+        expire_reflogs::Outcome {
+            rewritten_reflogs: 1,
+            removed_entries: 1,
+        },
+        "only the commit on the side branch isn't reachable from HEAD anymore"
+    );
+    assert_eq!(head_log()?, before, "dry-runs don't change anything");
+
+    let head_lock = repo.git_dir().join("HEAD.lock");
+    std::fs::write(&head_lock, b"")?;
+    assert!(
+        repo.expire_reflogs(expire_reflogs::Options {
+            dry_run: false,
+            ..options
+        })
+        .is_err(),
+        "the reference is locked while its reflog is rewritten, so a locked reference can't be expired"
+    );
+    assert_eq!(head_log()?, before);
+    std::fs::remove_file(head_lock)?;
+
+    let out = repo.expire_reflogs(expire_reflogs::Options {
+        dry_run: false,
+        ..options
+    })?;
+    assert_eq!(out.removed_entries, 1);
+    let after = head_log()?;
+    assert_eq!(after.lines().count(), 6);
+    assert!(!after.contains("commit: side"));
+    Ok(())
+}
+
+#[test]
+fn gc_with_auto_only_runs_if_needed() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let mut options = repo.gc_options(SystemTime::now())?;
+    assert_eq!(options.auto_threshold, 0, "the fixture sets `gc.auto=0`");
+    options.auto = true;
+    options.auto_pack_limit = 1;
+    assert!(
+        !repo.gc_is_needed(&options)?,
+        "a threshold of 0 disables automatic collection entirely"
+    );
+    assert!(repo
+        .gc(options, &mut gix::progress::Discard, &AtomicBool::default())?
+        .is_none());
+
+    options.auto_threshold = 6700;
+    options.auto_pack_limit = 50;
+    assert!(
+        !repo.gc_is_needed(&options)?,
+        "there are neither enough loose objects nor packs"
+    );
+    options.auto_pack_limit = 1;
+    assert!(repo.gc_is_needed(&options)?, "there are two packs");
+    Ok(())
+}
+
+#[test]
+fn gc() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let options = repo.gc_options(SystemTime::now())?;
+    assert!(options.pack_refs);
+    assert!(options.cruft_packs);
+    assert!(options.prune_expire_before.is_some());
+
+    let out = repo
+        .gc(options, &mut gix::progress::Discard, &AtomicBool::default())?
+        .expect("not in auto mode");
+    assert_eq!(out.pack_refs.map(|out| out.packed_refs), Some(2));
+    assert_eq!(
+        out.reflogs.removed_entries, 11,
+        "all reflog entries are older than 90 days"
+    );
+    assert_eq!(out.repack.packed_objects, 13);
+    assert_eq!(out.repack.cruft_objects, 4, "recent unreachable objects are kept");
+    assert_eq!(
+        out.prune.expect("pruning is enabled").pruned_objects,
+        [old_unreachable_blob()]
+    );
+    assert!(loose_objects(&repo)?.is_empty());
+    assert_eq!(pack_indices(&repo)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn gc_options_from_configuration() -> crate::Result {
+    let (mut repo, _tmp) = repo_rw()?;
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_value(&gix::config::tree::Gc::PRUNE_EXPIRE, "never")?;
+        config.set_value(&gix::config::tree::Gc::CRUFT_PACKS, "false")?;
+        config.set_value(&gix::config::tree::Gc::AUTO, "100")?;
+    }
+    let options: gc::Options = repo.gc_options(SystemTime::now())?;
+    assert_eq!(options.prune_expire_before, None);
+    assert!(!options.cruft_packs);
+    assert_eq!(options.auto_threshold, 100);
+    assert_eq!(options.auto_pack_limit, 50, "defaults are used for unset values");
+
+    let out = repo
+        .gc(options, &mut gix::progress::Discard, &AtomicBool::default())?
+        .expect("not in auto mode");
+    assert_eq!(out.prune, None, "objects never expire");
+    assert_eq!(out.repack.cruft_pack, None);
+    assert_eq!(
+        out.repack.loosened_objects, 3,
+        "unreachable packed objects are written as loose objects instead"
+    );
+    assert_eq!(loose_objects(&repo)?.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn gc_in_partial_clone_does_not_fetch_missing_objects() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable_with_args(
+        "make_partial_clone_maintenance_repo.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )?;
+    let mut repo = gix::open_opts(dir.path().join("partial"), crate::restricted())?;
+    let fetches = std::sync::Arc::new(std::sync::atomic::AtomicUsize::default());
+    repo.objects.set_promisor(Some(std::sync::Arc::new({
+        let fetches = fetches.clone();
+        move |_ids: &[gix_hash::ObjectId]| -> Result<(), gix_odb::promisor::Error> {
+            fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Err("fetching isn't possible".into())
+        }
+    })));
+    let promisor_packs = pack_indices(&repo)?;
+    assert_eq!(
+        promisor_packs.len(),
+        3,
+        "the clone, and the trees and blobs fetched for the checkout"
+    );
+
+    let out = repo
+        .gc(
+            repo.gc_options(SystemTime::now())?,
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+        )?
+        .expect("not in auto mode");
+    assert_eq!(
+        fetches.load(std::sync::atomic::Ordering::SeqCst),
+        0,
+        "nothing was fetched"
+    );
+    assert_eq!(
+        out.repack.packed_objects, 3,
+        "only the objects of the local commit are packed, promisor objects aren't traversed"
+    );
+    assert_eq!(out.repack.cruft_objects, 1, "the recent unreachable object");
+    assert!(out.repack.removed_packs.is_empty(), "promisor packs are kept");
+    assert!(loose_objects(&repo)?.is_empty());
+    let packs = pack_indices(&repo)?;
+    assert!(promisor_packs.iter().all(|pack| packs.contains(pack)));
+    assert_eq!(packs.len(), 5);
+    Ok(())
+}
@@ -1,10 +1,22 @@
 use gix::Repository;
 
+#[cfg(all(feature = "worktree-mutation", feature = "status", feature = "merge"))]
+mod am;
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+mod apply_patches;
+#[cfg(feature = "bundle")]
+mod bundle;
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+mod checkout_tree;
 mod config;
 #[cfg(feature = "excludes")]
 mod excludes;
 #[cfg(feature = "attributes")]
 mod filter;
+#[cfg(feature = "blob-diff")]
+mod format_patch;
+#[cfg(feature = "maintenance")]
+mod maintenance;
 #[cfg(feature = "merge")]
 mod merge;
 mod object;
@@ -13,7 +25,13 @@ mod open;
 mod pathspec;
 mod reference;
 mod remote;
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+mod reset;
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+mod restore;
 mod shallow;
+#[cfg(feature = "excludes")]
+mod sparse;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
@@ -58,6 +76,36 @@ mod index {
         );
         Ok(())
     }
+
+    #[test]
+    fn write_tree_from_index() -> crate::Result {
+        let (repo, _tmp) = crate::util::repo_rw("make_checkout_tree_repo.sh")?;
+        let mut index = repo.open_index()?;
+        assert_eq!(
+            repo.write_tree_from_index(&mut index)?,
+            repo.head_tree_id()?,
+            "the index matches the tree at HEAD"
+        );
+
+        index.remove_entries(|_, path, _| path == "dir/c");
+        let tree_id = repo.write_tree_from_index(&mut index)?;
+        let tree = repo.find_tree(tree_id)?;
+        assert_eq!(
+            tree.decode()?
+                .entries
+                .iter()
+                .map(|entry| entry.filename.to_string())
+                .collect::<Vec<_>>(),
+            ["a", "b", "m", "unchanged"],
+            "the tree was written to the object database, without the now empty directory"
+        );
+        assert_eq!(
+            index.tree().map(|tree| tree.id),
+            Some(tree_id.detach()),
+            "the TREE extension is updated"
+        );
+        Ok(())
+    }
 }
 
 #[cfg(feature = "dirwalk")]
@@ -285,6 +285,68 @@ mod write_object {
         );
         Ok(())
     }
+
+    #[test]
+    fn strict_creation_rejects_malformed_objects_and_missing_references() -> crate::Result {
+        let mut repo = empty_bare_in_memory_repo()?;
+        let missing_blob = repo.object_hash().null();
+        let tree_with_missing_blob = gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryKind::Blob.into(),
+                filename: "missing".into(),
+                oid: missing_blob,
+            }],
+        };
+        repo.write_object(&tree_with_missing_blob)
+            .expect("by default, referenced objects aren't checked");
+
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::gitoxide::Objects::STRICT_CREATION, "true")?;
+        let mut tree_with_missing_blob = tree_with_missing_blob;
+        tree_with_missing_blob.entries[0].filename = "other".into();
+        assert_eq!(
+            repo.write_object(&tree_with_missing_blob).unwrap_err().to_string(),
+            format!("The blob {missing_blob} referenced by the tree to write does not exist")
+        );
+
+        let blob = repo.write_blob("content")?.detach();
+        let mut tree = tree_with_missing_blob;
+        tree.entries[0].oid = blob;
+        tree.entries.push(gix::objs::tree::Entry {
+            mode: gix::objs::tree::EntryKind::Commit.into(),
+            filename: "submodule".into(),
+            oid: missing_blob,
+        });
+        let tree_id = repo.write_object(&tree).expect("submodule commits are never checked");
+        let actor = gix::actor::Signature {
+            name: "name".into(),
+            email: "name@example.com".into(),
+            time: Default::default(),
+        };
+        let commit = gix::objs::Commit {
+            tree: tree_id.detach(),
+            author: actor.clone(),
+            committer: actor,
+            parents: Default::default(),
+            encoding: None,
+            message: "valid".into(),
+            extra_headers: vec![],
+        };
+        repo.write_object(&commit)?;
+        let mut commit_with_missing_parent = commit;
+        commit_with_missing_parent.parents.push(missing_blob);
+        assert_eq!(
+            repo.write_object(&commit_with_missing_parent).unwrap_err().to_string(),
+            format!("The commit {missing_blob} referenced by the commit to write does not exist")
+        );
+
+        let mut malformed_tree = b"100644 .git\0".to_vec();
+        malformed_tree.extend_from_slice(blob.as_slice());
+        let err = gix::objs::Write::write_buf(&repo, gix::objs::Kind::Tree, &malformed_tree)
+            .expect_err("malformed trees are rejected");
+        assert_eq!(err.to_string(), "The tree to write is malformed");
+        Ok(())
+    }
 }
 
 mod write_blob {
@@ -419,6 +481,41 @@ fn writes_avoid_io_using_duplicate_check() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn strict_hash_verification_detects_corrupt_objects() -> crate::Result {
+    let (_tmp, mut repo) = empty_bare_repo()?;
+    let expected = repo.write_blob("expected")?.detach();
+    let actual = repo.write_blob("actual")?.detach();
+    let loose_path = |id: gix::ObjectId| {
+        let hex = id.to_hex().to_string();
+        repo.objects.store_ref().path().join(&hex[..2]).join(&hex[2..])
+    };
+    let (expected_path, actual_path) = (loose_path(expected), loose_path(actual));
+    std::fs::remove_file(&expected_path)?;
+    std::fs::copy(actual_path, expected_path)?;
+
+    assert_eq!(
+        repo.find_object(expected)?.data,
+        b"actual",
+        "by default, the hash isn't verified"
+    );
+
+    repo.config_snapshot_mut()
+        .set_value(&gix::config::tree::gitoxide::Objects::STRICT_HASH_VERIFICATION, "true")?;
+    let expected_message = format!("Object expected to have id {expected}, but actual id was {actual}");
+    assert_eq!(repo.find_object(expected).unwrap_err().to_string(), expected_message);
+    assert_eq!(
+        repo.try_find_object(expected).unwrap_err().to_string(),
+        expected_message
+    );
+    assert_eq!(
+        repo.find_blob(actual)?.data,
+        b"actual",
+        "valid objects can still be read"
+    );
+    Ok(())
+}
+
 mod find {
     use gix_pack::Find;
 
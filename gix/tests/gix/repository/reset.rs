@@ -0,0 +1,180 @@
+use gix::repository::reset::{Error, Mode};
+
+fn repo() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    crate::util::repo_rw("make_checkout_tree_repo.sh")
+}
+
+fn read(repo: &gix::Repository, rela_path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.workdir().expect("non-bare").join(rela_path))
+}
+
+fn write(repo: &gix::Repository, rela_path: &str, content: &str) -> std::io::Result<()> {
+    std::fs::write(repo.workdir().expect("non-bare").join(rela_path), content)
+}
+
+fn index_ids(repo: &gix::Repository) -> crate::Result<Vec<(gix::bstr::BString, gix_hash::ObjectId)>> {
+    let index = repo.open_index()?;
+    Ok(index
+        .entries()
+        .iter()
+        .map(|e| (e.path(&index).to_owned(), e.id))
+        .collect())
+}
+
+fn tree_ids(repo: &gix::Repository, spec: &str) -> crate::Result<Vec<(gix::bstr::BString, gix_hash::ObjectId)>> {
+    let index = repo.index_from_tree(&repo.rev_parse_single(spec)?)?;
+    Ok(index
+        .entries()
+        .iter()
+        .map(|e| (e.path(&index).to_owned(), e.id))
+        .collect())
+}
+
+fn reset(repo: &gix::Repository, spec: &str, mode: Mode) -> Result<gix_hash::ObjectId, Error> {
+    let target = repo.rev_parse_single(spec).expect("valid spec").detach();
+    repo.reset(target, mode)?;
+    Ok(target)
+}
+
+fn assert_head_moved(repo: &gix::Repository, target: gix_hash::ObjectId) -> crate::Result {
+    assert_eq!(repo.head_id()?, target);
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/main",
+        "the branch is moved, not HEAD"
+    );
+    for name in ["HEAD", "refs/heads/main"] {
+        let reference = repo.find_reference(name)?;
+        let mut log = reference.log_iter();
+        let last = log.rev()?.expect("log present").next().expect("one line")?;
+        assert_eq!(last.message, format!("reset: moving to {target}"));
+        assert_eq!(last.new_oid, target);
+    }
+    Ok(())
+}
+
+#[test]
+fn soft() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let expected = tree_ids(&repo, "main^{tree}")?;
+    let target = reset(&repo, "other", Mode::Soft)?;
+    assert_head_moved(&repo, target)?;
+    assert_eq!(index_ids(&repo)?, expected, "the index is untouched");
+    assert_eq!(read(&repo, "a")?, "a\n");
+    Ok(())
+}
+
+#[test]
+fn mixed() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let target = reset(&repo, "other", Mode::Mixed)?;
+    assert_head_moved(&repo, target)?;
+    assert_eq!(index_ids(&repo)?, tree_ids(&repo, "other^{tree}")?);
+    assert_eq!(read(&repo, "a")?, "a\n", "the worktree is untouched");
+    assert_eq!(read(&repo, "dir/c")?, "c\n");
+    assert!(repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn hard() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let main = repo.head_id()?.detach();
+    write(&repo, "unchanged", "local\n")?;
+    write(&repo, "untracked", "untracked\n")?;
+
+    let target = reset(&repo, "other", Mode::Hard)?;
+    assert_head_moved(&repo, target)?;
+    assert_eq!(index_ids(&repo)?, tree_ids(&repo, "other^{tree}")?);
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(read(&repo, "new-dir/new")?, "new\n");
+    assert_eq!(read(&repo, "unchanged")?, "unchanged\n", "local changes are discarded");
+    assert_eq!(read(&repo, "untracked")?, "untracked\n", "untracked files are kept");
+    assert!(!repo.workdir().expect("non-bare").join("dir").exists());
+    assert!(!repo.is_dirty()?);
+
+    repo.reset(main, Mode::Hard)?;
+    assert_eq!(read(&repo, "dir/c")?, "c\n");
+    assert!(!repo.workdir().expect("non-bare").join("new-dir").exists());
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn merge_keeps_unrelated_local_changes() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    write(&repo, "unchanged", "local\n")?;
+
+    let target = reset(&repo, "other", Mode::Merge)?;
+    assert_head_moved(&repo, target)?;
+    assert_eq!(index_ids(&repo)?, tree_ids(&repo, "other^{tree}")?);
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(read(&repo, "unchanged")?, "local\n");
+    Ok(())
+}
+
+#[test]
+fn merge_and_keep_refuse_to_overwrite_local_changes() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let head = repo.head_id()?.detach();
+    write(&repo, "m", "1\n2\n3\n4\nFIVE\n")?;
+
+    let err = reset(&repo, "other", Mode::Merge).unwrap_err();
+    let Error::LocalChanges { paths } = err else {
+        unreachable!("unexpected error: {err:?}")
+    };
+    assert_eq!(paths, ["m"]);
+
+    let err = reset(&repo, "other", Mode::Keep).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::CheckoutTree(gix::repository::checkout_tree::Error::LocalChanges { .. })
+        ),
+        "{err:?}"
+    );
+    assert_eq!(repo.head_id()?, head, "nothing was changed");
+    assert_eq!(read(&repo, "a")?, "a\n");
+    assert_eq!(read(&repo, "m")?, "1\n2\n3\n4\nFIVE\n");
+    Ok(())
+}
+
+#[test]
+fn keep() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    write(&repo, "unchanged", "local\n")?;
+
+    let target = reset(&repo, "other", Mode::Keep)?;
+    assert_head_moved(&repo, target)?;
+    assert_eq!(index_ids(&repo)?, tree_ids(&repo, "other^{tree}")?);
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(read(&repo, "unchanged")?, "local\n");
+    Ok(())
+}
+
+#[test]
+fn only_soft_resets_are_possible_in_bare_repositories() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let mut config = std::fs::OpenOptions::new()
+        .append(true)
+        .open(repo.git_dir().join("config"))?;
+    std::io::Write::write_all(&mut config, b"[core]\n\tbare = true\n")?;
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    assert!(repo.is_bare());
+
+    let head = repo.head_id()?.detach();
+    let index = std::fs::read(repo.index_path())?;
+    for mode in [Mode::Mixed, Mode::Hard, Mode::Keep, Mode::Merge] {
+        let err = reset(&repo, "other", mode).unwrap_err();
+        assert!(
+            matches!(err, Error::BareRepository { mode: actual } if actual == mode),
+            "{mode:?}: {err:?}"
+        );
+    }
+    assert_eq!(repo.head_id()?, head, "nothing was changed");
+    assert_eq!(std::fs::read(repo.index_path())?, index, "the index wasn't touched");
+
+    let target = reset(&repo, "other", Mode::Soft)?;
+    assert_eq!(repo.head_id()?, target);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use gix::repository::restore::{Error, Options};
+use gix_index::entry::Stage;
+
+fn repo() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    crate::util::repo_rw("make_checkout_tree_repo.sh")
+}
+
+fn path(repo: &gix::Repository, rela_path: &str) -> std::path::PathBuf {
+    repo.workdir().expect("non-bare").join(rela_path)
+}
+
+fn read(repo: &gix::Repository, rela_path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(path(repo, rela_path))
+}
+
+fn write(repo: &gix::Repository, rela_path: &str, content: &str) -> std::io::Result<()> {
+    std::fs::write(path(repo, rela_path), content)
+}
+
+fn index_id(repo: &gix::Repository, rela_path: &str) -> crate::Result<Option<gix_hash::ObjectId>> {
+    Ok(repo
+        .open_index()?
+        .entry_by_path_and_stage(rela_path.into(), Stage::Unconflicted)
+        .map(|e| e.id))
+}
+
+#[test]
+fn worktree_from_index() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    write(&repo, "a", "changed\n")?;
+    write(&repo, "dir/c", "changed\n")?;
+    std::fs::remove_file(path(&repo, "unchanged"))?;
+
+    let outcome = repo.restore(["a", "unchanged"], Options::default())?;
+    assert_eq!(outcome.checkout.files_updated, 2);
+    assert_eq!(outcome.files_removed, 0);
+    assert_eq!(read(&repo, "a")?, "a\n");
+    assert_eq!(read(&repo, "unchanged")?, "unchanged\n");
+    assert_eq!(read(&repo, "dir/c")?, "changed\n", "unselected paths are untouched");
+
+    repo.restore([":(glob)dir/**"], Options::default())?;
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn worktree_from_tree() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let outcome = repo.restore(
+        ["a", "dir", "new-dir"],
+        Options {
+            source: Some(repo.rev_parse_single("other")?.detach()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 2, "a and new-dir/new");
+    assert_eq!(outcome.files_removed, 1, "dir/c isn't in the source");
+    assert_eq!(outcome.index_entries_changed, 0);
+    assert_eq!(read(&repo, "a")?, "a-other\n");
+    assert_eq!(read(&repo, "new-dir/new")?, "new\n");
+    assert!(!path(&repo, "dir").exists());
+    assert_eq!(read(&repo, "m")?, "1\n2\n3\n4\n5\n");
+    assert_eq!(
+        index_id(&repo, "a")?,
+        Some(repo.rev_parse_single("main:a")?.detach()),
+        "the index isn't touched"
+    );
+    Ok(())
+}
+
+#[test]
+fn staged_from_head() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let mut index = repo.open_index()?;
+    index
+        .entry_mut_by_path_and_stage("a".into(), Stage::Unconflicted)
+        .expect("present")
+        .id = repo.rev_parse_single("other:a")?.detach();
+    index.write(Default::default())?;
+    write(&repo, "a", "a-other\n")?;
+
+    let outcome = repo.restore(
+        ["a"],
+        Options {
+            staged: true,
+            worktree: false,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.index_entries_changed, 1);
+    assert_eq!(index_id(&repo, "a")?, Some(repo.rev_parse_single("main:a")?.detach()));
+    assert_eq!(read(&repo, "a")?, "a-other\n", "the worktree isn't touched");
+    Ok(())
+}
+
+#[test]
+fn staged_and_worktree_from_tree() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let outcome = repo.restore(
+        ["*"],
+        Options {
+            source: Some(repo.rev_parse_single("other")?.detach()),
+            staged: true,
+            worktree: true,
+        },
+    )?;
+    assert_eq!(outcome.index_entries_changed, 5, "a, b, m, dir/c and new-dir/new");
+    let expected = repo.index_from_tree(&repo.rev_parse_single("other^{tree}")?)?;
+    let index = repo.open_index()?;
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .map(|e| (e.path(&index), e.id))
+            .collect::<Vec<_>>(),
+        expected
+            .entries()
+            .iter()
+            .map(|e| (e.path(&expected), e.id))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(read(&repo, "m")?, "1\nTWO\n3\n4\n5\n");
+    assert!(!path(&repo, "dir").exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path(&repo, "b"))?.permissions().mode();
+        assert_ne!(mode & 0o100, 0, "the executable bit was set");
+    }
+    Ok(())
+}
+
+#[test]
+fn pathspecs_are_required() -> crate::Result {
+    let (repo, _tmp) = repo()?;
+    let err = repo.restore(None::<&str>, Options::default()).unwrap_err();
+    assert!(matches!(err, Error::MissingPathspec));
+    Ok(())
+}
@@ -43,6 +43,35 @@ fn yes() -> crate::Result {
     Ok(())
 }
 
+#[test]
+#[parallel]
+fn prune_shallow_commits() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable("make_shallow_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("shallow"), crate::restricted())?;
+    let present = hex_to_id("30887839de28edf7ab66c860e5c58b4d445f6b12");
+    let missing = hex_to_id("0000000000000000000000000000000000000001");
+    assert!(repo.prune_shallow_commits()?.is_empty(), "nothing to do");
+
+    std::fs::write(repo.shallow_file(), format!("{present}\n{missing}\n"))?;
+    let lock = repo.shallow_file().with_extension("lock");
+    std::fs::write(&lock, b"")?;
+    assert!(
+        repo.prune_shallow_commits().is_err(),
+        "the file is locked before it's read, so a locked file can't be pruned"
+    );
+    std::fs::remove_file(lock)?;
+    assert_eq!(repo.prune_shallow_commits()?, [missing]);
+    assert_eq!(repo.shallow_commits()?.expect("still shallow").as_slice(), [present]);
+
+    std::fs::write(repo.shallow_file(), format!("{missing}\n"))?;
+    assert_eq!(repo.prune_shallow_commits()?, [missing]);
+    assert!(
+        !repo.shallow_file().exists(),
+        "without any shallow commit left, the file is removed"
+    );
+    Ok(())
+}
+
 mod traverse {
     use gix_traverse::commit::simple::CommitTimeOrder;
     use serial_test::parallel;
@@ -0,0 +1,123 @@
+use gix::bstr::BStr;
+use gix_index::entry::{Flags, Mode};
+
+use crate::util::named_subrepo_opts;
+
+fn entries(index: &gix_index::State) -> Vec<(&BStr, gix_hash::ObjectId, Mode, Flags)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index), e.id, e.mode, e.flags))
+        .collect()
+}
+
+fn repo(name: &str) -> crate::Result<gix::Repository> {
+    Ok(named_subrepo_opts(
+        "make_sparse_checkout_repos.sh",
+        name,
+        gix::open::Options::isolated(),
+    )?)
+}
+
+#[test]
+fn disabled() -> crate::Result {
+    let repo = repo("disabled")?;
+    assert_eq!(
+        repo.sparse_checkout_options()?.sparse_mode(),
+        gix_index::sparse::Mode::Disabled
+    );
+    assert!(repo.sparse_checkout_patterns()?.is_none());
+
+    let mut index = repo.index_from_tree(&repo.head_tree_id()?)?;
+    assert!(repo.apply_sparse_checkout(&mut index)?.is_none());
+    assert!(
+        index.entries().iter().all(|e| e.flags.is_empty()),
+        "nothing was changed"
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_sparse_checkout_in_cone_mode_with_sparse_index_matches_git() -> crate::Result {
+    let repo = repo("cone-sparse-index")?;
+    assert_eq!(
+        repo.sparse_checkout_options()?.sparse_mode(),
+        gix_index::sparse::Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs
+    );
+    assert!(repo.sparse_checkout_patterns()?.expect("configured").is_cone());
+
+    let mut index = repo.index_from_tree(&repo.head_tree_id()?)?;
+    let outcome = repo.apply_sparse_checkout(&mut index)?.expect("configured");
+    assert_eq!(outcome.num_excluded, 3);
+    assert_eq!(outcome.num_included, 0);
+
+    let expected = repo.open_index()?;
+    assert!(index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected));
+
+    let outcome = repo.apply_sparse_checkout(&mut index)?.expect("configured");
+    assert_eq!(
+        outcome,
+        Default::default(),
+        "sparse directories are expanded and collapsed again"
+    );
+    assert_eq!(entries(&index), entries(&expected));
+    Ok(())
+}
+
+#[test]
+fn apply_sparse_checkout_in_non_cone_mode_matches_git() -> crate::Result {
+    let repo = repo("non-cone")?;
+    assert_eq!(
+        repo.sparse_checkout_options()?.sparse_mode(),
+        gix_index::sparse::Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched
+    );
+
+    let mut index = repo.index_from_tree(&repo.head_tree_id()?)?;
+    let outcome = repo.apply_sparse_checkout(&mut index)?.expect("configured");
+    assert_eq!(outcome.num_excluded, 3, "root, a/file and a/x/file");
+
+    let expected = repo.open_index()?;
+    assert!(!index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected));
+    Ok(())
+}
+
+#[test]
+fn status_ignores_excluded_entries_and_finds_untracked_files_outside_of_the_cone() -> crate::Result {
+    use gix::status::index_worktree::iter::Summary::Added;
+    for (name, expected) in [
+        (
+            "cone-sparse-index",
+            // Git would also list the files in `d/`, but the directory walk doesn't enter directories
+            // that are excluded by a sparse directory entry.
+            &[("x/untracked", Added)][..],
+        ),
+        (
+            "non-cone",
+            &[("d/e/untracked", Added), ("d/untracked", Added), ("x/untracked", Added)],
+        ),
+    ] {
+        let mut repo = repo(name)?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Status::SHOW_UNTRACKED_FILES, "all")?;
+        let items: Vec<_> = repo
+            .status(gix::progress::Discard)?
+            .index_worktree_options_mut(|opts| {
+                opts.sorting =
+                    Some(gix::status::plumbing::index_as_worktree_with_renames::Sorting::ByPathCaseSensitive);
+            })
+            .into_index_worktree_iter(None)?
+            .map(|item| item.map(|item| (item.rela_path().to_string(), item.summary())))
+            .collect::<Result<_, _>>()?;
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(path, summary)| (path.to_string(), Some(*summary)))
+            .collect();
+        assert_eq!(
+            items, expected,
+            "{name}: entries excluded from the worktree aren't reported as removed, and untracked files are found"
+        );
+    }
+    Ok(())
+}
@@ -17,8 +17,8 @@ use gix::bstr::{io::BufReadExt, BString};
 use crate::plumbing::options::merge;
 use crate::plumbing::{
     options::{
-        attributes, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb, revision, tree,
-        Args, Subcommands,
+        attributes, bundle, commit, commitgraph, config, credential, exclude, free, fsck, gc, index, mailmap, odb,
+        prune, repack, revision, tree, Args, Subcommands,
     },
     show_progress,
 };
@@ -158,6 +158,7 @@ pub fn main() -> Result<()> {
         Subcommands::Merge(merge::Platform { cmd }) => match cmd {
             merge::SubCommands::File {
                 resolve_with,
+                whitespace,
                 ours,
                 base,
                 theirs,
@@ -174,6 +175,7 @@ pub fn main() -> Result<()> {
                         out,
                         format,
                         resolve_with.map(Into::into),
+                        whitespace.into(),
                         base,
                         ours,
                         theirs,
@@ -266,6 +268,10 @@ pub fn main() -> Result<()> {
             crate::plumbing::options::diff::SubCommands::Tree {
                 old_treeish,
                 new_treeish,
+                stat,
+                numstat,
+                shortstat,
+                dirstat,
             } => prepare_and_run(
                 "diff-tree",
                 trace,
@@ -274,12 +280,27 @@ pub fn main() -> Result<()> {
                 progress_keep_open,
                 None,
                 move |_progress, out, _err| {
-                    core::repository::diff::tree(repository(Mode::Lenient)?, out, old_treeish, new_treeish)
+                    use core::repository::diff::Format;
+                    let format = if stat {
+                        Format::Stat
+                    } else if numstat {
+                        Format::Numstat
+                    } else if shortstat {
+                        Format::Shortstat
+                    } else if dirstat {
+                        Format::Dirstat
+                    } else {
+                        Format::Changes
+                    };
+                    core::repository::diff::tree(repository(Mode::Lenient)?, out, old_treeish, new_treeish, format)
                 },
             ),
             crate::plumbing::options::diff::SubCommands::File {
                 old_revspec,
                 new_revspec,
+                word_diff,
+                color_moved,
+                whitespace,
             } => prepare_and_run(
                 "diff-file",
                 trace,
@@ -288,18 +309,41 @@ pub fn main() -> Result<()> {
                 progress_keep_open,
                 None,
                 move |_progress, out, _err| {
-                    core::repository::diff::file(repository(Mode::Lenient)?, out, old_revspec, new_revspec)
+                    use crate::plumbing::options::diff::{ColorMoved, WordDiff};
+                    use gix::diff::blob::{moved, word_diff};
+                    let format = match (word_diff, color_moved) {
+                        (Some(style), _) => core::repository::diff::FileFormat::WordDiff(match style {
+                            WordDiff::Plain => word_diff::Style::Plain,
+                            WordDiff::Porcelain => word_diff::Style::Porcelain,
+                            WordDiff::Color => word_diff::Style::Color,
+                        }),
+                        (None, Some(mode)) => core::repository::diff::FileFormat::ColorMoved(match mode {
+                            ColorMoved::Plain => moved::Mode::Plain,
+                            ColorMoved::Blocks => moved::Mode::Blocks,
+                            ColorMoved::Zebra => moved::Mode::Zebra,
+                            ColorMoved::DimmedZebra => moved::Mode::DimmedZebra,
+                        }),
+                        (None, None) => core::repository::diff::FileFormat::Unified,
+                    };
+                    core::repository::diff::file(
+                        repository(Mode::Lenient)?,
+                        out,
+                        old_revspec,
+                        new_revspec,
+                        format,
+                        whitespace.into(),
+                    )
                 },
             ),
         },
-        Subcommands::Log(crate::plumbing::options::log::Platform { pathspec }) => prepare_and_run(
+        Subcommands::Log(crate::plumbing::options::log::Platform { pathspec, stat }) => prepare_and_run(
             "log",
             trace,
             verbose,
             progress,
             progress_keep_open,
             None,
-            move |_progress, out, _err| core::repository::log::log(repository(Mode::Lenient)?, out, pathspec),
+            move |_progress, out, _err| core::repository::log::log(repository(Mode::Lenient)?, out, pathspec, stat),
         ),
         Subcommands::Worktree(crate::plumbing::options::worktree::Platform { cmd }) => match cmd {
             crate::plumbing::options::worktree::SubCommands::List => prepare_and_run(
@@ -573,6 +617,7 @@ pub fn main() -> Result<()> {
             ref_name,
             remote,
             shallow,
+            filter,
             directory,
         }) => {
             let opts = core::repository::clone::Options {
@@ -582,6 +627,7 @@ pub fn main() -> Result<()> {
                 no_tags,
                 ref_name,
                 shallow: shallow.into(),
+                filter,
             };
             prepare_and_run(
                 "clone",
@@ -601,6 +647,7 @@ pub fn main() -> Result<()> {
             open_negotiation_graph,
             remote,
             shallow,
+            filter,
             ref_spec,
         }) => {
             let opts = core::repository::fetch::Options {
@@ -611,6 +658,7 @@ pub fn main() -> Result<()> {
                 negotiation_info,
                 open_negotiation_graph,
                 shallow: shallow.into(),
+                filter,
                 ref_specs: ref_spec,
             };
             prepare_and_run(
@@ -1370,14 +1418,163 @@ pub fn main() -> Result<()> {
                 move |_progress, out, err| core::repository::odb::info(repository(Mode::Strict)?, format, out, err),
             ),
         },
-        Subcommands::Fsck(fsck::Platform { spec }) => prepare_and_run(
+        Subcommands::Fsck(fsck::Platform {
+            strict,
+            unreachable,
+            no_dangling,
+            spec,
+        }) => prepare_and_run(
             "fsck",
             trace,
             auto_verbose,
             progress,
             progress_keep_open,
-            None,
-            move |_progress, out, _err| core::repository::fsck(repository(Mode::Strict)?, spec, out),
+            core::repository::fsck::PROGRESS_RANGE,
+            move |progress, out, err| {
+                core::repository::fsck(
+                    repository(Mode::Strict)?,
+                    spec,
+                    progress,
+                    &should_interrupt,
+                    out,
+                    err,
+                    core::repository::fsck::Options {
+                        strict,
+                        unreachable,
+                        dangling: !no_dangling,
+                    },
+                )
+            },
+        ),
+        Subcommands::Bundle(cmd) => match cmd {
+            bundle::Subcommands::Create { file, specs } => prepare_and_run(
+                "bundle-create",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::bundle::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::bundle::create(
+                        repository(Mode::Strict)?,
+                        progress,
+                        &should_interrupt,
+                        file,
+                        specs,
+                        out,
+                        format,
+                    )
+                },
+            ),
+            bundle::Subcommands::Verify { file } => prepare_and_run(
+                "bundle-verify",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::bundle::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::bundle::verify(
+                        repository(Mode::Strict)?,
+                        progress,
+                        &should_interrupt,
+                        file,
+                        out,
+                        format,
+                    )
+                },
+            ),
+            bundle::Subcommands::ListHeads { file, names } => prepare_and_run(
+                "bundle-list-heads",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bundle::list_heads(file, names, out),
+            ),
+            bundle::Subcommands::Unbundle { file } => prepare_and_run(
+                "bundle-unbundle",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::bundle::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::bundle::unbundle(
+                        repository(Mode::Strict)?,
+                        progress,
+                        &should_interrupt,
+                        file,
+                        out,
+                        format,
+                    )
+                },
+            ),
+        },
+        Subcommands::Gc(gc::Platform { auto, prune }) => prepare_and_run(
+            "gc",
+            trace,
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::repository::maintenance::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::maintenance::gc(
+                    repository(Mode::Strict)?,
+                    progress,
+                    &should_interrupt,
+                    out,
+                    core::repository::maintenance::gc::Options { format, auto, prune },
+                )
+            },
+        ),
+        Subcommands::Repack(repack::Platform {
+            geometric,
+            cruft,
+            cruft_expiration,
+        }) => prepare_and_run(
+            "repack",
+            trace,
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::repository::maintenance::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::maintenance::repack(
+                    repository(Mode::Strict)?,
+                    progress,
+                    &should_interrupt,
+                    out,
+                    core::repository::maintenance::repack::Options {
+                        format,
+                        geometric,
+                        cruft,
+                        cruft_expiration,
+                    },
+                )
+            },
+        ),
+        Subcommands::Prune(prune::Platform { expire, dry_run }) => prepare_and_run(
+            "prune",
+            trace,
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::repository::maintenance::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::maintenance::prune(
+                    repository(Mode::Strict)?,
+                    progress,
+                    &should_interrupt,
+                    out,
+                    core::repository::maintenance::prune::Options {
+                        format,
+                        expire,
+                        dry_run,
+                    },
+                )
+            },
         ),
         Subcommands::Mailmap(cmd) => match cmd {
             mailmap::Subcommands::Entries => prepare_and_run(
@@ -1561,6 +1758,13 @@ pub fn main() -> Result<()> {
             file,
             range,
             since,
+            moved_lines,
+            copied_lines,
+            ignore_rev,
+            ignore_revs_file,
+            ignore_whitespace,
+            porcelain,
+            incremental,
         } => prepare_and_run(
             "blame",
             trace,
@@ -1575,13 +1779,41 @@ pub fn main() -> Result<()> {
                 core::repository::blame::blame_file(
                     repo,
                     &file,
-                    gix::blame::Options {
-                        diff_algorithm,
-                        range,
-                        since,
+                    core::repository::blame::Options {
+                        blame: gix::blame::Options {
+                            diff_algorithm,
+                            range,
+                            since,
+                            rewrites: Some(gix::diff::Rewrites::default()),
+                            moved_lines: match (moved_lines, copied_lines) {
+                                (false, 0) => None,
+                                (_, 0) => Some(gix::blame::MovedLinesSource::SameFile),
+                                (_, 1) => Some(gix::blame::MovedLinesSource::ChangedFiles),
+                                (_, _) => Some(gix::blame::MovedLinesSource::AnyFile),
+                            }
+                            .map(|source| gix::blame::MovedLines {
+                                source,
+                                ..Default::default()
+                            }),
+                            ignore_revs: Default::default(),
+                            whitespace: gix::diff::blob::whitespace::Options {
+                                ignore_all_space: ignore_whitespace,
+                                ..Default::default()
+                            },
+                        },
+                        ignore_revs: ignore_rev,
+                        ignore_revs_files: ignore_revs_file,
+                        format: if porcelain {
+                            core::repository::blame::Format::Porcelain
+                        } else if incremental {
+                            core::repository::blame::Format::Incremental
+                        } else {
+                            core::repository::blame::Format::Simple
+                        },
                     },
                     out,
                     statistics.then_some(err),
+                    &should_interrupt,
                 )
             },
         ),
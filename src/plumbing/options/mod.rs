@@ -94,6 +94,15 @@ pub enum Subcommands {
     Odb(odb::Subcommands),
     /// Check for missing objects.
     Fsck(fsck::Platform),
+    /// Create, verify and unpack bundles, like `git bundle`.
+    #[clap(subcommand)]
+    Bundle(bundle::Subcommands),
+    /// Pack references, expire reflogs, repack objects and prune unreachable ones, like `git gc`.
+    Gc(gc::Platform),
+    /// Consolidate objects into fewer packs, like `git repack -d`.
+    Repack(repack::Platform),
+    /// Remove unreachable loose objects and loose objects that are also packed, like `git prune`.
+    Prune(prune::Platform),
     /// Interact with tree objects.
     #[clap(subcommand)]
     Tree(tree::Subcommands),
@@ -170,6 +179,28 @@ pub enum Subcommands {
         /// Don't consider commits before the given date.
         #[clap(long,  value_parser=AsTime, value_name = "DATE")]
         since: Option<gix::date::Time>,
+        /// Detect lines that were moved or copied within the file.
+        #[clap(short = 'M')]
+        moved_lines: bool,
+        /// Detect lines that were moved or copied from files changed in the same commit.
+        /// Pass it twice or more to look at all files of the parent commit instead, which is very expensive.
+        #[clap(short = 'C', action = clap::ArgAction::Count)]
+        copied_lines: u8,
+        /// Ignore changes made by the given revision, blaming changed lines on similar lines in its parent instead.
+        #[clap(long, value_name = "REV")]
+        ignore_rev: Vec<String>,
+        /// Ignore the revisions listed in the given file, in addition to the one configured in `blame.ignoreRevsFile`.
+        #[clap(long, value_name = "FILE")]
+        ignore_revs_file: Vec<std::path::PathBuf>,
+        /// Ignore whitespace when comparing lines, like `git blame -w`.
+        #[clap(short = 'w')]
+        ignore_whitespace: bool,
+        /// Print the blame in a format for machine consumption, like `git blame --porcelain`.
+        #[clap(long, conflicts_with = "incremental")]
+        porcelain: bool,
+        /// Print each blame entry as soon as it's known, like `git blame --incremental`.
+        #[clap(long)]
+        incremental: bool,
     },
     /// Generate shell completions to stdout or a directory.
     #[clap(visible_alias = "generate-completions", visible_alias = "shell-completions")]
@@ -454,6 +485,9 @@ pub mod merge {
             /// Decide how to resolve conflicts. If unset, write conflict markers and fail.
             #[clap(long, short = 'c')]
             resolve_with: Option<ResolveWith>,
+            /// Changes in whitespace to ignore, like `git merge -Xignore-space-change` and related options.
+            #[clap(flatten)]
+            whitespace: crate::plumbing::options::diff::Whitespace,
 
             /// A path or revspec to our file.
             #[clap(value_name = "OURS", value_parser = crate::shared::AsBString)]
@@ -516,6 +550,18 @@ pub mod diff {
             /// A rev-spec representing the 'after' or new tree.
             #[clap(value_parser = crate::shared::AsBString)]
             new_treeish: BString,
+            /// Show the amount of changed lines of each file along with a graph, like `git diff --stat`.
+            #[clap(long, conflicts_with_all = ["numstat", "shortstat", "dirstat"])]
+            stat: bool,
+            /// Show the amount of changed lines of each file in a machine-readable format, like `git diff --numstat`.
+            #[clap(long, conflicts_with_all = ["shortstat", "dirstat"])]
+            numstat: bool,
+            /// Show only the amount of changed files and lines, like `git diff --shortstat`.
+            #[clap(long, conflicts_with = "dirstat")]
+            shortstat: bool,
+            /// Show the share of changed lines of each directory, like `git diff --dirstat=lines`.
+            #[clap(long)]
+            dirstat: bool,
         },
         /// Diff two versions of a file.
         File {
@@ -525,8 +571,86 @@ pub mod diff {
             /// A rev-spec representing the 'after' or new state of the file, like ':file'
             #[clap(value_parser = crate::shared::AsBString)]
             new_revspec: BString,
+            /// Show changed words instead of changed lines, like `git diff --word-diff=<style>`.
+            ///
+            /// Words are found with `diff.<driver>.wordRegex` or `diff.wordRegex` if configured.
+            #[clap(
+                long,
+                value_enum,
+                num_args = 0..=1,
+                require_equals = true,
+                default_missing_value = "plain",
+                conflicts_with = "color_moved"
+            )]
+            word_diff: Option<WordDiff>,
+            /// Show changed lines with colors and highlight blocks of moved lines, like `git diff --color-moved=<mode>`.
+            #[clap(
+                long,
+                value_enum,
+                num_args = 0..=1,
+                require_equals = true,
+                default_missing_value = "zebra"
+            )]
+            color_moved: Option<ColorMoved>,
+            #[clap(flatten)]
+            whitespace: Whitespace,
         },
     }
+
+    #[derive(Debug, Copy, Clone, clap::Parser)]
+    pub struct Whitespace {
+        /// Ignore changes in the amount of whitespace.
+        #[clap(long, short = 'b')]
+        pub ignore_space_change: bool,
+        /// Ignore whitespace when comparing lines.
+        #[clap(long, short = 'w')]
+        pub ignore_all_space: bool,
+        /// Ignore changes in whitespace at the end of lines.
+        #[clap(long)]
+        pub ignore_space_at_eol: bool,
+        /// Ignore carriage-returns at the end of lines.
+        #[clap(long)]
+        pub ignore_cr_at_eol: bool,
+        /// Ignore changes whose lines are all blank.
+        #[clap(long)]
+        pub ignore_blank_lines: bool,
+    }
+
+    impl From<Whitespace> for gix::diff::blob::whitespace::Options {
+        fn from(value: Whitespace) -> Self {
+            gix::diff::blob::whitespace::Options {
+                ignore_all_space: value.ignore_all_space,
+                ignore_space_change: value.ignore_space_change,
+                ignore_space_at_eol: value.ignore_space_at_eol,
+                ignore_cr_at_eol: value.ignore_cr_at_eol,
+                ignore_blank_lines: value.ignore_blank_lines,
+            }
+        }
+    }
+
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum WordDiff {
+        /// Mark removed words like `[-this-]` and added words like `{+this+}`.
+        #[default]
+        Plain,
+        /// A line-based format meant for consumption by scripts.
+        Porcelain,
+        /// Show removed words in red and added words in green.
+        Color,
+    }
+
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum ColorMoved {
+        /// Highlight every removed line that was added elsewhere, and vice versa.
+        Plain,
+        /// Highlight blocks of moved lines with at least 20 alphanumeric characters.
+        Blocks,
+        /// Like `blocks`, but alternate the colors of adjacent blocks.
+        #[default]
+        Zebra,
+        /// Like `zebra`, but dim all lines of a block except for the ones at its borders.
+        DimmedZebra,
+    }
 }
 
 pub mod log {
@@ -538,6 +662,9 @@ pub mod log {
         /// The git path specification to show a log for.
         #[clap(value_parser = crate::shared::AsBString)]
         pub pathspec: Option<BString>,
+        /// Show the amount of changed lines of each file compared to the first parent, like `git log --stat`.
+        #[clap(long)]
+        pub stat: bool,
     }
 }
 
@@ -584,6 +711,12 @@ pub mod fetch {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
+        /// Omit objects matching the filter, like `blob:none`, `blob:limit=<n>[kmg]`, `tree:<depth>` or `sparse:oid=<blob-ish>`.
+        ///
+        /// Defaults to the filter configured for the remote if it is a promisor remote.
+        #[clap(long, value_name = "FILTER_SPEC")]
+        pub filter: Option<gix::remote::fetch::Filter>,
+
         /// The name of the remote to connect to, or the url of the remote to connect to directly.
         ///
         /// If unset, the current branch will determine the remote.
@@ -663,6 +796,11 @@ pub mod clone {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
+        /// Create a partial clone by omitting objects matching the filter, like `blob:none`, `blob:limit=<n>[kmg]`,
+        /// `tree:<depth>` or `sparse:oid=<blob-ish>`. Missing objects are fetched on demand.
+        #[clap(long, value_name = "FILTER_SPEC")]
+        pub filter: Option<gix::remote::fetch::Filter>,
+
         /// The url of the remote to connect to, like `https://github.com/byron/gitoxide`.
         pub remote: OsString,
 
@@ -843,11 +981,104 @@ pub mod odb {
 pub mod fsck {
     #[derive(Debug, clap::Parser)]
     pub struct Platform {
-        /// A revspec to start the connectivity check from.
+        /// Treat warnings about objects as errors, like `git fsck --strict`.
+        #[clap(long)]
+        pub strict: bool,
+        /// Print all unreachable objects, not only dangling ones.
+        #[clap(long)]
+        pub unreachable: bool,
+        /// Do not print dangling objects.
+        #[clap(long)]
+        pub no_dangling: bool,
+        /// A revspec to only check the connectivity of the commits reachable from it.
+        ///
+        /// If unset, all objects are checked for validity and reachability from references, reflogs and the index.
         pub spec: Option<String>,
     }
 }
 
+pub mod bundle {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Write a bundle with all references and objects described by the given revisions.
+        Create {
+            /// The path to the bundle file to write.
+            file: PathBuf,
+            /// Revisions like `main`, ranges like `v1.0..main` or exclusions like `^v1.0`.
+            ///
+            /// Only revisions that are references will be listed in the bundle.
+            #[clap(required = true)]
+            specs: Vec<String>,
+        },
+        /// Check that the repository has all prerequisites of a bundle and that its pack is valid.
+        Verify {
+            /// The path to the bundle file.
+            file: PathBuf,
+        },
+        /// Print the references of a bundle, without requiring a repository.
+        ListHeads {
+            /// The path to the bundle file.
+            file: PathBuf,
+            /// Only print references with these full names, like `refs/heads/main`.
+            names: Vec<String>,
+        },
+        /// Write the pack of a bundle into the object database and print its references.
+        Unbundle {
+            /// The path to the bundle file.
+            file: PathBuf,
+        },
+    }
+}
+
+pub mod gc {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Only collect garbage if there are too many loose objects or packs, as configured by `gc.auto`
+        /// and `gc.autoPackLimit`.
+        #[clap(long)]
+        pub auto: bool,
+        /// Prune unreachable objects older than the given date, like `2.weeks.ago`, `now` or `never`.
+        ///
+        /// If unset, `gc.pruneExpire` is used.
+        #[clap(long, value_name = "DATE")]
+        pub prune: Option<String>,
+    }
+}
+
+pub mod repack {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Only combine small packs and all loose objects so that each pack has at least `FACTOR` times
+        /// as many objects as the next smaller one.
+        #[clap(long, value_name = "FACTOR", conflicts_with_all = ["cruft", "cruft_expiration"])]
+        pub geometric: Option<u32>,
+        /// Write unreachable objects into a cruft pack instead of dropping them.
+        #[clap(long)]
+        pub cruft: bool,
+        /// Drop unreachable objects older than the given date instead of writing them into the cruft pack.
+        ///
+        /// Implies `--cruft`.
+        #[clap(long, value_name = "DATE")]
+        pub cruft_expiration: Option<String>,
+    }
+}
+
+pub mod prune {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Only prune unreachable loose objects older than the given date, like `2.weeks.ago`.
+        ///
+        /// If unset, all unreachable loose objects are pruned.
+        #[clap(long, value_name = "DATE")]
+        pub expire: Option<String>,
+        /// Print the objects that would be pruned without removing anything.
+        #[clap(long, short = 'n')]
+        pub dry_run: bool,
+    }
+}
+
 pub mod tree {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {